@@ -2,6 +2,15 @@
 import { useState, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 
+// Full form of an edge, written instead of the machine name when it has an
+// offset, a scale or wraps around
+interface EdgeLink {
+  target?: string
+  offset_px: number
+  scale: number
+  wrap: boolean
+}
+
 interface Settings {
   version: number
   self_name: string
@@ -11,7 +20,7 @@ interface Settings {
   tls: {
     psk: string
   }
-  edges: Record<string, string>
+  edges: Record<string, string | EdgeLink>
   hotkeys: {
     focus_return: string | null
     kill_switch: string | null
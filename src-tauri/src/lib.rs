@@ -1,7 +1,16 @@
 use multishiva::core::config::Config;
+use multishiva::core::discovery::{Discovery, DiscoveryConfig};
+use multishiva::core::ipc::{
+    default_endpoint, query_status, request_shutdown, DiscoveredPeer, StatusResponse,
+};
+use multishiva::core::network::ConnectionCheck;
 use std::path::PathBuf;
+use std::time::Duration;
 use dirs::config_dir;
 
+/// How long `test_connection` waits for the host's answer
+const TEST_CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
 // Tauri commands
 #[tauri::command]
 fn get_version() -> String {
@@ -58,6 +67,80 @@ fn get_config_path() -> String {
         .to_string()
 }
 
+/// Resolve the config path the same way as load_config and save_config
+fn config_path(custom_path: Option<String>) -> PathBuf {
+    custom_path.map(PathBuf::from).unwrap_or_else(get_default_config_path)
+}
+
+/// The multishiva binary installed next to the GUI, or the one on the PATH
+fn daemon_binary() -> PathBuf {
+    let name = format!("multishiva{}", std::env::consts::EXE_SUFFIX);
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(&name)))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+#[tauri::command]
+async fn get_status() -> Result<StatusResponse, String> {
+    query_status(&default_endpoint())
+        .await
+        .map_err(|e| format!("MultiShiva is not running: {}", e))
+}
+
+#[tauri::command]
+async fn start_daemon(mode: String, custom_path: Option<String>) -> Result<(), String> {
+    if mode != "host" && mode != "agent" {
+        return Err(format!("Unknown mode '{}': expected host or agent", mode));
+    }
+    if let Ok(status) = query_status(&default_endpoint()).await {
+        return Err(format!("MultiShiva is already running in {} mode", status.mode));
+    }
+
+    std::process::Command::new(daemon_binary())
+        .arg("--mode")
+        .arg(&mode)
+        .arg("--config")
+        .arg(config_path(custom_path))
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to start MultiShiva: {}", e))
+}
+
+#[tauri::command]
+async fn stop_daemon() -> Result<(), String> {
+    request_shutdown(&default_endpoint())
+        .await
+        .map_err(|e| format!("Failed to stop MultiShiva: {}", e))
+}
+
+#[tauri::command]
+async fn list_discovered_peers(custom_path: Option<String>) -> Result<Vec<DiscoveredPeer>, String> {
+    let path = config_path(custom_path);
+    let config = Config::from_file(&path.to_string_lossy())
+        .map_err(|e| format!("Failed to load config: {}", e))?;
+    let timeout = DiscoveryConfig::from_config(&config).timeout;
+
+    let discovery = Discovery::new(config.self_name.clone())
+        .map_err(|e| format!("mDNS discovery is unavailable: {}", e))?;
+    let peers = discovery
+        .find_peers_with_timeout(&config.self_name, timeout)
+        .await
+        .map_err(|e| format!("mDNS discovery is unavailable: {}", e))?;
+    Ok(peers.into_iter().map(DiscoveredPeer::from).collect())
+}
+
+#[tauri::command]
+async fn test_connection(address: String, psk: String) -> Result<ConnectionCheck, String> {
+    multishiva::core::network::test_connection(&address, &psk, TEST_CONNECTION_TIMEOUT)
+        .await
+        .map_err(|e| format!("Connection to {} failed: {:#}", address, e))
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -77,7 +160,12 @@ pub fn run() {
             greet,
             load_config,
             save_config,
-            get_config_path
+            get_config_path,
+            get_status,
+            start_daemon,
+            stop_daemon,
+            list_discovered_peers,
+            test_connection
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,5 +1,13 @@
-use anyhow::{bail, Result};
-use clap::Parser;
+use anyhow::{bail, Context, Result};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use std::io::{BufRead, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use crate::core::config::{Config, ConfigMode, TlsConfig};
+use crate::core::input_backend::InputBackend;
+use crate::core::logging::LogLevel;
 
 /// Command-line arguments for MultiShiva
 ///
@@ -8,7 +16,7 @@ use clap::Parser;
 #[derive(Parser, Debug)]
 #[command(name = "multishiva")]
 #[command(author = "yrbane")]
-#[command(version = "1.0.0")]
+#[command(version)]
 #[command(about = "Control multiple computers with one keyboard and mouse", long_about = None)]
 pub struct Args {
     /// Mode of operation
@@ -30,6 +38,326 @@ pub struct Args {
     /// Host address for agent mode (e.g., "192.168.1.100:53421")
     #[arg(long, env = "MULTISHIVA_HOST")]
     pub host: Option<String>,
+
+    /// Address the host listens on: an IP address or iface:NAME (overrides bind_address)
+    #[arg(long, value_name = "ADDR", env = "MULTISHIVA_BIND")]
+    pub bind: Option<String>,
+
+    /// Seconds an agent waits for a host over mDNS (overrides behavior.discovery_timeout_ms)
+    #[arg(
+        long,
+        value_name = "SECS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        env = "MULTISHIVA_DISCOVERY_TIMEOUT"
+    )]
+    pub discovery_timeout: Option<u64>,
+
+    /// Serve Prometheus metrics on this address (e.g., "127.0.0.1:9100")
+    #[arg(long, env = "MULTISHIVA_METRICS_ADDR")]
+    pub metrics_addr: Option<SocketAddr>,
+
+    /// Scenario file to replay in simulation mode (YAML or JSON)
+    #[arg(long, env = "MULTISHIVA_SCENARIO")]
+    pub scenario: Option<String>,
+
+    /// Append every event of the host or agent loop to this file
+    #[arg(long, value_name = "FILE", env = "MULTISHIVA_RECORD")]
+    pub record: Option<PathBuf>,
+
+    /// Feed the events of a recording through the pipeline instead of real input
+    #[arg(long, value_name = "FILE", env = "MULTISHIVA_REPLAY")]
+    pub replay: Option<PathBuf>,
+
+    /// Playback speed multiplier for --replay (2.0 plays twice as fast)
+    #[arg(long, default_value_t = 1.0, requires = "replay")]
+    pub speed: f64,
+
+    /// Trust the host's new fingerprint if it changed, for this connection only
+    ///
+    /// For a supervised key rotation: the mismatch is logged and the new
+    /// fingerprint replaces the stored one.
+    #[arg(long)]
+    pub accept_new_fingerprint: bool,
+
+    /// Print the status of the running daemon as JSON and exit
+    #[arg(long)]
+    pub status: bool,
+
+    /// Print the input devices MultiShiva can see, with their capabilities, and exit
+    #[arg(long)]
+    pub list_devices: bool,
+
+    /// How an agent injects input on Linux: uinput or wayland (picked automatically if omitted)
+    ///
+    /// wayland uses the compositor's virtual pointer and keyboard protocols,
+    /// for Wayland sessions where /dev/uinput is not writable.
+    #[arg(long, value_name = "BACKEND", env = "MULTISHIVA_INPUT_BACKEND")]
+    pub input_backend: Option<InputBackend>,
+
+    /// Run in the background, with a PID file in the data directory
+    ///
+    /// Under systemd (`Type=notify`) MultiShiva stays in the foreground and
+    /// notifies systemd once ready. SIGTERM stops it, SIGHUP reloads the
+    /// configuration of a host (an agent must be restarted).
+    #[arg(long, env = "MULTISHIVA_DAEMON")]
+    pub daemon: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcommands that act on a running daemon or its configuration instead of starting one
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Print mode, uptime, peers, focus and counters of the running daemon
+    Status {
+        /// Print JSON for scripting instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Switch the running daemon to a new pre-shared key without disconnecting
+    ///
+    /// Run it on every agent first, then on the host.
+    RotatePsk {
+        /// The new pre-shared key
+        #[arg(long)]
+        new_psk: String,
+    },
+
+    /// Show the running daemon's clipboard history or paste an older entry
+    Clipboard {
+        #[command(subcommand)]
+        action: ClipboardCommand,
+    },
+
+    /// Write a new configuration file, prompting for its values
+    ///
+    /// Writes to --config, or to the default configuration path.
+    Init(InitArgs),
+
+    /// Manage the PSK stored in the system keyring
+    Keyring {
+        #[command(subcommand)]
+        action: KeyringCommand,
+    },
+
+    /// Print the newest log file, optionally following it
+    Logs(LogsArgs),
+
+    /// Check permissions, keyring and stored PSK, discovery, configuration and port
+    ///
+    /// Exits with an error if any check fails.
+    Doctor {
+        /// Print JSON for scripting instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Record local keyboard and mouse input to a file until Ctrl+C
+    Record {
+        /// Recording file to write (e.g. session.msrec)
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+    },
+
+    /// Inject the events of a recording into this machine
+    Replay {
+        /// Recording file to play (e.g. session.msrec)
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+
+        /// Playback speed multiplier (2.0 plays twice as fast)
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+    },
+
+    /// Hidden alias of `fingerprints trust`, or of `fingerprints remove`
+    /// with --accept-latest
+    #[command(hide = true)]
+    Trust {
+        /// Machine whose fingerprint changed
+        machine: String,
+
+        /// New fingerprint to trust (64 hexadecimal digits)
+        #[arg(
+            long,
+            value_name = "HASH",
+            value_parser = crate::core::fingerprint::parse_fingerprint_hash,
+            required_unless_present = "accept_latest",
+            conflicts_with = "accept_latest"
+        )]
+        fingerprint: Option<String>,
+
+        /// Forget the stored fingerprint and trust the one presented on the next connection
+        #[arg(long)]
+        accept_latest: bool,
+    },
+
+    /// Install the udev rule giving the input group access to input devices (Linux, as root),
+    /// or open the Accessibility settings (macOS)
+    SetupPermissions,
+
+    /// List, trust or remove the fingerprints of the hosts this machine connected to
+    Fingerprints {
+        #[command(subcommand)]
+        action: FingerprintsCommand,
+    },
+
+    /// List the MultiShiva instances found on the local network over mDNS
+    ///
+    /// Browses for `behavior.discovery_timeout_ms` (5 seconds by default), then
+    /// prints each peer with its address, whether its PSK matches ours and when
+    /// it was last seen.
+    ListAgents,
+
+    /// Pair with a host using a one-time code instead of copying its PSK
+    ///
+    /// `pair --host` on the host shows a 6-digit code valid for 2 minutes.
+    /// `pair CODE` on the agent finds the host over mDNS (or at --host ADDR),
+    /// stores its PSK in the keyring and writes host_address, host_name and self_name
+    /// into the --config file.
+    Pair {
+        /// Code shown on the host
+        #[arg(required_unless_present = "host", conflicts_with = "host")]
+        code: Option<String>,
+
+        /// Show a code and wait for an agent to pair
+        #[arg(long)]
+        host: bool,
+    },
+
+    /// Print a shell completion script (bash, zsh, fish, powershell, elvish)
+    ///
+    /// For bash: `multishiva completions bash > /etc/bash_completion.d/multishiva`
+    Completions {
+        /// Shell to generate the script for
+        shell: Shell,
+    },
+}
+
+/// Options of the `logs` subcommand
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct LogsArgs {
+    /// Number of entries to print
+    #[arg(short = 'n', long, default_value_t = 100)]
+    pub lines: usize,
+
+    /// Keep printing new entries as they are logged
+    #[arg(short, long)]
+    pub follow: bool,
+
+    /// Only print entries at least this severe (error, warn, info, debug, trace)
+    #[arg(long)]
+    pub level: Option<LogLevel>,
+
+    /// Only print entries newer than this age (e.g. 30s, 15m, 2h, 7d)
+    #[arg(long, value_parser = crate::core::log_viewer::parse_since)]
+    pub since: Option<chrono::Duration>,
+
+    /// Delete old log files, keeping the N most recent, and exit
+    #[arg(long, value_name = "N")]
+    pub cleanup: Option<usize>,
+}
+
+/// Actions of the `fingerprints` subcommand
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum FingerprintsCommand {
+    /// Print each machine with its fingerprint, first seen and last verified dates
+    List,
+
+    /// Forget a machine's fingerprint; the next connection trusts it again
+    Remove {
+        /// Machine whose fingerprint to forget
+        machine: String,
+    },
+
+    /// Trust HASH as the machine's fingerprint, replacing the stored one
+    Trust {
+        /// Machine to trust
+        machine: String,
+
+        /// Fingerprint to trust (64 hexadecimal digits)
+        #[arg(value_parser = crate::core::fingerprint::parse_fingerprint_hash)]
+        hash: String,
+    },
+}
+
+/// Actions of the `clipboard` subcommand
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum ClipboardCommand {
+    /// Print the recent clipboard changes, 0 being the most recent
+    History,
+
+    /// Put entry N back into the clipboard, without sending it to the peers
+    Restore {
+        /// Entry to restore, as numbered by `clipboard history`
+        index: usize,
+    },
+}
+
+/// Actions of the `keyring` subcommand
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum KeyringCommand {
+    /// Store a PSK in the keyring
+    Set {
+        /// The PSK to store (read from standard input if omitted)
+        #[arg(long)]
+        psk: Option<String>,
+    },
+
+    /// Print the stored PSK, masked
+    Get {
+        /// Print the PSK in clear
+        #[arg(long)]
+        show: bool,
+    },
+
+    /// Remove the stored PSK
+    Delete,
+
+    /// Move the PSK of a configuration file into the keyring
+    ///
+    /// The file's tls.psk is replaced with a placeholder resolved at startup.
+    Migrate {
+        /// Configuration file holding the plaintext PSK
+        #[arg(long)]
+        from_config: String,
+    },
+}
+
+/// Options of the `init` subcommand
+///
+/// In interactive mode these are the defaults offered at each prompt.
+#[derive(clap::Args, Debug, Clone, Default, PartialEq)]
+pub struct InitArgs {
+    /// Overwrite an existing configuration file
+    #[arg(long)]
+    pub force: bool,
+
+    /// Take every value from flags and defaults instead of prompting
+    #[arg(long)]
+    pub non_interactive: bool,
+
+    /// Name of this machine (defaults to the hostname)
+    #[arg(long)]
+    pub self_name: Option<String>,
+
+    /// Host or agent (defaults to host)
+    #[arg(long, value_enum)]
+    pub mode: Option<Mode>,
+
+    /// Port to listen on or connect to (defaults to 53421)
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// Pre-shared key (a random one is generated if omitted)
+    #[arg(long)]
+    pub psk: Option<String>,
+
+    /// Address of the host, for agents (mDNS discovery is used if omitted)
+    #[arg(long)]
+    pub host_address: Option<String>,
 }
 
 /// Operation mode for MultiShiva
@@ -51,6 +379,89 @@ impl Args {
             bail!("Cannot use --gui and --simulate together");
         }
 
+        if self.list_devices && (self.gui || self.simulate || self.command.is_some()) {
+            bail!("--list-devices cannot be combined with --gui, --simulate or a subcommand");
+        }
+        if self.daemon
+            && (self.gui
+                || self.simulate
+                || self.list_devices
+                || self.status_request().is_some()
+                || self.command.is_some())
+        {
+            bail!("--daemon only runs a host or agent, not --gui, --simulate or a subcommand");
+        }
+
+        // Querying a running daemon does not start anything
+        if self.status_request().is_some() && (self.gui || self.simulate) {
+            bail!("Cannot query the daemon status with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::RotatePsk { .. })) && (self.gui || self.simulate) {
+            bail!("Cannot rotate the daemon PSK with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::Clipboard { .. })) && (self.gui || self.simulate) {
+            bail!("Cannot use the daemon clipboard history with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::Init(_))) && (self.gui || self.simulate) {
+            bail!("Cannot initialize a configuration with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::Keyring { .. })) && (self.gui || self.simulate) {
+            bail!("Cannot manage the keyring with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::Logs(_))) && (self.gui || self.simulate) {
+            bail!("Cannot view the logs with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::Doctor { .. })) && (self.gui || self.simulate) {
+            bail!("Cannot run the doctor checks with --gui or --simulate");
+        }
+        if matches!(
+            self.command,
+            Some(Command::Record { .. } | Command::Replay { .. })
+        ) && (self.gui || self.simulate)
+        {
+            bail!("Cannot record or replay a session with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::Trust { .. })) && (self.gui || self.simulate) {
+            bail!("Cannot trust a fingerprint with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::SetupPermissions)) && (self.gui || self.simulate) {
+            bail!("Cannot set up permissions with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::Fingerprints { .. })) && (self.gui || self.simulate)
+        {
+            bail!("Cannot manage fingerprints with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::ListAgents)) && (self.gui || self.simulate) {
+            bail!("Cannot list agents with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::Completions { .. })) && (self.gui || self.simulate)
+        {
+            bail!("Cannot generate completions with --gui or --simulate");
+        }
+        if matches!(self.command, Some(Command::Pair { .. })) && (self.gui || self.simulate) {
+            bail!("Cannot pair with --gui or --simulate");
+        }
+        if let Some(Command::Replay { speed, .. }) = &self.command {
+            if !(speed.is_finite() && *speed > 0.0) {
+                bail!("--speed must be a positive number, got {}", speed);
+            }
+        }
+
+        // A scenario is only replayed by the simulation
+        if self.scenario.is_some() && !self.simulate {
+            bail!("--scenario requires --simulate");
+        }
+
+        if !(self.speed.is_finite() && self.speed > 0.0) {
+            bail!("--speed must be a positive number, got {}", self.speed);
+        }
+        if self.replay.is_some() && self.scenario.is_some() {
+            bail!("Cannot use --replay and --scenario together");
+        }
+        if self.record.is_some() && self.simulate {
+            bail!("Cannot use --record with --simulate");
+        }
+
         // If mode is explicitly set via CLI or env var, ensure it's valid
         if let Some(mode) = &self.mode {
             if self.gui {
@@ -63,6 +474,184 @@ impl Args {
 
         Ok(())
     }
+
+    /// Returns `Some(json)` when the daemon status was requested
+    ///
+    /// `json` is true for `--status` and `status --json`, false for `status`.
+    pub fn status_request(&self) -> Option<bool> {
+        match &self.command {
+            Some(Command::Status { json }) => Some(*json),
+            Some(
+                Command::RotatePsk { .. }
+                | Command::Clipboard { .. }
+                | Command::Init(_)
+                | Command::Keyring { .. }
+                | Command::Logs(_)
+                | Command::Doctor { .. }
+                | Command::Record { .. }
+                | Command::Replay { .. }
+                | Command::Trust { .. }
+                | Command::SetupPermissions
+                | Command::Fingerprints { .. }
+                | Command::ListAgents
+                | Command::Pair { .. }
+                | Command::Completions { .. },
+            ) => None,
+            None if self.status => Some(true),
+            None => None,
+        }
+    }
+}
+
+/// Write a new configuration file to `path` for the `init` subcommand
+///
+/// Unless `init.non_interactive` is set, each value is prompted for on
+/// `output` and read from `input`; an empty answer keeps the default shown.
+/// A random PSK is generated when none is given. The configuration is
+/// validated before it is written with [`Config::save_to_file`].
+///
+/// # Errors
+///
+/// Returns an error if `path` exists and `init.force` is not set, an answer
+/// is invalid, or the configuration cannot be written.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::cli::{init_config, InitArgs};
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("config.yml");
+/// let init = InitArgs {
+///     non_interactive: true,
+///     self_name: Some("desk".to_string()),
+///     ..Default::default()
+/// };
+///
+/// let config = init_config(&init, &path, std::io::empty(), std::io::sink()).unwrap();
+/// assert_eq!(config.self_name, "desk");
+/// assert!(path.exists());
+/// ```
+pub fn init_config<R: BufRead, W: Write>(
+    init: &InitArgs,
+    path: &Path,
+    mut input: R,
+    mut output: W,
+) -> Result<Config> {
+    if path.exists() && !init.force {
+        bail!(
+            "Configuration file {} already exists, use --force to overwrite it",
+            path.display()
+        );
+    }
+
+    let default_name = init.self_name.clone().unwrap_or_else(|| {
+        hostname::get()
+            .ok()
+            .and_then(|h| h.into_string().ok())
+            .unwrap_or_else(|| "multishiva".to_string())
+    });
+    let default_mode = match init.mode {
+        Some(Mode::Agent) => "agent",
+        _ => "host",
+    };
+    let default_port = init.port.unwrap_or(53421).to_string();
+
+    let mut ask = |question: &str, default: &str| -> Result<String> {
+        if init.non_interactive {
+            return Ok(default.to_string());
+        }
+        if default.is_empty() {
+            write!(output, "{}: ", question)?;
+        } else {
+            write!(output, "{} [{}]: ", question, default)?;
+        }
+        output.flush()?;
+
+        let mut answer = String::new();
+        input.read_line(&mut answer)?;
+        let answer = answer.trim();
+        Ok(if answer.is_empty() { default } else { answer }.to_string())
+    };
+
+    let self_name = ask("Machine name", &default_name)?;
+    let mode = match ask("Mode (host/agent)", default_mode)?.as_str() {
+        "host" => ConfigMode::Host,
+        "agent" => ConfigMode::Agent,
+        other => bail!("Invalid mode '{}', expected host or agent", other),
+    };
+    let port = ask("Port", &default_port)?;
+    let port: u16 = port
+        .parse()
+        .with_context(|| format!("Invalid port '{}'", port))?;
+    let psk = match ask(
+        "Pre-shared key (empty to generate one)",
+        init.psk.as_deref().unwrap_or(""),
+    )? {
+        psk if psk.is_empty() => generate_psk()?,
+        psk => psk,
+    };
+    let host_address = match mode {
+        ConfigMode::Agent => Some(ask(
+            "Host address (empty for mDNS discovery)",
+            init.host_address.as_deref().unwrap_or(""),
+        )?)
+        .filter(|address| !address.is_empty()),
+        ConfigMode::Host => None,
+    };
+
+    let config = Config {
+        self_name,
+        mode,
+        port,
+        host_address,
+        tls: TlsConfig {
+            psk,
+            psk_file: None,
+        },
+        ..Config::default()
+    };
+    config.validate()?;
+    config.save_to_file(path)?;
+    Ok(config)
+}
+
+/// Masks a secret for display, keeping only its first two characters
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::cli::mask_secret;
+///
+/// assert_eq!(mask_secret("secret-psk"), "se********");
+/// assert_eq!(mask_secret("ab"), "**");
+/// ```
+pub fn mask_secret(secret: &str) -> String {
+    let count = secret.chars().count();
+    if count <= 4 {
+        return "*".repeat(count);
+    }
+    let shown: String = secret.chars().take(2).collect();
+    format!("{}{}", shown, "*".repeat(count - 2))
+}
+
+/// Returns a random 256-bit pre-shared key, hex-encoded
+fn generate_psk() -> Result<String> {
+    let mut key = [0u8; 32];
+    getrandom::getrandom(&mut key).context("Failed to generate a random PSK")?;
+    Ok(hex::encode(key))
+}
+
+/// Write the completion script of the whole command line for `shell` to `out`
+///
+/// # Errors
+///
+/// Returns an error if the script cannot be written.
+pub fn write_completions(shell: Shell, out: &mut dyn Write) -> Result<()> {
+    let mut command = Args::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
+    out.flush().context("Failed to write the completion script")
 }
 
 /// Parse command-line arguments
@@ -92,6 +681,13 @@ pub fn parse_args() -> Args {
 /// Returns an error if:
 /// - `--gui` and `--simulate` are both specified
 /// - `--mode` is specified with `--gui`
+/// - `--scenario` is specified without `--simulate`
+/// - `--speed` is not a positive number, or `--replay` is used with `--scenario`
+/// - `--record` is specified with `--simulate`
+/// - `--status` or `status` is specified with `--gui` or `--simulate`
+/// - `rotate-psk` is specified with `--gui` or `--simulate`
+/// - `init` is specified with `--gui` or `--simulate`
+/// - `keyring` is specified with `--gui` or `--simulate`
 pub fn parse_and_validate() -> Result<Args> {
     let args = Args::parse();
     args.validate()?;
@@ -110,6 +706,19 @@ mod tests {
             gui: true,
             simulate: true,
             host: None,
+            bind: None,
+            discovery_timeout: None,
+            metrics_addr: None,
+            scenario: None,
+            record: None,
+            replay: None,
+            speed: 1.0,
+            status: false,
+            list_devices: false,
+            input_backend: None,
+            daemon: false,
+            accept_new_fingerprint: false,
+            command: None,
         };
         assert!(args.validate().is_err());
     }
@@ -122,6 +731,19 @@ mod tests {
             gui: true,
             simulate: false,
             host: None,
+            bind: None,
+            discovery_timeout: None,
+            metrics_addr: None,
+            scenario: None,
+            record: None,
+            replay: None,
+            speed: 1.0,
+            status: false,
+            list_devices: false,
+            input_backend: None,
+            daemon: false,
+            accept_new_fingerprint: false,
+            command: None,
         };
         assert!(args.validate().is_err());
     }
@@ -134,6 +756,19 @@ mod tests {
             gui: false,
             simulate: false,
             host: None,
+            bind: None,
+            discovery_timeout: None,
+            metrics_addr: None,
+            scenario: None,
+            record: None,
+            replay: None,
+            speed: 1.0,
+            status: false,
+            list_devices: false,
+            input_backend: None,
+            daemon: false,
+            accept_new_fingerprint: false,
+            command: None,
         };
         assert!(args.validate().is_ok());
     }
@@ -146,6 +781,19 @@ mod tests {
             gui: false,
             simulate: false,
             host: None,
+            bind: None,
+            discovery_timeout: None,
+            metrics_addr: None,
+            scenario: None,
+            record: None,
+            replay: None,
+            speed: 1.0,
+            status: false,
+            list_devices: false,
+            input_backend: None,
+            daemon: false,
+            accept_new_fingerprint: false,
+            command: None,
         };
         assert!(args.validate().is_ok());
     }
@@ -158,6 +806,19 @@ mod tests {
             gui: false,
             simulate: true,
             host: None,
+            bind: None,
+            discovery_timeout: None,
+            metrics_addr: None,
+            scenario: None,
+            record: None,
+            replay: None,
+            speed: 1.0,
+            status: false,
+            list_devices: false,
+            input_backend: None,
+            daemon: false,
+            accept_new_fingerprint: false,
+            command: None,
         };
         assert!(args.validate().is_ok());
     }
@@ -170,8 +831,580 @@ mod tests {
             gui: true,
             simulate: false,
             host: None,
+            bind: None,
+            discovery_timeout: None,
+            metrics_addr: None,
+            scenario: None,
+            record: None,
+            replay: None,
+            speed: 1.0,
+            status: false,
+            list_devices: false,
+            input_backend: None,
+            daemon: false,
+            accept_new_fingerprint: false,
+            command: None,
+        };
+        assert!(args.validate().is_ok());
+    }
+
+    #[test]
+    fn test_args_validation_scenario_requires_simulate() {
+        let mut args = Args {
+            mode: None,
+            config: None,
+            gui: false,
+            simulate: false,
+            host: None,
+            bind: None,
+            discovery_timeout: None,
+            metrics_addr: None,
+            scenario: Some("scenario.yml".to_string()),
+            record: None,
+            replay: None,
+            speed: 1.0,
+            status: false,
+            list_devices: false,
+            input_backend: None,
+            daemon: false,
+            accept_new_fingerprint: false,
+            command: None,
+        };
+        assert!(args.validate().is_err());
+
+        args.simulate = true;
+        assert!(args.validate().is_ok());
+    }
+
+    #[test]
+    fn test_args_validation_status_conflicts() {
+        let mut args = Args {
+            mode: None,
+            config: None,
+            gui: false,
+            simulate: true,
+            host: None,
+            bind: None,
+            discovery_timeout: None,
+            metrics_addr: None,
+            scenario: None,
+            record: None,
+            replay: None,
+            speed: 1.0,
+            status: true,
+            list_devices: false,
+            input_backend: None,
+            daemon: false,
+            accept_new_fingerprint: false,
+            command: None,
         };
+        assert!(args.validate().is_err());
+
+        args.simulate = false;
+        assert!(args.validate().is_ok());
+        assert_eq!(args.status_request(), Some(true));
+    }
+
+    #[test]
+    fn test_status_subcommand() {
+        let args = Args::try_parse_from(["multishiva", "status"]).unwrap();
+        assert_eq!(args.status_request(), Some(false));
+
+        let args = Args::try_parse_from(["multishiva", "status", "--json"]).unwrap();
+        assert_eq!(args.command, Some(Command::Status { json: true }));
+        assert_eq!(args.status_request(), Some(true));
+
+        let args = Args::try_parse_from(["multishiva"]).unwrap();
+        assert_eq!(args.status_request(), None);
+    }
+
+    #[test]
+    fn test_rotate_psk_subcommand() {
+        let args =
+            Args::try_parse_from(["multishiva", "rotate-psk", "--new-psk", "new-key"]).unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::RotatePsk {
+                new_psk: "new-key".to_string()
+            })
+        );
+        assert_eq!(args.status_request(), None);
+        assert!(args.validate().is_ok());
+
+        let args =
+            Args::try_parse_from(["multishiva", "--simulate", "rotate-psk", "--new-psk", "k"])
+                .unwrap();
+        assert!(args.validate().is_err());
+
+        assert!(Args::try_parse_from(["multishiva", "rotate-psk"]).is_err());
+    }
+
+    #[test]
+    fn test_init_subcommand() {
+        let args = Args::try_parse_from([
+            "multishiva",
+            "init",
+            "--non-interactive",
+            "--force",
+            "--mode",
+            "agent",
+            "--port",
+            "6000",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Init(InitArgs {
+                force: true,
+                non_interactive: true,
+                mode: Some(Mode::Agent),
+                port: Some(6000),
+                ..Default::default()
+            }))
+        );
+        assert_eq!(args.status_request(), None);
+        assert!(args.validate().is_ok());
+    }
+
+    #[test]
+    fn test_clipboard_subcommand() {
+        let args = Args::try_parse_from(["multishiva", "clipboard", "history"]).unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Clipboard {
+                action: ClipboardCommand::History
+            })
+        );
+        assert_eq!(args.status_request(), None);
+        assert!(args.validate().is_ok());
+
+        let args = Args::try_parse_from(["multishiva", "clipboard", "restore", "2"]).unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Clipboard {
+                action: ClipboardCommand::Restore { index: 2 }
+            })
+        );
+
+        assert!(Args::try_parse_from(["multishiva", "clipboard", "restore"]).is_err());
+        assert!(Args::try_parse_from(["multishiva", "clipboard", "restore", "-1"]).is_err());
+        let args = Args::try_parse_from(["multishiva", "--gui", "clipboard", "history"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    fn keyring_action(args: &[&str]) -> KeyringCommand {
+        let args = Args::try_parse_from(["multishiva", "keyring"].iter().chain(args)).unwrap();
+        assert_eq!(args.status_request(), None);
         assert!(args.validate().is_ok());
+        match args.command {
+            Some(Command::Keyring { action }) => action,
+            other => panic!("expected a keyring command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_keyring_set_subcommand() {
+        assert_eq!(
+            keyring_action(&["set", "--psk", "secret"]),
+            KeyringCommand::Set {
+                psk: Some("secret".to_string())
+            }
+        );
+        assert_eq!(keyring_action(&["set"]), KeyringCommand::Set { psk: None });
+    }
+
+    #[test]
+    fn test_keyring_get_subcommand() {
+        assert_eq!(
+            keyring_action(&["get"]),
+            KeyringCommand::Get { show: false }
+        );
+        assert_eq!(
+            keyring_action(&["get", "--show"]),
+            KeyringCommand::Get { show: true }
+        );
+    }
+
+    #[test]
+    fn test_keyring_delete_subcommand() {
+        assert_eq!(keyring_action(&["delete"]), KeyringCommand::Delete);
+    }
+
+    #[test]
+    fn test_keyring_migrate_subcommand() {
+        assert_eq!(
+            keyring_action(&["migrate", "--from-config", "multishiva.yml"]),
+            KeyringCommand::Migrate {
+                from_config: "multishiva.yml".to_string()
+            }
+        );
+        assert!(Args::try_parse_from(["multishiva", "keyring", "migrate"]).is_err());
+        assert!(Args::try_parse_from(["multishiva", "keyring"]).is_err());
+
+        let args = Args::try_parse_from(["multishiva", "--gui", "keyring", "delete"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_logs_subcommand() {
+        let args = Args::try_parse_from(["multishiva", "logs"]).unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Logs(LogsArgs {
+                lines: 100,
+                follow: false,
+                level: None,
+                since: None,
+                cleanup: None,
+            }))
+        );
+        assert_eq!(args.status_request(), None);
+
+        let args = Args::try_parse_from([
+            "multishiva",
+            "logs",
+            "-f",
+            "--level",
+            "warn",
+            "--since",
+            "2h",
+            "-n",
+            "20",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Logs(LogsArgs {
+                lines: 20,
+                follow: true,
+                level: Some(LogLevel::Warn),
+                since: Some(chrono::Duration::hours(2)),
+                cleanup: None,
+            }))
+        );
+
+        assert!(Args::try_parse_from(["multishiva", "logs", "--since", "soon"]).is_err());
+        assert!(Args::try_parse_from(["multishiva", "logs", "--level", "loud"]).is_err());
+    }
+
+    #[test]
+    fn test_record_and_replay_flags() {
+        let args = Args::try_parse_from([
+            "multishiva",
+            "--replay",
+            "events.bin",
+            "--speed",
+            "2.5",
+            "--record",
+            "again.bin",
+        ])
+        .unwrap();
+        assert_eq!(args.replay, Some(PathBuf::from("events.bin")));
+        assert_eq!(args.record, Some(PathBuf::from("again.bin")));
+        assert_eq!(args.speed, 2.5);
+        assert!(args.validate().is_ok());
+
+        // --speed only applies to a replay
+        assert!(Args::try_parse_from(["multishiva", "--speed", "2"]).is_err());
+
+        let args =
+            Args::try_parse_from(["multishiva", "--replay", "events.bin", "--speed", "0"]).unwrap();
+        assert!(args.validate().is_err());
+
+        let args =
+            Args::try_parse_from(["multishiva", "--simulate", "--record", "events.bin"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_discovery_timeout_flag() {
+        let args = Args::try_parse_from(["multishiva", "--discovery-timeout", "30"]).unwrap();
+        assert_eq!(args.discovery_timeout, Some(30));
+        assert!(Args::try_parse_from(["multishiva"])
+            .unwrap()
+            .discovery_timeout
+            .is_none());
+
+        assert!(Args::try_parse_from(["multishiva", "--discovery-timeout", "0"]).is_err());
+        assert!(Args::try_parse_from(["multishiva", "--discovery-timeout", "soon"]).is_err());
+    }
+
+    #[test]
+    fn test_bind_flag() {
+        let args = Args::try_parse_from(["multishiva", "--bind", "iface:eth0"]).unwrap();
+        assert_eq!(args.bind.as_deref(), Some("iface:eth0"));
+        assert!(Args::try_parse_from(["multishiva"]).unwrap().bind.is_none());
+    }
+
+    #[test]
+    fn test_doctor_subcommand() {
+        let args = Args::try_parse_from(["multishiva", "doctor", "--json"]).unwrap();
+        assert_eq!(args.command, Some(Command::Doctor { json: true }));
+        assert_eq!(args.status_request(), None);
+
+        let args = Args::try_parse_from(["multishiva", "--simulate", "doctor"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_list_devices_flag() {
+        let args = Args::try_parse_from(["multishiva", "--list-devices"]).unwrap();
+        assert!(args.list_devices);
+        assert!(args.validate().is_ok());
+
+        let args = Args::try_parse_from(["multishiva", "--list-devices", "--simulate"]).unwrap();
+        assert!(args.validate().is_err());
+        let args = Args::try_parse_from(["multishiva", "--list-devices", "doctor"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_input_backend_flag() {
+        let args = Args::try_parse_from([
+            "multishiva",
+            "--mode",
+            "agent",
+            "--input-backend",
+            "wayland",
+        ])
+        .unwrap();
+        assert_eq!(args.input_backend, Some(InputBackend::Wayland));
+
+        let args = Args::try_parse_from(["multishiva", "--mode", "agent"]).unwrap();
+        assert_eq!(args.input_backend, None);
+        assert!(Args::try_parse_from(["multishiva", "--input-backend", "x11"]).is_err());
+    }
+
+    #[test]
+    fn test_daemon_flag() {
+        let args = Args::try_parse_from(["multishiva", "--daemon", "--mode", "host"]).unwrap();
+        assert!(args.daemon);
+        assert!(args.validate().is_ok());
+
+        for conflicting in [
+            vec!["multishiva", "--daemon", "--gui"],
+            vec!["multishiva", "--daemon", "--simulate"],
+            vec!["multishiva", "--daemon", "--status"],
+            vec!["multishiva", "--daemon", "doctor"],
+        ] {
+            let args = Args::try_parse_from(&conflicting).unwrap();
+            assert!(args.validate().is_err(), "{:?}", conflicting);
+        }
+    }
+
+    #[test]
+    fn test_list_agents_subcommand() {
+        let args = Args::try_parse_from(["multishiva", "list-agents"]).unwrap();
+        assert_eq!(args.command, Some(Command::ListAgents));
+        assert_eq!(args.status_request(), None);
+        assert!(args.validate().is_ok());
+
+        let args = Args::try_parse_from(["multishiva", "--gui", "list-agents"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_pair_subcommand() {
+        let args = Args::try_parse_from(["multishiva", "pair", "--host"]).unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Pair {
+                code: None,
+                host: true
+            })
+        );
+        assert_eq!(args.status_request(), None);
+        assert!(args.validate().is_ok());
+
+        let args =
+            Args::try_parse_from(["multishiva", "--host", "10.0.0.1:53421", "pair", "123456"])
+                .unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Pair {
+                code: Some("123456".to_string()),
+                host: false
+            })
+        );
+        assert_eq!(args.host.as_deref(), Some("10.0.0.1:53421"));
+
+        assert!(Args::try_parse_from(["multishiva", "pair"]).is_err());
+        assert!(Args::try_parse_from(["multishiva", "pair", "123456", "--host"]).is_err());
+        let args = Args::try_parse_from(["multishiva", "--simulate", "pair", "--host"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_completions_subcommand() {
+        let args = Args::try_parse_from(["multishiva", "completions", "bash"]).unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Completions { shell: Shell::Bash })
+        );
+        assert_eq!(args.status_request(), None);
+        assert!(args.validate().is_ok());
+        assert!(Args::try_parse_from(["multishiva", "completions", "tcsh"]).is_err());
+
+        let mut script = Vec::new();
+        write_completions(Shell::Bash, &mut script).unwrap();
+        let script = String::from_utf8(script).unwrap();
+        for subcommand in [
+            "init",
+            "keyring",
+            "doctor",
+            "trust",
+            "list-agents",
+            "completions",
+        ] {
+            assert!(script.contains(subcommand), "missing {}", subcommand);
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay_subcommands() {
+        let args =
+            Args::try_parse_from(["multishiva", "record", "--output", "session.msrec"]).unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Record {
+                output: PathBuf::from("session.msrec")
+            })
+        );
+        assert_eq!(args.status_request(), None);
+
+        let args = Args::try_parse_from([
+            "multishiva",
+            "replay",
+            "-i",
+            "session.msrec",
+            "--speed",
+            "2",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Replay {
+                input: PathBuf::from("session.msrec"),
+                speed: 2.0
+            })
+        );
+        assert!(args.validate().is_ok());
+
+        let args = Args::try_parse_from([
+            "multishiva",
+            "replay",
+            "-i",
+            "session.msrec",
+            "--speed",
+            "0",
+        ])
+        .unwrap();
+        assert!(args.validate().is_err());
+        let args =
+            Args::try_parse_from(["multishiva", "--simulate", "record", "-o", "x.msrec"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_trust_subcommand() {
+        let hash = "0f".repeat(32);
+        let args = Args::try_parse_from([
+            "multishiva",
+            "trust",
+            "192.168.1.10:53421",
+            "--fingerprint",
+            &hash.to_uppercase(),
+        ])
+        .unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Trust {
+                machine: "192.168.1.10:53421".to_string(),
+                fingerprint: Some(hash),
+                accept_latest: false,
+            })
+        );
+        assert_eq!(args.status_request(), None);
+        assert!(args.validate().is_ok());
+        // `fingerprints trust` is the documented command
+        assert!(Args::command()
+            .find_subcommand("trust")
+            .unwrap()
+            .is_hide_set());
+
+        let args =
+            Args::try_parse_from(["multishiva", "trust", "desk", "--accept-latest"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Command::Trust {
+                fingerprint: None,
+                accept_latest: true,
+                ..
+            })
+        ));
+
+        // One of the two is required, and only one
+        assert!(Args::try_parse_from(["multishiva", "trust", "desk"]).is_err());
+        assert!(Args::try_parse_from([
+            "multishiva",
+            "trust",
+            "desk",
+            "--fingerprint",
+            &"0f".repeat(32),
+            "--accept-latest",
+        ])
+        .is_err());
+        assert!(
+            Args::try_parse_from(["multishiva", "trust", "desk", "--fingerprint", "abc"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_setup_permissions_subcommand() {
+        let args = Args::try_parse_from(["multishiva", "setup-permissions"]).unwrap();
+        assert_eq!(args.command, Some(Command::SetupPermissions));
+        assert_eq!(args.status_request(), None);
+        assert!(args.validate().is_ok());
+
+        let args = Args::try_parse_from(["multishiva", "--gui", "setup-permissions"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_fingerprints_subcommand() {
+        let action = |args: &[&str]| {
+            let args = Args::try_parse_from([&["multishiva", "fingerprints"], args].concat());
+            match args.unwrap().command {
+                Some(Command::Fingerprints { action }) => action,
+                other => panic!("unexpected command: {:?}", other),
+            }
+        };
+        assert_eq!(action(&["list"]), FingerprintsCommand::List);
+        assert_eq!(
+            action(&["remove", "192.168.1.10:53421"]),
+            FingerprintsCommand::Remove {
+                machine: "192.168.1.10:53421".to_string()
+            }
+        );
+        assert_eq!(
+            action(&["trust", "desktop", &"AB".repeat(32)]),
+            FingerprintsCommand::Trust {
+                machine: "desktop".to_string(),
+                hash: "ab".repeat(32)
+            }
+        );
+
+        assert!(Args::try_parse_from(["multishiva", "fingerprints", "trust", "desktop"]).is_err());
+        assert!(
+            Args::try_parse_from(["multishiva", "fingerprints", "trust", "desktop", "abc"])
+                .is_err()
+        );
+        let args = Args::try_parse_from(["multishiva", "--gui", "fingerprints", "list"]).unwrap();
+        assert_eq!(args.status_request(), None);
+        assert!(args.validate().is_err());
+
+        let args = Args::try_parse_from(["multishiva", "--accept-new-fingerprint"]).unwrap();
+        assert!(args.accept_new_fingerprint);
     }
 
     #[test]
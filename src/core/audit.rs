@@ -0,0 +1,404 @@
+/// Tamper-evident audit log
+///
+/// Records which machine had focus and when, PSK changes and peer connections.
+/// Each entry is a JSON line carrying an HMAC-SHA256 over its contents and the
+/// previous entry's HMAC, so editing, removing or reordering entries breaks the
+/// chain and is detected by [`AuditLog::verify_chain`].
+///
+/// The log is written to `audit.jsonl` in the data log directory by default.
+/// It does not use the `.log` extension, so log cleanup never removes it.
+/// Its HMAC key is a random key of its own, kept in `audit.key` in the
+/// configuration directory, so the chain outlives PSK rotations.
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::core::logging::get_default_log_dir;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// File name of the audit log inside the log directory.
+pub const AUDIT_LOG_FILE: &str = "audit.jsonl";
+
+/// File name of the audit key inside the configuration directory.
+pub const AUDIT_KEY_FILE: &str = "audit.key";
+
+/// Previous-hash value of the first entry in a chain.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// An audited action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// Focus moved from one machine to another.
+    FocusTransfer {
+        /// Machine that had focus
+        from: String,
+        /// Machine that received focus
+        to: String,
+    },
+    /// The pre-shared key was changed.
+    PskChange,
+    /// A peer connected.
+    Connection {
+        /// Name or address of the peer
+        peer: String,
+    },
+}
+
+/// A single line of the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the action happened.
+    pub timestamp: DateTime<Utc>,
+    /// What happened.
+    #[serde(flatten)]
+    pub event: AuditEvent,
+    /// HMAC of the previous entry, or zeros for the first entry.
+    pub prev: String,
+    /// HMAC-SHA256 of this entry's timestamp, event and `prev`.
+    pub hmac: String,
+}
+
+/// The part of an entry covered by its HMAC.
+#[derive(Serialize)]
+struct SignedPart<'a> {
+    timestamp: &'a DateTime<Utc>,
+    #[serde(flatten)]
+    event: &'a AuditEvent,
+    prev: &'a str,
+}
+
+/// Append-only audit log with an HMAC-SHA256 chain.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Utc;
+/// use multishiva::core::audit::AuditLog;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let mut audit = AuditLog::open(dir.path().join("audit.jsonl"), b"secret").unwrap();
+///
+/// audit.log_connection("agent1", Utc::now()).unwrap();
+/// audit.log_focus_transfer("host", "agent1", Utc::now()).unwrap();
+/// assert!(audit.verify_chain().unwrap());
+/// ```
+pub struct AuditLog {
+    path: PathBuf,
+    key: Vec<u8>,
+    last_hash: String,
+}
+
+impl AuditLog {
+    /// Opens the audit log at `path`, creating it if needed.
+    ///
+    /// New entries continue the chain of any entries already in the file.
+    /// The same `key` must be used to verify the log later.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the parent directory cannot be created or the
+    /// existing file cannot be read.
+    pub fn open<P: AsRef<Path>>(path: P, key: &[u8]) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create audit directory: {:?}", parent))?;
+        }
+
+        let last_hash = match read_entries(&path)?.last() {
+            Some(Ok(entry)) => entry.hmac.clone(),
+            Some(Err(_)) => anyhow::bail!("Audit log {:?} ends with a corrupt entry", path),
+            None => GENESIS_HASH.to_string(),
+        };
+
+        Ok(Self {
+            path,
+            key: key.to_vec(),
+            last_hash,
+        })
+    }
+
+    /// Opens the audit log in the default log directory, keyed by the
+    /// default audit key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be read or created, see also
+    /// [`AuditLog::open`].
+    pub fn open_default() -> Result<Self> {
+        let key = load_or_create_key(&default_key_path())?;
+        Self::open(default_audit_path(), &key)
+    }
+
+    /// Returns the path of the audit log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records that focus moved from `from` to `to`.
+    pub fn log_focus_transfer(
+        &mut self,
+        from: &str,
+        to: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Result<()> {
+        self.append(
+            AuditEvent::FocusTransfer {
+                from: from.to_string(),
+                to: to.to_string(),
+            },
+            timestamp,
+        )
+    }
+
+    /// Records that the pre-shared key was changed.
+    pub fn log_psk_change(&mut self, timestamp: DateTime<Utc>) -> Result<()> {
+        self.append(AuditEvent::PskChange, timestamp)
+    }
+
+    /// Records that `peer` connected.
+    pub fn log_connection(&mut self, peer: &str, timestamp: DateTime<Utc>) -> Result<()> {
+        self.append(
+            AuditEvent::Connection {
+                peer: peer.to_string(),
+            },
+            timestamp,
+        )
+    }
+
+    fn append(&mut self, event: AuditEvent, timestamp: DateTime<Utc>) -> Result<()> {
+        let hmac = sign(&self.key, &timestamp, &event, &self.last_hash)?;
+        let entry = AuditEntry {
+            timestamp,
+            event,
+            prev: self.last_hash.clone(),
+            hmac,
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open audit log: {:?}", self.path))?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.flush()?;
+
+        self.last_hash = entry.hmac;
+        Ok(())
+    }
+
+    /// Checks every entry's HMAC and its link to the previous entry.
+    ///
+    /// Returns `Ok(false)` if any entry was modified, removed, reordered or
+    /// cannot be parsed. A missing file is an empty, valid chain.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read.
+    pub fn verify_chain(&self) -> Result<bool> {
+        verify_file(&self.path, &self.key)
+    }
+}
+
+/// Returns the default audit log location, next to the data logs.
+pub fn default_audit_path() -> PathBuf {
+    get_default_log_dir().join(AUDIT_LOG_FILE)
+}
+
+/// Returns the default location of the audit key.
+pub fn default_key_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_default()
+        .join("multishiva")
+        .join(AUDIT_KEY_FILE)
+}
+
+/// Returns the audit key stored at `path`, first writing a random one
+/// readable by its owner only if there is none.
+///
+/// # Errors
+///
+/// Returns an error if the key cannot be created, or if the file exists but
+/// does not hold a hex-encoded key; it is never replaced, as that would break
+/// the chain.
+pub fn load_or_create_key(path: &Path) -> Result<Vec<u8>> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+    let mut key = [0u8; 32];
+    getrandom::getrandom(&mut key).context("Failed to generate an audit key")?;
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    match options.open(path) {
+        Ok(mut file) => {
+            file.write_all(hex::encode(key).as_bytes())
+                .with_context(|| format!("Failed to write audit key {:?}", path))?;
+            Ok(key.to_vec())
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read audit key {:?}", path))?;
+            let key = hex::decode(contents.trim())
+                .ok()
+                .filter(|key| !key.is_empty())
+                .with_context(|| format!("Audit key {:?} is corrupt", path))?;
+            Ok(key)
+        }
+        Err(e) => Err(e).with_context(|| format!("Failed to create audit key {:?}", path)),
+    }
+}
+
+/// Verifies the HMAC chain of the audit log at `path` using `key`.
+///
+/// Useful for offline verification without opening the log for writing.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn verify_file<P: AsRef<Path>>(path: P, key: &[u8]) -> Result<bool> {
+    let mut prev = GENESIS_HASH.to_string();
+    for entry in read_entries(path.as_ref())? {
+        let Ok(entry) = entry else {
+            return Ok(false);
+        };
+        if entry.prev != prev {
+            return Ok(false);
+        }
+
+        let mut mac = new_mac(key)?;
+        mac.update(signed_bytes(&entry.timestamp, &entry.event, &entry.prev)?.as_slice());
+        let Ok(received) = hex::decode(&entry.hmac) else {
+            return Ok(false);
+        };
+        if mac.verify_slice(&received).is_err() {
+            return Ok(false);
+        }
+        prev = entry.hmac;
+    }
+    Ok(true)
+}
+
+/// Reads all lines of the log, parsing each one independently.
+fn read_entries(path: &Path) -> Result<Vec<Result<AuditEntry, serde_json::Error>>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open audit log: {:?}", path))?;
+
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(serde_json::from_str(&line));
+    }
+    Ok(entries)
+}
+
+fn new_mac(key: &[u8]) -> Result<HmacSha256> {
+    HmacSha256::new_from_slice(key).context("Invalid audit key")
+}
+
+fn signed_bytes(timestamp: &DateTime<Utc>, event: &AuditEvent, prev: &str) -> Result<Vec<u8>> {
+    Ok(serde_json::to_vec(&SignedPart {
+        timestamp,
+        event,
+        prev,
+    })?)
+}
+
+fn sign(key: &[u8], timestamp: &DateTime<Utc>, event: &AuditEvent, prev: &str) -> Result<String> {
+    let mut mac = new_mac(key)?;
+    mac.update(&signed_bytes(timestamp, event, prev)?);
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_chain_detects_modified_entry() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(AUDIT_LOG_FILE);
+        let mut audit = AuditLog::open(&path, b"key").unwrap();
+        audit.log_connection("agent1", Utc::now()).unwrap();
+        audit
+            .log_focus_transfer("host", "agent1", Utc::now())
+            .unwrap();
+        audit.log_psk_change(Utc::now()).unwrap();
+        assert!(audit.verify_chain().unwrap());
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, contents.replace("agent1", "agent2")).unwrap();
+        assert!(!audit.verify_chain().unwrap());
+    }
+
+    #[test]
+    fn test_chain_detects_removed_entry() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(AUDIT_LOG_FILE);
+        let mut audit = AuditLog::open(&path, b"key").unwrap();
+        for peer in ["agent1", "agent2", "agent3"] {
+            audit.log_connection(peer, Utc::now()).unwrap();
+        }
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert!(!audit.verify_chain().unwrap());
+    }
+
+    #[test]
+    fn test_reopen_continues_chain() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(AUDIT_LOG_FILE);
+        AuditLog::open(&path, b"key")
+            .unwrap()
+            .log_connection("agent1", Utc::now())
+            .unwrap();
+
+        let mut audit = AuditLog::open(&path, b"key").unwrap();
+        audit
+            .log_focus_transfer("host", "agent1", Utc::now())
+            .unwrap();
+        assert!(audit.verify_chain().unwrap());
+
+        // A different key cannot vouch for the entries
+        assert!(!verify_file(&path, b"other").unwrap());
+    }
+
+    #[test]
+    fn test_key_is_created_once_and_private() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(AUDIT_KEY_FILE);
+        let key = load_or_create_key(&path).unwrap();
+        assert_eq!(key.len(), 32);
+        assert_eq!(load_or_create_key(&path).unwrap(), key);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::write(&path, "not hex").unwrap();
+        assert!(load_or_create_key(&path).is_err());
+    }
+}
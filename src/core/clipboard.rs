@@ -9,10 +9,23 @@
 /// - Automatic propagation across network
 /// - Duplicate prevention
 use anyhow::Result;
-use clipboard_rs::{Clipboard, ClipboardContext};
+use clipboard_rs::{
+    Clipboard, ClipboardContext, ClipboardHandler, ClipboardWatcher, ClipboardWatcherContext,
+    WatcherShutdown,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 
+use crate::core::config::ConfigMode;
+
+/// Number of clipboard changes kept in the history by default.
+pub const DEFAULT_HISTORY_CAPACITY: usize = 10;
+
 /// Represents different types of content that can be stored in the clipboard.
 ///
 /// This enum encapsulates various clipboard content formats. Currently only
@@ -27,7 +40,7 @@ use std::time::{Duration, SystemTime};
 /// let text_content = ClipboardContent::Text("Hello, World!".to_string());
 /// assert_eq!(text_content.as_text(), Some("Hello, World!"));
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ClipboardContent {
     /// Plain text content from the clipboard.
     ///
@@ -119,17 +132,217 @@ pub struct ClipboardChange {
     pub source: Option<String>,
 }
 
-/// Manages clipboard synchronization with polling-based change detection.
+/// The most recent clipboard changes, newest first, up to a capacity.
+struct HistoryRing {
+    entries: VecDeque<ClipboardChange>,
+    capacity: usize,
+}
+
+impl HistoryRing {
+    /// Adds `change` in front, evicting the oldest entry when full.
+    fn push(&mut self, change: ClipboardChange) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.push_front(change);
+        self.entries.truncate(self.capacity);
+    }
+}
+
+/// Handle on the clipboard history of a [`ClipboardManager`].
+///
+/// Every change the manager accepts is recorded, local ones with no source
+/// and remote ones with the machine they came from. Entries are numbered from
+/// 0, the most recent. Clones share the same history, so it can be read and
+/// restored away from the task owning the manager.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::clipboard::{ClipboardManager, DEFAULT_HISTORY_CAPACITY};
+///
+/// let manager = ClipboardManager::new().unwrap();
+/// let history = manager.history_handle();
+/// assert_eq!(history.capacity(), DEFAULT_HISTORY_CAPACITY);
+/// assert!(history.entries().is_empty());
+/// assert!(history.restore(0).is_err());
+/// ```
+#[derive(Clone)]
+pub struct ClipboardHistory {
+    ring: Arc<Mutex<HistoryRing>>,
+    last_content: Arc<Mutex<Option<ClipboardContent>>>,
+    last_update: Arc<Mutex<SystemTime>>,
+    remote_set_hash: Arc<Mutex<Option<u64>>>,
+}
+
+impl ClipboardHistory {
+    /// Returns the recorded changes, most recent first.
+    pub fn entries(&self) -> Vec<ClipboardChange> {
+        self.ring
+            .lock()
+            .map(|ring| ring.entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns how many changes are kept.
+    pub fn capacity(&self) -> usize {
+        self.ring.lock().map(|ring| ring.capacity).unwrap_or(0)
+    }
+
+    /// Keeps at most `capacity` changes, dropping the oldest ones; 0 keeps none.
+    pub fn set_capacity(&self, capacity: usize) {
+        if let Ok(mut ring) = self.ring.lock() {
+            ring.capacity = capacity;
+            ring.entries.truncate(capacity);
+        }
+    }
+
+    /// Forgets every recorded change.
+    pub fn clear(&self) {
+        if let Ok(mut ring) = self.ring.lock() {
+            ring.entries.clear();
+        }
+    }
+
+    /// Puts entry `index` back into the system clipboard without broadcasting it.
+    ///
+    /// The content is marked as already seen, as remote content is, so the
+    /// monitoring does not report it as a local change. The history itself
+    /// is left as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no entry `index` or the system clipboard
+    /// cannot be set.
+    pub fn restore(&self, index: usize) -> Result<()> {
+        self.restore_with(index, write_system_text)
+    }
+
+    /// Restores entry `index` through `write`.
+    fn restore_with<W>(&self, index: usize, write: W) -> Result<()>
+    where
+        W: FnOnce(&str) -> Result<()>,
+    {
+        let entries = self.entries();
+        let Some(change) = entries.get(index) else {
+            anyhow::bail!(
+                "No clipboard history entry {}, the history holds {}",
+                index,
+                entries.len()
+            );
+        };
+
+        // Mark it seen before writing so a concurrent poll cannot report it
+        let previous = self
+            .last_content
+            .lock()
+            .ok()
+            .and_then(|mut last| last.replace(change.content.clone()));
+        if let Ok(mut hash) = self.remote_set_hash.lock() {
+            *hash = Some(content_hash(&change.content));
+        }
+
+        let ClipboardContent::Text(text) = &change.content;
+        if let Err(e) = write(text) {
+            if let Ok(mut last) = self.last_content.lock() {
+                *last = previous;
+            }
+            if let Ok(mut hash) = self.remote_set_hash.lock() {
+                *hash = None;
+            }
+            return Err(e);
+        }
+        if let Ok(mut time) = self.last_update.lock() {
+            *time = SystemTime::now();
+        }
+        Ok(())
+    }
+}
+
+/// Which way clipboard content is allowed to flow between host and agents.
+///
+/// Local changes are always detected so the manager stays in sync with the
+/// system clipboard; the direction only decides whether they are broadcast and
+/// whether remote content is accepted.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::clipboard::ClipboardDirection;
+/// use multishiva::core::config::ConfigMode;
+///
+/// let direction = ClipboardDirection::HostToAgent;
+/// assert!(direction.allows_outbound(&ConfigMode::Host));
+/// assert!(!direction.allows_outbound(&ConfigMode::Agent));
+/// assert!(direction.allows_inbound(&ConfigMode::Agent));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardDirection {
+    /// Clipboard changes flow both ways.
+    #[default]
+    Bidirectional,
+
+    /// Only the host's clipboard is pushed to agents.
+    HostToAgent,
+
+    /// Only agents' clipboards are pushed to the host.
+    AgentToHost,
+}
+
+impl ClipboardDirection {
+    /// Returns whether a machine running as `role` may broadcast its local changes.
+    pub fn allows_outbound(&self, role: &ConfigMode) -> bool {
+        match self {
+            Self::Bidirectional => true,
+            Self::HostToAgent => *role == ConfigMode::Host,
+            Self::AgentToHost => *role == ConfigMode::Agent,
+        }
+    }
+
+    /// Returns whether a machine running as `role` may apply remote changes.
+    pub fn allows_inbound(&self, role: &ConfigMode) -> bool {
+        match self {
+            Self::Bidirectional => true,
+            Self::HostToAgent => *role == ConfigMode::Agent,
+            Self::AgentToHost => *role == ConfigMode::Host,
+        }
+    }
+}
+
+/// How the clipboard is being watched for changes.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::clipboard::MonitoringMode;
+///
+/// let mode = MonitoringMode::Polling;
+/// assert_ne!(mode, MonitoringMode::EventDriven);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitoringMode {
+    /// Changes are reported by platform notifications (XFixes selection
+    /// events on X11, clipboard listeners on Windows and macOS).
+    EventDriven,
+
+    /// The clipboard is read every `poll_interval` and compared to the last
+    /// known content.
+    Polling,
+}
+
+/// Manages clipboard synchronization with change detection.
 ///
 /// The `ClipboardManager` provides a robust system for monitoring clipboard changes
-/// and synchronizing content across multiple machines. It uses a polling mechanism
-/// to detect changes and maintains state to prevent duplicate notifications and
-/// synchronization loops.
+/// and synchronizing content across multiple machines. It maintains state to prevent
+/// duplicate notifications and synchronization loops.
 ///
 /// # Monitoring
 ///
-/// The manager uses background polling to detect clipboard changes at configurable
-/// intervals (default: 500ms). When a change is detected, registered callbacks are
+/// The manager listens for platform clipboard-change notifications where they are
+/// available and falls back to background polling at a configurable interval
+/// (default: 500ms) otherwise. Use [`ClipboardManager::monitoring_mode`] to find
+/// out which one is active. When a change is detected, registered callbacks are
 /// invoked with the change event.
 ///
 /// # Thread Safety
@@ -166,6 +379,25 @@ pub struct ClipboardManager {
 
     /// The interval between clipboard polls.
     poll_interval: Duration,
+
+    /// The change detection strategy in use while monitoring.
+    mode: Arc<Mutex<Option<MonitoringMode>>>,
+
+    /// Hash of the content last applied from a remote machine, used to
+    /// suppress the echo on the next monitoring cycle.
+    remote_set_hash: Arc<Mutex<Option<u64>>>,
+
+    /// Stops the event-driven watcher when dropped.
+    watcher_shutdown: Arc<Mutex<Option<WatcherShutdown>>>,
+
+    /// Allowed flow of clipboard content between host and agents.
+    direction: ClipboardDirection,
+
+    /// Whether this machine is the host or an agent.
+    role: ConfigMode,
+
+    /// Recent changes accepted by this manager.
+    history: ClipboardHistory,
 }
 
 impl ClipboardManager {
@@ -175,6 +407,7 @@ impl ClipboardManager {
     /// - Poll interval: 500ms
     /// - Monitoring: inactive (must call `start_monitoring` to begin)
     /// - No cached content
+    /// - History: the last [`DEFAULT_HISTORY_CAPACITY`] changes
     ///
     /// # Errors
     ///
@@ -194,11 +427,29 @@ impl ClipboardManager {
     /// # }
     /// ```
     pub fn new() -> Result<Self> {
+        let last_content = Arc::new(Mutex::new(None));
+        let last_update = Arc::new(Mutex::new(SystemTime::now()));
+        let remote_set_hash = Arc::new(Mutex::new(None));
+        let history = ClipboardHistory {
+            ring: Arc::new(Mutex::new(HistoryRing {
+                entries: VecDeque::new(),
+                capacity: DEFAULT_HISTORY_CAPACITY,
+            })),
+            last_content: Arc::clone(&last_content),
+            last_update: Arc::clone(&last_update),
+            remote_set_hash: Arc::clone(&remote_set_hash),
+        };
         Ok(Self {
-            last_content: Arc::new(Mutex::new(None)),
-            last_update: Arc::new(Mutex::new(SystemTime::now())),
+            last_content,
+            last_update,
             monitoring: Arc::new(Mutex::new(false)),
             poll_interval: Duration::from_millis(500),
+            mode: Arc::new(Mutex::new(None)),
+            remote_set_hash,
+            watcher_shutdown: Arc::new(Mutex::new(None)),
+            direction: ClipboardDirection::default(),
+            role: ConfigMode::Host,
+            history,
         })
     }
 
@@ -235,6 +486,80 @@ impl ClipboardManager {
         Ok(manager)
     }
 
+    /// Sets the allowed sync direction and the role of this machine.
+    ///
+    /// Changes take effect the next time `start_monitoring()` is called and
+    /// immediately for `set_content_from_remote()`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::clipboard::{ClipboardDirection, ClipboardManager};
+    /// use multishiva::core::config::ConfigMode;
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut manager = ClipboardManager::new()?;
+    /// manager.set_direction(ClipboardDirection::HostToAgent, ConfigMode::Agent);
+    /// assert_eq!(manager.direction(), ClipboardDirection::HostToAgent);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_direction(&mut self, direction: ClipboardDirection, role: ConfigMode) {
+        self.direction = direction;
+        self.role = role;
+    }
+
+    /// Returns the configured sync direction.
+    pub fn direction(&self) -> ClipboardDirection {
+        self.direction
+    }
+
+    /// Keeps the last `capacity` changes in the history, 0 to keep none.
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history.set_capacity(capacity);
+    }
+
+    /// Returns the changes accepted so far, most recent first.
+    ///
+    /// See [`ClipboardHistory`].
+    pub fn history(&self) -> Vec<ClipboardChange> {
+        self.history.entries()
+    }
+
+    /// Puts history entry `index` back into the system clipboard without
+    /// broadcasting it; 0 is the most recent entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no entry `index` or the system clipboard
+    /// cannot be set.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::clipboard::ClipboardManager;
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let manager = ClipboardManager::new()?;
+    /// // Paste what was copied before the current content
+    /// manager.restore(1)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn restore(&self, index: usize) -> Result<()> {
+        self.history.restore(index)
+    }
+
+    /// Forgets the clipboard history.
+    pub fn clear_history(&self) {
+        self.history.clear();
+    }
+
+    /// Returns a handle on the history that can be moved to another task.
+    pub fn history_handle(&self) -> ClipboardHistory {
+        self.history.clone()
+    }
+
     /// Retrieves the current content from the system clipboard.
     ///
     /// This method queries the system clipboard and returns its current contents
@@ -362,10 +687,27 @@ impl ClipboardManager {
         content: ClipboardContent,
         source: String,
     ) -> Result<()> {
+        if !self.direction.allows_inbound(&self.role) {
+            tracing::debug!(
+                "Ignoring clipboard from {} (direction: {:?})",
+                source,
+                self.direction
+            );
+            return Ok(());
+        }
+
         tracing::debug!("Setting clipboard from remote source: {}", source);
 
         // Set the content
         self.set_content(content.clone())?;
+        crate::core::metrics::record_clipboard_sync();
+        if let Ok(mut ring) = self.history.ring.lock() {
+            ring.push(ClipboardChange {
+                content: content.clone(),
+                timestamp: SystemTime::now(),
+                source: Some(source),
+            });
+        }
 
         // Mark as already processed to prevent echo
         if let Ok(mut last) = self.last_content.lock() {
@@ -375,14 +717,56 @@ impl ClipboardManager {
         Ok(())
     }
 
+    /// Sets clipboard content received from a remote machine and suppresses
+    /// the resulting local change notification.
+    ///
+    /// Works like [`set_content_from_remote`](Self::set_content_from_remote),
+    /// but also remembers a hash of the content. If the next monitoring cycle
+    /// reads back that same content, no [`ClipboardChange`] is emitted, so it is
+    /// not sent back to the machine it came from. The hash is cleared after one
+    /// cycle whether it matched or not.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the system clipboard cannot be set.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::clipboard::{ClipboardContent, ClipboardManager};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut manager = ClipboardManager::new()?;
+    /// let content = ClipboardContent::Text("from host".to_string());
+    ///
+    /// manager.set_content_from_remote_suppressing_echo(content, "host".to_string())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_content_from_remote_suppressing_echo(
+        &mut self,
+        content: ClipboardContent,
+        source: String,
+    ) -> Result<()> {
+        if !self.direction.allows_inbound(&self.role) {
+            return self.set_content_from_remote(content, source);
+        }
+
+        if let Ok(mut hash) = self.remote_set_hash.lock() {
+            *hash = Some(content_hash(&content));
+        }
+        self.set_content_from_remote(content, source)
+    }
+
     /// Starts monitoring the clipboard for changes in a background thread.
     ///
-    /// This method spawns a background polling thread that periodically checks
-    /// the system clipboard for changes. When a change is detected (content differs
-    /// from the last known state), the provided callback function is invoked with
-    /// a `ClipboardChange` event.
+    /// Where the platform supports it, the background thread registers for
+    /// clipboard-change notifications and only reads the clipboard when it is told
+    /// the owner changed. Otherwise, or if registering fails at runtime, it polls
+    /// the system clipboard every `poll_interval` (default 500ms). When a change is
+    /// detected (content differs from the last known state), the provided callback
+    /// function is invoked with a `ClipboardChange` event.
     ///
-    /// The polling interval is determined by the `poll_interval` setting (default 500ms).
     /// Empty clipboard contents are ignored and will not trigger callbacks.
     ///
     /// # Arguments
@@ -416,52 +800,79 @@ impl ClipboardManager {
     pub fn start_monitoring<F>(&mut self, callback: F) -> Result<()>
     where
         F: Fn(ClipboardChange) + Send + 'static,
+    {
+        let mode = if event_driven_available() {
+            MonitoringMode::EventDriven
+        } else {
+            MonitoringMode::Polling
+        };
+        self.start_monitoring_with(mode, read_system_text, callback)
+    }
+
+    /// Starts monitoring with an explicit initial mode and clipboard reader.
+    fn start_monitoring_with<R, F>(
+        &mut self,
+        mode: MonitoringMode,
+        read_text: R,
+        callback: F,
+    ) -> Result<()>
+    where
+        R: Fn() -> Option<String> + Send + 'static,
+        F: Fn(ClipboardChange) + Send + 'static,
     {
         // Set monitoring flag
         if let Ok(mut monitoring) = self.monitoring.lock() {
             *monitoring = true;
         }
+        if let Ok(mut current) = self.mode.lock() {
+            *current = Some(mode);
+        }
 
-        let last_content = Arc::clone(&self.last_content);
-        let last_update = Arc::clone(&self.last_update);
+        let detector = self.detector();
         let monitoring = Arc::clone(&self.monitoring);
+        let current_mode = Arc::clone(&self.mode);
+        let watcher_shutdown = Arc::clone(&self.watcher_shutdown);
         let poll_interval = self.poll_interval;
 
-        // Spawn background thread to poll clipboard
+        // Local changes are still tracked when they must not leave this machine
+        let direction = self.direction;
+        let broadcast = direction.allows_outbound(&self.role);
+        let callback = move |change: ClipboardChange| {
+            if broadcast {
+                callback(change);
+            } else {
+                tracing::debug!(
+                    "Local clipboard change not broadcast (direction: {:?})",
+                    direction
+                );
+            }
+        };
+
         std::thread::spawn(move || {
-            while let Ok(true) = monitoring.lock().map(|m| *m) {
-                // Get current clipboard content
-                if let Ok(ctx) = ClipboardContext::new() {
-                    if let Ok(text) = ctx.get_text() {
-                        let content = ClipboardContent::Text(text.clone());
-
-                        // Check if content actually changed
-                        let should_notify = if let Ok(last) = last_content.lock() {
-                            match &*last {
-                                Some(last_content) => last_content != &content,
-                                None => true,
-                            }
-                        } else {
-                            true
-                        };
-
-                        if should_notify && !content.is_empty() {
-                            // Update tracking
-                            if let Ok(mut last) = last_content.lock() {
-                                *last = Some(content.clone());
-                            }
-                            if let Ok(mut time) = last_update.lock() {
-                                *time = SystemTime::now();
-                            }
-
-                            // Trigger callback
-                            let change = ClipboardChange {
-                                content,
-                                timestamp: SystemTime::now(),
-                                source: None, // Local change
-                            };
-                            callback(change);
-                        }
+            if mode == MonitoringMode::EventDriven {
+                let watched = watch_clipboard(
+                    &detector,
+                    &read_text,
+                    &callback,
+                    &monitoring,
+                    &watcher_shutdown,
+                );
+                if watched || !is_flag_set(&monitoring) {
+                    return;
+                }
+
+                tracing::warn!(
+                    "Clipboard change notifications unavailable, falling back to polling"
+                );
+                if let Ok(mut current) = current_mode.lock() {
+                    *current = Some(MonitoringMode::Polling);
+                }
+            }
+
+            while is_flag_set(&monitoring) {
+                if let Some(text) = read_text() {
+                    if let Some(change) = detector.detect(text) {
+                        callback(change);
                     }
                 }
 
@@ -470,10 +881,15 @@ impl ClipboardManager {
             }
         });
 
-        tracing::info!(
-            "Clipboard monitoring started (poll interval: {:?})",
-            self.poll_interval
-        );
+        match mode {
+            MonitoringMode::EventDriven => {
+                tracing::info!("Clipboard monitoring started (event-driven)")
+            }
+            MonitoringMode::Polling => tracing::info!(
+                "Clipboard monitoring started (poll interval: {:?})",
+                self.poll_interval
+            ),
+        }
         Ok(())
     }
 
@@ -505,6 +921,13 @@ impl ClipboardManager {
             *monitoring = false;
             tracing::info!("Clipboard monitoring stopped");
         }
+        if let Ok(mut mode) = self.mode.lock() {
+            *mode = None;
+        }
+        // Dropping the shutdown handle stops the event-driven watcher
+        if let Ok(mut shutdown) = self.watcher_shutdown.lock() {
+            shutdown.take();
+        }
     }
 
     /// Checks whether clipboard monitoring is currently active.
@@ -527,7 +950,32 @@ impl ClipboardManager {
     /// # }
     /// ```
     pub fn is_monitoring(&self) -> bool {
-        self.monitoring.lock().map(|m| *m).unwrap_or(false)
+        is_flag_set(&self.monitoring)
+    }
+
+    /// Returns the change detection strategy currently in use.
+    ///
+    /// Returns `None` when monitoring is not active. The mode may switch from
+    /// [`MonitoringMode::EventDriven`] to [`MonitoringMode::Polling`] shortly
+    /// after `start_monitoring()` if the platform rejects the notification
+    /// registration at runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::clipboard::ClipboardManager;
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut manager = ClipboardManager::new()?;
+    /// assert_eq!(manager.monitoring_mode(), None);
+    ///
+    /// manager.start_monitoring(|_| {})?;
+    /// println!("Watching clipboard via {:?}", manager.monitoring_mode());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn monitoring_mode(&self) -> Option<MonitoringMode> {
+        self.mode.lock().map(|m| *m).unwrap_or(None)
     }
 
     /// Returns the timestamp of the last clipboard update detected by this manager.
@@ -596,6 +1044,174 @@ impl ClipboardManager {
     pub fn updated_since(&self, time: SystemTime) -> bool {
         self.last_update_time() > time
     }
+
+    /// Returns a detector sharing this manager's state.
+    fn detector(&self) -> ChangeDetector {
+        ChangeDetector {
+            last_content: Arc::clone(&self.last_content),
+            last_update: Arc::clone(&self.last_update),
+            remote_set_hash: Arc::clone(&self.remote_set_hash),
+            history: Arc::clone(&self.history.ring),
+        }
+    }
+}
+
+/// Shared state used to turn raw clipboard reads into change events.
+struct ChangeDetector {
+    last_content: Arc<Mutex<Option<ClipboardContent>>>,
+    last_update: Arc<Mutex<SystemTime>>,
+    remote_set_hash: Arc<Mutex<Option<u64>>>,
+    history: Arc<Mutex<HistoryRing>>,
+}
+
+impl ChangeDetector {
+    /// Returns a change event if `text` differs from the last known content.
+    ///
+    /// Content matching the hash stored by a remote set is treated as an echo
+    /// and ignored. The hash only lives for a single cycle.
+    fn detect(&self, text: String) -> Option<ClipboardChange> {
+        let content = ClipboardContent::Text(text);
+
+        let remote_hash = self.remote_set_hash.lock().ok().and_then(|mut h| h.take());
+        if remote_hash == Some(content_hash(&content)) {
+            if let Ok(mut last) = self.last_content.lock() {
+                *last = Some(content);
+            }
+            return None;
+        }
+
+        // Check if content actually changed
+        let changed = match self.last_content.lock() {
+            Ok(last) => last.as_ref() != Some(&content),
+            Err(_) => true,
+        };
+
+        if !changed || content.is_empty() {
+            return None;
+        }
+
+        // Update tracking
+        if let Ok(mut last) = self.last_content.lock() {
+            *last = Some(content.clone());
+        }
+        if let Ok(mut time) = self.last_update.lock() {
+            *time = SystemTime::now();
+        }
+
+        let change = ClipboardChange {
+            content,
+            timestamp: SystemTime::now(),
+            source: None, // Local change
+        };
+        if let Ok(mut history) = self.history.lock() {
+            history.push(change.clone());
+        }
+        Some(change)
+    }
+}
+
+/// Bridges clipboard-rs change notifications to a change callback.
+struct NotificationHandler<'a, R, F> {
+    detector: &'a ChangeDetector,
+    read_text: &'a R,
+    callback: &'a F,
+}
+
+impl<R, F> ClipboardHandler for NotificationHandler<'_, R, F>
+where
+    R: Fn() -> Option<String>,
+    F: Fn(ClipboardChange),
+{
+    fn on_clipboard_change(&mut self) {
+        if let Some(text) = (self.read_text)() {
+            if let Some(change) = self.detector.detect(text) {
+                (self.callback)(change);
+            }
+        }
+    }
+}
+
+/// Returns whether the platform can deliver clipboard-change notifications.
+fn event_driven_available() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        // Notifications rely on the XFixes extension of an X server (or XWayland)
+        std::env::var_os("DISPLAY").is_some()
+    }
+
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    {
+        true
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    {
+        false
+    }
+}
+
+/// Blocks on clipboard-change notifications until monitoring stops.
+///
+/// Returns `false` if the platform watcher could not be set up, in which case
+/// the caller should fall back to polling.
+fn watch_clipboard<R, F>(
+    detector: &ChangeDetector,
+    read_text: &R,
+    callback: &F,
+    monitoring: &Arc<Mutex<bool>>,
+    watcher_shutdown: &Arc<Mutex<Option<WatcherShutdown>>>,
+) -> bool
+where
+    R: Fn() -> Option<String>,
+    F: Fn(ClipboardChange),
+{
+    let mut watcher = match ClipboardWatcherContext::new() {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            tracing::debug!("Failed to create clipboard watcher: {}", e);
+            return false;
+        }
+    };
+    watcher.add_handler(NotificationHandler {
+        detector,
+        read_text,
+        callback,
+    });
+
+    if let Ok(mut shutdown) = watcher_shutdown.lock() {
+        *shutdown = Some(watcher.get_shutdown_channel());
+    }
+    // stop_monitoring() may have run before the shutdown handle was stored
+    if !is_flag_set(monitoring) {
+        return true;
+    }
+
+    // clipboard-rs panics when the display server lacks the required support
+    panic::catch_unwind(AssertUnwindSafe(|| watcher.start_watch())).is_ok()
+}
+
+/// Hashes clipboard content for echo detection.
+fn content_hash(content: &ClipboardContent) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Reads the current text content of the system clipboard.
+fn read_system_text() -> Option<String> {
+    ClipboardContext::new().ok()?.get_text().ok()
+}
+
+/// Replaces the text content of the system clipboard.
+fn write_system_text(text: &str) -> Result<()> {
+    let ctx = ClipboardContext::new()
+        .map_err(|e| anyhow::anyhow!("Failed to create clipboard context: {}", e))?;
+    ctx.set_text(text.to_string())
+        .map_err(|e| anyhow::anyhow!("Failed to set clipboard text: {}", e))
+}
+
+fn is_flag_set(flag: &Arc<Mutex<bool>>) -> bool {
+    flag.lock().map(|f| *f).unwrap_or(false)
 }
 
 impl Default for ClipboardManager {
@@ -710,6 +1326,201 @@ mod tests {
         assert!(!manager.is_monitoring());
     }
 
+    #[test]
+    fn test_polling_fallback_fires_callbacks() {
+        let mut manager = ClipboardManager::with_poll_interval(Duration::from_millis(5)).unwrap();
+        let reads = Arc::new(Mutex::new(vec!["second".to_string(), "first".to_string()]));
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let source = Arc::clone(&reads);
+        manager
+            .start_monitoring_with(
+                MonitoringMode::Polling,
+                move || {
+                    let mut reads = source.lock().unwrap();
+                    if reads.len() > 1 {
+                        reads.pop()
+                    } else {
+                        reads.last().cloned()
+                    }
+                },
+                move |change| {
+                    let _ = tx.send(change);
+                },
+            )
+            .unwrap();
+        assert_eq!(manager.monitoring_mode(), Some(MonitoringMode::Polling));
+
+        let first = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        let second = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(first.content.as_text(), Some("first"));
+        assert_eq!(second.content.as_text(), Some("second"));
+        assert!(second.source.is_none());
+
+        // Unchanged content must not fire again
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+
+        manager.stop_monitoring();
+        assert_eq!(manager.monitoring_mode(), None);
+    }
+
+    #[test]
+    fn test_host_to_agent_suppresses_agent_broadcast() {
+        let mut manager = ClipboardManager::with_poll_interval(Duration::from_millis(5)).unwrap();
+        manager.set_direction(ClipboardDirection::HostToAgent, ConfigMode::Agent);
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        manager
+            .start_monitoring_with(
+                MonitoringMode::Polling,
+                || Some("agent secret".to_string()),
+                move |change| {
+                    let _ = tx.send(change);
+                },
+            )
+            .unwrap();
+
+        // The change is detected locally...
+        let deadline = SystemTime::now() + Duration::from_secs(1);
+        while manager.last_content.lock().unwrap().is_none() && SystemTime::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(
+            *manager.last_content.lock().unwrap(),
+            Some(ClipboardContent::Text("agent secret".to_string()))
+        );
+
+        // ...but never handed to the sync callback
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+        manager.stop_monitoring();
+    }
+
+    #[test]
+    fn test_clipboard_direction_rules() {
+        use ClipboardDirection::*;
+
+        assert!(Bidirectional.allows_outbound(&ConfigMode::Agent));
+        assert!(Bidirectional.allows_inbound(&ConfigMode::Host));
+        assert!(!HostToAgent.allows_inbound(&ConfigMode::Host));
+        assert!(AgentToHost.allows_outbound(&ConfigMode::Agent));
+        assert!(!AgentToHost.allows_outbound(&ConfigMode::Host));
+        assert!(!AgentToHost.allows_inbound(&ConfigMode::Agent));
+    }
+
+    #[test]
+    fn test_remote_set_hash_suppresses_one_echo() {
+        let manager = ClipboardManager::new().unwrap();
+        let detector = manager.detector();
+        let remote = ClipboardContent::Text("from host".to_string());
+        *manager.remote_set_hash.lock().unwrap() = Some(content_hash(&remote));
+
+        // The polling tick that reads back the remote content is suppressed...
+        assert!(detector.detect("from host".to_string()).is_none());
+        assert!(manager.remote_set_hash.lock().unwrap().is_none());
+
+        // ...and a genuine local change afterwards is reported
+        let change = detector.detect("typed locally".to_string()).unwrap();
+        assert_eq!(change.content.as_text(), Some("typed locally"));
+    }
+
+    #[test]
+    fn test_remote_set_hash_cleared_after_one_cycle() {
+        let manager = ClipboardManager::new().unwrap();
+        let detector = manager.detector();
+        let remote = ClipboardContent::Text("from host".to_string());
+        *manager.remote_set_hash.lock().unwrap() = Some(content_hash(&remote));
+
+        // A different local copy wins over the pending remote content
+        assert!(detector.detect("local".to_string()).is_some());
+        assert!(manager.remote_set_hash.lock().unwrap().is_none());
+        assert!(detector.detect("from host".to_string()).is_some());
+    }
+
+    #[test]
+    fn test_history_evicts_oldest_changes() {
+        let mut manager = ClipboardManager::new().unwrap();
+        manager.set_history_capacity(3);
+        let detector = manager.detector();
+        for text in ["one", "two", "three", "four"] {
+            assert!(detector.detect(text.to_string()).is_some());
+        }
+        // Unchanged content is not a new entry
+        assert!(detector.detect("four".to_string()).is_none());
+
+        let texts = |manager: &ClipboardManager| {
+            manager
+                .history()
+                .iter()
+                .map(|c| c.content.as_text().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&manager), ["four", "three", "two"]);
+        assert!(manager.history().iter().all(|c| c.source.is_none()));
+
+        manager.history.ring.lock().unwrap().push(ClipboardChange {
+            content: ClipboardContent::Text("from host".to_string()),
+            timestamp: SystemTime::now(),
+            source: Some("host".to_string()),
+        });
+        assert_eq!(texts(&manager), ["from host", "four", "three"]);
+        assert_eq!(manager.history()[0].source.as_deref(), Some("host"));
+
+        manager.set_history_capacity(1);
+        assert_eq!(texts(&manager), ["from host"]);
+        manager.clear_history();
+        assert!(manager.history().is_empty());
+
+        manager.set_history_capacity(0);
+        assert!(detector.detect("five".to_string()).is_some());
+        assert!(manager.history().is_empty());
+    }
+
+    #[test]
+    fn test_restore_is_not_broadcast() {
+        let manager = ClipboardManager::new().unwrap();
+        let detector = manager.detector();
+        for text in ["older", "newer"] {
+            detector.detect(text.to_string());
+        }
+
+        let written = Mutex::new(None);
+        manager
+            .history
+            .restore_with(1, |text| {
+                *written.lock().unwrap() = Some(text.to_string());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(written.lock().unwrap().as_deref(), Some("older"));
+
+        // Reading the restored content back is not a local change
+        assert!(detector.detect("older".to_string()).is_none());
+        assert_eq!(manager.history().len(), 2);
+        assert_eq!(manager.history()[0].content.as_text(), Some("newer"));
+
+        let err = manager.history.restore_with(2, |_| Ok(())).unwrap_err();
+        assert!(err.to_string().contains("No clipboard history entry 2"));
+    }
+
+    #[test]
+    fn test_failed_restore_keeps_tracking() {
+        let manager = ClipboardManager::new().unwrap();
+        let detector = manager.detector();
+        for text in ["older", "newer"] {
+            detector.detect(text.to_string());
+        }
+
+        let result = manager
+            .history
+            .restore_with(1, |_| anyhow::bail!("no display"));
+        assert!(result.is_err());
+        assert!(manager.remote_set_hash.lock().unwrap().is_none());
+        assert_eq!(
+            *manager.last_content.lock().unwrap(),
+            Some(ClipboardContent::Text("newer".to_string()))
+        );
+    }
+
     // Note: Integration tests for actual clipboard operations
     // are difficult to test in CI environments without display/clipboard access.
     // These should be tested manually on local machines.
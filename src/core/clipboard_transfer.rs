@@ -0,0 +1,405 @@
+/// Size limits and chunked transfer of clipboard content
+///
+/// Text up to [`CHUNK_SIZE`] bytes travels as a single `ClipboardSync` event.
+/// Larger text is split into `ClipboardChunk` events that the sender queues in
+/// a [`ClipboardOutbox`] and sends one at a time, so input events keep flowing
+/// between chunks. The receiver collects them in a [`ClipboardReassembler`],
+/// which accepts chunks in any order and discards transfers that are still
+/// incomplete after a timeout.
+///
+/// Content larger than the configured maximum
+/// (`behavior.clipboard_max_bytes`, [`DEFAULT_MAX_CLIPBOARD_BYTES`] by default)
+/// is neither sent nor accepted.
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::core::events::Event;
+
+/// Largest clipboard payload sent or accepted by default, in bytes.
+pub const DEFAULT_MAX_CLIPBOARD_BYTES: usize = 1024 * 1024;
+
+/// Largest amount of text in a single event, in bytes.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// How long a chunked transfer may stay incomplete before it is discarded.
+pub const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Splits `text` into pieces of at most `chunk_size` bytes on character boundaries.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::clipboard_transfer::split_text;
+///
+/// assert_eq!(split_text("abcde", 2), vec!["ab", "cd", "e"]);
+/// // "é" is two bytes and is never cut in half
+/// assert_eq!(split_text("éé", 3), vec!["é", "é"]);
+/// ```
+pub fn split_text(text: &str, chunk_size: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let mut end = chunk_size.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        // A chunk smaller than one character still takes the whole character
+        if end == 0 {
+            end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+        }
+        let (piece, tail) = rest.split_at(end);
+        pieces.push(piece);
+        rest = tail;
+    }
+    pieces
+}
+
+/// Queue of outgoing clipboard events, sent one at a time.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::clipboard_transfer::ClipboardOutbox;
+/// use multishiva::core::events::Event;
+///
+/// let mut outbox = ClipboardOutbox::new(1024 * 1024);
+/// assert!(outbox.push("host", "x".repeat(100_000)));
+///
+/// // 100 kB of text takes two 64 kB chunks
+/// assert!(matches!(outbox.pop(), Some(Event::ClipboardChunk { seq: 0, total: 2, .. })));
+/// assert!(matches!(outbox.pop(), Some(Event::ClipboardChunk { seq: 1, total: 2, .. })));
+/// assert!(outbox.is_empty());
+/// ```
+#[derive(Debug)]
+pub struct ClipboardOutbox {
+    max_bytes: usize,
+    chunk_size: usize,
+    next_id: u64,
+    pending: VecDeque<Event>,
+}
+
+impl ClipboardOutbox {
+    /// Creates an outbox refusing content above `max_bytes`.
+    pub fn new(max_bytes: usize) -> Self {
+        Self::with_chunk_size(max_bytes, CHUNK_SIZE)
+    }
+
+    /// Creates an outbox splitting content above `chunk_size` bytes.
+    pub fn with_chunk_size(max_bytes: usize, chunk_size: usize) -> Self {
+        Self {
+            max_bytes,
+            chunk_size: chunk_size.max(1),
+            next_id: 0,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Queues `text` copied on `source`, split into chunks if needed.
+    ///
+    /// Returns `false` and queues nothing if `text` exceeds the maximum size.
+    pub fn push(&mut self, source: &str, text: String) -> bool {
+        if text.len() > self.max_bytes {
+            tracing::warn!(
+                "Clipboard content of {} bytes exceeds the {} byte limit, not syncing it",
+                text.len(),
+                self.max_bytes
+            );
+            return false;
+        }
+
+        if text.len() <= self.chunk_size {
+            self.pending.push_back(Event::ClipboardSync {
+                source: source.to_string(),
+                text,
+            });
+            return true;
+        }
+
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        let pieces = split_text(&text, self.chunk_size);
+        let total = pieces.len() as u32;
+        tracing::debug!(
+            "Sending {} bytes of clipboard in {} chunks",
+            text.len(),
+            total
+        );
+        for (seq, data) in pieces.into_iter().enumerate() {
+            self.pending.push_back(Event::ClipboardChunk {
+                source: source.to_string(),
+                id,
+                seq: seq as u32,
+                total,
+                data: data.to_string(),
+            });
+        }
+        true
+    }
+
+    /// Takes the next event to send.
+    pub fn pop(&mut self) -> Option<Event> {
+        self.pending.pop_front()
+    }
+
+    /// Returns `true` if nothing is waiting to be sent.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[derive(Debug)]
+struct PartialTransfer {
+    chunks: Vec<Option<String>>,
+    received: usize,
+    bytes: usize,
+    started: Instant,
+}
+
+/// Rebuilds clipboard text from `ClipboardChunk` events.
+///
+/// Transfers are keyed by source machine and transfer id, so several may be
+/// in flight at once. Time is passed in explicitly, like
+/// [`EdgeCrossingController`](crate::core::edge_crossing::EdgeCrossingController).
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::clipboard_transfer::ClipboardReassembler;
+/// use std::time::Instant;
+///
+/// let mut reassembler = ClipboardReassembler::new(1024);
+/// let now = Instant::now();
+///
+/// assert_eq!(reassembler.push("host", 7, 1, 2, "world".to_string(), now), None);
+/// let text = reassembler.push("host", 7, 0, 2, "hello ".to_string(), now);
+/// assert_eq!(text.as_deref(), Some("hello world"));
+/// ```
+#[derive(Debug)]
+pub struct ClipboardReassembler {
+    max_bytes: usize,
+    timeout: Duration,
+    transfers: HashMap<(String, u64), PartialTransfer>,
+}
+
+impl ClipboardReassembler {
+    /// Creates a reassembler accepting up to `max_bytes` per transfer.
+    pub fn new(max_bytes: usize) -> Self {
+        Self::with_timeout(max_bytes, REASSEMBLY_TIMEOUT)
+    }
+
+    /// Creates a reassembler discarding transfers incomplete after `timeout`.
+    pub fn with_timeout(max_bytes: usize, timeout: Duration) -> Self {
+        Self {
+            max_bytes,
+            timeout,
+            transfers: HashMap::new(),
+        }
+    }
+
+    /// Adds chunk `seq` of `total` of transfer `id` from `source`.
+    ///
+    /// Returns the full text once every chunk arrived. Duplicate chunks are
+    /// ignored, and a transfer growing beyond the maximum size is dropped.
+    /// Expired transfers are discarded first.
+    pub fn push(
+        &mut self,
+        source: &str,
+        id: u64,
+        seq: u32,
+        total: u32,
+        data: String,
+        now: Instant,
+    ) -> Option<String> {
+        self.expire(now);
+
+        let key = (source.to_string(), id);
+        if total == 0 || seq >= total || total as usize > self.max_bytes.max(1) {
+            tracing::warn!(
+                "Ignoring clipboard chunk {}/{} of transfer {} from '{}'",
+                seq,
+                total,
+                id,
+                source
+            );
+            return None;
+        }
+
+        let transfer = self
+            .transfers
+            .entry(key.clone())
+            .or_insert_with(|| PartialTransfer {
+                chunks: vec![None; total as usize],
+                received: 0,
+                bytes: 0,
+                started: now,
+            });
+        let Some(slot) = transfer.chunks.get_mut(seq as usize) else {
+            tracing::warn!("Clipboard transfer {} from '{}' changed size", id, source);
+            self.transfers.remove(&key);
+            return None;
+        };
+        if slot.is_some() {
+            return None;
+        }
+
+        transfer.bytes += data.len();
+        if transfer.bytes > self.max_bytes {
+            tracing::warn!(
+                "Clipboard transfer {} from '{}' exceeds the {} byte limit, dropping it",
+                id,
+                source,
+                self.max_bytes
+            );
+            self.transfers.remove(&key);
+            return None;
+        }
+        *slot = Some(data);
+        transfer.received += 1;
+        if transfer.received < transfer.chunks.len() {
+            return None;
+        }
+
+        let transfer = self.transfers.remove(&key)?;
+        Some(transfer.chunks.into_iter().flatten().collect())
+    }
+
+    /// Discards transfers started more than the timeout before `now`.
+    ///
+    /// Returns the number of discarded transfers.
+    pub fn expire(&mut self, now: Instant) -> usize {
+        let before = self.transfers.len();
+        let timeout = self.timeout;
+        self.transfers.retain(|(source, id), transfer| {
+            let alive = now.saturating_duration_since(transfer.started) < timeout;
+            if !alive {
+                tracing::warn!(
+                    "Discarding incomplete clipboard transfer {} from '{}' ({}/{} chunks)",
+                    id,
+                    source,
+                    transfer.received,
+                    transfer.chunks.len()
+                );
+            }
+            alive
+        });
+        before - self.transfers.len()
+    }
+
+    /// Returns the largest transfer accepted, in bytes.
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Returns the number of transfers waiting for chunks.
+    pub fn pending(&self) -> usize {
+        self.transfers.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(outbox: &mut ClipboardOutbox) -> Vec<(u64, u32, u32, String)> {
+        std::iter::from_fn(|| outbox.pop())
+            .map(|event| match event {
+                Event::ClipboardChunk {
+                    id,
+                    seq,
+                    total,
+                    data,
+                    ..
+                } => (id, seq, total, data),
+                other => panic!("expected a chunk, got {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_small_content_is_a_single_sync() {
+        let mut outbox = ClipboardOutbox::with_chunk_size(100, 10);
+        assert!(outbox.push("host", "short".to_string()));
+        assert_eq!(
+            outbox.pop(),
+            Some(Event::ClipboardSync {
+                source: "host".to_string(),
+                text: "short".to_string(),
+            })
+        );
+        assert!(outbox.is_empty());
+    }
+
+    #[test]
+    fn test_oversized_content_is_rejected() {
+        let mut outbox = ClipboardOutbox::with_chunk_size(100, 10);
+        assert!(!outbox.push("host", "x".repeat(101)));
+        assert!(outbox.is_empty());
+    }
+
+    #[test]
+    fn test_chunks_reassemble_out_of_order() {
+        let text = "The quick brown fox jumps over the lazy dog — två gånger".to_string();
+        let mut outbox = ClipboardOutbox::with_chunk_size(1024, 8);
+        assert!(outbox.push("agent1", text.clone()));
+
+        let mut chunks = chunks(&mut outbox);
+        assert!(chunks.len() > 2);
+        chunks.reverse();
+        chunks.swap(0, 1);
+
+        let mut reassembler = ClipboardReassembler::new(1024);
+        let now = Instant::now();
+        let last = chunks.pop().unwrap();
+        for (id, seq, total, data) in chunks {
+            assert_eq!(reassembler.push("agent1", id, seq, total, data, now), None);
+        }
+        let (id, seq, total, data) = last;
+        assert_eq!(
+            reassembler.push("agent1", id, seq, total, data, now),
+            Some(text)
+        );
+        assert_eq!(reassembler.pending(), 0);
+    }
+
+    #[test]
+    fn test_missing_chunk_expires() {
+        let mut outbox = ClipboardOutbox::with_chunk_size(1024, 4);
+        assert!(outbox.push("host", "0123456789".to_string()));
+        let mut chunks = chunks(&mut outbox);
+        chunks.remove(1);
+
+        let timeout = Duration::from_secs(5);
+        let mut reassembler = ClipboardReassembler::with_timeout(1024, timeout);
+        let start = Instant::now();
+        for (id, seq, total, data) in chunks {
+            assert_eq!(reassembler.push("host", id, seq, total, data, start), None);
+        }
+        assert_eq!(reassembler.pending(), 1);
+
+        assert_eq!(reassembler.expire(start + Duration::from_secs(4)), 0);
+        assert_eq!(reassembler.expire(start + timeout), 1);
+        assert_eq!(reassembler.pending(), 0);
+
+        // The late chunk starts a new transfer that never completes on its own
+        assert_eq!(
+            reassembler.push("host", 0, 1, 3, "4567".to_string(), start + timeout),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reassembly_enforces_size_limit_and_ignores_duplicates() {
+        let now = Instant::now();
+        let mut reassembler = ClipboardReassembler::new(8);
+        assert_eq!(reassembler.push("host", 1, 0, 3, "abcd".into(), now), None);
+        assert_eq!(reassembler.push("host", 1, 0, 3, "abcd".into(), now), None);
+        assert_eq!(reassembler.push("host", 1, 1, 3, "efgh".into(), now), None);
+        // The third chunk would go past 8 bytes
+        assert_eq!(reassembler.push("host", 1, 2, 3, "ijkl".into(), now), None);
+        assert_eq!(reassembler.pending(), 0);
+
+        // Chunks with an impossible sequence number are ignored
+        assert_eq!(reassembler.push("host", 2, 3, 3, "x".into(), now), None);
+        assert_eq!(reassembler.pending(), 0);
+    }
+}
@@ -2,6 +2,14 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::core::clipboard::ClipboardDirection;
+use crate::core::keyring::{KeyringManager, KEYRING_PLACEHOLDER};
+use crate::core::network::{AgentAllowlist, BindAddress};
+use crate::core::topology::{Edge, EdgeLink, EdgeTrigger, Position};
 
 /// Current configuration version for migration compatibility.
 ///
@@ -10,6 +18,10 @@ use std::path::{Path, PathBuf};
 /// format changes in a backwards-incompatible way, this version should be incremented.
 pub const CONFIG_VERSION: u32 = 1;
 
+/// How long [`Config::watch`] waits for a burst of file events to settle
+/// before reloading, as editors often write a file in several steps.
+pub const RELOAD_DEBOUNCE: Duration = Duration::from_millis(200);
+
 /// Main configuration structure for the multishiva application.
 ///
 /// This structure holds all configuration settings for both host and agent modes,
@@ -47,23 +59,83 @@ pub struct Config {
     /// Host address to connect to (required in agent mode, unused in host mode).
     pub host_address: Option<String>,
 
+    /// Machine name of the host (agent mode): the host's fingerprint and its
+    /// keyring PSK are stored under it, or under `host_address` if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_name: Option<String>,
+
+    /// Address the host listens on: an IP address or `iface:NAME` for the
+    /// addresses of a network interface (host mode, every address if unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind_address: Option<String>,
+
+    /// Agents the host lets in, by machine name, IP address or CIDR network
+    /// (host mode, every agent knowing the PSK if unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_agents: Option<Vec<String>>,
+
     /// TLS/encryption configuration including pre-shared key.
     pub tls: TlsConfig,
 
     /// Map of edge names to connected agent names for defining screen edges.
-    pub edges: HashMap<String, String>,
+    ///
+    /// An edge is either the neighbor's name or an [`EdgeLink`] map with an
+    /// entry offset, a scale or wrapping. Edge names are lowercased when read,
+    /// so `Right` and `right` are the same edge.
+    #[serde(deserialize_with = "deserialize_edges")]
+    pub edges: HashMap<String, EdgeLink>,
+
+    /// Optional grid positions of machines, keyed by machine name.
+    ///
+    /// Allows describing layouts where some machines are not direct neighbors of
+    /// this one (e.g. the diagonal machine of a 2x2 grid). Adjacent positions are
+    /// connected automatically when building the topology.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub layout: HashMap<String, Position>,
 
     /// Optional hotkey configuration for focus return and kill switch.
     pub hotkeys: Option<Hotkeys>,
 
     /// Optional behavioral settings like edge thresholds and timing parameters.
     pub behavior: Option<Behavior>,
+
+    /// Allowed flow of clipboard content between host and agents.
+    #[serde(default)]
+    pub clipboard_direction: ClipboardDirection,
 }
 
 fn default_version() -> u32 {
     CONFIG_VERSION
 }
 
+/// Reads `edges`, lowercasing the edge names.
+fn deserialize_edges<'de, D>(
+    deserializer: D,
+) -> std::result::Result<HashMap<String, EdgeLink>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let raw = HashMap::<String, EdgeLink>::deserialize(deserializer)?;
+    let mut edges = HashMap::with_capacity(raw.len());
+    for (edge, link) in raw {
+        let normalized = edge.to_lowercase();
+        if edges.insert(normalized.clone(), link).is_some() {
+            return Err(serde::de::Error::custom(format!(
+                "edges.{} is given more than once",
+                normalized
+            )));
+        }
+    }
+    Ok(edges)
+}
+
+/// The `version` field of a configuration, read before the rest of it.
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default = "default_version")]
+    version: u32,
+}
+
 /// Operating mode for a multishiva instance.
 ///
 /// Determines whether this instance acts as a host (server) or agent (client).
@@ -101,13 +173,21 @@ pub enum ConfigMode {
 ///
 /// let tls = TlsConfig {
 ///     psk: "my-secret-key".to_string(),
+///     psk_file: None,
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TlsConfig {
     /// Pre-shared key (PSK) for encrypted communication.
     /// Must be non-empty and identical across all communicating instances.
+    /// Not written back when it was read from `psk_file`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub psk: String,
+
+    /// File holding the PSK, read by [`Config::from_file`] in place of `psk`
+    /// so the key stays out of the configuration file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub psk_file: Option<PathBuf>,
 }
 
 /// Hotkey configuration for keyboard shortcuts.
@@ -150,19 +230,84 @@ pub struct Hotkeys {
 ///     edge_threshold_px: Some(5),
 ///     friction_ms: Some(100),
 ///     reconnect_delay_ms: Some(5000),
+///     ..Default::default()
 /// };
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Behavior {
     /// Distance in pixels from the screen edge to trigger transition.
     pub edge_threshold_px: Option<u32>,
 
+    /// Part of the edges that transfers focus: the full edge (default) or a
+    /// corner hotspot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge_trigger: Option<EdgeTrigger>,
+
     /// Delay in milliseconds before cursor crosses to another screen.
     /// Helps prevent accidental transitions.
     pub friction_ms: Option<u64>,
 
     /// Delay in milliseconds between reconnection attempts.
     pub reconnect_delay_ms: Option<u64>,
+
+    /// How long an agent waits for a host to show up over mDNS, in milliseconds.
+    pub discovery_timeout_ms: Option<u64>,
+
+    /// Interval in milliseconds between checks of the discovered peers.
+    pub discovery_poll_interval_ms: Option<u64>,
+
+    /// Largest clipboard content sent or accepted, in bytes (1 MB by default).
+    pub clipboard_max_bytes: Option<usize>,
+
+    /// Whether focus may cross an edge while a mouse button is held (off by
+    /// default, so a drag ending at the edge of the screen stays local).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_while_dragging: Option<bool>,
+
+    /// Silence in milliseconds after which the host drops an agent and takes
+    /// back the focus it held (15 seconds by default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_timeout_ms: Option<u64>,
+
+    /// Whether several edges may lead to the same machine (off by default,
+    /// as it usually is a typo that makes focus bounce between two edges).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_duplicate_edges: Option<bool>,
+
+    /// Send each event at once with TCP_NODELAY (default `true`); `false`
+    /// lets the system batch small frames, to measure the difference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_latency: Option<bool>,
+
+    /// Time without input in milliseconds after which the host takes focus
+    /// back from the agent holding it (never if unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_release_after_ms: Option<u64>,
+
+    /// Recent clipboard changes kept for `multishiva clipboard restore` (10
+    /// by default, 0 keeps none).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clipboard_history: Option<usize>,
+
+    /// Connections one address may open at once before the host drops the
+    /// next ones, to resist handshake flooding (10 by default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_burst: Option<u32>,
+
+    /// Connections a minute one address may open once its burst is used (60
+    /// by default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connections_per_minute: Option<u32>,
+
+    /// Events a second the host sends to one agent before dropping mouse
+    /// moves, against input looping between machines (2000 by default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_events_per_second: Option<u32>,
+
+    /// Days a host fingerprint stays trusted before the agent renews it on
+    /// the next matching connection (90 by default, 0 for never).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint_expiry_days: Option<u32>,
 }
 
 impl Default for Config {
@@ -173,10 +318,18 @@ impl Default for Config {
             mode: ConfigMode::Host,
             port: 53421,
             host_address: None,
-            tls: TlsConfig { psk: String::new() },
+            host_name: None,
+            bind_address: None,
+            allowed_agents: None,
+            tls: TlsConfig {
+                psk: String::new(),
+                psk_file: None,
+            },
             edges: HashMap::new(),
+            layout: HashMap::new(),
             hotkeys: None,
             behavior: None,
+            clipboard_direction: ClipboardDirection::default(),
         }
     }
 }
@@ -186,7 +339,12 @@ impl Config {
     ///
     /// Reads and parses a configuration file from the specified path. If the
     /// configuration version is older than the current version, it will be
-    /// automatically migrated to the latest schema.
+    /// automatically migrated to the latest schema; version 0 files are read
+    /// with [`Config::from_file_v0`].
+    ///
+    /// When `tls.psk_file` is set, the PSK is read from that file, trimmed, and
+    /// replaces `tls.psk`. A relative `psk_file` is relative to the directory
+    /// of the configuration file.
     ///
     /// # Arguments
     ///
@@ -198,6 +356,7 @@ impl Config {
     /// - The file cannot be read
     /// - The file content is not valid YAML
     /// - The YAML structure doesn't match the Config schema
+    /// - `tls.psk_file` cannot be read or is empty
     /// - Migration fails
     ///
     /// # Examples
@@ -211,8 +370,32 @@ impl Config {
     pub fn from_file(path: &str) -> Result<Self> {
         let content = std::fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {}", path))?;
-        let mut config: Config = serde_yaml::from_str(&content)
+        let probe: VersionProbe = serde_yaml::from_str(&content)
             .with_context(|| format!("Failed to parse config file: {}", path))?;
+        let mut config = if probe.version == 0 {
+            Self::from_file_v0(&content)
+        } else {
+            serde_yaml::from_str(&content).map_err(Into::into)
+        }
+        .with_context(|| format!("Failed to parse config file: {}", path))?;
+
+        if let Some(psk_file) = config
+            .tls
+            .psk_file
+            .as_ref()
+            .filter(|p| !p.as_os_str().is_empty())
+        {
+            let psk_path = Path::new(path)
+                .parent()
+                .map_or_else(|| psk_file.clone(), |dir| dir.join(psk_file));
+            let psk = std::fs::read_to_string(&psk_path)
+                .with_context(|| format!("Failed to read tls.psk_file: {:?}", psk_path))?;
+            let psk = psk.trim();
+            if psk.is_empty() {
+                anyhow::bail!("tls.psk_file {:?} is empty", psk_path);
+            }
+            config.tls.psk = psk.to_string();
+        }
 
         // Migrate if needed
         if config.version < CONFIG_VERSION {
@@ -227,7 +410,7 @@ impl Config {
     /// Serializes the configuration to YAML format and writes it to the specified
     /// path. If the file already exists, it will be backed up with a `.backup`
     /// extension before being overwritten. Parent directories are created automatically
-    /// if they don't exist.
+    /// if they don't exist. A PSK read from `tls.psk_file` is not written.
     ///
     /// # Arguments
     ///
@@ -263,8 +446,14 @@ impl Config {
             Self::backup_config(path)?;
         }
 
+        // A PSK read from psk_file stays in that file
+        let mut config = self.clone();
+        if config.tls.psk_file.is_some() {
+            config.tls.psk.clear();
+        }
+
         // Serialize config
-        let content = serde_yaml::to_string(self).context("Failed to serialize config")?;
+        let content = serde_yaml::to_string(&config).context("Failed to serialize config")?;
 
         // Write to file
         std::fs::write(path, content)
@@ -354,6 +543,10 @@ impl Config {
     /// - `tls.psk` is empty
     /// - `port` is 0
     /// - In agent mode: `host_address` is None
+    /// - An edge name is not `left`, `right`, `top` or `bottom`
+    /// - An edge has an empty target or points at `self_name`
+    /// - Two edges lead to the same machine, unless
+    ///   `behavior.allow_duplicate_edges` is set
     ///
     /// # Examples
     ///
@@ -376,6 +569,86 @@ impl Config {
         if self.port == 0 {
             anyhow::bail!("port cannot be 0");
         }
+        if self
+            .behavior
+            .as_ref()
+            .is_some_and(|b| b.agent_timeout_ms == Some(0))
+        {
+            anyhow::bail!("behavior.agent_timeout_ms cannot be 0");
+        }
+        if self
+            .behavior
+            .as_ref()
+            .is_some_and(|b| b.auto_release_after_ms == Some(0))
+        {
+            anyhow::bail!("behavior.auto_release_after_ms cannot be 0, leave it out to disable it");
+        }
+        if let Some(behavior) = &self.behavior {
+            if behavior.connection_burst == Some(0) {
+                anyhow::bail!("behavior.connection_burst cannot be 0, no agent could connect");
+            }
+            if behavior.connections_per_minute == Some(0) {
+                anyhow::bail!("behavior.connections_per_minute cannot be 0");
+            }
+            if behavior.max_events_per_second == Some(0) {
+                anyhow::bail!(
+                    "behavior.max_events_per_second cannot be 0, no mouse move would be sent"
+                );
+            }
+        }
+        if let Some(bind_address) = &self.bind_address {
+            bind_address
+                .parse::<BindAddress>()
+                .context("bind_address must be an IP address or iface:NAME")?;
+        }
+        if let Some(allowed_agents) = &self.allowed_agents {
+            AgentAllowlist::new(allowed_agents).context("Invalid allowed_agents")?;
+        }
+        let mut edges: Vec<(&String, &EdgeLink)> = self.edges.iter().collect();
+        edges.sort_by_key(|(edge, _)| *edge);
+        let mut targets: HashMap<&str, &str> = HashMap::new();
+        let allow_duplicates = self
+            .behavior
+            .as_ref()
+            .and_then(|b| b.allow_duplicate_edges)
+            .unwrap_or(false);
+        for (edge, link) in edges {
+            if edge.parse::<Edge>().is_err() {
+                anyhow::bail!(
+                    "edges.{} is not an edge, expected left, right, top or bottom",
+                    edge
+                );
+            }
+            if !(link.scale.is_finite() && link.scale > 0.0) {
+                anyhow::bail!("edges.{}.scale must be a positive number", edge);
+            }
+            let Some(target) = link.target.as_deref() else {
+                if !link.wrap {
+                    anyhow::bail!("edges.{} needs a target or wrap: true", edge);
+                }
+                continue;
+            };
+            if target.trim().is_empty() {
+                anyhow::bail!("edges.{} has an empty target", edge);
+            }
+            if target == self.self_name {
+                anyhow::bail!(
+                    "edges.{} points at this machine ('{}'), use wrap: true to wrap around",
+                    edge,
+                    target
+                );
+            }
+            if let Some(first) = targets.insert(target, edge) {
+                if !allow_duplicates {
+                    anyhow::bail!(
+                        "edges.{} and edges.{} both lead to '{}' (set behavior.allow_duplicate_edges to allow it)",
+                        first,
+                        edge,
+                        target
+                    );
+                }
+            }
+        }
 
         // Validate mode-specific requirements
         match self.mode {
@@ -390,6 +663,192 @@ impl Config {
         Ok(())
     }
 
+    /// Returns the neighbor of each edge, leaving out edges that only wrap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::config::Config;
+    /// use multishiva::core::topology::EdgeLink;
+    ///
+    /// let mut config = Config::default();
+    /// config.edges.insert("right".to_string(), "laptop".into());
+    /// config.edges.insert("top".to_string(), EdgeLink::wrapping());
+    ///
+    /// let targets = config.edge_targets();
+    /// assert_eq!(targets.len(), 1);
+    /// assert_eq!(targets["right"], "laptop");
+    /// ```
+    pub fn edge_targets(&self) -> HashMap<String, String> {
+        self.edges
+            .iter()
+            .filter_map(|(edge, link)| Some((edge.clone(), link.target.clone()?)))
+            .collect()
+    }
+
+    /// Replaces secrets kept out of the file with their real values.
+    ///
+    /// A PSK moved to the system keyring (see [`KeyringManager::migrate_from_config`])
+    /// is saved as [`KEYRING_PLACEHOLDER`]; it is fetched back through
+    /// [`KeyringManager::get_psk_or_env`]. Returns `true` if the PSK was resolved,
+    /// `false` if the configuration holds a literal PSK, which is left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the PSK is a placeholder and neither the keyring nor
+    /// the `MULTISHIVA_PSK` environment variable provides it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::config::Config;
+    /// use multishiva::core::keyring::KeyringManager;
+    ///
+    /// let mut config = Config::default();
+    /// config.tls.psk = "literal-psk".to_string();
+    ///
+    /// assert!(!config.resolve_secrets(&KeyringManager::new()).unwrap());
+    /// assert_eq!(config.tls.psk, "literal-psk");
+    /// ```
+    pub fn resolve_secrets(&mut self, keyring: &KeyringManager) -> Result<bool> {
+        if self.tls.psk != KEYRING_PLACEHOLDER {
+            return Ok(false);
+        }
+        self.tls.psk = keyring
+            .get_psk_or_env()
+            .context("The configuration's PSK is stored in the keyring but could not be read")?;
+        Ok(true)
+    }
+
+    /// Watches the configuration file at `path` and sends it on `tx` each
+    /// time it changes.
+    ///
+    /// The parent directory is watched, so files replaced by a rename (as many
+    /// editors save) are picked up too. Each reloaded configuration has its
+    /// secrets resolved (see [`Config::resolve_secrets`]) and is validated; an
+    /// invalid file is logged and skipped. The returned task runs until `tx`
+    /// is closed. Must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` has no file name or the file watcher cannot
+    /// be started.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::config::Config;
+    /// use std::path::PathBuf;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+    /// let _watch = Config::watch(PathBuf::from("multishiva.yml"), tx)?;
+    /// while let Some(config) = rx.recv().await {
+    ///     println!("Edges are now {:?}", config.edges);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// # });
+    /// ```
+    pub fn watch(path: PathBuf, tx: mpsc::Sender<Config>) -> Result<JoinHandle<()>> {
+        use notify::{RecursiveMode, Watcher};
+
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_os_string())
+            .with_context(|| format!("Cannot watch {:?}: not a file path", path))?;
+        let (changed_tx, mut changed_rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            let ours = event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == Some(file_name.as_os_str()));
+            if ours && (event.kind.is_create() || event.kind.is_modify()) {
+                let _ = changed_tx.send(());
+            }
+        })
+        .context("Failed to create the config file watcher")?;
+
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch config directory: {:?}", dir))?;
+
+        Ok(tokio::spawn(async move {
+            // Dropping the watcher stops the notifications
+            let _watcher = watcher;
+            while changed_rx.recv().await.is_some() {
+                tokio::time::sleep(RELOAD_DEBOUNCE).await;
+                while changed_rx.try_recv().is_ok() {}
+
+                match Self::reload(&path) {
+                    Ok(config) => {
+                        if tx.send(config).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("Ignoring configuration change in {:?}: {:#}", path, e)
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Loads a changed configuration, as [`Config::watch`] and SIGHUP do.
+    ///
+    /// Secrets are resolved (see [`Config::resolve_secrets`]) and the result
+    /// is validated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be loaded, its PSK cannot be
+    /// resolved or it is invalid.
+    pub fn reload(path: &Path) -> Result<Self> {
+        let mut config = Self::from_file(&path.to_string_lossy())?;
+        config.resolve_secrets(&KeyringManager::new())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Returns the settings that differ in `new` but only apply after a restart.
+    ///
+    /// The port, the PSK and the agent timeout are used when the connections
+    /// are set up, so a running daemon keeps its current values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::config::Config;
+    ///
+    /// let current = Config::default();
+    /// let mut new = current.clone();
+    /// new.edges.insert("right".to_string(), "laptop".into());
+    /// assert!(current.restart_required(&new).is_empty());
+    ///
+    /// new.port = 6000;
+    /// assert_eq!(current.restart_required(&new), vec!["port"]);
+    /// ```
+    pub fn restart_required(&self, new: &Config) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if self.port != new.port {
+            fields.push("port");
+        }
+        if self.tls.psk != new.tls.psk {
+            fields.push("tls.psk");
+        }
+        let agent_timeout = |config: &Config| config.behavior.as_ref()?.agent_timeout_ms;
+        if agent_timeout(self) != agent_timeout(new) {
+            fields.push("behavior.agent_timeout_ms");
+        }
+        fields
+    }
+
     /// Backup config file before overwriting
     fn backup_config(path: &Path) -> Result<()> {
         let backup_path = path.with_extension("yml.backup");
@@ -403,7 +862,72 @@ impl Config {
         Ok(())
     }
 
+    /// Parses a version 0 configuration and converts it to the current schema.
+    ///
+    /// Version 0 listed edges as `"edge:machine"` strings instead of a map:
+    ///
+    /// ```yaml
+    /// edges: ["right:agent1", "left:agent2"]
+    /// ```
+    ///
+    /// Edges already written as a map are kept as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the content is not valid YAML, an edge is not an
+    /// `"edge:machine"` string, an edge is listed twice, or the converted
+    /// configuration doesn't match the Config schema.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::config::{Config, CONFIG_VERSION};
+    /// use multishiva::core::topology::EdgeLink;
+    ///
+    /// let config = Config::from_file_v0(
+    ///     "version: 0\nself_name: desk\nmode: host\nport: 53421\n\
+    ///      tls:\n  psk: key\nedges: [\"right:laptop\"]\n",
+    /// )?;
+    /// assert_eq!(config.version, CONFIG_VERSION);
+    /// assert_eq!(config.edges["right"], EdgeLink::to("laptop"));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn from_file_v0(content: &str) -> Result<Config> {
+        let mut value: serde_yaml::Value =
+            serde_yaml::from_str(content).context("Invalid version 0 configuration")?;
+
+        if let Some(edges) = value.get_mut("edges") {
+            if let Some(list) = edges.as_sequence() {
+                let mut map = serde_yaml::Mapping::new();
+                for entry in list {
+                    let (edge, machine) = entry
+                        .as_str()
+                        .and_then(|entry| entry.split_once(':'))
+                        .with_context(|| {
+                            format!(
+                                "Invalid version 0 edge {:?}, expected \"edge:machine\"",
+                                entry
+                            )
+                        })?;
+                    let edge = serde_yaml::Value::from(edge.trim());
+                    if map.contains_key(&edge) {
+                        anyhow::bail!("Edge {:?} is listed twice", edge.as_str().unwrap_or(""));
+                    }
+                    map.insert(edge, serde_yaml::Value::from(machine.trim()));
+                }
+                *edges = serde_yaml::Value::Mapping(map);
+            }
+        }
+
+        let config: Config =
+            serde_yaml::from_value(value).context("Invalid version 0 configuration")?;
+        Self::migrate(config)
+    }
+
     /// Migrate config from older version
+    ///
+    /// Schema changes are converted while parsing, by [`Config::from_file_v0`],
+    /// so only the version is updated here.
     fn migrate(mut config: Config) -> Result<Self> {
         tracing::info!(
             "Migrating config from version {} to {}",
@@ -411,8 +935,6 @@ impl Config {
             CONFIG_VERSION
         );
 
-        // For now, just update the version
-        // In the future, add migration logic here
         config.version = CONFIG_VERSION;
 
         Ok(config)
@@ -522,6 +1044,7 @@ mod tests {
             self_name: String::new(),
             tls: TlsConfig {
                 psk: "test-psk".to_string(),
+                psk_file: None,
             },
             ..Default::default()
         };
@@ -532,7 +1055,10 @@ mod tests {
     fn test_config_validate_empty_psk() {
         let config = Config {
             self_name: "test".to_string(),
-            tls: TlsConfig { psk: String::new() },
+            tls: TlsConfig {
+                psk: String::new(),
+                psk_file: None,
+            },
             ..Default::default()
         };
         assert!(config.validate().is_err());
@@ -544,6 +1070,7 @@ mod tests {
             self_name: "test".to_string(),
             tls: TlsConfig {
                 psk: "test-psk".to_string(),
+                psk_file: None,
             },
             port: 0,
             ..Default::default()
@@ -551,6 +1078,72 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_config_validate_bind_address() {
+        let mut config = Config {
+            self_name: "test".to_string(),
+            tls: TlsConfig {
+                psk: "test-psk".to_string(),
+                psk_file: None,
+            },
+            ..Default::default()
+        };
+        for valid in ["127.0.0.1", "::1", "iface:eth0"] {
+            config.bind_address = Some(valid.to_string());
+            assert!(config.validate().is_ok(), "{}", valid);
+        }
+        for invalid in ["localhost", "127.0.0.1:53421", "iface:"] {
+            config.bind_address = Some(invalid.to_string());
+            assert!(config.validate().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_config_validate_allowed_agents() {
+        let mut config = Config {
+            self_name: "host".to_string(),
+            tls: TlsConfig {
+                psk: "test-psk".to_string(),
+                psk_file: None,
+            },
+            allowed_agents: Some(vec!["laptop".to_string(), "192.168.1.0/24".to_string()]),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.allowed_agents = Some(vec!["192.168.1.0/40".to_string()]);
+        assert!(config.validate().is_err());
+        config.allowed_agents = Some(vec![String::new()]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_validate_connection_rate_limit() {
+        let mut config = Config {
+            self_name: "host".to_string(),
+            tls: TlsConfig {
+                psk: "test-psk".to_string(),
+                psk_file: None,
+            },
+            behavior: Some(Behavior {
+                connection_burst: Some(5),
+                connections_per_minute: Some(12),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.behavior.as_mut().unwrap().connection_burst = Some(0);
+        assert!(config.validate().is_err());
+        config.behavior.as_mut().unwrap().connection_burst = None;
+        config.behavior.as_mut().unwrap().connections_per_minute = Some(0);
+        assert!(config.validate().is_err());
+        config.behavior.as_mut().unwrap().connections_per_minute = None;
+        config.behavior.as_mut().unwrap().max_events_per_second = Some(0);
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_config_validate_agent_without_host() {
         // Agent mode without host_address should now be valid (mDNS auto-discovery)
@@ -558,6 +1151,7 @@ mod tests {
             self_name: "test".to_string(),
             tls: TlsConfig {
                 psk: "test-psk".to_string(),
+                psk_file: None,
             },
             mode: ConfigMode::Agent,
             host_address: None,
@@ -572,12 +1166,174 @@ mod tests {
             self_name: "test".to_string(),
             tls: TlsConfig {
                 psk: "test-psk".to_string(),
+                psk_file: None,
             },
             ..Default::default()
         };
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_config_validate_edge_links() {
+        let mut config = Config {
+            self_name: "test".to_string(),
+            tls: TlsConfig {
+                psk: "test-psk".to_string(),
+                psk_file: None,
+            },
+            ..Default::default()
+        };
+        config.edges.insert("top".to_string(), EdgeLink::wrapping());
+        assert!(config.validate().is_ok());
+
+        let nowhere = EdgeLink {
+            wrap: false,
+            ..EdgeLink::wrapping()
+        };
+        config.edges.insert("top".to_string(), nowhere);
+        assert!(config.validate().is_err());
+
+        let flipped = EdgeLink {
+            scale: -1.0,
+            ..EdgeLink::to("laptop")
+        };
+        config.edges.insert("top".to_string(), flipped);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_validate_rejects_bad_edges() {
+        let base = "self_name: host\nmode: host\nport: 53421\ntls:\n  psk: psk\n";
+        let cases = [
+            ("edges:\n  diagonal: laptop\n", "edges.diagonal"),
+            ("edges:\n  right: \"\"\n", "edges.right has an empty target"),
+            (
+                "edges:\n  left: host\n",
+                "edges.left points at this machine",
+            ),
+            (
+                "edges:\n  left: laptop\n  right: laptop\n",
+                "edges.left and edges.right both lead to 'laptop'",
+            ),
+            (
+                "edges:\n  bottom: tablet\n  Top: { target: tablet, offset_px: 10 }\n",
+                "edges.bottom and edges.top both lead to 'tablet'",
+            ),
+        ];
+        for (edges, expected) in cases {
+            let config: Config = serde_yaml::from_str(&format!("{}{}", base, edges)).unwrap();
+            let error = config.validate().unwrap_err().to_string();
+            assert!(error.contains(expected), "{:?}: {}", edges, error);
+        }
+
+        // The same edge in two spellings is caught when reading
+        let error = serde_yaml::from_str::<Config>(&format!(
+            "{}edges:\n  right: laptop\n  RIGHT: tablet\n",
+            base
+        ))
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("edges.right is given more than once"));
+    }
+
+    #[test]
+    fn test_config_validate_accepts_complex_edges() {
+        let yaml = r#"
+self_name: host
+mode: host
+port: 53421
+tls:
+  psk: psk
+edges:
+  Right: laptop
+  LEFT: { target: tablet, offset_px: -300, scale: 0.75 }
+  top: { wrap: true }
+  bottom: { target: laptop }
+behavior:
+  allow_duplicate_edges: true
+"#;
+        let mut config: Config = serde_yaml::from_str(yaml).unwrap();
+        let mut edges: Vec<&str> = config.edges.keys().map(String::as_str).collect();
+        edges.sort_unstable();
+        assert_eq!(edges, ["bottom", "left", "right", "top"]);
+        assert!(config.validate().is_ok());
+
+        // Two edges to the laptop need the flag
+        config.behavior = None;
+        assert!(config.validate().is_err());
+        config.edges.remove("bottom");
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_edges_accept_simple_and_full_forms() {
+        let yaml = r#"
+self_name: desk
+mode: host
+port: 53421
+tls:
+  psk: key
+edges:
+  left: laptop
+  right: { target: agent1, offset_px: -300, scale: 0.75 }
+  top: { wrap: true }
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.edges["left"], EdgeLink::to("laptop"));
+        assert_eq!(
+            config.edges["right"],
+            EdgeLink {
+                offset_px: -300,
+                scale: 0.75,
+                ..EdgeLink::to("agent1")
+            }
+        );
+        assert_eq!(config.edges["top"], EdgeLink::wrapping());
+
+        // Plain links are written back in the simple form
+        let saved = serde_yaml::to_string(&config).unwrap();
+        assert!(saved.contains("left: laptop"));
+        let reloaded: Config = serde_yaml::from_str(&saved).unwrap();
+        assert_eq!(reloaded.edges, config.edges);
+
+        let json = serde_json::to_string(&config.edges).unwrap();
+        assert_eq!(
+            serde_json::from_str::<HashMap<String, EdgeLink>>(&json).unwrap(),
+            config.edges
+        );
+
+        assert!(serde_yaml::from_str::<EdgeLink>("{ target: a, skew: 2 }").is_err());
+    }
+
+    #[test]
+    fn test_psk_file_replaces_inline_psk() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("psk.txt"), "  file-psk\n").unwrap();
+        let config_path = temp_dir.path().join("multishiva.yml");
+        std::fs::write(
+            &config_path,
+            "self_name: desk\nmode: host\nport: 53421\ntls:\n  psk: inline-psk\n  psk_file: psk.txt\nedges: {}\n",
+        )
+        .unwrap();
+
+        let mut config = Config::from_file(config_path.to_str().unwrap()).unwrap();
+        assert_eq!(config.tls.psk, "file-psk");
+
+        // Saving keeps the indirection and leaves the key out
+        config.port = 6000;
+        config.save_to_file(&config_path).unwrap();
+        let saved = std::fs::read_to_string(&config_path).unwrap();
+        assert!(saved.contains("psk_file: psk.txt"));
+        assert!(!saved.contains("file-psk"));
+        let reloaded = Config::from_file(config_path.to_str().unwrap()).unwrap();
+        assert_eq!(reloaded.tls.psk, "file-psk");
+        assert_eq!(reloaded.port, 6000);
+
+        std::fs::write(temp_dir.path().join("psk.txt"), " \n").unwrap();
+        assert!(Config::from_file(config_path.to_str().unwrap()).is_err());
+    }
+
     #[test]
     fn test_config_save_and_load() {
         let temp_dir = TempDir::new().unwrap();
@@ -587,6 +1343,7 @@ mod tests {
             self_name: "test-machine".to_string(),
             tls: TlsConfig {
                 psk: "test-psk-12345".to_string(),
+                psk_file: None,
             },
             port: 12345,
             ..Default::default()
@@ -612,6 +1369,7 @@ mod tests {
             self_name: "first".to_string(),
             tls: TlsConfig {
                 psk: "psk1".to_string(),
+                psk_file: None,
             },
             ..Default::default()
         };
@@ -675,6 +1433,37 @@ edges: {}
         assert_eq!(config.self_name, "old-machine");
     }
 
+    #[test]
+    fn test_config_v0_edge_list() {
+        let content = r#"
+version: 0
+self_name: "old-machine"
+mode: host
+port: 53421
+tls:
+  psk: "old-psk"
+edges: ["right:agent1", "left: agent2"]
+"#;
+        let config = Config::from_file_v0(content).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.edges.len(), 2);
+        assert_eq!(config.edges["right"], EdgeLink::to("agent1"));
+        assert_eq!(config.edges["left"], EdgeLink::to("agent2"));
+
+        // from_file picks the version 0 parser
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("v0.yml");
+        std::fs::write(&config_path, content).unwrap();
+        let config = Config::from_file(config_path.to_str().unwrap()).unwrap();
+        assert_eq!(config.edges["left"], EdgeLink::to("agent2"));
+
+        let invalid = content.replace("\"left: agent2\"", "\"agent2\"");
+        assert!(Config::from_file_v0(&invalid).is_err());
+        let twice = content.replace("left: agent2", "right:agent2");
+        let error = Config::from_file_v0(&twice).unwrap_err();
+        assert!(error.to_string().contains("listed twice"));
+    }
+
     #[test]
     fn test_config_load_or_default() {
         let temp_dir = TempDir::new().unwrap();
@@ -690,6 +1479,7 @@ edges: {}
             self_name: "loaded".to_string(),
             tls: TlsConfig {
                 psk: "loaded-psk".to_string(),
+                psk_file: None,
             },
             ..Default::default()
         };
@@ -698,4 +1488,127 @@ edges: {}
         let loaded = Config::load_or_default(Some(&config_path)).unwrap();
         assert_eq!(loaded.self_name, "loaded");
     }
+
+    #[test]
+    fn test_resolve_secrets_keeps_literal_psk() {
+        let mut config = Config {
+            tls: TlsConfig {
+                psk: "literal-psk".to_string(),
+                psk_file: None,
+            },
+            ..Default::default()
+        };
+        let keyring = KeyringManager::with_service("multishiva-test-literal".to_string());
+        assert!(!config.resolve_secrets(&keyring).unwrap());
+        assert_eq!(config.tls.psk, "literal-psk");
+    }
+
+    #[test]
+    fn test_resolve_secrets_reads_placeholder_from_keyring() {
+        let keyring = KeyringManager::with_service("multishiva-test-placeholder".to_string());
+        // The system keyring may be unavailable in the test environment
+        let stored = keyring.set_psk("keyring-psk").is_ok();
+
+        let mut config = Config {
+            tls: TlsConfig {
+                psk: KEYRING_PLACEHOLDER.to_string(),
+                psk_file: None,
+            },
+            ..Default::default()
+        };
+        match config.resolve_secrets(&keyring) {
+            Ok(resolved) => {
+                assert!(resolved);
+                assert_ne!(config.tls.psk, KEYRING_PLACEHOLDER);
+                if stored {
+                    assert_eq!(config.tls.psk, "keyring-psk");
+                }
+            }
+            Err(e) => {
+                assert!(!stored);
+                assert!(e.to_string().contains("keyring"));
+                assert_eq!(config.tls.psk, KEYRING_PLACEHOLDER);
+            }
+        }
+        let _ = keyring.delete_psk();
+    }
+
+    #[tokio::test]
+    async fn test_watch_sends_changed_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("multishiva.yml");
+        let mut config = Config {
+            self_name: "host".to_string(),
+            tls: TlsConfig {
+                psk: "watch-psk".to_string(),
+                psk_file: None,
+            },
+            ..Default::default()
+        };
+        config.save_to_file(&path).unwrap();
+
+        let (tx, mut rx) = mpsc::channel(4);
+        let watch = Config::watch(path.clone(), tx).unwrap();
+
+        // Invalid content is skipped, the next valid save gets through
+        std::fs::write(&path, "self_name: [").unwrap();
+        tokio::time::sleep(RELOAD_DEBOUNCE * 2).await;
+        config.edges.insert("right".to_string(), "laptop".into());
+        config.save_to_file(&path).unwrap();
+
+        let reloaded = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("No configuration reloaded")
+            .unwrap();
+        assert_eq!(reloaded.edges.get("right"), Some(&EdgeLink::to("laptop")));
+
+        drop(rx);
+        watch.abort();
+    }
+
+    #[test]
+    fn test_restart_required_for_port_and_psk() {
+        let current = Config {
+            tls: TlsConfig {
+                psk: "old-psk".to_string(),
+                psk_file: None,
+            },
+            ..Default::default()
+        };
+        let mut new = current.clone();
+        new.behavior = Some(Behavior {
+            friction_ms: Some(250),
+            ..Default::default()
+        });
+        assert!(current.restart_required(&new).is_empty());
+
+        new.port += 1;
+        new.tls.psk = "new-psk".to_string();
+        assert_eq!(current.restart_required(&new), vec!["port", "tls.psk"]);
+
+        new.behavior.as_mut().unwrap().agent_timeout_ms = Some(5000);
+        assert_eq!(
+            current.restart_required(&new),
+            vec!["port", "tls.psk", "behavior.agent_timeout_ms"]
+        );
+    }
+
+    #[test]
+    fn test_config_validate_agent_timeout() {
+        let mut config = Config::default();
+        config.tls.psk = "psk".to_string();
+        config.behavior = Some(Behavior {
+            agent_timeout_ms: Some(0),
+            ..Default::default()
+        });
+        assert!(config.validate().is_err());
+
+        config.behavior.as_mut().unwrap().agent_timeout_ms = Some(2000);
+        assert!(config.validate().is_ok());
+
+        config.behavior.as_mut().unwrap().auto_release_after_ms = Some(0);
+        assert!(config.validate().is_err());
+        config.behavior.as_mut().unwrap().auto_release_after_ms = Some(600_000);
+        assert!(config.validate().is_ok());
+    }
 }
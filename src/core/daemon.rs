@@ -0,0 +1,426 @@
+use anyhow::{Context, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Set in the environment of the background process started by [`detach`].
+pub const DETACHED_ENV: &str = "MULTISHIVA_DETACHED";
+
+/// Written on its stderr by the background process once it is running.
+#[cfg(unix)]
+const READY_MARKER: &[u8] = b"\0multishiva-ready\n";
+
+/// A signal the daemon reacts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaemonSignal {
+    /// SIGINT, i.e. Ctrl+C
+    Interrupt,
+    /// SIGTERM, as sent by `kill` or a service manager
+    Terminate,
+    /// SIGHUP
+    Hangup,
+}
+
+/// What the daemon does when it receives a [`DaemonSignal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalAction {
+    /// Stop cleanly, saying goodbye to the peers
+    Shutdown,
+    /// Read the configuration file again, keeping the connections
+    ReloadConfig,
+}
+
+impl DaemonSignal {
+    /// Returns what the daemon does on this signal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::daemon::{DaemonSignal, SignalAction};
+    ///
+    /// assert_eq!(DaemonSignal::Terminate.action(), SignalAction::Shutdown);
+    /// assert_eq!(DaemonSignal::Hangup.action(), SignalAction::ReloadConfig);
+    /// ```
+    pub fn action(self) -> SignalAction {
+        match self {
+            DaemonSignal::Interrupt | DaemonSignal::Terminate => SignalAction::Shutdown,
+            DaemonSignal::Hangup => SignalAction::ReloadConfig,
+        }
+    }
+}
+
+impl fmt::Display for DaemonSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DaemonSignal::Interrupt => "SIGINT",
+            DaemonSignal::Terminate => "SIGTERM",
+            DaemonSignal::Hangup => "SIGHUP",
+        })
+    }
+}
+
+/// Receives the signals of [`DaemonSignal`].
+///
+/// Only Ctrl+C exists outside Unix. Must be created within a Tokio runtime.
+pub struct SignalListener {
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+}
+
+impl SignalListener {
+    /// Starts listening; from now on the signals no longer kill the process.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a signal handler cannot be installed.
+    pub fn new() -> Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            Ok(Self {
+                interrupt: signal(SignalKind::interrupt()).context("Failed to handle SIGINT")?,
+                terminate: signal(SignalKind::terminate()).context("Failed to handle SIGTERM")?,
+                hangup: signal(SignalKind::hangup()).context("Failed to handle SIGHUP")?,
+            })
+        }
+        #[cfg(not(unix))]
+        Ok(Self {})
+    }
+
+    /// Waits for the next signal.
+    pub async fn recv(&mut self) -> DaemonSignal {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = self.interrupt.recv() => DaemonSignal::Interrupt,
+                _ = self.terminate.recv() => DaemonSignal::Terminate,
+                _ = self.hangup.recv() => DaemonSignal::Hangup,
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            if tokio::signal::ctrl_c().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+            DaemonSignal::Interrupt
+        }
+    }
+}
+
+/// A file holding the PID of the running daemon, removed when dropped.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::daemon::PidFile;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("multishiva.pid");
+/// let pid_file = PidFile::create(&path).unwrap();
+/// assert_eq!(PidFile::read(&path), Some(std::process::id()));
+///
+/// // A second daemon would find this one running
+/// assert!(PidFile::create(&path).is_err());
+/// drop(pid_file);
+/// assert!(!path.exists());
+/// ```
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Returns where the daemon writes its PID: `multishiva.pid` in the data directory.
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("multishiva")
+            .join("multishiva.pid")
+    }
+
+    /// Writes the PID of this process to `path`.
+    ///
+    /// The file is created exclusively, so of two daemons starting together
+    /// only one gets it. A PID file left behind by a daemon that is no longer
+    /// running is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file names a running process, or cannot be written.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        let create_new = || {
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)
+        };
+        let mut file = match create_new() {
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                match Self::read(path) {
+                    Some(pid) if is_running(pid) => anyhow::bail!(
+                        "MultiShiva is already running with PID {} (see {:?})",
+                        pid,
+                        path
+                    ),
+                    Some(pid) => {
+                        tracing::warn!("Replacing stale PID file {:?} of PID {}", path, pid)
+                    }
+                    None => tracing::warn!("Replacing invalid PID file {:?}", path),
+                }
+                std::fs::remove_file(path)
+                    .with_context(|| format!("Failed to remove PID file: {:?}", path))?;
+                // A daemon replacing it at the same time makes this fail
+                create_new()
+            }
+            result => result,
+        }
+        .with_context(|| format!("Failed to create PID file: {:?}", path))?;
+        std::io::Write::write_all(&mut file, format!("{}\n", std::process::id()).as_bytes())
+            .with_context(|| format!("Failed to write PID file: {:?}", path))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Reads the PID stored in `path`, `None` if there is no valid one.
+    pub fn read<P: AsRef<Path>>(path: P) -> Option<u32> {
+        std::fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    /// Returns the path of the PID file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // Another daemon may have replaced a file it found stale
+        if Self::read(&self.path) == Some(std::process::id()) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Returns `true` if a process with `pid` exists.
+fn is_running(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        Path::new("/proc").join(pid.to_string()).exists()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+}
+
+/// Returns `true` when started by systemd with `Type=notify`.
+///
+/// The daemon then stays in the foreground and reports its state with
+/// [`notify_systemd`] instead of detaching.
+pub fn under_systemd() -> bool {
+    std::env::var_os("NOTIFY_SOCKET").is_some()
+}
+
+/// Sends `state` (e.g. `READY=1`) to systemd's notification socket.
+///
+/// Returns `false` without doing anything when not started by systemd.
+///
+/// # Errors
+///
+/// Returns an error if the notification cannot be sent.
+pub fn notify_systemd(state: &str) -> Result<bool> {
+    let Some(socket) = std::env::var_os("NOTIFY_SOCKET") else {
+        return Ok(false);
+    };
+    notify_socket(Path::new(&socket), state)?;
+    Ok(true)
+}
+
+/// Sends `state` to the notification socket at `socket`.
+///
+/// A path starting with `@` names a Linux abstract socket.
+#[cfg(unix)]
+fn notify_socket(socket: &Path, state: &str) -> Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    let datagram = UnixDatagram::unbound().context("Failed to create notification socket")?;
+    let name = socket.to_string_lossy();
+    #[cfg(target_os = "linux")]
+    if let Some(abstract_name) = name.strip_prefix('@') {
+        use std::os::linux::net::SocketAddrExt;
+
+        let addr = std::os::unix::net::SocketAddr::from_abstract_name(abstract_name)?;
+        datagram
+            .send_to_addr(state.as_bytes(), &addr)
+            .context("Failed to notify systemd")?;
+        return Ok(());
+    }
+    datagram
+        .send_to(state.as_bytes(), socket)
+        .with_context(|| format!("Failed to notify systemd at {}", name))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn notify_socket(_socket: &Path, _state: &str) -> Result<()> {
+    anyhow::bail!("systemd notifications are only supported on Unix")
+}
+
+/// Tells the terminal waiting in [`detach`] that this background process is
+/// running, then stops writing to it.
+///
+/// Returns `false` without doing anything when not started by [`detach`].
+///
+/// # Errors
+///
+/// Returns an error if stderr cannot be redirected to `/dev/null`.
+pub fn notify_detached_ready() -> Result<bool> {
+    if std::env::var_os(DETACHED_ENV).is_none() {
+        return Ok(false);
+    }
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::fd::AsRawFd;
+
+        // The terminal may be gone already
+        let _ = std::io::stderr().write_all(READY_MARKER);
+        let null = std::fs::OpenOptions::new()
+            .write(true)
+            .open("/dev/null")
+            .context("Failed to open /dev/null")?;
+        nix::unistd::dup2(null.as_raw_fd(), std::io::stderr().as_raw_fd())
+            .context("Failed to detach stderr")?;
+    }
+    Ok(true)
+}
+
+/// Starts this program again in the background, with the same arguments,
+/// and waits until it is running.
+///
+/// The new process has no terminal input or output (the logs still go to
+/// the log files), runs in its own process group so Ctrl+C in the terminal
+/// does not reach it, and finds [`DETACHED_ENV`] set. Until it calls
+/// [`notify_detached_ready`], what it writes on stderr, such as an invalid
+/// configuration or a daemon already running, is shown here. Returns its PID.
+///
+/// # Errors
+///
+/// Returns an error outside Unix, if the process cannot be started, or if it
+/// exits before it is ready.
+pub fn detach() -> Result<u32> {
+    #[cfg(unix)]
+    {
+        use std::io::{Read, Write};
+        use std::os::unix::process::CommandExt;
+        use std::process::{Command, Stdio};
+
+        let exe = std::env::current_exe().context("Failed to find the multishiva executable")?;
+        let (mut ready, ready_writer) =
+            std::io::pipe().context("Failed to create the readiness pipe")?;
+        let mut child = {
+            let mut command = Command::new(exe);
+            command
+                .args(std::env::args_os().skip(1))
+                .env(DETACHED_ENV, "1")
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(ready_writer)
+                .process_group(0);
+            command
+                .spawn()
+                .context("Failed to start the background process")?
+            // Dropping the command closes our end of the pipe
+        };
+
+        // Ends when the process is ready or exits
+        let mut output = Vec::new();
+        ready
+            .read_to_end(&mut output)
+            .context("Failed to wait for the background process")?;
+        if let Some(logged) = output.strip_suffix(READY_MARKER) {
+            let _ = std::io::stderr().write_all(logged);
+            return Ok(child.id());
+        }
+        let _ = std::io::stderr().write_all(&output);
+        let status = child
+            .wait()
+            .context("Failed to wait for the background process")?;
+        anyhow::bail!("The background process failed to start ({})", status)
+    }
+    #[cfg(not(unix))]
+    anyhow::bail!("--daemon is only supported on Unix, run MultiShiva as a service instead")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_actions() {
+        let cases = [
+            (DaemonSignal::Interrupt, SignalAction::Shutdown, "SIGINT"),
+            (DaemonSignal::Terminate, SignalAction::Shutdown, "SIGTERM"),
+            (DaemonSignal::Hangup, SignalAction::ReloadConfig, "SIGHUP"),
+        ];
+        for (signal, action, name) in cases {
+            assert_eq!(signal.action(), action, "{}", name);
+            assert_eq!(signal.to_string(), name);
+        }
+    }
+
+    #[test]
+    fn test_pid_file_replaces_stale_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run").join("multishiva.pid");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        // No process has a PID this large
+        std::fs::write(&path, "4194305\n").unwrap();
+
+        let pid_file = PidFile::create(&path).unwrap();
+        assert_eq!(pid_file.path(), path);
+        assert_eq!(PidFile::read(&path), Some(std::process::id()));
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_pid_file_replaces_invalid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("multishiva.pid");
+        std::fs::write(&path, "garbage").unwrap();
+
+        let _pid_file = PidFile::create(&path).unwrap();
+        assert_eq!(PidFile::read(&path), Some(std::process::id()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_notify_socket_sends_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("notify");
+        let receiver = std::os::unix::net::UnixDatagram::bind(&socket).unwrap();
+
+        notify_socket(&socket, "READY=1").unwrap();
+        let mut buf = [0u8; 16];
+        let len = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1");
+    }
+}
@@ -9,10 +9,16 @@
 /// - Automatic peer tracking
 /// - Event-driven notifications
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::IpAddr;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{Instant, MissedTickBehavior};
+
+use crate::core::config::Config;
 
 /// MultiShiva mDNS service type identifier.
 ///
@@ -24,6 +30,141 @@ use std::sync::{Arc, Mutex};
 /// - `local` is the domain for link-local multicast DNS
 pub const SERVICE_TYPE: &str = "_multishiva._tcp.local.";
 
+/// TXT property advertising the role of an instance, `host` or `agent`.
+pub const MODE_PROPERTY: &str = "mode";
+
+/// Events queued per [`Discovery::subscribe`] receiver before new ones are dropped.
+const SUBSCRIBER_CAPACITY: usize = 64;
+
+/// How often [`Discovery::wait_for_peers`] reports that it is still waiting.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A change to the peer list seen while browsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiscoveryEvent {
+    /// A peer was announced or its announcement changed
+    Discovered(PeerInfo),
+    /// The named peer withdrew its announcement
+    Lost(String),
+}
+
+type DiscoveredCallback = Box<dyn Fn(PeerInfo) + Send + 'static>;
+type LostCallback = Box<dyn Fn(String) + Send + 'static>;
+
+/// Callbacks and channels told about peer changes, shared with the browsing thread.
+#[derive(Clone, Default)]
+struct PeerListeners {
+    discovered: Arc<Mutex<Vec<DiscoveredCallback>>>,
+    lost: Arc<Mutex<Vec<LostCallback>>>,
+    subscribers: Arc<Mutex<Vec<mpsc::Sender<DiscoveryEvent>>>>,
+}
+
+impl PeerListeners {
+    fn notify(&self, event: DiscoveryEvent) {
+        match &event {
+            DiscoveryEvent::Discovered(peer) => {
+                if let Ok(callbacks) = self.discovered.lock() {
+                    for callback in callbacks.iter() {
+                        callback(peer.clone());
+                    }
+                }
+            }
+            DiscoveryEvent::Lost(name) => {
+                if let Ok(callbacks) = self.lost.lock() {
+                    for callback in callbacks.iter() {
+                        callback(name.clone());
+                    }
+                }
+            }
+        }
+
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|tx| match tx.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    tracing::warn!(
+                        "Discovery subscriber is not keeping up, dropping {:?}",
+                        event
+                    );
+                    true
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+            });
+        }
+    }
+}
+
+/// Why a discovered peer cannot be used as our host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostMismatch {
+    /// The peer advertises another role, e.g. `agent`.
+    NotHost {
+        /// The advertised role
+        mode: String,
+    },
+    /// The peer advertises the hash of a different PSK.
+    PskMismatch,
+}
+
+impl std::fmt::Display for HostMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HostMismatch::NotHost { mode } => write!(f, "advertises mode '{}', not host", mode),
+            HostMismatch::PskMismatch => write!(f, "advertised PSK hash does not match ours"),
+        }
+    }
+}
+
+/// No usable host was discovered before the timeout.
+///
+/// Returned, wrapped in an [`anyhow::Error`], by [`Discovery::find_host`].
+/// The peers that were found but rejected help diagnose an agent that only
+/// sees other agents, or hosts using another PSK.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::discovery::{DiscoveryError, HostMismatch, PeerInfo};
+/// use std::time::Duration;
+///
+/// let agent = PeerInfo::new("laptop".to_string(), "10.0.0.3".parse().unwrap(), 53421);
+/// let error = DiscoveryError {
+///     timeout: Duration::from_secs(5),
+///     rejected: vec![(agent, HostMismatch::NotHost { mode: "agent".to_string() })],
+/// };
+/// assert!(error.to_string().contains("laptop at 10.0.0.3:53421"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveryError {
+    /// How long discovery waited for a host
+    pub timeout: Duration,
+    /// Peers found that cannot be our host, with the reason
+    pub rejected: Vec<(PeerInfo, HostMismatch)>,
+}
+
+impl std::fmt::Display for DiscoveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No compatible MultiShiva host found after {:?}",
+            self.timeout
+        )?;
+        if !self.rejected.is_empty() {
+            let peers: Vec<String> = self
+                .rejected
+                .iter()
+                .map(|(peer, mismatch)| {
+                    format!("{} at {} ({})", peer.name, peer.full_address(), mismatch)
+                })
+                .collect();
+            write!(f, "; found only {}", peers.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DiscoveryError {}
+
 /// Information about a discovered MultiShiva peer on the network.
 ///
 /// This structure contains all the information needed to connect to and
@@ -70,6 +211,12 @@ pub struct PeerInfo {
     /// These are key-value pairs that can contain arbitrary metadata
     /// about the peer's capabilities or configuration.
     pub properties: HashMap<String, String>,
+
+    /// When the peer's announcement was last resolved.
+    ///
+    /// `None` for peers that were not discovered over mDNS, such as those
+    /// added with [`Discovery::add_manual_peer`].
+    pub last_seen: Option<DateTime<Utc>>,
 }
 
 impl PeerInfo {
@@ -105,9 +252,39 @@ impl PeerInfo {
             port,
             psk_hash: None,
             properties: HashMap::new(),
+            last_seen: None,
         }
     }
 
+    /// Returns whether this peer may share the local Pre-Shared Key.
+    ///
+    /// A peer is compatible when its advertised PSK hash equals `local_psk_hash`.
+    /// Peers that do not advertise a hash are treated as unknown and allowed;
+    /// the PSK handshake still decides whether the connection succeeds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::net::IpAddr;
+    /// use multishiva::core::discovery::PeerInfo;
+    ///
+    /// let mut peer = PeerInfo::new(
+    ///     "host".to_string(),
+    ///     "192.168.1.1".parse::<IpAddr>().unwrap(),
+    ///     53421,
+    /// );
+    /// assert!(peer.is_psk_compatible("abc"));
+    ///
+    /// peer.psk_hash = Some("abc".to_string());
+    /// assert!(peer.is_psk_compatible("abc"));
+    /// assert!(!peer.is_psk_compatible("def"));
+    /// ```
+    pub fn is_psk_compatible(&self, local_psk_hash: &str) -> bool {
+        self.psk_hash
+            .as_deref()
+            .is_none_or(|hash| hash == local_psk_hash)
+    }
+
     /// Returns the full network address in "IP:port" format.
     ///
     /// For IPv6 addresses, the IP is wrapped in brackets to ensure proper parsing.
@@ -141,6 +318,172 @@ impl PeerInfo {
             IpAddr::V6(addr) => format!("[{}]:{}", addr, self.port),
         }
     }
+
+    /// Returns the role advertised in the `mode` property, if any.
+    pub fn mode(&self) -> Option<&str> {
+        self.properties.get(MODE_PROPERTY).map(String::as_str)
+    }
+
+    /// Checks that this peer is a host sharing the local Pre-Shared Key.
+    ///
+    /// Like [`PeerInfo::is_psk_compatible`], a peer that does not advertise a
+    /// mode or a PSK hash is given the benefit of the doubt.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason the peer cannot be our host.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::discovery::{HostMismatch, PeerInfo, MODE_PROPERTY};
+    ///
+    /// let mut peer = PeerInfo::new("desk".to_string(), "10.0.0.2".parse().unwrap(), 53421);
+    /// peer.psk_hash = Some("abc".to_string());
+    /// peer.properties.insert(MODE_PROPERTY.to_string(), "host".to_string());
+    /// assert_eq!(peer.check_host("abc"), Ok(()));
+    /// assert_eq!(peer.check_host("def"), Err(HostMismatch::PskMismatch));
+    /// ```
+    pub fn check_host(&self, local_psk_hash: &str) -> Result<(), HostMismatch> {
+        if let Some(mode) = self.mode() {
+            if mode != "host" {
+                return Err(HostMismatch::NotHost {
+                    mode: mode.to_string(),
+                });
+            }
+        }
+        if !self.is_psk_compatible(local_psk_hash) {
+            return Err(HostMismatch::PskMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// Splits discovered `peers` into hosts an agent may connect to and rejected ones.
+///
+/// The machine named `self_name` is left out of both lists. Hosts keep the
+/// order of `peers`.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::discovery::{select_hosts, HostMismatch, PeerInfo};
+///
+/// let mut stranger = PeerInfo::new("stranger".to_string(), "10.0.0.3".parse().unwrap(), 53421);
+/// stranger.psk_hash = Some("other".to_string());
+/// let ours = PeerInfo::new("desk".to_string(), "10.0.0.2".parse().unwrap(), 53421);
+///
+/// let (hosts, rejected) = select_hosts(&[stranger, ours], "laptop", "abc");
+/// assert_eq!(hosts[0].name, "desk");
+/// assert_eq!(rejected[0].1, HostMismatch::PskMismatch);
+/// ```
+pub fn select_hosts(
+    peers: &[PeerInfo],
+    self_name: &str,
+    local_psk_hash: &str,
+) -> (Vec<PeerInfo>, Vec<(PeerInfo, HostMismatch)>) {
+    let mut hosts = Vec::new();
+    let mut rejected = Vec::new();
+    for peer in peers.iter().filter(|peer| peer.name != self_name) {
+        match peer.check_host(local_psk_hash) {
+            Ok(()) => hosts.push(peer.clone()),
+            Err(mismatch) => rejected.push((peer.clone(), mismatch)),
+        }
+    }
+    (hosts, rejected)
+}
+
+/// Picks the address to reach a peer at among those it advertises.
+///
+/// Addresses of the preferred family win, falling back to the other family
+/// when the peer advertises none. Link-local IPv6 addresses come last, as they
+/// cannot be connected to without the interface they belong to. Ties go to the
+/// lowest address so the choice does not depend on advertisement order.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::discovery::preferred_address;
+/// use std::net::IpAddr;
+///
+/// let v4: IpAddr = "192.168.1.20".parse().unwrap();
+/// let v6: IpAddr = "2001:db8::20".parse().unwrap();
+/// assert_eq!(preferred_address(&[v6, v4], false), Some(v4));
+/// assert_eq!(preferred_address(&[v6, v4], true), Some(v6));
+/// assert_eq!(preferred_address(&[v6], false), Some(v6));
+/// ```
+pub fn preferred_address<'a>(
+    addresses: impl IntoIterator<Item = &'a IpAddr>,
+    prefer_ipv6: bool,
+) -> Option<IpAddr> {
+    addresses.into_iter().copied().min_by_key(|address| {
+        let link_local = match address {
+            IpAddr::V4(_) => false,
+            IpAddr::V6(v6) => v6.segments()[0] & 0xffc0 == 0xfe80,
+        };
+        (address.is_ipv6() != prefer_ipv6, link_local, *address)
+    })
+}
+
+/// Properties a host registers on mDNS, besides its PSK hash.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::discovery::{host_properties, MODE_PROPERTY};
+///
+/// assert_eq!(host_properties()[MODE_PROPERTY], "host");
+/// ```
+pub fn host_properties() -> HashMap<String, String> {
+    HashMap::from([(MODE_PROPERTY.to_string(), "host".to_string())])
+}
+
+/// Timing of [`Discovery::wait_for_peers`].
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::discovery::DiscoveryConfig;
+/// use std::time::Duration;
+///
+/// let config = DiscoveryConfig::default();
+/// assert_eq!(config.timeout, Duration::from_secs(5));
+/// assert_eq!(config.poll_interval, Duration::from_millis(500));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiscoveryConfig {
+    /// How long to wait for a matching peer before giving up.
+    pub timeout: Duration,
+    /// How often the discovered peers are checked.
+    pub poll_interval: Duration,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+impl DiscoveryConfig {
+    /// Reads `discovery_timeout_ms` and `discovery_poll_interval_ms` from the
+    /// behavior settings, falling back to the defaults.
+    pub fn from_config(config: &Config) -> Self {
+        let defaults = Self::default();
+        let behavior = config.behavior.as_ref();
+        Self {
+            timeout: behavior
+                .and_then(|b| b.discovery_timeout_ms)
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.timeout),
+            poll_interval: behavior
+                .and_then(|b| b.discovery_poll_interval_ms)
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.poll_interval),
+        }
+    }
 }
 
 /// mDNS-based service discovery system for MultiShiva instances.
@@ -176,6 +519,11 @@ pub struct Discovery {
     daemon: ServiceDaemon,
     service_name: String,
     peers: Arc<Mutex<HashMap<String, PeerInfo>>>,
+    manual_peers: Arc<Mutex<HashSet<String>>>,
+    listeners: PeerListeners,
+    prefer_ipv6: bool,
+    /// Addresses published by [`Discovery::register`], all of them if empty
+    advertised: Vec<IpAddr>,
 }
 
 impl Discovery {
@@ -212,9 +560,53 @@ impl Discovery {
             daemon,
             service_name,
             peers: Arc::new(Mutex::new(HashMap::new())),
+            manual_peers: Arc::new(Mutex::new(HashSet::new())),
+            listeners: PeerListeners::default(),
+            prefer_ipv6: false,
+            advertised: Vec::new(),
         })
     }
 
+    /// Chooses which address of a peer advertising both IPv4 and IPv6 is kept.
+    ///
+    /// IPv4 is preferred by default. Applies to browsing started afterwards;
+    /// see [`preferred_address`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::discovery::Discovery;
+    ///
+    /// let mut discovery = Discovery::new("my-machine".to_string())?;
+    /// discovery.set_prefer_ipv6(true);
+    /// discovery.start_browsing()?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn set_prefer_ipv6(&mut self, prefer_ipv6: bool) {
+        self.prefer_ipv6 = prefer_ipv6;
+    }
+
+    /// Limits the addresses [`Discovery::register`] advertises to `addresses`.
+    ///
+    /// Used when the host only listens on some addresses, so agents are not
+    /// pointed to an address nothing listens on. An empty list advertises the
+    /// addresses of every interface.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::discovery::Discovery;
+    /// use std::net::{IpAddr, Ipv4Addr};
+    ///
+    /// let mut discovery = Discovery::new("my-machine".to_string())?;
+    /// discovery.set_advertised_addresses(vec![IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10))]);
+    /// discovery.register(53421, None, Default::default())?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn set_advertised_addresses(&mut self, addresses: Vec<IpAddr>) {
+        self.advertised = addresses;
+    }
+
     /// Registers this instance as a discoverable MultiShiva service on the network.
     ///
     /// This broadcasts the service's presence using mDNS, making it discoverable
@@ -279,7 +671,8 @@ impl Discovery {
             SERVICE_TYPE,
             &self.service_name,
             &mdns_hostname,
-            "", // Address will be auto-detected
+            // Address will be auto-detected unless restricted
+            self.advertised.as_slice(),
             port,
             Some(props),
         )
@@ -375,71 +768,18 @@ impl Discovery {
             .context("Failed to start browsing for services")?;
 
         let peers = Arc::clone(&self.peers);
+        let manual_peers = Arc::clone(&self.manual_peers);
+        let listeners = self.listeners.clone();
         let service_name = self.service_name.clone();
+        let prefer_ipv6 = self.prefer_ipv6;
 
         // Spawn background task to handle service events
         std::thread::spawn(move || {
             for event in receiver.iter() {
-                match event {
-                    ServiceEvent::ServiceResolved(info) => {
-                        // Skip self
-                        if info
-                            .get_fullname()
-                            .starts_with(&format!("{}.", service_name))
-                        {
-                            continue;
-                        }
-
-                        // Extract peer information
-                        let name = info
-                            .get_fullname()
-                            .split('.')
-                            .next()
-                            .unwrap_or("unknown")
-                            .to_string();
-
-                        if let Some(address) = info.get_addresses().iter().next() {
-                            let port = info.get_port();
-                            let psk_hash =
-                                info.get_property_val_str("psk_hash").map(|s| s.to_string());
-
-                            let mut properties = HashMap::new();
-                            for prop in info.get_properties().iter() {
-                                let key = prop.key();
-                                if key != "psk_hash" {
-                                    let value = prop.val_str();
-                                    properties.insert(key.to_string(), value.to_string());
-                                }
-                            }
-
-                            let peer = PeerInfo {
-                                name: name.clone(),
-                                address: *address,
-                                port,
-                                psk_hash,
-                                properties,
-                            };
-
-                            // Add to peers list
-                            if let Ok(mut peers) = peers.lock() {
-                                peers.insert(name.clone(), peer.clone());
-                                tracing::info!(
-                                    "Discovered peer: {} at {}",
-                                    name,
-                                    peer.full_address()
-                                );
-                            }
-                        }
-                    }
-                    ServiceEvent::ServiceRemoved(_, fullname) => {
-                        let name = fullname.split('.').next().unwrap_or("unknown");
-                        if let Ok(mut peers) = peers.lock() {
-                            if peers.remove(name).is_some() {
-                                tracing::info!("Peer removed: {}", name);
-                            }
-                        }
-                    }
-                    _ => {}
+                if let Some(change) =
+                    handle_service_event(event, &service_name, &peers, &manual_peers, prefer_ipv6)
+                {
+                    listeners.notify(change);
                 }
             }
         });
@@ -448,6 +788,115 @@ impl Discovery {
         Ok(())
     }
 
+    /// Registers a callback invoked with each peer discovered while browsing.
+    ///
+    /// Callbacks run on the browsing thread after the peer list is updated,
+    /// so they should return quickly and must not register further callbacks.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::discovery::Discovery;
+    ///
+    /// let discovery = Discovery::new("my-machine".to_string())?;
+    /// discovery.on_peer_discovered(|peer| println!("Found {}", peer.name));
+    /// discovery.on_peer_lost(|name| println!("Lost {}", name));
+    /// discovery.start_browsing()?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn on_peer_discovered(&self, callback: impl Fn(PeerInfo) + Send + 'static) {
+        if let Ok(mut callbacks) = self.listeners.discovered.lock() {
+            callbacks.push(Box::new(callback));
+        }
+    }
+
+    /// Registers a callback invoked with the name of each peer that withdraws.
+    ///
+    /// Manual peers are never reported as lost. The same restrictions as
+    /// [`Discovery::on_peer_discovered`] apply.
+    pub fn on_peer_lost(&self, callback: impl Fn(String) + Send + 'static) {
+        if let Ok(mut callbacks) = self.listeners.lost.lock() {
+            callbacks.push(Box::new(callback));
+        }
+    }
+
+    /// Returns a channel receiving every peer change seen while browsing.
+    ///
+    /// Each receiver buffers up to 64 events; later events are dropped until
+    /// it catches up. Dropping the receiver unsubscribes it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::discovery::{Discovery, DiscoveryEvent};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let discovery = Discovery::new("my-machine".to_string())?;
+    /// let mut changes = discovery.subscribe();
+    /// discovery.start_browsing()?;
+    ///
+    /// while let Some(change) = changes.recv().await {
+    ///     match change {
+    ///         DiscoveryEvent::Discovered(peer) => println!("+ {}", peer.name),
+    ///         DiscoveryEvent::Lost(name) => println!("- {}", name),
+    ///     }
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// # });
+    /// ```
+    pub fn subscribe(&self) -> mpsc::Receiver<DiscoveryEvent> {
+        let (tx, rx) = mpsc::channel(SUBSCRIBER_CAPACITY);
+        if let Ok(mut subscribers) = self.listeners.subscribers.lock() {
+            subscribers.push(tx);
+        }
+        rx
+    }
+
+    /// Adds a peer that was configured by hand rather than discovered.
+    ///
+    /// Useful on networks where mDNS multicast is blocked. The peer is visible
+    /// through `get_peers()`, `get_peer()` and `has_peer()` like a discovered one,
+    /// and is kept when mDNS reports a service of the same name as removed.
+    /// mDNS announcements for other peers are still merged in while browsing.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::discovery::{Discovery, PeerInfo};
+    ///
+    /// let discovery = Discovery::new("my-machine".to_string())?;
+    /// discovery.add_manual_peer(PeerInfo::new(
+    ///     "office-host".to_string(),
+    ///     "10.0.4.20".parse()?,
+    ///     53421,
+    /// ));
+    ///
+    /// assert!(discovery.has_peer("office-host"));
+    /// assert!(discovery.is_manual_peer("office-host"));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn add_manual_peer(&self, peer: PeerInfo) {
+        if let Ok(mut manual) = self.manual_peers.lock() {
+            manual.insert(peer.name.clone());
+        }
+        if let Ok(mut peers) = self.peers.lock() {
+            tracing::info!(
+                "Added manual peer: {} at {}",
+                peer.name,
+                peer.full_address()
+            );
+            peers.insert(peer.name.clone(), peer);
+        }
+    }
+
+    /// Returns whether the named peer was added with `add_manual_peer()`.
+    pub fn is_manual_peer(&self, name: &str) -> bool {
+        self.manual_peers
+            .lock()
+            .map(|manual| manual.contains(name))
+            .unwrap_or(false)
+    }
+
     /// Returns a list of all currently discovered peers.
     ///
     /// This creates a snapshot of the current peer list at the time of the call.
@@ -478,6 +927,243 @@ impl Discovery {
             .unwrap_or_default()
     }
 
+    /// Returns the discovered peers that may share the local Pre-Shared Key.
+    ///
+    /// Peers advertising a different PSK hash are left out, since connecting to
+    /// them would only fail at handshake. Peers without an advertised hash are
+    /// kept. See [`PeerInfo::is_psk_compatible`].
+    ///
+    /// # Arguments
+    ///
+    /// * `local_psk_hash` - Hash of the local PSK, as published in the `psk_hash` TXT property
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::discovery::Discovery;
+    /// use multishiva::core::network::compute_psk_hash;
+    ///
+    /// let discovery = Discovery::new("my-machine".to_string())?;
+    /// discovery.start_browsing()?;
+    ///
+    /// std::thread::sleep(std::time::Duration::from_secs(2));
+    ///
+    /// let local_hash = compute_psk_hash("my-secret-psk");
+    /// for peer in discovery.get_compatible_peers(&local_hash) {
+    ///     println!("  - {} at {}", peer.name, peer.full_address());
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn get_compatible_peers(&self, local_psk_hash: &str) -> Vec<PeerInfo> {
+        self.get_peers()
+            .into_iter()
+            .filter(|peer| peer.is_psk_compatible(local_psk_hash))
+            .collect()
+    }
+
+    /// Waits until at least one discovered peer matches `filter`.
+    ///
+    /// Peers are checked immediately, then every `config.poll_interval` until
+    /// `config.timeout` has elapsed, logging progress every two seconds.
+    /// Returns all matching peers, or an empty list if none showed up in time.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::discovery::{Discovery, DiscoveryConfig};
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let discovery = Discovery::new("my-machine".to_string())?;
+    /// discovery.start_browsing()?;
+    ///
+    /// let hosts = discovery
+    ///     .wait_for_peers(|peer| peer.name != "my-machine", DiscoveryConfig::default())
+    ///     .await;
+    /// println!("Found {} host(s)", hosts.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_peers<F>(&self, mut filter: F, config: DiscoveryConfig) -> Vec<PeerInfo>
+    where
+        F: FnMut(&PeerInfo) -> bool,
+    {
+        let started = Instant::now();
+        let deadline = started + config.timeout;
+        let mut next_progress = started + PROGRESS_INTERVAL;
+        let mut poll = tokio::time::interval(config.poll_interval.max(Duration::from_millis(1)));
+        poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes immediately
+        poll.tick().await;
+
+        loop {
+            let peers = self.get_peers();
+            let seen = peers.len();
+            let matching: Vec<PeerInfo> = peers.into_iter().filter(|peer| filter(peer)).collect();
+            if !matching.is_empty() {
+                return matching;
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Vec::new();
+            }
+            if now >= next_progress {
+                tracing::info!(
+                    "Still looking for peers: {}s elapsed, {} seen, {}s left",
+                    now.duration_since(started).as_secs(),
+                    seen,
+                    deadline.saturating_duration_since(now).as_secs()
+                );
+                next_progress += PROGRESS_INTERVAL;
+            }
+            // Wake at the next poll, or at the deadline for a last check
+            let _ = tokio::time::timeout_at(deadline, poll.tick()).await;
+        }
+    }
+
+    /// Waits for a host that an agent named `self_name` can connect to.
+    ///
+    /// A host is a peer other than `self_name` that advertises the host role
+    /// and the hash of our PSK, see [`PeerInfo::check_host`]. Rejected peers
+    /// are logged once each. If several hosts answer, the first is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DiscoveryError`] listing the rejected peers if no host shows
+    /// up within `config.timeout`.
+    pub async fn find_host(
+        &self,
+        self_name: &str,
+        local_psk_hash: &str,
+        config: DiscoveryConfig,
+    ) -> Result<PeerInfo> {
+        let mut skipped = HashSet::new();
+        let hosts = self
+            .wait_for_peers(
+                |peer| {
+                    if peer.name == self_name {
+                        return false;
+                    }
+                    // Agents and hosts with a different PSK would only fail at handshake
+                    match peer.check_host(local_psk_hash) {
+                        Ok(()) => true,
+                        Err(mismatch) => {
+                            if skipped.insert(peer.name.clone()) {
+                                tracing::warn!(
+                                    "Skipping '{}' at {}: {}",
+                                    peer.name,
+                                    peer.full_address(),
+                                    mismatch
+                                );
+                            }
+                            false
+                        }
+                    }
+                },
+                config,
+            )
+            .await;
+
+        let Some(host) = hosts.first() else {
+            let (_, rejected) = select_hosts(&self.get_peers(), self_name, local_psk_hash);
+            return Err(DiscoveryError {
+                timeout: config.timeout,
+                rejected,
+            }
+            .into());
+        };
+        if hosts.len() > 1 {
+            tracing::warn!(
+                "Multiple hosts found on network, using first one: {}",
+                host.name
+            );
+            for info in hosts.iter().skip(1) {
+                tracing::warn!("  - Also found: {} at {}", info.name, info.full_address());
+            }
+        }
+        Ok(host.clone())
+    }
+
+    /// [`find_host`](Self::find_host) with the default poll interval and the given `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DiscoveryError`] if no host shows up within `timeout`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::discovery::Discovery;
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let discovery = Discovery::new("laptop".to_string())?;
+    /// discovery.start_browsing()?;
+    ///
+    /// let host = discovery
+    ///     .find_host_with_timeout("laptop", "psk-hash", Duration::from_secs(10))
+    ///     .await?;
+    /// println!("Host at {}", host.full_address());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_host_with_timeout(
+        &self,
+        self_name: &str,
+        local_psk_hash: &str,
+        timeout: Duration,
+    ) -> Result<PeerInfo> {
+        let config = DiscoveryConfig {
+            timeout,
+            ..DiscoveryConfig::default()
+        };
+        self.find_host(self_name, local_psk_hash, config).await
+    }
+
+    /// Browses for `timeout`, then returns every peer found other than `self_name`.
+    ///
+    /// Unlike [`Discovery::find_host_with_timeout`], this always waits the full
+    /// `timeout` so that slow responders are listed too. Peers are sorted by
+    /// name and are not filtered by mode or PSK hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if browsing cannot be started.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::discovery::Discovery;
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let discovery = Discovery::new("laptop".to_string())?;
+    /// for peer in discovery
+    ///     .find_peers_with_timeout("laptop", Duration::from_secs(5))
+    ///     .await?
+    /// {
+    ///     println!("{} at {}", peer.name, peer.full_address());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_peers_with_timeout(
+        &self,
+        self_name: &str,
+        timeout: Duration,
+    ) -> Result<Vec<PeerInfo>> {
+        self.start_browsing()?;
+        tokio::time::sleep(timeout).await;
+
+        let mut peers: Vec<PeerInfo> = self
+            .get_peers()
+            .into_iter()
+            .filter(|peer| peer.name != self_name)
+            .collect();
+        peers.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(peers)
+    }
+
     /// Retrieves information about a specific peer by name.
     ///
     /// Returns `Some(PeerInfo)` if a peer with the given name is currently
@@ -568,6 +1254,9 @@ impl Discovery {
         if let Ok(mut peers) = self.peers.lock() {
             peers.clear();
         }
+        if let Ok(mut manual) = self.manual_peers.lock() {
+            manual.clear();
+        }
     }
 
     /// Shuts down the discovery system gracefully.
@@ -610,6 +1299,87 @@ impl Discovery {
     }
 }
 
+/// Applies a single mDNS service event to the peer list.
+///
+/// Returns the change made to the list, if any.
+fn handle_service_event(
+    event: ServiceEvent,
+    service_name: &str,
+    peers: &Mutex<HashMap<String, PeerInfo>>,
+    manual_peers: &Mutex<HashSet<String>>,
+    prefer_ipv6: bool,
+) -> Option<DiscoveryEvent> {
+    match event {
+        ServiceEvent::ServiceResolved(info) => {
+            // Skip self
+            if info
+                .get_fullname()
+                .starts_with(&format!("{}.", service_name))
+            {
+                return None;
+            }
+
+            // Extract peer information
+            let name = info
+                .get_fullname()
+                .split('.')
+                .next()
+                .unwrap_or("unknown")
+                .to_string();
+
+            if let Some(address) = preferred_address(info.get_addresses(), prefer_ipv6) {
+                let port = info.get_port();
+                let psk_hash = info.get_property_val_str("psk_hash").map(|s| s.to_string());
+
+                let mut properties = HashMap::new();
+                for prop in info.get_properties().iter() {
+                    let key = prop.key();
+                    if key != "psk_hash" {
+                        let value = prop.val_str();
+                        properties.insert(key.to_string(), value.to_string());
+                    }
+                }
+
+                let peer = PeerInfo {
+                    name: name.clone(),
+                    address,
+                    port,
+                    psk_hash,
+                    properties,
+                    last_seen: Some(Utc::now()),
+                };
+
+                // Add to peers list
+                if let Ok(mut peers) = peers.lock() {
+                    peers.insert(name.clone(), peer.clone());
+                    tracing::info!("Discovered peer: {} at {}", name, peer.full_address());
+                    return Some(DiscoveryEvent::Discovered(peer));
+                }
+            }
+            None
+        }
+        ServiceEvent::ServiceRemoved(_, fullname) => {
+            let name = fullname.split('.').next().unwrap_or("unknown");
+
+            // Manual peers were never announced, so they are never withdrawn
+            let is_manual = manual_peers
+                .lock()
+                .map(|manual| manual.contains(name))
+                .unwrap_or(false);
+            if is_manual {
+                tracing::debug!("Ignoring mDNS removal of manual peer: {}", name);
+                return None;
+            }
+
+            let mut peers = peers.lock().ok()?;
+            peers.remove(name)?;
+            tracing::info!("Peer removed: {}", name);
+            Some(DiscoveryEvent::Lost(name.to_string()))
+        }
+        _ => None,
+    }
+}
+
 impl Drop for Discovery {
     fn drop(&mut self) {
         let _ = self.shutdown();
@@ -670,6 +1440,33 @@ mod tests {
             PeerInfo::new("agent-local".to_string(), "fe80::1".parse().unwrap(), 8080);
 
         assert_eq!(peer_v6_local.full_address(), "[fe80::1]:8080");
+
+        let peer_loopback = PeerInfo::new("agent-lo".to_string(), "::1".parse().unwrap(), 53421);
+        assert_eq!(peer_loopback.full_address(), "[::1]:53421");
+        assert!(peer_loopback
+            .full_address()
+            .parse::<std::net::SocketAddr>()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_preferred_address() {
+        let v4: IpAddr = "192.168.1.20".parse().unwrap();
+        let other_v4: IpAddr = "10.0.0.20".parse().unwrap();
+        let v6: IpAddr = "2001:db8::20".parse().unwrap();
+        let link_local: IpAddr = "fe80::20".parse().unwrap();
+
+        assert_eq!(preferred_address(&[v6, v4, link_local], false), Some(v4));
+        assert_eq!(preferred_address(&[v4, link_local, v6], true), Some(v6));
+        // Only IPv6 advertised, link-local as a last resort
+        assert_eq!(preferred_address(&[link_local, v6], false), Some(v6));
+        assert_eq!(preferred_address(&[link_local], false), Some(link_local));
+        // Only IPv4 advertised
+        assert_eq!(preferred_address(&[v4], true), Some(v4));
+        // Independent of advertisement order
+        assert_eq!(preferred_address(&[v4, other_v4], false), Some(other_v4));
+        assert_eq!(preferred_address(&[other_v4, v4], false), Some(other_v4));
+        assert_eq!(preferred_address(&[], false), None);
     }
 
     #[test]
@@ -712,6 +1509,254 @@ mod tests {
         assert_eq!(discovery.get_peers().len(), 0);
     }
 
+    #[test]
+    fn test_manual_peer_survives_browse_cycle() {
+        let discovery = Discovery::new("test-host".to_string()).unwrap();
+        discovery.add_manual_peer(PeerInfo::new(
+            "office-host".to_string(),
+            "10.0.4.20".parse().unwrap(),
+            53421,
+        ));
+        {
+            let mut peers = discovery.peers.lock().unwrap();
+            let peer = PeerInfo::new(
+                "agent1".to_string(),
+                "192.168.1.100".parse().unwrap(),
+                53421,
+            );
+            peers.insert("agent1".to_string(), peer);
+        }
+
+        for name in ["office-host", "agent1"] {
+            handle_service_event(
+                ServiceEvent::ServiceRemoved(
+                    SERVICE_TYPE.to_string(),
+                    format!("{}.{}", name, SERVICE_TYPE),
+                ),
+                &discovery.service_name,
+                &discovery.peers,
+                &discovery.manual_peers,
+                false,
+            );
+        }
+
+        assert!(discovery.has_peer("office-host"));
+        assert!(discovery.is_manual_peer("office-host"));
+        assert!(!discovery.has_peer("agent1"));
+        assert_eq!(discovery.get_peers().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_peer_change_callbacks_and_subscribers() {
+        let discovery = Discovery::new("test-host".to_string()).unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        {
+            let seen = seen.clone();
+            discovery.on_peer_discovered(move |peer| seen.lock().unwrap().push(peer.name));
+        }
+        {
+            let seen = seen.clone();
+            discovery.on_peer_lost(move |name| seen.lock().unwrap().push(format!("-{}", name)));
+        }
+        let mut changes = discovery.subscribe();
+        let dropped = discovery.subscribe();
+        drop(dropped);
+
+        let peer = PeerInfo::new(
+            "agent1".to_string(),
+            "192.168.1.100".parse().unwrap(),
+            53421,
+        );
+        discovery
+            .peers
+            .lock()
+            .unwrap()
+            .insert("agent1".to_string(), peer.clone());
+        discovery
+            .listeners
+            .notify(DiscoveryEvent::Discovered(peer.clone()));
+
+        // Removing a known peer reports it lost; an unknown one is no change
+        for name in ["agent1", "ghost"] {
+            let change = handle_service_event(
+                ServiceEvent::ServiceRemoved(
+                    SERVICE_TYPE.to_string(),
+                    format!("{}.{}", name, SERVICE_TYPE),
+                ),
+                &discovery.service_name,
+                &discovery.peers,
+                &discovery.manual_peers,
+                false,
+            );
+            if let Some(change) = change {
+                discovery.listeners.notify(change);
+            }
+        }
+
+        assert_eq!(*seen.lock().unwrap(), vec!["agent1", "-agent1"]);
+        assert_eq!(changes.recv().await, Some(DiscoveryEvent::Discovered(peer)));
+        assert_eq!(
+            changes.recv().await,
+            Some(DiscoveryEvent::Lost("agent1".to_string()))
+        );
+        assert!(changes.try_recv().is_err());
+        assert_eq!(discovery.listeners.subscribers.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_get_compatible_peers_filters_mismatched_hash() {
+        let discovery = Discovery::new("test-agent".to_string()).unwrap();
+        {
+            let mut peers = discovery.peers.lock().unwrap();
+            for (name, hash) in [
+                ("same-psk", Some("local-hash")),
+                ("other-psk", Some("other-hash")),
+                ("no-hash", None),
+            ] {
+                let mut peer = PeerInfo::new(name.to_string(), "10.0.0.1".parse().unwrap(), 53421);
+                peer.psk_hash = hash.map(str::to_string);
+                peers.insert(name.to_string(), peer);
+            }
+        }
+
+        let mut names: Vec<String> = discovery
+            .get_compatible_peers("local-hash")
+            .into_iter()
+            .map(|peer| peer.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["no-hash", "same-psk"]);
+        assert_eq!(discovery.get_peers().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_peers_returns_seeded_peer_immediately() {
+        let discovery = Discovery::new("test-agent".to_string()).unwrap();
+        discovery.add_manual_peer(PeerInfo::new(
+            "host".to_string(),
+            "10.0.0.1".parse().unwrap(),
+            53421,
+        ));
+
+        let config = DiscoveryConfig {
+            timeout: Duration::from_secs(60),
+            poll_interval: Duration::from_secs(30),
+        };
+        let started = std::time::Instant::now();
+        let peers = discovery
+            .wait_for_peers(|peer| peer.name != "test-agent", config)
+            .await;
+
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].name, "host");
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_peers_times_out_without_match() {
+        let discovery = Discovery::new("test-agent".to_string()).unwrap();
+        let config = DiscoveryConfig {
+            timeout: Duration::from_millis(50),
+            poll_interval: Duration::from_millis(10),
+        };
+        assert!(discovery.wait_for_peers(|_| true, config).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_host_reports_non_host_peers_on_timeout() {
+        let discovery = Discovery::new("test-agent".to_string()).unwrap();
+        discovery.add_manual_peer(advertised("other-agent", Some("agent"), Some("hash")));
+
+        let error = discovery
+            .find_host_with_timeout("test-agent", "hash", Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        let error = error.downcast::<DiscoveryError>().unwrap();
+        assert_eq!(error.timeout, Duration::from_millis(50));
+        assert_eq!(error.rejected.len(), 1);
+        assert_eq!(error.rejected[0].0.name, "other-agent");
+        assert_eq!(
+            error.rejected[0].1,
+            HostMismatch::NotHost {
+                mode: "agent".to_string()
+            }
+        );
+
+        discovery.add_manual_peer(advertised("desk", Some("host"), Some("hash")));
+        let host = discovery
+            .find_host_with_timeout("test-agent", "hash", Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert_eq!(host.name, "desk");
+    }
+
+    #[tokio::test]
+    async fn test_find_peers_with_timeout_lists_others_sorted() {
+        let discovery = Discovery::new("test-agent".to_string()).unwrap();
+        discovery.add_manual_peer(advertised("zeta", Some("agent"), None));
+        discovery.add_manual_peer(advertised("test-agent", Some("agent"), None));
+        discovery.add_manual_peer(advertised("alpha", Some("host"), Some("hash")));
+
+        let peers = discovery
+            .find_peers_with_timeout("test-agent", Duration::from_millis(50))
+            .await
+            .unwrap();
+        let names: Vec<&str> = peers.iter().map(|peer| peer.name.as_str()).collect();
+        // Other instances on the network may show up as well
+        assert!(names.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(names.contains(&"alpha") && names.contains(&"zeta"));
+        assert!(!names.contains(&"test-agent"));
+    }
+
+    fn advertised(name: &str, mode: Option<&str>, psk_hash: Option<&str>) -> PeerInfo {
+        let mut peer = PeerInfo::new(name.to_string(), "192.168.1.20".parse().unwrap(), 53421);
+        peer.psk_hash = psk_hash.map(str::to_string);
+        if let Some(mode) = mode {
+            peer.properties
+                .insert(MODE_PROPERTY.to_string(), mode.to_string());
+        }
+        peer
+    }
+
+    #[test]
+    fn test_select_hosts_filters_mode_and_psk() {
+        let peers = vec![
+            advertised("laptop", Some("host"), Some("ours")),
+            advertised("other-office", Some("host"), Some("theirs")),
+            advertised("agent2", Some("agent"), Some("ours")),
+            advertised("desk", Some("host"), Some("ours")),
+        ];
+
+        let (hosts, rejected) = select_hosts(&peers, "laptop", "ours");
+        let names: Vec<&str> = hosts.iter().map(|peer| peer.name.as_str()).collect();
+        assert_eq!(names, vec!["desk"]);
+        assert_eq!(
+            rejected
+                .iter()
+                .map(|(peer, mismatch)| (peer.name.as_str(), mismatch.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("other-office", HostMismatch::PskMismatch),
+                (
+                    "agent2",
+                    HostMismatch::NotHost {
+                        mode: "agent".to_string()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_hosts_allows_peers_without_advertisement() {
+        let peers = vec![advertised("old-host", None, None)];
+        let (hosts, rejected) = select_hosts(&peers, "laptop", "ours");
+        assert_eq!(hosts.len(), 1);
+        assert!(rejected.is_empty());
+
+        assert!(select_hosts(&[], "laptop", "ours").0.is_empty());
+    }
+
     #[test]
     fn test_service_type_constant() {
         assert_eq!(SERVICE_TYPE, "_multishiva._tcp.local.");
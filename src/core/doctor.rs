@@ -0,0 +1,574 @@
+/// Environment checks for `multishiva doctor`
+///
+/// Each check is an independent function returning a [`CheckResult`] that
+/// names what was checked, whether it passed and, when it did not, the
+/// concrete command or setting that fixes it. [`run_checks`] runs them all in
+/// order for the command line.
+use std::fmt;
+use std::net::TcpListener;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::core::config::{Config, ConfigMode};
+use crate::core::keyring::{KeyringManager, KEYRING_PLACEHOLDER};
+
+/// Port checked when the configuration cannot be read
+const DEFAULT_PORT: u16 = 53421;
+
+/// Keyring entry written and removed by [`check_keyring`]
+const KEYRING_PROBE_KEY: &str = "doctor_probe";
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    /// The check succeeded
+    Pass,
+    /// MultiShiva works, but in a degraded way
+    Warn,
+    /// MultiShiva will not work until this is fixed
+    Fail,
+    /// The check does not apply to this platform or configuration
+    Skip,
+}
+
+/// Result of one named check
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::doctor::{CheckResult, CheckStatus};
+///
+/// let result = CheckResult::fail("uinput", "/dev/uinput is missing", "sudo modprobe uinput");
+/// assert_eq!(result.status, CheckStatus::Fail);
+/// assert_eq!(result.remediation.as_deref(), Some("sudo modprobe uinput"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckResult {
+    /// Short name of the check, e.g. `"uinput"`
+    pub name: String,
+    /// Whether the check passed
+    pub status: CheckStatus,
+    /// What was found
+    pub detail: String,
+    /// How to fix it, for warnings and failures
+    pub remediation: Option<String>,
+}
+
+impl CheckResult {
+    /// A passing check
+    pub fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Pass, detail.into(), None)
+    }
+
+    /// A check that found a degraded setup
+    pub fn warn(name: &str, detail: impl Into<String>, remediation: impl Into<String>) -> Self {
+        Self::new(
+            name,
+            CheckStatus::Warn,
+            detail.into(),
+            Some(remediation.into()),
+        )
+    }
+
+    /// A failing check
+    pub fn fail(name: &str, detail: impl Into<String>, remediation: impl Into<String>) -> Self {
+        Self::new(
+            name,
+            CheckStatus::Fail,
+            detail.into(),
+            Some(remediation.into()),
+        )
+    }
+
+    /// A check that does not apply here
+    pub fn skip(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Skip, detail.into(), None)
+    }
+
+    fn new(name: &str, status: CheckStatus, detail: String, remediation: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail,
+            remediation,
+        }
+    }
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = match self.status {
+            CheckStatus::Pass => "✓",
+            CheckStatus::Warn => "!",
+            CheckStatus::Fail => "✗",
+            CheckStatus::Skip => "-",
+        };
+        write!(f, "{} {:<16} {}", mark, self.name, self.detail)?;
+        if let Some(remediation) = &self.remediation {
+            for line in remediation.lines() {
+                write!(f, "\n    → {}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Results of every check, in the order they were run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DoctorReport {
+    /// Individual check results
+    pub checks: Vec<CheckResult>,
+}
+
+impl DoctorReport {
+    /// Returns the number of failed checks
+    ///
+    /// Warnings and skipped checks do not count.
+    pub fn failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .count()
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "{}", check)?;
+        }
+        match self.failures() {
+            0 => write!(f, "\nAll checks passed."),
+            n => write!(f, "\n{} check(s) failed.", n),
+        }
+    }
+}
+
+/// Runs every check against the configuration at `config_path`
+///
+/// The port check uses the configured port, or the default one if the
+/// configuration cannot be read. A keyring failure only fails the report when
+/// the configuration's PSK is stored in the keyring.
+pub fn run_checks(config_path: &Path) -> DoctorReport {
+    let (config_check, config) = check_config(config_path);
+    let port_check = match &config {
+        Some(config) if config.mode == ConfigMode::Agent => CheckResult::skip(
+            "port",
+            "agents connect to the host and do not listen on a port",
+        ),
+        Some(config) => check_port(config.port),
+        None => check_port(DEFAULT_PORT),
+    };
+
+    // The keyring is only required when the PSK is stored in it
+    let mut keyring_check = check_keyring(&KeyringManager::with_service(
+        "multishiva-doctor".to_string(),
+    ));
+    let psk_in_keyring = config
+        .as_ref()
+        .is_some_and(|config| config.tls.psk == KEYRING_PLACEHOLDER);
+    if keyring_check.status == CheckStatus::Fail && !psk_in_keyring {
+        keyring_check.status = CheckStatus::Warn;
+    }
+    let stored_psk_check = check_stored_psk(&KeyringManager::new(), psk_in_keyring);
+
+    DoctorReport {
+        checks: vec![
+            check_input_devices(),
+            check_uinput(),
+            check_display(),
+            keyring_check,
+            stored_psk_check,
+            check_mdns(),
+            config_check,
+            port_check,
+        ],
+    }
+}
+
+/// Checks that at least one input device can be read for capture
+///
+/// On Linux this opens every `/dev/input/event*` device; elsewhere it relies
+/// on the platform permission check.
+pub fn check_input_devices() -> CheckResult {
+    #[cfg(target_os = "linux")]
+    {
+        check_input_dir(Path::new("/dev/input"))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        use crate::core::permissions::{check_permissions, PermissionStatus};
+
+        match check_permissions() {
+            Ok(PermissionStatus::Granted) => {
+                CheckResult::pass("input devices", "input capture is permitted")
+            }
+            Ok(PermissionStatus::Denied { missing }) => CheckResult::fail(
+                "input devices",
+                format!("missing: {}", missing.join(", ")),
+                platform_input_remediation(),
+            ),
+            Ok(PermissionStatus::Unknown) | Err(_) => CheckResult::warn(
+                "input devices",
+                "could not determine input permissions",
+                platform_input_remediation(),
+            ),
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn platform_input_remediation() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Grant Accessibility and Input Monitoring in System Settings → Privacy & Security, then restart MultiShiva"
+    } else {
+        "Run MultiShiva as Administrator to control elevated windows"
+    }
+}
+
+/// Checks that `/dev/input/event*` devices under `dir` can be opened for reading
+#[cfg(target_os = "linux")]
+fn check_input_dir(dir: &Path) -> CheckResult {
+    const NAME: &str = "input devices";
+    const REMEDIATION: &str = "sudo usermod -aG input $USER\nthen log out and back in";
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            return CheckResult::fail(
+                NAME,
+                format!("cannot list {}: {}", dir.display(), e),
+                REMEDIATION,
+            )
+        }
+    };
+
+    let devices: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("event"))
+        })
+        .collect();
+    if devices.is_empty() {
+        return CheckResult::fail(
+            NAME,
+            format!("no event devices in {}", dir.display()),
+            "Check that the evdev kernel module is loaded: sudo modprobe evdev",
+        );
+    }
+
+    let readable = devices
+        .iter()
+        .filter(|path| std::fs::File::open(path).is_ok())
+        .count();
+    match readable {
+        0 => CheckResult::fail(
+            NAME,
+            format!("none of the {} event devices are readable", devices.len()),
+            REMEDIATION,
+        ),
+        n => CheckResult::pass(
+            NAME,
+            format!("{} of {} event devices readable", n, devices.len()),
+        ),
+    }
+}
+
+/// Checks that `/dev/uinput` can be opened for writing, for injecting input
+///
+/// Skipped on platforms other than Linux.
+pub fn check_uinput() -> CheckResult {
+    #[cfg(target_os = "linux")]
+    {
+        check_uinput_path(Path::new("/dev/uinput"))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        CheckResult::skip("uinput", "only used on Linux")
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn check_uinput_path(path: &Path) -> CheckResult {
+    const NAME: &str = "uinput";
+
+    if !path.exists() {
+        return CheckResult::fail(
+            NAME,
+            format!("{} does not exist", path.display()),
+            "sudo modprobe uinput\nTo load it at boot: echo uinput | sudo tee /etc/modules-load.d/uinput.conf",
+        );
+    }
+    match std::fs::OpenOptions::new().write(true).open(path) {
+        Ok(_) => CheckResult::pass(NAME, format!("{} is writable", path.display())),
+        Err(e) => CheckResult::fail(
+            NAME,
+            format!("cannot open {} for writing: {}", path.display(), e),
+            "echo 'KERNEL==\"uinput\", GROUP=\"input\", MODE=\"0660\"' | sudo tee /etc/udev/rules.d/99-uinput.rules\n\
+             sudo udevadm control --reload-rules && sudo udevadm trigger\n\
+             sudo usermod -aG input $USER, then log out and back in",
+        ),
+    }
+}
+
+/// Checks which display server capture will go through
+///
+/// Pure Wayland sessions work only through the evdev backend, which is
+/// reported as a warning. Skipped on platforms other than Linux.
+pub fn check_display() -> CheckResult {
+    #[cfg(target_os = "linux")]
+    {
+        let x11 = std::env::var_os("DISPLAY").is_some();
+        let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+        match (x11, wayland) {
+            (true, _) => CheckResult::pass("display", "X11 display available"),
+            (false, true) => CheckResult::warn(
+                "display",
+                "Wayland session without XWayland; only the evdev backend can capture input",
+                "Make sure the input devices and uinput checks pass, or enable XWayland",
+            ),
+            (false, false) => CheckResult::fail(
+                "display",
+                "neither DISPLAY nor WAYLAND_DISPLAY is set",
+                "Run MultiShiva from inside a graphical session",
+            ),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        CheckResult::skip("display", "only checked on Linux")
+    }
+}
+
+/// Checks that `manager` can store, read back and delete a credential
+///
+/// A throwaway credential is used so the stored PSK is never touched.
+pub fn check_keyring(manager: &KeyringManager) -> CheckResult {
+    const NAME: &str = "keyring";
+    let remediation = if cfg!(target_os = "linux") {
+        "Start a Secret Service provider (gnome-keyring or KWallet) and unlock it,\nor keep the PSK in the configuration file"
+    } else {
+        "Unlock the system keychain, or keep the PSK in the configuration file"
+    };
+
+    let probe = "multishiva-doctor-probe";
+    if let Err(e) = manager.set_credential(KEYRING_PROBE_KEY, probe) {
+        return CheckResult::fail(
+            NAME,
+            format!("cannot store a credential: {:#}", e),
+            remediation,
+        );
+    }
+    let read_back = manager.get_credential(KEYRING_PROBE_KEY);
+    let deleted = manager.delete_credential(KEYRING_PROBE_KEY);
+
+    match (read_back, deleted) {
+        (Ok(value), Ok(())) if value == probe => {
+            CheckResult::pass(NAME, "credentials can be stored, read and deleted")
+        }
+        (Ok(_), _) => {
+            CheckResult::fail(NAME, "stored credential read back differently", remediation)
+        }
+        (Err(e), _) => CheckResult::fail(
+            NAME,
+            format!("cannot read a stored credential: {:#}", e),
+            remediation,
+        ),
+    }
+}
+
+/// Checks that the PSK is in `manager`'s keyring when the configuration expects it there
+///
+/// Skipped when the PSK is kept in the configuration file and the keyring
+/// holds none.
+pub fn check_stored_psk(manager: &KeyringManager, psk_in_keyring: bool) -> CheckResult {
+    const NAME: &str = "stored psk";
+
+    match (manager.has_psk(), psk_in_keyring) {
+        (true, true) => CheckResult::pass(NAME, "the configured PSK is in the keyring"),
+        (true, false) => CheckResult::warn(
+            NAME,
+            "a PSK is in the keyring, but the configuration uses its own",
+            "Use it with multishiva keyring migrate --from-config <file>,\nor remove it with multishiva keyring delete",
+        ),
+        (false, true) => CheckResult::fail(
+            NAME,
+            "the configuration expects the PSK in the keyring, but none is stored",
+            "multishiva keyring set",
+        ),
+        (false, false) => CheckResult::skip(NAME, "the PSK is kept in the configuration file"),
+    }
+}
+
+/// Checks that a multicast socket can be opened for mDNS discovery
+///
+/// The socket shares the mDNS port with any running responder (e.g. Avahi).
+/// Discovery is optional when `host_address` is configured, so failures are
+/// warnings.
+pub fn check_mdns() -> CheckResult {
+    const NAME: &str = "mdns";
+
+    match mdns_sd::ServiceDaemon::new() {
+        Ok(daemon) => {
+            let _ = daemon.shutdown();
+            CheckResult::pass(NAME, "mDNS daemon started")
+        }
+        Err(e) => CheckResult::warn(
+            NAME,
+            format!("cannot start mDNS discovery: {}", e),
+            "Allow UDP port 5353 (multicast) in the firewall,\nor set host_address on agents to skip discovery",
+        ),
+    }
+}
+
+/// Checks that the configuration at `path` loads and validates
+///
+/// Returns the configuration along with the result so later checks can use
+/// it.
+pub fn check_config(path: &Path) -> (CheckResult, Option<Config>) {
+    const NAME: &str = "config";
+
+    let loaded = Config::from_file(&path.to_string_lossy()).and_then(|config| {
+        config.validate()?;
+        Ok(config)
+    });
+    match loaded {
+        Ok(config) => (
+            CheckResult::pass(NAME, format!("{} is valid", path.display())),
+            Some(config),
+        ),
+        Err(e) if !path.exists() => (
+            CheckResult::fail(
+                NAME,
+                format!("{} not found: {:#}", path.display(), e),
+                format!("multishiva init --config {}", path.display()),
+            ),
+            None,
+        ),
+        Err(e) => (
+            CheckResult::fail(
+                NAME,
+                format!("{}: {:#}", path.display(), e),
+                "Fix the reported field, or compare with multishiva.yml.example",
+            ),
+            None,
+        ),
+    }
+}
+
+/// Checks that `port` is free to listen on
+pub fn check_port(port: u16) -> CheckResult {
+    const NAME: &str = "port";
+
+    match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(_) => CheckResult::pass(NAME, format!("TCP port {} is free", port)),
+        Err(e) => CheckResult::fail(
+            NAME,
+            format!("cannot listen on TCP port {}: {}", port, e),
+            "Stop the other process using it (is MultiShiva already running?),\nor change port in the configuration",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_config() {
+        let dir = TempDir::new().unwrap();
+
+        let missing = dir.path().join("missing.yml");
+        let (result, config) = check_config(&missing);
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert!(result.remediation.unwrap().contains("multishiva init"));
+        assert!(config.is_none());
+
+        let invalid = dir.path().join("invalid.yml");
+        std::fs::write(
+            &invalid,
+            "self_name: \"\"\nmode: host\nport: 53421\nedges: {}\ntls:\n  psk: key\n",
+        )
+        .unwrap();
+        assert_eq!(check_config(&invalid).0.status, CheckStatus::Fail);
+
+        let valid = dir.path().join("valid.yml");
+        std::fs::write(
+            &valid,
+            "self_name: desk\nmode: host\nport: 53421\nedges: {}\ntls:\n  psk: key\n",
+        )
+        .unwrap();
+        let (result, config) = check_config(&valid);
+        assert_eq!(result.status, CheckStatus::Pass);
+        assert_eq!(config.unwrap().self_name, "desk");
+    }
+
+    #[test]
+    fn test_check_stored_psk_without_stored_psk() {
+        // No PSK can be stored under a service nothing writes to
+        let manager = KeyringManager::with_service("multishiva-doctor-test-empty".to_string());
+
+        let result = check_stored_psk(&manager, true);
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert_eq!(
+            result.remediation.as_deref(),
+            Some("multishiva keyring set")
+        );
+        assert_eq!(check_stored_psk(&manager, false).status, CheckStatus::Skip);
+    }
+
+    #[test]
+    fn test_check_port_in_use() {
+        let listener = TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let result = check_port(port);
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert!(result.remediation.is_some());
+
+        drop(listener);
+        assert_eq!(check_port(port).status, CheckStatus::Pass);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_check_input_dir_without_devices() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(check_input_dir(dir.path()).status, CheckStatus::Fail);
+
+        std::fs::write(dir.path().join("event0"), "").unwrap();
+        assert_eq!(check_input_dir(dir.path()).status, CheckStatus::Pass);
+
+        let missing = dir.path().join("missing");
+        assert_eq!(check_uinput_path(&missing).status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_report_counts_failures_and_serializes() {
+        let report = DoctorReport {
+            checks: vec![
+                CheckResult::pass("config", "valid"),
+                CheckResult::warn("mdns", "blocked", "open 5353"),
+                CheckResult::fail("port", "in use", "change port"),
+            ],
+        };
+        assert_eq!(report.failures(), 1);
+
+        let text = report.to_string();
+        assert!(text.contains("→ change port"));
+        assert!(text.contains("1 check(s) failed"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["checks"][2]["status"], "fail");
+        assert_eq!(json["checks"][0]["remediation"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,787 @@
+/// Edge crossing with friction
+///
+/// Decides when the cursor has dwelled long enough in the zone of a screen
+/// edge to hand focus to the neighbor on that edge. The cursor must stay in
+/// the zone of the same edge for `friction_ms`; leaving the zone resets the
+/// timer. A friction of zero transfers as soon as the zone is entered.
+///
+/// Time is passed in explicitly, so the controller can be driven by a real
+/// clock in production and by hand-made instants in tests.
+///
+/// [`detect_crossing`] turns a cursor position into the neighbor and entry
+/// point of a transfer. Agents use [`return_edge`] to find the edge that sends
+/// focus back to the machine it came from.
+///
+/// Screens of different resolutions are bridged with [`scale_position`], for
+/// the entry point, and [`DeltaScaler`], for the cursor deltas forwarded while
+/// an agent has focus.
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::core::topology::{Edge, EdgeTrigger};
+
+/// A focus transfer decided by the [`EdgeCrossingController`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crossing {
+    /// Edge the cursor leaves through.
+    pub edge: Edge,
+    /// Machine receiving focus.
+    pub target: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CrossingState {
+    /// The cursor is not dwelling at an edge with a neighbor.
+    Idle,
+    /// The cursor entered the edge zone at `since` and must stay there.
+    Pending { crossing: Crossing, since: Instant },
+    /// Focus was handed to a remote machine.
+    Transferred,
+}
+
+/// Tracks the cursor at screen edges and applies the friction delay.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::edge_crossing::EdgeCrossingController;
+/// use multishiva::core::topology::Edge;
+/// use std::time::{Duration, Instant};
+///
+/// let mut controller = EdgeCrossingController::new(Duration::from_millis(100));
+/// let start = Instant::now();
+///
+/// controller.update(Some((Edge::Right, "agent1")), start);
+/// assert!(controller.poll(start + Duration::from_millis(50)).is_none());
+///
+/// let crossing = controller.poll(start + Duration::from_millis(100)).unwrap();
+/// assert_eq!(crossing.target, "agent1");
+/// assert!(controller.is_transferred());
+/// ```
+#[derive(Debug, Clone)]
+pub struct EdgeCrossingController {
+    friction: Duration,
+    state: CrossingState,
+    held: bool,
+}
+
+impl EdgeCrossingController {
+    /// Creates a controller requiring the cursor to dwell `friction` at an edge.
+    pub fn new(friction: Duration) -> Self {
+        Self {
+            friction,
+            state: CrossingState::Idle,
+            held: false,
+        }
+    }
+
+    /// Returns the configured dwell time.
+    pub fn friction(&self) -> Duration {
+        self.friction
+    }
+
+    /// Changes the dwell time, e.g. after the configuration was reloaded.
+    ///
+    /// A pending transfer becomes due `friction` after the cursor entered the
+    /// zone.
+    pub fn set_friction(&mut self, friction: Duration) {
+        self.friction = friction;
+    }
+
+    /// Feeds the current cursor zone observed at `now`.
+    ///
+    /// `zone` is the edge the cursor is in together with the neighbor on that
+    /// edge, or `None` when the cursor is away from any edge with a neighbor.
+    /// Entering a zone starts the timer, staying in the same zone keeps it
+    /// running, and leaving or switching zones restarts it. Has no effect once
+    /// focus was transferred, until [`EdgeCrossingController::reset`].
+    pub fn update(&mut self, zone: Option<(Edge, &str)>, now: Instant) {
+        if self.is_transferred() {
+            return;
+        }
+
+        self.state = match (
+            zone,
+            std::mem::replace(&mut self.state, CrossingState::Idle),
+        ) {
+            (Some((edge, target)), CrossingState::Pending { crossing, since })
+                if crossing.edge == edge && crossing.target == target =>
+            {
+                CrossingState::Pending { crossing, since }
+            }
+            (Some((edge, target)), _) => {
+                tracing::info!(
+                    "🖱️  Mouse at {} edge - transferring to '{}' in {:?}",
+                    edge,
+                    target,
+                    self.friction
+                );
+                CrossingState::Pending {
+                    crossing: Crossing {
+                        edge,
+                        target: target.to_string(),
+                    },
+                    since: now,
+                }
+            }
+            (None, CrossingState::Pending { crossing, .. }) => {
+                tracing::debug!(
+                    "Cursor left {} edge zone, transfer to '{}' cancelled",
+                    crossing.edge,
+                    crossing.target
+                );
+                CrossingState::Idle
+            }
+            (None, _) => CrossingState::Idle,
+        };
+    }
+
+    /// Holds back the pending transfer, e.g. while a mouse button is held.
+    ///
+    /// The cursor keeps being tracked while held: once released in the zone
+    /// it was dwelling in, the transfer is due as soon as the cursor has
+    /// dwelled `friction` there, counting the time it was held.
+    pub fn set_held(&mut self, held: bool) {
+        if held && !self.held && self.is_pending() {
+            tracing::debug!("Mouse button held, deferring edge crossing");
+        }
+        self.held = held;
+    }
+
+    /// Returns whether transfers are held back.
+    pub fn is_held(&self) -> bool {
+        self.held
+    }
+
+    /// Returns when the pending transfer becomes due, if one is pending.
+    ///
+    /// Returns `None` while transfers are held back.
+    pub fn deadline(&self) -> Option<Instant> {
+        match &self.state {
+            CrossingState::Pending { since, .. } if !self.held => Some(*since + self.friction),
+            _ => None,
+        }
+    }
+
+    /// Returns the pending transfer if the cursor has dwelled long enough by `now`.
+    ///
+    /// The controller then considers focus transferred. Call
+    /// [`EdgeCrossingController::reset`] if the transfer could not be performed.
+    pub fn poll(&mut self, now: Instant) -> Option<Crossing> {
+        if self.deadline().is_none_or(|deadline| now < deadline) {
+            return None;
+        }
+        match std::mem::replace(&mut self.state, CrossingState::Transferred) {
+            CrossingState::Pending { crossing, .. } => Some(crossing),
+            other => {
+                self.state = other;
+                None
+            }
+        }
+    }
+
+    /// Returns to the idle state, e.g. when focus comes back to this machine.
+    pub fn reset(&mut self) {
+        self.state = CrossingState::Idle;
+    }
+
+    /// Returns whether a transfer is waiting for the dwell time to elapse.
+    pub fn is_pending(&self) -> bool {
+        matches!(self.state, CrossingState::Pending { .. })
+    }
+
+    /// Returns whether focus was handed to a remote machine.
+    pub fn is_transferred(&self) -> bool {
+        self.state == CrossingState::Transferred
+    }
+}
+
+/// Returns the screen edge closest to `position`.
+///
+/// Ties are broken in [`Edge::ALL`] order.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::edge_crossing::nearest_edge;
+/// use multishiva::core::topology::Edge;
+///
+/// assert_eq!(nearest_edge((10, 540), (1920, 1080)), Edge::Left);
+/// assert_eq!(nearest_edge((960, 1069), (1920, 1080)), Edge::Bottom);
+/// ```
+pub fn nearest_edge(position: (i32, i32), screen_size: (u32, u32)) -> Edge {
+    let (x, y) = position;
+    let (width, height) = (screen_size.0 as i32, screen_size.1 as i32);
+    let distance = |edge: Edge| match edge {
+        Edge::Right => width - x,
+        Edge::Left => x,
+        Edge::Top => y,
+        Edge::Bottom => height - y,
+    };
+    Edge::ALL
+        .into_iter()
+        .min_by_key(|edge| distance(*edge))
+        .unwrap_or(Edge::Left)
+}
+
+/// Returns whether `position` lies in the zone of `edge`, `threshold` pixels wide.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::edge_crossing::is_in_edge_zone;
+/// use multishiva::core::topology::Edge;
+///
+/// assert!(is_in_edge_zone(Edge::Top, (960, 3), (1920, 1080), 10));
+/// assert!(!is_in_edge_zone(Edge::Top, (960, 540), (1920, 1080), 10));
+/// ```
+pub fn is_in_edge_zone(
+    edge: Edge,
+    position: (i32, i32),
+    screen_size: (u32, u32),
+    threshold: i32,
+) -> bool {
+    let (x, y) = position;
+    match edge {
+        Edge::Right => x > screen_size.0 as i32 - threshold,
+        Edge::Left => x < threshold,
+        Edge::Top => y < threshold,
+        Edge::Bottom => y > screen_size.1 as i32 - threshold,
+    }
+}
+
+/// Returns the edge whose zone contains `position`, if any.
+///
+/// Left and right take precedence over top and bottom in the corners.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::edge_crossing::edge_at;
+/// use multishiva::core::topology::Edge;
+///
+/// assert_eq!(edge_at((1915, 540), (1920, 1080), 10), Some(Edge::Right));
+/// assert_eq!(edge_at((960, 540), (1920, 1080), 10), None);
+/// ```
+pub fn edge_at(position: (i32, i32), screen_size: (u32, u32), threshold: i32) -> Option<Edge> {
+    let (x, y) = position;
+    if x < threshold {
+        Some(Edge::Left)
+    } else if x > screen_size.0 as i32 - threshold {
+        Some(Edge::Right)
+    } else if y < threshold {
+        Some(Edge::Top)
+    } else if y > screen_size.1 as i32 - threshold {
+        Some(Edge::Bottom)
+    } else {
+        None
+    }
+}
+
+/// Returns where the cursor enters the neighbor when leaving through `edge`.
+///
+/// The cursor is placed just inside the opposite edge of a screen of
+/// `screen_size`, outside its edge zone so it does not bounce straight back.
+/// The coordinate along the edge is kept, within the screen.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::edge_crossing::entry_point;
+/// use multishiva::core::topology::Edge;
+///
+/// assert_eq!(entry_point(Edge::Right, (1915, 300), (1920, 1080), 10), (10, 300));
+/// assert_eq!(entry_point(Edge::Top, (700, 2), (1920, 1080), 10), (700, 1069));
+/// ```
+pub fn entry_point(
+    edge: Edge,
+    position: (i32, i32),
+    screen_size: (u32, u32),
+    threshold: i32,
+) -> (i32, i32) {
+    let x = position.0.clamp(0, (screen_size.0 as i32 - 1).max(0));
+    let y = position.1.clamp(0, (screen_size.1 as i32 - 1).max(0));
+    match edge {
+        Edge::Left => (screen_size.0 as i32 - threshold - 1, y),
+        Edge::Right => (threshold, y),
+        Edge::Top => (x, screen_size.1 as i32 - threshold - 1),
+        Edge::Bottom => (x, threshold),
+    }
+}
+
+/// Detects a cursor at `position` in the zone of an edge with a neighbor.
+///
+/// `edges` maps edge names to neighbors, as in the `edges` configuration.
+/// With [`EdgeTrigger::FullEdge`] the zone is `threshold` pixels wide along
+/// each edge; with [`EdgeTrigger::Hotspot`] it is the corner box, crossing
+/// through the first of the corner's edges that has a neighbor.
+/// Returns the edge, its neighbor and the entry point on the neighbor, which
+/// is assumed to have the same screen size.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::edge_crossing::detect_crossing;
+/// use multishiva::core::topology::{Edge, EdgeTrigger};
+/// use std::collections::HashMap;
+///
+/// let edges = HashMap::from([("left".to_string(), "agent1".to_string())]);
+/// let trigger = EdgeTrigger::FullEdge;
+///
+/// let crossing = detect_crossing((1920, 1080), 10, &trigger, &edges, (3, 400));
+/// assert_eq!(crossing, Some((Edge::Left, "agent1".to_string(), (1909, 400))));
+///
+/// // No neighbor configured on the right
+/// assert_eq!(detect_crossing((1920, 1080), 10, &trigger, &edges, (1915, 400)), None);
+/// ```
+pub fn detect_crossing(
+    screen_size: (u32, u32),
+    threshold: i32,
+    trigger: &EdgeTrigger,
+    edges: &HashMap<String, String>,
+    position: (i32, i32),
+) -> Option<(Edge, String, (i32, i32))> {
+    let (edge, neighbor) = match *trigger {
+        EdgeTrigger::FullEdge => {
+            let edge = edge_at(position, screen_size, threshold)?;
+            (edge, edges.get(edge.as_str())?)
+        }
+        EdgeTrigger::Hotspot { corner, size_px } => {
+            if !corner.contains(position, screen_size, size_px) {
+                return None;
+            }
+            corner
+                .edges()
+                .into_iter()
+                .find_map(|edge| Some((edge, edges.get(edge.as_str())?)))?
+        }
+    };
+    Some((
+        edge,
+        neighbor.clone(),
+        entry_point(edge, position, screen_size, threshold),
+    ))
+}
+
+/// Picks the edge through which an agent hands focus back to `from`, the
+/// machine that granted it.
+///
+/// The edge of `edges` (the agent's `edges` configuration) whose neighbor is
+/// `from` is used, the one nearest to the `FocusGrant` entry position if
+/// several are. Without one, the host placing the cursor next to the edge
+/// facing it, the edge nearest to the entry position is used if it has a
+/// neighbor, then the only configured edge if there is exactly one. Returns
+/// the edge with its configured neighbor.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::edge_crossing::return_edge;
+/// use multishiva::core::topology::Edge;
+/// use std::collections::HashMap;
+///
+/// let edges = HashMap::from([
+///     ("top".to_string(), "host".to_string()),
+///     ("right".to_string(), "agent2".to_string()),
+/// ]);
+///
+/// // Focus returns upwards to the host, wherever the cursor entered
+/// let (edge, neighbor) = return_edge(&edges, "host", (10, 540), (1920, 1080));
+/// assert_eq!(edge, Edge::Top);
+/// assert_eq!(neighbor.as_deref(), Some("host"));
+/// ```
+pub fn return_edge(
+    edges: &HashMap<String, String>,
+    from: &str,
+    entry: (i32, i32),
+    screen_size: (u32, u32),
+) -> (Edge, Option<String>) {
+    let entry_edge = nearest_edge(entry, screen_size);
+    let leads_back = |edge: &Edge| edges.get(edge.as_str()).is_some_and(|n| n == from);
+    if let Some(edge) = std::iter::once(entry_edge)
+        .chain(Edge::ALL)
+        .find(leads_back)
+    {
+        return (edge, Some(from.to_string()));
+    }
+
+    if let Some(neighbor) = edges.get(entry_edge.as_str()) {
+        return (entry_edge, Some(neighbor.clone()));
+    }
+
+    let mut configured = edges
+        .iter()
+        .filter_map(|(edge, neighbor)| Some((edge.parse::<Edge>().ok()?, neighbor)));
+    match (configured.next(), configured.next()) {
+        (Some((edge, neighbor)), None) => (edge, Some(neighbor.clone())),
+        _ => (entry_edge, None),
+    }
+}
+
+/// Maps `position` on a screen of `from_size` to the same relative spot on `to_size`.
+///
+/// A zero dimension on either side leaves that coordinate unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::edge_crossing::scale_position;
+///
+/// assert_eq!(scale_position((960, 540), (1920, 1080), (3840, 2160)), (1920, 1080));
+/// assert_eq!(scale_position((960, 540), (0, 0), (3840, 2160)), (960, 540));
+/// ```
+pub fn scale_position(
+    position: (i32, i32),
+    from_size: (u32, u32),
+    to_size: (u32, u32),
+) -> (i32, i32) {
+    let scale = |value: i32, from: u32, to: u32| {
+        if from == 0 || to == 0 {
+            value
+        } else {
+            (value as f64 * to as f64 / from as f64).round() as i32
+        }
+    };
+    (
+        scale(position.0, from_size.0, to_size.0),
+        scale(position.1, from_size.1, to_size.1),
+    )
+}
+
+/// Scales cursor deltas from one screen resolution to another.
+///
+/// Moving 10px on a 1920px wide host moves 20px on a 3840px wide agent, so
+/// the cursor crosses both screens in the same hand movement. Fractions of a
+/// pixel are carried over to the next delta rather than lost to rounding.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::edge_crossing::DeltaScaler;
+///
+/// let mut scaler = DeltaScaler::new((1920, 1080), (3840, 2160));
+/// assert_eq!(scaler.scale(10, -5), (20, -10));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeltaScaler {
+    ratio: (f64, f64),
+    carry: (f64, f64),
+}
+
+impl DeltaScaler {
+    /// Creates a scaler from a screen of `from_size` to one of `to_size`.
+    ///
+    /// If any dimension is unknown (zero), deltas pass through unchanged.
+    pub fn new(from_size: (u32, u32), to_size: (u32, u32)) -> Self {
+        let known = [from_size.0, from_size.1, to_size.0, to_size.1]
+            .iter()
+            .all(|dim| *dim > 0);
+        if !known {
+            return Self::default();
+        }
+        Self {
+            ratio: (
+                to_size.0 as f64 / from_size.0 as f64,
+                to_size.1 as f64 / from_size.1 as f64,
+            ),
+            carry: (0.0, 0.0),
+        }
+    }
+
+    /// Returns the scaled delta, keeping the rounding remainder for next time.
+    pub fn scale(&mut self, dx: i32, dy: i32) -> (i32, i32) {
+        let exact_x = dx as f64 * self.ratio.0 + self.carry.0;
+        let exact_y = dy as f64 * self.ratio.1 + self.carry.1;
+        let (x, y) = (exact_x.round(), exact_y.round());
+        self.carry = (exact_x - x, exact_y - y);
+        (x as i32, y as i32)
+    }
+}
+
+impl Default for DeltaScaler {
+    /// A scaler that leaves deltas unchanged.
+    fn default() -> Self {
+        Self {
+            ratio: (1.0, 1.0),
+            carry: (0.0, 0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRICTION: Duration = Duration::from_millis(100);
+
+    fn ms(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_zero_friction_transfers_immediately() {
+        let mut controller = EdgeCrossingController::new(Duration::ZERO);
+        let start = Instant::now();
+        controller.update(Some((Edge::Left, "agent1")), start);
+        assert_eq!(
+            controller.poll(start),
+            Some(Crossing {
+                edge: Edge::Left,
+                target: "agent1".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_sustained_dwell_transfers_after_friction() {
+        let mut controller = EdgeCrossingController::new(FRICTION);
+        let start = Instant::now();
+        controller.update(Some((Edge::Right, "agent1")), start);
+        controller.update(Some((Edge::Right, "agent1")), ms(start, 50));
+        assert!(controller.poll(ms(start, 99)).is_none());
+
+        controller.update(Some((Edge::Right, "agent1")), ms(start, 100));
+        let crossing = controller.poll(ms(start, 100)).unwrap();
+        assert_eq!(crossing.target, "agent1");
+    }
+
+    #[test]
+    fn test_leaving_zone_cancels_transfer() {
+        let mut controller = EdgeCrossingController::new(FRICTION);
+        let start = Instant::now();
+        controller.update(Some((Edge::Right, "agent1")), start);
+        controller.update(None, ms(start, 60));
+        assert!(!controller.is_pending());
+        assert!(controller.poll(ms(start, 200)).is_none());
+    }
+
+    #[test]
+    fn test_reentering_zone_restarts_timer() {
+        let mut controller = EdgeCrossingController::new(FRICTION);
+        let start = Instant::now();
+        controller.update(Some((Edge::Right, "agent1")), start);
+        controller.update(None, ms(start, 60));
+        controller.update(Some((Edge::Right, "agent1")), ms(start, 80));
+
+        // Staying in the zone keeps the original entry time
+        controller.update(Some((Edge::Right, "agent1")), ms(start, 150));
+        assert_eq!(controller.deadline(), Some(ms(start, 180)));
+        assert!(controller.poll(ms(start, 150)).is_none());
+        assert!(controller.poll(ms(start, 180)).is_some());
+    }
+
+    #[test]
+    fn test_switching_edge_restarts_timer() {
+        let mut controller = EdgeCrossingController::new(FRICTION);
+        let start = Instant::now();
+        controller.update(Some((Edge::Right, "agent1")), start);
+        controller.update(Some((Edge::Bottom, "agent2")), ms(start, 90));
+        assert!(controller.poll(ms(start, 120)).is_none());
+
+        let crossing = controller.poll(ms(start, 190)).unwrap();
+        assert_eq!(crossing.edge, Edge::Bottom);
+        assert_eq!(crossing.target, "agent2");
+    }
+
+    #[test]
+    fn test_held_transfer_is_due_on_release() {
+        let mut controller = EdgeCrossingController::new(FRICTION);
+        let start = Instant::now();
+        controller.set_held(true);
+        controller.update(Some((Edge::Right, "agent1")), start);
+        assert!(controller.is_pending());
+        assert_eq!(controller.deadline(), None);
+        assert!(controller.poll(ms(start, 500)).is_none());
+
+        // Released in the zone after the dwell time: due right away
+        controller.set_held(false);
+        assert_eq!(controller.deadline(), Some(ms(start, 100)));
+        assert_eq!(controller.poll(ms(start, 500)).unwrap().target, "agent1");
+    }
+
+    #[test]
+    fn test_transferred_ignores_updates_until_reset() {
+        let mut controller = EdgeCrossingController::new(Duration::ZERO);
+        let start = Instant::now();
+        controller.update(Some((Edge::Right, "agent1")), start);
+        assert!(controller.poll(start).is_some());
+
+        controller.update(Some((Edge::Right, "agent1")), ms(start, 10));
+        assert!(controller.is_transferred());
+        assert!(controller.poll(ms(start, 10)).is_none());
+
+        controller.reset();
+        controller.update(Some((Edge::Right, "agent1")), ms(start, 20));
+        assert!(controller.poll(ms(start, 20)).is_some());
+    }
+
+    fn edges(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(edge, neighbor)| (edge.to_string(), neighbor.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_is_in_edge_zone_all_directions() {
+        const SCREEN: (u32, u32) = (1920, 1080);
+        let cases = [
+            (Edge::Left, (9, 540), (10, 540)),
+            (Edge::Right, (1911, 540), (1910, 540)),
+            (Edge::Top, (960, 9), (960, 10)),
+            (Edge::Bottom, (960, 1071), (960, 1070)),
+        ];
+        for (edge, inside, outside) in cases {
+            assert!(is_in_edge_zone(edge, inside, SCREEN, 10), "{} inside", edge);
+            assert!(
+                !is_in_edge_zone(edge, outside, SCREEN, 10),
+                "{} outside",
+                edge
+            );
+            // The other edges' zones do not reach the middle of this one
+            for other in Edge::ALL.into_iter().filter(|other| *other != edge) {
+                assert!(!is_in_edge_zone(other, inside, SCREEN, 10));
+            }
+        }
+    }
+
+    #[test]
+    fn test_return_edge_leads_to_granting_machine() {
+        const SCREEN: (u32, u32) = (1920, 1080);
+        let around = edges(&[("left", "host"), ("bottom", "host2"), ("right", "agent2")]);
+
+        // The edge facing the machine granting focus, wherever the cursor entered
+        assert_eq!(
+            return_edge(&around, "host", (960, 1069), SCREEN),
+            (Edge::Left, Some("host".to_string()))
+        );
+        assert_eq!(
+            return_edge(&around, "host2", (10, 500), SCREEN),
+            (Edge::Bottom, Some("host2".to_string()))
+        );
+
+        // Several edges face it: the one nearest to the entry position
+        let wrapping = edges(&[("left", "host"), ("right", "host")]);
+        assert_eq!(
+            return_edge(&wrapping, "host", (10, 500), SCREEN),
+            (Edge::Left, Some("host".to_string()))
+        );
+        assert_eq!(
+            return_edge(&wrapping, "host", (1909, 500), SCREEN),
+            (Edge::Right, Some("host".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_return_edge_follows_entry_side() {
+        const SCREEN: (u32, u32) = (1920, 1080);
+        let edges = edges(&[("left", "desk"), ("bottom", "host2"), ("right", "agent2")]);
+
+        // No edge faces the granting machine: it lies on the side entered through
+        assert_eq!(
+            return_edge(&edges, "host", (10, 500), SCREEN),
+            (Edge::Left, Some("desk".to_string()))
+        );
+        assert_eq!(
+            return_edge(&edges, "host", (960, 1069), SCREEN),
+            (Edge::Bottom, Some("host2".to_string()))
+        );
+
+        // Entry side not configured and several edges: trust the entry side
+        assert_eq!(
+            return_edge(&edges, "host", (960, 10), SCREEN),
+            (Edge::Top, None)
+        );
+    }
+
+    #[test]
+    fn test_return_edge_uses_single_configured_edge() {
+        let edges = edges(&[("top", "desk")]);
+        assert_eq!(
+            return_edge(&edges, "host", (10, 500), (1920, 1080)),
+            (Edge::Top, Some("desk".to_string()))
+        );
+        assert_eq!(
+            return_edge(&HashMap::new(), "host", (10, 500), (1920, 1080)),
+            (Edge::Left, None)
+        );
+    }
+
+    #[test]
+    fn test_detect_crossing_ignores_edges_without_neighbor() {
+        let edges = edges(&[("right", "agent1")]);
+        assert_eq!(
+            detect_crossing((1920, 1080), 10, &EdgeTrigger::FullEdge, &edges, (3, 500)),
+            None
+        );
+        assert_eq!(
+            detect_crossing((1920, 1080), 10, &EdgeTrigger::FullEdge, &edges, (960, 500)),
+            None
+        );
+        assert_eq!(
+            detect_crossing(
+                (1920, 1080),
+                10,
+                &EdgeTrigger::FullEdge,
+                &edges,
+                (1911, 500)
+            ),
+            Some((Edge::Right, "agent1".to_string(), (10, 500)))
+        );
+    }
+
+    #[test]
+    fn test_detect_crossing_in_hotspot_corner_only() {
+        use crate::core::topology::Corner;
+
+        let edges = edges(&[("right", "agent1"), ("top", "agent2")]);
+        let hotspot = EdgeTrigger::Hotspot {
+            corner: Corner::TopRight,
+            size_px: 50,
+        };
+        // Right edge outside the corner box
+        assert_eq!(
+            detect_crossing((1920, 1080), 10, &hotspot, &edges, (1915, 500)),
+            None
+        );
+        // Inside the box the horizontal edge wins
+        assert_eq!(
+            detect_crossing((1920, 1080), 10, &hotspot, &edges, (1890, 20)),
+            Some((Edge::Right, "agent1".to_string(), (10, 20)))
+        );
+
+        // Without a right neighbor the corner leads through the top edge
+        let edges = self::edges(&[("top", "agent2")]);
+        assert_eq!(
+            detect_crossing((1920, 1080), 10, &hotspot, &edges, (1890, 20)),
+            Some((Edge::Top, "agent2".to_string(), (1890, 1069)))
+        );
+    }
+
+    #[test]
+    fn test_delta_scaler_maps_host_pixels_proportionally() {
+        // 10 host px on a 1080p host is 20 px on a 4K agent
+        let mut scaler = DeltaScaler::new((1920, 1080), (3840, 2160));
+        assert_eq!(scaler.scale(10, 10), (20, 20));
+
+        // and 5 px on a 960x540 agent
+        let mut scaler = DeltaScaler::new((1920, 1080), (960, 540));
+        assert_eq!(scaler.scale(10, -10), (5, -5));
+
+        // 1920 -> 2560 is 13.33 px: the remainder is carried, not dropped
+        let mut scaler = DeltaScaler::new((1920, 1080), (2560, 1440));
+        let moved: Vec<_> = (0..3).map(|_| scaler.scale(10, 0)).collect();
+        assert_eq!(moved[0], (13, 0));
+        assert_eq!(moved.iter().map(|(dx, _)| dx).sum::<i32>(), 40);
+    }
+
+    #[test]
+    fn test_unknown_screen_size_does_not_scale() {
+        let mut scaler = DeltaScaler::new((0, 0), (3840, 2160));
+        assert_eq!(scaler.scale(10, 7), (10, 7));
+        assert_eq!(
+            scale_position((1915, 300), (1920, 1080), (0, 0)),
+            (1915, 300)
+        );
+    }
+}
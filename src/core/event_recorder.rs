@@ -0,0 +1,391 @@
+/// Recording and replay of input sessions
+///
+/// A recording (conventionally a `.msrec` file) is a sequence of frames, one
+/// per event:
+///
+/// - the time since the recording started, in microseconds, as a big-endian `u64`
+/// - the length of the payload as a big-endian `u32`
+/// - the [`Event`] in MessagePack
+///
+/// Recordings are made by `multishiva record` and `--record`, and played back
+/// by `multishiva replay`, `--replay` and the simulation.
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use crate::core::events::Event;
+use crate::core::input::InputHandler;
+
+/// An event of a recording, with when it was recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Microseconds since the recording started, from a monotonic clock
+    pub offset_us: u64,
+    /// The recorded event
+    pub event: Event,
+}
+
+/// Appends timestamped events to a recording.
+///
+/// The writer is flushed after every event so a recording survives a crash.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::event_recorder::{EventRecorder, EventReplayer};
+/// use multishiva::core::events::Event;
+///
+/// let mut recording = Vec::new();
+/// let mut recorder = EventRecorder::new(&mut recording);
+/// recorder.record(&Event::MouseMove { x: 10, y: 20 }).unwrap();
+/// recorder.record(&Event::FocusRelease).unwrap();
+/// assert_eq!(recorder.recorded(), 2);
+///
+/// let replayer = EventReplayer::from_reader(recording.as_slice()).unwrap();
+/// assert_eq!(replayer.events().len(), 2);
+/// assert_eq!(replayer.events()[1].event, Event::FocusRelease);
+/// ```
+pub struct EventRecorder<W: Write = BufWriter<File>> {
+    writer: W,
+    start: Instant,
+    recorded: usize,
+}
+
+impl EventRecorder {
+    /// Creates (or truncates) the recording file at `path`.
+    ///
+    /// A recording holds every key typed, so on Unix a new file is readable
+    /// by its owner only.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = options
+            .open(path)
+            .with_context(|| format!("Failed to create recording file: {:?}", path))?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> EventRecorder<W> {
+    /// Records to `writer`, starting the clock now.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            start: Instant::now(),
+            recorded: 0,
+        }
+    }
+
+    /// Appends `event`, stamped with the time elapsed since the recording started.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event cannot be serialized or written.
+    pub fn record(&mut self, event: &Event) -> Result<()> {
+        let offset_us = self.start.elapsed().as_micros() as u64;
+        let data = rmp_serde::to_vec(event).context("Failed to serialize recorded event")?;
+        self.writer.write_all(&offset_us.to_be_bytes())?;
+        self.writer.write_all(&(data.len() as u32).to_be_bytes())?;
+        self.writer.write_all(&data)?;
+        self.writer.flush()?;
+        self.recorded += 1;
+        Ok(())
+    }
+
+    /// Records every event received on `rx` until the channel closes.
+    ///
+    /// Returns the number of events recorded by this call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an event cannot be written; the events before it
+    /// are kept.
+    pub async fn record_all(&mut self, mut rx: mpsc::Receiver<Event>) -> Result<usize> {
+        let before = self.recorded;
+        while let Some(event) = rx.recv().await {
+            self.record(&event)?;
+        }
+        Ok(self.recorded - before)
+    }
+
+    /// Returns the number of events recorded so far.
+    pub fn recorded(&self) -> usize {
+        self.recorded
+    }
+}
+
+/// Plays back a recording made by [`EventRecorder`].
+pub struct EventReplayer {
+    events: Vec<RecordedEvent>,
+    speed: f64,
+}
+
+impl EventReplayer {
+    /// Reads the recording file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or holds an invalid record.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open recording file: {:?}", path))?;
+        Self::from_reader(BufReader::new(file))
+            .with_context(|| format!("Invalid recording file: {:?}", path))
+    }
+
+    /// Reads a recording from `reader`.
+    ///
+    /// A record cut short at the end, as left by a process killed while
+    /// recording, is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails or a complete record cannot be decoded.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        let mut events = Vec::new();
+        loop {
+            let mut offset_buf = [0u8; 8];
+            match reader.read_exact(&mut offset_buf) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+
+            let mut len_buf = [0u8; 4];
+            let mut data = Vec::new();
+            let complete = read_or_eof(&mut reader, &mut len_buf)? && {
+                data.resize(u32::from_be_bytes(len_buf) as usize, 0);
+                read_or_eof(&mut reader, &mut data)?
+            };
+            if !complete {
+                tracing::warn!("Ignoring truncated record at the end of the recording");
+                break;
+            }
+
+            let event = rmp_serde::from_slice(&data)
+                .with_context(|| format!("Invalid record #{}", events.len() + 1))?;
+            events.push(RecordedEvent {
+                offset_us: u64::from_be_bytes(offset_buf),
+                event,
+            });
+        }
+
+        Ok(Self { events, speed: 1.0 })
+    }
+
+    /// Returns the recorded events, in recording order.
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    /// Sets the playback speed multiplier (2.0 plays twice as fast).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` is not a positive finite number.
+    pub fn set_speed(&mut self, speed: f64) {
+        assert!(
+            speed.is_finite() && speed > 0.0,
+            "replay speed must be positive"
+        );
+        self.speed = speed;
+    }
+
+    /// Returns when the event recorded at `offset_us` plays, from the start of playback.
+    pub fn playback_offset(&self, offset_us: u64) -> Duration {
+        Duration::from_secs_f64(offset_us as f64 / 1_000_000.0 / self.speed)
+    }
+
+    /// Sends every event to `tx` at its recorded time, scaled by the speed.
+    ///
+    /// Returns the number of events sent, which is less than the recording
+    /// holds if the receiver was dropped.
+    pub async fn replay(&self, tx: &mpsc::Sender<Event>) -> usize {
+        let start = tokio::time::Instant::now();
+        for (sent, record) in self.events.iter().enumerate() {
+            tokio::time::sleep_until(start + self.playback_offset(record.offset_us)).await;
+            if tx.send(record.event.clone()).await.is_err() {
+                return sent;
+            }
+        }
+        self.events.len()
+    }
+
+    /// Injects every event into `handler` at its recorded time, scaled by the speed.
+    ///
+    /// Returns the number of events injected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an event cannot be injected; the events after it
+    /// are not played.
+    pub async fn replay_into<H: InputHandler>(&self, handler: &H) -> Result<usize> {
+        let start = tokio::time::Instant::now();
+        for (index, record) in self.events.iter().enumerate() {
+            tokio::time::sleep_until(start + self.playback_offset(record.offset_us)).await;
+            handler
+                .inject_event(record.event.clone())
+                .await
+                .with_context(|| format!("Failed to inject record #{}", index + 1))?;
+        }
+        Ok(self.events.len())
+    }
+}
+
+/// Fills `buf` from `reader`, returning `false` if the input ends first.
+fn read_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Collects injected events instead of touching the real devices.
+    #[derive(Default)]
+    struct CollectingHandler {
+        injected: Mutex<Vec<Event>>,
+    }
+
+    impl InputHandler for CollectingHandler {
+        async fn start_capture(&mut self, _tx: mpsc::Sender<Event>) -> Result<()> {
+            Ok(())
+        }
+
+        async fn stop_capture(&mut self) {}
+
+        async fn inject_event(&self, event: Event) -> Result<()> {
+            if event == Event::FocusRelease {
+                anyhow::bail!("rejected");
+            }
+            self.injected.lock().unwrap().push(event);
+            Ok(())
+        }
+
+        fn is_capturing(&self) -> bool {
+            false
+        }
+
+        fn get_screen_size(&self) -> (u32, u32) {
+            (1920, 1080)
+        }
+
+        fn get_cursor_position(&self) -> Result<(i32, i32)> {
+            Ok((0, 0))
+        }
+
+        fn check_permissions(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_frame_starts_with_timestamp() {
+        let mut recording = Vec::new();
+        let mut recorder = EventRecorder::new(&mut recording);
+        std::thread::sleep(Duration::from_millis(2));
+        recorder.record(&Event::FocusRelease).unwrap();
+
+        let payload = rmp_serde::to_vec(&Event::FocusRelease).unwrap();
+        let offset_us = u64::from_be_bytes(recording[..8].try_into().unwrap());
+        assert!(offset_us >= 2_000);
+        assert_eq!(
+            u32::from_be_bytes(recording[8..12].try_into().unwrap()) as usize,
+            payload.len()
+        );
+        assert_eq!(&recording[12..], payload.as_slice());
+    }
+
+    #[test]
+    fn test_recording_file_is_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.msrec");
+        let mut recorder = EventRecorder::create(&path).unwrap();
+        recorder.record(&Event::FocusRelease).unwrap();
+        drop(recorder);
+
+        assert_eq!(EventReplayer::open(&path).unwrap().events().len(), 1);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_all_until_channel_closes() {
+        let mut recording = Vec::new();
+        let mut recorder = EventRecorder::new(&mut recording);
+        let (tx, rx) = mpsc::channel(4);
+        tx.send(Event::MouseMove { x: 1, y: 2 }).await.unwrap();
+        tx.send(Event::FocusRelease).await.unwrap();
+        drop(tx);
+
+        assert_eq!(recorder.record_all(rx).await.unwrap(), 2);
+        assert_eq!(recorder.recorded(), 2);
+        let replayer = EventReplayer::from_reader(recording.as_slice()).unwrap();
+        assert_eq!(replayer.events()[0].event, Event::MouseMove { x: 1, y: 2 });
+    }
+
+    #[tokio::test]
+    async fn test_replay_into_injects_at_recorded_times() {
+        let mut replayer = EventReplayer {
+            events: vec![
+                RecordedEvent {
+                    offset_us: 0,
+                    event: Event::MouseMove { x: 1, y: 2 },
+                },
+                RecordedEvent {
+                    offset_us: 40_000,
+                    event: Event::MouseMove { x: 3, y: 4 },
+                },
+            ],
+            speed: 1.0,
+        };
+        replayer.set_speed(2.0);
+
+        let handler = CollectingHandler::default();
+        let start = Instant::now();
+        assert_eq!(replayer.replay_into(&handler).await.unwrap(), 2);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(
+            *handler.injected.lock().unwrap(),
+            vec![
+                Event::MouseMove { x: 1, y: 2 },
+                Event::MouseMove { x: 3, y: 4 }
+            ]
+        );
+
+        // A rejected event stops the replay
+        replayer.events.insert(
+            1,
+            RecordedEvent {
+                offset_us: 1_000,
+                event: Event::FocusRelease,
+            },
+        );
+        let error = replayer.replay_into(&handler).await.unwrap_err();
+        assert!(error.to_string().contains("#2"));
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::core::topology::Edge;
+
 /// Represents all possible events that can occur in the multishiva system.
 ///
 /// Events are the core communication mechanism for input handling and system state changes.
@@ -19,7 +21,7 @@ use serde::{Deserialize, Serialize};
 /// // Create a mouse click event
 /// let click_event = Event::MouseClick { button: MouseButton::Left };
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Event {
     /// Mouse cursor moved to a new position.
     MouseMove {
@@ -75,13 +77,286 @@ pub enum Event {
         x: i32,
         /// The vertical position where focus was granted
         y: i32,
+        /// Screen width of the machine granting focus, 0 if unknown
+        ///
+        /// With the height, lets the receiver scale cursor deltas to its own screen.
+        #[serde(default)]
+        host_width: u32,
+        /// Screen height of the machine granting focus, 0 if unknown
+        #[serde(default)]
+        host_height: u32,
     },
 
     /// Focus was released from the current target.
     FocusRelease,
 
-    /// Periodic heartbeat event for keepalive or timing purposes.
-    Heartbeat,
+    /// Periodic heartbeat, answered with [`Event::HeartbeatAck`] to measure latency.
+    Heartbeat {
+        /// Sender's clock when the heartbeat was sent, in milliseconds
+        sent_at_ms: u64,
+    },
+
+    /// Clipboard content copied on one machine, to be applied on the others.
+    ClipboardSync {
+        /// Name of the machine the content was copied on
+        source: String,
+        /// The copied text
+        text: String,
+    },
+
+    /// First message sent by an agent after the PSK handshake.
+    Hello {
+        /// Configured name of the agent machine
+        name: String,
+        /// Width of the agent's screen in pixels
+        screen_width: u32,
+        /// Height of the agent's screen in pixels
+        screen_height: u32,
+    },
+
+    /// Host asks an agent to switch to a new pre-shared key.
+    PskRotate {
+        /// SHA-256 hash of the new PSK, as computed by `compute_psk_hash`
+        new_psk_hash: String,
+        /// HMAC-SHA256 of `new_psk_hash` keyed with the current PSK, hex-encoded
+        signature: String,
+    },
+
+    /// Agent confirms it switched to the new pre-shared key.
+    PskRotateAck {
+        /// Hash of the PSK the agent now uses
+        new_psk_hash: String,
+    },
+
+    /// Mouse moved by a relative amount, as reported by the mouse itself.
+    ///
+    /// Sent instead of [`Event::MouseMove`] while the host's devices are
+    /// grabbed, where the host cursor no longer moves.
+    MouseMoveRelative {
+        /// Horizontal movement in pixels (positive = right)
+        dx: i32,
+        /// Vertical movement in pixels (positive = down)
+        dy: i32,
+    },
+
+    /// Focus granted to the last machine of `path`, relayed by the ones in between.
+    ///
+    /// Used when the target is several hops away, e.g. host → agent1 → agent2.
+    FocusGrantChain {
+        /// Machines from the sender to the target, both included
+        path: Vec<String>,
+        /// The horizontal position where focus is granted on the target
+        x: i32,
+        /// The vertical position where focus is granted on the target
+        y: i32,
+    },
+
+    /// Part of clipboard content too large for a single [`Event::ClipboardSync`].
+    ///
+    /// The receiver joins the `total` chunks of transfer `id` in `seq` order.
+    ClipboardChunk {
+        /// Name of the machine the content was copied on
+        source: String,
+        /// Transfer identifier, unique per source
+        id: u64,
+        /// Position of this chunk, from 0
+        seq: u32,
+        /// Number of chunks in the transfer
+        total: u32,
+        /// This chunk's part of the text
+        data: String,
+    },
+
+    /// The kill switch combination was pressed and input capture stopped.
+    ///
+    /// Emitted locally by the input handler, never sent over the network.
+    KillSwitch,
+
+    /// Text typed as composed characters rather than physical keys.
+    ///
+    /// Carries what [`Event::KeyPress`] cannot, such as accented letters,
+    /// emoji or IME output. It is not a key and has no scancode: the injecting
+    /// side types the characters whatever its keyboard layout.
+    TextInput {
+        /// The composed text
+        text: String,
+    },
+
+    /// The host refused the agent's [`Event::Hello`] and closes the connection.
+    Refused {
+        /// Why the agent was refused, to be shown to the user
+        reason: String,
+    },
+
+    /// Mouse wheel or touchpad scrolled by fractions of a detent.
+    ///
+    /// Deltas are in 1/120ths of a wheel detent ([`SCROLL_DETENT`]), as in
+    /// `REL_WHEEL_HI_RES` and Windows' `WHEEL_DELTA`, with the same directions
+    /// as [`Event::MouseScroll`]. Peers that cannot inject partial detents
+    /// scroll once a whole detent has accumulated.
+    MouseScrollPrecise {
+        /// Horizontal scroll amount, in 1/120 detents
+        dx_120: i32,
+        /// Vertical scroll amount, in 1/120 detents
+        dy_120: i32,
+    },
+
+    /// The peer is shutting down and closes the connection after this frame.
+    ///
+    /// Everything queued before it has been sent, so the receiver can release
+    /// focus and any input left held right away instead of waiting for the
+    /// heartbeat timeout.
+    Disconnect {
+        /// Why the peer is leaving, for the logs
+        reason: String,
+    },
+
+    /// Answer to an [`Event::Heartbeat`], echoing its timestamp.
+    HeartbeatAck {
+        /// `sent_at_ms` of the heartbeat being answered
+        sent_at_ms: u64,
+    },
+
+    /// Sent by the host to each agent every second to measure its latency.
+    Ping {
+        /// Number of the ping on this connection
+        seq: u64,
+        /// Host's clock when the ping was sent, in milliseconds
+        sent_at_ms: u64,
+    },
+
+    /// The agent's immediate answer to an [`Event::Ping`], echoing its fields.
+    Pong {
+        /// `seq` of the ping being answered
+        seq: u64,
+        /// `sent_at_ms` of the ping being answered
+        sent_at_ms: u64,
+    },
+
+    /// The focused agent's cursor reached an edge of its screen.
+    ///
+    /// Sent to the host, which looks up the neighbor on that edge and moves
+    /// focus there, to another agent or back to itself.
+    EdgeReached {
+        /// Edge of the agent's screen the cursor reached
+        edge: Edge,
+        /// Horizontal cursor position on the agent's screen
+        x: i32,
+        /// Vertical cursor position on the agent's screen
+        y: i32,
+    },
+
+    /// Mouse wheel scrolled while modifier keys were held, e.g. Ctrl+scroll to zoom.
+    ///
+    /// Captured instead of [`Event::MouseScroll`] when a modifier is held. The
+    /// modifier key presses are sent as well, so a peer may inject it as a
+    /// plain scroll.
+    MouseScrollWithModifiers {
+        /// Horizontal scroll amount (positive = right, negative = left)
+        delta_x: i64,
+        /// Vertical scroll amount (positive = down, negative = up)
+        delta_y: i64,
+        /// Modifier keys held while scrolling
+        modifiers: Modifiers,
+    },
+}
+
+impl Event {
+    /// Attaches the held `modifiers` to an [`Event::MouseScroll`].
+    ///
+    /// Other events, and scrolls with no modifier held, are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::events::{Event, Modifiers};
+    ///
+    /// let scroll = Event::MouseScroll { delta_x: 0, delta_y: -1 };
+    /// assert_eq!(
+    ///     scroll.clone().with_modifiers(Modifiers::CTRL),
+    ///     Event::MouseScrollWithModifiers { delta_x: 0, delta_y: -1, modifiers: Modifiers::CTRL }
+    /// );
+    /// assert_eq!(scroll.clone().with_modifiers(Modifiers::empty()), scroll);
+    /// ```
+    pub fn with_modifiers(self, modifiers: Modifiers) -> Self {
+        match self {
+            Event::MouseScroll { delta_x, delta_y } if !modifiers.is_empty() => {
+                Event::MouseScrollWithModifiers {
+                    delta_x,
+                    delta_y,
+                    modifiers,
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// Returns `true` for large payloads that travel behind input and focus events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::events::Event;
+    ///
+    /// let sync = Event::ClipboardSync {
+    ///     source: "host".to_string(),
+    ///     text: "copied".to_string(),
+    /// };
+    /// assert!(sync.is_bulk());
+    /// assert!(!Event::MouseMove { x: 1, y: 2 }.is_bulk());
+    /// ```
+    pub fn is_bulk(&self) -> bool {
+        matches!(
+            self,
+            Event::ClipboardSync { .. } | Event::ClipboardChunk { .. }
+        )
+    }
+}
+
+/// Units of [`Event::MouseScrollPrecise`] in one wheel detent.
+pub const SCROLL_DETENT: i32 = 120;
+
+bitflags::bitflags! {
+    /// Modifier keys held during an event, left and right keys alike.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::events::{Key, Modifiers};
+    ///
+    /// let held = Modifiers::from_keys(&[Key::ControlRight, Key::KeyA, Key::ShiftLeft]);
+    /// assert_eq!(held, Modifiers::CTRL | Modifiers::SHIFT);
+    /// assert!(Modifiers::of_key(&Key::KeyA).is_empty());
+    /// ```
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    pub struct Modifiers: u8 {
+        /// Either Control key
+        const CTRL = 1 << 0;
+        /// Either Shift key
+        const SHIFT = 1 << 1;
+        /// Either Alt key
+        const ALT = 1 << 2;
+        /// Either Meta key: Windows, Command or Super
+        const META = 1 << 3;
+    }
+}
+
+impl Modifiers {
+    /// Returns the flag of a modifier key, empty for any other key.
+    pub fn of_key(key: &Key) -> Self {
+        match key {
+            Key::ControlLeft | Key::ControlRight => Modifiers::CTRL,
+            Key::ShiftLeft | Key::ShiftRight => Modifiers::SHIFT,
+            Key::AltLeft | Key::AltRight => Modifiers::ALT,
+            Key::MetaLeft | Key::MetaRight => Modifiers::META,
+            _ => Modifiers::empty(),
+        }
+    }
+
+    /// Returns the modifiers among `keys`, e.g. the keys currently held.
+    pub fn from_keys<'a>(keys: impl IntoIterator<Item = &'a Key>) -> Self {
+        keys.into_iter().map(Self::of_key).collect()
+    }
 }
 
 /// Represents the physical buttons on a mouse.
@@ -109,6 +384,12 @@ pub enum MouseButton {
 
     /// The tertiary (middle) mouse button, typically a scroll wheel click.
     Middle,
+
+    /// The "back" side button found on many mice.
+    Back,
+
+    /// The "forward" side button found on many mice.
+    Forward,
 }
 
 /// Represents keyboard keys that can be pressed or released.
@@ -214,6 +495,164 @@ pub enum Key {
     Backspace,
     /// The Tab key for indentation and navigation.
     Tab,
+    /// The Delete key for deleting characters forward.
+    Delete,
+
+    // Function keys
+    /// The F1 function key.
+    F1,
+    /// The F2 function key.
+    F2,
+    /// The F3 function key.
+    F3,
+    /// The F4 function key.
+    F4,
+    /// The F5 function key.
+    F5,
+    /// The F6 function key.
+    F6,
+    /// The F7 function key.
+    F7,
+    /// The F8 function key.
+    F8,
+    /// The F9 function key.
+    F9,
+    /// The F10 function key.
+    F10,
+    /// The F11 function key.
+    F11,
+    /// The F12 function key.
+    F12,
+
+    // Number row
+    /// The 0 key of the number row.
+    Num0,
+    /// The 1 key of the number row.
+    Num1,
+    /// The 2 key of the number row.
+    Num2,
+    /// The 3 key of the number row.
+    Num3,
+    /// The 4 key of the number row.
+    Num4,
+    /// The 5 key of the number row.
+    Num5,
+    /// The 6 key of the number row.
+    Num6,
+    /// The 7 key of the number row.
+    Num7,
+    /// The 8 key of the number row.
+    Num8,
+    /// The 9 key of the number row.
+    Num9,
+
+    // Punctuation
+    /// The minus/underscore key.
+    Minus,
+    /// The equal/plus key.
+    Equal,
+    /// The left bracket/brace key.
+    LeftBracket,
+    /// The right bracket/brace key.
+    RightBracket,
+    /// The semicolon/colon key.
+    SemiColon,
+    /// The quote/double-quote key.
+    Quote,
+    /// The backquote/tilde key.
+    BackQuote,
+    /// The backslash/pipe key.
+    BackSlash,
+    /// The extra backslash key of ISO keyboards, next to left Shift.
+    IntlBackslash,
+    /// The comma/less-than key.
+    Comma,
+    /// The period/greater-than key.
+    Dot,
+    /// The slash/question mark key.
+    Slash,
+
+    // Navigation
+    /// The up arrow key.
+    UpArrow,
+    /// The down arrow key.
+    DownArrow,
+    /// The left arrow key.
+    LeftArrow,
+    /// The right arrow key.
+    RightArrow,
+    /// The Insert key.
+    Insert,
+    /// The Home key.
+    Home,
+    /// The End key.
+    End,
+    /// The Page Up key.
+    PageUp,
+    /// The Page Down key.
+    PageDown,
+
+    // Locks and system keys
+    /// The Caps Lock key.
+    CapsLock,
+    /// The Num Lock key.
+    NumLock,
+    /// The Scroll Lock key.
+    ScrollLock,
+    /// The Print Screen key.
+    PrintScreen,
+    /// The Pause/Break key.
+    Pause,
+
+    // Numpad
+    /// The 0 key of the numeric keypad.
+    Kp0,
+    /// The 1 key of the numeric keypad.
+    Kp1,
+    /// The 2 key of the numeric keypad.
+    Kp2,
+    /// The 3 key of the numeric keypad.
+    Kp3,
+    /// The 4 key of the numeric keypad.
+    Kp4,
+    /// The 5 key of the numeric keypad.
+    Kp5,
+    /// The 6 key of the numeric keypad.
+    Kp6,
+    /// The 7 key of the numeric keypad.
+    Kp7,
+    /// The 8 key of the numeric keypad.
+    Kp8,
+    /// The 9 key of the numeric keypad.
+    Kp9,
+    /// The plus key of the numeric keypad.
+    KpPlus,
+    /// The minus key of the numeric keypad.
+    KpMinus,
+    /// The multiply key of the numeric keypad.
+    KpMultiply,
+    /// The divide key of the numeric keypad.
+    KpDivide,
+    /// The Enter key of the numeric keypad.
+    KpReturn,
+    /// The decimal point/Delete key of the numeric keypad.
+    KpDelete,
+
+    // Media keys
+    /// The play/pause media key.
+    MediaPlayPause,
+    /// The stop media key.
+    MediaStop,
+    /// The next track media key.
+    MediaNext,
+    /// The previous track media key.
+    MediaPrevious,
+    /// The volume up media key.
+    VolumeUp,
+    /// The volume down media key.
+    VolumeDown,
+    /// The mute media key.
+    VolumeMute,
 }
 
 #[cfg(test)]
@@ -3,7 +3,12 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long a trusted fingerprint stays valid by default: 90 days.
+pub const DEFAULT_FINGERPRINT_EXPIRY: Duration = Duration::from_secs(90 * 24 * 60 * 60);
 
 /// TLS certificate fingerprint for MITM detection.
 ///
@@ -38,6 +43,9 @@ pub struct Fingerprint {
     first_seen: Option<String>,
     #[serde(default)]
     last_verified: Option<String>,
+    /// Set by the [`FingerprintStore`] holding this fingerprint
+    #[serde(skip)]
+    expires_after: Option<Duration>,
 }
 
 impl PartialEq for Fingerprint {
@@ -75,6 +83,7 @@ impl Fingerprint {
             hash: hash.into(),
             first_seen: Some(chrono::Utc::now().to_rfc3339()),
             last_verified: Some(chrono::Utc::now().to_rfc3339()),
+            expires_after: None,
         }
     }
 
@@ -141,6 +150,25 @@ impl Fingerprint {
         &self.hash
     }
 
+    /// Returns when the fingerprint was first trusted, if recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::fingerprint::Fingerprint;
+    ///
+    /// let fp = Fingerprint::new("example.com", "abc123");
+    /// assert!(fp.first_seen().is_some());
+    /// ```
+    pub fn first_seen(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        parse_timestamp(self.first_seen.as_deref())
+    }
+
+    /// Returns when the fingerprint was last verified on a connection, if recorded.
+    pub fn last_verified(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        parse_timestamp(self.last_verified.as_deref())
+    }
+
     /// Verifies if a certificate hash matches this fingerprint.
     ///
     /// Returns `true` if the provided hash matches the stored hash,
@@ -181,6 +209,39 @@ impl Fingerprint {
     pub fn touch(&mut self) {
         self.last_verified = Some(chrono::Utc::now().to_rfc3339());
     }
+
+    /// Returns whether the fingerprint was first seen longer ago than its expiry.
+    ///
+    /// The expiry comes from the [`FingerprintStore`] holding the fingerprint;
+    /// one outside a store, or without a valid `first_seen`, never expires.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::fingerprint::Fingerprint;
+    ///
+    /// let fp = Fingerprint::new("example.com", "abc123");
+    /// assert!(!fp.is_expired());
+    /// ```
+    pub fn is_expired(&self) -> bool {
+        let (Some(expires_after), Some(first_seen)) = (self.expires_after, &self.first_seen) else {
+            return false;
+        };
+        let Ok(first_seen) = chrono::DateTime::parse_from_rfc3339(first_seen) else {
+            return false;
+        };
+        let Ok(expires_after) = chrono::Duration::from_std(expires_after) else {
+            return false;
+        };
+        first_seen.with_timezone(&chrono::Utc) + expires_after <= chrono::Utc::now()
+    }
+}
+
+/// Parses a timestamp stored in RFC 3339, ignoring invalid ones.
+fn parse_timestamp(timestamp: Option<&str>) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(timestamp?)
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&chrono::Utc))
 }
 
 /// Persistent storage for TLS certificate fingerprints.
@@ -194,11 +255,13 @@ impl Fingerprint {
 /// - Creating the storage directory if it doesn't exist
 /// - Persisting changes to disk
 /// - First-time certificate acceptance (TOFU - Trust On First Use)
+/// - Renewal of fingerprints first seen more than 90 days ago, see
+///   [`FingerprintStore::set_expires_after`]
 ///
 /// # Examples
 ///
 /// ```no_run
-/// use multishiva::core::fingerprint::FingerprintStore;
+/// use multishiva::core::fingerprint::{FingerprintStore, FingerprintVerification};
 ///
 /// // Load the default store
 /// let mut store = FingerprintStore::load_default()?;
@@ -208,8 +271,9 @@ impl Fingerprint {
 ///     FingerprintVerification::Verified => println!("Certificate verified"),
 ///     FingerprintVerification::FirstConnection => println!("First connection, fingerprint saved"),
 ///     FingerprintVerification::Mismatch { stored, received } => {
-///         println!("WARNING: Certificate mismatch!");
+///         println!("WARNING: Certificate mismatch! {} != {}", stored, received);
 ///     }
+///     FingerprintVerification::Renewed => println!("Expired fingerprint renewed"),
 /// }
 /// # Ok::<(), anyhow::Error>(())
 /// ```
@@ -217,6 +281,7 @@ impl Fingerprint {
 pub struct FingerprintStore {
     path: PathBuf,
     fingerprints: HashMap<String, Fingerprint>,
+    expires_after: Option<Duration>,
 }
 
 impl FingerprintStore {
@@ -224,7 +289,7 @@ impl FingerprintStore {
     ///
     /// If the file exists, fingerprints are loaded from it. If the file doesn't
     /// exist, an empty store is created and the parent directories are created
-    /// if necessary.
+    /// if necessary. Fingerprints expire after [`DEFAULT_FINGERPRINT_EXPIRY`].
     ///
     /// # Arguments
     ///
@@ -261,7 +326,41 @@ impl FingerprintStore {
             HashMap::new()
         };
 
-        Ok(Self { path, fingerprints })
+        let mut store = Self {
+            path,
+            fingerprints,
+            expires_after: None,
+        };
+        store.set_expires_after(Some(DEFAULT_FINGERPRINT_EXPIRY));
+        Ok(store)
+    }
+
+    /// Sets how long fingerprints stay trusted after they were first seen.
+    ///
+    /// An expired fingerprint is still compared: the same hash renews it,
+    /// another is a mismatch. `None` keeps them forever. Applies to the stored
+    /// fingerprints as well as to those saved later.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::fingerprint::FingerprintStore;
+    /// use std::time::Duration;
+    ///
+    /// let mut store = FingerprintStore::load_default()?;
+    /// store.set_expires_after(Some(Duration::from_secs(30 * 24 * 60 * 60)));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn set_expires_after(&mut self, expires_after: Option<Duration>) {
+        self.expires_after = expires_after;
+        for fingerprint in self.fingerprints.values_mut() {
+            fingerprint.expires_after = expires_after;
+        }
+    }
+
+    /// Returns how long fingerprints stay trusted, `None` for forever.
+    pub fn expires_after(&self) -> Option<Duration> {
+        self.expires_after
     }
 
     /// Returns the default store path for fingerprints.
@@ -335,13 +434,71 @@ impl FingerprintStore {
     pub fn save(
         &mut self,
         machine_name: impl Into<String>,
-        fingerprint: Fingerprint,
+        mut fingerprint: Fingerprint,
     ) -> Result<()> {
         let machine_name = machine_name.into();
+        fingerprint.expires_after = self.expires_after;
         self.fingerprints.insert(machine_name, fingerprint);
         self.persist()
     }
 
+    /// Trusts `new_hash` for a machine in place of its stored fingerprint and
+    /// persists the change to disk.
+    ///
+    /// This accepts a legitimate key rotation: `first_seen` restarts from now,
+    /// and so does the expiry. A machine without a stored fingerprint simply
+    /// gets one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written to disk.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::fingerprint::{FingerprintStore, FingerprintVerification};
+    ///
+    /// let mut store = FingerprintStore::load_default()?;
+    /// store.update("192.168.1.10:53421", "abc123")?;
+    /// assert_eq!(
+    ///     store.verify_or_save("192.168.1.10:53421", "abc123")?,
+    ///     FingerprintVerification::Verified
+    /// );
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn update(&mut self, machine_name: &str, new_hash: &str) -> Result<()> {
+        self.upsert(machine_name, new_hash).map(|_| ())
+    }
+
+    /// Stores `hash` as the fingerprint of a machine, whether or not it has one,
+    /// persists the change and returns the hash it replaced.
+    ///
+    /// Like [`FingerprintStore::update`], a fresh fingerprint is stored, so
+    /// `first_seen` and the expiry restart from now.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written to disk.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::fingerprint::FingerprintStore;
+    ///
+    /// let mut store = FingerprintStore::load_default()?;
+    /// if let Some(previous) = store.upsert("desktop", "abc123")? {
+    ///     println!("Replaced {}", previous);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn upsert(&mut self, machine_name: &str, hash: &str) -> Result<Option<String>> {
+        let previous = self
+            .get(machine_name)
+            .map(|fingerprint| fingerprint.hash().to_string());
+        self.save(machine_name, Fingerprint::new(machine_name, hash))?;
+        Ok(previous)
+    }
+
     /// Retrieves the stored fingerprint for a machine.
     ///
     /// Returns `None` if no fingerprint has been stored for the given machine.
@@ -392,6 +549,38 @@ impl FingerprintStore {
         self.persist()
     }
 
+    /// Moves the fingerprint stored under `from` to `to`, keeping its
+    /// timestamps, and persists the change. Returns whether one was moved:
+    /// nothing is done if `from` has none or `to` already has one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written to disk.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::fingerprint::FingerprintStore;
+    ///
+    /// let mut store = FingerprintStore::load_default()?;
+    /// if store.rename("192.168.1.10:53421", "desk")? {
+    ///     println!("The fingerprint of desk is now stored under its name");
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<bool> {
+        if self.fingerprints.contains_key(to) {
+            return Ok(false);
+        }
+        let Some(mut fingerprint) = self.fingerprints.remove(from) else {
+            return Ok(false);
+        };
+        fingerprint.machine_name = to.to_string();
+        self.fingerprints.insert(to.to_string(), fingerprint);
+        self.persist()?;
+        Ok(true)
+    }
+
     /// Returns a list of all stored fingerprints.
     ///
     /// The fingerprints are returned in an arbitrary order.
@@ -411,11 +600,151 @@ impl FingerprintStore {
         self.fingerprints.values().collect()
     }
 
+    /// Returns every stored fingerprint with the machine it is stored for,
+    /// sorted by machine name.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::fingerprint::FingerprintStore;
+    ///
+    /// let store = FingerprintStore::load_default()?;
+    /// for (machine, fp) in store.list_sorted() {
+    ///     println!("{}: {} (first seen {:?})", machine, fp.hash(), fp.first_seen());
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn list_sorted(&self) -> Vec<(&str, &Fingerprint)> {
+        let mut fingerprints: Vec<(&str, &Fingerprint)> = self
+            .fingerprints
+            .iter()
+            .map(|(machine, fingerprint)| (machine.as_str(), fingerprint))
+            .collect();
+        fingerprints.sort_by_key(|(machine, _)| *machine);
+        fingerprints
+    }
+
+    /// Writes every stored fingerprint to `path` as a JSON array, sorted by
+    /// machine name, and returns how many were written.
+    ///
+    /// The file can be given to [`FingerprintStore::import_from`] on other
+    /// machines to trust these fingerprints without a first connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::fingerprint::FingerprintStore;
+    ///
+    /// let store = FingerprintStore::load_default()?;
+    /// let exported = store.export_to("fleet-fingerprints.json")?;
+    /// println!("Exported {} fingerprint(s)", exported);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn export_to<P: AsRef<Path>>(&self, path: P) -> Result<usize> {
+        let path = path.as_ref();
+        let fingerprints: Vec<&Fingerprint> = self
+            .list_sorted()
+            .into_iter()
+            .map(|(_, fingerprint)| fingerprint)
+            .collect();
+        let json = serde_json::to_string_pretty(&fingerprints)
+            .context("Failed to serialize fingerprints")?;
+        fs::write(path, json)
+            .with_context(|| format!("Failed to write fingerprints to {:?}", path))?;
+        Ok(fingerprints.len())
+    }
+
+    /// Adds the fingerprints of a file written by [`FingerprintStore::export_to`]
+    /// and persists the store.
+    ///
+    /// A machine whose stored hash differs from the imported one is a conflict,
+    /// settled by `strategy`. Identical fingerprints are left as they are.
+    /// Returns how many fingerprints were added or replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, if the store
+    /// cannot be written, or on a conflict with [`MergeStrategy::ErrorOnConflict`],
+    /// in which case nothing is imported.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::fingerprint::{FingerprintStore, MergeStrategy};
+    ///
+    /// let mut store = FingerprintStore::load_default()?;
+    /// let imported = store.import_from("fleet-fingerprints.json", MergeStrategy::KeepExisting)?;
+    /// println!("Imported {} fingerprint(s)", imported);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn import_from<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        strategy: MergeStrategy,
+    ) -> Result<usize> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fingerprints from {:?}", path))?;
+        let imported: Vec<Fingerprint> = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse fingerprints from {:?}", path))?;
+
+        if strategy == MergeStrategy::ErrorOnConflict {
+            let conflicts: Vec<&str> = imported
+                .iter()
+                .filter(|fingerprint| {
+                    self.get(fingerprint.machine_name())
+                        .is_some_and(|stored| stored.hash() != fingerprint.hash())
+                })
+                .map(|fingerprint| fingerprint.machine_name())
+                .collect();
+            if !conflicts.is_empty() {
+                anyhow::bail!(
+                    "Imported fingerprints conflict with the stored ones for: {}",
+                    conflicts.join(", ")
+                );
+            }
+        }
+
+        let mut changed = 0;
+        for mut fingerprint in imported {
+            match self.get(fingerprint.machine_name()) {
+                Some(stored) if stored.hash() == fingerprint.hash() => continue,
+                Some(_) if strategy == MergeStrategy::KeepExisting => continue,
+                _ => {}
+            }
+            fingerprint.expires_after = self.expires_after;
+            self.fingerprints
+                .insert(fingerprint.machine_name().to_string(), fingerprint);
+            changed += 1;
+        }
+        self.persist()?;
+        Ok(changed)
+    }
+
     /// Persist fingerprints to disk
+    ///
+    /// The JSON goes to `<path>.tmp` first and is then renamed over the store,
+    /// so a crash midway leaves the previous file intact.
     fn persist(&self) -> Result<()> {
         let json = serde_json::to_string_pretty(&self.fingerprints)
             .context("Failed to serialize fingerprints")?;
-        fs::write(&self.path, json)
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let mut file = fs::File::create(&tmp_path)
+            .with_context(|| format!("Failed to create {:?}", tmp_path))?;
+        file.write_all(json.as_bytes())
+            .and_then(|()| file.sync_all())
+            .with_context(|| format!("Failed to write fingerprints to {:?}", tmp_path))?;
+        drop(file);
+
+        // On Windows this is MoveFileExW with MOVEFILE_REPLACE_EXISTING
+        fs::rename(&tmp_path, &self.path)
             .with_context(|| format!("Failed to write fingerprints to {:?}", self.path))?;
         Ok(())
     }
@@ -426,6 +755,9 @@ impl FingerprintStore {
     /// - If this is the first connection to the machine, the fingerprint is saved
     /// - If the hash matches the stored fingerprint, verification succeeds
     /// - If the hash doesn't match, a mismatch is reported (potential MITM attack)
+    /// - If the hash matches a fingerprint past its expiry, the fingerprint is
+    ///   renewed: `first_seen` restarts from now. An expired fingerprint is
+    ///   compared like any other, so a different hash is still a mismatch
     ///
     /// # Arguments
     ///
@@ -438,10 +770,12 @@ impl FingerprintStore {
     /// - `Verified` - The hash matches the stored fingerprint
     /// - `FirstConnection` - No stored fingerprint, the provided hash was saved
     /// - `Mismatch` - The hash doesn't match the stored fingerprint
+    /// - `Renewed` - The hash matches an expired fingerprint, which was renewed
     ///
     /// # Errors
     ///
-    /// Returns an error if the fingerprint cannot be saved to disk (only on first connection).
+    /// Returns an error if the fingerprint cannot be saved to disk (only on
+    /// first connection or renewal).
     ///
     /// # Examples
     ///
@@ -461,6 +795,9 @@ impl FingerprintStore {
     ///         eprintln!("Stored: {}", stored);
     ///         eprintln!("Received: {}", received);
     ///     }
+    ///     FingerprintVerification::Renewed => {
+    ///         println!("Certificate verified, its expiry restarted");
+    ///     }
     /// }
     /// # Ok::<(), anyhow::Error>(())
     /// ```
@@ -469,17 +806,20 @@ impl FingerprintStore {
         machine_name: &str,
         cert_hash: &str,
     ) -> Result<FingerprintVerification> {
-        match self.get(machine_name) {
-            Some(stored_fp) => {
-                if stored_fp.verify(cert_hash) {
-                    Ok(FingerprintVerification::Verified)
-                } else {
-                    Ok(FingerprintVerification::Mismatch {
-                        stored: stored_fp.hash().to_string(),
-                        received: cert_hash.to_string(),
-                    })
-                }
+        match self.fingerprints.get_mut(machine_name) {
+            Some(stored_fp) if !stored_fp.verify(cert_hash) => {
+                Ok(FingerprintVerification::Mismatch {
+                    stored: stored_fp.hash().to_string(),
+                    received: cert_hash.to_string(),
+                })
             }
+            Some(stored_fp) if stored_fp.is_expired() => {
+                stored_fp.first_seen = Some(chrono::Utc::now().to_rfc3339());
+                stored_fp.touch();
+                self.persist()?;
+                Ok(FingerprintVerification::Renewed)
+            }
+            Some(_) => Ok(FingerprintVerification::Verified),
             None => {
                 // First connection - save fingerprint
                 let fp = Fingerprint::new(machine_name, cert_hash);
@@ -492,7 +832,7 @@ impl FingerprintStore {
 
 /// Result of a fingerprint verification operation.
 ///
-/// This enum represents the three possible outcomes when verifying a
+/// This enum represents the four possible outcomes when verifying a
 /// certificate fingerprint against the store.
 ///
 /// # Examples
@@ -511,6 +851,9 @@ impl FingerprintStore {
 ///     FingerprintVerification::Mismatch { stored, received } => {
 ///         eprintln!("WARNING: Possible MITM attack!");
 ///     }
+///     FingerprintVerification::Renewed => {
+///         println!("Safe to connect, fingerprint renewed");
+///     }
 /// }
 /// # Ok::<(), anyhow::Error>(())
 /// ```
@@ -525,13 +868,56 @@ pub enum FingerprintVerification {
     /// The fingerprint does not match the stored value.
     ///
     /// This indicates a possible man-in-the-middle (MITM) attack or
-    /// legitimate certificate rotation. Manual verification is recommended.
+    /// legitimate certificate rotation. Manual verification is recommended;
+    /// a rotation is then accepted with [`FingerprintStore::update`].
     Mismatch {
         /// The fingerprint hash stored in the database
         stored: String,
         /// The fingerprint hash received from the current connection
         received: String,
     },
+    /// The fingerprint matches a stored value older than the store's expiry.
+    ///
+    /// The connection is safe; `first_seen` restarted from now, and so did
+    /// the expiry.
+    Renewed,
+}
+
+/// How [`FingerprintStore::import_from`] handles a machine whose stored hash
+/// differs from the imported one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep the stored fingerprint
+    KeepExisting,
+    /// Replace the stored fingerprint with the imported one
+    Overwrite,
+    /// Fail and import nothing
+    ErrorOnConflict,
+}
+
+/// Parses a fingerprint hash given on the command line: 64 hexadecimal digits.
+///
+/// The hash is returned in lowercase, as it is stored.
+///
+/// # Errors
+///
+/// Returns an error if the hash is not a hex-encoded SHA-256.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::fingerprint::parse_fingerprint_hash;
+///
+/// let hash = "AB".repeat(32);
+/// assert_eq!(parse_fingerprint_hash(&hash).unwrap(), "ab".repeat(32));
+/// assert!(parse_fingerprint_hash("abc123").is_err());
+/// ```
+pub fn parse_fingerprint_hash(s: &str) -> Result<String> {
+    let hash = s.trim();
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("expected 64 hexadecimal digits, got {:?}", s);
+    }
+    Ok(hash.to_ascii_lowercase())
 }
 
 #[cfg(test)]
@@ -556,4 +942,20 @@ mod tests {
 
         assert_ne!(fp.last_verified, first_verified);
     }
+
+    #[test]
+    fn test_fingerprint_expiry() {
+        let mut fp = Fingerprint::new("machine", "hash");
+        fp.expires_after = Some(Duration::from_secs(60));
+        assert!(!fp.is_expired());
+
+        fp.first_seen = Some((chrono::Utc::now() - chrono::Duration::seconds(61)).to_rfc3339());
+        assert!(fp.is_expired());
+
+        fp.expires_after = None;
+        assert!(!fp.is_expired());
+        fp.expires_after = Some(Duration::from_secs(60));
+        fp.first_seen = None;
+        assert!(!fp.is_expired());
+    }
 }
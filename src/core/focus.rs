@@ -1,12 +1,76 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use tokio::sync::{broadcast, watch};
 use tokio::time::{sleep, Duration};
 
+use crate::core::events::{Event, Key, MouseButton};
+use crate::core::metrics;
+
+/// Transitions buffered per [`FocusManager::subscribe`] receiver before the oldest are dropped.
+const TRANSITION_CAPACITY: usize = 64;
+
+/// Machines kept by [`FocusManager::push_history`] before the oldest are forgotten.
+const FOCUS_HISTORY_CAPACITY: usize = 16;
+
+/// How focus moved from one machine to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusChange {
+    /// The host handed focus to a remote machine
+    Grant,
+    /// The remote machine gave focus back to the host
+    Release,
+    /// The host took focus back, e.g. with the kill switch or on a disconnect
+    Recall,
+    /// Focus moved with [`FocusManager::transfer_focus`], without checks
+    Transfer,
+}
+
+/// A change of the machine holding focus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusTransition {
+    /// Machine that held focus before
+    pub from: String,
+    /// Machine holding focus now
+    pub to: String,
+    /// Cursor position on `to` at the time of the transition
+    pub position: (i32, i32),
+    /// How focus moved
+    pub change: FocusChange,
+}
+
+/// A change of focus, as given to the callbacks of [`FocusManager::on_focus_changed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusEvent {
+    /// Machine that held focus before
+    pub from: String,
+    /// Machine holding focus now
+    pub to: String,
+    /// Horizontal cursor position on `to`
+    pub x: i32,
+    /// Vertical cursor position on `to`
+    pub y: i32,
+    /// When focus changed
+    pub timestamp: Instant,
+}
+
+/// A callback registered with [`FocusManager::on_focus_changed`].
+type FocusCallback = Box<dyn Fn(FocusEvent) + Send + Sync>;
+
 /// Manages focus state across multiple machines in a multi-monitor setup.
 ///
 /// The `FocusManager` tracks which machine currently has focus, maintains a history
 /// of focus transitions, and supports configurable friction delays to prevent rapid
 /// focus switching. It also tracks cursor position at the time of focus transfer.
 ///
+/// [`grant_to`](Self::grant_to), [`release`](Self::release) and
+/// [`recall`](Self::recall) move focus between the host and a remote machine
+/// and reject transitions that make no sense from the current state. Every
+/// transition is logged, counted in the metrics, sent to the receivers of
+/// [`subscribe`](Self::subscribe) and [`watch`](Self::watch) and given to the
+/// callbacks of [`on_focus_changed`](Self::on_focus_changed).
+///
 /// # Examples
 ///
 /// ```
@@ -21,7 +85,12 @@ pub struct FocusManager {
     host_machine: String,
     current_position: (i32, i32),
     focus_history: Vec<String>,
+    /// Machines focus came from, with where the cursor entered them, newest last
+    back_history: VecDeque<(String, i32, i32)>,
     friction_ms: u64,
+    transitions: broadcast::Sender<FocusTransition>,
+    holder: watch::Sender<String>,
+    callbacks: Arc<RwLock<Vec<FocusCallback>>>,
 }
 
 impl FocusManager {
@@ -49,8 +118,12 @@ impl FocusManager {
             current_focus: initial_focus.clone(),
             host_machine: host,
             current_position: (0, 0),
-            focus_history: vec![initial_focus],
+            focus_history: vec![initial_focus.clone()],
+            back_history: VecDeque::with_capacity(FOCUS_HISTORY_CAPACITY),
             friction_ms: 0,
+            transitions: broadcast::channel(TRANSITION_CAPACITY).0,
+            holder: watch::channel(initial_focus).0,
+            callbacks: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
@@ -95,13 +168,172 @@ impl FocusManager {
             sleep(Duration::from_millis(self.friction_ms)).await;
         }
 
-        self.current_focus = target.clone();
-        self.current_position = (x, y);
-        self.focus_history.push(target);
-
+        self.apply(target, (x, y), FocusChange::Transfer);
         Ok(())
     }
 
+    /// Hands focus from the host to `machine`, with the cursor entering at (`x`, `y`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a remote machine already holds focus, or if
+    /// `machine` is the host itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::focus::FocusManager;
+    ///
+    /// let mut manager = FocusManager::new("host".to_string());
+    /// manager.grant_to("agent1", 10, 500).unwrap();
+    /// assert_eq!(manager.remote(), Some("agent1"));
+    ///
+    /// // Focus must come back before going elsewhere
+    /// assert!(manager.grant_to("agent2", 0, 0).is_err());
+    /// manager.release().unwrap();
+    /// assert!(manager.is_local());
+    /// ```
+    pub fn grant_to(&mut self, machine: &str, x: i32, y: i32) -> Result<FocusTransition> {
+        if !self.is_local() {
+            bail!(
+                "Cannot grant focus to '{}': '{}' already holds it",
+                machine,
+                self.current_focus
+            );
+        }
+        if machine == self.host_machine {
+            bail!("Cannot grant focus to '{}': it is the host", machine);
+        }
+        Ok(self.apply(machine.to_string(), (x, y), FocusChange::Grant))
+    }
+
+    /// Takes focus back on the host because the remote machine gave it up.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host already holds focus.
+    pub fn release(&mut self) -> Result<FocusTransition> {
+        self.back_to_host(FocusChange::Release)
+    }
+
+    /// Takes focus back on the host from the remote machine holding it.
+    ///
+    /// Used when the remote machine cannot be asked, e.g. for the kill switch
+    /// or after it disconnected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host already holds focus.
+    pub fn recall(&mut self) -> Result<FocusTransition> {
+        self.back_to_host(FocusChange::Recall)
+    }
+
+    fn back_to_host(&mut self, change: FocusChange) -> Result<FocusTransition> {
+        if self.is_local() {
+            bail!(
+                "Cannot {:?} focus: it is already on '{}'",
+                change,
+                self.host_machine
+            );
+        }
+        Ok(self.apply(self.host_machine.clone(), (0, 0), change))
+    }
+
+    /// Records a transition to `to` and tells every listener about it.
+    fn apply(&mut self, to: String, position: (i32, i32), change: FocusChange) -> FocusTransition {
+        let transition = FocusTransition {
+            from: std::mem::replace(&mut self.current_focus, to.clone()),
+            to: to.clone(),
+            position,
+            change,
+        };
+        tracing::info!(
+            "Focus {:?}: '{}' → '{}' at ({}, {})",
+            change,
+            transition.from,
+            transition.to,
+            position.0,
+            position.1
+        );
+
+        self.current_position = position;
+        self.focus_history.push(to.clone());
+        metrics::record_focus_transfer();
+        // Nobody listening is fine
+        let _ = self.transitions.send(transition.clone());
+        self.holder.send_replace(to);
+        if let Ok(callbacks) = self.callbacks.read() {
+            let event = FocusEvent {
+                from: transition.from.clone(),
+                to: transition.to.clone(),
+                x: position.0,
+                y: position.1,
+                timestamp: Instant::now(),
+            };
+            for callback in callbacks.iter() {
+                callback(event.clone());
+            }
+        }
+        transition
+    }
+
+    /// Calls `cb` on every focus change from now on.
+    ///
+    /// Callbacks run synchronously, in registration order, from whichever
+    /// method moved focus, e.g. [`transfer_focus`](Self::transfer_focus); they
+    /// should return quickly. A callback cannot register another one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::focus::FocusManager;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut manager = FocusManager::new("host".to_string());
+    /// let seen = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = seen.clone();
+    /// manager.on_focus_changed(move |event| sink.lock().unwrap().push(event.to));
+    ///
+    /// manager.transfer_focus("agent1".to_string(), 10, 500).await.unwrap();
+    /// assert_eq!(*seen.lock().unwrap(), vec!["agent1"]);
+    /// # });
+    /// ```
+    pub fn on_focus_changed(&self, cb: impl Fn(FocusEvent) + Send + Sync + 'static) {
+        if let Ok(mut callbacks) = self.callbacks.write() {
+            callbacks.push(Box::new(cb));
+        }
+    }
+
+    /// Returns a stream of every transition from now on.
+    ///
+    /// A receiver that falls more than 64 transitions behind skips the oldest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::focus::{FocusChange, FocusManager};
+    ///
+    /// let mut manager = FocusManager::new("host".to_string());
+    /// let mut transitions = manager.subscribe();
+    /// manager.grant_to("agent1", 10, 500).unwrap();
+    ///
+    /// let transition = transitions.try_recv().unwrap();
+    /// assert_eq!(transition.change, FocusChange::Grant);
+    /// assert_eq!(transition.to, "agent1");
+    /// ```
+    pub fn subscribe(&self) -> broadcast::Receiver<FocusTransition> {
+        self.transitions.subscribe()
+    }
+
+    /// Returns a receiver always holding the name of the machine with focus.
+    ///
+    /// Unlike the manager itself, the receiver can be shared with other tasks,
+    /// such as the control socket answering `multishiva status`.
+    pub fn watch(&self) -> watch::Receiver<String> {
+        self.holder.subscribe()
+    }
+
     /// Returns focus to the host machine.
     ///
     /// This is a convenience method that transfers focus back to the machine that was
@@ -166,6 +398,21 @@ impl FocusManager {
         self.current_focus == machine
     }
 
+    /// Returns the name of the host, the machine focus returns to.
+    pub fn host(&self) -> &str {
+        &self.host_machine
+    }
+
+    /// Returns `true` if the host holds focus.
+    pub fn is_local(&self) -> bool {
+        self.current_focus == self.host_machine
+    }
+
+    /// Returns the remote machine holding focus, or `None` if the host holds it.
+    pub fn remote(&self) -> Option<&str> {
+        (!self.is_local()).then_some(self.current_focus.as_str())
+    }
+
     /// Returns the current cursor position as a tuple of (x, y) coordinates.
     ///
     /// The position represents the cursor coordinates at the time of the last focus
@@ -213,6 +460,45 @@ impl FocusManager {
         &self.focus_history
     }
 
+    /// Remembers `machine`, entered at (`x`, `y`), as the machine to go back to.
+    ///
+    /// Called before focus leaves `machine`. Only the last 16 machines are
+    /// kept; older ones are forgotten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::focus::FocusManager;
+    ///
+    /// let mut manager = FocusManager::new("host".to_string());
+    /// manager.push_history("host".to_string(), 0, 0);
+    /// manager.push_history("agent1".to_string(), 10, 500);
+    ///
+    /// assert_eq!(manager.pop_history(), Some(("agent1".to_string(), 10, 500)));
+    /// assert_eq!(manager.pop_history(), Some(("host".to_string(), 0, 0)));
+    /// assert_eq!(manager.pop_history(), None);
+    /// ```
+    pub fn push_history(&mut self, machine: String, x: i32, y: i32) {
+        if self.back_history.len() == FOCUS_HISTORY_CAPACITY {
+            self.back_history.pop_front();
+        }
+        self.back_history.push_back((machine, x, y));
+    }
+
+    /// Takes the machine most recently given to [`push_history`](Self::push_history).
+    ///
+    /// Returns the machine and where the cursor entered it, or `None` once
+    /// every machine has been taken.
+    pub fn pop_history(&mut self) -> Option<(String, i32, i32)> {
+        self.back_history.pop_back()
+    }
+
+    /// Forgets the machines given to [`push_history`](Self::push_history),
+    /// e.g. once focus is back on the host.
+    pub fn clear_history(&mut self) {
+        self.back_history.clear();
+    }
+
     /// Sets the friction delay in milliseconds.
     ///
     /// Friction delay introduces an intentional pause before completing focus transfers,
@@ -237,6 +523,217 @@ impl FocusManager {
     }
 }
 
+/// Takes focus back from a remote machine once no input was forwarded for a while.
+///
+/// The host calls [`track`](Self::track) whenever focus may have moved,
+/// [`touch`](Self::touch) for every event it forwards, and
+/// [`poll`](Self::poll) once the [`deadline`](Self::deadline) passes. Every
+/// method takes the current time, so any clock will do.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::focus::IdleRelease;
+/// use std::time::{Duration, Instant};
+///
+/// let mut idle = IdleRelease::new(Some(Duration::from_secs(60)));
+/// let start = Instant::now();
+/// idle.track(true, start);
+/// idle.touch(start + Duration::from_secs(50));
+///
+/// assert!(!idle.poll(start + Duration::from_secs(100)));
+/// assert!(idle.poll(start + Duration::from_secs(110)));
+/// // Fires once, until focus goes remote again
+/// assert_eq!(idle.deadline(), None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IdleRelease {
+    timeout: Option<Duration>,
+    /// Last input forwarded while a remote machine has focus
+    last_input: Option<std::time::Instant>,
+}
+
+impl IdleRelease {
+    /// Releases focus after `timeout` without input, never with `None`.
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            last_input: None,
+        }
+    }
+
+    /// Changes the timeout, e.g. after the configuration was reloaded.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Returns the time without input after which focus is released.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Starts counting when focus went to a remote machine, stops when it came back.
+    pub fn track(&mut self, remote: bool, now: std::time::Instant) {
+        if !remote {
+            self.last_input = None;
+        } else if self.last_input.is_none() {
+            self.last_input = Some(now);
+        }
+    }
+
+    /// Restarts the count on input forwarded to the remote machine.
+    pub fn touch(&mut self, now: std::time::Instant) {
+        if self.last_input.is_some() {
+            self.last_input = Some(now);
+        }
+    }
+
+    /// Returns when focus is released unless input comes first.
+    pub fn deadline(&self) -> Option<std::time::Instant> {
+        Some(self.last_input? + self.timeout?)
+    }
+
+    /// Returns `true` if the deadline passed at `now`, and stops counting.
+    pub fn poll(&mut self, now: std::time::Instant) -> bool {
+        match self.deadline() {
+            Some(deadline) if now >= deadline => {
+                self.last_input = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Tracks the keys and mouse buttons held down on the machine that currently has focus.
+///
+/// A key pressed before a focus transition is released on the other side of
+/// it: the press went to one machine and the release to another, leaving the
+/// modifier stuck on the first. Feeding every captured key event through
+/// [`observe`](Self::observe) and calling
+/// [`release_modifiers`](Self::release_modifiers) on each transition gives the
+/// `KeyRelease` events to send to the machine that is losing focus. When the
+/// other machine goes away altogether, [`release_all`](Self::release_all)
+/// also lets go of the other keys and the mouse buttons.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::events::{Event, Key};
+/// use multishiva::core::focus::ModifierTracker;
+///
+/// let mut tracker = ModifierTracker::new();
+/// tracker.observe(&Event::KeyPress { key: Key::ControlLeft });
+///
+/// // Focus moves away while Ctrl is held
+/// assert_eq!(
+///     tracker.release_modifiers(),
+///     vec![Event::KeyRelease { key: Key::ControlLeft }]
+/// );
+/// assert!(tracker.release_modifiers().is_empty());
+/// ```
+#[derive(Debug, Default)]
+pub struct ModifierTracker {
+    held: Vec<Key>,
+    buttons: Vec<MouseButton>,
+}
+
+impl ModifierTracker {
+    /// Creates a tracker with no keys held.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a key or mouse button press or release; other events are ignored.
+    pub fn observe(&mut self, event: &Event) {
+        match event {
+            Event::KeyPress { key } if !self.held.contains(key) => self.held.push(key.clone()),
+            Event::KeyRelease { key } => self.held.retain(|held| held != key),
+            Event::MouseButtonPress { button } if !self.buttons.contains(button) => {
+                self.buttons.push(button.clone())
+            }
+            Event::MouseButtonRelease { button } => self.buttons.retain(|held| held != button),
+            _ => {}
+        }
+    }
+
+    /// Returns the keys currently held, in the order they were pressed.
+    pub fn held(&self) -> &[Key] {
+        &self.held
+    }
+
+    /// Returns the mouse buttons currently held, in the order they were pressed.
+    pub fn buttons(&self) -> &[MouseButton] {
+        &self.buttons
+    }
+
+    /// Returns whether a mouse button is held, e.g. during a drag.
+    pub fn is_dragging(&self) -> bool {
+        !self.buttons.is_empty()
+    }
+
+    /// Forgets the held modifiers and returns a `KeyRelease` for each.
+    ///
+    /// Other held keys are kept, so a hotkey such as the kill switch can still
+    /// be matched against [`held`](Self::held) after a transition.
+    pub fn release_modifiers(&mut self) -> Vec<Event> {
+        let mut releases = Vec::new();
+        self.held.retain(|key| {
+            if is_modifier(key) {
+                releases.push(Event::KeyRelease { key: key.clone() });
+                false
+            } else {
+                true
+            }
+        });
+        releases
+    }
+
+    /// Forgets every held key and button and returns the events releasing them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::events::{Event, Key, MouseButton};
+    /// use multishiva::core::focus::ModifierTracker;
+    ///
+    /// let mut tracker = ModifierTracker::new();
+    /// tracker.observe(&Event::KeyPress { key: Key::KeyA });
+    /// tracker.observe(&Event::MouseButtonPress { button: MouseButton::Left });
+    ///
+    /// assert_eq!(
+    ///     tracker.release_all(),
+    ///     vec![
+    ///         Event::KeyRelease { key: Key::KeyA },
+    ///         Event::MouseButtonRelease { button: MouseButton::Left },
+    ///     ]
+    /// );
+    /// assert!(tracker.release_all().is_empty());
+    /// ```
+    pub fn release_all(&mut self) -> Vec<Event> {
+        let keys = self.held.drain(..).map(|key| Event::KeyRelease { key });
+        let buttons = self
+            .buttons
+            .drain(..)
+            .map(|button| Event::MouseButtonRelease { button });
+        keys.chain(buttons).collect()
+    }
+}
+
+fn is_modifier(key: &Key) -> bool {
+    matches!(
+        key,
+        Key::ShiftLeft
+            | Key::ShiftRight
+            | Key::ControlLeft
+            | Key::ControlRight
+            | Key::AltLeft
+            | Key::AltRight
+            | Key::MetaLeft
+            | Key::MetaRight
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -246,4 +743,168 @@ mod tests {
         let manager = FocusManager::new("host".to_string());
         assert_eq!(manager.current(), "host");
     }
+
+    #[test]
+    fn test_illegal_transitions() {
+        let mut manager = FocusManager::new("host".to_string());
+        let mut transitions = manager.subscribe();
+        let holder = manager.watch();
+
+        // Release and recall only make sense while a remote machine has focus
+        assert!(manager.release().is_err());
+        assert!(manager.recall().is_err());
+        assert!(manager.grant_to("host", 0, 0).is_err());
+
+        manager.grant_to("agent1", 10, 500).unwrap();
+        assert_eq!(*holder.borrow(), "agent1");
+        assert!(manager.grant_to("agent2", 0, 0).is_err());
+        assert!(manager.grant_to("agent1", 0, 0).is_err());
+        assert_eq!(manager.remote(), Some("agent1"));
+        assert_eq!(manager.current_position(), (10, 500));
+
+        let recalled = manager.recall().unwrap();
+        assert_eq!(recalled.from, "agent1");
+        assert!(manager.is_local());
+        assert!(manager.release().is_err());
+
+        // Rejected transitions are neither recorded nor sent
+        assert_eq!(manager.focus_history(), &["host", "agent1", "host"]);
+        let changes: Vec<FocusChange> = std::iter::from_fn(|| transitions.try_recv().ok())
+            .map(|transition| transition.change)
+            .collect();
+        assert_eq!(changes, vec![FocusChange::Grant, FocusChange::Recall]);
+        assert_eq!(*holder.borrow(), "host");
+    }
+
+    #[tokio::test]
+    async fn test_focus_changed_callbacks() {
+        let mut manager = FocusManager::new("host".to_string());
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        for _ in 0..2 {
+            let sink = seen.clone();
+            manager.on_focus_changed(move |event| {
+                sink.lock()
+                    .unwrap()
+                    .push((event.from, event.to, event.x, event.y));
+            });
+        }
+
+        manager
+            .transfer_focus("agent1".to_string(), 10, 500)
+            .await
+            .unwrap();
+        // Staying on the same machine is not a change
+        manager
+            .transfer_focus("agent1".to_string(), 20, 20)
+            .await
+            .unwrap();
+        manager.release().unwrap();
+
+        let to_agent = ("host".to_string(), "agent1".to_string(), 10, 500);
+        let to_host = ("agent1".to_string(), "host".to_string(), 0, 0);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![to_agent.clone(), to_agent, to_host.clone(), to_host]
+        );
+    }
+
+    #[test]
+    fn test_idle_release() {
+        let start = std::time::Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // Disabled without a timeout
+        let mut idle = IdleRelease::new(None);
+        idle.track(true, start);
+        assert_eq!(idle.deadline(), None);
+        assert!(!idle.poll(at(3600)));
+
+        // Only counts while a remote machine has focus
+        idle.set_timeout(Some(Duration::from_secs(30)));
+        assert_eq!(idle.deadline(), Some(at(30)));
+        idle.track(false, at(10));
+        idle.touch(at(20));
+        assert_eq!(idle.deadline(), None);
+
+        // Tracking again does not restart the count, input does
+        idle.track(true, at(40));
+        idle.track(true, at(60));
+        assert_eq!(idle.deadline(), Some(at(70)));
+        idle.touch(at(65));
+        assert!(!idle.poll(at(70)));
+        assert!(idle.poll(at(95)));
+        assert!(!idle.poll(at(200)));
+    }
+
+    #[test]
+    fn test_history_keeps_last_machines() {
+        let mut manager = FocusManager::new("host".to_string());
+        for i in 0..20 {
+            manager.push_history(format!("agent{}", i), i, -i);
+        }
+
+        assert_eq!(
+            manager.pop_history(),
+            Some(("agent19".to_string(), 19, -19))
+        );
+        let mut remaining = 1;
+        while let Some((machine, _, _)) = manager.pop_history() {
+            remaining += 1;
+            if remaining == FOCUS_HISTORY_CAPACITY {
+                // The four oldest were dropped
+                assert_eq!(machine, "agent4");
+            }
+        }
+        assert_eq!(remaining, FOCUS_HISTORY_CAPACITY);
+
+        manager.push_history("agent1".to_string(), 0, 0);
+        manager.clear_history();
+        assert_eq!(manager.pop_history(), None);
+    }
+
+    #[test]
+    fn test_transfer_while_modifier_held() {
+        let mut tracker = ModifierTracker::new();
+        tracker.observe(&Event::KeyPress {
+            key: Key::ControlLeft,
+        });
+        tracker.observe(&Event::KeyPress { key: Key::KeyC });
+        tracker.observe(&Event::MouseMove { x: 0, y: 0 });
+
+        // Crossing the edge releases Ctrl on the host but keeps C tracked
+        assert_eq!(
+            tracker.release_modifiers(),
+            vec![Event::KeyRelease {
+                key: Key::ControlLeft
+            }]
+        );
+        assert_eq!(tracker.held(), &[Key::KeyC]);
+
+        // The physical release then goes to the agent and is a no-op here
+        tracker.observe(&Event::KeyRelease {
+            key: Key::ControlLeft,
+        });
+        tracker.observe(&Event::KeyRelease { key: Key::KeyC });
+        assert!(tracker.held().is_empty());
+    }
+
+    #[test]
+    fn test_release_on_return() {
+        let mut tracker = ModifierTracker::new();
+        // Pressed while the agent has focus
+        tracker.observe(&Event::KeyPress {
+            key: Key::ShiftRight,
+        });
+        tracker.observe(&Event::KeyPress { key: Key::AltLeft });
+        tracker.observe(&Event::KeyPress { key: Key::AltLeft });
+        tracker.observe(&Event::KeyRelease { key: Key::AltLeft });
+
+        assert_eq!(
+            tracker.release_modifiers(),
+            vec![Event::KeyRelease {
+                key: Key::ShiftRight
+            }]
+        );
+        assert!(tracker.release_modifiers().is_empty());
+    }
 }
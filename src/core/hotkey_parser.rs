@@ -0,0 +1,280 @@
+/// Parsing of hotkey strings from the configuration
+///
+/// Hotkeys such as `hotkeys.focus_return` are written as key names joined by
+/// `+`, e.g. `"Ctrl+Alt+F12"` or `"Super+L"`. Names are case-insensitive and
+/// can be either a [`Key`] variant name (`"ControlRight"`, `"KeyA"`) or one of
+/// the usual short names:
+///
+/// - `Ctrl`/`Control`, `Shift`, `Alt`/`Option`, `Win`/`Super`/`Meta`/`Cmd`
+/// - single letters `A`–`Z`, digits `0`–`9` and function keys `F1`–`F12`
+/// - `Esc`, `Enter`, `Space`, `Backspace`, `Tab`, `Del`/`Delete`, `Ins`/`Insert`
+/// - `Up`, `Down`, `Left`, `Right`, `Home`, `End`, `PgUp`/`PageUp`, `PgDn`/`PageDown`
+/// - `PrtSc`/`PrintScreen`, `Pause`, `CapsLock`, `NumLock`, `ScrollLock`
+///
+/// Short modifier names map to the left-hand key; [`is_triggered`] accepts
+/// either side when matching.
+use anyhow::Result;
+
+use crate::core::events::Key;
+
+/// Parses a hotkey string like `"Ctrl+Alt+F12"` into its keys.
+///
+/// # Errors
+///
+/// Returns an error listing every unrecognised token, or if the string has
+/// an empty token (e.g. `""` or `"Ctrl++A"`).
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::events::Key;
+/// use multishiva::core::hotkey_parser::parse_hotkey;
+///
+/// assert_eq!(
+///     parse_hotkey("Ctrl+Alt+F12").unwrap(),
+///     vec![Key::ControlLeft, Key::AltLeft, Key::F12]
+/// );
+/// assert!(parse_hotkey("Ctrl+Hyper+Foo").is_err());
+/// ```
+pub fn parse_hotkey(s: &str) -> Result<Vec<Key>> {
+    let mut keys = Vec::new();
+    let mut unknown = Vec::new();
+
+    for token in s.split('+').map(str::trim) {
+        if token.is_empty() {
+            anyhow::bail!("Hotkey '{}' has an empty key name", s);
+        }
+        match key_from_name(token) {
+            Some(key) => keys.push(key),
+            None => unknown.push(token),
+        }
+    }
+
+    if !unknown.is_empty() {
+        anyhow::bail!(
+            "Unrecognised key(s) in hotkey '{}': {}",
+            s,
+            unknown.join(", ")
+        );
+    }
+    Ok(keys)
+}
+
+/// Returns `true` if every key of `hotkey` is in `held_keys`.
+///
+/// A modifier matches both its left and right key, so `Ctrl` is triggered by
+/// either Control key. An empty hotkey is never triggered.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::events::Key;
+/// use multishiva::core::hotkey_parser::{is_triggered, parse_hotkey};
+///
+/// let hotkey = parse_hotkey("Ctrl+L").unwrap();
+/// assert!(is_triggered(&[Key::ControlRight, Key::KeyL], &hotkey));
+/// assert!(!is_triggered(&[Key::KeyL], &hotkey));
+/// ```
+pub fn is_triggered(held_keys: &[Key], hotkey: &[Key]) -> bool {
+    !hotkey.is_empty()
+        && hotkey
+            .iter()
+            .all(|wanted| held_keys.iter().any(|held| same_key(held, wanted)))
+}
+
+/// Compares keys, treating left and right modifiers as the same key.
+fn same_key(a: &Key, b: &Key) -> bool {
+    fn side_free(key: &Key) -> &Key {
+        match key {
+            Key::ControlRight => &Key::ControlLeft,
+            Key::ShiftRight => &Key::ShiftLeft,
+            Key::AltRight => &Key::AltLeft,
+            Key::MetaRight => &Key::MetaLeft,
+            other => other,
+        }
+    }
+    side_free(a) == side_free(b)
+}
+
+/// Maps a key name to its key, ignoring case.
+fn key_from_name(name: &str) -> Option<Key> {
+    let name = name.to_ascii_lowercase();
+    let key = match name.as_str() {
+        // Modifiers
+        "ctrl" | "control" | "controlleft" => Key::ControlLeft,
+        "controlright" => Key::ControlRight,
+        "shift" | "shiftleft" => Key::ShiftLeft,
+        "shiftright" => Key::ShiftRight,
+        "alt" | "option" | "altleft" => Key::AltLeft,
+        "altgr" | "altright" => Key::AltRight,
+        "win" | "super" | "meta" | "cmd" | "command" | "metaleft" => Key::MetaLeft,
+        "metaright" => Key::MetaRight,
+
+        // Special keys
+        "esc" | "escape" => Key::Escape,
+        "enter" | "return" => Key::Return,
+        "space" => Key::Space,
+        "backspace" => Key::Backspace,
+        "tab" => Key::Tab,
+        "del" | "delete" => Key::Delete,
+        "ins" | "insert" => Key::Insert,
+        "prtsc" | "printscreen" => Key::PrintScreen,
+        "pause" => Key::Pause,
+        "capslock" => Key::CapsLock,
+        "numlock" => Key::NumLock,
+        "scrolllock" => Key::ScrollLock,
+
+        // Navigation
+        "up" | "uparrow" => Key::UpArrow,
+        "down" | "downarrow" => Key::DownArrow,
+        "left" | "leftarrow" => Key::LeftArrow,
+        "right" | "rightarrow" => Key::RightArrow,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pgup" | "pageup" => Key::PageUp,
+        "pgdn" | "pagedown" => Key::PageDown,
+
+        // Number row, as "1" or "num1"
+        "0" | "num0" => Key::Num0,
+        "1" | "num1" => Key::Num1,
+        "2" | "num2" => Key::Num2,
+        "3" | "num3" => Key::Num3,
+        "4" | "num4" => Key::Num4,
+        "5" | "num5" => Key::Num5,
+        "6" | "num6" => Key::Num6,
+        "7" | "num7" => Key::Num7,
+        "8" | "num8" => Key::Num8,
+        "9" | "num9" => Key::Num9,
+
+        // Function keys
+        "f1" => Key::F1,
+        "f2" => Key::F2,
+        "f3" => Key::F3,
+        "f4" => Key::F4,
+        "f5" => Key::F5,
+        "f6" => Key::F6,
+        "f7" => Key::F7,
+        "f8" => Key::F8,
+        "f9" => Key::F9,
+        "f10" => Key::F10,
+        "f11" => Key::F11,
+        "f12" => Key::F12,
+
+        // Letters, as "a" or "keya"
+        other => return letter_key(other.strip_prefix("key").unwrap_or(other)),
+    };
+    Some(key)
+}
+
+fn letter_key(letter: &str) -> Option<Key> {
+    const LETTERS: [Key; 26] = [
+        Key::KeyA,
+        Key::KeyB,
+        Key::KeyC,
+        Key::KeyD,
+        Key::KeyE,
+        Key::KeyF,
+        Key::KeyG,
+        Key::KeyH,
+        Key::KeyI,
+        Key::KeyJ,
+        Key::KeyK,
+        Key::KeyL,
+        Key::KeyM,
+        Key::KeyN,
+        Key::KeyO,
+        Key::KeyP,
+        Key::KeyQ,
+        Key::KeyR,
+        Key::KeyS,
+        Key::KeyT,
+        Key::KeyU,
+        Key::KeyV,
+        Key::KeyW,
+        Key::KeyX,
+        Key::KeyY,
+        Key::KeyZ,
+    ];
+    match letter.as_bytes() {
+        [c @ b'a'..=b'z'] => Some(LETTERS[(c - b'a') as usize].clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ctrl_alt_del() {
+        assert_eq!(
+            parse_hotkey("Ctrl+Alt+Del").unwrap(),
+            vec![Key::ControlLeft, Key::AltLeft, Key::Delete]
+        );
+        // Case and surrounding spaces do not matter
+        assert_eq!(
+            parse_hotkey("ctrl + ALT + delete").unwrap(),
+            vec![Key::ControlLeft, Key::AltLeft, Key::Delete]
+        );
+    }
+
+    #[test]
+    fn test_parse_super_l() {
+        assert_eq!(
+            parse_hotkey("Super+L").unwrap(),
+            vec![Key::MetaLeft, Key::KeyL]
+        );
+        assert_eq!(
+            parse_hotkey("Win+KeyL").unwrap(),
+            vec![Key::MetaLeft, Key::KeyL]
+        );
+    }
+
+    #[test]
+    fn test_parse_function_keys_and_variant_names() {
+        assert_eq!(parse_hotkey("F1").unwrap(), vec![Key::F1]);
+        assert_eq!(
+            parse_hotkey("ShiftRight+F10").unwrap(),
+            vec![Key::ShiftRight, Key::F10]
+        );
+    }
+
+    #[test]
+    fn test_parse_navigation_and_digits() {
+        assert_eq!(
+            parse_hotkey("Ctrl+Alt+Home").unwrap(),
+            vec![Key::ControlLeft, Key::AltLeft, Key::Home]
+        );
+        assert_eq!(
+            parse_hotkey("Super+PgDn+Right+1").unwrap(),
+            vec![Key::MetaLeft, Key::PageDown, Key::RightArrow, Key::Num1]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_input() {
+        let error = parse_hotkey("Ctrl+Hyper+F13").unwrap_err().to_string();
+        assert!(error.contains("Hyper"));
+        assert!(error.contains("F13"));
+        assert!(!error.contains("Ctrl,"));
+
+        assert!(parse_hotkey("").is_err());
+        assert!(parse_hotkey("Ctrl++A").is_err());
+        assert!(parse_hotkey("Ctrl+AB").is_err());
+    }
+
+    #[test]
+    fn test_is_triggered() {
+        let hotkey = parse_hotkey("Ctrl+Alt+Del").unwrap();
+        assert!(is_triggered(
+            &[Key::AltRight, Key::Delete, Key::ControlLeft],
+            &hotkey
+        ));
+        assert!(is_triggered(
+            &[Key::ControlLeft, Key::AltLeft, Key::Delete, Key::KeyA],
+            &hotkey
+        ));
+        assert!(!is_triggered(&[Key::ControlLeft, Key::Delete], &hotkey));
+        assert!(!is_triggered(&[Key::ControlLeft], &[]));
+    }
+}
@@ -4,7 +4,9 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock as StdRwLock};
 use tokio::sync::mpsc;
 
-use crate::core::events::{Event, Key, MouseButton};
+use crate::core::events::{Event, Key, Modifiers, MouseButton, SCROLL_DETENT};
+use crate::core::focus::ModifierTracker;
+use crate::core::hotkey_parser::is_triggered;
 
 type EventFilter = Box<dyn Fn(&Event) -> bool + Send + Sync>;
 
@@ -111,9 +113,81 @@ pub trait InputHandler: Send + Sync {
 pub struct RdevInputHandler {
     capturing: Arc<AtomicBool>,
     block_local: Arc<AtomicBool>,
-    kill_switch: Arc<StdRwLock<Option<Vec<Key>>>>,
+    kill_switch: KillSwitch,
     event_filter: Arc<StdRwLock<Option<EventFilter>>>,
-    pressed_keys: Arc<StdRwLock<Vec<Key>>>,
+    scroll: Arc<std::sync::Mutex<ScrollAccumulator>>,
+}
+
+/// Collects [`Event::MouseScrollPrecise`] deltas into whole wheel detents.
+///
+/// The remainder of each axis is kept for the next delta, so slow touchpad
+/// scrolling still adds up.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::input::ScrollAccumulator;
+///
+/// let mut scroll = ScrollAccumulator::default();
+/// assert_eq!(scroll.push(0, 60), None);
+/// assert_eq!(scroll.push(0, 90), Some((0, 1)));
+/// assert_eq!(scroll.push(0, -270), Some((0, -2)));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrollAccumulator {
+    dx_120: i32,
+    dy_120: i32,
+}
+
+impl ScrollAccumulator {
+    /// Adds a delta in 1/120 detents and returns the whole detents reached, if any.
+    pub fn push(&mut self, dx_120: i32, dy_120: i32) -> Option<(i64, i64)> {
+        self.dx_120 = self.dx_120.saturating_add(dx_120);
+        self.dy_120 = self.dy_120.saturating_add(dy_120);
+        // Truncates toward zero, so the remainder keeps the sign of the motion
+        let detents = (self.dx_120 / SCROLL_DETENT, self.dy_120 / SCROLL_DETENT);
+        self.dx_120 %= SCROLL_DETENT;
+        self.dy_120 %= SCROLL_DETENT;
+        (detents != (0, 0)).then_some((detents.0 as i64, detents.1 as i64))
+    }
+}
+
+/// The kill switch keys and the keys held down, shared with the capture thread.
+#[derive(Clone, Default)]
+struct KillSwitch {
+    keys: Arc<StdRwLock<Option<Vec<Key>>>>,
+    pressed: Arc<StdRwLock<Vec<Key>>>,
+}
+
+impl KillSwitch {
+    /// Records a key press or release and returns whether the whole
+    /// combination is now held.
+    ///
+    /// Left and right modifiers are interchangeable, as for other hotkeys.
+    fn observe(&self, event: &Event) -> bool {
+        let Ok(mut pressed) = self.pressed.write() else {
+            return false;
+        };
+        match event {
+            Event::KeyPress { key } => {
+                if !pressed.contains(key) {
+                    pressed.push(key.clone());
+                }
+            }
+            Event::KeyRelease { key } => {
+                pressed.retain(|k| k != key);
+                return false;
+            }
+            _ => return false,
+        }
+
+        match self.keys.read() {
+            Ok(keys) => keys
+                .as_ref()
+                .is_some_and(|keys| is_triggered(&pressed, keys)),
+            Err(_) => false,
+        }
+    }
 }
 
 impl Default for RdevInputHandler {
@@ -131,7 +205,7 @@ impl RdevInputHandler {
     /// # Examples
     ///
     /// ```
-    /// use multishiva::core::input::RdevInputHandler;
+    /// use multishiva::core::input::{InputHandler, RdevInputHandler};
     ///
     /// let handler = RdevInputHandler::new();
     /// assert!(!handler.is_capturing());
@@ -141,16 +215,18 @@ impl RdevInputHandler {
         Self {
             capturing: Arc::new(AtomicBool::new(false)),
             block_local: Arc::new(AtomicBool::new(false)),
-            kill_switch: Arc::new(StdRwLock::new(None)),
+            kill_switch: KillSwitch::default(),
             event_filter: Arc::new(StdRwLock::new(None)),
-            pressed_keys: Arc::new(StdRwLock::new(Vec::new())),
+            scroll: Arc::new(std::sync::Mutex::new(ScrollAccumulator::default())),
         }
     }
 
     /// Sets a kill switch key combination.
     ///
     /// When all specified keys are pressed simultaneously, the kill switch
-    /// activates. This is typically used as an emergency stop mechanism.
+    /// activates: capture stops and [`Event::KillSwitch`] is sent instead of
+    /// the last key press. This is typically used as an emergency stop
+    /// mechanism. Can be changed while capturing.
     ///
     /// # Examples
     ///
@@ -163,14 +239,14 @@ impl RdevInputHandler {
     /// assert!(handler.has_kill_switch());
     /// ```
     pub fn set_kill_switch(&self, keys: Vec<Key>) {
-        if let Ok(mut lock) = self.kill_switch.write() {
+        if let Ok(mut lock) = self.kill_switch.keys.write() {
             *lock = Some(keys);
         }
     }
 
     /// Returns whether a kill switch is currently configured.
     pub fn has_kill_switch(&self) -> bool {
-        if let Ok(lock) = self.kill_switch.read() {
+        if let Ok(lock) = self.kill_switch.keys.read() {
             lock.is_some()
         } else {
             false
@@ -239,30 +315,6 @@ impl RdevInputHandler {
             false
         }
     }
-
-    #[allow(dead_code)]
-    fn check_kill_switch(&self, key: &Key) -> bool {
-        if let Ok(mut pressed) = self.pressed_keys.write() {
-            if !pressed.contains(key) {
-                pressed.push(key.clone());
-            }
-
-            if let Ok(kill_switch_guard) = self.kill_switch.read() {
-                if let Some(keys) = kill_switch_guard.as_ref() {
-                    // Check if all kill switch keys are pressed
-                    return keys.iter().all(|k| pressed.contains(k));
-                }
-            }
-        }
-        false
-    }
-
-    #[allow(dead_code)]
-    fn handle_key_release(&self, key: &Key) {
-        if let Ok(mut pressed) = self.pressed_keys.write() {
-            pressed.retain(|k| k != key);
-        }
-    }
 }
 
 impl InputHandler for RdevInputHandler {
@@ -274,6 +326,8 @@ impl InputHandler for RdevInputHandler {
         self.capturing.store(true, Ordering::SeqCst);
         let capturing = self.capturing.clone();
         let block_local = self.block_local.clone();
+        let kill_switch = self.kill_switch.clone();
+        let mut held = ModifierTracker::new();
 
         // Create a standard channel for the rdev thread
         let (std_tx, std_rx) = std::sync::mpsc::channel::<Event>();
@@ -288,17 +342,30 @@ impl InputHandler for RdevInputHandler {
 
                 tracing::trace!("rdev event received: {:?}", event.event_type);
 
-                // Convert rdev event to our Event type
-                let our_event = match convert_rdev_to_event(event.event_type) {
+                // Convert rdev event to our Event type; keys we do not know
+                // are sent as the text they composed, if any
+                let modifiers = Modifiers::from_keys(held.held());
+                let our_event = match convert_rdev_to_event(event.event_type, modifiers)
+                    .or_else(|| composed_text(&event).map(|text| Event::TextInput { text }))
+                {
                     Some(e) => e,
                     None => {
                         tracing::trace!("Event ignored (not converted)");
                         return;
                     }
                 };
+                held.observe(&our_event);
 
                 tracing::trace!("Converted event: {:?}", our_event);
 
+                // Emergency stop: nothing more is captured after this
+                if kill_switch.observe(&our_event) {
+                    capturing.store(false, Ordering::SeqCst);
+                    tracing::warn!("Kill switch pressed, input capture stopped");
+                    let _ = std_tx.send(Event::KillSwitch);
+                    return;
+                }
+
                 // Send through standard channel
                 if let Err(e) = std_tx.send(our_event) {
                     tracing::error!("Failed to send event through channel: {:?}", e);
@@ -353,6 +420,29 @@ impl InputHandler for RdevInputHandler {
     }
 
     async fn inject_event(&self, event: Event) -> Result<()> {
+        // Text is typed as characters, not converted to a key
+        if let Event::TextInput { text } = event {
+            return tokio::task::spawn_blocking(move || inject_text(&text))
+                .await
+                .context("Task join error")?;
+        }
+
+        // rdev scrolls by whole detents only
+        let event = match event {
+            Event::MouseScrollPrecise { dx_120, dy_120 } => {
+                let detents = self
+                    .scroll
+                    .lock()
+                    .map_err(|_| anyhow::anyhow!("Scroll accumulator poisoned"))?
+                    .push(dx_120, dy_120);
+                match detents {
+                    Some((delta_x, delta_y)) => Event::MouseScroll { delta_x, delta_y },
+                    None => return Ok(()),
+                }
+            }
+            other => other,
+        };
+
         let rdev_event =
             convert_event_to_rdev(&event).context("Failed to convert event to rdev format")?;
 
@@ -412,9 +502,8 @@ impl InputHandler for RdevInputHandler {
 
         #[cfg(target_os = "macos")]
         {
-            // On macOS, check Accessibility permissions
-            // This would require objective-c bindings or a crate like cocoa
-            true
+            // On macOS, both capture and injection need Accessibility access
+            crate::core::permissions::is_accessibility_trusted()
         }
 
         #[cfg(target_os = "linux")]
@@ -439,8 +528,9 @@ impl InputHandler for RdevInputHandler {
 /// Converts an rdev event type to our internal Event representation.
 ///
 /// Maps platform-specific rdev events to our unified Event enum. Returns
-/// `None` for events that cannot be mapped or are not supported.
-fn convert_rdev_to_event(event: RdevEventType) -> Option<Event> {
+/// `None` for events that cannot be mapped or are not supported. Wheel
+/// events carry the `modifiers` held, as tracked from the key events.
+fn convert_rdev_to_event(event: RdevEventType, modifiers: Modifiers) -> Option<Event> {
     match event {
         RdevEventType::MouseMove { x, y } => Some(Event::MouseMove {
             x: x as i32,
@@ -454,7 +544,9 @@ fn convert_rdev_to_event(event: RdevEventType) -> Option<Event> {
             let our_button = convert_rdev_button(button)?;
             Some(Event::MouseButtonRelease { button: our_button })
         }
-        RdevEventType::Wheel { delta_x, delta_y } => Some(Event::MouseScroll { delta_x, delta_y }),
+        RdevEventType::Wheel { delta_x, delta_y } => {
+            Some(Event::MouseScroll { delta_x, delta_y }.with_modifiers(modifiers))
+        }
         RdevEventType::KeyPress(key) => {
             let our_key = convert_rdev_key(key)?;
             Some(Event::KeyPress { key: our_key })
@@ -470,7 +562,9 @@ fn convert_rdev_to_event(event: RdevEventType) -> Option<Event> {
 ///
 /// Maps our unified Event enum to platform-specific rdev event types.
 /// Returns `None` for events that cannot be injected (e.g., MouseClick,
-/// FocusGrant, FocusRelease, Heartbeat).
+/// FocusGrant, FocusRelease, Heartbeat, ClipboardSync). Relative moves must be
+/// turned into absolute ones by the caller, which knows the cursor position,
+/// and text input is typed by [`inject_text`].
 fn convert_event_to_rdev(event: &Event) -> Option<RdevEventType> {
     match event {
         Event::MouseMove { x, y } => Some(RdevEventType::MouseMove {
@@ -485,7 +579,11 @@ fn convert_event_to_rdev(event: &Event) -> Option<RdevEventType> {
             let rdev_button = convert_button_to_rdev(button)?;
             Some(RdevEventType::ButtonRelease(rdev_button))
         }
-        Event::MouseScroll { delta_x, delta_y } => Some(RdevEventType::Wheel {
+        // The modifiers are held by their own key events
+        Event::MouseScroll { delta_x, delta_y }
+        | Event::MouseScrollWithModifiers {
+            delta_x, delta_y, ..
+        } => Some(RdevEventType::Wheel {
             delta_x: *delta_x,
             delta_y: *delta_y,
         }),
@@ -501,18 +599,195 @@ fn convert_event_to_rdev(event: &Event) -> Option<RdevEventType> {
         Event::MouseClick { .. }
         | Event::FocusGrant { .. }
         | Event::FocusRelease
-        | Event::Heartbeat => None,
+        | Event::Heartbeat { .. }
+        | Event::ClipboardSync { .. }
+        | Event::Hello { .. }
+        | Event::PskRotate { .. }
+        | Event::PskRotateAck { .. }
+        | Event::MouseMoveRelative { .. }
+        | Event::FocusGrantChain { .. }
+        | Event::ClipboardChunk { .. }
+        | Event::KillSwitch
+        | Event::TextInput { .. }
+        | Event::Refused { .. }
+        | Event::MouseScrollPrecise { .. }
+        | Event::Disconnect { .. }
+        | Event::HeartbeatAck { .. }
+        | Event::Ping { .. }
+        | Event::Pong { .. }
+        | Event::EdgeReached { .. } => None,
+    }
+}
+
+/// Returns the text composed by a key press, if it is printable.
+///
+/// rdev reports the character a key produced with the current layout, dead
+/// keys included where the platform supports it.
+fn composed_text(event: &rdev::Event) -> Option<String> {
+    if !matches!(event.event_type, RdevEventType::KeyPress(_)) {
+        return None;
     }
+    event
+        .name
+        .clone()
+        .filter(|text| !text.is_empty() && !text.chars().any(char::is_control))
 }
 
+/// Pause between the keys of a unicode entry sequence, so the input method keeps up.
+#[cfg(target_os = "linux")]
+pub(crate) const UNICODE_ENTRY_DELAY: std::time::Duration = std::time::Duration::from_millis(2);
+
+/// Types `text` on this machine, whatever the keyboard layout.
+///
+/// On Linux each character is entered with the Ctrl+Shift+U unicode sequence
+/// of IBus and GTK; on Windows each UTF-16 unit is sent with
+/// `KEYEVENTF_UNICODE`.
+///
+/// # Errors
+///
+/// Returns an error if the events cannot be injected, or on other platforms.
+fn inject_text(text: &str) -> Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        for c in text.chars() {
+            for event in unicode_entry_sequence(c)
+                .iter()
+                .filter_map(convert_event_to_rdev)
+            {
+                simulate(&event)
+                    .map_err(|e| anyhow::anyhow!("Failed to simulate event: {:?}", e))?;
+                std::thread::sleep(UNICODE_ENTRY_DELAY);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::winuser::{
+            SendInput, INPUT, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE,
+        };
+
+        let mut inputs = Vec::new();
+        for unit in text.encode_utf16() {
+            for flags in [KEYEVENTF_UNICODE, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP] {
+                // SAFETY: INPUT is a plain C struct, valid when zeroed
+                let mut input: INPUT = unsafe { std::mem::zeroed() };
+                input.type_ = INPUT_KEYBOARD;
+                // SAFETY: the union holds a KEYBDINPUT for INPUT_KEYBOARD
+                unsafe {
+                    *input.u.ki_mut() = KEYBDINPUT {
+                        wVk: 0,
+                        wScan: unit,
+                        dwFlags: flags,
+                        time: 0,
+                        dwExtraInfo: 0,
+                    };
+                }
+                inputs.push(input);
+            }
+        }
+        // SAFETY: `inputs` is a valid array of INPUT structures of the given size
+        let sent = unsafe {
+            SendInput(
+                inputs.len() as u32,
+                inputs.as_mut_ptr(),
+                std::mem::size_of::<INPUT>() as i32,
+            )
+        };
+        if sent as usize != inputs.len() {
+            anyhow::bail!("SendInput injected {} of {} key events", sent, inputs.len());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        anyhow::bail!(
+            "Text input injection is not supported on this platform: {:?}",
+            text
+        )
+    }
+}
+
+/// Returns the key events typing `c` with the Ctrl+Shift+U unicode entry.
+///
+/// The code point is typed in hexadecimal and confirmed with Space. Every
+/// Linux backend types [`Event::TextInput`] this way, pausing
+/// [`UNICODE_ENTRY_DELAY`] after each event.
+#[cfg(target_os = "linux")]
+pub(crate) fn unicode_entry_sequence(c: char) -> Vec<Event> {
+    const HEX_KEYS: [Key; 16] = [
+        Key::Num0,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+        Key::KeyA,
+        Key::KeyB,
+        Key::KeyC,
+        Key::KeyD,
+        Key::KeyE,
+        Key::KeyF,
+    ];
+    let tap = |key: Key| {
+        [
+            Event::KeyPress { key: key.clone() },
+            Event::KeyRelease { key },
+        ]
+    };
+
+    let mut events = vec![
+        Event::KeyPress {
+            key: Key::ControlLeft,
+        },
+        Event::KeyPress {
+            key: Key::ShiftLeft,
+        },
+    ];
+    events.extend(tap(Key::KeyU));
+    events.push(Event::KeyRelease {
+        key: Key::ShiftLeft,
+    });
+    events.push(Event::KeyRelease {
+        key: Key::ControlLeft,
+    });
+    for digit in format!("{:x}", c as u32).chars() {
+        let value = digit.to_digit(16).unwrap_or(0) as usize;
+        events.extend(tap(HEX_KEYS[value].clone()));
+    }
+    events.extend(tap(Key::Space));
+    events
+}
+
+/// rdev button code of the "back" side button (`XBUTTON1` on Windows, button 8 on X11).
+#[cfg(target_os = "windows")]
+const RDEV_BUTTON_BACK: u8 = 1;
+#[cfg(not(target_os = "windows"))]
+const RDEV_BUTTON_BACK: u8 = 8;
+
+/// rdev button code of the "forward" side button (`XBUTTON2` on Windows, button 9 on X11).
+#[cfg(target_os = "windows")]
+const RDEV_BUTTON_FORWARD: u8 = 2;
+#[cfg(not(target_os = "windows"))]
+const RDEV_BUTTON_FORWARD: u8 = 9;
+
 /// Converts an rdev mouse button to our MouseButton type.
 ///
-/// Returns `None` for buttons that are not Left, Right, or Middle.
+/// Side buttons are reported by rdev as `Button::Unknown` with a
+/// platform-specific code. Returns `None` for any other unknown button.
 fn convert_rdev_button(button: Button) -> Option<MouseButton> {
     match button {
         Button::Left => Some(MouseButton::Left),
         Button::Right => Some(MouseButton::Right),
         Button::Middle => Some(MouseButton::Middle),
+        Button::Unknown(RDEV_BUTTON_BACK) => Some(MouseButton::Back),
+        Button::Unknown(RDEV_BUTTON_FORWARD) => Some(MouseButton::Forward),
         _ => None,
     }
 }
@@ -526,16 +801,16 @@ fn convert_button_to_rdev(button: &MouseButton) -> Option<Button> {
         MouseButton::Left => Some(Button::Left),
         MouseButton::Right => Some(Button::Right),
         MouseButton::Middle => Some(Button::Middle),
+        MouseButton::Back => Some(Button::Unknown(RDEV_BUTTON_BACK)),
+        MouseButton::Forward => Some(Button::Unknown(RDEV_BUTTON_FORWARD)),
     }
 }
 
 /// Converts an rdev key to our internal Key representation.
 ///
-/// Maps platform-specific rdev keys to our unified Key enum. Only common
-/// keys (letters, modifiers, special keys) are supported. Returns `None`
+/// Maps platform-specific rdev keys to our unified Key enum. Returns `None`
 /// for unmapped keys.
 fn convert_rdev_key(key: RdevKey) -> Option<Key> {
-    // Map common keys - this is a simplified mapping
     match key {
         // Letters
         RdevKey::KeyA => Some(Key::KeyA),
@@ -581,6 +856,86 @@ fn convert_rdev_key(key: RdevKey) -> Option<Key> {
         RdevKey::Space => Some(Key::Space),
         RdevKey::Backspace => Some(Key::Backspace),
         RdevKey::Tab => Some(Key::Tab),
+        RdevKey::Delete => Some(Key::Delete),
+
+        // Function keys
+        RdevKey::F1 => Some(Key::F1),
+        RdevKey::F2 => Some(Key::F2),
+        RdevKey::F3 => Some(Key::F3),
+        RdevKey::F4 => Some(Key::F4),
+        RdevKey::F5 => Some(Key::F5),
+        RdevKey::F6 => Some(Key::F6),
+        RdevKey::F7 => Some(Key::F7),
+        RdevKey::F8 => Some(Key::F8),
+        RdevKey::F9 => Some(Key::F9),
+        RdevKey::F10 => Some(Key::F10),
+        RdevKey::F11 => Some(Key::F11),
+        RdevKey::F12 => Some(Key::F12),
+
+        // Number row
+        RdevKey::Num0 => Some(Key::Num0),
+        RdevKey::Num1 => Some(Key::Num1),
+        RdevKey::Num2 => Some(Key::Num2),
+        RdevKey::Num3 => Some(Key::Num3),
+        RdevKey::Num4 => Some(Key::Num4),
+        RdevKey::Num5 => Some(Key::Num5),
+        RdevKey::Num6 => Some(Key::Num6),
+        RdevKey::Num7 => Some(Key::Num7),
+        RdevKey::Num8 => Some(Key::Num8),
+        RdevKey::Num9 => Some(Key::Num9),
+
+        // Punctuation
+        RdevKey::Minus => Some(Key::Minus),
+        RdevKey::Equal => Some(Key::Equal),
+        RdevKey::LeftBracket => Some(Key::LeftBracket),
+        RdevKey::RightBracket => Some(Key::RightBracket),
+        RdevKey::SemiColon => Some(Key::SemiColon),
+        RdevKey::Quote => Some(Key::Quote),
+        RdevKey::BackQuote => Some(Key::BackQuote),
+        RdevKey::BackSlash => Some(Key::BackSlash),
+        RdevKey::IntlBackslash => Some(Key::IntlBackslash),
+        RdevKey::Comma => Some(Key::Comma),
+        RdevKey::Dot => Some(Key::Dot),
+        RdevKey::Slash => Some(Key::Slash),
+
+        // Navigation
+        RdevKey::UpArrow => Some(Key::UpArrow),
+        RdevKey::DownArrow => Some(Key::DownArrow),
+        RdevKey::LeftArrow => Some(Key::LeftArrow),
+        RdevKey::RightArrow => Some(Key::RightArrow),
+        RdevKey::Insert => Some(Key::Insert),
+        RdevKey::Home => Some(Key::Home),
+        RdevKey::End => Some(Key::End),
+        RdevKey::PageUp => Some(Key::PageUp),
+        RdevKey::PageDown => Some(Key::PageDown),
+
+        // Locks and system keys
+        RdevKey::CapsLock => Some(Key::CapsLock),
+        RdevKey::NumLock => Some(Key::NumLock),
+        RdevKey::ScrollLock => Some(Key::ScrollLock),
+        RdevKey::PrintScreen => Some(Key::PrintScreen),
+        RdevKey::Pause => Some(Key::Pause),
+
+        // Numpad
+        RdevKey::Kp0 => Some(Key::Kp0),
+        RdevKey::Kp1 => Some(Key::Kp1),
+        RdevKey::Kp2 => Some(Key::Kp2),
+        RdevKey::Kp3 => Some(Key::Kp3),
+        RdevKey::Kp4 => Some(Key::Kp4),
+        RdevKey::Kp5 => Some(Key::Kp5),
+        RdevKey::Kp6 => Some(Key::Kp6),
+        RdevKey::Kp7 => Some(Key::Kp7),
+        RdevKey::Kp8 => Some(Key::Kp8),
+        RdevKey::Kp9 => Some(Key::Kp9),
+        RdevKey::KpPlus => Some(Key::KpPlus),
+        RdevKey::KpMinus => Some(Key::KpMinus),
+        RdevKey::KpMultiply => Some(Key::KpMultiply),
+        RdevKey::KpDivide => Some(Key::KpDivide),
+        RdevKey::KpReturn => Some(Key::KpReturn),
+        RdevKey::KpDelete => Some(Key::KpDelete),
+
+        // Media keys have no rdev name, only a platform key code
+        RdevKey::Unknown(code) => media_key_from_code(code),
 
         _ => None, // Unmapped keys
     }
@@ -588,8 +943,8 @@ fn convert_rdev_key(key: RdevKey) -> Option<Key> {
 
 /// Converts our internal Key to rdev's Key type for injection.
 ///
-/// Maps our unified Key enum to platform-specific rdev key codes.
-/// Only common keys (letters, modifiers, special keys) are supported.
+/// Maps our unified Key enum to platform-specific rdev key codes. Media keys
+/// are only available where their platform key code is known.
 fn convert_key_to_rdev(key: &Key) -> Option<RdevKey> {
     match key {
         // Letters
@@ -636,9 +991,146 @@ fn convert_key_to_rdev(key: &Key) -> Option<RdevKey> {
         Key::Space => Some(RdevKey::Space),
         Key::Backspace => Some(RdevKey::Backspace),
         Key::Tab => Some(RdevKey::Tab),
+        Key::Delete => Some(RdevKey::Delete),
+
+        // Function keys
+        Key::F1 => Some(RdevKey::F1),
+        Key::F2 => Some(RdevKey::F2),
+        Key::F3 => Some(RdevKey::F3),
+        Key::F4 => Some(RdevKey::F4),
+        Key::F5 => Some(RdevKey::F5),
+        Key::F6 => Some(RdevKey::F6),
+        Key::F7 => Some(RdevKey::F7),
+        Key::F8 => Some(RdevKey::F8),
+        Key::F9 => Some(RdevKey::F9),
+        Key::F10 => Some(RdevKey::F10),
+        Key::F11 => Some(RdevKey::F11),
+        Key::F12 => Some(RdevKey::F12),
+
+        // Number row
+        Key::Num0 => Some(RdevKey::Num0),
+        Key::Num1 => Some(RdevKey::Num1),
+        Key::Num2 => Some(RdevKey::Num2),
+        Key::Num3 => Some(RdevKey::Num3),
+        Key::Num4 => Some(RdevKey::Num4),
+        Key::Num5 => Some(RdevKey::Num5),
+        Key::Num6 => Some(RdevKey::Num6),
+        Key::Num7 => Some(RdevKey::Num7),
+        Key::Num8 => Some(RdevKey::Num8),
+        Key::Num9 => Some(RdevKey::Num9),
+
+        // Punctuation
+        Key::Minus => Some(RdevKey::Minus),
+        Key::Equal => Some(RdevKey::Equal),
+        Key::LeftBracket => Some(RdevKey::LeftBracket),
+        Key::RightBracket => Some(RdevKey::RightBracket),
+        Key::SemiColon => Some(RdevKey::SemiColon),
+        Key::Quote => Some(RdevKey::Quote),
+        Key::BackQuote => Some(RdevKey::BackQuote),
+        Key::BackSlash => Some(RdevKey::BackSlash),
+        Key::IntlBackslash => Some(RdevKey::IntlBackslash),
+        Key::Comma => Some(RdevKey::Comma),
+        Key::Dot => Some(RdevKey::Dot),
+        Key::Slash => Some(RdevKey::Slash),
+
+        // Navigation
+        Key::UpArrow => Some(RdevKey::UpArrow),
+        Key::DownArrow => Some(RdevKey::DownArrow),
+        Key::LeftArrow => Some(RdevKey::LeftArrow),
+        Key::RightArrow => Some(RdevKey::RightArrow),
+        Key::Insert => Some(RdevKey::Insert),
+        Key::Home => Some(RdevKey::Home),
+        Key::End => Some(RdevKey::End),
+        Key::PageUp => Some(RdevKey::PageUp),
+        Key::PageDown => Some(RdevKey::PageDown),
+
+        // Locks and system keys
+        Key::CapsLock => Some(RdevKey::CapsLock),
+        Key::NumLock => Some(RdevKey::NumLock),
+        Key::ScrollLock => Some(RdevKey::ScrollLock),
+        Key::PrintScreen => Some(RdevKey::PrintScreen),
+        Key::Pause => Some(RdevKey::Pause),
+
+        // Numpad
+        Key::Kp0 => Some(RdevKey::Kp0),
+        Key::Kp1 => Some(RdevKey::Kp1),
+        Key::Kp2 => Some(RdevKey::Kp2),
+        Key::Kp3 => Some(RdevKey::Kp3),
+        Key::Kp4 => Some(RdevKey::Kp4),
+        Key::Kp5 => Some(RdevKey::Kp5),
+        Key::Kp6 => Some(RdevKey::Kp6),
+        Key::Kp7 => Some(RdevKey::Kp7),
+        Key::Kp8 => Some(RdevKey::Kp8),
+        Key::Kp9 => Some(RdevKey::Kp9),
+        Key::KpPlus => Some(RdevKey::KpPlus),
+        Key::KpMinus => Some(RdevKey::KpMinus),
+        Key::KpMultiply => Some(RdevKey::KpMultiply),
+        Key::KpDivide => Some(RdevKey::KpDivide),
+        Key::KpReturn => Some(RdevKey::KpReturn),
+        Key::KpDelete => Some(RdevKey::KpDelete),
+
+        // Media keys
+        Key::MediaPlayPause
+        | Key::MediaStop
+        | Key::MediaNext
+        | Key::MediaPrevious
+        | Key::VolumeUp
+        | Key::VolumeDown
+        | Key::VolumeMute => media_key_code(key).map(RdevKey::Unknown),
     }
 }
 
+/// Platform key codes of the media keys, which rdev reports as `Unknown`.
+///
+/// X11 keycodes on Linux and virtual-key codes on Windows. macOS sends media
+/// keys as system events that rdev does not see.
+const MEDIA_KEY_CODES: &[(Key, u32)] = &[
+    #[cfg(target_os = "linux")]
+    (Key::VolumeMute, 121),
+    #[cfg(target_os = "linux")]
+    (Key::VolumeDown, 122),
+    #[cfg(target_os = "linux")]
+    (Key::VolumeUp, 123),
+    #[cfg(target_os = "linux")]
+    (Key::MediaNext, 171),
+    #[cfg(target_os = "linux")]
+    (Key::MediaPlayPause, 172),
+    #[cfg(target_os = "linux")]
+    (Key::MediaPrevious, 173),
+    #[cfg(target_os = "linux")]
+    (Key::MediaStop, 174),
+    #[cfg(target_os = "windows")]
+    (Key::VolumeMute, 0xAD),
+    #[cfg(target_os = "windows")]
+    (Key::VolumeDown, 0xAE),
+    #[cfg(target_os = "windows")]
+    (Key::VolumeUp, 0xAF),
+    #[cfg(target_os = "windows")]
+    (Key::MediaNext, 0xB0),
+    #[cfg(target_os = "windows")]
+    (Key::MediaPrevious, 0xB1),
+    #[cfg(target_os = "windows")]
+    (Key::MediaStop, 0xB2),
+    #[cfg(target_os = "windows")]
+    (Key::MediaPlayPause, 0xB3),
+];
+
+/// Returns the media key with platform key `code`, if any.
+fn media_key_from_code(code: u32) -> Option<Key> {
+    MEDIA_KEY_CODES
+        .iter()
+        .find(|(_, known)| *known == code)
+        .map(|(key, _)| key.clone())
+}
+
+/// Returns the platform key code of a media key.
+fn media_key_code(key: &Key) -> Option<u32> {
+    MEDIA_KEY_CODES
+        .iter()
+        .find(|(known, _)| known == key)
+        .map(|(_, code)| *code)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -648,6 +1140,64 @@ mod tests {
         let _handler = RdevInputHandler::new();
     }
 
+    #[test]
+    fn test_scroll_accumulator_keeps_partial_detents() {
+        let mut scroll = ScrollAccumulator::default();
+        // A touchpad scrolling down in eighths of a detent
+        for _ in 0..7 {
+            assert_eq!(scroll.push(0, 15), None);
+        }
+        assert_eq!(scroll.push(0, 15), Some((0, 1)));
+
+        // Both axes at once, the remainder keeping its sign
+        assert_eq!(scroll.push(-300, 130), Some((-2, 1)));
+        assert_eq!(scroll.push(-60, -10), Some((-1, 0)));
+        assert_eq!(scroll.push(0, 0), None);
+        assert!(convert_event_to_rdev(&Event::MouseScrollPrecise {
+            dx_120: 0,
+            dy_120: 120
+        })
+        .is_none());
+    }
+
+    #[test]
+    fn test_wheel_conversion_carries_modifiers() {
+        let wheel = RdevEventType::Wheel {
+            delta_x: 0,
+            delta_y: -1,
+        };
+        assert_eq!(
+            convert_rdev_to_event(wheel, Modifiers::empty()),
+            Some(Event::MouseScroll {
+                delta_x: 0,
+                delta_y: -1
+            })
+        );
+        let zoom = convert_rdev_to_event(wheel, Modifiers::CTRL | Modifiers::SHIFT).unwrap();
+        assert_eq!(
+            zoom,
+            Event::MouseScrollWithModifiers {
+                delta_x: 0,
+                delta_y: -1,
+                modifiers: Modifiers::CTRL | Modifiers::SHIFT,
+            }
+        );
+        // Keys are not affected by the modifiers held
+        assert_eq!(
+            convert_rdev_to_event(RdevEventType::KeyPress(RdevKey::KeyA), Modifiers::CTRL),
+            Some(Event::KeyPress { key: Key::KeyA })
+        );
+
+        // Injected as a plain wheel event
+        assert!(matches!(
+            convert_event_to_rdev(&zoom),
+            Some(RdevEventType::Wheel {
+                delta_x: 0,
+                delta_y: -1
+            })
+        ));
+    }
+
     #[test]
     fn test_event_conversion() {
         let event = Event::MouseMove { x: 100, y: 200 };
@@ -662,4 +1212,94 @@ mod tests {
             _ => panic!("Wrong event type"),
         }
     }
+
+    #[test]
+    fn test_side_button_round_trip() {
+        for button in [MouseButton::Back, MouseButton::Forward] {
+            let rdev_button = convert_button_to_rdev(&button).unwrap();
+            assert_eq!(convert_rdev_button(rdev_button), Some(button));
+        }
+    }
+
+    #[test]
+    fn test_text_input_is_not_a_key() {
+        let text = Event::TextInput {
+            text: "é".to_string(),
+        };
+        assert!(convert_event_to_rdev(&text).is_none());
+
+        let press = |event_type, name: Option<&str>| rdev::Event {
+            time: std::time::SystemTime::now(),
+            name: name.map(str::to_string),
+            event_type,
+        };
+        let unknown = RdevEventType::KeyPress(RdevKey::Unknown(0x1000));
+        assert_eq!(
+            composed_text(&press(unknown, Some("ñ"))).as_deref(),
+            Some("ñ")
+        );
+        assert_eq!(composed_text(&press(unknown, Some("\u{8}"))), None);
+        assert_eq!(
+            composed_text(&press(RdevEventType::KeyRelease(RdevKey::KeyA), Some("a"))),
+            None
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_unicode_entry_sequence_types_hex_code_point() {
+        let sequence = unicode_entry_sequence('é');
+        // Ctrl+Shift+U, then "e9" and Space
+        assert_eq!(
+            sequence[..4],
+            [
+                Event::KeyPress {
+                    key: Key::ControlLeft
+                },
+                Event::KeyPress {
+                    key: Key::ShiftLeft
+                },
+                Event::KeyPress { key: Key::KeyU },
+                Event::KeyRelease { key: Key::KeyU },
+            ]
+        );
+        let typed: Vec<Key> = sequence[6..]
+            .iter()
+            .filter_map(|event| match event {
+                Event::KeyPress { key } => Some(key.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(typed, vec![Key::KeyE, Key::Num9, Key::Space]);
+        // rdev injects every key of it
+        assert!(sequence
+            .iter()
+            .all(|event| convert_event_to_rdev(event).is_some()));
+
+        // Characters outside the BMP have more digits: U+1F44D
+        assert_eq!(unicode_entry_sequence('👍').len(), 6 + 2 * 6);
+    }
+
+    #[test]
+    fn test_kill_switch_triggers_on_full_combination() {
+        let handler = RdevInputHandler::new();
+        let press = |key| Event::KeyPress { key };
+        let release = |key| Event::KeyRelease { key };
+
+        // No combination configured
+        assert!(!handler.kill_switch.observe(&press(Key::KeyQ)));
+        handler.kill_switch.observe(&release(Key::KeyQ));
+
+        handler.set_kill_switch(vec![Key::ControlLeft, Key::AltLeft, Key::KeyQ]);
+        let tracker = &handler.kill_switch;
+        assert!(!tracker.observe(&press(Key::ControlRight)));
+        assert!(!tracker.observe(&press(Key::AltLeft)));
+        assert!(!tracker.observe(&Event::MouseMove { x: 1, y: 1 }));
+
+        // Releasing a key of the combination breaks it
+        assert!(!tracker.observe(&release(Key::AltLeft)));
+        assert!(!tracker.observe(&press(Key::KeyQ)));
+
+        assert!(tracker.observe(&press(Key::AltLeft)));
+    }
 }
@@ -0,0 +1,191 @@
+use anyhow::Result;
+use std::path::Path;
+
+/// Environment variable naming the Wayland compositor socket.
+pub const WAYLAND_DISPLAY_ENV: &str = "WAYLAND_DISPLAY";
+
+/// How an agent injects the events it receives, on Linux.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::input_backend::InputBackend;
+///
+/// assert_eq!("wayland".parse::<InputBackend>().unwrap(), InputBackend::Wayland);
+/// assert_eq!(InputBackend::Uinput.to_string(), "uinput");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputBackend {
+    /// Kernel virtual devices through `/dev/uinput`, with evdev for capture
+    Uinput,
+    /// The compositor's wlroots virtual pointer and virtual keyboard protocols
+    ///
+    /// Injection only: capture still needs the evdev devices.
+    Wayland,
+}
+
+impl std::fmt::Display for InputBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputBackend::Uinput => write!(f, "uinput"),
+            InputBackend::Wayland => write!(f, "wayland"),
+        }
+    }
+}
+
+impl std::str::FromStr for InputBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "uinput" | "evdev" => Ok(InputBackend::Uinput),
+            "wayland" => Ok(InputBackend::Wayland),
+            other => anyhow::bail!(
+                "Unknown input backend '{}' (expected uinput or wayland)",
+                other
+            ),
+        }
+    }
+}
+
+/// Picks the injection backend from what was asked for and what is usable.
+///
+/// Without `requested`, uinput is preferred and Wayland is the fallback
+/// when a compositor is running. A requested backend is only checked.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::input_backend::{select_backend, InputBackend};
+///
+/// // A Wayland session where /dev/uinput is not writable
+/// assert_eq!(select_backend(None, true, false).unwrap(), InputBackend::Wayland);
+/// assert_eq!(select_backend(None, true, true).unwrap(), InputBackend::Uinput);
+/// assert!(select_backend(None, false, false).is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the requested backend, or without one either
+/// backend, cannot be used.
+pub fn select_backend(
+    requested: Option<InputBackend>,
+    wayland_display: bool,
+    uinput_available: bool,
+) -> Result<InputBackend> {
+    match requested {
+        Some(InputBackend::Uinput) if !uinput_available => anyhow::bail!(
+            "The uinput input backend was requested but /dev/uinput cannot be opened for writing \
+             (run `multishiva doctor` for the fix)"
+        ),
+        Some(InputBackend::Wayland) if !wayland_display => anyhow::bail!(
+            "The wayland input backend was requested but {} is not set",
+            WAYLAND_DISPLAY_ENV
+        ),
+        Some(backend) => Ok(backend),
+        None if uinput_available => Ok(InputBackend::Uinput),
+        None if wayland_display => Ok(InputBackend::Wayland),
+        None => anyhow::bail!(
+            "No input backend can inject events: /dev/uinput cannot be opened for writing \
+             and {} is not set (run `multishiva doctor` for the fix)",
+            WAYLAND_DISPLAY_ENV
+        ),
+    }
+}
+
+/// Calls [`select_backend`] with the state of this machine.
+///
+/// # Errors
+///
+/// Returns an error if no usable backend matches `requested`.
+pub fn detect_backend(requested: Option<InputBackend>) -> Result<InputBackend> {
+    select_backend(
+        requested,
+        std::env::var_os(WAYLAND_DISPLAY_ENV).is_some_and(|display| !display.is_empty()),
+        uinput_available(Path::new("/dev/uinput")),
+    )
+}
+
+/// Returns `true` if `path` can be opened for writing, as uinput needs.
+fn uinput_available(path: &Path) -> bool {
+    std::fs::OpenOptions::new().write(true).open(path).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_backend_decision_matrix() {
+        use InputBackend::{Uinput, Wayland};
+
+        // (requested, WAYLAND_DISPLAY set, uinput writable) -> backend, None for an error
+        let cases = [
+            (None, false, false, None),
+            (None, false, true, Some(Uinput)),
+            (None, true, false, Some(Wayland)),
+            (None, true, true, Some(Uinput)),
+            (Some(Uinput), false, false, None),
+            (Some(Uinput), true, false, None),
+            (Some(Uinput), false, true, Some(Uinput)),
+            (Some(Uinput), true, true, Some(Uinput)),
+            (Some(Wayland), false, false, None),
+            (Some(Wayland), false, true, None),
+            (Some(Wayland), true, false, Some(Wayland)),
+            (Some(Wayland), true, true, Some(Wayland)),
+        ];
+        for (requested, wayland, uinput, expected) in cases {
+            let selected = select_backend(requested, wayland, uinput);
+            assert_eq!(
+                selected.as_ref().ok().copied(),
+                expected,
+                "{:?} wayland={} uinput={}: {:?}",
+                requested,
+                wayland,
+                uinput,
+                selected
+            );
+        }
+    }
+
+    #[test]
+    fn test_select_backend_errors_name_the_cause() {
+        let neither = select_backend(None, false, false).unwrap_err().to_string();
+        assert!(neither.contains("/dev/uinput"), "{}", neither);
+        assert!(neither.contains(WAYLAND_DISPLAY_ENV), "{}", neither);
+
+        let wayland = select_backend(Some(InputBackend::Wayland), false, true)
+            .unwrap_err()
+            .to_string();
+        assert!(wayland.contains(WAYLAND_DISPLAY_ENV), "{}", wayland);
+    }
+
+    #[test]
+    fn test_input_backend_parse() {
+        assert_eq!(
+            "Uinput".parse::<InputBackend>().unwrap(),
+            InputBackend::Uinput
+        );
+        assert_eq!(
+            "evdev".parse::<InputBackend>().unwrap(),
+            InputBackend::Uinput
+        );
+        assert!("x11".parse::<InputBackend>().is_err());
+        for backend in [InputBackend::Uinput, InputBackend::Wayland] {
+            assert_eq!(
+                backend.to_string().parse::<InputBackend>().unwrap(),
+                backend
+            );
+        }
+    }
+
+    #[test]
+    fn test_uinput_available_needs_write_access() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!uinput_available(&dir.path().join("uinput")));
+
+        let path = dir.path().join("writable");
+        std::fs::write(&path, "").unwrap();
+        assert!(uinput_available(&path));
+    }
+}
@@ -1,22 +1,253 @@
 use anyhow::{Context, Result};
-use evdev::{Device, EventType, InputEventKind, Key as EvdevKey};
+use evdev::{
+    AbsoluteAxisType, AttributeSetRef, Device, EventType, InputEventKind, Key as EvdevKey,
+    RelativeAxisType,
+};
 use std::os::unix::io::AsRawFd;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 
-use crate::core::events::{Event, Key, MouseButton};
+use crate::core::events::{Event, Key, Modifiers, MouseButton};
+use crate::core::focus::ModifierTracker;
 use crate::core::input::InputHandler;
 
+/// evdev key event value for a release.
+const KEY_RELEASE: i32 = 0;
+
+/// evdev key event value for a press.
+const KEY_PRESS: i32 = 1;
+
+/// evdev key event value for an autorepeat.
+const KEY_REPEAT: i32 = 2;
+
+/// Screen size assumed until the display server is queried.
+const DEFAULT_SCREEN_SIZE: (u32, u32) = (1920, 1080);
+
+/// Range of an absolute axis and the number of screen pixels it spans.
+///
+/// Absolute devices such as tablets and touchpads report positions in their
+/// own units, e.g. `0..=32767`; this maps them to screen pixels.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::input_evdev::AbsAxisInfo;
+///
+/// let axis = AbsAxisInfo { min: 0, max: 32767, screen_pixels: 1920 };
+/// assert_eq!(axis.to_screen(0), 0);
+/// assert_eq!(axis.to_screen(32767), 1919);
+/// assert_eq!(axis.to_screen(16384), 960);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbsAxisInfo {
+    /// Smallest value the device reports
+    pub min: i32,
+    /// Largest value the device reports
+    pub max: i32,
+    /// Screen extent the axis maps to, in pixels
+    pub screen_pixels: u32,
+}
+
+impl AbsAxisInfo {
+    /// Maps a raw axis value to a screen coordinate in `0..screen_pixels`.
+    ///
+    /// Values outside the device range are clamped.
+    pub fn to_screen(&self, value: i32) -> i32 {
+        let last_pixel = self.screen_pixels.saturating_sub(1) as i64;
+        let range = self.max as i64 - self.min as i64;
+        if range <= 0 {
+            return 0;
+        }
+        let offset = (value as i64 - self.min as i64).clamp(0, range);
+        ((offset * last_pixel + range / 2) / range) as i32
+    }
+}
+
+/// Absolute axes of one device, read when the device is opened.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct AbsAxes {
+    x: Option<AbsAxisInfo>,
+    y: Option<AbsAxisInfo>,
+    mt_x: Option<AbsAxisInfo>,
+    mt_y: Option<AbsAxisInfo>,
+    /// Multi-touch slot the following `ABS_MT_*` events refer to.
+    slot: i32,
+    /// The device reports `REL_WHEEL_HI_RES` along with `REL_WHEEL`.
+    hi_res_wheel: bool,
+    /// The device reports `REL_HWHEEL_HI_RES` along with `REL_HWHEEL`.
+    hi_res_hwheel: bool,
+}
+
+impl AbsAxes {
+    /// Reads the absolute axis ranges and high-resolution wheels of `device`.
+    fn read(device: &Device, screen_size: (u32, u32)) -> Self {
+        let mut axes = Self::default();
+        if let Some(relative) = device.supported_relative_axes() {
+            axes.hi_res_wheel = relative.contains(RelativeAxisType::REL_WHEEL_HI_RES);
+            axes.hi_res_hwheel = relative.contains(RelativeAxisType::REL_HWHEEL_HI_RES);
+        }
+        let (Some(supported), Ok(state)) =
+            (device.supported_absolute_axes(), device.get_abs_state())
+        else {
+            return axes;
+        };
+
+        let info = |axis: AbsoluteAxisType, screen_pixels: u32| {
+            if !supported.contains(axis) {
+                return None;
+            }
+            let abs = state[axis.0 as usize];
+            tracing::debug!(
+                "  {:?}: {}..={} ({} units/mm)",
+                axis,
+                abs.minimum,
+                abs.maximum,
+                abs.resolution
+            );
+            Some(AbsAxisInfo {
+                min: abs.minimum,
+                max: abs.maximum,
+                screen_pixels,
+            })
+        };
+        axes.x = info(AbsoluteAxisType::ABS_X, screen_size.0);
+        axes.y = info(AbsoluteAxisType::ABS_Y, screen_size.1);
+        axes.mt_x = info(AbsoluteAxisType::ABS_MT_POSITION_X, screen_size.0);
+        axes.mt_y = info(AbsoluteAxisType::ABS_MT_POSITION_Y, screen_size.1);
+        axes
+    }
+}
+
+/// An event device under `/dev/input` and what it can report.
+///
+/// # Examples
+///
+/// ```
+/// use evdev::{AttributeSet, EventType};
+/// use multishiva::core::input_evdev::DeviceInfo;
+///
+/// let mut events = AttributeSet::<EventType>::new();
+/// events.insert(EventType::RELATIVE);
+///
+/// let info = DeviceInfo::from_capabilities("/dev/input/event3".into(), Some("USB Mouse"), &events);
+/// assert!(info.mouse && !info.keyboard);
+/// assert!(info.is_input());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Device node, e.g. `/dev/input/event3`
+    pub path: PathBuf,
+    /// Name reported by the device, `Unknown` when it has none or cannot be opened
+    pub name: String,
+    /// Reports key events
+    pub keyboard: bool,
+    /// Reports relative or absolute motion
+    pub mouse: bool,
+    /// Why the device could not be opened, when it could not
+    pub error: Option<String>,
+}
+
+impl DeviceInfo {
+    /// Describes the device at `path` from its supported event types.
+    pub fn from_capabilities(
+        path: PathBuf,
+        name: Option<&str>,
+        events: &AttributeSetRef<EventType>,
+    ) -> Self {
+        Self {
+            path,
+            name: name.unwrap_or("Unknown").to_string(),
+            keyboard: events.contains(EventType::KEY),
+            mouse: events.contains(EventType::RELATIVE) || events.contains(EventType::ABSOLUTE),
+            error: None,
+        }
+    }
+
+    /// Describes a device at `path` that could not be opened.
+    pub fn unreadable(path: PathBuf, error: impl Into<String>) -> Self {
+        Self {
+            path,
+            name: "Unknown".to_string(),
+            keyboard: false,
+            mouse: false,
+            error: Some(error.into()),
+        }
+    }
+
+    /// Returns `true` if the device was opened and reports keyboard or mouse input.
+    pub fn is_input(&self) -> bool {
+        self.error.is_none() && (self.keyboard || self.mouse)
+    }
+}
+
+/// Lists every event device under `/dev/input`, including those that cannot be opened.
+///
+/// Devices are sorted by event number.
+///
+/// # Errors
+///
+/// Returns an error if `/dev/input` cannot be read.
+pub fn list_input_devices() -> Result<Vec<DeviceInfo>> {
+    Ok(scan_input_devices(Path::new("/dev/input"))?
+        .into_iter()
+        .map(|(info, _)| info)
+        .collect())
+}
+
+/// Opens every `event*` device under `dir`, sorted by event number.
+fn scan_input_devices(dir: &Path) -> Result<Vec<(DeviceInfo, Option<Device>)>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read {:?}", dir))? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("event"))
+        {
+            paths.push(path);
+        }
+    }
+    // event2 before event10
+    paths.sort_by_key(|path| (path.as_os_str().len(), path.clone()));
+
+    Ok(paths
+        .into_iter()
+        .map(|path| match Device::open(&path) {
+            Ok(device) => {
+                let info =
+                    DeviceInfo::from_capabilities(path, device.name(), device.supported_events());
+                (info, Some(device))
+            }
+            Err(e) => (DeviceInfo::unreadable(path, e.to_string()), None),
+        })
+        .collect())
+}
+
+/// An opened input device with its absolute axis ranges.
+struct InputDevice {
+    info: DeviceInfo,
+    device: Device,
+    axes: AbsAxes,
+}
+
 /// Linux-specific input handler using evdev for native Wayland/X11 support.
 ///
 /// This implementation reads directly from /dev/input/event* devices,
 /// which works on both Wayland and X11. Requires the user to be in the
 /// 'input' group or run with appropriate permissions.
+///
+/// The devices are opened once and shared with the capture thread, so an
+/// exclusive grab applies to the same file handles the events are read from.
+///
+/// Absolute positions from tablets and touchpads are scaled to screen pixels
+/// using each device's [`AbsAxisInfo`]. Multi-touch devices without `ABS_X`
+/// and `ABS_Y` are followed through the first touch (slot 0). While the
+/// devices are grabbed, mouse motion is captured as
+/// [`Event::MouseMoveRelative`].
 pub struct EvdevInputHandler {
     capturing: Arc<AtomicBool>,
-    devices: Vec<PathBuf>,
+    devices: Arc<Mutex<Vec<InputDevice>>>,
     mouse_position: Arc<std::sync::RwLock<(i32, i32)>>,
     grabbed: Arc<AtomicBool>,
 }
@@ -37,14 +268,14 @@ impl EvdevInputHandler {
             tracing::warn!("  3. Or run with sudo (not recommended for production)");
         } else {
             tracing::info!("Detected {} input device(s)", devices.len());
-            for device in &devices {
-                tracing::debug!("  - {:?}", device);
+            for input in &devices {
+                tracing::debug!("  - {} ({:?})", input.info.name, input.info.path);
             }
         }
 
         Ok(Self {
             capturing: Arc::new(AtomicBool::new(false)),
-            devices,
+            devices: Arc::new(Mutex::new(devices)),
             // Initialize mouse at center of screen (will be updated by real events)
             mouse_position: Arc::new(std::sync::RwLock::new((960, 540))),
             grabbed: Arc::new(AtomicBool::new(false)),
@@ -53,60 +284,63 @@ impl EvdevInputHandler {
 
     /// Detects all available input devices (keyboard and mouse).
     ///
-    /// Scans /dev/input/event* and filters for devices that support
-    /// keyboard or mouse events.
-    fn detect_input_devices() -> Result<Vec<PathBuf>> {
+    /// Scans /dev/input/event* and keeps the devices that support keyboard or
+    /// mouse events open in non-blocking mode, with their absolute axis ranges.
+    fn detect_input_devices() -> Result<Vec<InputDevice>> {
         let mut devices = Vec::new();
 
-        // Scan /dev/input/event* files
-        for entry in
-            std::fs::read_dir("/dev/input").context("Failed to read /dev/input directory")?
-        {
-            let entry = entry?;
-            let path = entry.path();
-
-            // Only check eventX files
-            if let Some(name) = path.file_name() {
-                if !name.to_string_lossy().starts_with("event") {
-                    continue;
-                }
+        for (info, device) in scan_input_devices(Path::new("/dev/input"))? {
+            let Some(device) = device else {
+                tracing::trace!(
+                    "Skipping {:?}: {}",
+                    info.path,
+                    info.error.unwrap_or_default()
+                );
+                continue;
+            };
+            if !info.is_input() {
+                continue;
             }
 
-            // Try to open the device
-            match Device::open(&path) {
-                Ok(device) => {
-                    let has_keyboard = device.supported_events().contains(EventType::KEY);
-                    let has_mouse = device.supported_events().contains(EventType::RELATIVE)
-                        || device.supported_events().contains(EventType::ABSOLUTE);
-
-                    if has_keyboard || has_mouse {
-                        let name = device.name().unwrap_or("Unknown");
-                        tracing::debug!(
-                            "Found input device: {} ({:?}) - Keyboard: {}, Mouse: {}",
-                            name,
-                            path,
-                            has_keyboard,
-                            has_mouse
-                        );
-                        devices.push(path);
-                    }
-                }
-                Err(e) => {
-                    tracing::trace!("Skipping {:?}: {}", path, e);
-                }
+            tracing::debug!(
+                "Found input device: {} ({:?}) - Keyboard: {}, Mouse: {}",
+                info.name,
+                info.path,
+                info.keyboard,
+                info.mouse
+            );
+            if let Err(e) = set_nonblocking(&device) {
+                tracing::warn!("Skipping {:?}: {}", info.path, e);
+                continue;
             }
+            let axes = AbsAxes::read(&device, DEFAULT_SCREEN_SIZE);
+            devices.push(InputDevice { info, device, axes });
         }
 
         Ok(devices)
     }
 
     /// Converts an evdev event to our internal Event type.
+    ///
+    /// `axes` are the absolute axes of the device the event comes from. With
+    /// `relative`, mouse motion is reported as raw deltas and the tracked
+    /// position is left alone, as the local cursor does not move while the
+    /// devices are grabbed.
     fn convert_evdev_event(
         kind: InputEventKind,
         value: i32,
         mouse_pos: &Arc<std::sync::RwLock<(i32, i32)>>,
+        axes: &mut AbsAxes,
+        relative: bool,
     ) -> Option<Event> {
         match kind {
+            InputEventKind::RelAxis(evdev::RelativeAxisType::REL_X) if relative => {
+                Some(Event::MouseMoveRelative { dx: value, dy: 0 })
+            }
+            InputEventKind::RelAxis(evdev::RelativeAxisType::REL_Y) if relative => {
+                Some(Event::MouseMoveRelative { dx: 0, dy: value })
+            }
+
             // Mouse movement (relative) - accumulate deltas
             InputEventKind::RelAxis(evdev::RelativeAxisType::REL_X) => {
                 if let Ok(mut pos) = mouse_pos.write() {
@@ -130,43 +364,70 @@ impl EvdevInputHandler {
             }
 
             // Mouse movement (absolute) - for touchpads/tablets
-            InputEventKind::AbsAxis(evdev::AbsoluteAxisType::ABS_X) => {
-                if let Ok(mut pos) = mouse_pos.write() {
-                    pos.0 = value;
-                    Some(Event::MouseMove { x: pos.0, y: pos.1 })
-                } else {
-                    None
-                }
+            InputEventKind::AbsAxis(AbsoluteAxisType::ABS_X) => {
+                move_absolute(mouse_pos, Some(axes.x?.to_screen(value)), None)
             }
-            InputEventKind::AbsAxis(evdev::AbsoluteAxisType::ABS_Y) => {
-                if let Ok(mut pos) = mouse_pos.write() {
-                    pos.1 = value;
-                    Some(Event::MouseMove { x: pos.0, y: pos.1 })
-                } else {
-                    None
-                }
+            InputEventKind::AbsAxis(AbsoluteAxisType::ABS_Y) => {
+                move_absolute(mouse_pos, None, Some(axes.y?.to_screen(value)))
+            }
+
+            // Multi-touch: follow the first touch, unless ABS_X/ABS_Y already
+            // report the pointer position
+            InputEventKind::AbsAxis(AbsoluteAxisType::ABS_MT_SLOT) => {
+                axes.slot = value;
+                None
+            }
+            InputEventKind::AbsAxis(AbsoluteAxisType::ABS_MT_POSITION_X)
+                if axes.slot == 0 && axes.x.is_none() =>
+            {
+                move_absolute(mouse_pos, Some(axes.mt_x?.to_screen(value)), None)
+            }
+            InputEventKind::AbsAxis(AbsoluteAxisType::ABS_MT_POSITION_Y)
+                if axes.slot == 0 && axes.y.is_none() =>
+            {
+                move_absolute(mouse_pos, None, Some(axes.mt_y?.to_screen(value)))
             }
 
-            // Mouse buttons
+            // Mouse buttons and keyboard keys
+            // The event value is 1 for press, 0 for release and 2 for autorepeat
             InputEventKind::Key(key) => {
-                match key {
-                    // Mouse buttons
-                    EvdevKey::BTN_LEFT => Some(Event::MouseButtonPress {
-                        button: MouseButton::Left,
-                    }),
-                    EvdevKey::BTN_RIGHT => Some(Event::MouseButtonPress {
-                        button: MouseButton::Right,
-                    }),
-                    EvdevKey::BTN_MIDDLE => Some(Event::MouseButtonPress {
-                        button: MouseButton::Middle,
-                    }),
-
-                    // Keyboard keys
-                    _ => convert_evdev_key(key).map(|our_key| Event::KeyPress { key: our_key }),
+                if let Some(button) = convert_evdev_button(key) {
+                    // Buttons don't autorepeat; ignore anything but press/release
+                    match value {
+                        KEY_PRESS => Some(Event::MouseButtonPress { button }),
+                        KEY_RELEASE => Some(Event::MouseButtonRelease { button }),
+                        _ => None,
+                    }
+                } else {
+                    let key = convert_evdev_key(key)?;
+                    match value {
+                        KEY_PRESS | KEY_REPEAT => Some(Event::KeyPress { key }),
+                        KEY_RELEASE => Some(Event::KeyRelease { key }),
+                        _ => None,
+                    }
                 }
             }
 
-            // Mouse wheel
+            // Mouse wheel. Devices with a high-resolution wheel report each
+            // detent twice, so only their `*_HI_RES` events are used.
+            InputEventKind::RelAxis(evdev::RelativeAxisType::REL_WHEEL_HI_RES) => {
+                Some(Event::MouseScrollPrecise {
+                    dx_120: 0,
+                    dy_120: value,
+                })
+            }
+            InputEventKind::RelAxis(evdev::RelativeAxisType::REL_HWHEEL_HI_RES) => {
+                Some(Event::MouseScrollPrecise {
+                    dx_120: value,
+                    dy_120: 0,
+                })
+            }
+            InputEventKind::RelAxis(evdev::RelativeAxisType::REL_WHEEL) if axes.hi_res_wheel => {
+                None
+            }
+            InputEventKind::RelAxis(evdev::RelativeAxisType::REL_HWHEEL) if axes.hi_res_hwheel => {
+                None
+            }
             InputEventKind::RelAxis(evdev::RelativeAxisType::REL_WHEEL) => {
                 Some(Event::MouseScroll {
                     delta_x: 0,
@@ -198,7 +459,7 @@ impl InputHandler for EvdevInputHandler {
             return Ok(());
         }
 
-        if self.devices.is_empty() {
+        if self.device_count() == 0 {
             anyhow::bail!(
                 "No input devices available. Please check permissions:\n\
                  1. sudo usermod -a -G input $USER\n\
@@ -210,34 +471,10 @@ impl InputHandler for EvdevInputHandler {
         self.capturing.store(true, Ordering::SeqCst);
         let capturing = self.capturing.clone();
         let mouse_pos = self.mouse_position.clone();
+        let devices = self.devices.clone();
+        let grabbed = self.grabbed.clone();
 
-        // Open devices
-        let mut devices = Vec::new();
-        for path in &self.devices {
-            match Device::open(path) {
-                Ok(device) => {
-                    tracing::info!(
-                        "Opened input device: {} ({:?})",
-                        device.name().unwrap_or("Unknown"),
-                        path
-                    );
-                    devices.push(device);
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        "Failed to open {:?}: {}. Try: sudo usermod -a -G input $USER",
-                        path,
-                        e
-                    );
-                }
-            }
-        }
-
-        if devices.is_empty() {
-            anyhow::bail!("Could not open any input devices. Check permissions.");
-        }
-
-        // Create a standard channel for the evdev threads to communicate with tokio
+        // Create a standard channel for the evdev thread to communicate with tokio
         let (std_tx, std_rx) = std::sync::mpsc::channel::<Event>();
 
         // Spawn async task to forward events from std channel to tokio channel
@@ -264,65 +501,95 @@ impl InputHandler for EvdevInputHandler {
             tracing::debug!("evdev event bridge task exiting");
         });
 
-        // Spawn capture thread for each device
-        for mut device in devices {
-            let std_tx = std_tx.clone();
-            let capturing = capturing.clone();
-            let mouse_pos = mouse_pos.clone();
+        // Poll all devices from a single capture thread. The devices are
+        // non-blocking, so the lock is only held while draining pending events
+        // and grab_devices() can take it in between.
+        std::thread::spawn(move || {
+            tracing::debug!("evdev capture thread started");
+            // Keys held across all devices, as modifiers and wheel usually
+            // come from different ones
+            let mut held = ModifierTracker::new();
 
-            std::thread::spawn(move || {
-                tracing::debug!(
-                    "evdev capture thread started for {}",
-                    device.name().unwrap_or("Unknown")
-                );
+            'capture: loop {
+                if !capturing.load(Ordering::SeqCst) {
+                    tracing::debug!("Stopping evdev capture thread");
+                    break;
+                }
 
-                loop {
-                    if !capturing.load(Ordering::SeqCst) {
-                        tracing::debug!("Stopping evdev capture thread");
+                let mut idle = true;
+                let relative = grabbed.load(Ordering::SeqCst);
+                {
+                    let Ok(mut devices) = devices.lock() else {
+                        tracing::error!("evdev device list poisoned");
                         break;
-                    }
+                    };
+
+                    let mut failed = Vec::new();
+                    for (index, input) in devices.iter_mut().enumerate() {
+                        let InputDevice { device, axes, .. } = input;
+                        match device.fetch_events() {
+                            Ok(events) => {
+                                idle = false;
+                                for event in events {
+                                    tracing::trace!(
+                                        "evdev raw event: {:?} value={}",
+                                        event.kind(),
+                                        event.value()
+                                    );
 
-                    match device.fetch_events() {
-                        Ok(events) => {
-                            for event in events {
-                                tracing::trace!(
-                                    "evdev raw event: {:?} value={}",
-                                    event.kind(),
-                                    event.value()
-                                );
-
-                                if let Some(our_event) = Self::convert_evdev_event(
-                                    event.kind(),
-                                    event.value(),
-                                    &mouse_pos,
-                                ) {
-                                    tracing::debug!("Converted evdev event: {:?}", our_event);
-
-                                    // Send through standard channel (non-async)
-                                    if let Err(e) = std_tx.send(our_event) {
-                                        tracing::error!(
-                                            "Failed to send event through channel: {:?}",
-                                            e
-                                        );
-                                        break;
+                                    if let Some(our_event) = Self::convert_evdev_event(
+                                        event.kind(),
+                                        event.value(),
+                                        &mouse_pos,
+                                        axes,
+                                        relative,
+                                    ) {
+                                        let our_event = our_event
+                                            .with_modifiers(Modifiers::from_keys(held.held()));
+                                        held.observe(&our_event);
+                                        tracing::debug!("Converted evdev event: {:?}", our_event);
+
+                                        // Send through standard channel (non-async)
+                                        if let Err(e) = std_tx.send(our_event) {
+                                            tracing::error!(
+                                                "Failed to send event through channel: {:?}",
+                                                e
+                                            );
+                                            break 'capture;
+                                        }
                                     }
                                 }
                             }
-                        }
-                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                            // No events available, sleep briefly
-                            std::thread::sleep(std::time::Duration::from_millis(10));
-                        }
-                        Err(e) => {
-                            tracing::error!("Error fetching evdev events: {:?}", e);
-                            break;
+                            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                            Err(e) => {
+                                tracing::error!("Error fetching evdev events: {:?}", e);
+                                failed.push(index);
+                            }
                         }
                     }
+
+                    // Stop reading devices that failed, e.g. because they were unplugged
+                    for index in failed.into_iter().rev() {
+                        let input = devices.remove(index);
+                        tracing::warn!(
+                            "Closing input device {}",
+                            input.device.name().unwrap_or("Unknown")
+                        );
+                    }
+                    if devices.is_empty() {
+                        tracing::error!("No input devices left to capture");
+                        break;
+                    }
                 }
 
-                tracing::debug!("evdev capture thread exiting");
-            });
-        }
+                if idle {
+                    // No events available, sleep briefly
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                }
+            }
+
+            tracing::debug!("evdev capture thread exiting");
+        });
 
         tracing::info!("✓ evdev input capture started");
         Ok(())
@@ -334,12 +601,11 @@ impl InputHandler for EvdevInputHandler {
     }
 
     async fn inject_event(&self, event: Event) -> Result<()> {
-        // TODO: Implement uinput-based event injection
-        tracing::warn!(
-            "Event injection not yet implemented for evdev backend: {:?}",
+        // Reading devices cannot write to them; agents inject with UinputInjector
+        anyhow::bail!(
+            "The evdev backend only captures, inject through uinput instead: {:?}",
             event
-        );
-        Ok(())
+        )
     }
 
     fn is_capturing(&self) -> bool {
@@ -355,12 +621,12 @@ impl InputHandler for EvdevInputHandler {
         #[cfg(target_os = "linux")]
         {
             // TODO: Query actual screen size from X11/Wayland
-            (1920, 1080)
+            DEFAULT_SCREEN_SIZE
         }
 
         #[cfg(not(target_os = "linux"))]
         {
-            (1920, 1080)
+            DEFAULT_SCREEN_SIZE
         }
     }
 
@@ -374,44 +640,60 @@ impl InputHandler for EvdevInputHandler {
 
     fn check_permissions(&self) -> bool {
         // Check if we can access /dev/input devices
-        !self.devices.is_empty()
+        self.device_count() > 0
     }
 }
 
 impl EvdevInputHandler {
+    /// Returns the number of opened input devices.
+    pub fn device_count(&self) -> usize {
+        self.devices
+            .lock()
+            .map(|devices| devices.len())
+            .unwrap_or(0)
+    }
+
+    /// Returns whether the input devices are currently grabbed.
+    pub fn is_grabbed(&self) -> bool {
+        self.grabbed.load(Ordering::SeqCst)
+    }
+
     /// Grabs all input devices exclusively, preventing other applications from receiving events.
     ///
     /// This is useful when transferring input focus to a remote machine - it prevents
-    /// the local OS from processing the events while we send them remotely.
-    pub fn grab_devices(&self) -> Result<()> {
-        if self.grabbed.load(Ordering::SeqCst) {
+    /// the local OS from processing the events while we send them remotely. Events
+    /// keep flowing to the capture started with [`InputHandler::start_capture`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any device cannot be grabbed, e.g. because another
+    /// process already holds it. Devices grabbed before the failure are released.
+    pub fn grab_devices(&mut self) -> Result<()> {
+        if self.is_grabbed() {
             return Ok(()); // Already grabbed
         }
 
-        for device_path in &self.devices {
-            match Device::open(device_path) {
-                Ok(device) => {
-                    // Use EVIOCGRAB ioctl to grab the device
-                    // EVIOCGRAB = 0x40044590 (IOW('E', 0x90, int))
-                    let fd = device.as_raw_fd();
-                    unsafe {
-                        const EVIOCGRAB: nix::libc::c_ulong = 0x40044590;
-                        // EVIOCGRAB with 1 = grab, 0 = release
-                        if nix::libc::ioctl(fd, EVIOCGRAB, 1) < 0 {
-                            tracing::warn!("Failed to grab device {:?}", device_path);
-                        } else {
-                            tracing::debug!("Grabbed device {:?}", device_path);
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        "Failed to open device {:?} for grabbing: {}",
-                        device_path,
-                        e
-                    );
+        let mut devices = self
+            .devices
+            .lock()
+            .map_err(|_| anyhow::anyhow!("evdev device list poisoned"))?;
+
+        for index in 0..devices.len() {
+            if let Err(e) = devices[index].device.grab() {
+                let name = devices[index]
+                    .device
+                    .name()
+                    .unwrap_or("Unknown")
+                    .to_string();
+                for input in devices.iter_mut().take(index) {
+                    let _ = input.device.ungrab();
                 }
+                return Err(e).with_context(|| format!("Failed to grab device {}", name));
             }
+            tracing::debug!(
+                "Grabbed device {}",
+                devices[index].device.name().unwrap_or("Unknown")
+            );
         }
 
         self.grabbed.store(true, Ordering::SeqCst);
@@ -420,38 +702,83 @@ impl EvdevInputHandler {
     }
 
     /// Releases the exclusive grab on all input devices, allowing other applications to receive events again.
-    pub fn ungrab_devices(&self) -> Result<()> {
-        if !self.grabbed.load(Ordering::SeqCst) {
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first device that could not be released.
+    /// The remaining devices are still released.
+    pub fn ungrab_devices(&mut self) -> Result<()> {
+        if !self.is_grabbed() {
             return Ok(()); // Not grabbed
         }
 
-        for device_path in &self.devices {
-            match Device::open(device_path) {
-                Ok(device) => {
-                    let fd = device.as_raw_fd();
-                    unsafe {
-                        const EVIOCGRAB: nix::libc::c_ulong = 0x40044590;
-                        // EVIOCGRAB with 0 = release
-                        if nix::libc::ioctl(fd, EVIOCGRAB, 0) < 0 {
-                            tracing::warn!("Failed to ungrab device {:?}", device_path);
-                        } else {
-                            tracing::debug!("Ungrabbed device {:?}", device_path);
-                        }
-                    }
-                }
+        let mut devices = self
+            .devices
+            .lock()
+            .map_err(|_| anyhow::anyhow!("evdev device list poisoned"))?;
+
+        let mut result = Ok(());
+        for input in devices.iter_mut() {
+            let name = input.device.name().unwrap_or("Unknown").to_string();
+            match input.device.ungrab() {
+                Ok(()) => tracing::debug!("Ungrabbed device {}", name),
                 Err(e) => {
-                    tracing::warn!(
-                        "Failed to open device {:?} for ungrabbing: {}",
-                        device_path,
-                        e
-                    );
+                    tracing::warn!("Failed to ungrab device {}: {}", name, e);
+                    if result.is_ok() {
+                        result =
+                            Err(e).with_context(|| format!("Failed to ungrab device {}", name));
+                    }
                 }
             }
         }
 
         self.grabbed.store(false, Ordering::SeqCst);
         tracing::info!("🔓 Input devices released - local input enabled");
-        Ok(())
+        result
+    }
+}
+
+/// Updates the tracked position with the given absolute coordinates.
+fn move_absolute(
+    mouse_pos: &std::sync::RwLock<(i32, i32)>,
+    x: Option<i32>,
+    y: Option<i32>,
+) -> Option<Event> {
+    let mut pos = mouse_pos.write().ok()?;
+    if let Some(x) = x {
+        pos.0 = x;
+    }
+    if let Some(y) = y {
+        pos.1 = y;
+    }
+    Some(Event::MouseMove { x: pos.0, y: pos.1 })
+}
+
+/// Switches a device to non-blocking reads so `fetch_events` returns
+/// `WouldBlock` instead of waiting for input.
+fn set_nonblocking(device: &Device) -> std::io::Result<()> {
+    let fd = device.as_raw_fd();
+    // SAFETY: fcntl with F_GETFL/F_SETFL only reads and updates the status flags
+    // of a file descriptor owned by `device`, which stays open for the call.
+    unsafe {
+        let flags = nix::libc::fcntl(fd, nix::libc::F_GETFL);
+        if flags < 0 || nix::libc::fcntl(fd, nix::libc::F_SETFL, flags | nix::libc::O_NONBLOCK) < 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Converts an evdev button code to our MouseButton representation.
+fn convert_evdev_button(key: EvdevKey) -> Option<MouseButton> {
+    match key {
+        EvdevKey::BTN_LEFT => Some(MouseButton::Left),
+        EvdevKey::BTN_RIGHT => Some(MouseButton::Right),
+        EvdevKey::BTN_MIDDLE => Some(MouseButton::Middle),
+        EvdevKey::BTN_SIDE => Some(MouseButton::Back),
+        EvdevKey::BTN_EXTRA => Some(MouseButton::Forward),
+        _ => None,
     }
 }
 
@@ -502,11 +829,119 @@ fn convert_evdev_key(key: EvdevKey) -> Option<Key> {
         EvdevKey::KEY_SPACE => Some(Key::Space),
         EvdevKey::KEY_BACKSPACE => Some(Key::Backspace),
         EvdevKey::KEY_TAB => Some(Key::Tab),
+        EvdevKey::KEY_DELETE => Some(Key::Delete),
+
+        // Function keys
+        EvdevKey::KEY_F1 => Some(Key::F1),
+        EvdevKey::KEY_F2 => Some(Key::F2),
+        EvdevKey::KEY_F3 => Some(Key::F3),
+        EvdevKey::KEY_F4 => Some(Key::F4),
+        EvdevKey::KEY_F5 => Some(Key::F5),
+        EvdevKey::KEY_F6 => Some(Key::F6),
+        EvdevKey::KEY_F7 => Some(Key::F7),
+        EvdevKey::KEY_F8 => Some(Key::F8),
+        EvdevKey::KEY_F9 => Some(Key::F9),
+        EvdevKey::KEY_F10 => Some(Key::F10),
+        EvdevKey::KEY_F11 => Some(Key::F11),
+        EvdevKey::KEY_F12 => Some(Key::F12),
+
+        // Number row
+        EvdevKey::KEY_0 => Some(Key::Num0),
+        EvdevKey::KEY_1 => Some(Key::Num1),
+        EvdevKey::KEY_2 => Some(Key::Num2),
+        EvdevKey::KEY_3 => Some(Key::Num3),
+        EvdevKey::KEY_4 => Some(Key::Num4),
+        EvdevKey::KEY_5 => Some(Key::Num5),
+        EvdevKey::KEY_6 => Some(Key::Num6),
+        EvdevKey::KEY_7 => Some(Key::Num7),
+        EvdevKey::KEY_8 => Some(Key::Num8),
+        EvdevKey::KEY_9 => Some(Key::Num9),
+
+        // Punctuation
+        EvdevKey::KEY_MINUS => Some(Key::Minus),
+        EvdevKey::KEY_EQUAL => Some(Key::Equal),
+        EvdevKey::KEY_LEFTBRACE => Some(Key::LeftBracket),
+        EvdevKey::KEY_RIGHTBRACE => Some(Key::RightBracket),
+        EvdevKey::KEY_SEMICOLON => Some(Key::SemiColon),
+        EvdevKey::KEY_APOSTROPHE => Some(Key::Quote),
+        EvdevKey::KEY_GRAVE => Some(Key::BackQuote),
+        EvdevKey::KEY_BACKSLASH => Some(Key::BackSlash),
+        EvdevKey::KEY_102ND => Some(Key::IntlBackslash),
+        EvdevKey::KEY_COMMA => Some(Key::Comma),
+        EvdevKey::KEY_DOT => Some(Key::Dot),
+        EvdevKey::KEY_SLASH => Some(Key::Slash),
+
+        // Navigation
+        EvdevKey::KEY_UP => Some(Key::UpArrow),
+        EvdevKey::KEY_DOWN => Some(Key::DownArrow),
+        EvdevKey::KEY_LEFT => Some(Key::LeftArrow),
+        EvdevKey::KEY_RIGHT => Some(Key::RightArrow),
+        EvdevKey::KEY_INSERT => Some(Key::Insert),
+        EvdevKey::KEY_HOME => Some(Key::Home),
+        EvdevKey::KEY_END => Some(Key::End),
+        EvdevKey::KEY_PAGEUP => Some(Key::PageUp),
+        EvdevKey::KEY_PAGEDOWN => Some(Key::PageDown),
+
+        // Locks and system keys
+        EvdevKey::KEY_CAPSLOCK => Some(Key::CapsLock),
+        EvdevKey::KEY_NUMLOCK => Some(Key::NumLock),
+        EvdevKey::KEY_SCROLLLOCK => Some(Key::ScrollLock),
+        EvdevKey::KEY_SYSRQ => Some(Key::PrintScreen),
+        EvdevKey::KEY_PAUSE => Some(Key::Pause),
+
+        // Numpad
+        EvdevKey::KEY_KP0 => Some(Key::Kp0),
+        EvdevKey::KEY_KP1 => Some(Key::Kp1),
+        EvdevKey::KEY_KP2 => Some(Key::Kp2),
+        EvdevKey::KEY_KP3 => Some(Key::Kp3),
+        EvdevKey::KEY_KP4 => Some(Key::Kp4),
+        EvdevKey::KEY_KP5 => Some(Key::Kp5),
+        EvdevKey::KEY_KP6 => Some(Key::Kp6),
+        EvdevKey::KEY_KP7 => Some(Key::Kp7),
+        EvdevKey::KEY_KP8 => Some(Key::Kp8),
+        EvdevKey::KEY_KP9 => Some(Key::Kp9),
+        EvdevKey::KEY_KPPLUS => Some(Key::KpPlus),
+        EvdevKey::KEY_KPMINUS => Some(Key::KpMinus),
+        EvdevKey::KEY_KPASTERISK => Some(Key::KpMultiply),
+        EvdevKey::KEY_KPSLASH => Some(Key::KpDivide),
+        EvdevKey::KEY_KPENTER => Some(Key::KpReturn),
+        EvdevKey::KEY_KPDOT => Some(Key::KpDelete),
+
+        // Media keys
+        EvdevKey::KEY_PLAYPAUSE => Some(Key::MediaPlayPause),
+        EvdevKey::KEY_STOPCD => Some(Key::MediaStop),
+        EvdevKey::KEY_NEXTSONG => Some(Key::MediaNext),
+        EvdevKey::KEY_PREVIOUSSONG => Some(Key::MediaPrevious),
+        EvdevKey::KEY_VOLUMEUP => Some(Key::VolumeUp),
+        EvdevKey::KEY_VOLUMEDOWN => Some(Key::VolumeDown),
+        EvdevKey::KEY_MUTE => Some(Key::VolumeMute),
 
         _ => None,
     }
 }
 
+/// Returns the evdev code of `key`, the inverse of [`convert_evdev_key`].
+pub(crate) fn evdev_key_code(key: &Key) -> Option<u16> {
+    evdev_key_codes().find(|&code| convert_evdev_key(EvdevKey::new(code)).as_ref() == Some(key))
+}
+
+/// Returns the evdev codes of every key [`convert_evdev_key`] knows.
+pub(crate) fn evdev_key_codes() -> impl Iterator<Item = u16> {
+    // KEY_MAX is 0x2ff
+    (0..0x300).filter(|&code| convert_evdev_key(EvdevKey::new(code)).is_some())
+}
+
+/// Returns the evdev code of `button`, the inverse of [`convert_evdev_button`].
+pub(crate) fn evdev_button_code(button: &MouseButton) -> u16 {
+    match button {
+        MouseButton::Left => EvdevKey::BTN_LEFT.code(),
+        MouseButton::Right => EvdevKey::BTN_RIGHT.code(),
+        MouseButton::Middle => EvdevKey::BTN_MIDDLE.code(),
+        MouseButton::Back => EvdevKey::BTN_SIDE.code(),
+        MouseButton::Forward => EvdevKey::BTN_EXTRA.code(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -526,4 +961,266 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_device_info_from_capabilities() {
+        let mut events = evdev::AttributeSet::<EventType>::new();
+        events.insert(EventType::SYNCHRONIZATION);
+        let info = DeviceInfo::from_capabilities("/dev/input/event0".into(), None, &events);
+        assert_eq!(info.name, "Unknown");
+        assert!(!info.keyboard && !info.mouse);
+        assert!(!info.is_input());
+
+        events.insert(EventType::KEY);
+        events.insert(EventType::ABSOLUTE);
+        let info = DeviceInfo::from_capabilities("/dev/input/event1".into(), Some("Pad"), &events);
+        assert!(info.keyboard && info.mouse);
+        assert!(info.is_input());
+
+        let info = DeviceInfo::unreadable("/dev/input/event2".into(), "Permission denied");
+        assert!(!info.is_input());
+        assert_eq!(info.error.as_deref(), Some("Permission denied"));
+    }
+
+    #[test]
+    fn test_scan_input_devices_sorts_by_event_number() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["event10", "event2", "mouse0"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        // Regular files are not event devices, so they are listed as unreadable
+        let scanned = scan_input_devices(dir.path()).unwrap();
+        let names: Vec<_> = scanned
+            .iter()
+            .map(|(info, _)| info.path.file_name().unwrap().to_owned())
+            .collect();
+        assert_eq!(names, ["event2", "event10"]);
+        assert!(scanned
+            .iter()
+            .all(|(info, device)| device.is_none() && !info.is_input()));
+    }
+
+    #[test]
+    fn test_convert_evdev_event_press_release_sequence() {
+        let mouse_pos = Arc::new(std::sync::RwLock::new((0, 0)));
+        let mut axes = AbsAxes::default();
+        let inputs = [
+            (InputEventKind::Key(EvdevKey::BTN_LEFT), 1),
+            (InputEventKind::Key(EvdevKey::BTN_LEFT), 0),
+            (InputEventKind::Key(EvdevKey::BTN_SIDE), 1),
+            (InputEventKind::Key(EvdevKey::BTN_SIDE), 2),
+            (InputEventKind::Key(EvdevKey::BTN_SIDE), 0),
+            (InputEventKind::Key(EvdevKey::BTN_EXTRA), 1),
+            (InputEventKind::Key(EvdevKey::BTN_EXTRA), 0),
+            (InputEventKind::Key(EvdevKey::KEY_A), 1),
+            (InputEventKind::Key(EvdevKey::KEY_A), 2),
+            (InputEventKind::Key(EvdevKey::KEY_A), 0),
+        ];
+
+        let events: Vec<Event> = inputs
+            .into_iter()
+            .filter_map(|(kind, value)| {
+                EvdevInputHandler::convert_evdev_event(kind, value, &mouse_pos, &mut axes, false)
+            })
+            .collect();
+
+        assert_eq!(
+            events,
+            vec![
+                Event::MouseButtonPress {
+                    button: MouseButton::Left
+                },
+                Event::MouseButtonRelease {
+                    button: MouseButton::Left
+                },
+                Event::MouseButtonPress {
+                    button: MouseButton::Back
+                },
+                Event::MouseButtonRelease {
+                    button: MouseButton::Back
+                },
+                Event::MouseButtonPress {
+                    button: MouseButton::Forward
+                },
+                Event::MouseButtonRelease {
+                    button: MouseButton::Forward
+                },
+                Event::KeyPress { key: Key::KeyA },
+                Event::KeyPress { key: Key::KeyA },
+                Event::KeyRelease { key: Key::KeyA },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grabbed_mouse_reports_relative_motion() {
+        let mouse_pos = Arc::new(std::sync::RwLock::new((0, 1080)));
+        let mut axes = AbsAxes::default();
+        let mut convert = |axis, value, relative| {
+            EvdevInputHandler::convert_evdev_event(
+                InputEventKind::RelAxis(axis),
+                value,
+                &mouse_pos,
+                &mut axes,
+                relative,
+            )
+        };
+
+        // Pinned at the bottom-left corner, absolute motion cannot go further
+        assert_eq!(
+            convert(evdev::RelativeAxisType::REL_Y, 5, false),
+            Some(Event::MouseMove { x: 0, y: 1080 })
+        );
+        assert_eq!(
+            convert(evdev::RelativeAxisType::REL_X, -4, true),
+            Some(Event::MouseMoveRelative { dx: -4, dy: 0 })
+        );
+        assert_eq!(
+            convert(evdev::RelativeAxisType::REL_Y, 7, true),
+            Some(Event::MouseMoveRelative { dx: 0, dy: 7 })
+        );
+        assert_eq!(*mouse_pos.read().unwrap(), (0, 1080));
+    }
+
+    #[test]
+    fn test_hi_res_wheel_replaces_coarse_wheel() {
+        let mouse_pos = Arc::new(std::sync::RwLock::new((0, 0)));
+        let convert = |axes: &mut AbsAxes, axis, value| {
+            EvdevInputHandler::convert_evdev_event(
+                InputEventKind::RelAxis(axis),
+                value,
+                &mouse_pos,
+                axes,
+                false,
+            )
+        };
+
+        let mut touchpad = AbsAxes {
+            hi_res_wheel: true,
+            hi_res_hwheel: true,
+            ..AbsAxes::default()
+        };
+        assert_eq!(
+            convert(&mut touchpad, RelativeAxisType::REL_WHEEL_HI_RES, -30),
+            Some(Event::MouseScrollPrecise {
+                dx_120: 0,
+                dy_120: -30
+            })
+        );
+        assert_eq!(
+            convert(&mut touchpad, RelativeAxisType::REL_HWHEEL_HI_RES, 240),
+            Some(Event::MouseScrollPrecise {
+                dx_120: 240,
+                dy_120: 0
+            })
+        );
+        assert_eq!(
+            convert(&mut touchpad, RelativeAxisType::REL_WHEEL, -1),
+            None
+        );
+        assert_eq!(
+            convert(&mut touchpad, RelativeAxisType::REL_HWHEEL, 2),
+            None
+        );
+
+        // A plain wheel still scrolls by detents
+        let mut mouse = AbsAxes::default();
+        assert_eq!(
+            convert(&mut mouse, RelativeAxisType::REL_WHEEL, -1),
+            Some(Event::MouseScroll {
+                delta_x: 0,
+                delta_y: -1
+            })
+        );
+    }
+
+    fn tablet_axes() -> AbsAxes {
+        let axis = |screen_pixels| {
+            Some(AbsAxisInfo {
+                min: 0,
+                max: 32767,
+                screen_pixels,
+            })
+        };
+        AbsAxes {
+            mt_x: axis(1920),
+            mt_y: axis(1080),
+            ..AbsAxes::default()
+        }
+    }
+
+    #[test]
+    fn test_convert_absolute_position_to_screen() {
+        let mouse_pos = Arc::new(std::sync::RwLock::new((0, 0)));
+        let mut axes = AbsAxes {
+            x: Some(AbsAxisInfo {
+                min: 100,
+                max: 4100,
+                screen_pixels: 1920,
+            }),
+            y: Some(AbsAxisInfo {
+                min: 0,
+                max: 3000,
+                screen_pixels: 1080,
+            }),
+            ..AbsAxes::default()
+        };
+        let mut convert = |axis, value| {
+            EvdevInputHandler::convert_evdev_event(
+                InputEventKind::AbsAxis(axis),
+                value,
+                &mouse_pos,
+                &mut axes,
+                false,
+            )
+        };
+
+        assert_eq!(
+            convert(AbsoluteAxisType::ABS_X, 2100),
+            Some(Event::MouseMove { x: 960, y: 0 })
+        );
+        assert_eq!(
+            convert(AbsoluteAxisType::ABS_Y, 3000),
+            Some(Event::MouseMove { x: 960, y: 1079 })
+        );
+        // Out-of-range values stay on screen
+        assert_eq!(
+            convert(AbsoluteAxisType::ABS_X, 0),
+            Some(Event::MouseMove { x: 0, y: 1079 })
+        );
+        // ABS_X already reports the pointer, multi-touch positions are ignored
+        assert_eq!(convert(AbsoluteAxisType::ABS_MT_POSITION_X, 500), None);
+    }
+
+    #[test]
+    fn test_multitouch_tracks_slot_zero_only() {
+        let mouse_pos = Arc::new(std::sync::RwLock::new((0, 0)));
+        let mut axes = tablet_axes();
+        let mut convert = |axis, value| {
+            EvdevInputHandler::convert_evdev_event(
+                InputEventKind::AbsAxis(axis),
+                value,
+                &mouse_pos,
+                &mut axes,
+                false,
+            )
+        };
+
+        assert_eq!(
+            convert(AbsoluteAxisType::ABS_MT_POSITION_X, 32767),
+            Some(Event::MouseMove { x: 1919, y: 0 })
+        );
+
+        // A second finger does not move the pointer
+        assert_eq!(convert(AbsoluteAxisType::ABS_MT_SLOT, 1), None);
+        assert_eq!(convert(AbsoluteAxisType::ABS_MT_POSITION_X, 0), None);
+        assert_eq!(convert(AbsoluteAxisType::ABS_MT_POSITION_Y, 0), None);
+
+        assert_eq!(convert(AbsoluteAxisType::ABS_MT_SLOT, 0), None);
+        assert_eq!(
+            convert(AbsoluteAxisType::ABS_MT_POSITION_Y, 16384),
+            Some(Event::MouseMove { x: 1919, y: 540 })
+        );
+    }
 }
@@ -0,0 +1,321 @@
+use anyhow::{Context, Result};
+use evdev::uinput::{VirtualDevice, VirtualDeviceBuilder};
+use evdev::{
+    AbsInfo, AbsoluteAxisType, AttributeSet, EventType, InputEvent, Key as EvdevKey,
+    RelativeAxisType, UinputAbsSetup,
+};
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+
+use crate::core::events::{Event, Key, MouseButton, SCROLL_DETENT};
+use crate::core::input::{unicode_entry_sequence, InputHandler, UNICODE_ENTRY_DELAY};
+use crate::core::input_evdev::{evdev_button_code, evdev_key_code, evdev_key_codes};
+
+/// Screen size the absolute pointer is scaled to; the compositor maps its
+/// range onto the whole desktop.
+const DEFAULT_SCREEN_SIZE: (u32, u32) = (1920, 1080);
+
+/// `EV_KEY` values of a release and a press.
+const KEY_RELEASE: i32 = 0;
+const KEY_PRESS: i32 = 1;
+
+/// Every mouse button an event can carry.
+const BUTTONS: [MouseButton; 5] = [
+    MouseButton::Left,
+    MouseButton::Right,
+    MouseButton::Middle,
+    MouseButton::Back,
+    MouseButton::Forward,
+];
+
+/// Returns the wheel events of a scroll, in detents or in 1/120ths of one.
+///
+/// Positive `delta_y` scrolls down, while `REL_WHEEL` counts up.
+fn wheel_events(delta_x: i32, delta_y: i32, detents: bool) -> Vec<InputEvent> {
+    let rel =
+        |axis: RelativeAxisType, value: i32| InputEvent::new(EventType::RELATIVE, axis.0, value);
+    let mut events = Vec::new();
+    if delta_y != 0 {
+        let hi_res = if detents {
+            delta_y.saturating_mul(SCROLL_DETENT)
+        } else {
+            delta_y
+        };
+        if detents {
+            events.push(rel(RelativeAxisType::REL_WHEEL, -delta_y));
+        }
+        events.push(rel(RelativeAxisType::REL_WHEEL_HI_RES, -hi_res));
+    }
+    if delta_x != 0 {
+        let hi_res = if detents {
+            delta_x.saturating_mul(SCROLL_DETENT)
+        } else {
+            delta_x
+        };
+        if detents {
+            events.push(rel(RelativeAxisType::REL_HWHEEL, delta_x));
+        }
+        events.push(rel(RelativeAxisType::REL_HWHEEL_HI_RES, hi_res));
+    }
+    events
+}
+
+/// Injects events through kernel virtual devices created on `/dev/uinput`.
+///
+/// Two devices are created: a keyboard and relative mouse, and an absolute
+/// pointer for [`Event::MouseMove`], as a tablet would be. They work under
+/// X11, Wayland and the console alike; [`Event::TextInput`] is typed with
+/// the Ctrl+Shift+U unicode entry of IBus and GTK. Injection only:
+/// [`start_capture`] fails, capture goes through [`EvdevInputHandler`].
+///
+/// [`start_capture`]: InputHandler::start_capture
+/// [`EvdevInputHandler`]: crate::core::input_evdev::EvdevInputHandler
+pub struct UinputInjector {
+    keyboard: Mutex<VirtualDevice>,
+    pointer: Mutex<VirtualDevice>,
+    screen_size: (u32, u32),
+    /// Last position the pointer was moved to
+    cursor: Mutex<(i32, i32)>,
+}
+
+impl UinputInjector {
+    /// Creates the virtual devices.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `/dev/uinput` cannot be opened for writing or the
+    /// kernel refuses the devices.
+    pub fn create() -> Result<Self> {
+        let screen_size = DEFAULT_SCREEN_SIZE;
+
+        let mut buttons = AttributeSet::<EvdevKey>::new();
+        for button in &BUTTONS {
+            buttons.insert(EvdevKey::new(evdev_button_code(button)));
+        }
+        let mut keys = AttributeSet::<EvdevKey>::new();
+        for code in evdev_key_codes() {
+            keys.insert(EvdevKey::new(code));
+        }
+        for button in buttons.iter() {
+            keys.insert(button);
+        }
+        let mut axes = AttributeSet::<RelativeAxisType>::new();
+        for axis in [
+            RelativeAxisType::REL_X,
+            RelativeAxisType::REL_Y,
+            RelativeAxisType::REL_WHEEL,
+            RelativeAxisType::REL_HWHEEL,
+            RelativeAxisType::REL_WHEEL_HI_RES,
+            RelativeAxisType::REL_HWHEEL_HI_RES,
+        ] {
+            axes.insert(axis);
+        }
+        let keyboard = VirtualDeviceBuilder::new()
+            .context("Failed to open /dev/uinput (run `multishiva doctor` for the fix)")?
+            .name("MultiShiva virtual keyboard")
+            .with_keys(&keys)?
+            .with_relative_axes(&axes)?
+            .build()
+            .context("Failed to create the uinput keyboard")?;
+
+        let axis = |code, max: u32| {
+            UinputAbsSetup::new(
+                code,
+                AbsInfo::new(0, 0, max.saturating_sub(1) as i32, 0, 0, 0),
+            )
+        };
+        let pointer = VirtualDeviceBuilder::new()?
+            .name("MultiShiva virtual pointer")
+            .with_keys(&buttons)?
+            .with_absolute_axis(&axis(AbsoluteAxisType::ABS_X, screen_size.0))?
+            .with_absolute_axis(&axis(AbsoluteAxisType::ABS_Y, screen_size.1))?
+            .build()
+            .context("Failed to create the uinput pointer")?;
+
+        tracing::info!("✓ uinput virtual devices created");
+        Ok(Self {
+            keyboard: Mutex::new(keyboard),
+            pointer: Mutex::new(pointer),
+            screen_size,
+            cursor: Mutex::new((0, 0)),
+        })
+    }
+
+    fn emit(device: &Mutex<VirtualDevice>, events: &[InputEvent]) -> Result<()> {
+        device
+            .lock()
+            .map_err(|_| anyhow::anyhow!("uinput device poisoned"))?
+            .emit(events)
+            .context("Failed to write to the uinput device")
+    }
+
+    fn move_to(&self, x: i32, y: i32) -> Result<()> {
+        let x = x.clamp(0, self.screen_size.0 as i32 - 1);
+        let y = y.clamp(0, self.screen_size.1 as i32 - 1);
+        Self::emit(
+            &self.pointer,
+            &[
+                InputEvent::new(EventType::ABSOLUTE, AbsoluteAxisType::ABS_X.0, x),
+                InputEvent::new(EventType::ABSOLUTE, AbsoluteAxisType::ABS_Y.0, y),
+            ],
+        )?;
+        if let Ok(mut cursor) = self.cursor.lock() {
+            *cursor = (x, y);
+        }
+        Ok(())
+    }
+
+    fn move_by(&self, dx: i32, dy: i32) -> Result<()> {
+        Self::emit(
+            &self.keyboard,
+            &[
+                InputEvent::new(EventType::RELATIVE, RelativeAxisType::REL_X.0, dx),
+                InputEvent::new(EventType::RELATIVE, RelativeAxisType::REL_Y.0, dy),
+            ],
+        )?;
+        if let Ok(mut cursor) = self.cursor.lock() {
+            cursor.0 = cursor.0.saturating_add(dx);
+            cursor.1 = cursor.1.saturating_add(dy);
+        }
+        Ok(())
+    }
+
+    fn button(&self, button: &MouseButton, value: i32) -> Result<()> {
+        let code = evdev_button_code(button);
+        Self::emit(
+            &self.keyboard,
+            &[InputEvent::new(EventType::KEY, code, value)],
+        )
+    }
+
+    fn key(&self, key: &Key, value: i32) -> Result<()> {
+        let Some(code) = evdev_key_code(key) else {
+            tracing::debug!("No evdev code for {:?}, ignored", key);
+            return Ok(());
+        };
+        Self::emit(
+            &self.keyboard,
+            &[InputEvent::new(EventType::KEY, code, value)],
+        )
+    }
+}
+
+impl InputHandler for UinputInjector {
+    async fn start_capture(&mut self, _tx: mpsc::Sender<Event>) -> Result<()> {
+        anyhow::bail!("The uinput backend only injects events, capture uses evdev")
+    }
+
+    async fn stop_capture(&mut self) {}
+
+    async fn inject_event(&self, event: Event) -> Result<()> {
+        match event {
+            Event::MouseMove { x, y } => self.move_to(x, y),
+            Event::MouseMoveRelative { dx, dy } => self.move_by(dx, dy),
+            Event::MouseButtonPress { button } => self.button(&button, KEY_PRESS),
+            Event::MouseButtonRelease { button } => self.button(&button, KEY_RELEASE),
+            Event::MouseClick { button } => {
+                self.button(&button, KEY_PRESS)?;
+                self.button(&button, KEY_RELEASE)
+            }
+            Event::MouseScroll { delta_x, delta_y }
+            | Event::MouseScrollWithModifiers {
+                delta_x, delta_y, ..
+            } => Self::emit(
+                &self.keyboard,
+                &wheel_events(delta_x as i32, delta_y as i32, true),
+            ),
+            Event::MouseScrollPrecise { dx_120, dy_120 } => {
+                Self::emit(&self.keyboard, &wheel_events(dx_120, dy_120, false))
+            }
+            Event::KeyPress { key } => self.key(&key, KEY_PRESS),
+            Event::KeyRelease { key } => self.key(&key, KEY_RELEASE),
+            Event::TextInput { text } => {
+                for event in text.chars().flat_map(unicode_entry_sequence) {
+                    match event {
+                        Event::KeyPress { key } => self.key(&key, KEY_PRESS)?,
+                        Event::KeyRelease { key } => self.key(&key, KEY_RELEASE)?,
+                        _ => {}
+                    }
+                    tokio::time::sleep(UNICODE_ENTRY_DELAY).await;
+                }
+                Ok(())
+            }
+            other => {
+                tracing::debug!("Not an input event, ignored: {:?}", other);
+                Ok(())
+            }
+        }
+    }
+
+    fn is_capturing(&self) -> bool {
+        false
+    }
+
+    fn get_screen_size(&self) -> (u32, u32) {
+        self.screen_size
+    }
+
+    fn get_cursor_position(&self) -> Result<(i32, i32)> {
+        self.cursor
+            .lock()
+            .map(|cursor| *cursor)
+            .map_err(|_| anyhow::anyhow!("Cursor position poisoned"))
+    }
+
+    fn check_permissions(&self) -> bool {
+        // Creating the devices succeeded
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(events: &[InputEvent]) -> Vec<(u16, i32)> {
+        events.iter().map(|e| (e.code(), e.value())).collect()
+    }
+
+    #[test]
+    fn test_wheel_events_detents() {
+        // One detent down: the wheel counts up, the hi-res axis in 1/120ths
+        assert_eq!(
+            codes(&wheel_events(0, 1, true)),
+            vec![
+                (RelativeAxisType::REL_WHEEL.0, -1),
+                (RelativeAxisType::REL_WHEEL_HI_RES.0, -120),
+            ]
+        );
+        assert_eq!(
+            codes(&wheel_events(-2, 0, true)),
+            vec![
+                (RelativeAxisType::REL_HWHEEL.0, -2),
+                (RelativeAxisType::REL_HWHEEL_HI_RES.0, -240),
+            ]
+        );
+        assert!(wheel_events(0, 0, true).is_empty());
+    }
+
+    #[test]
+    fn test_wheel_events_precise() {
+        assert_eq!(
+            codes(&wheel_events(30, -60, false)),
+            vec![
+                (RelativeAxisType::REL_WHEEL_HI_RES.0, 60),
+                (RelativeAxisType::REL_HWHEEL_HI_RES.0, 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_uinput_injector_creation() {
+        // Needs write access to /dev/uinput, like the real agent
+        match UinputInjector::create() {
+            Ok(injector) => {
+                assert!(!injector.is_capturing());
+                assert_eq!(injector.get_screen_size(), DEFAULT_SCREEN_SIZE);
+            }
+            Err(e) => println!("uinput not available: {:#}", e),
+        }
+    }
+}
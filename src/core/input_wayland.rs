@@ -0,0 +1,420 @@
+use anyhow::{Context, Result};
+use nix::sys::memfd::{memfd_create, MemFdCreateFlag};
+use std::io::Write;
+use std::os::fd::AsFd;
+use std::sync::Mutex;
+use std::time::Instant;
+use tokio::sync::mpsc;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::wl_keyboard::KeymapFormat;
+use wayland_client::protocol::wl_output::{self, WlOutput};
+use wayland_client::protocol::wl_pointer::{Axis, AxisSource, ButtonState};
+use wayland_client::protocol::wl_registry::WlRegistry;
+use wayland_client::protocol::wl_seat::WlSeat;
+use wayland_client::{delegate_noop, Connection, Dispatch, EventQueue, Proxy, QueueHandle, WEnum};
+use wayland_protocols_misc::zwp_virtual_keyboard_v1::client::{
+    zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1,
+    zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1,
+};
+use wayland_protocols_wlr::virtual_pointer::v1::client::{
+    zwlr_virtual_pointer_manager_v1::ZwlrVirtualPointerManagerV1,
+    zwlr_virtual_pointer_v1::ZwlrVirtualPointerV1,
+};
+
+use crate::core::events::{Event, Key, SCROLL_DETENT};
+use crate::core::input::{unicode_entry_sequence, InputHandler, UNICODE_ENTRY_DELAY};
+use crate::core::input_evdev::{evdev_button_code, evdev_key_code};
+
+/// Screen size assumed when the compositor reports no output mode.
+const DEFAULT_SCREEN_SIZE: (u32, u32) = (1920, 1080);
+
+/// XKB keycodes are the evdev codes shifted by this offset.
+const XKB_KEYCODE_OFFSET: u32 = 8;
+
+/// Scroll distance of one wheel detent, in surface units, as libinput reports it.
+const DETENT_SCROLL_DISTANCE: f64 = 15.0;
+
+/// `wl_keyboard.key_state` values, which the virtual keyboard takes as a plain `uint`.
+const KEY_RELEASED: u32 = 0;
+const KEY_PRESSED: u32 = 1;
+
+/// Returns the XKB keycode of `key`, `None` for keys evdev has no code for.
+///
+/// The virtual keyboard sends `keycode - 8`, the evdev code, as `wl_keyboard` does.
+pub fn xkb_keycode(key: &Key) -> Option<u32> {
+    evdev_key_code(key).map(|code| u32::from(code) + XKB_KEYCODE_OFFSET)
+}
+
+/// Returns the XKB keymap given to the compositor for the virtual keyboard.
+///
+/// Uses `$XKB_DEFAULT_LAYOUT` when it is a plain layout name, `us` otherwise.
+fn keymap(layout: Option<&str>) -> String {
+    let layout = layout
+        .filter(|layout| {
+            !layout.is_empty()
+                && layout
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_-()".contains(c))
+        })
+        .unwrap_or("us");
+    format!(
+        "xkb_keymap {{\n\
+         \txkb_keycodes {{ include \"evdev+aliases(qwerty)\" }};\n\
+         \txkb_types {{ include \"complete\" }};\n\
+         \txkb_compat {{ include \"complete\" }};\n\
+         \txkb_symbols {{ include \"pc+{}+inet(evdev)\" }};\n\
+         }};\n",
+        layout
+    )
+}
+
+/// What the registry roundtrip learns about the compositor.
+#[derive(Default)]
+struct State {
+    /// Current mode of the output the pointer is mapped to
+    output_size: Option<(u32, u32)>,
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for State {
+    fn event(
+        _state: &mut Self,
+        _registry: &WlRegistry,
+        _event: wayland_client::protocol::wl_registry::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlOutput, ()> for State {
+    fn event(
+        state: &mut Self,
+        _output: &WlOutput,
+        event: wl_output::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Mode {
+            flags: WEnum::Value(flags),
+            width,
+            height,
+            ..
+        } = event
+        {
+            if flags.contains(wl_output::Mode::Current) && width > 0 && height > 0 {
+                state.output_size = Some((width as u32, height as u32));
+            }
+        }
+    }
+}
+
+delegate_noop!(State: ignore WlSeat);
+delegate_noop!(State: ZwlrVirtualPointerManagerV1);
+delegate_noop!(State: ZwlrVirtualPointerV1);
+delegate_noop!(State: ZwpVirtualKeyboardManagerV1);
+delegate_noop!(State: ZwpVirtualKeyboardV1);
+
+/// Injects events through the compositor, for Wayland sessions without uinput.
+///
+/// Uses the wlroots virtual pointer (`zwlr_virtual_pointer_v1`) and virtual
+/// keyboard (`zwp_virtual_keyboard_v1`) protocols, which Sway, Hyprland and
+/// other wlroots-based compositors offer. It cannot capture input. Absolute
+/// positions are mapped to the first output. [`Event::TextInput`] is typed
+/// with the Ctrl+Shift+U unicode entry of IBus and GTK.
+///
+/// # Examples
+///
+/// ```no_run
+/// use multishiva::core::events::{Event, Key};
+/// use multishiva::core::input::InputHandler;
+/// use multishiva::core::input_wayland::WaylandInjector;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let injector = WaylandInjector::connect()?;
+/// injector.inject_event(Event::KeyPress { key: Key::KeyA }).await?;
+/// injector.inject_event(Event::KeyRelease { key: Key::KeyA }).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct WaylandInjector {
+    conn: Connection,
+    pointer: ZwlrVirtualPointerV1,
+    keyboard: ZwpVirtualKeyboardV1,
+    /// Kept so the objects above stay attached to a live queue
+    _queue: Mutex<EventQueue<State>>,
+    screen_size: (u32, u32),
+    cursor: Mutex<(i32, i32)>,
+    started: Instant,
+}
+
+impl WaylandInjector {
+    /// Connects to the compositor named by `$WAYLAND_DISPLAY`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the compositor cannot be reached, or lacks a seat
+    /// or one of the two virtual input protocols.
+    pub fn connect() -> Result<Self> {
+        let conn =
+            Connection::connect_to_env().context("Failed to connect to the Wayland compositor")?;
+        let (globals, mut queue) =
+            registry_queue_init::<State>(&conn).context("Failed to list Wayland globals")?;
+        let qh = queue.handle();
+
+        let seat: WlSeat = globals
+            .bind(&qh, 1..=7, ())
+            .context("The compositor has no wl_seat")?;
+        let pointer_manager: ZwlrVirtualPointerManagerV1 = globals
+            .bind(&qh, 1..=2, ())
+            .context("The compositor does not support zwlr_virtual_pointer_manager_v1")?;
+        let keyboard_manager: ZwpVirtualKeyboardManagerV1 = globals
+            .bind(&qh, 1..=1, ())
+            .context("The compositor does not support zwp_virtual_keyboard_manager_v1")?;
+        let output: Option<WlOutput> = globals.bind(&qh, 1..=4, ()).ok();
+
+        let pointer = match &output {
+            Some(output) if pointer_manager.version() >= 2 => pointer_manager
+                .create_virtual_pointer_with_output(Some(&seat), Some(output), &qh, ()),
+            _ => pointer_manager.create_virtual_pointer(Some(&seat), &qh, ()),
+        };
+        let keyboard = keyboard_manager.create_virtual_keyboard(&seat, &qh, ());
+        send_keymap(&keyboard)?;
+
+        let mut state = State::default();
+        queue
+            .roundtrip(&mut state)
+            .context("The compositor refused the virtual input devices")?;
+        let screen_size = state.output_size.unwrap_or(DEFAULT_SCREEN_SIZE);
+        tracing::info!(
+            "Injecting through the Wayland virtual pointer and keyboard ({}x{})",
+            screen_size.0,
+            screen_size.1
+        );
+
+        Ok(Self {
+            conn,
+            pointer,
+            keyboard,
+            _queue: Mutex::new(queue),
+            screen_size,
+            cursor: Mutex::new((screen_size.0 as i32 / 2, screen_size.1 as i32 / 2)),
+            started: Instant::now(),
+        })
+    }
+
+    /// Milliseconds since the injector was created, for the event timestamps.
+    fn time(&self) -> u32 {
+        self.started.elapsed().as_millis() as u32
+    }
+
+    /// Moves the pointer to `(x, y)`, clamped to the output.
+    fn move_to(&self, x: i32, y: i32) -> Result<()> {
+        let (width, height) = self.screen_size;
+        let x = x.clamp(0, width as i32 - 1);
+        let y = y.clamp(0, height as i32 - 1);
+        *self
+            .cursor
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Cursor position poisoned"))? = (x, y);
+        self.pointer
+            .motion_absolute(self.time(), x as u32, y as u32, width, height);
+        self.pointer.frame();
+        Ok(())
+    }
+
+    fn button(&self, button: &crate::core::events::MouseButton, state: ButtonState) {
+        self.pointer
+            .button(self.time(), u32::from(evdev_button_code(button)), state);
+        self.pointer.frame();
+    }
+
+    fn key(&self, key: &Key, state: u32) {
+        match xkb_keycode(key) {
+            Some(keycode) => self
+                .keyboard
+                .key(self.time(), keycode - XKB_KEYCODE_OFFSET, state),
+            None => tracing::debug!("No keycode for {:?}, not injected", key),
+        }
+    }
+
+    /// Scrolls by `dx_120`, `dy_120` in 1/120 detents, positive being right and down.
+    fn scroll(&self, dx_120: i32, dy_120: i32, discrete: bool) {
+        let time = self.time();
+        self.pointer.axis_source(AxisSource::Wheel);
+        for (axis, delta) in [
+            (Axis::HorizontalScroll, dx_120),
+            (Axis::VerticalScroll, dy_120),
+        ] {
+            if delta == 0 {
+                continue;
+            }
+            let value = DETENT_SCROLL_DISTANCE * f64::from(delta) / f64::from(SCROLL_DETENT);
+            if discrete {
+                self.pointer
+                    .axis_discrete(time, axis, value, delta / SCROLL_DETENT);
+            } else {
+                self.pointer.axis(time, axis, value);
+            }
+        }
+        self.pointer.frame();
+    }
+}
+
+/// Returns an anonymous in-memory file holding `keymap`, for the compositor to map.
+///
+/// Nothing is created in a shared directory another user could swap or clobber.
+fn keymap_file(keymap: &[u8]) -> Result<std::fs::File> {
+    let fd = memfd_create(c"multishiva-keymap", MemFdCreateFlag::MFD_CLOEXEC)
+        .context("Failed to create the keymap file")?;
+    let mut file = std::fs::File::from(fd);
+    file.write_all(keymap)
+        .context("Failed to write the keymap")?;
+    Ok(file)
+}
+
+/// Gives `keyboard` its keymap through an anonymous in-memory file.
+fn send_keymap(keyboard: &ZwpVirtualKeyboardV1) -> Result<()> {
+    let layout = std::env::var("XKB_DEFAULT_LAYOUT").ok();
+    let mut keymap = keymap(layout.as_deref()).into_bytes();
+    // The compositor reads it as a C string
+    keymap.push(0);
+
+    let file = keymap_file(&keymap)?;
+    keyboard.keymap(
+        KeymapFormat::XkbV1 as u32,
+        file.as_fd(),
+        keymap.len() as u32,
+    );
+    Ok(())
+}
+
+impl InputHandler for WaylandInjector {
+    async fn start_capture(&mut self, _tx: mpsc::Sender<Event>) -> Result<()> {
+        anyhow::bail!("The Wayland backend only injects input; capture needs the evdev backend")
+    }
+
+    async fn stop_capture(&mut self) {}
+
+    async fn inject_event(&self, event: Event) -> Result<()> {
+        match event {
+            Event::MouseMove { x, y } => self.move_to(x, y)?,
+            Event::MouseMoveRelative { dx, dy } => {
+                let (x, y) = self.get_cursor_position()?;
+                self.move_to(x.saturating_add(dx), y.saturating_add(dy))?;
+            }
+            Event::MouseButtonPress { button } => self.button(&button, ButtonState::Pressed),
+            Event::MouseButtonRelease { button } => self.button(&button, ButtonState::Released),
+            Event::MouseClick { button } => {
+                self.button(&button, ButtonState::Pressed);
+                self.button(&button, ButtonState::Released);
+            }
+            Event::MouseScroll { delta_x, delta_y }
+            | Event::MouseScrollWithModifiers {
+                delta_x, delta_y, ..
+            } => self.scroll(
+                (delta_x as i32).saturating_mul(SCROLL_DETENT),
+                (delta_y as i32).saturating_mul(SCROLL_DETENT),
+                true,
+            ),
+            Event::MouseScrollPrecise { dx_120, dy_120 } => self.scroll(dx_120, dy_120, false),
+            Event::KeyPress { key } => self.key(&key, KEY_PRESSED),
+            Event::KeyRelease { key } => self.key(&key, KEY_RELEASED),
+            Event::TextInput { text } => {
+                for event in text.chars().flat_map(unicode_entry_sequence) {
+                    match event {
+                        Event::KeyPress { key } => self.key(&key, KEY_PRESSED),
+                        Event::KeyRelease { key } => self.key(&key, KEY_RELEASED),
+                        _ => {}
+                    }
+                    self.conn
+                        .flush()
+                        .context("Failed to send the event to the compositor")?;
+                    tokio::time::sleep(UNICODE_ENTRY_DELAY).await;
+                }
+            }
+            other => {
+                tracing::debug!("Not an input event, ignored: {:?}", other);
+                return Ok(());
+            }
+        }
+        self.conn
+            .flush()
+            .context("Failed to send the event to the compositor")
+    }
+
+    fn is_capturing(&self) -> bool {
+        false
+    }
+
+    fn get_screen_size(&self) -> (u32, u32) {
+        self.screen_size
+    }
+
+    fn get_cursor_position(&self) -> Result<(i32, i32)> {
+        self.cursor
+            .lock()
+            .map(|cursor| *cursor)
+            .map_err(|_| anyhow::anyhow!("Cursor position poisoned"))
+    }
+
+    fn check_permissions(&self) -> bool {
+        // Connecting succeeded, so the compositor lets us inject
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::events::MouseButton;
+    use std::os::fd::AsRawFd;
+
+    #[test]
+    fn test_xkb_keycodes() {
+        // evdev codes from linux/input-event-codes.h, plus 8
+        assert_eq!(xkb_keycode(&Key::Escape), Some(9));
+        assert_eq!(xkb_keycode(&Key::KeyA), Some(38));
+        assert_eq!(xkb_keycode(&Key::Return), Some(36));
+        assert_eq!(xkb_keycode(&Key::ShiftLeft), Some(50));
+        assert_eq!(xkb_keycode(&Key::UpArrow), Some(111));
+        assert_eq!(xkb_keycode(&Key::MetaLeft), Some(133));
+    }
+
+    #[test]
+    fn test_button_codes() {
+        assert_eq!(evdev_button_code(&MouseButton::Left), 0x110);
+        assert_eq!(evdev_button_code(&MouseButton::Right), 0x111);
+        assert_eq!(evdev_button_code(&MouseButton::Middle), 0x112);
+    }
+
+    #[test]
+    fn test_keymap_file_is_anonymous() {
+        use std::io::{Read, Seek};
+
+        let mut file = keymap_file(b"xkb_keymap {};\0").unwrap();
+        let link = std::fs::read_link(format!("/proc/self/fd/{}", file.as_raw_fd())).unwrap();
+        assert!(link
+            .to_string_lossy()
+            .starts_with("/memfd:multishiva-keymap"));
+
+        let mut written = Vec::new();
+        file.rewind().unwrap();
+        file.read_to_end(&mut written).unwrap();
+        assert_eq!(written, b"xkb_keymap {};\0");
+    }
+
+    #[test]
+    fn test_keymap_layout() {
+        assert!(keymap(Some("fr")).contains("\"pc+fr+inet(evdev)\""));
+        assert!(keymap(Some("de(nodeadkeys)")).contains("pc+de(nodeadkeys)+"));
+        for unusable in [None, Some(""), Some("us\"; };"), Some("fr,us")] {
+            assert!(
+                keymap(unusable).contains("\"pc+us+inet(evdev)\""),
+                "{:?}",
+                unusable
+            );
+        }
+    }
+}
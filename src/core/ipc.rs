@@ -0,0 +1,1016 @@
+/// Local control socket for a running daemon
+///
+/// The daemon listens on a local endpoint and answers newline-delimited JSON
+/// requests. One request is read per line and answered with one JSON line:
+///
+/// ```text
+/// {"method":"status"}
+/// {"mode":"host","uptime_secs":120,"connected_agents":["agent1"],"focus":"agent1",...}
+/// ```
+///
+/// Requests that cannot be handled are answered with `{"error":"..."}`, and
+/// requests without a result, such as `{"method":"rotate_psk","new_psk":"..."}`,
+/// `{"method":"clipboard_restore","index":1}` or `{"method":"shutdown"}`, with
+/// `{"ok":true}`.
+///
+/// The endpoint is a Unix domain socket, `$XDG_RUNTIME_DIR/multishiva.sock` by
+/// default, on Linux and macOS. Only its owner may connect to it. Other
+/// platforms listen on localhost TCP port [`CONTROL_PORT`] instead, which
+/// any local user can reach, so every request carries the token the daemon
+/// wrote to [`TOKEN_NAME`] in the user's configuration directory:
+///
+/// ```text
+/// {"method":"shutdown","token":"..."}
+/// ```
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::core::clipboard::ClipboardChange;
+use crate::core::discovery::PeerInfo;
+use crate::core::metrics::MetricsSnapshot;
+
+/// File name of the control socket inside the runtime directory.
+pub const SOCKET_NAME: &str = "multishiva.sock";
+
+/// Localhost port of the control endpoint where Unix sockets are unavailable.
+pub const CONTROL_PORT: u16 = 53422;
+
+/// File name of the token TCP clients must send, inside the configuration directory.
+pub const TOKEN_NAME: &str = "control.token";
+
+/// Address of the control endpoint: a socket path on Unix, a TCP address elsewhere.
+#[cfg(unix)]
+pub type Endpoint = std::path::PathBuf;
+
+/// Address of the control endpoint: a socket path on Unix, a TCP address elsewhere.
+#[cfg(not(unix))]
+pub type Endpoint = std::net::SocketAddr;
+
+/// A request sent to the daemon.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum IpcRequest {
+    /// Ask for the daemon's current status.
+    Status,
+    /// Switch the daemon to a new pre-shared key.
+    RotatePsk {
+        /// The new key
+        new_psk: String,
+    },
+    /// Ask for the recent clipboard changes, most recent first.
+    ClipboardHistory,
+    /// Put a clipboard history entry back without sending it to the peers.
+    ClipboardRestore {
+        /// Position in the history, 0 being the most recent
+        index: usize,
+    },
+    /// Stop the daemon as Ctrl+C would.
+    Shutdown,
+}
+
+/// A machine connected to the daemon.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerStatus {
+    /// Name announced by the peer, or its address when unknown
+    pub name: String,
+    /// Network address of the peer
+    pub address: String,
+    /// Milliseconds since the last frame was received from the peer
+    pub last_heartbeat_ms: Option<u64>,
+    /// Seconds since the peer connected, when known
+    #[serde(default)]
+    pub connected_secs: Option<u64>,
+    /// Round trip to the peer in milliseconds, when measured
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    /// Events the host sent the peer, reported by the host only
+    #[serde(default)]
+    pub traffic: Option<PeerTraffic>,
+}
+
+/// What a host sent one of its agents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerTraffic {
+    /// Events written to the agent
+    pub events_sent: u64,
+    /// Mouse moves dropped by the host's throttle instead of being written
+    pub events_dropped: u64,
+    /// Bytes of the events written to the agent
+    pub bytes_sent: u64,
+}
+
+/// A machine found through mDNS.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveredPeer {
+    /// Advertised instance name
+    pub name: String,
+    /// Advertised address, as `ip:port`
+    pub address: String,
+}
+
+impl From<PeerInfo> for DiscoveredPeer {
+    fn from(peer: PeerInfo) -> Self {
+        Self {
+            address: peer.full_address(),
+            name: peer.name,
+        }
+    }
+}
+
+/// A change in the daemon's clipboard history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipboardEntry {
+    /// Copied text
+    pub text: String,
+    /// Machine the change came from, `None` if copied locally
+    pub source: Option<String>,
+    /// When the change was accepted
+    pub copied_at: DateTime<Utc>,
+}
+
+impl From<ClipboardChange> for ClipboardEntry {
+    fn from(change: ClipboardChange) -> Self {
+        Self {
+            text: change.content.as_text().unwrap_or_default().to_string(),
+            source: change.source,
+            copied_at: change.timestamp.into(),
+        }
+    }
+}
+
+/// The daemon's answer to [`IpcRequest::ClipboardHistory`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ClipboardHistoryResponse {
+    entries: Vec<ClipboardEntry>,
+}
+
+/// The daemon's answer to [`IpcRequest::Status`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusResponse {
+    /// `host` or `agent`
+    pub mode: String,
+    /// Seconds since the daemon started
+    pub uptime_secs: u64,
+    /// Names of the agents connected to a host, sorted
+    pub connected_agents: Vec<String>,
+    /// Connected machines with their address and heartbeat age
+    #[serde(default)]
+    pub peers: Vec<PeerStatus>,
+    /// Machine currently receiving input, if it is not the local one
+    pub focus: Option<String>,
+    /// Events written to peers since the daemon started
+    #[serde(default)]
+    pub events_forwarded: u64,
+    /// Events waiting to be written to peers
+    #[serde(default)]
+    pub queue_depth: usize,
+    /// Heartbeat latency and traffic of the network
+    #[serde(default)]
+    pub metrics: Option<MetricsSnapshot>,
+    /// Machines found through mDNS
+    #[serde(default)]
+    pub discovered_peers: Vec<DiscoveredPeer>,
+    /// Last time the host took focus back after a period without input
+    #[serde(default)]
+    pub last_auto_release: Option<DateTime<Utc>>,
+}
+
+impl std::fmt::Display for StatusResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let uptime = self.uptime_secs;
+        writeln!(f, "Mode:             {}", self.mode)?;
+        writeln!(
+            f,
+            "Uptime:           {}h {:02}m {:02}s",
+            uptime / 3600,
+            uptime / 60 % 60,
+            uptime % 60
+        )?;
+        writeln!(
+            f,
+            "Focus:            {}",
+            self.focus.as_deref().unwrap_or("local")
+        )?;
+        writeln!(f, "Events forwarded: {}", self.events_forwarded)?;
+        writeln!(f, "Queue depth:      {}", self.queue_depth)?;
+        if let Some(released) = self.last_auto_release {
+            writeln!(f, "Auto-released:    {}", released.to_rfc3339())?;
+        }
+        if let Some(metrics) = &self.metrics {
+            writeln!(f, "Network:          {}", metrics)?;
+        }
+
+        writeln!(f, "Connected peers ({}):", self.peers.len())?;
+        for peer in &self.peers {
+            let heartbeat = match peer.last_heartbeat_ms {
+                Some(ms) => format!("{:.1}s ago", ms as f64 / 1000.0),
+                None => "never".to_string(),
+            };
+            write!(
+                f,
+                "  {:<20} {:<24} last heartbeat {}",
+                peer.name, peer.address, heartbeat
+            )?;
+            if let Some(secs) = peer.connected_secs {
+                write!(f, ", connected for {}s", secs)?;
+            }
+            if let Some(ms) = peer.latency_ms {
+                write!(f, ", latency {}ms", ms)?;
+            }
+            match peer.traffic {
+                Some(traffic) => writeln!(
+                    f,
+                    ", sent {} events ({} bytes), dropped {}",
+                    traffic.events_sent, traffic.bytes_sent, traffic.events_dropped
+                )?,
+                None => writeln!(f)?,
+            }
+        }
+
+        write!(f, "Discovered peers ({}):", self.discovered_peers.len())?;
+        for peer in &self.discovered_peers {
+            write!(f, "\n  {:<20} {}", peer.name, peer.address)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the status reported to clients, called once per request.
+pub type StatusProvider = Arc<dyn Fn() -> StatusResponse + Send + Sync>;
+
+/// Starts rotating the daemon's pre-shared key, for [`IpcRequest::RotatePsk`].
+pub type PskRotator = Arc<dyn Fn(String) -> Result<()> + Send + Sync>;
+
+/// Lists the clipboard history, for [`IpcRequest::ClipboardHistory`].
+pub type ClipboardHistoryProvider = Arc<dyn Fn() -> Vec<ClipboardEntry> + Send + Sync>;
+
+/// Restores a clipboard history entry, for [`IpcRequest::ClipboardRestore`].
+pub type ClipboardRestorer = Arc<dyn Fn(usize) -> Result<()> + Send + Sync>;
+
+/// Asks the daemon to stop, for [`IpcRequest::Shutdown`].
+pub type ShutdownHandler = Arc<dyn Fn() + Send + Sync>;
+
+/// A request line: the request, and the token where the endpoint needs one.
+#[derive(Debug, Serialize, Deserialize)]
+struct RequestLine {
+    #[serde(flatten)]
+    request: IpcRequest,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+}
+
+/// Error answer sent for requests that cannot be handled.
+#[derive(Debug, Serialize, Deserialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Answer sent for requests that succeeded without a result.
+#[derive(Debug, Serialize, Deserialize)]
+struct OkResponse {
+    ok: bool,
+}
+
+/// Callbacks answering the requests of a server.
+#[derive(Clone)]
+struct Handlers {
+    status: StatusProvider,
+    rotate_psk: Option<PskRotator>,
+    clipboard: Option<(ClipboardHistoryProvider, ClipboardRestorer)>,
+    shutdown: Option<ShutdownHandler>,
+    /// Token every request must carry, if any
+    token: Option<String>,
+}
+
+/// Returns the default control endpoint.
+///
+/// On Unix this is `multishiva.sock` in `$XDG_RUNTIME_DIR`, or when it is
+/// not set in a `multishiva-<uid>` directory of the temporary directory
+/// that only the user can enter. Elsewhere it is
+/// `127.0.0.1:`[`CONTROL_PORT`].
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::ipc::default_endpoint;
+///
+/// println!("Control endpoint: {:?}", default_endpoint());
+/// ```
+pub fn default_endpoint() -> Endpoint {
+    #[cfg(unix)]
+    {
+        let uid = nix::unistd::getuid();
+        dirs::runtime_dir()
+            .unwrap_or_else(|| private_dir(&std::env::temp_dir(), uid.as_raw()))
+            .join(SOCKET_NAME)
+    }
+
+    #[cfg(not(unix))]
+    {
+        std::net::SocketAddr::from(([127, 0, 0, 1], CONTROL_PORT))
+    }
+}
+
+/// Returns `multishiva-<uid>` in `parent`, created with mode 0700.
+///
+/// A directory of that name that another user could enter, or that is not
+/// a directory owned by `uid`, is not used: the socket then goes in a path
+/// that cannot be bound, so the daemon fails instead of listening where
+/// others can reach it.
+#[cfg(unix)]
+fn private_dir(parent: &std::path::Path, uid: u32) -> std::path::PathBuf {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    let dir = parent.join(format!("multishiva-{}", uid));
+    let _ = std::fs::DirBuilder::new().mode(0o700).create(&dir);
+    match std::fs::symlink_metadata(&dir) {
+        Ok(meta)
+            if meta.is_dir() && meta.uid() == uid && meta.permissions().mode() & 0o077 == 0 =>
+        {
+            dir
+        }
+        _ => {
+            tracing::warn!(
+                "{:?} is not a private directory of this user, not using it for the control socket",
+                dir
+            );
+            dir.join("unsafe")
+        }
+    }
+}
+
+/// Returns where the daemon writes the token of its TCP control endpoint.
+pub fn token_path() -> std::path::PathBuf {
+    dirs::config_dir()
+        .unwrap_or_default()
+        .join("multishiva")
+        .join(TOKEN_NAME)
+}
+
+/// Writes a new random token to `path`, readable by its owner only.
+///
+/// # Errors
+///
+/// Returns an error if no random bytes are available or `path` cannot be written.
+pub fn write_token(path: &std::path::Path) -> Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).context("Failed to generate a control token")?;
+    let token = hex::encode(bytes);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+    let _ = std::fs::remove_file(path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create control token {:?}", path))?;
+    std::io::Write::write_all(&mut file, token.as_bytes())
+        .with_context(|| format!("Failed to write control token {:?}", path))?;
+    Ok(token)
+}
+
+/// Compares tokens in a time that does not depend on where they differ.
+fn token_matches(expected: &str, given: Option<&str>) -> bool {
+    let Some(given) = given else {
+        return false;
+    };
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Answers a single request line.
+fn handle_line(line: &str, handlers: &Handlers) -> String {
+    let error = |error: String| serde_json::to_string(&ErrorResponse { error });
+    let request = serde_json::from_str::<RequestLine>(line).map(|line| {
+        let authorized = handlers
+            .token
+            .as_deref()
+            .is_none_or(|expected| token_matches(expected, line.token.as_deref()));
+        (line.request, authorized)
+    });
+    let request = match request {
+        Ok((_, false)) => {
+            return r#"{"error":"Missing or wrong control token"}"#.to_string();
+        }
+        Ok((request, true)) => Ok(request),
+        Err(e) => Err(e),
+    };
+    let result = match request {
+        Ok(IpcRequest::Status) => serde_json::to_string(&(handlers.status)()),
+        Ok(IpcRequest::RotatePsk { new_psk }) => match &handlers.rotate_psk {
+            Some(rotate) => match rotate(new_psk) {
+                Ok(()) => serde_json::to_string(&OkResponse { ok: true }),
+                Err(e) => error(format!("PSK rotation failed: {}", e)),
+            },
+            None => error("This daemon does not support PSK rotation".to_string()),
+        },
+        Ok(IpcRequest::ClipboardHistory) => match &handlers.clipboard {
+            Some((history, _)) => {
+                serde_json::to_string(&ClipboardHistoryResponse { entries: history() })
+            }
+            None => error("This daemon does not keep a clipboard history".to_string()),
+        },
+        Ok(IpcRequest::ClipboardRestore { index }) => match &handlers.clipboard {
+            Some((_, restore)) => match restore(index) {
+                Ok(()) => serde_json::to_string(&OkResponse { ok: true }),
+                Err(e) => error(format!("Clipboard restore failed: {}", e)),
+            },
+            None => error("This daemon does not keep a clipboard history".to_string()),
+        },
+        Ok(IpcRequest::Shutdown) => match &handlers.shutdown {
+            Some(shutdown) => {
+                shutdown();
+                serde_json::to_string(&OkResponse { ok: true })
+            }
+            None => error("This daemon cannot be stopped remotely".to_string()),
+        },
+        Err(e) => error(format!("Invalid request: {}", e)),
+    };
+    result.unwrap_or_else(|e| format!("{{\"error\":\"{}\"}}", e))
+}
+
+/// Parses a response line, turning error answers into errors.
+fn parse_status(line: &str) -> Result<StatusResponse> {
+    if let Ok(ErrorResponse { error }) = serde_json::from_str(line) {
+        anyhow::bail!("Daemon returned an error: {}", error);
+    }
+    serde_json::from_str(line).context("Invalid status response from daemon")
+}
+
+/// Parses the answer to a clipboard history request.
+fn parse_clipboard_history(line: &str) -> Result<Vec<ClipboardEntry>> {
+    if let Ok(ErrorResponse { error }) = serde_json::from_str(line) {
+        anyhow::bail!("Daemon returned an error: {}", error);
+    }
+    let response: ClipboardHistoryResponse =
+        serde_json::from_str(line).context("Invalid clipboard history from daemon")?;
+    Ok(response.entries)
+}
+
+/// Parses the answer to a request without a result.
+fn parse_ok(line: &str) -> Result<()> {
+    if let Ok(ErrorResponse { error }) = serde_json::from_str(line) {
+        anyhow::bail!("Daemon returned an error: {}", error);
+    }
+    let response: OkResponse =
+        serde_json::from_str(line).context("Invalid response from daemon")?;
+    anyhow::ensure!(response.ok, "Daemon did not accept the request");
+    Ok(())
+}
+
+#[cfg(unix)]
+type Listener = tokio::net::UnixListener;
+
+#[cfg(unix)]
+type Stream = tokio::net::UnixStream;
+
+#[cfg(not(unix))]
+type Listener = tokio::net::TcpListener;
+
+#[cfg(not(unix))]
+type Stream = tokio::net::TcpStream;
+
+/// Control endpoint server of a running daemon.
+///
+/// On Unix the socket file is removed when the server is dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use multishiva::core::ipc::{default_endpoint, IpcServer, StatusResponse};
+/// use std::sync::Arc;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let server = IpcServer::bind(default_endpoint()).await?;
+/// tokio::spawn(server.serve(Arc::new(|| StatusResponse {
+///     mode: "host".to_string(),
+///     uptime_secs: 0,
+///     connected_agents: Vec::new(),
+///     peers: Vec::new(),
+///     focus: None,
+///     events_forwarded: 0,
+///     queue_depth: 0,
+///     metrics: None,
+///     discovered_peers: Vec::new(),
+///     last_auto_release: None,
+/// })));
+/// # Ok(())
+/// # }
+/// ```
+pub struct IpcServer {
+    listener: Listener,
+    endpoint: Endpoint,
+    psk_rotator: Option<PskRotator>,
+    clipboard: Option<(ClipboardHistoryProvider, ClipboardRestorer)>,
+    shutdown: Option<ShutdownHandler>,
+    token: Option<String>,
+}
+
+impl IpcServer {
+    /// Binds the control endpoint.
+    ///
+    /// On Unix, a socket file left behind by a daemon that is no longer
+    /// running is replaced. Elsewhere a new token is written to
+    /// [`token_path`], and requests without it are refused.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another daemon is already answering on `endpoint`
+    /// or it cannot be bound.
+    pub async fn bind(endpoint: Endpoint) -> Result<Self> {
+        #[cfg(unix)]
+        if endpoint.exists() {
+            if Stream::connect(&endpoint).await.is_ok() {
+                anyhow::bail!("Another MultiShiva daemon is listening on {:?}", endpoint);
+            }
+            std::fs::remove_file(&endpoint)
+                .with_context(|| format!("Failed to remove stale socket {:?}", endpoint))?;
+        }
+
+        #[cfg(unix)]
+        let listener = Listener::bind(&endpoint);
+        #[cfg(not(unix))]
+        let listener = Listener::bind(endpoint).await;
+        let listener =
+            listener.with_context(|| format!("Failed to bind control endpoint {:?}", endpoint))?;
+
+        // The endpoint can change the PSK, so keep other users out
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&endpoint, std::fs::Permissions::from_mode(0o600))
+                .with_context(|| format!("Failed to restrict access to {:?}", endpoint))?;
+        }
+
+        // Any local user can reach a TCP port, only this one can read the token
+        #[cfg(unix)]
+        let token = None;
+        #[cfg(not(unix))]
+        let token = Some(write_token(&token_path())?);
+
+        tracing::info!("Control endpoint listening on {:?}", endpoint);
+        Ok(Self {
+            listener,
+            endpoint,
+            psk_rotator: None,
+            clipboard: None,
+            shutdown: None,
+            token,
+        })
+    }
+
+    /// Answers [`IpcRequest::RotatePsk`] with `rotator`.
+    ///
+    /// Without a rotator, rotation requests are answered with an error.
+    pub fn with_psk_rotator(mut self, rotator: PskRotator) -> Self {
+        self.psk_rotator = Some(rotator);
+        self
+    }
+
+    /// Answers [`IpcRequest::ClipboardHistory`] with `history` and
+    /// [`IpcRequest::ClipboardRestore`] with `restorer`.
+    ///
+    /// Without them, clipboard requests are answered with an error.
+    pub fn with_clipboard_history(
+        mut self,
+        history: ClipboardHistoryProvider,
+        restorer: ClipboardRestorer,
+    ) -> Self {
+        self.clipboard = Some((history, restorer));
+        self
+    }
+
+    /// Answers [`IpcRequest::Shutdown`] by calling `handler`.
+    ///
+    /// Without a handler, shutdown requests are answered with an error.
+    pub fn with_shutdown_handler(mut self, handler: ShutdownHandler) -> Self {
+        self.shutdown = Some(handler);
+        self
+    }
+
+    /// Returns the endpoint this server listens on.
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    /// Answers requests until accepting a connection fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if accepting a connection fails.
+    pub async fn serve(self, provider: StatusProvider) -> Result<()> {
+        let handlers = Handlers {
+            status: provider,
+            rotate_psk: self.psk_rotator.clone(),
+            clipboard: self.clipboard.clone(),
+            shutdown: self.shutdown.clone(),
+            token: self.token.clone(),
+        };
+        loop {
+            let (stream, _) = self.listener.accept().await?;
+            let handlers = handlers.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_client(stream, handlers).await {
+                    tracing::debug!("Control endpoint client failed: {}", e);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.endpoint);
+    }
+}
+
+async fn handle_client<S>(stream: S, handlers: Handlers) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut response = handle_line(&line, &handlers);
+        response.push('\n');
+        writer.write_all(response.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Asks the daemon listening on `endpoint` for its status.
+///
+/// # Errors
+///
+/// Returns an error if no daemon is listening on `endpoint` or it answers
+/// with an error.
+pub async fn query_status(endpoint: &Endpoint) -> Result<StatusResponse> {
+    parse_status(&send_request(endpoint, &IpcRequest::Status).await?)
+}
+
+/// Asks the daemon listening on `endpoint` to switch to `new_psk`.
+///
+/// The daemon only starts the rotation; see
+/// [`Network::rotate_psk`](crate::core::network::Network::rotate_psk).
+///
+/// # Errors
+///
+/// Returns an error if no daemon is listening on `endpoint` or it rejects
+/// the new key.
+pub async fn request_psk_rotation(endpoint: &Endpoint, new_psk: String) -> Result<()> {
+    parse_ok(&send_request(endpoint, &IpcRequest::RotatePsk { new_psk }).await?)
+}
+
+/// Asks the daemon listening on `endpoint` for its clipboard history, most
+/// recent first.
+///
+/// # Errors
+///
+/// Returns an error if no daemon is listening on `endpoint` or it keeps no
+/// clipboard history.
+pub async fn query_clipboard_history(endpoint: &Endpoint) -> Result<Vec<ClipboardEntry>> {
+    parse_clipboard_history(&send_request(endpoint, &IpcRequest::ClipboardHistory).await?)
+}
+
+/// Asks the daemon listening on `endpoint` to put clipboard history entry
+/// `index` back into its clipboard, without sending it to the peers.
+///
+/// # Errors
+///
+/// Returns an error if no daemon is listening on `endpoint`, it has no entry
+/// `index` or it cannot set its clipboard.
+pub async fn request_clipboard_restore(endpoint: &Endpoint, index: usize) -> Result<()> {
+    parse_ok(&send_request(endpoint, &IpcRequest::ClipboardRestore { index }).await?)
+}
+
+/// Asks the daemon listening on `endpoint` to stop.
+///
+/// The daemon answers before it stops, so it may still be running for a
+/// moment when this returns.
+///
+/// # Errors
+///
+/// Returns an error if no daemon is listening on `endpoint` or it cannot be
+/// stopped remotely.
+pub async fn request_shutdown(endpoint: &Endpoint) -> Result<()> {
+    parse_ok(&send_request(endpoint, &IpcRequest::Shutdown).await?)
+}
+
+/// Sends one request and returns the answer line.
+async fn send_request(endpoint: &Endpoint, request: &IpcRequest) -> Result<String> {
+    let stream = Stream::connect(endpoint)
+        .await
+        .with_context(|| format!("No MultiShiva daemon is listening on {:?}", endpoint))?;
+    let (reader, mut writer) = tokio::io::split(stream);
+
+    #[cfg(unix)]
+    let token = None;
+    #[cfg(not(unix))]
+    let token = Some(
+        std::fs::read_to_string(token_path())
+            .context("Failed to read the control token of the daemon")?
+            .trim()
+            .to_string(),
+    );
+    let mut request = serde_json::to_string(&RequestLine {
+        request: request.clone(),
+        token,
+    })?;
+    request.push('\n');
+    writer.write_all(request.as_bytes()).await?;
+
+    BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await?
+        .context("Daemon closed the control endpoint without answering")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> StatusResponse {
+        StatusResponse {
+            mode: "host".to_string(),
+            uptime_secs: 3725,
+            connected_agents: vec!["agent1".to_string()],
+            peers: vec![PeerStatus {
+                name: "agent1".to_string(),
+                address: "192.168.1.20:50412".to_string(),
+                last_heartbeat_ms: Some(1500),
+                connected_secs: Some(90),
+                latency_ms: Some(7),
+                traffic: Some(PeerTraffic {
+                    events_sent: 40,
+                    events_dropped: 2,
+                    bytes_sent: 1800,
+                }),
+            }],
+            focus: Some("agent1".to_string()),
+            events_forwarded: 42,
+            queue_depth: 3,
+            metrics: Some(MetricsSnapshot {
+                latency_samples: 12,
+                latency_p50_ms: Some(4),
+                latency_p95_ms: Some(31),
+                latency_max_ms: Some(48),
+                events_forwarded: 42,
+                events_received: 17,
+                events_per_sec: 2.5,
+                bytes_sent: 2048,
+                bytes_received: 1024,
+                injection_failures: 0,
+            }),
+            discovered_peers: vec![DiscoveredPeer {
+                name: "agent2".to_string(),
+                address: "192.168.1.30:53421".to_string(),
+            }],
+            last_auto_release: Some("2026-10-14T08:30:00Z".parse().unwrap()),
+        }
+    }
+
+    fn provider() -> StatusProvider {
+        Arc::new(status)
+    }
+
+    fn handlers() -> Handlers {
+        Handlers {
+            status: provider(),
+            rotate_psk: None,
+            clipboard: None,
+            shutdown: None,
+            token: None,
+        }
+    }
+
+    #[test]
+    fn test_status_request_format() {
+        assert_eq!(
+            serde_json::to_string(&IpcRequest::Status).unwrap(),
+            r#"{"method":"status"}"#
+        );
+        let response = handle_line(r#"{"method":"status"}"#, &handlers());
+        assert_eq!(parse_status(&response).unwrap(), status());
+    }
+
+    #[test]
+    fn test_status_accepts_minimal_response() {
+        let status = parse_status(
+            r#"{"mode":"host","connected_agents":["agent1"],"focus":"agent1","uptime_secs":120}"#,
+        )
+        .unwrap();
+        assert_eq!(status.connected_agents, vec!["agent1".to_string()]);
+        assert!(status.peers.is_empty());
+        assert_eq!(status.events_forwarded, 0);
+        assert_eq!(status.queue_depth, 0);
+        assert_eq!(status.metrics, None);
+    }
+
+    #[test]
+    fn test_status_display() {
+        let text = status().to_string();
+        assert!(text.contains("Uptime:           1h 02m 05s"));
+        assert!(text.contains("Focus:            agent1"));
+        assert!(text.contains("Queue depth:      3"));
+        assert!(text.contains("Network:          latency p50 4ms, p95 31ms, max 48ms"));
+        assert!(text.contains("192.168.1.20:50412"));
+        assert!(text.contains("last heartbeat 1.5s ago, connected for 90s, latency 7ms"));
+        assert!(text.contains("sent 40 events (1800 bytes), dropped 2"));
+        assert!(text.contains("Discovered peers (1):"));
+        assert!(text.contains("Auto-released:    2026-10-14T08:30:00+00:00"));
+    }
+
+    #[test]
+    fn test_unknown_method_is_an_error() {
+        let response = handle_line(r#"{"method":"reboot"}"#, &handlers());
+        let err = parse_status(&response).unwrap_err();
+        assert!(err.to_string().contains("Invalid request"));
+    }
+
+    #[test]
+    fn test_rotate_psk_request() {
+        let request = r#"{"method":"rotate_psk","new_psk":"new-key"}"#;
+        let err = parse_ok(&handle_line(request, &handlers())).unwrap_err();
+        assert!(err.to_string().contains("does not support"));
+
+        let rotator: PskRotator = Arc::new(|new_psk| {
+            anyhow::ensure!(new_psk == "new-key", "unexpected key");
+            Ok(())
+        });
+        let handlers = Handlers {
+            rotate_psk: Some(rotator),
+            ..handlers()
+        };
+        parse_ok(&handle_line(request, &handlers)).unwrap();
+
+        let rejected = r#"{"method":"rotate_psk","new_psk":"other"}"#;
+        let err = parse_ok(&handle_line(rejected, &handlers)).unwrap_err();
+        assert!(err.to_string().contains("unexpected key"));
+    }
+
+    #[test]
+    fn test_clipboard_requests() {
+        let history = r#"{"method":"clipboard_history"}"#;
+        assert_eq!(
+            serde_json::to_string(&IpcRequest::ClipboardHistory).unwrap(),
+            history
+        );
+        let restore = r#"{"method":"clipboard_restore","index":1}"#;
+        assert_eq!(
+            serde_json::to_string(&IpcRequest::ClipboardRestore { index: 1 }).unwrap(),
+            restore
+        );
+        let err = parse_clipboard_history(&handle_line(history, &handlers())).unwrap_err();
+        assert!(err.to_string().contains("does not keep"));
+
+        let entry = ClipboardEntry {
+            text: "copied on the host".to_string(),
+            source: Some("host".to_string()),
+            copied_at: "2026-10-14T08:30:00Z".parse().unwrap(),
+        };
+        let entries = vec![entry.clone()];
+        let provider: ClipboardHistoryProvider = Arc::new(move || entries.clone());
+        let restorer: ClipboardRestorer = Arc::new(|index| {
+            anyhow::ensure!(index == 0, "no entry {}", index);
+            Ok(())
+        });
+        let handlers = Handlers {
+            clipboard: Some((provider, restorer)),
+            ..handlers()
+        };
+        assert_eq!(
+            parse_clipboard_history(&handle_line(history, &handlers)).unwrap(),
+            vec![entry]
+        );
+        parse_ok(&handle_line(
+            r#"{"method":"clipboard_restore","index":0}"#,
+            &handlers,
+        ))
+        .unwrap();
+        let err = parse_ok(&handle_line(restore, &handlers)).unwrap_err();
+        assert!(err.to_string().contains("no entry 1"));
+    }
+
+    #[test]
+    fn test_shutdown_request() {
+        let request = r#"{"method":"shutdown"}"#;
+        assert_eq!(
+            serde_json::to_string(&IpcRequest::Shutdown).unwrap(),
+            request
+        );
+        let err = parse_ok(&handle_line(request, &handlers())).unwrap_err();
+        assert!(err.to_string().contains("cannot be stopped"));
+
+        let requested = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = requested.clone();
+        let handlers = Handlers {
+            shutdown: Some(Arc::new(move || {
+                flag.store(true, std::sync::atomic::Ordering::SeqCst)
+            })),
+            ..handlers()
+        };
+        parse_ok(&handle_line(request, &handlers)).unwrap();
+        assert!(requested.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_token_required_on_every_request() {
+        let requested = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = requested.clone();
+        let handlers = Handlers {
+            shutdown: Some(Arc::new(move || {
+                flag.store(true, std::sync::atomic::Ordering::SeqCst)
+            })),
+            token: Some("secret".to_string()),
+            ..handlers()
+        };
+        for line in [
+            r#"{"method":"shutdown"}"#,
+            r#"{"method":"shutdown","token":"wrong!"}"#,
+            r#"{"method":"clipboard_history","token":"secre"}"#,
+            r#"{"method":"status","token":null}"#,
+        ] {
+            let err = parse_ok(&handle_line(line, &handlers)).unwrap_err();
+            assert!(err.to_string().contains("control token"), "{}", line);
+        }
+        assert!(!requested.load(std::sync::atomic::Ordering::SeqCst));
+
+        let line = serde_json::to_string(&RequestLine {
+            request: IpcRequest::Shutdown,
+            token: Some("secret".to_string()),
+        })
+        .unwrap();
+        assert_eq!(line, r#"{"method":"shutdown","token":"secret"}"#);
+        parse_ok(&handle_line(&line, &handlers)).unwrap();
+        assert!(requested.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_write_token_is_private_and_fresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("multishiva").join(TOKEN_NAME);
+        let first = write_token(&path).unwrap();
+        assert_eq!(first.len(), 64);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), first);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert_ne!(write_token(&path).unwrap(), first);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_private_dir_fallback() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let parent = tempfile::tempdir().unwrap();
+        let uid = nix::unistd::getuid().as_raw();
+        let dir = private_dir(parent.path(), uid);
+        assert_eq!(dir, parent.path().join(format!("multishiva-{}", uid)));
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        // Someone else's directory, or one others can enter, is not used
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(private_dir(parent.path(), uid).ends_with("unsafe"));
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700)).unwrap();
+        assert_eq!(private_dir(parent.path(), uid), dir);
+        assert!(private_dir(parent.path(), uid + 1).ends_with("unsafe"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_query_status_over_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint = dir.path().join(SOCKET_NAME);
+        let server = IpcServer::bind(endpoint.clone()).await.unwrap();
+        tokio::spawn(server.serve(provider()));
+
+        assert_eq!(query_status(&endpoint).await.unwrap(), status());
+
+        // A second daemon must not steal the socket
+        assert!(IpcServer::bind(endpoint).await.is_err());
+    }
+}
@@ -30,6 +30,22 @@ pub const SERVICE_NAME: &str = "multishiva";
 /// MultiShiva clients and servers.
 pub const PSK_KEY: &str = "tls_psk";
 
+/// Prefix of the keys holding the PSK of a single peer, followed by its name.
+pub const PEER_PSK_PREFIX: &str = "psk:";
+
+/// Key of the list of peers with their own PSK, one name per line.
+///
+/// System keyrings cannot be enumerated portably, so
+/// [`KeyringManager::set_psk_for`] and [`KeyringManager::delete_psk_for`]
+/// keep this list for [`KeyringManager::list_peers`].
+pub const PEER_INDEX_KEY: &str = "psk_peers";
+
+/// Value of `tls.psk` in a configuration whose PSK lives in the keyring.
+///
+/// Written by [`KeyringManager::migrate_from_config`]; configuration loading
+/// replaces it with [`KeyringManager::get_psk_or_env`].
+pub const KEYRING_PLACEHOLDER: &str = "***STORED_IN_KEYRING***";
+
 /// Manager for secure credential storage using the system keyring.
 ///
 /// `KeyringManager` provides a high-level interface for storing and retrieving
@@ -250,7 +266,7 @@ impl KeyringManager {
         self.set_psk(plaintext_psk)?;
 
         // Return placeholder
-        Ok("***STORED_IN_KEYRING***".to_string())
+        Ok(KEYRING_PLACEHOLDER.to_string())
     }
 
     /// Retrieves the PSK from the keyring with fallback to an environment variable.
@@ -411,6 +427,113 @@ impl KeyringManager {
     pub fn has_credential(&self, key: &str) -> bool {
         self.get_credential(key).is_ok()
     }
+
+    /// Stores the PSK used with one peer, next to the global one.
+    ///
+    /// The PSK is stored under `psk:<peer>` and the peer added to
+    /// [`list_peers`](Self::list_peers).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::keyring::KeyringManager;
+    ///
+    /// let manager = KeyringManager::new();
+    /// manager.set_psk_for("laptop", "laptop-psk").expect("Failed to store PSK");
+    /// assert_eq!(manager.get_psk_for("laptop").unwrap(), "laptop-psk");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the peer name is empty or spans several lines, or
+    /// if the system keyring is unavailable or inaccessible.
+    pub fn set_psk_for(&self, peer: &str, psk: &str) -> Result<()> {
+        if peer.is_empty() || peer.contains('\n') {
+            anyhow::bail!("Invalid peer name: {:?}", peer);
+        }
+        self.set_credential(&peer_psk_key(peer), psk)
+            .with_context(|| format!("Failed to store PSK for {}", peer))?;
+
+        let mut peers = self.list_peers()?;
+        if !peers.iter().any(|known| known == peer) {
+            peers.push(peer.to_string());
+            self.write_peer_index(&peers)?;
+        }
+        Ok(())
+    }
+
+    /// Retrieves the PSK stored for one peer with [`set_psk_for`](Self::set_psk_for).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no PSK is stored for `peer` or the system keyring
+    /// is unavailable or inaccessible.
+    pub fn get_psk_for(&self, peer: &str) -> Result<String> {
+        self.get_credential(&peer_psk_key(peer))
+            .with_context(|| format!("No PSK stored for {}", peer))
+    }
+
+    /// Deletes the PSK stored for one peer, leaving the global one alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no PSK is stored for `peer` or the system keyring
+    /// is unavailable or inaccessible.
+    pub fn delete_psk_for(&self, peer: &str) -> Result<()> {
+        self.delete_credential(&peer_psk_key(peer))
+            .with_context(|| format!("Failed to delete PSK for {}", peer))?;
+
+        let mut peers = self.list_peers()?;
+        peers.retain(|known| known != peer);
+        self.write_peer_index(&peers)
+    }
+
+    /// Returns the peers with their own PSK, sorted by name.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::keyring::KeyringManager;
+    ///
+    /// let manager = KeyringManager::new();
+    /// for peer in manager.list_peers().expect("Failed to read the keyring") {
+    ///     println!("{} has its own PSK", peer);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the system keyring is unavailable or inaccessible.
+    /// No peer stored yet is not an error.
+    pub fn list_peers(&self) -> Result<Vec<String>> {
+        let entry =
+            Entry::new(&self.service, PEER_INDEX_KEY).context("Failed to create keyring entry")?;
+        let index = match entry.get_password() {
+            Ok(index) => index,
+            Err(keyring::Error::NoEntry) => return Ok(Vec::new()),
+            Err(e) => return Err(e).context("Failed to read the peer list from keyring"),
+        };
+        let mut peers: Vec<String> = index
+            .lines()
+            .filter(|peer| !peer.is_empty())
+            .map(str::to_string)
+            .collect();
+        peers.sort();
+        Ok(peers)
+    }
+
+    fn write_peer_index(&self, peers: &[String]) -> Result<()> {
+        if peers.is_empty() {
+            let _ = self.delete_credential(PEER_INDEX_KEY);
+            return Ok(());
+        }
+        self.set_credential(PEER_INDEX_KEY, &peers.join("\n"))
+    }
+}
+
+/// Returns the keyring key of the PSK used with `peer`.
+fn peer_psk_key(peer: &str) -> String {
+    format!("{}{}", PEER_PSK_PREFIX, peer)
 }
 
 impl Default for KeyringManager {
@@ -561,6 +684,48 @@ mod tests {
         std::env::remove_var("MULTISHIVA_PSK");
     }
 
+    #[test]
+    fn test_peer_psk_storage() {
+        let manager = KeyringManager::with_service("multishiva-test-peers".to_string());
+        let _ = manager.delete_psk_for("laptop");
+        let _ = manager.delete_psk_for("desktop");
+
+        if manager.set_psk_for("laptop", "laptop-psk").is_err()
+            || manager.get_psk_for("laptop").is_err()
+        {
+            // No usable keyring in this environment
+            let _ = manager.delete_psk_for("laptop");
+            return;
+        }
+        let global = manager.set_psk("global-psk");
+        manager.set_psk_for("desktop", "desktop-psk").unwrap();
+        manager.set_psk_for("laptop", "new-laptop-psk").unwrap();
+
+        assert_eq!(manager.list_peers().unwrap(), ["desktop", "laptop"]);
+        assert_eq!(manager.get_psk_for("laptop").unwrap(), "new-laptop-psk");
+        assert_eq!(manager.get_psk_for("desktop").unwrap(), "desktop-psk");
+
+        // Deleting a peer's PSK keeps the others and the global one
+        manager.delete_psk_for("laptop").unwrap();
+        assert!(manager.get_psk_for("laptop").is_err());
+        assert_eq!(manager.list_peers().unwrap(), ["desktop"]);
+        if global.is_ok() {
+            assert_eq!(manager.get_psk().unwrap(), "global-psk");
+            let _ = manager.delete_psk();
+        }
+
+        manager.delete_psk_for("desktop").unwrap();
+        assert!(manager.list_peers().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_peer_psk_key() {
+        assert_eq!(peer_psk_key("laptop"), "psk:laptop");
+        let manager = KeyringManager::with_service("multishiva-test-peers".to_string());
+        assert!(manager.set_psk_for("", "psk").is_err());
+        assert!(manager.set_psk_for("a\nb", "psk").is_err());
+    }
+
     #[test]
     fn test_constants() {
         assert_eq!(SERVICE_NAME, "multishiva");
@@ -0,0 +1,432 @@
+/// Reading back the log files for `multishiva logs`
+///
+/// Log files hold entries in the pretty format
+/// (`<timestamp>  INFO ThreadId(01) target: file:line: message`) or as JSON
+/// lines with `timestamp` and `level` fields. A message spanning several
+/// lines only has the timestamp on its first one, so [`LogParser`] groups the
+/// following lines with it and filters apply to whole entries.
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::core::logging::{get_log_files_in, LogLevel};
+
+/// How often a followed log file is checked for new lines.
+pub const FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// One log event, with the continuation lines of a multi-line message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// When the event was logged, if the first line starts with a timestamp
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Level of the event, if it could be read
+    pub level: Option<LogLevel>,
+    /// The lines of the entry, joined with newlines
+    pub text: String,
+}
+
+impl LogEntry {
+    /// Parses the first line of an entry, or returns `None` for a line that
+    /// continues the previous entry.
+    fn start(line: &str) -> Option<Self> {
+        let (timestamp, level) = if line.starts_with('{') {
+            let json: serde_json::Value = serde_json::from_str(line).ok()?;
+            let timestamp = json["timestamp"].as_str().and_then(parse_timestamp)?;
+            let level = json["level"].as_str().and_then(|l| l.parse().ok());
+            (timestamp, level)
+        } else {
+            let mut tokens = line.split_whitespace();
+            let timestamp = tokens.next().and_then(parse_timestamp)?;
+            let level = tokens.next().and_then(|l| l.parse().ok());
+            (timestamp, level)
+        };
+        Some(Self {
+            timestamp: Some(timestamp),
+            level,
+            text: line.to_string(),
+        })
+    }
+}
+
+fn parse_timestamp(token: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(token)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Groups log lines into entries.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::log_viewer::LogParser;
+/// use multishiva::core::logging::LogLevel;
+///
+/// let mut parser = LogParser::new();
+/// assert!(parser.push_line("2026-10-14T08:00:00.000000Z  WARN multishiva: Peer list:").is_none());
+/// assert!(parser.push_line("  agent1").is_none());
+///
+/// let entry = parser.push_line("2026-10-14T08:00:01.000000Z  INFO multishiva: Done").unwrap();
+/// assert_eq!(entry.level, Some(LogLevel::Warn));
+/// assert!(entry.text.ends_with("\n  agent1"));
+/// assert_eq!(parser.finish().unwrap().level, Some(LogLevel::Info));
+/// ```
+#[derive(Debug, Default)]
+pub struct LogParser {
+    current: Option<LogEntry>,
+}
+
+impl LogParser {
+    /// Creates a parser with no pending entry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a line, returning the previous entry once a new one starts.
+    ///
+    /// Lines before the first timestamped one form an entry without
+    /// timestamp or level.
+    pub fn push_line(&mut self, line: &str) -> Option<LogEntry> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if let Some(entry) = LogEntry::start(line) {
+            return self.current.replace(entry);
+        }
+        match self.current.as_mut() {
+            Some(entry) => {
+                entry.text.push('\n');
+                entry.text.push_str(line);
+            }
+            None => {
+                self.current = Some(LogEntry {
+                    timestamp: None,
+                    level: None,
+                    text: line.to_string(),
+                })
+            }
+        }
+        None
+    }
+
+    /// Returns the pending entry, if any.
+    pub fn finish(&mut self) -> Option<LogEntry> {
+        self.current.take()
+    }
+}
+
+/// Parses a whole log into entries.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::log_viewer::parse_log;
+///
+/// let log = "2026-10-14T08:00:00Z  INFO a: one\n2026-10-14T08:00:01Z ERROR a: two\n";
+/// assert_eq!(parse_log(log).len(), 2);
+/// ```
+pub fn parse_log(text: &str) -> Vec<LogEntry> {
+    let mut parser = LogParser::new();
+    let mut entries: Vec<LogEntry> = text.lines().filter_map(|l| parser.push_line(l)).collect();
+    entries.extend(parser.finish());
+    entries
+}
+
+/// Which entries `multishiva logs` prints.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Only entries at least this severe
+    pub level: Option<LogLevel>,
+    /// Only entries logged at or after this time
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl LogFilter {
+    /// Returns whether `entry` passes the filter.
+    ///
+    /// Entries without a level or timestamp only pass when the matching
+    /// criterion is not set.
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        let level_ok = match (self.level, entry.level) {
+            (None, _) => true,
+            (Some(min), Some(level)) => level <= min,
+            (Some(_), None) => false,
+        };
+        let since_ok = match (self.since, entry.timestamp) {
+            (None, _) => true,
+            (Some(since), Some(timestamp)) => timestamp >= since,
+            (Some(_), None) => false,
+        };
+        level_ok && since_ok
+    }
+}
+
+/// Parses a `--since` age such as `30s`, `15m`, `2h` or `7d`.
+///
+/// # Errors
+///
+/// Returns an error if the number or the unit is missing or invalid.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::log_viewer::parse_since;
+///
+/// assert_eq!(parse_since("2h").unwrap(), chrono::Duration::hours(2));
+/// assert!(parse_since("2 weeks").is_err());
+/// ```
+pub fn parse_since(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: i64 = number
+        .parse()
+        .with_context(|| format!("Invalid duration '{}': expected e.g. 30s, 15m, 2h or 7d", s))?;
+    match unit {
+        "s" => Ok(Duration::seconds(number)),
+        "m" => Ok(Duration::minutes(number)),
+        "h" => Ok(Duration::hours(number)),
+        "d" => Ok(Duration::days(number)),
+        _ => anyhow::bail!("Invalid duration unit in '{}': expected s, m, h or d", s),
+    }
+}
+
+/// Returns the most recently modified uncompressed log file in `log_dir`.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read.
+pub fn newest_log_file(log_dir: &Path) -> Result<Option<PathBuf>> {
+    let newest = get_log_files_in(log_dir)?
+        .into_iter()
+        .filter(|path| path.extension().is_none_or(|ext| ext != "gz"))
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((modified, path))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path);
+    Ok(newest)
+}
+
+/// Writes the last `count` entries of `reader` that pass `filter` to `out`.
+///
+/// # Errors
+///
+/// Returns an error if reading or writing fails.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::log_viewer::{print_tail, LogFilter};
+///
+/// let log = "2026-10-14T08:00:00Z  INFO a: one\n2026-10-14T08:00:01Z  INFO a: two\n";
+/// let mut out = Vec::new();
+/// print_tail(log.as_bytes(), &LogFilter::default(), 1, &mut out).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "2026-10-14T08:00:01Z  INFO a: two\n");
+/// ```
+pub fn print_tail<R: Read, W: Write>(
+    reader: R,
+    filter: &LogFilter,
+    count: usize,
+    out: &mut W,
+) -> Result<()> {
+    let mut parser = LogParser::new();
+    let mut tail = VecDeque::with_capacity(count.min(1024));
+    let mut keep = |entry: LogEntry| {
+        if count > 0 && filter.matches(&entry) {
+            if tail.len() == count {
+                tail.pop_front();
+            }
+            tail.push_back(entry);
+        }
+    };
+
+    for line in BufReader::new(reader).lines() {
+        let line = line.context("Failed to read log file")?;
+        if let Some(entry) = parser.push_line(&line) {
+            keep(entry);
+        }
+    }
+    if let Some(entry) = parser.finish() {
+        keep(entry);
+    }
+
+    for entry in tail {
+        writeln!(out, "{}", entry.text)?;
+    }
+    Ok(())
+}
+
+/// Prints new entries of the newest log file in `log_dir` as they are written.
+///
+/// Starts at the end of the current file and switches to the next one when
+/// the log rotates, e.g. to the new daily file at midnight. Runs until an
+/// error occurs.
+///
+/// # Errors
+///
+/// Returns an error if a log file cannot be read or `out` cannot be written.
+pub async fn follow<W: Write>(log_dir: &Path, filter: &LogFilter, out: &mut W) -> Result<()> {
+    let mut path = newest_log_file(log_dir)?;
+    let mut offset = match &path {
+        Some(path) => std::fs::metadata(path)?.len(),
+        None => 0,
+    };
+    let mut pending = String::new();
+    let mut parser = LogParser::new();
+
+    loop {
+        if let Some(current) = &path {
+            let len = std::fs::metadata(current).map(|m| m.len()).unwrap_or(0);
+            // The file was truncated or replaced: read it again from the start
+            if len < offset {
+                offset = 0;
+            }
+            if len > offset {
+                let mut file = File::open(current)
+                    .with_context(|| format!("Failed to open log file: {:?}", current))?;
+                file.seek(SeekFrom::Start(offset))?;
+                let mut chunk = Vec::new();
+                file.read_to_end(&mut chunk)?;
+                offset += chunk.len() as u64;
+                pending.push_str(&String::from_utf8_lossy(&chunk));
+
+                // Keep a trailing partial line for the next read
+                while let Some(end) = pending.find('\n') {
+                    let line: String = pending.drain(..=end).collect();
+                    if let Some(entry) = parser.push_line(&line) {
+                        print_entry(&entry, filter, out)?;
+                    }
+                }
+                // Writes hold whole events, so the last entry is complete
+                if let Some(entry) = parser.finish() {
+                    print_entry(&entry, filter, out)?;
+                }
+                out.flush()?;
+            }
+        }
+
+        let newest = newest_log_file(log_dir)?;
+        if newest.is_some() && newest != path {
+            path = newest;
+            offset = 0;
+            pending.clear();
+            continue;
+        }
+
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+    }
+}
+
+fn print_entry<W: Write>(entry: &LogEntry, filter: &LogFilter, out: &mut W) -> Result<()> {
+    if filter.matches(entry) {
+        writeln!(out, "{}", entry.text)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Captured from a host run, with a multi-line error and a JSON line
+    const SAMPLE: &str = "\
+2026-10-14T07:59:58.102345Z  INFO ThreadId(01) multishiva: src/main.rs:53: 🕉️  MultiShiva v1.2.0 starting...
+2026-10-14T08:00:03.551002Z  WARN ThreadId(04) multishiva: src/main.rs:893: Agent 'laptop' is not connected, staying on host
+2026-10-14T08:00:05.000114Z ERROR ThreadId(04) multishiva::core::network: src/core/network.rs:412: Connection failed: handshake
+Caused by:
+    0: PSK mismatch
+2026-10-14T08:00:06.250000Z DEBUG ThreadId(04) multishiva: src/main.rs:860: No neighbor configured on top edge
+{\"timestamp\":\"2026-10-14T08:00:07.000000Z\",\"level\":\"ERROR\",\"message\":\"Heartbeat timeout\",\"agent\":\"laptop\"}
+";
+
+    #[test]
+    fn test_parse_groups_multi_line_messages() {
+        let entries = parse_log(SAMPLE);
+        assert_eq!(entries.len(), 5);
+
+        let levels: Vec<_> = entries.iter().map(|e| e.level).collect();
+        assert_eq!(
+            levels,
+            vec![
+                Some(LogLevel::Info),
+                Some(LogLevel::Warn),
+                Some(LogLevel::Error),
+                Some(LogLevel::Debug),
+                Some(LogLevel::Error),
+            ]
+        );
+        assert!(entries[2].text.ends_with("Caused by:\n    0: PSK mismatch"));
+        assert_eq!(
+            entries[4].timestamp,
+            Some("2026-10-14T08:00:07Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_filter_by_level_and_since() {
+        let entries = parse_log(SAMPLE);
+        let warnings = LogFilter {
+            level: Some(LogLevel::Warn),
+            since: None,
+        };
+        assert_eq!(entries.iter().filter(|e| warnings.matches(e)).count(), 3);
+
+        let recent = LogFilter {
+            level: None,
+            since: Some("2026-10-14T08:00:05Z".parse().unwrap()),
+        };
+        assert_eq!(entries.iter().filter(|e| recent.matches(e)).count(), 3);
+
+        // Lines without a timestamp are dropped once a time filter is set
+        let orphan = parse_log("   continued\n");
+        assert!(LogFilter::default().matches(&orphan[0]));
+        assert!(!recent.matches(&orphan[0]));
+    }
+
+    #[test]
+    fn test_print_tail_keeps_last_matching_entries() {
+        let filter = LogFilter {
+            level: Some(LogLevel::Error),
+            since: None,
+        };
+        let mut out = Vec::new();
+        print_tail(SAMPLE.as_bytes(), &filter, 1, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("{\"timestamp\""));
+        assert_eq!(out.lines().count(), 1);
+
+        let mut out = Vec::new();
+        print_tail(SAMPLE.as_bytes(), &filter, 100, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 4);
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("30s").unwrap(), Duration::seconds(30));
+        assert_eq!(parse_since("15m").unwrap(), Duration::minutes(15));
+        assert_eq!(parse_since("7d").unwrap(), Duration::days(7));
+        assert!(parse_since("h").is_err());
+        assert!(parse_since("2").is_err());
+        assert!(parse_since("2y").is_err());
+    }
+
+    #[test]
+    fn test_newest_log_file_includes_daily_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("multishiva.log.2026-10-13");
+        let new = dir.path().join("multishiva.log.2026-10-14");
+        std::fs::write(&old, "old\n").unwrap();
+        std::fs::write(&new, "new\n").unwrap();
+        File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(3600))
+            .unwrap();
+
+        assert_eq!(newest_log_file(dir.path()).unwrap(), Some(new));
+    }
+}
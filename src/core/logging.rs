@@ -1,14 +1,20 @@
 /// Logging system with rotation and filtering
 ///
 /// Provides structured logging with:
-/// - File rotation (daily)
+/// - File rotation (daily, or by size with optional gzip compression)
 /// - Console output
 /// - Module filtering
 /// - Multiple log levels
+/// - Human-readable or JSON-lines output
 use anyhow::{Context, Result};
-use std::path::PathBuf;
-use tracing::Level;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{Level, Subscriber};
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 
 /// Log level configuration for the logging system.
@@ -18,13 +24,17 @@ use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, Env
 ///
 /// # Examples
 ///
+/// Levels are ordered from most to least severe, so `Error < Warn`.
+///
 /// ```
 /// use multishiva::core::logging::LogLevel;
 ///
 /// let level = LogLevel::Info;
 /// println!("Current log level: {}", level);
+/// assert!(LogLevel::Error < LogLevel::Warn);
+/// assert_eq!("WARN".parse::<LogLevel>().unwrap(), LogLevel::Warn);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel {
     /// Error level - only critical errors are logged
     Error,
@@ -62,6 +72,76 @@ impl std::fmt::Display for LogLevel {
     }
 }
 
+impl std::str::FromStr for LogLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            other => anyhow::bail!(
+                "Unknown log level '{}' (expected error, warn, info, debug or trace)",
+                other
+            ),
+        }
+    }
+}
+
+/// Output format of log lines.
+///
+/// `Pretty` is the human-readable format. `Json` writes JSON Lines: one
+/// object per line with `timestamp`, `level`, `target`, `message` and the
+/// event's other fields at the top level, plus the current span under `span`
+/// and all entered spans under `spans`. Suitable for `jq` and log aggregators.
+///
+/// `text` is accepted for `Pretty`, and `jsonl` or `json-lines` for `Json`.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::logging::LogFormat;
+///
+/// let format: LogFormat = "json".parse().unwrap();
+/// assert_eq!(format, LogFormat::Json);
+/// assert_eq!("jsonl".parse::<LogFormat>().unwrap(), LogFormat::Json);
+/// assert_eq!(LogFormat::default(), LogFormat::Pretty);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable text lines
+    #[default]
+    Pretty,
+    /// One JSON object per line
+    Json,
+}
+
+impl std::fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogFormat::Pretty => write!(f, "pretty"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "pretty" | "text" => Ok(LogFormat::Pretty),
+            "json" | "jsonl" | "json-lines" => Ok(LogFormat::Json),
+            other => anyhow::bail!(
+                "Unknown log format '{}' (expected 'pretty' or 'json')",
+                other
+            ),
+        }
+    }
+}
+
 /// Configuration for the logging system.
 ///
 /// Controls logging behavior including output destinations (file and/or console),
@@ -70,7 +150,7 @@ impl std::fmt::Display for LogLevel {
 /// # Examples
 ///
 /// ```
-/// use multishiva::core::logging::{LogConfig, LogLevel};
+/// use multishiva::core::logging::{LogConfig, LogFormat, LogLevel};
 /// use std::path::PathBuf;
 ///
 /// let config = LogConfig {
@@ -79,6 +159,9 @@ impl std::fmt::Display for LogLevel {
 ///     enable_console: true,
 ///     log_dir: Some(PathBuf::from("/var/log/myapp")),
 ///     filter: Some("multishiva=debug,tokio=warn".to_string()),
+///     format: LogFormat::Json,
+///     max_file_bytes: Some(10 * 1024 * 1024),
+///     compress_rotated: true,
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -93,6 +176,13 @@ pub struct LogConfig {
     pub log_dir: Option<PathBuf>,
     /// Module-specific filters (e.g., "multishiva=debug,tokio=warn")
     pub filter: Option<String>,
+    /// Format of the console and file output
+    pub format: LogFormat,
+    /// Start a new log file once the current one reaches this size, instead
+    /// of rotating daily
+    pub max_file_bytes: Option<u64>,
+    /// Gzip log files rotated by size once they are closed
+    pub compress_rotated: bool,
 }
 
 impl Default for LogConfig {
@@ -103,6 +193,9 @@ impl Default for LogConfig {
             enable_console: true,
             log_dir: None,
             filter: None,
+            format: LogFormat::Pretty,
+            max_file_bytes: None,
+            compress_rotated: false,
         }
     }
 }
@@ -157,32 +250,23 @@ pub fn init_logging(config: LogConfig) -> Result<()> {
 
     // Console layer
     if config.enable_console {
-        let console_layer = fmt::layer()
-            .with_writer(std::io::stdout)
-            .with_ansi(true)
-            .with_target(true)
-            .with_level(true)
-            .with_thread_ids(false)
-            .with_file(false)
-            .with_line_number(false)
-            .boxed();
-        layers.push(console_layer);
-    }
-
-    // File layer with daily rotation
-    if config.enable_file {
-        let file_appender = RollingFileAppender::new(Rotation::DAILY, &log_dir, "multishiva.log");
+        layers.push(console_layer(config.format));
+    }
 
-        let file_layer = fmt::layer()
-            .with_writer(file_appender)
-            .with_ansi(false)
-            .with_target(true)
-            .with_level(true)
-            .with_thread_ids(true)
-            .with_file(true)
-            .with_line_number(true)
-            .boxed();
-        layers.push(file_layer);
+    // File layer with size-based or daily rotation
+    if config.enable_file {
+        if let Some(max_bytes) = config.max_file_bytes {
+            let writer = SizeRotatingWriter::new(
+                &log_dir,
+                LOG_FILE_NAME,
+                max_bytes,
+                config.compress_rotated,
+            )?;
+            layers.push(file_layer(config.format, move || writer.clone()));
+        } else {
+            let file_appender = RollingFileAppender::new(Rotation::DAILY, &log_dir, LOG_FILE_NAME);
+            layers.push(file_layer(config.format, file_appender));
+        }
     }
 
     // Initialize subscriber
@@ -199,6 +283,218 @@ pub fn init_logging(config: LogConfig) -> Result<()> {
     Ok(())
 }
 
+/// Name of the active log file in the log directory.
+pub const LOG_FILE_NAME: &str = "multishiva.log";
+
+/// Log file writer that rotates once the file reaches a size limit.
+///
+/// Writes go to `<dir>/<file_name>`. When a write would grow the file past
+/// `max_bytes`, the file is renamed to `<stem>.<timestamp>.log` and a new one
+/// is started, so every log line stays whole. With compression enabled the
+/// rotated file is gzipped to `<stem>.<timestamp>.log.gz` in the background.
+///
+/// The writer is cheap to clone; clones share the same file.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::logging::SizeRotatingWriter;
+/// use std::io::Write;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let mut writer = SizeRotatingWriter::new(dir.path(), "app.log", 16, false).unwrap();
+/// writer.write_all(b"first line 0123\n").unwrap();
+/// writer.write_all(b"second line\n").unwrap();
+///
+/// assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+/// ```
+#[derive(Clone)]
+pub struct SizeRotatingWriter {
+    state: Arc<Mutex<RotatingState>>,
+}
+
+struct RotatingState {
+    dir: PathBuf,
+    file_name: String,
+    max_bytes: u64,
+    compress: bool,
+    file: File,
+    written: u64,
+}
+
+impl SizeRotatingWriter {
+    /// Opens `<dir>/<file_name>` for appending, creating `dir` if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or the file cannot
+    /// be opened.
+    pub fn new<P: AsRef<Path>>(
+        dir: P,
+        file_name: &str,
+        max_bytes: u64,
+        compress: bool,
+    ) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create log directory: {:?}", dir))?;
+        let file = open_append(&dir.join(file_name))?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Ok(Self {
+            state: Arc::new(Mutex::new(RotatingState {
+                dir,
+                file_name: file_name.to_string(),
+                max_bytes,
+                compress,
+                file,
+                written,
+            })),
+        })
+    }
+}
+
+impl RotatingState {
+    /// Moves the current file aside and starts a new one.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+
+        let active = self.dir.join(&self.file_name);
+        let stem = Path::new(&self.file_name)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.file_name.clone());
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S%.3f");
+        let mut rotated = self.dir.join(format!("{}.{}.log", stem, timestamp));
+        let mut n = 1;
+        while rotated.exists() || rotated.with_extension("log.gz").exists() {
+            rotated = self.dir.join(format!("{}.{}-{}.log", stem, timestamp, n));
+            n += 1;
+        }
+
+        std::fs::rename(&active, &rotated)?;
+        self.file = open_append(&active).map_err(std::io::Error::other)?;
+        self.written = 0;
+
+        if self.compress {
+            std::thread::spawn(move || {
+                if let Err(e) = compress_file(&rotated) {
+                    eprintln!("Failed to compress rotated log {:?}: {}", rotated, e);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Write for SizeRotatingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| std::io::Error::other("log writer poisoned"))?;
+        if state.written > 0 && state.written + buf.len() as u64 > state.max_bytes {
+            state.rotate()?;
+        }
+        let n = state.file.write(buf)?;
+        state.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.state
+            .lock()
+            .map_err(|_| std::io::Error::other("log writer poisoned"))?
+            .file
+            .flush()
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log file: {:?}", path))
+}
+
+/// Gzips `path` to `<path>.gz` and removes the original.
+///
+/// Returns the path of the compressed file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, or the archive cannot be
+/// written. The original is kept in that case.
+pub fn compress_file(path: &Path) -> Result<PathBuf> {
+    let mut gz_name = path.as_os_str().to_owned();
+    gz_name.push(".gz");
+    let gz_path = PathBuf::from(gz_name);
+
+    let mut input =
+        File::open(path).with_context(|| format!("Failed to open log file: {:?}", path))?;
+    let output = File::create(&gz_path)
+        .with_context(|| format!("Failed to create archive: {:?}", gz_path))?;
+    let mut encoder = flate2::write::GzEncoder::new(output, flate2::Compression::default());
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+
+    std::fs::remove_file(path)
+        .with_context(|| format!("Failed to remove compressed log file: {:?}", path))?;
+    Ok(gz_path)
+}
+
+/// Builds the stdout layer in the given format.
+fn console_layer<S>(format: LogFormat) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let layer = fmt::layer()
+        .with_writer(std::io::stdout)
+        .with_target(true)
+        .with_level(true)
+        .with_thread_ids(false)
+        .with_file(false)
+        .with_line_number(false);
+
+    match format {
+        LogFormat::Pretty => layer.with_ansi(true).boxed(),
+        LogFormat::Json => layer
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_ansi(false)
+            .boxed(),
+    }
+}
+
+/// Builds the file layer in the given format, writing to `writer`.
+fn file_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer()
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_target(true)
+        .with_level(true)
+        .with_thread_ids(true)
+        .with_file(true)
+        .with_line_number(true);
+
+    match format {
+        LogFormat::Pretty => layer.boxed(),
+        LogFormat::Json => layer
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+    }
+}
+
 /// Get the default log directory path.
 ///
 /// Returns the platform-specific data directory for multishiva logs.
@@ -228,8 +524,9 @@ pub fn get_default_log_dir() -> PathBuf {
 
 /// Get all log files in the log directory.
 ///
-/// Scans the default log directory and returns paths to all `.log` files,
-/// sorted alphabetically. Returns an empty vector if the directory doesn't exist.
+/// Scans the default log directory and returns paths to all `.log` files and
+/// compressed `.log.gz` archives, sorted alphabetically. Returns an empty
+/// vector if the directory doesn't exist.
 ///
 /// # Returns
 ///
@@ -252,19 +549,28 @@ pub fn get_default_log_dir() -> PathBuf {
 /// }
 /// ```
 pub fn get_log_files() -> Result<Vec<PathBuf>> {
-    let log_dir = get_default_log_dir();
+    get_log_files_in(&get_default_log_dir())
+}
 
+/// Get all log files in `log_dir`.
+///
+/// See [`get_log_files`].
+///
+/// # Errors
+///
+/// Returns an error if the directory or one of its entries cannot be read.
+pub fn get_log_files_in(log_dir: &Path) -> Result<Vec<PathBuf>> {
     if !log_dir.exists() {
         return Ok(Vec::new());
     }
 
     let mut files = Vec::new();
-    for entry in std::fs::read_dir(&log_dir)
+    for entry in std::fs::read_dir(log_dir)
         .with_context(|| format!("Failed to read log directory: {:?}", log_dir))?
     {
         let entry = entry.context("Failed to read directory entry")?;
         let path = entry.path();
-        if path.is_file() && path.extension().map(|e| e == "log").unwrap_or(false) {
+        if path.is_file() && is_log_file(&path) {
             files.push(path);
         }
     }
@@ -323,6 +629,77 @@ pub fn cleanup_old_logs(keep_count: usize) -> Result<()> {
     Ok(())
 }
 
+/// Delete the oldest log files until they take at most `max_total_bytes`.
+///
+/// Complements [`cleanup_old_logs`] with a size budget. Files are removed
+/// oldest first by modification time. The active log file is counted but
+/// never removed.
+///
+/// # Errors
+///
+/// Returns an error if log files or their metadata cannot be read, or a file
+/// cannot be deleted.
+///
+/// # Examples
+///
+/// ```no_run
+/// use multishiva::core::logging::cleanup_by_total_size;
+///
+/// // Keep at most 100 MiB of logs
+/// cleanup_by_total_size(100 * 1024 * 1024).expect("Failed to cleanup logs");
+/// ```
+pub fn cleanup_by_total_size(max_total_bytes: u64) -> Result<()> {
+    cleanup_by_total_size_in(&get_default_log_dir(), max_total_bytes)
+}
+
+/// Delete the oldest log files in `log_dir` until they take at most `max_total_bytes`.
+///
+/// See [`cleanup_by_total_size`].
+///
+/// # Errors
+///
+/// Returns an error if log files or their metadata cannot be read, or a file
+/// cannot be deleted.
+pub fn cleanup_by_total_size_in(log_dir: &Path, max_total_bytes: u64) -> Result<()> {
+    let mut files = Vec::new();
+    for path in get_log_files_in(log_dir)? {
+        let metadata = std::fs::metadata(&path)
+            .with_context(|| format!("Failed to read metadata: {:?}", path))?;
+        files.push((metadata.modified().ok(), metadata.len(), path));
+    }
+
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    files.sort_by_key(|(modified, _, _)| *modified);
+
+    for (_, len, path) in files {
+        if total <= max_total_bytes {
+            break;
+        }
+        if path.file_name().is_some_and(|name| name == LOG_FILE_NAME) {
+            continue;
+        }
+        tracing::info!("Deleting log file over size budget: {:?}", path);
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Failed to delete log file: {:?}", path))?;
+        total -= len;
+    }
+
+    Ok(())
+}
+
+/// Returns whether `path` is a log file or a compressed rotated log file.
+///
+/// Daily rotation names files `multishiva.log.<date>`, which count as well.
+fn is_log_file(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    name.ends_with(".log")
+        || name.ends_with(".log.gz")
+        || name.starts_with(&format!("{}.", LOG_FILE_NAME))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -354,6 +731,120 @@ mod tests {
         assert!(config.enable_console);
         assert!(config.log_dir.is_none());
         assert!(config.filter.is_none());
+        assert_eq!(config.format, LogFormat::Pretty);
+        assert!(config.max_file_bytes.is_none());
+        assert!(!config.compress_rotated);
+    }
+
+    #[test]
+    fn test_size_rotation_creates_multiple_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer =
+            SizeRotatingWriter::new(temp_dir.path(), LOG_FILE_NAME, 100, false).unwrap();
+
+        let line = "x".repeat(39) + "\n";
+        for _ in 0..10 {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        // Two 40-byte lines fit in 100 bytes, so 10 lines need 5 files
+        let files = get_log_files_in(temp_dir.path()).unwrap();
+        assert_eq!(files.len(), 5);
+        for file in &files {
+            let contents = std::fs::read_to_string(file).unwrap();
+            assert!(contents.len() <= 100);
+            assert!(contents.lines().all(|l| l.len() == 39));
+        }
+        assert!(temp_dir.path().join(LOG_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_compress_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("multishiva.20260101-000000.000.log");
+        std::fs::write(&path, "test log line\n".repeat(100)).unwrap();
+
+        let gz_path = compress_file(&path).unwrap();
+        assert!(!path.exists());
+        assert!(gz_path.to_string_lossy().ends_with(".log.gz"));
+        assert!(std::fs::metadata(&gz_path).unwrap().len() < 1400);
+
+        let mut decoded = String::new();
+        std::io::Read::read_to_string(
+            &mut flate2::read::GzDecoder::new(File::open(&gz_path).unwrap()),
+            &mut decoded,
+        )
+        .unwrap();
+        assert_eq!(decoded, "test log line\n".repeat(100));
+        assert_eq!(get_log_files_in(temp_dir.path()).unwrap(), vec![gz_path]);
+    }
+
+    #[test]
+    fn test_cleanup_by_total_size_removes_oldest_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let now = std::time::SystemTime::now();
+
+        // Four 100-byte files, oldest first, the last one being the active file
+        let names = ["a.log", "b.log.gz", "c.log", LOG_FILE_NAME];
+        for (age, name) in names.iter().enumerate() {
+            let path = dir.join(name);
+            std::fs::write(&path, [b'x'; 100]).unwrap();
+            let modified = now - std::time::Duration::from_secs(100 - age as u64 * 10);
+            File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+        std::fs::write(dir.join("audit.jsonl"), [b'x'; 500]).unwrap();
+
+        cleanup_by_total_size_in(dir, 250).unwrap();
+        assert!(!dir.join("a.log").exists());
+        assert!(!dir.join("b.log.gz").exists());
+        assert!(dir.join("c.log").exists());
+        assert!(dir.join(LOG_FILE_NAME).exists());
+        assert!(dir.join("audit.jsonl").exists());
+
+        // The active file is never removed, even over budget
+        cleanup_by_total_size_in(dir, 0).unwrap();
+        assert!(!dir.join("c.log").exists());
+        assert!(dir.join(LOG_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_log_format_from_str() {
+        assert_eq!("pretty".parse::<LogFormat>().unwrap(), LogFormat::Pretty);
+        assert_eq!("text".parse::<LogFormat>().unwrap(), LogFormat::Pretty);
+        assert_eq!("JSON".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("json-lines".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_json_file_layer_writes_json_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let appender = RollingFileAppender::new(Rotation::NEVER, temp_dir.path(), "test.log");
+        let subscriber = tracing_subscriber::registry().with(file_layer(LogFormat::Json, appender));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _connection = tracing::info_span!("connection", peer = "10.0.0.2").entered();
+            tracing::warn!(agent = "agent1", "Heartbeat timeout");
+        });
+
+        let contents = std::fs::read_to_string(temp_dir.path().join("test.log")).unwrap();
+        let line = contents.lines().next().expect("No log line written");
+        let json: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert!(json["timestamp"].is_string());
+        assert_eq!(json["level"], "WARN");
+        assert_eq!(json["target"], "multishiva::core::logging::tests");
+        assert_eq!(json["message"], "Heartbeat timeout");
+        assert_eq!(json["agent"], "agent1");
+        assert_eq!(json["span"]["name"], "connection");
+        assert_eq!(json["span"]["peer"], "10.0.0.2");
+        assert_eq!(json["spans"][0]["name"], "connection");
     }
 
     #[test]
@@ -410,6 +901,9 @@ mod tests {
             enable_console: true,
             log_dir: None,
             filter: None,
+            format: LogFormat::Pretty,
+            max_file_bytes: None,
+            compress_rotated: false,
         };
 
         // This should not panic
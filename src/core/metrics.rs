@@ -0,0 +1,755 @@
+/// Runtime metrics for MultiShiva
+///
+/// This module records counters and gauges through the `metrics` facade and
+/// exposes them in Prometheus text format over a minimal HTTP endpoint.
+///
+/// Recorded metrics:
+/// - `events_sent_total{target}` - Events written to a peer
+/// - `events_received_total{source}` - Events read from a peer
+/// - `events_dropped_total{target}` - Mouse moves the host throttled instead of sending
+/// - `focus_transfers_total` - Focus handovers between machines
+/// - `clipboard_syncs_total` - Clipboard updates applied from a remote machine
+/// - `connection_errors_total` - Failed connections, handshakes and I/O errors
+/// - `connected_agents` - Agents currently connected to the host
+/// - `send_queue_depth` - Events waiting to be written to a peer
+/// - `heartbeat_rtt_seconds` - Round-trip time of heartbeats
+/// - `bytes_sent_total` / `bytes_received_total` - Frame bytes exchanged with peers
+/// - `injection_failures_total` - Events that could not be injected
+///
+/// Recording is a no-op until [`install`] has been called, so call sites can
+/// increment metrics unconditionally.
+///
+/// Independently of Prometheus, [`NetworkStats`] keeps the same figures for a
+/// single network, so they can be logged and shown by `--status` through a
+/// [`MetricsSnapshot`]. With the `metrics-json` feature, the endpoint also
+/// serves the snapshot of [`set_json_source`] as JSON at `/metrics.json`.
+use anyhow::{Context, Result};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "metrics-json")]
+use std::sync::{Arc, RwLock};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Counter of events sent, labelled by `target`
+pub const EVENTS_SENT_TOTAL: &str = "events_sent_total";
+
+/// Counter of events received, labelled by `source`
+pub const EVENTS_RECEIVED_TOTAL: &str = "events_received_total";
+
+/// Counter of mouse moves dropped by the host's throttle, labelled by `target`
+pub const EVENTS_DROPPED_TOTAL: &str = "events_dropped_total";
+
+/// Counter of focus transfers between machines
+pub const FOCUS_TRANSFERS_TOTAL: &str = "focus_transfers_total";
+
+/// Counter of clipboard updates received from remote machines
+pub const CLIPBOARD_SYNCS_TOTAL: &str = "clipboard_syncs_total";
+
+/// Counter of connection-level errors
+pub const CONNECTION_ERRORS_TOTAL: &str = "connection_errors_total";
+
+/// Gauge of agents currently connected to the host
+pub const CONNECTED_AGENTS: &str = "connected_agents";
+
+/// Gauge of events waiting in the network send queues
+pub const SEND_QUEUE_DEPTH: &str = "send_queue_depth";
+
+/// Gauge of the bulk events (clipboard) among them
+pub const BULK_QUEUE_DEPTH: &str = "bulk_queue_depth";
+
+/// Histogram of heartbeat round-trip times, in seconds
+pub const HEARTBEAT_RTT_SECONDS: &str = "heartbeat_rtt_seconds";
+
+/// Counter of frame bytes written to peers
+pub const BYTES_SENT_TOTAL: &str = "bytes_sent_total";
+
+/// Counter of frame bytes read from peers
+pub const BYTES_RECEIVED_TOTAL: &str = "bytes_received_total";
+
+/// Counter of remote events that could not be injected
+pub const INJECTION_FAILURES_TOTAL: &str = "injection_failures_total";
+
+/// Number of round-trip times the latency percentiles are computed over
+pub const LATENCY_WINDOW: usize = 64;
+
+/// Period the events-per-second rate is averaged over
+pub const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Builds the snapshot served at `/metrics.json`, called once per request.
+#[cfg(feature = "metrics-json")]
+pub type MetricsSource = Arc<dyn Fn() -> MetricsSnapshot + Send + Sync>;
+
+#[cfg(feature = "metrics-json")]
+static JSON_SOURCE: RwLock<Option<MetricsSource>> = RwLock::new(None);
+
+/// Sets what the endpoint serves at `/metrics.json`, which answers 404 while
+/// there is none.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::{metrics, network::Network};
+/// use std::sync::Arc;
+///
+/// let monitor = Network::new("psk".to_string()).monitor();
+/// metrics::set_json_source(Some(Arc::new(move || monitor.metrics_snapshot())));
+/// ```
+#[cfg(feature = "metrics-json")]
+pub fn set_json_source(source: Option<MetricsSource>) {
+    if let Ok(mut current) = JSON_SOURCE.write() {
+        *current = source;
+    }
+}
+
+/// Installs the Prometheus recorder as the global metrics recorder.
+///
+/// This function is idempotent: the first call installs the recorder and
+/// subsequent calls return the same handle.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::metrics;
+///
+/// let handle = metrics::install();
+/// metrics::record_focus_transfer();
+/// assert!(handle.render().contains("focus_transfers_total"));
+/// ```
+pub fn install() -> &'static PrometheusHandle {
+    HANDLE.get_or_init(|| {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        if metrics::set_global_recorder(recorder).is_err() {
+            tracing::warn!("A global metrics recorder is already installed");
+        }
+        describe();
+        handle
+    })
+}
+
+fn describe() {
+    metrics::describe_counter!(EVENTS_SENT_TOTAL, "Events sent to a peer machine");
+    metrics::describe_counter!(EVENTS_RECEIVED_TOTAL, "Events received from a peer machine");
+    metrics::describe_counter!(
+        EVENTS_DROPPED_TOTAL,
+        "Mouse moves dropped instead of being sent to a peer machine"
+    );
+    metrics::describe_counter!(FOCUS_TRANSFERS_TOTAL, "Focus transfers between machines");
+    metrics::describe_counter!(
+        CLIPBOARD_SYNCS_TOTAL,
+        "Clipboard updates applied from remote machines"
+    );
+    metrics::describe_counter!(CONNECTION_ERRORS_TOTAL, "Connection-level errors");
+    metrics::describe_gauge!(CONNECTED_AGENTS, "Agents currently connected to the host");
+    metrics::describe_gauge!(SEND_QUEUE_DEPTH, "Events waiting to be written to a peer");
+    metrics::describe_gauge!(
+        BULK_QUEUE_DEPTH,
+        "Bulk events (clipboard) waiting to be written to a peer"
+    );
+    metrics::describe_histogram!(
+        HEARTBEAT_RTT_SECONDS,
+        metrics::Unit::Seconds,
+        "Round-trip time of heartbeats"
+    );
+    metrics::describe_counter!(
+        BYTES_SENT_TOTAL,
+        metrics::Unit::Bytes,
+        "Frame bytes written to peers"
+    );
+    metrics::describe_counter!(
+        BYTES_RECEIVED_TOTAL,
+        metrics::Unit::Bytes,
+        "Frame bytes read from peers"
+    );
+    metrics::describe_counter!(
+        INJECTION_FAILURES_TOTAL,
+        "Events that could not be injected"
+    );
+}
+
+/// Renders all recorded metrics in Prometheus text format.
+///
+/// Installs the recorder first if needed.
+pub fn render() -> String {
+    install().render()
+}
+
+/// Records an event sent to `target`.
+pub fn record_event_sent(target: &str) {
+    metrics::counter!(EVENTS_SENT_TOTAL, "target" => target.to_string()).increment(1);
+}
+
+/// Records an event received from `source`.
+pub fn record_event_received(source: &str) {
+    metrics::counter!(EVENTS_RECEIVED_TOTAL, "source" => source.to_string()).increment(1);
+}
+
+/// Records a mouse move dropped instead of being sent to `target`.
+pub fn record_event_dropped(target: &str) {
+    metrics::counter!(EVENTS_DROPPED_TOTAL, "target" => target.to_string()).increment(1);
+}
+
+/// Records a focus transfer between machines.
+pub fn record_focus_transfer() {
+    metrics::counter!(FOCUS_TRANSFERS_TOTAL).increment(1);
+}
+
+/// Records a clipboard update applied from a remote machine.
+pub fn record_clipboard_sync() {
+    metrics::counter!(CLIPBOARD_SYNCS_TOTAL).increment(1);
+}
+
+/// Records a connection-level error.
+pub fn record_connection_error() {
+    metrics::counter!(CONNECTION_ERRORS_TOTAL).increment(1);
+}
+
+/// Sets the number of agents currently connected to the host.
+pub fn set_connected_agents(count: usize) {
+    metrics::gauge!(CONNECTED_AGENTS).set(count as f64);
+}
+
+/// Sets the number of events waiting in the network send queues.
+pub fn set_queue_depth(depth: usize) {
+    metrics::gauge!(SEND_QUEUE_DEPTH).set(depth as f64);
+}
+
+/// Sets the number of bulk events waiting in the network send queues.
+pub fn set_bulk_queue_depth(depth: usize) {
+    metrics::gauge!(BULK_QUEUE_DEPTH).set(depth as f64);
+}
+
+/// Round-trip times of the last heartbeats, for latency percentiles.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::metrics::LatencyWindow;
+/// use std::time::Duration;
+///
+/// let mut window = LatencyWindow::new(3);
+/// for ms in [10, 40, 20, 30] {
+///     window.push(Duration::from_millis(ms));
+/// }
+/// // The oldest sample (10ms) left the window
+/// assert_eq!(window.len(), 3);
+/// assert_eq!(window.percentile(50.0), Some(Duration::from_millis(30)));
+/// assert_eq!(window.max(), Some(Duration::from_millis(40)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LatencyWindow {
+    samples: VecDeque<Duration>,
+    capacity: usize,
+}
+
+impl Default for LatencyWindow {
+    fn default() -> Self {
+        Self::new(LATENCY_WINDOW)
+    }
+}
+
+impl LatencyWindow {
+    /// Keeps the last `capacity` samples (at least one).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Adds a sample, dropping the oldest one if the window is full.
+    pub fn push(&mut self, rtt: Duration) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(rtt);
+    }
+
+    /// Returns the number of samples in the window.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns `true` if no sample was recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Returns the `p`th percentile (0 to 100) of the samples, by nearest rank.
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    /// Returns the largest sample.
+    pub fn max(&self) -> Option<Duration> {
+        self.samples.iter().max().copied()
+    }
+}
+
+/// Rate of a growing counter, from samples of its total.
+///
+/// The rate is averaged from the oldest sample still covering the window to
+/// the newest one.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::metrics::RateWindow;
+/// use std::time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// let mut rate = RateWindow::new(Duration::from_secs(10));
+/// assert_eq!(rate.sample(start, 100), 0.0);
+/// assert_eq!(rate.sample(start + Duration::from_secs(2), 300), 100.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateWindow {
+    samples: VecDeque<(Instant, u64)>,
+    span: Duration,
+}
+
+impl Default for RateWindow {
+    fn default() -> Self {
+        Self::new(RATE_WINDOW)
+    }
+}
+
+impl RateWindow {
+    /// Averages over the last `span`.
+    pub fn new(span: Duration) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            span,
+        }
+    }
+
+    /// Records that the counter reached `total` at `at`, and returns the rate
+    /// per second over the window.
+    ///
+    /// A counter that went backwards restarts the window.
+    pub fn sample(&mut self, at: Instant, total: u64) -> f64 {
+        if self.samples.back().is_some_and(|&(_, last)| last > total) {
+            self.samples.clear();
+        }
+        self.samples.push_back((at, total));
+        // Keep one sample at or before the start of the window
+        while self.samples.len() > 1 && at.saturating_duration_since(self.samples[1].0) >= self.span
+        {
+            self.samples.pop_front();
+        }
+
+        let (since, first) = self.samples[0];
+        let elapsed = at.saturating_duration_since(since).as_secs_f64();
+        if elapsed > 0.0 {
+            (total - first) as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Counters of a network's traffic, shared by its connections.
+///
+/// Besides its own figures, every record also goes to the Prometheus metrics.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::metrics::NetworkStats;
+/// use std::time::Duration;
+///
+/// let stats = NetworkStats::default();
+/// stats.record_sent(128);
+/// stats.record_latency(Duration::from_millis(12));
+///
+/// let snapshot = stats.snapshot(0);
+/// assert_eq!(snapshot.bytes_sent, 128);
+/// assert_eq!(snapshot.latency_p50_ms, Some(12));
+/// ```
+#[derive(Debug)]
+pub struct NetworkStats {
+    started: Instant,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    events_received: AtomicU64,
+    injection_failures: AtomicU64,
+    latency: Mutex<LatencyWindow>,
+    rate: Mutex<RateWindow>,
+}
+
+impl Default for NetworkStats {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            events_received: AtomicU64::new(0),
+            injection_failures: AtomicU64::new(0),
+            latency: Mutex::new(LatencyWindow::default()),
+            rate: Mutex::new(RateWindow::default()),
+        }
+    }
+}
+
+impl NetworkStats {
+    /// Returns the milliseconds elapsed since the stats were created.
+    ///
+    /// Used as the heartbeat timestamp: the peer echoes it back unchanged, so
+    /// the clocks of the two machines never need to agree.
+    pub fn now_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    /// Records `bytes` written to a peer.
+    pub fn record_sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+        metrics::counter!(BYTES_SENT_TOTAL).increment(bytes as u64);
+    }
+
+    /// Records `bytes` read from a peer.
+    pub fn record_received(&self, bytes: usize) {
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        metrics::counter!(BYTES_RECEIVED_TOTAL).increment(bytes as u64);
+    }
+
+    /// Records an event read from a peer, heartbeats and pings aside.
+    pub fn record_event_received(&self) {
+        self.events_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records an event that could not be injected.
+    pub fn record_injection_failure(&self) {
+        self.injection_failures.fetch_add(1, Ordering::Relaxed);
+        metrics::counter!(INJECTION_FAILURES_TOTAL).increment(1);
+    }
+
+    /// Records the round-trip time of a heartbeat.
+    pub fn record_latency(&self, rtt: Duration) {
+        if let Ok(mut latency) = self.latency.lock() {
+            latency.push(rtt);
+        }
+        metrics::histogram!(HEARTBEAT_RTT_SECONDS).record(rtt.as_secs_f64());
+    }
+
+    /// Returns the current figures, with `events_forwarded` as the network's
+    /// count of events written to peers.
+    pub fn snapshot(&self, events_forwarded: u64) -> MetricsSnapshot {
+        let (latency_samples, p50, p95, max) = match self.latency.lock() {
+            Ok(latency) => (
+                latency.len(),
+                latency.percentile(50.0),
+                latency.percentile(95.0),
+                latency.max(),
+            ),
+            Err(_) => (0, None, None, None),
+        };
+        let events_per_sec = self
+            .rate
+            .lock()
+            .map(|mut rate| rate.sample(Instant::now(), events_forwarded))
+            .unwrap_or_default();
+        let ms = |d: Option<Duration>| d.map(|d| d.as_millis() as u64);
+
+        MetricsSnapshot {
+            latency_samples,
+            latency_p50_ms: ms(p50),
+            latency_p95_ms: ms(p95),
+            latency_max_ms: ms(max),
+            events_forwarded,
+            events_received: self.events_received.load(Ordering::Relaxed),
+            events_per_sec,
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            injection_failures: self.injection_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Figures of a network at one point in time, from [`NetworkStats::snapshot`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    /// Heartbeat round trips the latency figures are computed over
+    pub latency_samples: usize,
+    /// Median heartbeat round-trip time
+    pub latency_p50_ms: Option<u64>,
+    /// 95th percentile of the heartbeat round-trip time
+    pub latency_p95_ms: Option<u64>,
+    /// Slowest heartbeat round trip in the window
+    pub latency_max_ms: Option<u64>,
+    /// Events written to peers since the network was created
+    pub events_forwarded: u64,
+    /// Events read from peers, heartbeats and pings aside
+    #[serde(default)]
+    pub events_received: u64,
+    /// Events written to peers per second, over the last [`RATE_WINDOW`]
+    pub events_per_sec: f64,
+    /// Frame bytes written to peers
+    pub bytes_sent: u64,
+    /// Frame bytes read from peers
+    pub bytes_received: u64,
+    /// Events that could not be injected
+    pub injection_failures: u64,
+}
+
+/// What [`Network::metrics`](crate::core::network::Network::metrics) returns.
+pub type NetworkMetrics = MetricsSnapshot;
+
+impl std::fmt::Display for MetricsSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (
+            self.latency_p50_ms,
+            self.latency_p95_ms,
+            self.latency_max_ms,
+        ) {
+            (Some(p50), Some(p95), Some(max)) => write!(
+                f,
+                "latency p50 {}ms, p95 {}ms, max {}ms over {} heartbeats",
+                p50, p95, max, self.latency_samples
+            )?,
+            _ => write!(f, "latency unknown")?,
+        }
+        write!(
+            f,
+            "; {:.1} events/s, {} received; {} bytes sent, {} received; {} injection failures",
+            self.events_per_sec,
+            self.events_received,
+            self.bytes_sent,
+            self.bytes_received,
+            self.injection_failures
+        )
+    }
+}
+
+/// Starts a minimal HTTP server exposing `/metrics` in Prometheus text format.
+///
+/// Installs the recorder if needed, binds `addr` and serves requests until an
+/// accept error occurs. Any path other than `/metrics` receives a 404.
+///
+/// # Errors
+///
+/// Returns an error if the address cannot be bound or accepting fails.
+///
+/// # Examples
+///
+/// ```no_run
+/// use multishiva::core::metrics;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// tokio::spawn(metrics::serve_metrics("127.0.0.1:9100".parse()?));
+/// # Ok(())
+/// # }
+/// ```
+pub async fn serve_metrics(addr: SocketAddr) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind metrics endpoint on {}", addr))?;
+    tracing::info!("Metrics endpoint listening on http://{}/metrics", addr);
+    serve(listener).await
+}
+
+async fn serve(listener: TcpListener) -> Result<()> {
+    let handle = install();
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(async move {
+            if let Err(e) = handle_request(stream, handle).await {
+                tracing::debug!("Metrics request failed: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_request(mut stream: TcpStream, handle: &PrometheusHandle) -> Result<()> {
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let mut parts = request.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+
+    let not_found = ("404 Not Found", "text/plain", "Not Found\n".to_string());
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => ("200 OK", "text/plain; version=0.0.4", handle.render()),
+        #[cfg(feature = "metrics-json")]
+        ("GET", "/metrics.json") => {
+            let source = JSON_SOURCE.read().ok().and_then(|source| source.clone());
+            match source {
+                Some(source) => (
+                    "200 OK",
+                    "application/json",
+                    serde_json::to_string(&source())?,
+                ),
+                None => not_found,
+            }
+        }
+        _ => not_found,
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_serve_metrics_endpoint() {
+        install();
+        record_event_sent("agent1");
+        record_connection_error();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener));
+
+        let response = get(addr, "/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("events_sent_total{target=\"agent1\"}"));
+        assert!(response.contains("connection_errors_total"));
+
+        let response = get(addr, "/other").await;
+        assert!(response.starts_with("HTTP/1.1 404"));
+    }
+
+    #[cfg(feature = "metrics-json")]
+    #[tokio::test]
+    async fn test_serve_metrics_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener));
+
+        set_json_source(None);
+        assert!(get(addr, "/metrics.json").await.starts_with("HTTP/1.1 404"));
+
+        let stats = Arc::new(NetworkStats::default());
+        stats.record_sent(64);
+        stats.record_event_received();
+        let source = stats.clone();
+        set_json_source(Some(Arc::new(move || source.snapshot(3))));
+
+        let response = get(addr, "/metrics.json").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("Content-Type: application/json"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let snapshot: MetricsSnapshot = serde_json::from_str(body).unwrap();
+        assert_eq!(snapshot, stats.snapshot(3));
+        assert_eq!(snapshot.events_received, 1);
+        set_json_source(None);
+    }
+
+    #[test]
+    fn test_latency_window_percentiles() {
+        let mut window = LatencyWindow::new(100);
+        assert_eq!(window.percentile(50.0), None);
+        assert_eq!(window.max(), None);
+
+        // 1ms to 100ms, shuffled
+        for ms in (1..=100).map(|i| i * 37 % 101) {
+            window.push(Duration::from_millis(ms));
+        }
+        assert_eq!(window.len(), 100);
+        assert_eq!(window.percentile(50.0), Some(Duration::from_millis(50)));
+        assert_eq!(window.percentile(95.0), Some(Duration::from_millis(95)));
+        assert_eq!(window.percentile(100.0), Some(Duration::from_millis(100)));
+        assert_eq!(window.percentile(0.0), Some(Duration::from_millis(1)));
+        assert_eq!(window.max(), Some(Duration::from_millis(100)));
+
+        let mut single = LatencyWindow::new(4);
+        single.push(Duration::from_millis(7));
+        assert_eq!(single.percentile(95.0), Some(Duration::from_millis(7)));
+    }
+
+    #[test]
+    fn test_latency_window_slides() {
+        let mut window = LatencyWindow::new(4);
+        window.push(Duration::from_millis(500));
+        for _ in 0..4 {
+            window.push(Duration::from_millis(10));
+        }
+        // The spike left the window
+        assert_eq!(window.len(), 4);
+        assert_eq!(window.max(), Some(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_rate_window() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut rate = RateWindow::new(Duration::from_secs(10));
+
+        // A single sample has no rate
+        assert_eq!(rate.sample(at(0), 0), 0.0);
+        assert_eq!(rate.sample(at(5), 500), 100.0);
+        assert_eq!(rate.sample(at(10), 1000), 100.0);
+        // The sample at 0s is superseded by the one at 5s
+        assert_eq!(rate.sample(at(15), 1000), 50.0);
+        assert_eq!(rate.sample(at(40), 1000), 0.0);
+
+        // A counter reset starts over
+        assert_eq!(rate.sample(at(41), 10), 0.0);
+        assert_eq!(rate.sample(at(42), 30), 20.0);
+    }
+
+    #[test]
+    fn test_network_stats_snapshot() {
+        let stats = NetworkStats::default();
+        assert_eq!(stats.snapshot(0).latency_p50_ms, None);
+        assert!(stats.snapshot(0).to_string().starts_with("latency unknown"));
+
+        stats.record_sent(10);
+        stats.record_sent(5);
+        stats.record_received(7);
+        stats.record_event_received();
+        stats.record_injection_failure();
+        for ms in [3, 1, 2] {
+            stats.record_latency(Duration::from_millis(ms));
+        }
+
+        let snapshot = stats.snapshot(42);
+        assert_eq!(snapshot.bytes_sent, 15);
+        assert_eq!(snapshot.bytes_received, 7);
+        assert_eq!(snapshot.injection_failures, 1);
+        assert_eq!(snapshot.events_forwarded, 42);
+        assert_eq!(snapshot.events_received, 1);
+        assert_eq!(snapshot.latency_samples, 3);
+        assert_eq!(snapshot.latency_p50_ms, Some(2));
+        assert_eq!(snapshot.latency_max_ms, Some(3));
+        assert!(snapshot
+            .to_string()
+            .starts_with("latency p50 2ms, p95 3ms, max 3ms over 3 heartbeats"));
+    }
+}
@@ -1,12 +1,30 @@
+/// Tamper-evident audit log of focus transfers, PSK changes and connections
+pub mod audit;
+
 /// Clipboard synchronization across machines
 pub mod clipboard;
 
+/// Clipboard size limits and chunked transfer of large content
+pub mod clipboard_transfer;
+
 /// Configuration management with persistence and validation
 pub mod config;
 
+/// Background mode: PID file, signals and systemd notifications
+pub mod daemon;
+
 /// mDNS-based auto-discovery of MultiShiva instances
 pub mod discovery;
 
+/// Environment checks for `multishiva doctor`
+pub mod doctor;
+
+/// Friction-aware edge crossing decisions
+pub mod edge_crossing;
+
+/// Recording and replay of input sessions
+pub mod event_recorder;
+
 /// Input event types and handling
 pub mod events;
 
@@ -16,25 +34,54 @@ pub mod fingerprint;
 /// Focus management across multiple machines
 pub mod focus;
 
+/// Parsing of hotkey strings from the configuration
+pub mod hotkey_parser;
+
 /// Input capture and injection (keyboard/mouse)
 pub mod input;
 
+/// Choice of the Linux input injection backend
+pub mod input_backend;
+
 /// Linux-specific input handling via evdev (Wayland/X11 support)
 #[cfg(target_os = "linux")]
 pub mod input_evdev;
 
+/// Injection through kernel virtual devices on `/dev/uinput`
+#[cfg(target_os = "linux")]
+pub mod input_uinput;
+
+/// Injection through the Wayland virtual pointer and keyboard protocols
+#[cfg(target_os = "linux")]
+pub mod input_wayland;
+
+/// Local control endpoint for querying a running daemon
+pub mod ipc;
+
 /// Secure credential storage using system keyring
 pub mod keyring;
 
+/// Reading and filtering of log files for `multishiva logs`
+pub mod log_viewer;
+
 /// Structured logging with rotation
 pub mod logging;
 
+/// Prometheus metrics for events, focus and connections
+pub mod metrics;
+
 /// TLS-encrypted network communication
 pub mod network;
 
+/// Pairing an agent to a host with a one-time code
+pub mod pairing;
+
 /// System permission checks and requirements
 pub mod permissions;
 
+/// Scripted scenarios replayed in simulation mode
+pub mod scenario;
+
 /// Simulation mode for testing without hardware
 pub mod simulation;
 
@@ -1,23 +1,1006 @@
 use anyhow::{Context, Result};
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr, SocketAddrV6};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedWriteHalf;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::sync::{broadcast, mpsc, watch, Mutex, RwLock};
 use tokio::time::{sleep, Duration};
 
 use crate::core::events::Event;
 use crate::core::fingerprint::{Fingerprint, FingerprintStore, FingerprintVerification};
+use crate::core::metrics::{self, MetricsSnapshot, NetworkMetrics, NetworkStats};
 
-/// Interval between heartbeat messages sent to maintain connection liveness.
+type HmacSha256 = hmac::Hmac<sha2::Sha256>;
+
+/// Default interval between heartbeat messages sent to maintain connection liveness.
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 
-/// Maximum time to wait when establishing a TCP connection before timing out.
+/// Maximum silence tolerated from a peer before the connection is considered dead.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Reason forwarded to the host when the watchdog drops a silent agent.
+const AGENT_TIMEOUT_REASON: &str = "timed out";
+
+/// Interval between the pings measuring each agent's latency.
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Round trip to an agent above which a warning is logged.
+const LATENCY_WARNING: Duration = Duration::from_millis(50);
+
+/// Default maximum time to wait when establishing a TCP connection before timing out.
 const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Default number of events each send queue holds.
+const CHANNEL_CAPACITY: usize = 100;
+
+/// Default number of bulk events (clipboard) each send queue holds.
+const BULK_CHANNEL_CAPACITY: usize = 16;
+
+/// Longest [`Network::stop`] waits for the connections to flush their queues.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// Magic bytes used to identify and validate PSK handshake protocol version.
 const PSK_MAGIC: &[u8] = b"MULTISHIVA_PSK_V1";
 
+/// Number of connection events buffered for slow subscribers.
+const CONNECTION_EVENT_CAPACITY: usize = 64;
+
+/// Default number of connections one address may open at once.
+pub const DEFAULT_CONNECTION_BURST: u32 = 10;
+
+/// Default rate at which an address may open more connections after a burst.
+pub const DEFAULT_CONNECTIONS_PER_MINUTE: u32 = 60;
+
+/// Addresses the rate limiter tracks before forgetting those back to a full bucket.
+const RATE_LIMITER_PRUNE_THRESHOLD: usize = 1024;
+
+/// Default number of events a second the host sends before dropping mouse moves.
+pub const DEFAULT_MAX_EVENTS_PER_SECOND: u32 = 2000;
+
+/// Shortest time between two warnings about dropped mouse moves.
+const THROTTLE_WARNING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Connection lifecycle event, observable through [`Network::subscribe`].
+///
+/// On a host, `name` is the agent's announced name. On an agent, the only
+/// peer is the host and `name` is the address it connected to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// A peer completed the handshake and is ready to exchange events.
+    AgentConnected {
+        /// Name of the peer
+        name: String,
+    },
+
+    /// The connection to a peer ended.
+    ///
+    /// Preceded by [`ConnectionEvent::PeerLeft`] when the peer closed it
+    /// cleanly; otherwise the connection dropped or timed out.
+    AgentDisconnected {
+        /// Name of the peer
+        name: String,
+    },
+
+    /// The peer announced it was leaving with [`Event::Disconnect`].
+    PeerLeft {
+        /// Name of the peer
+        name: String,
+        /// Reason given by the peer
+        reason: String,
+    },
+
+    /// Nothing was received from a peer within the heartbeat timeout.
+    HeartbeatTimeout {
+        /// Name of the peer
+        name: String,
+    },
+
+    /// The host's fingerprint differs from the stored one; the connection was refused.
+    FingerprintMismatch {
+        /// Name of the peer
+        name: String,
+    },
+
+    /// The host refused this agent, e.g. because its name is already connected.
+    Refused {
+        /// Name of the peer
+        name: String,
+        /// Reason given by the host
+        reason: String,
+    },
+}
+
+/// State of an agent's connection to its host.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::{ConnectionStatus, Network};
+///
+/// let network = Network::new("psk".to_string());
+/// assert_eq!(network.connection_status(), ConnectionStatus::Disconnected);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    /// Connected and the host was last heard from at `last_heartbeat`.
+    Connected {
+        /// When the last frame (heartbeat or event) was received from the host.
+        last_heartbeat: Instant,
+    },
+
+    /// Not connected, or the host stopped responding.
+    Disconnected,
+
+    /// A connection attempt is in progress.
+    Connecting,
+}
+
+/// State of an agent connected to the host, as tracked by the host.
+///
+/// Agents are registered when they send [`Event::Hello`] after the PSK
+/// handshake and removed when their connection ends. A second agent
+/// announcing a name that is already registered is refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgentState {
+    /// Whether this agent currently receives the host's input.
+    pub has_focus: bool,
+    /// When the last frame (heartbeat or event) was received from the agent.
+    pub last_seen: Instant,
+    /// Screen size announced by the agent, as (width, height).
+    pub screen_size: (u32, u32),
+    /// Address the agent connected from, which identifies its connection.
+    pub address: SocketAddr,
+    /// When the agent registered.
+    pub connected_at: Instant,
+    /// Round trip of the last [`Event::Ping`] the agent answered.
+    pub latency: Option<Duration>,
+    /// Events the host wrote to the agent.
+    pub events_sent: u64,
+    /// Mouse moves the host dropped instead of sending, see
+    /// [`NetworkConfig::max_events_per_second`].
+    pub events_dropped: u64,
+    /// Bytes of the events the host wrote to the agent, framing included.
+    pub bytes_sent: u64,
+}
+
+/// A host that accepted the PSK handshake of [`test_connection`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionCheck {
+    /// Address that was tested
+    pub address: String,
+    /// Milliseconds from opening the connection to the host's acknowledgment
+    pub handshake_ms: u64,
+}
+
+/// A connected agent, as listed by [`Network::connected_peers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerSummary {
+    /// Name announced by the agent
+    pub name: String,
+    /// Address the agent connected from
+    pub address: SocketAddr,
+    /// How long the agent has been connected
+    pub connected_for: Duration,
+    /// How long since the last frame was received from the agent
+    pub last_seen: Duration,
+    /// Whether the agent currently receives the host's input
+    pub has_focus: bool,
+    /// Round trip of the agent's last answered ping
+    pub latency: Option<Duration>,
+    /// Events sent to the agent
+    pub events_sent: u64,
+    /// Mouse moves dropped instead of being sent to the agent
+    pub events_dropped: u64,
+    /// Bytes of the events sent to the agent
+    pub bytes_sent: u64,
+}
+
+/// Reason sent to an agent whose name is already connected.
+const DUPLICATE_NAME_REASON: &str = "name already connected, set self_name uniquely";
+
+/// Reason sent to an agent missing from the host's allowlist.
+const NOT_ALLOWED_REASON: &str = "not in the host's allowed_agents";
+
+/// Reason sent to an agent announcing the host's own name.
+const SELF_CONNECTION_REASON: &str = "same name as the host, is it connecting to itself?";
+
+/// Agents connected to a host, keyed by the name announced in [`Event::Hello`].
+type AgentRegistry = Arc<std::sync::Mutex<HashMap<String, AgentState>>>;
+
+/// Write halves of the host's client connections, keyed by agent name, with
+/// the address identifying each connection.
+type ClientWriters = Arc<std::sync::Mutex<HashMap<String, (SocketAddr, SharedWriter)>>>;
+
+/// Write half of a connection, shared by the tasks writing to it.
+type SharedWriter = Arc<Mutex<OwnedWriteHalf>>;
+
+/// Where the host listens for agents.
+///
+/// Parsed from a literal IP address (`192.168.1.10`, `::1`) or from
+/// `iface:NAME`, which stands for the addresses of that network interface.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::BindAddress;
+/// use std::net::{IpAddr, Ipv4Addr};
+///
+/// let bind: BindAddress = "127.0.0.1".parse().unwrap();
+/// assert_eq!(bind, BindAddress::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+///
+/// let bind: BindAddress = "iface:eth0".parse().unwrap();
+/// assert_eq!(bind, BindAddress::Interface("eth0".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindAddress {
+    /// A single local address
+    Ip(IpAddr),
+    /// Every address of the named interface
+    Interface(String),
+}
+
+impl BindAddress {
+    /// Returns the local addresses this bind address stands for.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the interfaces cannot be listed, or if the named
+    /// interface does not exist or has no address.
+    pub fn resolve(&self) -> Result<Vec<IpAddr>> {
+        match self {
+            BindAddress::Ip(ip) => Ok(vec![*ip]),
+            BindAddress::Interface(name) => {
+                let addresses: Vec<IpAddr> = if_addrs::get_if_addrs()
+                    .context("Failed to list network interfaces")?
+                    .into_iter()
+                    .filter(|interface| interface.name == *name)
+                    .map(|interface| interface.ip())
+                    .collect();
+                if addresses.is_empty() {
+                    anyhow::bail!("Network interface {} not found or has no address", name);
+                }
+                Ok(addresses)
+            }
+        }
+    }
+
+    /// Returns the socket addresses to listen on for `port`.
+    ///
+    /// IPv6 link-local addresses get the scope of their interface, without
+    /// which they cannot be bound.
+    ///
+    /// # Errors
+    ///
+    /// See [`BindAddress::resolve`].
+    pub fn socket_addrs(&self, port: u16) -> Result<Vec<SocketAddr>> {
+        let scope_id = match self {
+            BindAddress::Interface(name) => if_addrs::get_if_addrs()
+                .ok()
+                .and_then(|interfaces| {
+                    interfaces
+                        .into_iter()
+                        .find(|interface| interface.name == *name)
+                })
+                .and_then(|interface| interface.index)
+                .unwrap_or(0),
+            BindAddress::Ip(_) => 0,
+        };
+        Ok(self
+            .resolve()?
+            .into_iter()
+            .map(|ip| match ip {
+                IpAddr::V6(v6) if (v6.segments()[0] & 0xffc0) == 0xfe80 => {
+                    SocketAddr::V6(SocketAddrV6::new(v6, port, 0, scope_id))
+                }
+                ip => SocketAddr::new(ip, port),
+            })
+            .collect())
+    }
+}
+
+impl FromStr for BindAddress {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(name) = s.strip_prefix("iface:") {
+            if name.is_empty() {
+                anyhow::bail!("Missing interface name in bind address: {}", s);
+            }
+            return Ok(BindAddress::Interface(name.to_string()));
+        }
+        s.parse().map(BindAddress::Ip).map_err(|_| {
+            anyhow::anyhow!(
+                "Invalid bind address: {} (expected an IP address or iface:NAME)",
+                s
+            )
+        })
+    }
+}
+
+impl std::fmt::Display for BindAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindAddress::Ip(ip) => write!(f, "{}", ip),
+            BindAddress::Interface(name) => write!(f, "iface:{}", name),
+        }
+    }
+}
+
+/// The agents a host lets in, by name, IP address or network.
+///
+/// Entries are machine names, IP addresses (`192.168.1.20`) or networks in
+/// CIDR notation (`192.168.1.0/24`, `fd00::/8`). Addresses are checked when a
+/// connection is accepted, names once the agent has authenticated: a name
+/// matches the hostname the agent authenticates with or the `self_name` it
+/// announces.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::AgentAllowlist;
+///
+/// let allowlist = AgentAllowlist::new(&["laptop", "192.168.1.0/24"]).unwrap();
+/// assert!(allowlist.allows_name("laptop"));
+/// assert!(!allowlist.allows_name("desktop"));
+/// assert!(allowlist.allows_address("192.168.1.20".parse().unwrap()));
+/// assert!(!allowlist.allows_address("10.0.0.5".parse().unwrap()));
+///
+/// assert!(AgentAllowlist::new(&["192.168.1.0/33"]).is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgentAllowlist {
+    names: HashSet<String>,
+    /// Networks as their address and prefix length
+    networks: Vec<(IpAddr, u8)>,
+}
+
+impl AgentAllowlist {
+    /// Builds the allowlist of `entries`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entry is empty or is not a valid network.
+    pub fn new<S: AsRef<str>>(entries: &[S]) -> Result<Self> {
+        let mut allowlist = Self::default();
+        for entry in entries {
+            let entry = entry.as_ref().trim();
+            if entry.is_empty() {
+                anyhow::bail!("Empty entry in the agent allowlist");
+            }
+            if let Some((ip, prefix)) = entry.split_once('/') {
+                let ip: IpAddr = ip.parse().map_err(|_| {
+                    anyhow::anyhow!("Invalid network in the agent allowlist: {}", entry)
+                })?;
+                let max = if ip.is_ipv4() { 32 } else { 128 };
+                let prefix = prefix
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|prefix| *prefix <= max)
+                    .with_context(|| {
+                        format!("Invalid prefix length in the agent allowlist: {}", entry)
+                    })?;
+                allowlist.networks.push((ip, prefix));
+            } else if let Ok(ip) = entry.parse::<IpAddr>() {
+                let prefix = if ip.is_ipv4() { 32 } else { 128 };
+                allowlist.networks.push((ip, prefix));
+            } else {
+                allowlist.names.insert(entry.to_string());
+            }
+        }
+        Ok(allowlist)
+    }
+
+    /// Returns whether an agent named `name` may connect.
+    pub fn allows_name(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    /// Returns whether connections from `ip` are let in before authentication.
+    pub fn allows_address(&self, ip: IpAddr) -> bool {
+        // Dual-stack listeners see IPv4 peers as ::ffff:a.b.c.d
+        let ip = ip.to_canonical();
+        self.networks
+            .iter()
+            .any(|(network, prefix)| match (network, ip) {
+                (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                    let mask = u32::MAX.checked_shl(32 - u32::from(*prefix)).unwrap_or(0);
+                    u32::from(*network) & mask == u32::from(ip) & mask
+                }
+                (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                    let mask = u128::MAX.checked_shl(128 - u32::from(*prefix)).unwrap_or(0);
+                    u128::from(*network) & mask == u128::from(ip) & mask
+                }
+                _ => false,
+            })
+    }
+
+    /// Returns whether some entries are names, which can only be checked
+    /// after authentication.
+    fn has_names(&self) -> bool {
+        !self.names.is_empty()
+    }
+}
+
+/// Token bucket per source address, deciding which connections the host accepts.
+///
+/// Each address may open `burst` connections at once, then one more each
+/// time a token is refilled, at `per_minute` tokens a minute. IPv4 peers of a
+/// dual-stack listener share the bucket of their IPv4 address.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::ConnectionRateLimiter;
+/// use std::time::{Duration, Instant};
+///
+/// let mut limiter = ConnectionRateLimiter::new(2, 60);
+/// let ip = "192.168.1.20".parse().unwrap();
+/// let now = Instant::now();
+/// assert!(limiter.allow(ip, now));
+/// assert!(limiter.allow(ip, now));
+/// assert!(!limiter.allow(ip, now));
+///
+/// // One token a second comes back
+/// assert!(limiter.allow(ip, now + Duration::from_secs(1)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectionRateLimiter {
+    burst: u32,
+    per_second: f64,
+    buckets: HashMap<IpAddr, TokenBucket>,
+}
+
+impl ConnectionRateLimiter {
+    /// Creates a limiter letting each address open `burst` connections at
+    /// once and `per_minute` a minute after that.
+    pub fn new(burst: u32, per_minute: u32) -> Self {
+        Self {
+            burst,
+            per_second: f64::from(per_minute) / 60.0,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Takes a token from the bucket of `ip` if one is left at `now`.
+    ///
+    /// Returns `false` when the connection should be dropped.
+    pub fn allow(&mut self, ip: IpAddr, now: Instant) -> bool {
+        if self.buckets.len() >= RATE_LIMITER_PRUNE_THRESHOLD {
+            self.buckets.retain(|_, bucket| !bucket.is_full(now));
+        }
+        let (burst, per_second) = (self.burst, self.per_second);
+        self.buckets
+            .entry(ip.to_canonical())
+            .or_insert_with(|| TokenBucket::new(burst, per_second, now))
+            .try_take(now)
+    }
+}
+
+/// Tokens refilled at a steady rate up to a capacity, measured on a given clock.
+///
+/// The time is passed in, so the bucket can be driven by simulated time.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::TokenBucket;
+/// use std::time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// let mut bucket = TokenBucket::new(2, 10.0, start);
+/// assert!(bucket.try_take(start));
+/// assert!(bucket.try_take(start));
+/// assert!(!bucket.try_take(start));
+/// assert!(bucket.try_take(start + Duration::from_millis(100)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket of `capacity` tokens refilled at `per_second`.
+    pub fn new(capacity: u32, per_second: f64, now: Instant) -> Self {
+        Self {
+            capacity: f64::from(capacity),
+            per_second,
+            tokens: f64::from(capacity),
+            updated: now,
+        }
+    }
+
+    /// Takes a token if one is left at `now`.
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns whether the bucket has refilled to its capacity at `now`.
+    pub fn is_full(&self, now: Instant) -> bool {
+        self.tokens + self.elapsed(now) * self.per_second >= self.capacity
+    }
+
+    fn refill(&mut self, now: Instant) {
+        self.tokens = (self.tokens + self.elapsed(now) * self.per_second).min(self.capacity);
+        self.updated = self.updated.max(now);
+    }
+
+    fn elapsed(&self, now: Instant) -> f64 {
+        now.saturating_duration_since(self.updated).as_secs_f64()
+    }
+}
+
+/// Drops the mouse moves a host sends beyond a rate, against feedback loops.
+///
+/// Two machines both running as host and pointing at each other forward
+/// each other's events forever. Every event takes a token; once there are
+/// none left [`Event::MouseMove`] is dropped, as the next one replaces it
+/// anyway, and other events still go through. [`Event::MouseMoveRelative`]
+/// cannot be dropped without losing the distance moved: its deltas are
+/// summed and go out with the next event sent.
+#[derive(Debug)]
+struct ForwardThrottle {
+    bucket: TokenBucket,
+    /// Relative motion held back since the last move sent
+    pending: (i32, i32),
+    /// Moves dropped since the last warning
+    dropped: u64,
+    last_warning: Option<Instant>,
+}
+
+impl ForwardThrottle {
+    /// Lets `per_second` events through each second, as a burst or spread out.
+    fn new(per_second: u32, now: Instant) -> Self {
+        Self {
+            bucket: TokenBucket::new(per_second, f64::from(per_second), now),
+            pending: (0, 0),
+            dropped: 0,
+            last_warning: None,
+        }
+    }
+
+    /// Returns the events to send at `now` for `event`: none when it is
+    /// dropped or held back, preceded by the held relative motion otherwise.
+    fn admit(&mut self, event: Event, now: Instant) -> Vec<Event> {
+        let admitted = self.bucket.try_take(now);
+        match event {
+            Event::MouseMoveRelative { dx, dy } => {
+                let dx = self.pending.0.saturating_add(dx);
+                let dy = self.pending.1.saturating_add(dy);
+                if admitted {
+                    self.pending = (0, 0);
+                    vec![Event::MouseMoveRelative { dx, dy }]
+                } else {
+                    self.pending = (dx, dy);
+                    Vec::new()
+                }
+            }
+            Event::MouseMove { .. } if !admitted => {
+                self.dropped += 1;
+                Vec::new()
+            }
+            event if self.pending == (0, 0) => vec![event],
+            event => {
+                // A click lands where the held motion took the cursor
+                let (dx, dy) = std::mem::take(&mut self.pending);
+                vec![Event::MouseMoveRelative { dx, dy }, event]
+            }
+        }
+    }
+
+    /// Returns the moves dropped since the last warning, at most once per
+    /// [`THROTTLE_WARNING_INTERVAL`].
+    fn warning(&mut self, now: Instant) -> Option<u64> {
+        if self.dropped == 0
+            || self
+                .last_warning
+                .is_some_and(|last| now.saturating_duration_since(last) < THROTTLE_WARNING_INTERVAL)
+        {
+            return None;
+        }
+        self.last_warning = Some(now);
+        Some(std::mem::take(&mut self.dropped))
+    }
+}
+
+/// Queue sizes and timers of a [`Network`].
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::{Network, NetworkConfig};
+/// use std::time::Duration;
+///
+/// let config = NetworkConfig {
+///     channel_capacity: 1000,
+///     heartbeat_interval: Duration::from_secs(2),
+///     ..NetworkConfig::default()
+/// };
+/// let network = Network::with_config("psk".to_string(), config);
+/// assert_eq!(network.queue_depth(), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkConfig {
+    /// Events each send queue holds before [`Network::send_event`] waits (default 100)
+    pub channel_capacity: usize,
+    /// Time between heartbeats (default 5 seconds); keep it well below
+    /// `agent_timeout` and the 15 seconds of silence after which an agent
+    /// drops its host
+    pub heartbeat_interval: Duration,
+    /// Time allowed to connect to a host and for a peer to say hello (default 10 seconds)
+    pub connection_timeout: Duration,
+    /// Silence after which the host drops an agent (default 15 seconds)
+    pub agent_timeout: Duration,
+    /// Bulk events (clipboard) each send queue holds before sending them
+    /// fails with [`SendError::BulkLaneFull`] (default 16)
+    pub bulk_capacity: usize,
+    /// Connections one address may open at once before the host drops the
+    /// next ones (default [`DEFAULT_CONNECTION_BURST`])
+    pub connection_burst: u32,
+    /// Connections a minute one address may open after its burst (default
+    /// [`DEFAULT_CONNECTIONS_PER_MINUTE`])
+    pub connections_per_minute: u32,
+    /// Events a second the host sends to one agent before dropping mouse
+    /// moves (default [`DEFAULT_MAX_EVENTS_PER_SECOND`])
+    pub max_events_per_second: u32,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            channel_capacity: CHANNEL_CAPACITY,
+            heartbeat_interval: HEARTBEAT_INTERVAL,
+            connection_timeout: CONNECTION_TIMEOUT,
+            agent_timeout: HEARTBEAT_TIMEOUT,
+            bulk_capacity: BULK_CHANNEL_CAPACITY,
+            connection_burst: DEFAULT_CONNECTION_BURST,
+            connections_per_minute: DEFAULT_CONNECTIONS_PER_MINUTE,
+            max_events_per_second: DEFAULT_MAX_EVENTS_PER_SECOND,
+        }
+    }
+}
+
+/// TCP options set on every connection of a [`Network`].
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::{Network, SocketOptions};
+///
+/// let network = Network::new("psk".to_string()).with_socket_options(SocketOptions {
+///     send_buffer_size: Some(64 * 1024),
+///     ..SocketOptions::default()
+/// });
+/// assert!(network.socket_options().nodelay);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SocketOptions {
+    /// Send each frame at once instead of letting Nagle's algorithm batch
+    /// small frames such as mouse moves (default `true`)
+    pub nodelay: bool,
+    /// Size of the kernel send buffer in bytes (system default if unset)
+    pub send_buffer_size: Option<usize>,
+    /// Size of the kernel receive buffer in bytes (system default if unset)
+    pub recv_buffer_size: Option<usize>,
+}
+
+impl Default for SocketOptions {
+    fn default() -> Self {
+        Self {
+            nodelay: true,
+            send_buffer_size: None,
+            recv_buffer_size: None,
+        }
+    }
+}
+
+impl SocketOptions {
+    /// Sets these options on `stream`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the system refuses an option.
+    pub fn apply(&self, stream: &TcpStream) -> Result<()> {
+        stream
+            .set_nodelay(self.nodelay)
+            .context("Failed to set TCP_NODELAY")?;
+        let socket = socket2::SockRef::from(stream);
+        if let Some(size) = self.send_buffer_size {
+            socket
+                .set_send_buffer_size(size)
+                .context("Failed to set the send buffer size")?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            socket
+                .set_recv_buffer_size(size)
+                .context("Failed to set the receive buffer size")?;
+        }
+        Ok(())
+    }
+}
+
+/// Why [`Network::send_event`] or [`Network::send_event_to_host`] refused an event.
+///
+/// Returned inside the `anyhow::Error`, so callers can
+/// [`downcast_ref`](anyhow::Error::downcast_ref) it.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::SendError;
+///
+/// let error = anyhow::Error::from(SendError::BulkLaneFull { capacity: 16 });
+/// assert_eq!(
+///     error.downcast_ref::<SendError>(),
+///     Some(&SendError::BulkLaneFull { capacity: 16 })
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendError {
+    /// The bulk lane already holds `capacity` events; input events are unaffected.
+    BulkLaneFull {
+        /// Bulk events the lane holds
+        capacity: usize,
+    },
+}
+
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendError::BulkLaneFull { capacity } => write!(
+                f,
+                "Bulk send queue is full ({} events), try again later",
+                capacity
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+/// Receiving side of a send queue, split into two lanes.
+///
+/// Input and focus events take the priority lane and are always handed out
+/// first; clipboard content takes the bulk lane (see [`Event::is_bulk`]), so
+/// a large transfer never holds back the cursor.
+struct SendLanes {
+    priority: mpsc::Receiver<Event>,
+    bulk: mpsc::Receiver<Event>,
+    /// Bulk events queued, see [`Network::bulk_queue_depth`]
+    bulk_queued: Arc<AtomicUsize>,
+}
+
+impl SendLanes {
+    /// Waits for the next event, from the priority lane if it has one.
+    async fn recv(&mut self) -> Option<Event> {
+        tokio::select! {
+            biased;
+            Some(event) = self.priority.recv() => Some(event),
+            Some(event) = self.bulk.recv() => {
+                count_bulk_dequeued(&self.bulk_queued);
+                Some(event)
+            }
+            else => None,
+        }
+    }
+
+    /// Takes the next event without waiting, from the priority lane if it has one.
+    fn try_recv(&mut self) -> Option<Event> {
+        if let Ok(event) = self.priority.try_recv() {
+            return Some(event);
+        }
+        let event = self.bulk.try_recv().ok()?;
+        count_bulk_dequeued(&self.bulk_queued);
+        Some(event)
+    }
+}
+
+/// Read-only view of a [`Network`]'s state.
+///
+/// Obtained from [`Network::monitor`]. It shares the network's state, so it
+/// stays up to date and can be moved into other tasks, e.g. to answer status
+/// queries.
+#[derive(Clone)]
+pub struct NetworkMonitor {
+    agents: AgentRegistry,
+    status: Arc<std::sync::Mutex<ConnectionStatus>>,
+    connection_count: Arc<AtomicUsize>,
+    events_forwarded: Arc<AtomicU64>,
+    queued: Arc<AtomicUsize>,
+    stats: Arc<NetworkStats>,
+}
+
+impl NetworkMonitor {
+    /// Returns the names of the connected agents, sorted alphabetically.
+    pub fn connected_agents(&self) -> Vec<String> {
+        self.agents().into_iter().map(|(name, _)| name).collect()
+    }
+
+    /// Returns the connected agents with their state, sorted by name.
+    pub fn agents(&self) -> Vec<(String, AgentState)> {
+        let mut agents: Vec<(String, AgentState)> = self
+            .agents
+            .lock()
+            .map(|agents| {
+                agents
+                    .iter()
+                    .map(|(name, state)| (name.clone(), *state))
+                    .collect()
+            })
+            .unwrap_or_default();
+        agents.sort_by(|a, b| a.0.cmp(&b.0));
+        agents
+    }
+
+    /// Returns the connected agents, sorted by name. See [`Network::connected_peers`].
+    pub fn connected_peers(&self) -> Vec<PeerSummary> {
+        self.agents()
+            .into_iter()
+            .map(|(name, state)| PeerSummary {
+                name,
+                address: state.address,
+                connected_for: state.connected_at.elapsed(),
+                last_seen: state.last_seen.elapsed(),
+                has_focus: state.has_focus,
+                latency: state.latency,
+                events_sent: state.events_sent,
+                events_dropped: state.events_dropped,
+                bytes_sent: state.bytes_sent,
+            })
+            .collect()
+    }
+
+    /// Returns the round trip of the last ping `name` answered. See [`Network::agent_latency`].
+    pub fn agent_latency(&self, name: &str) -> Option<Duration> {
+        self.agents
+            .lock()
+            .ok()
+            .and_then(|agents| agents.get(name).and_then(|state| state.latency))
+    }
+
+    /// Returns the agent currently holding focus, if any.
+    pub fn focused_agent(&self) -> Option<String> {
+        self.agents.lock().ok().and_then(|agents| {
+            agents
+                .iter()
+                .find(|(_, state)| state.has_focus)
+                .map(|(name, _)| name.clone())
+        })
+    }
+
+    /// Returns the agent-side connection status. See [`Network::connection_status`].
+    pub fn connection_status(&self) -> ConnectionStatus {
+        self.status
+            .lock()
+            .map(|status| *status)
+            .unwrap_or(ConnectionStatus::Disconnected)
+    }
+
+    /// Returns the number of open client connections. See [`Network::connection_count`].
+    pub fn connection_count(&self) -> usize {
+        self.connection_count.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of events written to peers since the network was created.
+    pub fn events_forwarded(&self) -> u64 {
+        self.events_forwarded.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of events waiting to be sent. See [`Network::queue_depth`].
+    pub fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// Returns the latency and traffic figures. See [`Network::metrics_snapshot`].
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.stats.snapshot(self.events_forwarded())
+    }
+}
+
+/// Name and screen size an agent announces in [`Event::Hello`].
+type AgentInfo = Option<(String, (u32, u32))>;
+
+/// Pre-shared key in use, shared with the connection tasks so it can be rotated.
+type SharedPsk = Arc<std::sync::RwLock<String>>;
+
+/// Key waiting for the other side to confirm a rotation.
+type PendingPsk = Arc<std::sync::Mutex<Option<String>>>;
+
+/// Pre-shared keys of the peers that do not use the shared one, by peer name.
+type PeerPsks = Arc<std::sync::RwLock<HashMap<String, String>>>;
+
+/// Agents a host lets in, every one that knows the PSK when `None`.
+type AllowedAgents = Arc<std::sync::RwLock<Option<AgentAllowlist>>>;
+
+/// Number of control frames buffered per agent connection.
+const CONTROL_EVENT_CAPACITY: usize = 16;
+
+/// Handle for rotating the pre-shared key of a running [`Network`].
+///
+/// Obtained from [`Network::psk_rotation`]. It shares the network's state, so
+/// it can be moved into other tasks, e.g. to serve `multishiva rotate-psk`.
+///
+/// On a host with connected agents, the new key takes effect once an agent
+/// acknowledges it; without agents it takes effect immediately. On an agent,
+/// the new key is kept until the host announces the same key, so agents must
+/// be given the new key before the host.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::Network;
+///
+/// let network = Network::new("old-psk".to_string());
+/// let rotation = network.psk_rotation();
+/// assert!(rotation.rotate(String::new()).is_err());
+/// ```
+#[derive(Clone)]
+pub struct PskRotation {
+    psk: SharedPsk,
+    pending: PendingPsk,
+    control: broadcast::Sender<Event>,
+    running: Arc<AtomicBool>,
+    connection_count: Arc<AtomicUsize>,
+}
+
+impl PskRotation {
+    /// Starts switching to `new_psk`.
+    ///
+    /// See [`Network::rotate_psk`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `new_psk` is empty or equal to the current key.
+    pub fn rotate(&self, new_psk: String) -> Result<()> {
+        if new_psk.is_empty() {
+            anyhow::bail!("The new PSK must not be empty");
+        }
+        let current = read_psk(&self.psk);
+        if new_psk == current {
+            anyhow::bail!("The new PSK is the same as the current one");
+        }
+
+        let agents = self.connection_count.load(Ordering::SeqCst);
+        if !self.running.load(Ordering::SeqCst) {
+            tracing::info!("🔑 New PSK ready, waiting for the host to rotate");
+            set_pending_psk(&self.pending, Some(new_psk));
+        } else if agents == 0 {
+            write_psk(&self.psk, new_psk);
+            set_pending_psk(&self.pending, None);
+            tracing::info!("🔑 PSK rotated");
+        } else {
+            let new_psk_hash = compute_psk_hash(&new_psk);
+            let signature = sign_psk_rotation(&current, &new_psk_hash)?;
+            set_pending_psk(&self.pending, Some(new_psk));
+            let _ = self.control.send(Event::PskRotate {
+                new_psk_hash,
+                signature,
+            });
+            tracing::info!("🔑 PSK rotation sent to {} agent(s)", agents);
+        }
+        Ok(())
+    }
+
+    /// Returns whether a new key is waiting to be confirmed.
+    pub fn is_pending(&self) -> bool {
+        self.pending.lock().map(|p| p.is_some()).unwrap_or(false)
+    }
+}
+
 /// Network manager for secure peer-to-peer communication with PSK authentication.
 ///
 /// The `Network` struct handles both hosting and connecting to remote peers,
@@ -34,65 +1017,223 @@ const PSK_MAGIC: &[u8] = b"MULTISHIVA_PSK_V1";
 ///     let mut network = Network::new("my-secure-psk".to_string());
 ///
 ///     // Start hosting on port 8080
-///     let port = network.start_host(8080).await?;
+///     let port = network.start_host(8080, None).await?;
 ///     println!("Hosting on port {}", port);
 ///
 ///     Ok(())
 /// }
 /// ```
 pub struct Network {
-    psk: String,
+    psk: SharedPsk,
+    pending_psk: PendingPsk,
+    peer_psks: PeerPsks,
+    allowed_agents: AllowedAgents,
+    control_events: broadcast::Sender<Event>,
     running: Arc<AtomicBool>,
     connected: Arc<AtomicBool>,
+    status: Arc<std::sync::Mutex<ConnectionStatus>>,
     connection_count: Arc<AtomicUsize>,
     event_tx: Arc<RwLock<Option<mpsc::Sender<Event>>>>,
-    event_rx: Arc<RwLock<Option<mpsc::Receiver<Event>>>>,
+    bulk_tx: mpsc::Sender<Event>,
+    event_rx: Arc<RwLock<Option<SendLanes>>>,
     // Second channel for agent→host communication (bidirectional)
     agent_tx: Arc<RwLock<Option<mpsc::Sender<Event>>>>,
-    agent_rx: Arc<RwLock<Option<mpsc::Receiver<Event>>>>,
+    agent_bulk_tx: mpsc::Sender<Event>,
+    agent_rx: Arc<RwLock<Option<SendLanes>>>,
     fingerprint_store: Arc<Mutex<FingerprintStore>>,
+    agents: AgentRegistry,
+    writers: ClientWriters,
+    agent_info: Arc<std::sync::Mutex<AgentInfo>>,
+    connection_events: broadcast::Sender<ConnectionEvent>,
+    events_forwarded: Arc<AtomicU64>,
+    /// Set by [`Network::stop`] to the reason sent to the peers
+    shutdown: watch::Sender<Option<String>>,
+    /// Connection handlers still running, on either side
+    handlers: Arc<AtomicUsize>,
+    /// Events sent to the queues and not yet taken by a connection
+    queued: Arc<AtomicUsize>,
+    /// Bulk events among them
+    bulk_queued: Arc<AtomicUsize>,
+    stats: Arc<NetworkStats>,
+    config: NetworkConfig,
+    socket_options: SocketOptions,
+    bind_address: Option<BindAddress>,
+    /// Trust the next changed host fingerprint instead of refusing it
+    accept_new_fingerprint: Arc<AtomicBool>,
+    /// Addresses the host listeners are bound to
+    local_addrs: Vec<SocketAddr>,
+    /// Name of this machine, which agents must not announce
+    self_name: Option<String>,
+    /// Machine name of the host, see [`Network::set_host_name`]
+    host_name: Option<String>,
 }
 
 impl Network {
     /// Creates a new `Network` instance with the specified pre-shared key.
     ///
-    /// Initializes the network manager with an event channel, fingerprint store,
-    /// and connection state tracking. The fingerprint store is loaded from the
-    /// default location, or a new one is created if loading fails.
+    /// Initializes the network manager with an event channel, fingerprint store,
+    /// and connection state tracking. The fingerprint store is loaded from the
+    /// default location, or a new one is created if loading fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let network = Network::new("my-secret-key".to_string());
+    /// ```
+    pub fn new(psk: String) -> Self {
+        Self::with_config(psk, NetworkConfig::default())
+    }
+
+    /// Creates a new `Network` with the given queue sizes and timers.
+    ///
+    /// See [`NetworkConfig`] for an example.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.channel_capacity` or `config.bulk_capacity` is 0.
+    pub fn with_config(psk: String, config: NetworkConfig) -> Self {
+        assert!(
+            config.channel_capacity > 0 && config.bulk_capacity > 0,
+            "channel capacity must be at least 1"
+        );
+        let bulk_queued = Arc::new(AtomicUsize::new(0));
+        let lanes = |bulk_queued: &Arc<AtomicUsize>| {
+            let (tx, priority) = mpsc::channel(config.channel_capacity);
+            let (bulk_tx, bulk) = mpsc::channel(config.bulk_capacity);
+            let lanes = SendLanes {
+                priority,
+                bulk,
+                bulk_queued: bulk_queued.clone(),
+            };
+            (tx, bulk_tx, lanes)
+        };
+        let (tx, bulk_tx, rx) = lanes(&bulk_queued);
+        let (agent_tx, agent_bulk_tx, agent_rx) = lanes(&bulk_queued);
+        let (connection_events, _) = broadcast::channel(CONNECTION_EVENT_CAPACITY);
+        let (control_events, _) = broadcast::channel(CONTROL_EVENT_CAPACITY);
+        let fingerprint_store = FingerprintStore::load_default().unwrap_or_else(|e| {
+            tracing::warn!("Could not load fingerprint store: {}. Creating new one.", e);
+            FingerprintStore::new(FingerprintStore::default_path()).unwrap()
+        });
+
+        Self {
+            psk: Arc::new(std::sync::RwLock::new(psk)),
+            pending_psk: Arc::new(std::sync::Mutex::new(None)),
+            peer_psks: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            allowed_agents: Arc::new(std::sync::RwLock::new(None)),
+            control_events,
+            running: Arc::new(AtomicBool::new(false)),
+            connected: Arc::new(AtomicBool::new(false)),
+            status: Arc::new(std::sync::Mutex::new(ConnectionStatus::Disconnected)),
+            connection_count: Arc::new(AtomicUsize::new(0)),
+            event_tx: Arc::new(RwLock::new(Some(tx))),
+            bulk_tx,
+            event_rx: Arc::new(RwLock::new(Some(rx))),
+            agent_tx: Arc::new(RwLock::new(Some(agent_tx))),
+            agent_bulk_tx,
+            agent_rx: Arc::new(RwLock::new(Some(agent_rx))),
+            fingerprint_store: Arc::new(Mutex::new(fingerprint_store)),
+            agents: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            writers: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            agent_info: Arc::new(std::sync::Mutex::new(None)),
+            connection_events,
+            events_forwarded: Arc::new(AtomicU64::new(0)),
+            shutdown: watch::channel(None).0,
+            handlers: Arc::new(AtomicUsize::new(0)),
+            queued: Arc::new(AtomicUsize::new(0)),
+            bulk_queued,
+            stats: Arc::new(NetworkStats::default()),
+            config,
+            socket_options: SocketOptions::default(),
+            bind_address: None,
+            accept_new_fingerprint: Arc::new(AtomicBool::new(false)),
+            local_addrs: Vec::new(),
+            self_name: None,
+            host_name: None,
+        }
+    }
+
+    /// Restricts [`Network::start_host`] to the addresses of `bind_address`.
+    ///
+    /// With `None`, the default, the host listens on every address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let mut network = Network::new("psk".to_string());
+    /// network.set_bind_address(Some("127.0.0.1".parse().unwrap()));
+    /// ```
+    pub fn set_bind_address(&mut self, bind_address: Option<BindAddress>) {
+        self.bind_address = bind_address;
+    }
+
+    /// Sets the name of this machine, so that [`Network::start_host`]
+    /// refuses an agent announcing it: that agent is this machine
+    /// connecting to itself, which would loop its own input back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let mut network = Network::new("psk".to_string());
+    /// network.set_self_name(Some("desk".to_string()));
+    /// ```
+    pub fn set_self_name(&mut self, self_name: Option<String>) {
+        self.self_name = self_name;
+    }
+
+    /// Sets the machine name of the host [`Network::connect_to_host`]
+    /// reaches: its fingerprint is stored under it rather than under the
+    /// address connected to, which changes with DHCP or mDNS.
     ///
     /// # Examples
     ///
     /// ```
     /// use multishiva::core::network::Network;
     ///
-    /// let network = Network::new("my-secret-key".to_string());
+    /// let mut network = Network::new("psk".to_string());
+    /// network.set_host_name(Some("desk".to_string()));
     /// ```
-    pub fn new(psk: String) -> Self {
-        let (tx, rx) = mpsc::channel(100);
-        let (agent_tx, agent_rx) = mpsc::channel(100);
-        let fingerprint_store = FingerprintStore::load_default().unwrap_or_else(|e| {
-            tracing::warn!("Could not load fingerprint store: {}. Creating new one.", e);
-            FingerprintStore::new(FingerprintStore::default_path()).unwrap()
-        });
+    pub fn set_host_name(&mut self, host_name: Option<String>) {
+        self.host_name = host_name;
+    }
 
-        Self {
-            psk,
-            running: Arc::new(AtomicBool::new(false)),
-            connected: Arc::new(AtomicBool::new(false)),
-            connection_count: Arc::new(AtomicUsize::new(0)),
-            event_tx: Arc::new(RwLock::new(Some(tx))),
-            event_rx: Arc::new(RwLock::new(Some(rx))),
-            agent_tx: Arc::new(RwLock::new(Some(agent_tx))),
-            agent_rx: Arc::new(RwLock::new(Some(agent_rx))),
-            fingerprint_store: Arc::new(Mutex::new(fingerprint_store)),
-        }
+    /// Returns the name the host at `addr` is known by: its configured
+    /// machine name, or the address without one.
+    fn host_id<'a>(&'a self, addr: &'a str) -> &'a str {
+        self.host_name.as_deref().unwrap_or(addr)
+    }
+
+    /// Sets the TCP options of the connections made from now on.
+    ///
+    /// See [`SocketOptions`] for an example.
+    pub fn with_socket_options(mut self, options: SocketOptions) -> Self {
+        self.socket_options = options;
+        self
+    }
+
+    /// Returns the TCP options set on every connection.
+    pub fn socket_options(&self) -> SocketOptions {
+        self.socket_options
+    }
+
+    /// Returns the addresses the host listens on, once [`Network::start_host`] succeeded.
+    pub fn local_addrs(&self) -> &[SocketAddr] {
+        &self.local_addrs
     }
 
     /// Starts hosting on the specified port and listens for incoming connections.
     ///
-    /// Binds to `127.0.0.1` on the given port and spawns an async task to accept
-    /// incoming client connections. Each client connection is authenticated using
-    /// PSK handshake before being handled in a separate task.
+    /// Binds to every address on the given port (dual-stack `[::]`, or `0.0.0.0`
+    /// without IPv6), or to the addresses set with [`Network::set_bind_address`],
+    /// and spawns an async task to accept incoming client connections. Each client
+    /// connection is authenticated using PSK handshake before being handled in a
+    /// separate task.
     ///
     /// # Examples
     ///
@@ -102,7 +1243,7 @@ impl Network {
     /// #[tokio::main]
     /// async fn main() -> anyhow::Result<()> {
     ///     let mut network = Network::new("psk".to_string());
-    ///     let actual_port = network.start_host(8080).await?;
+    ///     let actual_port = network.start_host(8080, None).await?;
     ///     println!("Hosting on port {}", actual_port);
     ///     Ok(())
     /// }
@@ -113,6 +1254,7 @@ impl Network {
     /// Returns an error if:
     /// - The port is already in use
     /// - Unable to bind to the specified address
+    /// - The bind interface does not exist or has no address
     /// - Cannot retrieve the local address from the listener
     ///
     /// # Parameters
@@ -125,57 +1267,161 @@ impl Network {
         port: u16,
         input_event_tx: Option<mpsc::Sender<Event>>,
     ) -> Result<u16> {
-        // Try to bind on IPv6 dual-stack first (supports both IPv4 and IPv6)
-        // Falls back to IPv4-only if IPv6 is not available
-        let listener = match TcpListener::bind(format!("[::]:{}", port)).await {
-            Ok(listener) => {
-                tracing::debug!("Bound to IPv6 dual-stack address [::]:{}", port);
-                listener
-            }
-            Err(_) => {
-                tracing::debug!("IPv6 not available, falling back to IPv4");
-                TcpListener::bind(format!("0.0.0.0:{}", port))
-                    .await
-                    .context("Failed to bind to address")?
-            }
+        let listeners = match &self.bind_address {
+            Some(bind_address) => bind_listeners(bind_address, port).await?,
+            // Try to bind on IPv6 dual-stack first (supports both IPv4 and IPv6)
+            // Falls back to IPv4-only if IPv6 is not available
+            None => match TcpListener::bind(format!("[::]:{}", port)).await {
+                Ok(listener) => {
+                    tracing::debug!("Bound to IPv6 dual-stack address [::]:{}", port);
+                    vec![listener]
+                }
+                Err(_) => {
+                    tracing::debug!("IPv6 not available, falling back to IPv4");
+                    vec![TcpListener::bind(format!("0.0.0.0:{}", port))
+                        .await
+                        .context("Failed to bind to address")?]
+                }
+            },
         };
 
-        let actual_port = listener.local_addr()?.port();
+        self.local_addrs = listeners
+            .iter()
+            .map(|listener| listener.local_addr())
+            .collect::<std::io::Result<_>>()?;
+        let actual_port = self.local_addrs[0].port();
         self.running.store(true, Ordering::SeqCst);
+        self.shutdown.send_replace(None);
 
         let running = self.running.clone();
         let connection_count = self.connection_count.clone();
         let psk = self.psk.clone();
+        let pending_psk = self.pending_psk.clone();
+        let peer_psks = self.peer_psks.clone();
+        let allowed_agents = self.allowed_agents.clone();
+        let control_events = self.control_events.clone();
         let event_rx = self.event_rx.clone();
         let input_event_tx = Arc::new(input_event_tx);
+        let agents = self.agents.clone();
+        let writers = self.writers.clone();
+        let connection_events = self.connection_events.clone();
+        let events_forwarded = self.events_forwarded.clone();
+        let shutdown = self.shutdown.clone();
+        let handlers = self.handlers.clone();
+        let queued = self.queued.clone();
+        let stats = self.stats.clone();
+        let heartbeat_interval = self.config.heartbeat_interval;
+        let hello_timeout = self.config.connection_timeout;
+        let agent_timeout = self.config.agent_timeout;
+        let max_events_per_second = self.config.max_events_per_second;
+        let self_name = self.self_name.clone();
+        let socket_options = self.socket_options;
+        let (connection_burst, connections_per_minute) = (
+            self.config.connection_burst,
+            self.config.connections_per_minute,
+        );
+        let mut rate_limiter = ConnectionRateLimiter::new(connection_burst, connections_per_minute);
+
+        // Each listener hands its connections to the host listener task
+        let (accepted_tx, mut accepted_rx) = mpsc::channel(listeners.len());
+        for listener in listeners {
+            let running = running.clone();
+            let accepted_tx = accepted_tx.clone();
+            tokio::spawn(async move {
+                while running.load(Ordering::SeqCst) {
+                    if let Ok(accepted) =
+                        tokio::time::timeout(Duration::from_millis(100), listener.accept()).await
+                    {
+                        if accepted_tx.send(accepted).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+        drop(accepted_tx);
 
         // Spawn host listener task
+        let local_addrs = self.local_addrs.clone();
         tokio::spawn(async move {
-            tracing::info!("Host listening on port {}", actual_port);
+            tracing::info!("Host listening on {:?}", local_addrs);
 
             while running.load(Ordering::SeqCst) {
-                match tokio::time::timeout(Duration::from_millis(100), listener.accept()).await {
-                    Ok(Ok((stream, addr))) => {
+                match tokio::time::timeout(Duration::from_millis(100), accepted_rx.recv()).await {
+                    Ok(Some(Ok((stream, addr)))) => {
+                        // Checked before any handler is spawned, so a flood
+                        // costs no more than the accept
+                        if !rate_limiter.allow(addr.ip(), Instant::now()) {
+                            tracing::warn!(
+                                "Refusing connection from {}: more than {} connections at once \
+                                 or {} a minute",
+                                addr,
+                                connection_burst,
+                                connections_per_minute
+                            );
+                            metrics::record_connection_error();
+                            continue;
+                        }
                         tracing::info!("New connection from {}", addr);
-                        connection_count.fetch_add(1, Ordering::SeqCst);
+                        let allowed = allowed_agents.read().ok().and_then(|a| a.clone());
+                        if allowed.as_ref().is_some_and(|allowed| {
+                            !allowed.allows_address(addr.ip()) && !allowed.has_names()
+                        }) {
+                            tracing::warn!(
+                                "Refusing connection from {}: not in allowed_agents",
+                                addr
+                            );
+                            metrics::record_connection_error();
+                            continue;
+                        }
+                        if let Err(e) = socket_options.apply(&stream) {
+                            tracing::warn!("Socket options not applied to {}: {:#}", addr, e);
+                        }
+                        let count = connection_count.fetch_add(1, Ordering::SeqCst) + 1;
+                        metrics::set_connected_agents(count);
 
-                        let psk = psk.clone();
                         let connection_count = connection_count.clone();
                         let event_rx = event_rx.clone();
                         let input_event_tx = input_event_tx.clone();
-
+                        let client = HostClient {
+                            addr,
+                            agents: agents.clone(),
+                            writers: writers.clone(),
+                            events: connection_events.clone(),
+                            events_forwarded: events_forwarded.clone(),
+                            psk: psk.clone(),
+                            pending_psk: pending_psk.clone(),
+                            peer_psks: peer_psks.clone(),
+                            allowed,
+                            control: control_events.subscribe(),
+                            shutdown: shutdown.subscribe(),
+                            queued: queued.clone(),
+                            stats: stats.clone(),
+                            heartbeat_interval,
+                            hello_timeout,
+                            agent_timeout,
+                            max_events_per_second,
+                            self_name: self_name.clone(),
+                        };
+
+                        let handlers = handlers.clone();
+                        handlers.fetch_add(1, Ordering::SeqCst);
                         tokio::spawn(async move {
                             if let Err(e) =
-                                handle_client(stream, psk, event_rx, input_event_tx).await
+                                handle_client(stream, event_rx, input_event_tx, client).await
                             {
                                 tracing::error!("Client handler error: {}", e);
                             }
-                            connection_count.fetch_sub(1, Ordering::SeqCst);
+                            let count = connection_count.fetch_sub(1, Ordering::SeqCst) - 1;
+                            metrics::set_connected_agents(count);
+                            handlers.fetch_sub(1, Ordering::SeqCst);
                         });
                     }
-                    Ok(Err(e)) => {
+                    Ok(Some(Err(e))) => {
                         tracing::error!("Accept error: {}", e);
+                        metrics::record_connection_error();
                     }
+                    Ok(None) => break,
                     Err(_) => {
                         // Timeout, continue loop to check running flag
                     }
@@ -218,125 +1464,548 @@ impl Network {
     pub async fn connect_to_host(&self, addr: &str) -> Result<()> {
         tracing::debug!("Attempting to connect to host at: {}", addr);
 
-        let mut stream =
-            match tokio::time::timeout(CONNECTION_TIMEOUT, TcpStream::connect(addr)).await {
-                Ok(Ok(stream)) => {
-                    tracing::debug!("TCP connection established to {}", addr);
-                    stream
-                }
-                Ok(Err(e)) => {
-                    tracing::error!("TCP connection failed to {}: {:?}", addr, e);
-                    return Err(e).context("Failed to connect to host");
-                }
-                Err(_) => {
-                    tracing::error!(
-                        "Connection timeout after {:?} to {}",
-                        CONNECTION_TIMEOUT,
-                        addr
-                    );
-                    anyhow::bail!("Connection timeout");
+        set_status(&self.status, ConnectionStatus::Connecting);
+        self.shutdown.send_replace(None);
+        let result = self.establish_connection(addr).await;
+        if result.is_err() {
+            set_status(&self.status, ConnectionStatus::Disconnected);
+        }
+        result
+    }
+
+    async fn establish_connection(&self, addr: &str) -> Result<()> {
+        let timeout = self.config.connection_timeout;
+        let mut stream = match tokio::time::timeout(timeout, TcpStream::connect(addr)).await {
+            Ok(Ok(stream)) => {
+                tracing::debug!("TCP connection established to {}", addr);
+                if let Err(e) = self.socket_options.apply(&stream) {
+                    tracing::warn!("Socket options not applied to {}: {:#}", addr, e);
                 }
-            };
+                stream
+            }
+            Ok(Err(e)) => {
+                tracing::error!("TCP connection failed to {}: {:?}", addr, e);
+                metrics::record_connection_error();
+                return Err(e).context("Failed to connect to host");
+            }
+            Err(_) => {
+                tracing::error!("Connection timeout after {:?} to {}", timeout, addr);
+                metrics::record_connection_error();
+                anyhow::bail!("Connection timeout");
+            }
+        };
 
-        // Perform PSK handshake and get machine name
-        let machine_name = perform_psk_handshake(&mut stream, &self.psk, false)
+        // Perform PSK handshake
+        let psk = self.psk_for(self.host_id(addr));
+        let machine_name = perform_psk_handshake(&mut stream, &psk, &HashMap::new(), false)
             .await
+            .inspect_err(|_| metrics::record_connection_error())
             .context("PSK handshake failed")?;
 
-        // Verify fingerprint
-        let psk_fingerprint = Fingerprint::from_cert_data(&machine_name, self.psk.as_bytes());
+        // Verify fingerprint, keyed by the host's name (the handshake only carries
+        // our own machine name)
+        let host_id = self.host_id(addr);
+        let psk_fingerprint = Fingerprint::from_cert_data(host_id, psk.as_bytes());
         let mut store = self.fingerprint_store.lock().await;
+        // Fingerprints used to be stored under the address
+        if host_id != addr && store.rename(addr, host_id)? {
+            tracing::info!("Fingerprint of {} moved from {} to its name", host_id, addr);
+        }
 
-        match store.verify_or_save(&machine_name, psk_fingerprint.hash())? {
+        match store.verify_or_save(host_id, psk_fingerprint.hash())? {
             FingerprintVerification::Verified => {
-                tracing::info!("✓ Fingerprint verified for {}", machine_name);
+                tracing::info!("✓ Fingerprint verified for {}", host_id);
             }
             FingerprintVerification::FirstConnection => {
-                tracing::warn!("First connection to {}. Fingerprint saved.", machine_name);
+                tracing::warn!("First connection to {}. Fingerprint saved.", host_id);
+            }
+            FingerprintVerification::Renewed => {
+                tracing::info!("✓ Fingerprint verified for {}, its expiry renewed", host_id);
+            }
+            FingerprintVerification::Mismatch { stored, received }
+                if self.accept_new_fingerprint.swap(false, Ordering::SeqCst) =>
+            {
+                tracing::warn!(
+                    "Fingerprint of {} changed from {} to {}; trusting the new one as \
+                     --accept-new-fingerprint asked",
+                    host_id,
+                    stored,
+                    received
+                );
+                store.update(host_id, &received)?;
             }
             FingerprintVerification::Mismatch { stored, received } => {
                 tracing::error!(
                     "⚠️  SECURITY WARNING: Fingerprint mismatch for {}!\n\
                      Stored:   {}\n\
                      Received: {}\n\
-                     This could indicate a Man-in-the-Middle attack!",
-                    machine_name,
+                     This could indicate a Man-in-the-Middle attack!\n\
+                     If the host changed its key on purpose, trust the new fingerprint with:\n  \
+                     multishiva fingerprints trust {} {}\n\
+                     or reconnect once with --accept-new-fingerprint",
+                    host_id,
                     stored,
+                    received,
+                    host_id,
+                    received
+                );
+                metrics::record_connection_error();
+                let _ = self
+                    .connection_events
+                    .send(ConnectionEvent::FingerprintMismatch {
+                        name: host_id.to_string(),
+                    });
+                anyhow::bail!(
+                    "Fingerprint mismatch - possible MITM attack. If {} changed its key on \
+                     purpose, run `multishiva fingerprints trust {} {}`",
+                    host_id,
+                    host_id,
                     received
                 );
-                anyhow::bail!("Fingerprint mismatch - possible MITM attack");
             }
         }
 
-        self.connected.store(true, Ordering::SeqCst);
+        drop(store);
 
-        let connected = self.connected.clone();
-        let psk = self.psk.clone();
+        // Introduce ourselves before anything else is sent
+        let (name, (screen_width, screen_height)) = self
+            .agent_info
+            .lock()
+            .ok()
+            .and_then(|info| info.clone())
+            .unwrap_or((machine_name, DEFAULT_SCREEN_SIZE));
+        let hello = Event::Hello {
+            name,
+            screen_width,
+            screen_height,
+        };
+        write_frame(&mut stream, &hello)
+            .await
+            .inspect_err(|_| metrics::record_connection_error())
+            .context("Failed to send Hello")?;
+
+        self.connected.store(true, Ordering::SeqCst);
+        set_status(
+            &self.status,
+            ConnectionStatus::Connected {
+                last_heartbeat: Instant::now(),
+            },
+        );
+
+        let connection = AgentConnection {
+            host: addr.to_string(),
+            host_id: host_id.to_string(),
+            connected: self.connected.clone(),
+            status: self.status.clone(),
+            events: self.connection_events.clone(),
+            events_forwarded: self.events_forwarded.clone(),
+            psk: self.psk.clone(),
+            pending_psk: self.pending_psk.clone(),
+            fingerprint_store: self.fingerprint_store.clone(),
+            agent_tx: self.agent_tx.clone(),
+            shutdown: self.shutdown.subscribe(),
+            queued: self.queued.clone(),
+            stats: self.stats.clone(),
+            heartbeat_interval: self.config.heartbeat_interval,
+        };
         let event_tx = self.event_tx.clone();
         let agent_rx = self.agent_rx.clone();
+        let handlers = self.handlers.clone();
 
         // Spawn connection handler
+        handlers.fetch_add(1, Ordering::SeqCst);
         tokio::spawn(async move {
-            if let Err(e) =
-                handle_connection(stream, psk, connected.clone(), event_tx, agent_rx).await
-            {
+            let connected = connection.connected.clone();
+            let status = connection.status.clone();
+            if let Err(e) = handle_connection(stream, connection, event_tx, agent_rx).await {
                 tracing::error!("Connection handler error: {}", e);
             }
             connected.store(false, Ordering::SeqCst);
+            set_status(&status, ConnectionStatus::Disconnected);
+            handlers.fetch_sub(1, Ordering::SeqCst);
         });
 
         Ok(())
     }
 
+    /// Sets the name and screen size this agent announces to the host.
+    ///
+    /// Must be called before [`Network::connect_to_host`]. Without it, the agent
+    /// announces its hostname and a 1920x1080 screen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let network = Network::new("psk".to_string());
+    /// network.set_agent_info("laptop".to_string(), (2560, 1440));
+    /// ```
+    pub fn set_agent_info(&self, name: String, screen_size: (u32, u32)) {
+        if let Ok(mut info) = self.agent_info.lock() {
+            *info = Some((name, screen_size));
+        }
+    }
+
+    /// Uses `psk` instead of the shared key for the connections with `peer`.
+    ///
+    /// `peer` is a machine name: a host matches it against the name an agent
+    /// sends in the PSK handshake (its hostname), an agent against the name
+    /// set with [`Network::set_host_name`], or the address given to
+    /// [`Network::connect_to_host`] without one. Peer keys are not rotated by
+    /// [`PskRotation::rotate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let network = Network::new("shared-psk".to_string());
+    /// network.set_peer_psk("laptop", "laptop-psk".to_string());
+    /// assert_eq!(network.psk_for("laptop"), "laptop-psk");
+    /// assert_eq!(network.psk_for("desktop"), "shared-psk");
+    /// ```
+    pub fn set_peer_psk(&self, peer: &str, psk: String) {
+        if let Ok(mut peer_psks) = self.peer_psks.write() {
+            peer_psks.insert(peer.to_string(), psk);
+        }
+    }
+
+    /// Restricts the agents a host lets in to `allowlist`.
+    ///
+    /// With `None`, the default, every agent that knows the PSK may connect.
+    /// Applies to the connections accepted from now on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::{AgentAllowlist, Network};
+    ///
+    /// let network = Network::new("psk".to_string());
+    /// network.set_allowed_agents(Some(AgentAllowlist::new(&["laptop"]).unwrap()));
+    /// ```
+    pub fn set_allowed_agents(&self, allowlist: Option<AgentAllowlist>) {
+        if let Ok(mut allowed) = self.allowed_agents.write() {
+            *allowed = allowlist;
+        }
+    }
+
+    /// Returns the pre-shared key used with the machine named `peer`: its own,
+    /// or the shared one.
+    pub fn psk_for(&self, peer: &str) -> String {
+        peer_psk(&self.peer_psks, peer).unwrap_or_else(|| read_psk(&self.psk))
+    }
+
+    /// Makes the next [`Network::connect_to_host`] that finds a changed host
+    /// fingerprint trust it instead of failing.
+    ///
+    /// One-shot: only the first mismatch is accepted, for a supervised key
+    /// rotation. The change is logged as a warning.
+    pub fn set_accept_new_fingerprint(&self, accept: bool) {
+        self.accept_new_fingerprint.store(accept, Ordering::SeqCst);
+    }
+
+    /// Sets how long host fingerprints stay trusted before they must be seen
+    /// again to be renewed, `None` for forever.
+    ///
+    /// See [`FingerprintStore::set_expires_after`].
+    pub async fn set_fingerprint_expiry(&self, expires_after: Option<Duration>) {
+        self.fingerprint_store
+            .lock()
+            .await
+            .set_expires_after(expires_after);
+    }
+
     /// Sends an event from agent back to host (for bidirectional communication).
     ///
     /// This is used by the agent to send events like FocusRelease back to the host.
+    /// Bulk events are queued as with [`Network::send_event`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError::BulkLaneFull`] for a bulk event the queue has no
+    /// room for, and an error if the channel is closed.
     pub async fn send_event_to_host(&self, event: Event) -> Result<()> {
+        if event.is_bulk() {
+            return self.send_bulk(&self.agent_bulk_tx, event);
+        }
         let tx_guard = self.agent_tx.read().await;
         if let Some(tx) = tx_guard.as_ref() {
-            tx.send(event)
+            let permit = tx
+                .reserve()
                 .await
                 .context("Failed to send event to host channel")?;
+            // Counted before the connection can take the event
+            count_queued(&self.queued);
+            permit.send(event);
+        }
+        Ok(())
+    }
+
+    /// Sends an event through the internal event channel.
+    ///
+    /// Queues the event for processing by the network subsystem. Events are
+    /// buffered in an async channel holding [`NetworkConfig::channel_capacity`]
+    /// messages; once it is full this waits for the connection to catch up.
+    ///
+    /// Bulk events (see [`Event::is_bulk`]) go to a separate lane of
+    /// [`NetworkConfig::bulk_capacity`] events that is only written once no
+    /// input event is waiting. Rather than waiting when that lane is full,
+    /// this fails at once; [`Network::bulk_lane_ready`] tells when to retry.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::network::Network;
+    /// use multishiva::core::events::Event;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let network = Network::new("psk".to_string());
+    ///     // network.send_event(Event::Connect).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError::BulkLaneFull`] for a bulk event the queue has no
+    /// room for, and an error if the event channel is closed or the receiver
+    /// has been dropped.
+    pub async fn send_event(&self, event: Event) -> Result<()> {
+        if event.is_bulk() {
+            return self.send_bulk(&self.bulk_tx, event);
         }
+        let tx_guard = self.event_tx.read().await;
+        if let Some(tx) = tx_guard.as_ref() {
+            let permit = tx
+                .reserve()
+                .await
+                .context("Failed to send event to channel")?;
+            // Counted before the connection can take the event
+            count_queued(&self.queued);
+            permit.send(event);
+        }
+        Ok(())
+    }
+
+    /// Sends `event` straight to the connected agent `name`, ahead of the queue.
+    ///
+    /// Used by the host to address an agent other than the focused one, e.g.
+    /// to release focus on an agent while granting it to the next.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` is not connected or the event cannot be written.
+    pub async fn send_event_to(&self, name: &str, event: Event) -> Result<()> {
+        let writer = self
+            .writers
+            .lock()
+            .ok()
+            .and_then(|writers| writers.get(name).map(|(_, writer)| writer.clone()))
+            .with_context(|| format!("Agent '{}' is not connected", name))?;
+        let bytes = write_frame(&mut *writer.lock().await, &event)
+            .await
+            .with_context(|| format!("Failed to send event to '{}'", name))?;
+        self.stats.record_sent(bytes);
+        metrics::record_event_sent(name);
+        self.events_forwarded.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Queues a bulk event on `lane` without waiting.
+    fn send_bulk(&self, lane: &mpsc::Sender<Event>, event: Event) -> Result<()> {
+        let permit = match lane.try_reserve() {
+            Ok(permit) => permit,
+            Err(mpsc::error::TrySendError::Full(())) => {
+                return Err(SendError::BulkLaneFull {
+                    capacity: self.config.bulk_capacity,
+                }
+                .into());
+            }
+            Err(mpsc::error::TrySendError::Closed(())) => {
+                anyhow::bail!("Failed to send event to bulk channel")
+            }
+        };
+        // Counted before the connection can take the event
+        count_queued(&self.queued);
+        let depth = self.bulk_queued.fetch_add(1, Ordering::SeqCst) + 1;
+        metrics::set_bulk_queue_depth(depth);
+        permit.send(event);
         Ok(())
     }
 
-    /// Sends an event through the internal event channel.
-    ///
-    /// Queues the event for processing by the network subsystem. Events are
-    /// buffered in an async channel with a capacity of 100 messages.
+    /// Waits until the bulk lane of [`Network::send_event`] has room for an event.
+    ///
+    /// Returns at once if it has room already.
+    pub async fn bulk_lane_ready(&self) {
+        // The permit is dropped at once; it only waits for room
+        let _ = self.bulk_tx.reserve().await;
+    }
+
+    /// Waits until the bulk lane of [`Network::send_event_to_host`] has room for an event.
+    pub async fn host_bulk_lane_ready(&self) {
+        let _ = self.agent_bulk_tx.reserve().await;
+    }
+
+    /// Returns the number of bulk events among [`Network::queue_depth`].
+    pub fn bulk_queue_depth(&self) -> usize {
+        self.bulk_queued.load(Ordering::SeqCst)
+    }
+
+    /// Receives the next event from the internal event channel.
+    ///
+    /// Blocks asynchronously until an event is available or the channel is closed.
+    /// Returns `None` if the event sender has been dropped or the channel is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::network::Network;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let network = Network::new("psk".to_string());
+    ///
+    ///     if let Some(event) = network.receive_event().await {
+    ///         println!("Received event: {:?}", event);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn receive_event(&self) -> Option<Event> {
+        let mut rx_guard = self.event_rx.write().await;
+        if let Some(rx) = rx_guard.as_mut() {
+            rx.recv().await
+        } else {
+            None
+        }
+    }
+
+    /// Stops all network operations and closes active connections.
+    ///
+    /// Each connection first sends what is still queued followed by
+    /// [`Event::Disconnect`], so peers react at once rather than at the heartbeat
+    /// timeout. Waits up to two seconds for that, then signals the remaining
+    /// tasks to terminate by setting the running and connected flags to false.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::network::Network;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let mut network = Network::new("psk".to_string());
+    ///     network.start_host(8080, None).await?;
+    ///
+    ///     // Later...
+    ///     network.stop().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn stop(&mut self) {
+        self.stop_with_reason("shutting down").await;
+    }
+
+    /// Like [`Network::stop`], telling the peers why in [`Event::Disconnect`].
+    pub async fn stop_with_reason(&mut self, reason: &str) {
+        self.running.store(false, Ordering::SeqCst);
+        self.shutdown.send_replace(Some(reason.to_string()));
+
+        let deadline = tokio::time::Instant::now() + SHUTDOWN_TIMEOUT;
+        while self.handlers.load(Ordering::SeqCst) > 0 {
+            if tokio::time::Instant::now() >= deadline {
+                tracing::warn!(
+                    "{} connection(s) did not close within {:?}",
+                    self.handlers.load(Ordering::SeqCst),
+                    SHUTDOWN_TIMEOUT
+                );
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        self.connected.store(false, Ordering::SeqCst);
+        set_status(&self.status, ConnectionStatus::Disconnected);
+        sleep(Duration::from_millis(200)).await; // Give time for tasks to cleanup
+    }
+
+    /// Returns whether the network is currently running and hosting.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::network::Network;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let mut network = Network::new("psk".to_string());
+    ///     assert!(!network.is_running());
+    ///
+    ///     network.start_host(8080, None).await?;
+    ///     assert!(network.is_running());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Returns whether the network is currently connected to a remote host.
     ///
     /// # Examples
     ///
     /// ```no_run
     /// use multishiva::core::network::Network;
-    /// use multishiva::core::events::Event;
     ///
     /// #[tokio::main]
     /// async fn main() -> anyhow::Result<()> {
     ///     let network = Network::new("psk".to_string());
-    ///     // network.send_event(Event::Connect).await?;
+    ///     assert!(!network.is_connected());
+    ///
+    ///     network.connect_to_host("127.0.0.1:8080").await?;
+    ///     assert!(network.is_connected());
     ///     Ok(())
     /// }
     /// ```
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    /// Returns the liveness state of the connection to the host.
     ///
-    /// # Errors
+    /// The host sends a heartbeat every 5 seconds; if nothing is received for
+    /// 15 seconds the connection is dropped and the status becomes
+    /// [`ConnectionStatus::Disconnected`], even if the socket was never closed.
     ///
-    /// Returns an error if the event channel is closed or the receiver has been dropped.
-    pub async fn send_event(&self, event: Event) -> Result<()> {
-        let tx_guard = self.event_tx.read().await;
-        if let Some(tx) = tx_guard.as_ref() {
-            tx.send(event)
-                .await
-                .context("Failed to send event to channel")?;
-        }
-        Ok(())
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use multishiva::core::network::{ConnectionStatus, Network};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let network = Network::new("psk".to_string());
+    ///     network.connect_to_host("127.0.0.1:8080").await?;
+    ///
+    ///     if let ConnectionStatus::Connected { last_heartbeat } = network.connection_status() {
+    ///         println!("Host last seen {:?} ago", last_heartbeat.elapsed());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn connection_status(&self) -> ConnectionStatus {
+        self.status
+            .lock()
+            .map(|s| *s)
+            .unwrap_or(ConnectionStatus::Disconnected)
     }
 
-    /// Receives the next event from the internal event channel.
+    /// Returns the number of currently active client connections.
     ///
-    /// Blocks asynchronously until an event is available or the channel is closed.
-    /// Returns `None` if the event sender has been dropped or the channel is closed.
+    /// This count only applies when hosting. Each time a client connects,
+    /// the count is incremented, and decremented when they disconnect.
     ///
     /// # Examples
     ///
@@ -346,49 +2015,48 @@ impl Network {
     /// #[tokio::main]
     /// async fn main() -> anyhow::Result<()> {
     ///     let mut network = Network::new("psk".to_string());
+    ///     network.start_host(8080, None).await?;
     ///
-    ///     if let Some(event) = network.receive_event().await {
-    ///         println!("Received event: {:?}", event);
-    ///     }
+    ///     println!("Active connections: {}", network.connection_count());
     ///     Ok(())
     /// }
     /// ```
-    pub async fn receive_event(&mut self) -> Option<Event> {
-        let mut rx_guard = self.event_rx.write().await;
-        if let Some(rx) = rx_guard.as_mut() {
-            rx.recv().await
-        } else {
-            None
-        }
+    pub fn connection_count(&self) -> usize {
+        self.connection_count.load(Ordering::SeqCst)
     }
 
-    /// Stops all network operations and closes active connections.
+    /// Subscribes to connection lifecycle events.
     ///
-    /// Signals all running tasks to terminate by setting the running and connected
-    /// flags to false, then waits briefly to allow tasks to clean up gracefully.
+    /// Every subscriber receives the events emitted after it subscribed. A
+    /// subscriber that falls more than 64 events behind skips the oldest ones.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use multishiva::core::network::Network;
+    /// use multishiva::core::network::{ConnectionEvent, Network};
     ///
     /// #[tokio::main]
     /// async fn main() -> anyhow::Result<()> {
     ///     let mut network = Network::new("psk".to_string());
-    ///     network.start_host(8080).await?;
+    ///     let mut events = network.subscribe();
+    ///     network.start_host(8080, None).await?;
     ///
-    ///     // Later...
-    ///     network.stop().await;
+    ///     while let Ok(event) = events.recv().await {
+    ///         if let ConnectionEvent::AgentConnected { name } = event {
+    ///             println!("{} connected", name);
+    ///         }
+    ///     }
     ///     Ok(())
     /// }
     /// ```
-    pub async fn stop(&mut self) {
-        self.running.store(false, Ordering::SeqCst);
-        self.connected.store(false, Ordering::SeqCst);
-        sleep(Duration::from_millis(200)).await; // Give time for tasks to cleanup
+    pub fn subscribe(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.connection_events.subscribe()
     }
 
-    /// Returns whether the network is currently running and hosting.
+    /// Returns the names of the agents registered with this host, sorted.
+    ///
+    /// Only agents that completed the handshake and sent [`Event::Hello`] are
+    /// listed.
     ///
     /// # Examples
     ///
@@ -398,18 +2066,189 @@ impl Network {
     /// #[tokio::main]
     /// async fn main() -> anyhow::Result<()> {
     ///     let mut network = Network::new("psk".to_string());
-    ///     assert!(!network.is_running());
+    ///     network.start_host(8080, None).await?;
     ///
-    ///     network.start_host(8080).await?;
-    ///     assert!(network.is_running());
+    ///     for agent in network.connected_agents() {
+    ///         println!("Connected: {}", agent);
+    ///     }
     ///     Ok(())
     /// }
     /// ```
-    pub fn is_running(&self) -> bool {
-        self.running.load(Ordering::SeqCst)
+    pub fn connected_agents(&self) -> Vec<String> {
+        self.monitor().connected_agents()
     }
 
-    /// Returns whether the network is currently connected to a remote host.
+    /// Returns the agents connected to this host with their address and
+    /// connection time, sorted by name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let network = Network::new("psk".to_string());
+    /// assert!(network.connected_peers().is_empty());
+    /// ```
+    pub fn connected_peers(&self) -> Vec<PeerSummary> {
+        self.monitor().connected_peers()
+    }
+
+    /// Returns a view of this network's state that can outlive this borrow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let network = Network::new("psk".to_string());
+    /// let monitor = network.monitor();
+    /// assert_eq!(monitor.events_forwarded(), 0);
+    /// assert!(monitor.agents().is_empty());
+    /// ```
+    pub fn monitor(&self) -> NetworkMonitor {
+        NetworkMonitor {
+            agents: self.agents.clone(),
+            status: self.status.clone(),
+            connection_count: self.connection_count.clone(),
+            events_forwarded: self.events_forwarded.clone(),
+            queued: self.queued.clone(),
+            stats: self.stats.clone(),
+        }
+    }
+
+    /// Returns the number of events written to peers since the network was created.
+    ///
+    /// Heartbeats and handshake frames are not counted.
+    pub fn events_forwarded(&self) -> u64 {
+        self.events_forwarded.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of events queued by [`Network::send_event`] or
+    /// [`Network::send_event_to_host`] that no connection has taken yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::events::Event;
+    /// use multishiva::core::network::Network;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// let network = Network::new("psk".to_string());
+    /// network.send_event(Event::FocusRelease).await?;
+    /// assert_eq!(network.queue_depth(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// Returns the heartbeat latency and traffic figures of this network.
+    ///
+    /// Latency is the round trip of the heartbeats sent every
+    /// [`NetworkConfig::heartbeat_interval`], so it is unknown until the first
+    /// one was answered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let network = Network::new("psk".to_string());
+    /// let snapshot = network.metrics_snapshot();
+    /// assert_eq!(snapshot.latency_p50_ms, None);
+    /// assert_eq!(snapshot.bytes_sent, 0);
+    /// ```
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.stats.snapshot(self.events_forwarded())
+    }
+
+    /// Returns the events, bytes and heartbeat latency of this network.
+    ///
+    /// The same figures as [`Network::metrics_snapshot`], which `--status`
+    /// shows and the `metrics-json` feature serves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let metrics = Network::new("psk".to_string()).metrics();
+    /// assert_eq!(metrics.events_forwarded, 0);
+    /// assert_eq!(metrics.events_received, 0);
+    /// ```
+    pub fn metrics(&self) -> NetworkMetrics {
+        self.metrics_snapshot()
+    }
+
+    /// Records that an event could not be injected, for [`Network::metrics_snapshot`].
+    pub fn record_injection_failure(&self) {
+        self.stats.record_injection_failure();
+    }
+
+    /// Returns the round trip of the last [`Event::Ping`] the agent `name` answered.
+    ///
+    /// The host pings each agent every second; `None` until the first answer,
+    /// or if no such agent is connected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let network = Network::new("psk".to_string());
+    /// assert_eq!(network.agent_latency("laptop"), None);
+    /// ```
+    pub fn agent_latency(&self, name: &str) -> Option<Duration> {
+        self.agents
+            .lock()
+            .ok()
+            .and_then(|agents| agents.get(name).and_then(|state| state.latency))
+    }
+
+    /// Returns the tracked state of a connected agent.
+    pub fn agent_state(&self, name: &str) -> Option<AgentState> {
+        self.agents
+            .lock()
+            .ok()
+            .and_then(|agents| agents.get(name).copied())
+    }
+
+    /// Marks `name` as the agent holding focus, or none of them with `None`.
+    ///
+    /// At most one agent has focus at a time; every other agent is cleared.
+    pub fn set_focused_agent(&self, name: Option<&str>) {
+        if let Ok(mut agents) = self.agents.lock() {
+            for (agent, state) in agents.iter_mut() {
+                state.has_focus = Some(agent.as_str()) == name;
+            }
+        }
+    }
+
+    /// Returns the agent currently holding focus, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let network = Network::new("psk".to_string());
+    /// // Unknown agents cannot receive focus
+    /// network.set_focused_agent(Some("agent1"));
+    /// assert_eq!(network.focused_agent(), None);
+    /// ```
+    pub fn focused_agent(&self) -> Option<String> {
+        self.monitor().focused_agent()
+    }
+
+    /// Grants focus to the last machine of `path`, through the machines in between.
+    ///
+    /// `path` starts with this machine, as returned by
+    /// [`Topology::shortest_path`](crate::core::topology::Topology::shortest_path).
+    /// A host sends the [`Event::FocusGrantChain`] straight to the target; a
+    /// machine in between that receives it passes it on (see
+    /// [`next_focus_hop`]). `x` and `y` are the entry position on the target.
     ///
     /// # Examples
     ///
@@ -418,45 +2257,375 @@ impl Network {
     ///
     /// #[tokio::main]
     /// async fn main() -> anyhow::Result<()> {
-    ///     let network = Network::new("psk".to_string());
-    ///     assert!(!network.is_connected());
+    ///     let mut network = Network::new("psk".to_string());
+    ///     network.start_host(8080, None).await?;
     ///
-    ///     network.connect_to_host("127.0.0.1:8080").await?;
-    ///     assert!(network.is_connected());
+    ///     let path = vec!["host".to_string(), "agent1".to_string(), "agent2".to_string()];
+    ///     network.transfer_focus_chain(path, 10, 540).await?;
     ///     Ok(())
     /// }
     /// ```
-    pub fn is_connected(&self) -> bool {
-        self.connected.load(Ordering::SeqCst)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` has fewer than two machines or the event
+    /// cannot be queued.
+    pub async fn transfer_focus_chain(&self, path: Vec<String>, x: i32, y: i32) -> Result<()> {
+        if path.len() < 2 {
+            anyhow::bail!("A focus chain needs at least a sender and a target");
+        }
+        tracing::info!("Granting focus along {}", path.join(" → "));
+        self.send_event(Event::FocusGrantChain { path, x, y }).await
+    }
+
+    /// Switches to a new pre-shared key without dropping active connections.
+    ///
+    /// A host sends [`Event::PskRotate`] to its agents, signed with the current
+    /// key, and uses the new key once an agent answers [`Event::PskRotateAck`].
+    /// An agent keeps the new key until the host announces it, then switches
+    /// and acknowledges. New connections always use the key in effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::network::Network;
+    ///
+    /// let network = Network::new("old-psk".to_string());
+    /// network.rotate_psk("new-psk".to_string())?;
+    /// assert!(network.rotate_psk("new-psk".to_string()).is_ok());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `new_psk` is empty or equal to the current key.
+    pub fn rotate_psk(&self, new_psk: String) -> Result<()> {
+        self.psk_rotation().rotate(new_psk)
+    }
+
+    /// Returns a handle that rotates this network's key from other tasks.
+    pub fn psk_rotation(&self) -> PskRotation {
+        PskRotation {
+            psk: self.psk.clone(),
+            pending: self.pending_psk.clone(),
+            control: self.control_events.clone(),
+            running: self.running.clone(),
+            connection_count: self.connection_count.clone(),
+        }
+    }
+}
+
+/// Screen size announced by agents that did not call [`Network::set_agent_info`].
+const DEFAULT_SCREEN_SIZE: (u32, u32) = (1920, 1080);
+
+/// Writes `event` as a length-prefixed MessagePack frame.
+async fn write_frame<W: AsyncWriteExt + Unpin>(writer: &mut W, event: &Event) -> Result<usize> {
+    let data = rmp_serde::to_vec(event).context("Failed to serialize event")?;
+    writer.write_all(&(data.len() as u32).to_be_bytes()).await?;
+    writer.write_all(&data).await?;
+    Ok(4 + data.len())
+}
+
+/// Binds a listener on each address of `bind_address`.
+///
+/// With port 0, the first listener picks a free port which the others reuse,
+/// so the host has a single port to advertise.
+async fn bind_listeners(bind_address: &BindAddress, port: u16) -> Result<Vec<TcpListener>> {
+    let mut port = port;
+    let mut listeners = Vec::new();
+    for mut addr in bind_address.socket_addrs(port)? {
+        addr.set_port(port);
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind to {}", addr))?;
+        tracing::debug!("Bound to {}", addr);
+        port = listener.local_addr()?.port();
+        listeners.push(listener);
+    }
+    Ok(listeners)
+}
+
+/// Reads the agent's `Event::Hello`, skipping any heartbeat sent before it.
+async fn read_hello(stream: &mut TcpStream, timeout: Duration) -> Result<(String, (u32, u32))> {
+    loop {
+        let mut len_buf = [0u8; 4];
+        tokio::time::timeout(timeout, stream.read_exact(&mut len_buf))
+            .await
+            .context("Timed out waiting for Hello")??;
+        let len = u32::from_be_bytes(len_buf) as usize;
+        if len == 0 {
+            continue;
+        }
+
+        let mut data = vec![0u8; len];
+        stream.read_exact(&mut data).await?;
+        return match rmp_serde::from_slice::<Event>(&data)? {
+            Event::Hello {
+                name,
+                screen_width,
+                screen_height,
+            } => Ok((name, (screen_width, screen_height))),
+            other => anyhow::bail!("Expected Hello as first message, got {:?}", other),
+        };
+    }
+}
+
+/// Updates `last_seen` of a registered agent.
+/// Adds an agent to the registry unless its name is taken.
+///
+/// Returns the address of the agent already registered under `name`, if any.
+fn register_agent(
+    agents: &std::sync::Mutex<HashMap<String, AgentState>>,
+    name: &str,
+    screen_size: (u32, u32),
+    address: SocketAddr,
+) -> Option<SocketAddr> {
+    let mut registry = agents.lock().ok()?;
+    if let Some(existing) = registry.get(name) {
+        return Some(existing.address);
+    }
+    let now = Instant::now();
+    registry.insert(
+        name.to_string(),
+        AgentState {
+            has_focus: false,
+            last_seen: now,
+            screen_size,
+            address,
+            connected_at: now,
+            latency: None,
+            events_sent: 0,
+            events_dropped: 0,
+            bytes_sent: 0,
+        },
+    );
+    None
+}
+
+/// Waits until [`Network::stop`] sets a shutdown reason.
+///
+/// Never returns if the network is dropped without being stopped.
+/// Counts an event sent to a send queue.
+fn count_queued(queued: &AtomicUsize) {
+    let depth = queued.fetch_add(1, Ordering::SeqCst) + 1;
+    metrics::set_queue_depth(depth);
+}
+
+/// Counts a bulk event taken from a send queue; see also [`count_dequeued`].
+fn count_bulk_dequeued(bulk_queued: &AtomicUsize) {
+    let depth = bulk_queued
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+            Some(n.saturating_sub(1))
+        })
+        .map_or(0, |n| n.saturating_sub(1));
+    metrics::set_bulk_queue_depth(depth);
+}
+
+/// Counts an event taken from a send queue.
+fn count_dequeued(queued: &AtomicUsize) {
+    let depth = queued
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+            Some(n.saturating_sub(1))
+        })
+        .map_or(0, |n| n.saturating_sub(1));
+    metrics::set_queue_depth(depth);
+}
+
+async fn shutdown_requested(shutdown: &mut watch::Receiver<Option<String>>) {
+    if shutdown.wait_for(Option::is_some).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Removes `name` from the registry if it is still the connection from `address`.
+fn unregister_agent(
+    agents: &std::sync::Mutex<HashMap<String, AgentState>>,
+    name: &str,
+    address: SocketAddr,
+) {
+    if let Ok(mut registry) = agents.lock() {
+        if registry
+            .get(name)
+            .is_some_and(|state| state.address == address)
+        {
+            registry.remove(name);
+        }
+    }
+}
+
+/// Picks the connection a queued host event is written to.
+///
+/// A [`Event::FocusGrant`] goes to its target and a [`Event::FocusGrantChain`]
+/// to the last machine of its path; anything else goes to the focused agent.
+/// Returns `None` when that agent is not connected, or no agent has focus,
+/// so the connection reading the queue sends the event itself.
+fn route_event(
+    event: &Event,
+    agents: &std::sync::Mutex<HashMap<String, AgentState>>,
+    writers: &std::sync::Mutex<HashMap<String, (SocketAddr, SharedWriter)>>,
+) -> Option<(String, SharedWriter)> {
+    let name = match event {
+        Event::FocusGrant { target, .. } => target.clone(),
+        Event::FocusGrantChain { path, .. } => path.last()?.clone(),
+        _ => agents
+            .lock()
+            .ok()?
+            .iter()
+            .find(|(_, state)| state.has_focus)
+            .map(|(name, _)| name.clone())?,
+    };
+    let writer = writers.lock().ok()?.get(&name)?.1.clone();
+    Some((name, writer))
+}
+
+/// Fires once nothing was received from a peer for `timeout`.
+///
+/// Clones share the last-seen time: the receive loop calls
+/// [`touch`](Self::touch) on every frame while another task awaits
+/// [`expired`](Self::expired).
+#[derive(Debug, Clone)]
+struct WatchdogTimer {
+    last_seen: Arc<std::sync::Mutex<tokio::time::Instant>>,
+    timeout: Duration,
+}
+
+impl WatchdogTimer {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            last_seen: Arc::new(std::sync::Mutex::new(tokio::time::Instant::now())),
+            timeout,
+        }
+    }
+
+    /// Records that the peer was heard from just now.
+    fn touch(&self) {
+        if let Ok(mut last_seen) = self.last_seen.lock() {
+            *last_seen = tokio::time::Instant::now();
+        }
+    }
+
+    fn deadline(&self) -> tokio::time::Instant {
+        let last_seen = self
+            .last_seen
+            .lock()
+            .map(|last_seen| *last_seen)
+            .unwrap_or_else(|_| tokio::time::Instant::now());
+        last_seen + self.timeout
+    }
+
+    /// Waits until the peer has been silent for the whole timeout.
+    async fn expired(&self) {
+        loop {
+            let deadline = self.deadline();
+            if tokio::time::Instant::now() >= deadline {
+                return;
+            }
+            tokio::time::sleep_until(deadline).await;
+        }
+    }
+}
+
+fn touch_agent(agents: &std::sync::Mutex<HashMap<String, AgentState>>, name: &str) {
+    if let Some(state) = agents.lock().ok().as_mut().and_then(|a| a.get_mut(name)) {
+        state.last_seen = Instant::now();
+    }
+}
+
+/// Counts an event of `bytes` written to a registered agent.
+fn count_agent_sent(
+    agents: &std::sync::Mutex<HashMap<String, AgentState>>,
+    name: &str,
+    bytes: usize,
+) {
+    if let Some(state) = agents.lock().ok().as_mut().and_then(|a| a.get_mut(name)) {
+        state.events_sent += 1;
+        state.bytes_sent += bytes as u64;
+    }
+}
+
+/// Counts a mouse move dropped instead of being written to a registered agent.
+fn count_agent_dropped(agents: &std::sync::Mutex<HashMap<String, AgentState>>, name: &str) {
+    if let Some(state) = agents.lock().ok().as_mut().and_then(|a| a.get_mut(name)) {
+        state.events_dropped += 1;
+    }
+}
+
+fn set_status(status: &std::sync::Mutex<ConnectionStatus>, new_status: ConnectionStatus) {
+    if let Ok(mut current) = status.lock() {
+        *current = new_status;
+    }
+}
+
+/// Writes an [`Event::Heartbeat`] every `interval` until the peer is gone.
+async fn send_heartbeats(
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+    interval: Duration,
+    stats: Arc<NetworkStats>,
+) {
+    let mut heartbeat_interval = tokio::time::interval(interval);
+    heartbeat_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        heartbeat_interval.tick().await;
+        let heartbeat = Event::Heartbeat {
+            sent_at_ms: stats.now_ms(),
+        };
+        match write_frame(&mut *writer.lock().await, &heartbeat).await {
+            Ok(bytes) => stats.record_sent(bytes),
+            Err(_) => {
+                tracing::warn!("Failed to send heartbeat, disconnected");
+                break;
+            }
+        }
     }
+}
 
-    /// Returns the number of currently active client connections.
-    ///
-    /// This count only applies when hosting. Each time a client connects,
-    /// the count is incremented, and decremented when they disconnect.
-    ///
-    /// # Examples
-    ///
-    /// ```no_run
-    /// use multishiva::core::network::Network;
-    ///
-    /// #[tokio::main]
-    /// async fn main() -> anyhow::Result<()> {
-    ///     let mut network = Network::new("psk".to_string());
-    ///     network.start_host(8080).await?;
-    ///
-    ///     println!("Active connections: {}", network.connection_count());
-    ///     Ok(())
-    /// }
-    /// ```
-    pub fn connection_count(&self) -> usize {
-        self.connection_count.load(Ordering::SeqCst)
+/// Writes an [`Event::Ping`] every [`PING_INTERVAL`] until the agent is gone.
+async fn send_pings(writer: Arc<Mutex<OwnedWriteHalf>>, stats: Arc<NetworkStats>) {
+    let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+    ping_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    for seq in 0.. {
+        ping_interval.tick().await;
+        let ping = Event::Ping {
+            seq,
+            sent_at_ms: stats.now_ms(),
+        };
+        match write_frame(&mut *writer.lock().await, &ping).await {
+            Ok(bytes) => stats.record_sent(bytes),
+            Err(_) => break,
+        }
+    }
+}
+
+/// Stores the round trip of the ping an agent's [`Event::Pong`] answers.
+fn record_agent_latency(agents: &AgentRegistry, name: &str, stats: &NetworkStats, sent_at_ms: u64) {
+    let rtt = Duration::from_millis(stats.now_ms().saturating_sub(sent_at_ms));
+    if rtt > LATENCY_WARNING {
+        tracing::warn!("High latency to agent '{}': {:?}", name, rtt);
+    } else {
+        tracing::trace!("Ping round trip to {}: {:?}", name, rtt);
+    }
+    if let Some(state) = agents.lock().ok().as_mut().and_then(|a| a.get_mut(name)) {
+        state.latency = Some(rtt);
     }
 }
 
+/// Records the round trip of the heartbeat an [`Event::HeartbeatAck`] answers.
+fn record_round_trip(stats: &NetworkStats, sent_at_ms: u64, peer: &str) {
+    let rtt = Duration::from_millis(stats.now_ms().saturating_sub(sent_at_ms));
+    tracing::trace!("Heartbeat round trip to {}: {:?}", peer, rtt);
+    stats.record_latency(rtt);
+}
+
+/// Authenticates a connection with the pre-shared key.
+///
+/// The server checks the client's key against `peer_psks` when the client's
+/// machine name has its own key there, and against `psk` otherwise.
 async fn perform_psk_handshake(
     stream: &mut TcpStream,
     psk: &str,
+    peer_psks: &HashMap<String, String>,
     is_server: bool,
 ) -> Result<String> {
     let psk_hash = compute_psk_hash(psk);
@@ -487,7 +2656,11 @@ async fn perform_psk_handshake(
             .to_string();
         let received_hash = std::str::from_utf8(parts[1]).context("Invalid PSK hash")?;
 
-        if received_hash != psk_hash {
+        let expected_hash = match peer_psks.get(&machine_name) {
+            Some(peer_psk) => compute_psk_hash(peer_psk),
+            None => psk_hash,
+        };
+        if received_hash != expected_hash {
             anyhow::bail!("PSK mismatch");
         }
 
@@ -521,7 +2694,21 @@ async fn perform_psk_handshake(
     }
 }
 
-fn compute_psk_hash(psk: &str) -> String {
+/// Computes the SHA-256 hex digest of a Pre-Shared Key.
+///
+/// The same hash is used in the PSK handshake and advertised over mDNS so
+/// agents can skip hosts configured with a different key.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::compute_psk_hash;
+///
+/// assert_eq!(compute_psk_hash("secret"), compute_psk_hash("secret"));
+/// assert_ne!(compute_psk_hash("secret"), compute_psk_hash("other"));
+/// assert_eq!(compute_psk_hash("secret").len(), 64);
+/// ```
+pub fn compute_psk_hash(psk: &str) -> String {
     // Use SHA-256 for cryptographically secure hashing
     use sha2::{Digest, Sha256};
 
@@ -531,65 +2718,527 @@ fn compute_psk_hash(psk: &str) -> String {
     hex::encode(result)
 }
 
+/// Checks that the host at `addr` accepts `psk`, then closes the connection.
+///
+/// Only the PSK handshake is performed: the host never sees an agent
+/// register, and no fingerprint is checked or stored.
+///
+/// # Errors
+///
+/// Returns an error if the host cannot be reached within `timeout` or
+/// rejects the PSK.
+///
+/// # Examples
+///
+/// ```no_run
+/// use multishiva::core::network::test_connection;
+/// use std::time::Duration;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let check = test_connection("192.168.1.10:53421", "secret", Duration::from_secs(5)).await?;
+/// println!("Handshake took {}ms", check.handshake_ms);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn test_connection(addr: &str, psk: &str, timeout: Duration) -> Result<ConnectionCheck> {
+    let started = Instant::now();
+    let handshake = async {
+        let mut stream = TcpStream::connect(addr)
+            .await
+            .with_context(|| format!("Failed to connect to {}", addr))?;
+        perform_psk_handshake(&mut stream, psk, &HashMap::new(), false)
+            .await
+            .context("PSK handshake failed")
+    };
+    tokio::time::timeout(timeout, handshake)
+        .await
+        .map_err(|_| anyhow::anyhow!("No answer from {} within {:?}", addr, timeout))??;
+
+    Ok(ConnectionCheck {
+        address: addr.to_string(),
+        handshake_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// What a machine named `me` does with an [`Event::FocusGrantChain`] along `path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FocusHop {
+    /// `me` is the target and takes focus.
+    Target,
+    /// `me` is in between and passes the grant on to the named machine.
+    Relay(String),
+    /// `me` is not on the path, or is its first machine.
+    NotOnPath,
+}
+
+/// Returns what `me` does with a focus grant travelling along `path`.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::{next_focus_hop, FocusHop};
+///
+/// let path = vec!["host".to_string(), "agent1".to_string(), "agent2".to_string()];
+/// assert_eq!(next_focus_hop(&path, "agent1"), FocusHop::Relay("agent2".to_string()));
+/// assert_eq!(next_focus_hop(&path, "agent2"), FocusHop::Target);
+/// assert_eq!(next_focus_hop(&path, "agent3"), FocusHop::NotOnPath);
+/// ```
+pub fn next_focus_hop(path: &[String], me: &str) -> FocusHop {
+    match path.iter().skip(1).position(|machine| machine == me) {
+        Some(index) => match path.get(index + 2) {
+            Some(next) => FocusHop::Relay(next.clone()),
+            None => FocusHop::Target,
+        },
+        None => FocusHop::NotOnPath,
+    }
+}
+
+/// Signs the hash of a new PSK with the current one, for [`Event::PskRotate`].
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::network::{compute_psk_hash, sign_psk_rotation, verify_psk_rotation};
+///
+/// let new_hash = compute_psk_hash("new-psk");
+/// let signature = sign_psk_rotation("old-psk", &new_hash)?;
+/// assert!(verify_psk_rotation("old-psk", &new_hash, &signature));
+/// assert!(!verify_psk_rotation("other-psk", &new_hash, &signature));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the HMAC cannot be keyed with `psk`.
+pub fn sign_psk_rotation(psk: &str, new_psk_hash: &str) -> Result<String> {
+    use hmac::Mac;
+
+    let mut mac = HmacSha256::new_from_slice(psk.as_bytes()).context("Invalid PSK")?;
+    mac.update(new_psk_hash.as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Checks that `signature` is the HMAC of `new_psk_hash` keyed with `psk`.
+pub fn verify_psk_rotation(psk: &str, new_psk_hash: &str, signature: &str) -> bool {
+    use hmac::Mac;
+
+    let (Ok(mut mac), Ok(received)) = (
+        HmacSha256::new_from_slice(psk.as_bytes()),
+        hex::decode(signature),
+    ) else {
+        return false;
+    };
+    mac.update(new_psk_hash.as_bytes());
+    mac.verify_slice(&received).is_ok()
+}
+
+fn peer_psk(peer_psks: &std::sync::RwLock<HashMap<String, String>>, peer: &str) -> Option<String> {
+    peer_psks.read().ok()?.get(peer).cloned()
+}
+
+fn read_psk(psk: &std::sync::RwLock<String>) -> String {
+    psk.read().map(|psk| psk.clone()).unwrap_or_default()
+}
+
+fn write_psk(psk: &std::sync::RwLock<String>, new_psk: String) {
+    if let Ok(mut psk) = psk.write() {
+        *psk = new_psk;
+    }
+}
+
+fn set_pending_psk(pending: &std::sync::Mutex<Option<String>>, new_psk: Option<String>) {
+    if let Ok(mut pending) = pending.lock() {
+        *pending = new_psk;
+    }
+}
+
+/// Host side: switches to the pending key once an agent acknowledged it.
+fn confirm_psk_rotation(psk: &SharedPsk, pending: &PendingPsk, new_psk_hash: &str, agent: &str) {
+    let Ok(mut pending) = pending.lock() else {
+        return;
+    };
+    match pending.take() {
+        Some(new_psk) if compute_psk_hash(&new_psk) == new_psk_hash => {
+            write_psk(psk, new_psk);
+            tracing::info!("🔑 Agent '{}' switched to the new PSK, PSK rotated", agent);
+        }
+        other => {
+            *pending = other;
+            if compute_psk_hash(&read_psk(psk)) == new_psk_hash {
+                tracing::info!("🔑 Agent '{}' switched to the new PSK", agent);
+            } else {
+                tracing::warn!("Agent '{}' acknowledged an unknown PSK", agent);
+            }
+        }
+    }
+}
+
+/// Agent side: switches to the pending key if the host's announcement is genuine.
+///
+/// Returns the new key on success.
+fn accept_psk_rotation(
+    psk: &SharedPsk,
+    pending: &PendingPsk,
+    new_psk_hash: &str,
+    signature: &str,
+) -> Option<String> {
+    if !verify_psk_rotation(&read_psk(psk), new_psk_hash, signature) {
+        tracing::warn!("Rejected PSK rotation with an invalid signature");
+        return None;
+    }
+
+    let mut pending = pending.lock().ok()?;
+    match pending.take() {
+        Some(new_psk) if compute_psk_hash(&new_psk) == new_psk_hash => {
+            write_psk(psk, new_psk.clone());
+            tracing::info!("🔑 Host rotated the PSK, switched to the new key");
+            Some(new_psk)
+        }
+        other => {
+            *pending = other;
+            tracing::warn!(
+                "Host is rotating to a PSK this agent does not know; \
+                 run `multishiva rotate-psk` on this agent with the new key"
+            );
+            None
+        }
+    }
+}
+
+/// Writes frames broadcast to all agents, such as [`Event::PskRotate`].
+async fn send_control_events(
+    mut control: broadcast::Receiver<Event>,
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+    stats: Arc<NetworkStats>,
+) {
+    loop {
+        match control.recv().await {
+            Ok(event) => match write_frame(&mut *writer.lock().await, &event).await {
+                Ok(bytes) => stats.record_sent(bytes),
+                Err(_) => {
+                    tracing::warn!("Failed to send control event, client disconnected");
+                    break;
+                }
+            },
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!("Skipped {} control event(s)", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Shared state of a host-side client connection.
+struct HostClient {
+    /// Address the client connected from.
+    addr: SocketAddr,
+    agents: AgentRegistry,
+    writers: ClientWriters,
+    events: broadcast::Sender<ConnectionEvent>,
+    events_forwarded: Arc<AtomicU64>,
+    psk: SharedPsk,
+    pending_psk: PendingPsk,
+    peer_psks: PeerPsks,
+    /// Agents let in, when restricted.
+    allowed: Option<AgentAllowlist>,
+    /// Frames sent to every agent.
+    control: broadcast::Receiver<Event>,
+    /// Becomes the reason for [`Event::Disconnect`] when the host stops.
+    shutdown: watch::Receiver<Option<String>>,
+    /// Events waiting in the send queue, see [`Network::queue_depth`].
+    queued: Arc<AtomicUsize>,
+    stats: Arc<NetworkStats>,
+    heartbeat_interval: Duration,
+    /// Time the client gets to send [`Event::Hello`].
+    hello_timeout: Duration,
+    /// Silence after which the watchdog drops the client.
+    agent_timeout: Duration,
+    /// Events a second sent to one agent before mouse moves are dropped.
+    max_events_per_second: u32,
+    /// Name of the host, refused as an agent name.
+    self_name: Option<String>,
+}
+
 async fn handle_client(
     mut stream: TcpStream,
-    psk: String,
-    event_rx: Arc<RwLock<Option<mpsc::Receiver<Event>>>>,
+    event_rx: Arc<RwLock<Option<SendLanes>>>,
     input_event_tx: Arc<Option<mpsc::Sender<Event>>>,
+    client: HostClient,
 ) -> Result<()> {
+    let HostClient {
+        addr,
+        agents,
+        writers,
+        events: connection_events,
+        events_forwarded,
+        psk,
+        pending_psk,
+        peer_psks,
+        allowed,
+        control,
+        mut shutdown,
+        queued,
+        stats,
+        heartbeat_interval,
+        hello_timeout,
+        agent_timeout,
+        max_events_per_second,
+        self_name,
+    } = client;
+
     // Perform PSK handshake and get machine name
-    let machine_name = match perform_psk_handshake(&mut stream, &psk, true).await {
-        Ok(name) => name,
+    let current_psk = read_psk(&psk);
+    let peer_psks = peer_psks.read().map(|p| p.clone()).unwrap_or_default();
+    let machine_name =
+        match perform_psk_handshake(&mut stream, &current_psk, &peer_psks, true).await {
+            Ok(name) => name,
+            Err(e) => {
+                tracing::warn!("PSK handshake failed: {}", e);
+                metrics::record_connection_error();
+                return Err(e);
+            }
+        };
+
+    tracing::info!("✓ Client '{}' authenticated successfully", machine_name);
+    let allowed_before_hello = allowed.as_ref().is_none_or(|allowed| {
+        allowed.allows_address(addr.ip()) || allowed.allows_name(&machine_name)
+    });
+
+    // The agent introduces itself before sending anything else
+    let (agent_name, screen_size) = match read_hello(&mut stream, hello_timeout).await {
+        Ok(hello) => hello,
         Err(e) => {
-            tracing::warn!("PSK handshake failed: {}", e);
+            tracing::warn!("No Hello from '{}': {}", machine_name, e);
+            metrics::record_connection_error();
             return Err(e);
         }
     };
-
-    tracing::info!("✓ Client '{}' authenticated successfully", machine_name);
+    if !allowed_before_hello && !allowed.as_ref().is_some_and(|a| a.allows_name(&agent_name)) {
+        tracing::warn!(
+            "Refusing agent '{}' ({}) from {}: not in allowed_agents",
+            agent_name,
+            machine_name,
+            addr
+        );
+        metrics::record_connection_error();
+        let refused = Event::Refused {
+            reason: NOT_ALLOWED_REASON.to_string(),
+        };
+        let _ = write_frame(&mut stream, &refused).await;
+        let _ = stream.shutdown().await;
+        anyhow::bail!("Agent '{}' is not in allowed_agents", agent_name);
+    }
+    if self_name
+        .as_deref()
+        .is_some_and(|own| own == agent_name || own == machine_name)
+    {
+        tracing::warn!(
+            "Refusing agent '{}' ({}) from {}: it has the host's own name",
+            agent_name,
+            machine_name,
+            addr
+        );
+        metrics::record_connection_error();
+        let refused = Event::Refused {
+            reason: SELF_CONNECTION_REASON.to_string(),
+        };
+        let _ = write_frame(&mut stream, &refused).await;
+        let _ = stream.shutdown().await;
+        anyhow::bail!("Agent '{}' has the host's own name", agent_name);
+    }
+    if let Some(existing) = register_agent(&agents, &agent_name, screen_size, addr) {
+        tracing::warn!(
+            "Refusing agent '{}' from {}: already connected from {}",
+            agent_name,
+            addr,
+            existing
+        );
+        metrics::record_connection_error();
+        let refused = Event::Refused {
+            reason: DUPLICATE_NAME_REASON.to_string(),
+        };
+        let _ = write_frame(&mut stream, &refused).await;
+        let _ = stream.shutdown().await;
+        anyhow::bail!("Agent name '{}' is already connected", agent_name);
+    }
+    tracing::info!(
+        "✓ Agent '{}' registered ({}x{})",
+        agent_name,
+        screen_size.0,
+        screen_size.1
+    );
+    let _ = connection_events.send(ConnectionEvent::AgentConnected {
+        name: agent_name.clone(),
+    });
 
     // Split stream for concurrent read/write (takes ownership)
-    let (mut read_half, mut write_half) = stream.into_split();
+    let (mut read_half, write_half) = stream.into_split();
+    let writer = Arc::new(Mutex::new(write_half));
+    if let Ok(mut writers) = writers.lock() {
+        writers.insert(agent_name.clone(), (addr, writer.clone()));
+    }
+    let send_writers = writers.clone();
+    let send_agents = agents.clone();
+
+    let target = agent_name.clone();
+    let source = agent_name.clone();
+    let receive_agents = agents.clone();
+    let receive_events = connection_events.clone();
+    let watchdog = WatchdogTimer::new(agent_timeout);
+    let receive_watchdog = watchdog.clone();
+
+    // Spawn task dropping the client once it has been silent for too long
+    let watchdog_events = connection_events.clone();
+    let watchdog_agents = agents.clone();
+    let watchdog_name = agent_name.clone();
+    let watchdog_input_tx = input_event_tx.clone();
+    let watchdog_task = tokio::spawn(async move {
+        watchdog.expired().await;
+        tracing::warn!(
+            "Agent '{}' silent for {:?}, dropping it",
+            watchdog_name,
+            agent_timeout
+        );
+        metrics::record_connection_error();
+        let _ = watchdog_events.send(ConnectionEvent::HeartbeatTimeout {
+            name: watchdog_name.clone(),
+        });
+        // Unregister first, so the host sees a focused agent is gone and takes focus back
+        unregister_agent(&watchdog_agents, &watchdog_name, addr);
+        if let Some(ref tx) = *watchdog_input_tx {
+            let disconnect = Event::Disconnect {
+                reason: AGENT_TIMEOUT_REASON.to_string(),
+            };
+            let _ = tx.send(disconnect).await;
+        }
+    });
+
+    // Spawn task to keep the client aware that the host is alive
+    let heartbeat_task = tokio::spawn(send_heartbeats(
+        writer.clone(),
+        heartbeat_interval,
+        stats.clone(),
+    ));
 
-    // Spawn task to send events from host to client
+    // Spawn task to measure the agent's latency
+    let ping_task = tokio::spawn(send_pings(writer.clone(), stats.clone()));
+
+    // Spawn task to send frames addressed to every agent
+    let control_task = tokio::spawn(send_control_events(control, writer.clone(), stats.clone()));
+    let receive_writer = writer.clone();
+    let receive_stats = stats.clone();
+
+    // Spawn task to send events from host to client, and say goodbye on shutdown
     let send_task = tokio::spawn(async move {
-        let mut rx_guard = event_rx.write().await;
-        if let Some(rx) = rx_guard.as_mut() {
-            while let Some(event) = rx.recv().await {
-                tracing::debug!("Sending event to client: {:?}", event);
-
-                // Serialize event using MessagePack
-                match rmp_serde::to_vec(&event) {
-                    Ok(data) => {
-                        // Send length prefix (4 bytes) + data
-                        let len = data.len() as u32;
-                        if write_half.write_all(&len.to_be_bytes()).await.is_err() {
-                            tracing::warn!("Failed to write event length, client disconnected");
-                            break;
+        // One throttle per recipient, as this connection may be the one
+        // reading the queue for every agent
+        let throttles = std::sync::Mutex::new(HashMap::<String, ForwardThrottle>::new());
+        let forward = |event: Event| {
+            // Events addressed to another agent are written to its connection
+            let (writer, recipient, own) = match route_event(&event, &send_agents, &send_writers) {
+                Some((name, other)) if name != target => (other, name, false),
+                _ => (writer.clone(), target.clone(), true),
+            };
+            let now = Instant::now();
+            let absolute_move = matches!(event, Event::MouseMove { .. });
+            let events = match throttles.lock() {
+                Ok(mut throttles) => {
+                    let throttle = throttles
+                        .entry(recipient.clone())
+                        .or_insert_with(|| ForwardThrottle::new(max_events_per_second, now));
+                    let events = throttle.admit(event, now);
+                    if let Some(dropped) = throttle.warning(now) {
+                        tracing::warn!(
+                            "Throttling events to '{}': dropped {} mouse moves beyond {} \
+                             events/s (is input looping between two hosts?)",
+                            recipient,
+                            dropped,
+                            max_events_per_second
+                        );
+                    }
+                    events
+                }
+                Err(_) => vec![event],
+            };
+            let events_forwarded = events_forwarded.clone();
+            let stats = stats.clone();
+            let agents = send_agents.clone();
+            async move {
+                // Held relative motion is sent later, not dropped
+                if events.is_empty() && absolute_move {
+                    metrics::record_event_dropped(&recipient);
+                    count_agent_dropped(&agents, &recipient);
+                }
+                for event in events {
+                    tracing::debug!("Sending event to '{}': {:?}", recipient, event);
+                    match write_frame(&mut *writer.lock().await, &event).await {
+                        Ok(bytes) => {
+                            stats.record_sent(bytes);
+                            count_agent_sent(&agents, &recipient, bytes);
                         }
-                        if write_half.write_all(&data).await.is_err() {
-                            tracing::warn!("Failed to write event data, client disconnected");
-                            break;
+                        Err(e) => {
+                            tracing::warn!("Failed to write event to '{}': {}", recipient, e);
+                            // Only losing this client's own connection ends the task
+                            return !own;
+                        }
+                    }
+                    metrics::record_event_sent(&recipient);
+                    events_forwarded.fetch_add(1, Ordering::SeqCst);
+                }
+                true
+            }
+        };
+
+        // Only one connection at a time reads the event queue
+        let mut rx_guard = tokio::select! {
+            guard = event_rx.write() => Some(guard),
+            _ = shutdown_requested(&mut shutdown) => None,
+        };
+        if let Some(rx) = rx_guard.as_deref_mut().and_then(Option::as_mut) {
+            loop {
+                tokio::select! {
+                    event = rx.recv() => {
+                        let Some(event) = event else {
+                            return;
+                        };
+                        count_dequeued(&queued);
+                        if !forward(event).await {
+                            return;
                         }
                     }
-                    Err(e) => {
-                        tracing::error!("Failed to serialize event: {}", e);
+                    _ = shutdown_requested(&mut shutdown) => {
+                        // Flush what was queued before the shutdown
+                        while let Some(event) = rx.try_recv() {
+                            count_dequeued(&queued);
+                            if !forward(event).await {
+                                return;
+                            }
+                        }
+                        break;
                     }
                 }
             }
         }
+
+        let reason = shutdown.borrow().clone().unwrap_or_default();
+        if let Ok(bytes) =
+            write_frame(&mut *writer.lock().await, &Event::Disconnect { reason }).await
+        {
+            stats.record_sent(bytes);
+        }
         tracing::info!("Send task ending for client");
     });
 
-    // Receive events from client (including heartbeats)
+    // Receive events from client (including heartbeats); the watchdog handles silence
     let receive_task = tokio::spawn(async move {
         loop {
             let mut len_buf = [0u8; 4];
-            match tokio::time::timeout(Duration::from_secs(15), read_half.read_exact(&mut len_buf))
-                .await
-            {
-                Ok(Ok(_)) => {
+            match read_half.read_exact(&mut len_buf).await {
+                Ok(_) => {
                     let len = u32::from_be_bytes(len_buf) as usize;
+                    receive_watchdog.touch();
+                    touch_agent(&receive_agents, &source);
+                    receive_stats.record_received(4 + len);
 
                     // Length 0 = heartbeat, ignore
                     if len == 0 {
@@ -604,7 +3253,70 @@ async fn handle_client(
                             // Deserialize event
                             match rmp_serde::from_slice::<Event>(&data) {
                                 Ok(event) => {
+                                    match event {
+                                        Event::Heartbeat { sent_at_ms } => {
+                                            let ack = Event::HeartbeatAck { sent_at_ms };
+                                            match write_frame(
+                                                &mut *receive_writer.lock().await,
+                                                &ack,
+                                            )
+                                            .await
+                                            {
+                                                Ok(bytes) => receive_stats.record_sent(bytes),
+                                                Err(e) => {
+                                                    tracing::warn!(
+                                                        "Failed to answer heartbeat: {}",
+                                                        e
+                                                    );
+                                                    break;
+                                                }
+                                            }
+                                            continue;
+                                        }
+                                        Event::HeartbeatAck { sent_at_ms } => {
+                                            record_round_trip(&receive_stats, sent_at_ms, &source);
+                                            continue;
+                                        }
+                                        Event::Pong { sent_at_ms, .. } => {
+                                            record_agent_latency(
+                                                &receive_agents,
+                                                &source,
+                                                &receive_stats,
+                                                sent_at_ms,
+                                            );
+                                            continue;
+                                        }
+                                        _ => {}
+                                    }
                                     tracing::debug!("Received event from agent: {:?}", event);
+                                    metrics::record_event_received(&source);
+                                    receive_stats.record_event_received();
+                                    if let Event::Disconnect { reason } = &event {
+                                        tracing::info!(
+                                            "Agent '{}' disconnected: {}",
+                                            source,
+                                            reason
+                                        );
+                                        // Unregister first, so the host sees the agent is gone
+                                        unregister_agent(&receive_agents, &source, addr);
+                                        let _ = receive_events.send(ConnectionEvent::PeerLeft {
+                                            name: source.clone(),
+                                            reason: reason.clone(),
+                                        });
+                                        if let Some(ref tx) = *input_event_tx {
+                                            let _ = tx.send(event).await;
+                                        }
+                                        break;
+                                    }
+                                    if let Event::PskRotateAck { new_psk_hash } = &event {
+                                        confirm_psk_rotation(
+                                            &psk,
+                                            &pending_psk,
+                                            new_psk_hash,
+                                            &source,
+                                        );
+                                        continue;
+                                    }
                                     // Forward to host's input event loop if available
                                     if let Some(ref tx) = *input_event_tx {
                                         if tx.send(event).await.is_err() {
@@ -623,34 +3335,80 @@ async fn handle_client(
                         }
                     }
                 }
-                Ok(Err(e)) => {
+                Err(e) => {
                     tracing::warn!("Client disconnected: {}", e);
                     break;
                 }
-                Err(_) => {
-                    tracing::warn!("Client heartbeat timeout");
-                    break;
-                }
             }
         }
         tracing::info!("Receive task ending for client");
     });
 
-    // Wait for either task to complete
+    // Wait for any task to complete, then tear down the others
+    let heartbeat_abort = heartbeat_task.abort_handle();
+    let ping_abort = ping_task.abort_handle();
+    let control_abort = control_task.abort_handle();
+    let send_abort = send_task.abort_handle();
+    let receive_abort = receive_task.abort_handle();
+    let watchdog_abort = watchdog_task.abort_handle();
     tokio::select! {
+        _ = watchdog_task => {}
+        _ = heartbeat_task => {}
+        _ = ping_task => {}
+        _ = control_task => {}
         _ = send_task => {}
         _ = receive_task => {}
     }
+    heartbeat_abort.abort();
+    ping_abort.abort();
+    control_abort.abort();
+    send_abort.abort();
+    receive_abort.abort();
+    watchdog_abort.abort();
+
+    unregister_agent(&agents, &agent_name, addr);
+    if let Ok(mut writers) = writers.lock() {
+        if writers
+            .get(&agent_name)
+            .is_some_and(|(address, _)| *address == addr)
+        {
+            writers.remove(&agent_name);
+        }
+    }
+    tracing::info!("Agent '{}' unregistered", agent_name);
+    let _ = connection_events.send(ConnectionEvent::AgentDisconnected { name: agent_name });
 
     Ok(())
 }
 
+/// Shared state of an agent-side connection.
+struct AgentConnection {
+    /// Address of the host, used as the metrics label.
+    host: String,
+    /// Name the host's fingerprint is stored under, see [`Network::host_id`].
+    host_id: String,
+    connected: Arc<AtomicBool>,
+    status: Arc<std::sync::Mutex<ConnectionStatus>>,
+    events: broadcast::Sender<ConnectionEvent>,
+    events_forwarded: Arc<AtomicU64>,
+    psk: SharedPsk,
+    pending_psk: PendingPsk,
+    fingerprint_store: Arc<Mutex<FingerprintStore>>,
+    /// Queue of frames for the host, used to acknowledge a PSK rotation.
+    agent_tx: Arc<RwLock<Option<mpsc::Sender<Event>>>>,
+    /// Becomes the reason for [`Event::Disconnect`] when the agent stops.
+    shutdown: watch::Receiver<Option<String>>,
+    /// Events waiting in the send queue, see [`Network::queue_depth`].
+    queued: Arc<AtomicUsize>,
+    stats: Arc<NetworkStats>,
+    heartbeat_interval: Duration,
+}
+
 async fn handle_connection(
     stream: TcpStream,
-    _psk: String,
-    connected: Arc<AtomicBool>,
+    connection: AgentConnection,
     event_tx: Arc<RwLock<Option<mpsc::Sender<Event>>>>,
-    agent_rx: Arc<RwLock<Option<mpsc::Receiver<Event>>>>,
+    agent_rx: Arc<RwLock<Option<SendLanes>>>,
 ) -> Result<()> {
     tracing::info!("Agent connected to host, bidirectional communication enabled...");
 
@@ -658,12 +3416,32 @@ async fn handle_connection(
     let (mut read_half, mut write_half) = stream.into_split();
 
     // Clone connected for tasks
+    let connected = connection.connected;
     let connected_send = connected.clone();
     let connected_recv = connected.clone();
+    let status = connection.status;
+    let events = connection.events;
+    let receive_events = events.clone();
+    let events_forwarded = connection.events_forwarded;
+    let host = connection.host;
+    let target = host.clone();
+    let source = host.clone();
+    let host_id = connection.host_id;
+    let psk = connection.psk;
+    let pending_psk = connection.pending_psk;
+    let fingerprint_store = connection.fingerprint_store;
+    let agent_tx = connection.agent_tx;
+    let mut shutdown = connection.shutdown;
+    let queued = connection.queued;
+    let ack_queued = queued.clone();
+    let stats = connection.stats;
+    let receive_stats = stats.clone();
+
+    let _ = events.send(ConnectionEvent::AgentConnected { name: host.clone() });
 
     // Task 1: Send events from agent back to host (including heartbeats)
     let send_task = tokio::spawn(async move {
-        let mut heartbeat_interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+        let mut heartbeat_interval = tokio::time::interval(connection.heartbeat_interval);
         heartbeat_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
         let mut rx_guard = agent_rx.write().await;
@@ -671,10 +3449,15 @@ async fn handle_connection(
         loop {
             tokio::select! {
                 _ = heartbeat_interval.tick() => {
-                    // Send heartbeat (4 zero bytes = length 0)
-                    if write_half.write_all(&[0u8; 4]).await.is_err() {
-                        tracing::warn!("Failed to send heartbeat, disconnected");
-                        break;
+                    let heartbeat = Event::Heartbeat {
+                        sent_at_ms: stats.now_ms(),
+                    };
+                    match write_frame(&mut write_half, &heartbeat).await {
+                        Ok(bytes) => stats.record_sent(bytes),
+                        Err(_) => {
+                            tracing::warn!("Failed to send heartbeat, disconnected");
+                            break;
+                        }
                     }
                 }
                 Some(event) = async {
@@ -684,6 +3467,7 @@ async fn handle_connection(
                         None
                     }
                 } => {
+                    count_dequeued(&queued);
                     tracing::debug!("Sending event to host: {:?}", event);
 
                     // Serialize and send event
@@ -698,12 +3482,41 @@ async fn handle_connection(
                                 tracing::warn!("Failed to write event data, disconnected");
                                 break;
                             }
+                            stats.record_sent(4 + data.len());
+                            // Heartbeat and ping answers are not events
+                            if !matches!(event, Event::HeartbeatAck { .. } | Event::Pong { .. }) {
+                                metrics::record_event_sent(&target);
+                                events_forwarded.fetch_add(1, Ordering::SeqCst);
+                            }
                         }
                         Err(e) => {
                             tracing::error!("Failed to serialize event: {}", e);
                         }
                     }
                 }
+                _ = shutdown_requested(&mut shutdown) => {
+                    // Flush what was queued before the shutdown, then say goodbye
+                    let mut frames = Vec::new();
+                    if let Some(ref mut r) = *rx_guard {
+                        while let Some(event) = r.try_recv() {
+                            count_dequeued(&queued);
+                            frames.push(event);
+                        }
+                    }
+                    let reason = shutdown.borrow().clone().unwrap_or_default();
+                    frames.push(Event::Disconnect { reason });
+                    for event in &frames {
+                        match write_frame(&mut write_half, event).await {
+                            Ok(bytes) => stats.record_sent(bytes),
+                            Err(e) => {
+                                tracing::warn!("Failed to flush event to host: {}", e);
+                                break;
+                            }
+                        }
+                    }
+                    let _ = write_half.shutdown().await;
+                    break;
+                }
             }
 
             if !connected_send.load(Ordering::SeqCst) {
@@ -724,9 +3537,20 @@ async fn handle_connection(
 
                 // Read length prefix (4 bytes)
                 let mut len_buf = [0u8; 4];
-                match read_half.read_exact(&mut len_buf).await {
-                    Ok(_) => {
+                match tokio::time::timeout(HEARTBEAT_TIMEOUT, read_half.read_exact(&mut len_buf))
+                    .await
+                {
+                    Ok(Ok(_)) => {
                         let len = u32::from_be_bytes(len_buf) as usize;
+                        receive_stats.record_received(4 + len);
+
+                        // Any frame proves the host is alive
+                        set_status(
+                            &status,
+                            ConnectionStatus::Connected {
+                                last_heartbeat: Instant::now(),
+                            },
+                        );
 
                         // Length 0 = heartbeat, ignore
                         if len == 0 {
@@ -741,7 +3565,110 @@ async fn handle_connection(
                                 // Deserialize event
                                 match rmp_serde::from_slice::<Event>(&data) {
                                     Ok(event) => {
+                                        match event {
+                                            Event::Heartbeat { sent_at_ms } => {
+                                                // The send task owns the socket
+                                                if let Some(ack_tx) = agent_tx.read().await.as_ref()
+                                                {
+                                                    if let Ok(permit) = ack_tx.reserve().await {
+                                                        count_queued(&ack_queued);
+                                                        permit.send(Event::HeartbeatAck {
+                                                            sent_at_ms,
+                                                        });
+                                                    }
+                                                }
+                                                continue;
+                                            }
+                                            Event::Ping { seq, sent_at_ms } => {
+                                                if let Some(pong_tx) =
+                                                    agent_tx.read().await.as_ref()
+                                                {
+                                                    if let Ok(permit) = pong_tx.reserve().await {
+                                                        count_queued(&ack_queued);
+                                                        permit
+                                                            .send(Event::Pong { seq, sent_at_ms });
+                                                    }
+                                                }
+                                                continue;
+                                            }
+                                            Event::HeartbeatAck { sent_at_ms } => {
+                                                record_round_trip(
+                                                    &receive_stats,
+                                                    sent_at_ms,
+                                                    &source,
+                                                );
+                                                continue;
+                                            }
+                                            _ => {}
+                                        }
                                         tracing::debug!("Received event from host: {:?}", event);
+                                        metrics::record_event_received(&source);
+                                        receive_stats.record_event_received();
+                                        if let Event::Disconnect { reason } = &event {
+                                            tracing::warn!(
+                                                "Host at {} disconnected: {}",
+                                                source,
+                                                reason
+                                            );
+                                            let _ =
+                                                receive_events.send(ConnectionEvent::PeerLeft {
+                                                    name: source.clone(),
+                                                    reason: reason.clone(),
+                                                });
+                                            let _ = tx.send(event).await;
+                                            break;
+                                        }
+                                        if let Event::Refused { reason } = event {
+                                            tracing::error!(
+                                                "Host at {} refused this agent: {}",
+                                                source,
+                                                reason
+                                            );
+                                            metrics::record_connection_error();
+                                            let _ = receive_events.send(ConnectionEvent::Refused {
+                                                name: source.clone(),
+                                                reason,
+                                            });
+                                            break;
+                                        }
+                                        if let Event::PskRotate {
+                                            new_psk_hash,
+                                            signature,
+                                        } = &event
+                                        {
+                                            if let Some(new_psk) = accept_psk_rotation(
+                                                &psk,
+                                                &pending_psk,
+                                                new_psk_hash,
+                                                signature,
+                                            ) {
+                                                // Reconnecting must not look like a MITM
+                                                let fingerprint = Fingerprint::from_cert_data(
+                                                    &host_id,
+                                                    new_psk.as_bytes(),
+                                                );
+                                                if let Err(e) = fingerprint_store
+                                                    .lock()
+                                                    .await
+                                                    .save(host_id.clone(), fingerprint)
+                                                {
+                                                    tracing::warn!(
+                                                        "Failed to update host fingerprint: {}",
+                                                        e
+                                                    );
+                                                }
+                                                if let Some(ack_tx) = agent_tx.read().await.as_ref()
+                                                {
+                                                    if let Ok(permit) = ack_tx.reserve().await {
+                                                        count_queued(&ack_queued);
+                                                        permit.send(Event::PskRotateAck {
+                                                            new_psk_hash: new_psk_hash.clone(),
+                                                        });
+                                                    }
+                                                }
+                                            }
+                                            continue;
+                                        }
                                         if tx.send(event).await.is_err() {
                                             tracing::warn!(
                                                 "Failed to forward event, channel closed"
@@ -760,23 +3687,39 @@ async fn handle_connection(
                             }
                         }
                     }
-                    Err(e) => {
+                    Ok(Err(e)) => {
                         tracing::warn!("Failed to read event length: {}", e);
                         break;
                     }
+                    Err(_) => {
+                        tracing::warn!(
+                            "Host heartbeat timeout ({:?} without data)",
+                            HEARTBEAT_TIMEOUT
+                        );
+                        metrics::record_connection_error();
+                        let _ = receive_events.send(ConnectionEvent::HeartbeatTimeout {
+                            name: source.clone(),
+                        });
+                        break;
+                    }
                 }
             }
         }
         tracing::info!("Receive task ending");
     });
 
-    // Wait for either task to complete
+    // Wait for either task to complete, then tear down the other
+    let send_abort = send_task.abort_handle();
+    let receive_abort = receive_task.abort_handle();
     tokio::select! {
         _ = send_task => {}
         _ = receive_task => {}
     }
+    send_abort.abort();
+    receive_abort.abort();
 
     connected.store(false, Ordering::SeqCst);
+    let _ = events.send(ConnectionEvent::AgentDisconnected { name: host });
     Ok(())
 }
 
@@ -793,4 +3736,235 @@ mod tests {
         assert_eq!(hash1, hash2);
         assert_ne!(hash1, hash3);
     }
+
+    #[test]
+    fn test_bind_address_parse() {
+        assert_eq!(
+            "::1".parse::<BindAddress>().unwrap(),
+            BindAddress::Ip("::1".parse().unwrap())
+        );
+        assert_eq!(
+            "iface:lo".parse::<BindAddress>().unwrap().to_string(),
+            "iface:lo"
+        );
+        assert!("iface:".parse::<BindAddress>().is_err());
+        assert!("0.0.0.0:53421".parse::<BindAddress>().is_err());
+        assert!(BindAddress::Interface("no-such-iface0".to_string())
+            .resolve()
+            .is_err());
+    }
+
+    #[test]
+    fn test_agent_allowlist_matching() {
+        let allowlist =
+            AgentAllowlist::new(&["laptop", "10.1.2.3", "fd00::/8", "0.0.0.0/0"]).unwrap();
+        assert!(allowlist.allows_name("laptop"));
+        assert!(!allowlist.allows_name("10.1.2.3"));
+        assert!(allowlist.allows_address("fd12::1".parse().unwrap()));
+        assert!(!allowlist.allows_address("fe80::1".parse().unwrap()));
+        // IPv4 peers of a dual-stack listener match IPv4 entries
+        assert!(allowlist.allows_address("::ffff:192.168.1.5".parse().unwrap()));
+
+        let exact = AgentAllowlist::new(&["10.1.2.3", "::1"]).unwrap();
+        assert!(exact.allows_address("10.1.2.3".parse().unwrap()));
+        assert!(!exact.allows_address("10.1.2.4".parse().unwrap()));
+        assert!(exact.allows_address("::1".parse().unwrap()));
+        assert!(!exact.has_names());
+
+        for invalid in [
+            "",
+            " ",
+            "10.0.0.0/33",
+            "fd00::/129",
+            "laptop/24",
+            "10.0.0.0/x",
+        ] {
+            assert!(AgentAllowlist::new(&[invalid]).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_burst_then_refill() {
+        let mut limiter = ConnectionRateLimiter::new(3, 30);
+        let flooding: IpAddr = "10.0.0.9".parse().unwrap();
+        let start = Instant::now();
+
+        // The burst goes through, the rest of the flood is dropped
+        for _ in 0..3 {
+            assert!(limiter.allow(flooding, start));
+        }
+        for _ in 0..100 {
+            assert!(!limiter.allow(flooding, start + Duration::from_millis(10)));
+        }
+        // Dropped attempts do not delay the refill: a token every 2 seconds
+        assert!(!limiter.allow(flooding, start + Duration::from_millis(1900)));
+        assert!(limiter.allow(flooding, start + Duration::from_secs(2)));
+        assert!(!limiter.allow(flooding, start + Duration::from_secs(3)));
+
+        // The bucket refills up to the burst and no further
+        let later = start + Duration::from_secs(600);
+        for _ in 0..3 {
+            assert!(limiter.allow(flooding, later));
+        }
+        assert!(!limiter.allow(flooding, later));
+
+        // Other addresses have their own bucket, shared with their mapped form
+        let other: IpAddr = "10.0.0.10".parse().unwrap();
+        assert!(limiter.allow(other, start));
+        assert!(limiter.allow("::ffff:10.0.0.10".parse().unwrap(), start));
+        assert!(limiter.allow(other, start));
+        assert!(!limiter.allow("::ffff:10.0.0.10".parse().unwrap(), start));
+    }
+
+    #[test]
+    fn test_rate_limiter_forgets_idle_addresses() {
+        let mut limiter = ConnectionRateLimiter::new(1, 60);
+        let start = Instant::now();
+        for i in 0..RATE_LIMITER_PRUNE_THRESHOLD as u32 {
+            assert!(limiter.allow(IpAddr::from(i.to_be_bytes()), start));
+        }
+        let busy = IpAddr::from(0u32.to_be_bytes());
+        assert!(!limiter.allow(busy, start));
+
+        // A second later every bucket is full again and can be forgotten
+        assert!(limiter.allow(
+            "192.168.0.1".parse().unwrap(),
+            start + Duration::from_secs(1)
+        ));
+        assert_eq!(limiter.buckets.len(), 1);
+    }
+
+    #[test]
+    fn test_token_bucket_refills_over_simulated_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(4, 2.0, start);
+        for _ in 0..4 {
+            assert!(bucket.try_take(start));
+        }
+        assert!(!bucket.try_take(start));
+        assert!(!bucket.try_take(start + Duration::from_millis(400)));
+
+        // Half a second at 2 tokens a second is one token
+        assert!(bucket.try_take(start + Duration::from_millis(500)));
+        assert!(!bucket.try_take(start + Duration::from_millis(500)));
+
+        // Idle time never fills it beyond its capacity
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.is_full(later));
+        for _ in 0..4 {
+            assert!(bucket.try_take(later));
+        }
+        assert!(!bucket.try_take(later));
+
+        // A clock going backwards adds no tokens
+        assert!(!bucket.try_take(start));
+    }
+
+    #[test]
+    fn test_forward_throttle_drops_only_mouse_moves() {
+        let start = Instant::now();
+        let mut throttle = ForwardThrottle::new(2, start);
+        let moved = Event::MouseMove { x: 1, y: 2 };
+        let key = Event::KeyPress {
+            key: crate::core::events::Key::KeyA,
+        };
+
+        assert_eq!(throttle.admit(moved.clone(), start), vec![moved.clone()]);
+        assert_eq!(throttle.admit(key.clone(), start), vec![key.clone()]);
+        assert!(throttle.admit(moved.clone(), start).is_empty());
+        assert_eq!(throttle.admit(key.clone(), start), vec![key.clone()]);
+        assert!(throttle.admit(moved.clone(), start).is_empty());
+
+        // The next second lets two more through
+        let next = start + Duration::from_secs(1);
+        assert_eq!(throttle.admit(moved.clone(), next), vec![moved.clone()]);
+        assert_eq!(throttle.admit(moved.clone(), next), vec![moved.clone()]);
+        assert!(throttle.admit(moved.clone(), next).is_empty());
+    }
+
+    #[test]
+    fn test_forward_throttle_coalesces_relative_moves() {
+        let start = Instant::now();
+        let mut throttle = ForwardThrottle::new(1, start);
+        let nudge = Event::MouseMoveRelative { dx: 3, dy: -1 };
+
+        assert_eq!(throttle.admit(nudge.clone(), start), vec![nudge.clone()]);
+        // Held back, but not lost
+        assert!(throttle.admit(nudge.clone(), start).is_empty());
+        assert!(throttle.admit(nudge.clone(), start).is_empty());
+        assert_eq!(throttle.warning(start), None);
+
+        let next = start + Duration::from_secs(1);
+        assert_eq!(
+            throttle.admit(nudge.clone(), next),
+            [Event::MouseMoveRelative { dx: 9, dy: -3 }]
+        );
+
+        // Other events go through after the motion held before them
+        let click = Event::MouseClick {
+            button: crate::core::events::MouseButton::Left,
+        };
+        assert!(throttle.admit(nudge.clone(), next).is_empty());
+        assert_eq!(
+            throttle.admit(click.clone(), next),
+            [nudge.clone(), click.clone()]
+        );
+        assert_eq!(throttle.admit(click.clone(), next), [click]);
+    }
+
+    #[test]
+    fn test_forward_throttle_warns_at_most_once_per_second() {
+        let start = Instant::now();
+        let mut throttle = ForwardThrottle::new(1, start);
+        let moved = Event::MouseMove { x: 1, y: 2 };
+        assert_eq!(throttle.warning(start), None);
+
+        assert_eq!(throttle.admit(moved.clone(), start), vec![moved.clone()]);
+        for _ in 0..3 {
+            assert!(throttle.admit(moved.clone(), start).is_empty());
+        }
+        assert_eq!(throttle.warning(start), Some(3));
+
+        let soon = start + Duration::from_millis(500);
+        assert!(throttle.admit(moved.clone(), soon).is_empty());
+        assert!(throttle.admit(moved.clone(), soon).is_empty());
+        assert_eq!(throttle.warning(soon), None);
+
+        // The moves dropped meanwhile are reported by the next warning
+        let later = start + Duration::from_millis(1000);
+        assert_eq!(throttle.admit(moved.clone(), later), vec![moved.clone()]);
+        assert!(throttle.admit(moved.clone(), later).is_empty());
+        assert_eq!(throttle.warning(later), Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_socket_options_set_nodelay() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (accepted, _) = listener.accept().await.unwrap();
+        assert!(!client.nodelay().unwrap());
+
+        let options = SocketOptions {
+            send_buffer_size: Some(64 * 1024),
+            recv_buffer_size: Some(64 * 1024),
+            ..SocketOptions::default()
+        };
+        options.apply(&client).unwrap();
+        options.apply(&accepted).unwrap();
+        assert!(client.nodelay().unwrap());
+        assert!(accepted.nodelay().unwrap());
+        // The kernel may round the size up, never down
+        let socket = socket2::SockRef::from(&client);
+        assert!(socket.send_buffer_size().unwrap() >= 64 * 1024);
+
+        SocketOptions {
+            nodelay: false,
+            ..SocketOptions::default()
+        }
+        .apply(&client)
+        .unwrap();
+        assert!(!client.nodelay().unwrap());
+    }
 }
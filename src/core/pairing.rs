@@ -0,0 +1,532 @@
+use anyhow::{Context, Result};
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use serde::{Deserialize, Serialize};
+use spake2::{Ed25519Group, Identity, Password, Spake2};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::core::config::{Config, ConfigMode};
+use crate::core::discovery::MODE_PROPERTY;
+
+type HmacSha256 = hmac::Hmac<sha2::Sha256>;
+
+/// Time a pairing code stays valid.
+pub const PAIRING_CODE_TTL: Duration = Duration::from_secs(120);
+
+/// Failed attempts after which a pairing code is revoked.
+pub const MAX_FAILED_ATTEMPTS: u32 = 5;
+
+/// Pause after a failed attempt before the host accepts the next one.
+const FAILED_ATTEMPT_DELAY: Duration = Duration::from_secs(1);
+
+/// Time allowed for each message of the exchange.
+const EXCHANGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Value of the mDNS `mode` property of a host waiting for an agent to pair.
+pub const PAIRING_MODE: &str = "pairing";
+
+/// Sent by the agent first, so a pairing host is not mistaken for a running one.
+const PAIRING_MAGIC: &[u8] = b"MULTISHIVA_PAIR_V1";
+
+/// Largest message of the exchange, the encrypted offer included.
+const MAX_MESSAGE_LEN: usize = 4096;
+
+const AGENT_IDENTITY: &[u8] = b"multishiva-agent";
+const HOST_IDENTITY: &[u8] = b"multishiva-host";
+
+/// Why pairing failed, returned inside the `anyhow::Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairingError {
+    /// The code expired before an agent paired
+    Expired,
+    /// Too many wrong codes were tried, so the code was revoked
+    TooManyAttempts {
+        /// Failed attempts, [`MAX_FAILED_ATTEMPTS`]
+        attempts: u32,
+    },
+    /// The host refused the code, or is not the host that showed it
+    Rejected,
+}
+
+impl fmt::Display for PairingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PairingError::Expired => f.write_str("The pairing code expired"),
+            PairingError::TooManyAttempts { attempts } => write!(
+                f,
+                "The pairing code was revoked after {} failed attempts",
+                attempts
+            ),
+            PairingError::Rejected => f.write_str("The pairing code was rejected"),
+        }
+    }
+}
+
+impl std::error::Error for PairingError {}
+
+/// A 6-digit code shown on the host, valid once and for a limited time.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::pairing::{PairingCode, PAIRING_CODE_TTL};
+///
+/// let code = PairingCode::generate(PAIRING_CODE_TTL).unwrap();
+/// assert_eq!(code.as_str().len(), 6);
+/// assert!(!code.is_expired());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PairingCode {
+    code: String,
+    expires_at: Instant,
+}
+
+impl PairingCode {
+    /// Draws a random code valid for `ttl`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the system has no random number generator.
+    pub fn generate(ttl: Duration) -> Result<Self> {
+        // Drawing below a multiple of a million keeps every code equally likely
+        const LIMIT: u32 = u32::MAX - u32::MAX % 1_000_000;
+        let number = loop {
+            let mut bytes = [0u8; 4];
+            getrandom::getrandom(&mut bytes).context("Failed to generate a pairing code")?;
+            let number = u32::from_be_bytes(bytes);
+            if number < LIMIT {
+                break number % 1_000_000;
+            }
+        };
+        Ok(Self {
+            code: format!("{:06}", number),
+            expires_at: Instant::now() + ttl,
+        })
+    }
+
+    /// Returns the six digits.
+    pub fn as_str(&self) -> &str {
+        &self.code
+    }
+
+    /// Returns `true` once the code can no longer be used.
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+
+    /// Returns the time left before the code expires.
+    pub fn expires_in(&self) -> Duration {
+        self.expires_at.saturating_duration_since(Instant::now())
+    }
+}
+
+impl fmt::Display for PairingCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", &self.code[..3], &self.code[3..])
+    }
+}
+
+/// Reads a code as typed by the user, e.g. `123 456` or `123-456`.
+///
+/// # Errors
+///
+/// Returns an error unless six digits are left once spaces and dashes are removed.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::pairing::parse_pairing_code;
+///
+/// assert_eq!(parse_pairing_code("123 456").unwrap(), "123456");
+/// assert!(parse_pairing_code("12345").is_err());
+/// ```
+pub fn parse_pairing_code(input: &str) -> Result<String> {
+    let code: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+    if code.len() != 6 || !code.chars().all(|c| c.is_ascii_digit()) {
+        anyhow::bail!("A pairing code has 6 digits, got '{}'", input);
+    }
+    Ok(code)
+}
+
+/// What the host hands to an agent that paired.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairingOffer {
+    /// Name of the host
+    pub host_name: String,
+    /// Port the host listens on for agents
+    pub port: u16,
+    /// The pre-shared key of the host
+    pub psk: String,
+}
+
+/// An agent that paired with a [`PairingHost`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairedAgent {
+    /// Name the agent gave
+    pub name: String,
+    /// Address it paired from
+    pub address: SocketAddr,
+}
+
+/// A host that paired, as seen by the agent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairedHost {
+    /// What the host handed over
+    pub offer: PairingOffer,
+    /// Address of the host agents connect to, from `offer.port`
+    pub address: SocketAddr,
+}
+
+impl PairedHost {
+    /// Makes `config` the configuration of an agent of this host.
+    ///
+    /// `psk` is what goes into `tls.psk`: the host's PSK, or
+    /// [`KEYRING_PLACEHOLDER`](crate::core::keyring::KEYRING_PLACEHOLDER)
+    /// once it is in the keyring.
+    pub fn configure(&self, config: &mut Config, psk: String) {
+        config.mode = ConfigMode::Agent;
+        config.port = self.offer.port;
+        config.host_address = Some(self.address.to_string());
+        config.host_name = Some(self.offer.host_name.clone());
+        config.tls.psk = psk;
+        config.tls.psk_file = None;
+    }
+}
+
+/// Waits for an agent to pair with a [`PairingCode`], then gives it the PSK.
+///
+/// Both sides run SPAKE2 with the code as password, which gives them the
+/// same key only if they used the same code; an eavesdropper learns nothing
+/// about the code, and anyone else gets a single guess per connection. The
+/// agent proves it has the key with an HMAC, and the host answers with the
+/// [`PairingOffer`] encrypted with it.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::pairing::{pair_with_host, PairingCode, PairingHost, PairingOffer};
+/// use std::time::Duration;
+///
+/// # tokio_test::block_on(async {
+/// let offer = PairingOffer {
+///     host_name: "desk".to_string(),
+///     port: 53421,
+///     psk: "secret".to_string(),
+/// };
+/// let code = PairingCode::generate(Duration::from_secs(120)).unwrap();
+/// let typed = code.as_str().to_string();
+/// let host = PairingHost::bind("127.0.0.1:0".parse().unwrap(), offer, code).await.unwrap();
+/// let address = host.local_addr().unwrap().to_string();
+/// let serving = tokio::spawn(host.serve());
+///
+/// let paired = pair_with_host(&address, &typed, "laptop").await.unwrap();
+/// assert_eq!(paired.offer.psk, "secret");
+/// assert_eq!(serving.await.unwrap().unwrap().name, "laptop");
+/// # });
+/// ```
+pub struct PairingHost {
+    listener: TcpListener,
+    offer: PairingOffer,
+    code: PairingCode,
+    failure_delay: Duration,
+}
+
+impl PairingHost {
+    /// Listens on `addr` for an agent pairing with `code`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `addr` cannot be bound.
+    pub async fn bind(addr: SocketAddr, offer: PairingOffer, code: PairingCode) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind to {}", addr))?;
+        Ok(Self {
+            listener,
+            offer,
+            code,
+            failure_delay: FAILED_ATTEMPT_DELAY,
+        })
+    }
+
+    /// Changes the pause after a failed attempt (1 second by default).
+    pub fn set_failure_delay(&mut self, delay: Duration) {
+        self.failure_delay = delay;
+    }
+
+    /// Returns the code agents must give.
+    pub fn code(&self) -> &PairingCode {
+        &self.code
+    }
+
+    /// Returns the address the host listens on.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Returns the mDNS properties telling agents this host is pairing.
+    pub fn properties() -> HashMap<String, String> {
+        HashMap::from([(MODE_PROPERTY.to_string(), PAIRING_MODE.to_string())])
+    }
+
+    /// Serves agents one at a time until one pairs.
+    ///
+    /// Consumes the host, so the code cannot be used twice. Each failed
+    /// attempt is followed by a pause.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PairingError`] if the code expires first or after
+    /// [`MAX_FAILED_ATTEMPTS`] failed attempts.
+    pub async fn serve(self) -> Result<PairedAgent> {
+        let mut failures = 0;
+        loop {
+            let accepted = tokio::time::timeout(self.code.expires_in(), self.listener.accept())
+                .await
+                .map_err(|_| PairingError::Expired)?;
+            let (mut stream, address) = match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    tracing::warn!("Failed to accept a pairing connection: {}", e);
+                    continue;
+                }
+            };
+
+            match self.exchange(&mut stream).await {
+                Ok(name) if !self.code.is_expired() => {
+                    tracing::info!("Agent '{}' paired from {}", name, address);
+                    return Ok(PairedAgent { name, address });
+                }
+                Ok(_) => return Err(PairingError::Expired.into()),
+                Err(e) => {
+                    failures += 1;
+                    tracing::warn!(
+                        "Pairing attempt {}/{} from {} failed: {:#}",
+                        failures,
+                        MAX_FAILED_ATTEMPTS,
+                        address,
+                        e
+                    );
+                    if failures >= MAX_FAILED_ATTEMPTS {
+                        return Err(PairingError::TooManyAttempts { attempts: failures }.into());
+                    }
+                    drop(stream);
+                    tokio::time::sleep(self.failure_delay).await;
+                }
+            }
+        }
+    }
+
+    /// Runs the exchange with one agent, returning its name if it knew the code.
+    async fn exchange(&self, stream: &mut TcpStream) -> Result<String> {
+        let mut magic = [0u8; PAIRING_MAGIC.len()];
+        within(stream.read_exact(&mut magic)).await?;
+        if magic != PAIRING_MAGIC {
+            anyhow::bail!("Not a pairing request");
+        }
+        let name = String::from_utf8(read_message(stream).await?).context("Invalid agent name")?;
+        let agent_message = read_message(stream).await?;
+
+        let (spake, host_message) = Spake2::<Ed25519Group>::start_b(
+            &Password::new(self.code.as_str().as_bytes()),
+            &Identity::new(AGENT_IDENTITY),
+            &Identity::new(HOST_IDENTITY),
+        );
+        write_message(stream, &host_message).await?;
+        let key = spake
+            .finish(&agent_message)
+            .map_err(|e| anyhow::anyhow!("Invalid key exchange: {:?}", e))?;
+
+        let transcript = [agent_message.as_slice(), host_message.as_slice()].concat();
+        let proof = read_message(stream).await?;
+        if !verify_proof(&key, &transcript, &proof) {
+            // An empty answer tells the agent its code was wrong
+            write_message(stream, &[]).await?;
+            anyhow::bail!("Wrong code");
+        }
+
+        let offer = rmp_serde::to_vec(&self.offer).context("Failed to serialize the offer")?;
+        write_message(stream, &seal(&key, &transcript, &offer)?).await?;
+        Ok(name)
+    }
+}
+
+/// Pairs with the host at `addr` using `code`, calling this machine `agent_name`.
+///
+/// See [`PairingHost`] for an example.
+///
+/// # Errors
+///
+/// Returns [`PairingError::Rejected`] if the host refuses the code or cannot
+/// prove it knows it, and other errors if the host cannot be reached.
+pub async fn pair_with_host(addr: &str, code: &str, agent_name: &str) -> Result<PairedHost> {
+    let code = parse_pairing_code(code)?;
+    let mut stream = within(TcpStream::connect(addr))
+        .await
+        .with_context(|| format!("Failed to connect to {}", addr))?;
+    let peer = stream.peer_addr()?;
+
+    let (spake, agent_message) = Spake2::<Ed25519Group>::start_a(
+        &Password::new(code.as_bytes()),
+        &Identity::new(AGENT_IDENTITY),
+        &Identity::new(HOST_IDENTITY),
+    );
+    stream.write_all(PAIRING_MAGIC).await?;
+    write_message(&mut stream, agent_name.as_bytes()).await?;
+    write_message(&mut stream, &agent_message).await?;
+
+    let host_message = read_message(&mut stream).await?;
+    let key = spake
+        .finish(&host_message)
+        .map_err(|_| PairingError::Rejected)?;
+    let transcript = [agent_message.as_slice(), host_message.as_slice()].concat();
+    write_message(&mut stream, &proof(&key, &transcript)).await?;
+
+    let sealed = read_message(&mut stream).await?;
+    if sealed.is_empty() {
+        return Err(PairingError::Rejected.into());
+    }
+    let offer = open(&key, &transcript, &sealed).ok_or(PairingError::Rejected)?;
+    let offer: PairingOffer = rmp_serde::from_slice(&offer).context("Invalid pairing offer")?;
+    Ok(PairedHost {
+        address: SocketAddr::new(peer.ip(), offer.port),
+        offer,
+    })
+}
+
+/// Runs `future` within [`EXCHANGE_TIMEOUT`].
+async fn within<T>(future: impl std::future::Future<Output = std::io::Result<T>>) -> Result<T> {
+    Ok(tokio::time::timeout(EXCHANGE_TIMEOUT, future)
+        .await
+        .context("Timed out waiting for the other side")??)
+}
+
+async fn write_message(stream: &mut TcpStream, message: &[u8]) -> Result<()> {
+    stream
+        .write_all(&(message.len() as u32).to_be_bytes())
+        .await?;
+    stream.write_all(message).await?;
+    Ok(())
+}
+
+async fn read_message(stream: &mut TcpStream) -> Result<Vec<u8>> {
+    let mut len_buf = [0u8; 4];
+    within(stream.read_exact(&mut len_buf)).await?;
+    let len = u32::from_be_bytes(len_buf) as usize;
+    if len > MAX_MESSAGE_LEN {
+        anyhow::bail!("Pairing message too large ({} bytes)", len);
+    }
+    let mut message = vec![0u8; len];
+    within(stream.read_exact(&mut message)).await?;
+    Ok(message)
+}
+
+/// Derives the key of `purpose` from the shared SPAKE2 key.
+fn derive(key: &[u8], purpose: &[u8], transcript: &[u8]) -> HmacSha256 {
+    use hmac::Mac;
+
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(purpose);
+    mac.update(transcript);
+    mac
+}
+
+/// Returns the HMAC by which the agent proves it has the key.
+fn proof(key: &[u8], transcript: &[u8]) -> Vec<u8> {
+    use hmac::Mac;
+
+    derive(key, b"agent proof", transcript)
+        .finalize()
+        .into_bytes()
+        .to_vec()
+}
+
+fn verify_proof(key: &[u8], transcript: &[u8], proof: &[u8]) -> bool {
+    use hmac::Mac;
+
+    derive(key, b"agent proof", transcript)
+        .verify_slice(proof)
+        .is_ok()
+}
+
+fn cipher(key: &[u8], transcript: &[u8]) -> ChaCha20Poly1305 {
+    use chacha20poly1305::KeyInit;
+    use hmac::Mac;
+
+    let key = derive(key, b"offer encryption", transcript)
+        .finalize()
+        .into_bytes();
+    ChaCha20Poly1305::new(&key)
+}
+
+/// Encrypts `plaintext`, returning the nonce followed by the ciphertext.
+fn seal(key: &[u8], transcript: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; 12];
+    getrandom::getrandom(&mut nonce).context("Failed to generate a nonce")?;
+    let ciphertext = cipher(key, transcript)
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: transcript,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt the offer"))?;
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+/// Decrypts what [`seal`] returned, `None` if it was not sealed with `key`.
+fn open(key: &[u8], transcript: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < 12 {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(12);
+    cipher(key, transcript)
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: transcript,
+            },
+        )
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pairing_code_format() {
+        for _ in 0..100 {
+            let code = PairingCode::generate(PAIRING_CODE_TTL).unwrap();
+            assert_eq!(parse_pairing_code(code.as_str()).unwrap(), code.as_str());
+            assert_eq!(
+                parse_pairing_code(&code.to_string()).unwrap(),
+                code.as_str()
+            );
+        }
+        assert!(PairingCode::generate(Duration::ZERO).unwrap().is_expired());
+        assert_eq!(parse_pairing_code(" 012-345 ").unwrap(), "012345");
+        assert!(parse_pairing_code("12a456").is_err());
+        assert!(parse_pairing_code("1234567").is_err());
+    }
+
+    #[test]
+    fn test_sealed_offer_needs_same_key() {
+        let sealed = seal(b"key", b"transcript", b"psk").unwrap();
+        assert_eq!(open(b"key", b"transcript", &sealed).unwrap(), b"psk");
+        assert!(open(b"other", b"transcript", &sealed).is_none());
+        assert!(open(b"key", b"other", &sealed).is_none());
+        assert!(verify_proof(b"key", b"t", &proof(b"key", b"t")));
+        assert!(!verify_proof(b"key", b"t", &proof(b"other", b"t")));
+    }
+}
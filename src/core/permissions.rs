@@ -1,8 +1,19 @@
 /// System permissions verification module
 ///
 /// Checks if MultiShiva has the necessary permissions to capture and inject
-/// input events on different operating systems.
-use anyhow::{Context, Result};
+/// input events on different operating systems, and on Linux installs the
+/// udev rule that grants them.
+use anyhow::Context;
+use anyhow::Result;
+
+/// Path of the udev rule written by [`install_udev_rules`].
+#[cfg(target_os = "linux")]
+pub const UDEV_RULES_PATH: &str = "/etc/udev/rules.d/99-multishiva.rules";
+
+/// udev rule giving the `input` group read and write access to input devices.
+#[cfg(target_os = "linux")]
+pub const UDEV_RULE: &str =
+    "KERNEL==\"event*\", SUBSYSTEM==\"input\", GROUP=\"input\", MODE=\"0660\"\n";
 
 /// Represents the status of system permissions required by MultiShiva.
 ///
@@ -84,13 +95,47 @@ impl PermissionStatus {
     }
 }
 
+/// Outcome of [`request_permission`].
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::permissions::PermissionRequest;
+///
+/// let request = PermissionRequest::ManualSteps {
+///     instructions: "sudo usermod -a -G input $USER".to_string(),
+/// };
+/// assert!(!request.prompted());
+/// assert!(PermissionRequest::Prompted.prompted());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum PermissionRequest {
+    /// Every permission was already granted; nothing was shown
+    AlreadyGranted,
+    /// The system prompt or settings pane was opened for the user
+    Prompted,
+    /// The user has to follow these instructions; nothing was shown
+    ManualSteps {
+        /// Steps granting the missing permissions
+        instructions: String,
+    },
+}
+
+impl PermissionRequest {
+    /// Returns `true` if a prompt or settings pane was shown to the user.
+    pub fn prompted(&self) -> bool {
+        matches!(self, PermissionRequest::Prompted)
+    }
+}
+
 /// Checks system permissions for input capture and injection.
 ///
 /// This function verifies that the application has the necessary permissions
 /// to capture keyboard and mouse events and inject input on the current operating system.
 /// The specific permissions checked vary by platform:
 ///
-/// - **macOS**: Checks for Accessibility API access
+/// - **macOS**: Checks for Accessibility API access, showing the system
+///   dialog that grants it when missing (see [`prompt_accessibility_trust`])
 /// - **Linux**: Checks for `/dev/uinput` access and input group membership
 /// - **Windows**: Checks for administrator privileges (optional)
 ///
@@ -144,6 +189,79 @@ pub fn check_permissions() -> Result<PermissionStatus> {
     }
 }
 
+/// Asks the user to grant the permissions that are missing.
+///
+/// - **macOS**: opens the Accessibility pane of System Settings, where
+///   MultiShiva (or the terminal running it) has to be enabled
+/// - **Linux**: returns the steps joining the `input` group, as no system
+///   prompt exists for it
+/// - **Other systems**: returns [`get_permission_help`]
+///
+/// Nothing is shown when the permissions are already granted.
+///
+/// # Errors
+///
+/// Returns an error if the permissions cannot be checked or the settings pane
+/// cannot be opened.
+///
+/// # Examples
+///
+/// ```no_run
+/// use multishiva::core::permissions::{request_permission, PermissionRequest};
+///
+/// match request_permission().unwrap() {
+///     PermissionRequest::AlreadyGranted => println!("Nothing to do"),
+///     PermissionRequest::Prompted => println!("Enable MultiShiva, then restart it"),
+///     PermissionRequest::ManualSteps { instructions } => println!("{}", instructions),
+/// }
+/// ```
+pub fn request_permission() -> Result<PermissionRequest> {
+    let status = check_permissions()?;
+    if status.is_granted() {
+        return Ok(PermissionRequest::AlreadyGranted);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        open_accessibility_settings()?;
+        Ok(PermissionRequest::Prompted)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Ok(manual_steps(&status))
+    }
+}
+
+/// Returns the instructions granting the permissions missing in `status`.
+#[cfg(not(target_os = "macos"))]
+fn manual_steps(status: &PermissionStatus) -> PermissionRequest {
+    #[cfg(target_os = "linux")]
+    let instructions = {
+        let missing = status.missing_permissions();
+        let mut instructions = if missing.is_empty() {
+            String::new()
+        } else {
+            format!("Missing: {}\n\n", missing.join(", "))
+        };
+        instructions.push_str(
+            "Add your user to the input group, then log out and log back in:\n\
+             \x20 sudo usermod -a -G input $USER\n\
+             If input devices are not owned by the input group, install a udev rule:\n\
+             \x20 sudo multishiva setup-permissions",
+        );
+        instructions
+    };
+
+    #[cfg(not(target_os = "linux"))]
+    let instructions = {
+        let _ = status;
+        get_permission_help()
+    };
+
+    PermissionRequest::ManualSteps { instructions }
+}
+
 /// Returns a help message for fixing permission issues on the current operating system.
 ///
 /// This function provides platform-specific instructions for granting the necessary
@@ -184,21 +302,109 @@ pub fn get_permission_help() -> String {
     }
 }
 
+/// Opaque CoreFoundation object.
 #[cfg(target_os = "macos")]
-fn check_macos_permissions() -> Result<PermissionStatus> {
-    use std::process::Command;
+type CFTypeRef = *const std::ffi::c_void;
 
-    // Check if we can access Accessibility API
-    // Note: This is a simplified check. Full check would use macOS APIs via FFI
-    let output = Command::new("ioreg")
-        .arg("-c")
-        .arg("IOHIDSystem")
-        .output()
-        .context("Failed to check macOS permissions")?;
+/// Opaque callback table of a CoreFoundation dictionary, only used by address.
+#[cfg(target_os = "macos")]
+#[repr(C)]
+struct CFDictionaryCallBacks {
+    _private: [u8; 0],
+}
 
-    if output.status.success() {
-        // Try to detect if Accessibility is enabled
-        // In a real implementation, we'd use macOS Security framework via FFI
+#[cfg(target_os = "macos")]
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+    fn AXIsProcessTrustedWithOptions(options: CFTypeRef) -> bool;
+    static kAXTrustedCheckOptionPrompt: CFTypeRef;
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    static kCFBooleanTrue: CFTypeRef;
+    static kCFTypeDictionaryKeyCallBacks: CFDictionaryCallBacks;
+    static kCFTypeDictionaryValueCallBacks: CFDictionaryCallBacks;
+    fn CFDictionaryCreate(
+        allocator: CFTypeRef,
+        keys: *const CFTypeRef,
+        values: *const CFTypeRef,
+        count: isize,
+        key_callbacks: *const CFDictionaryCallBacks,
+        value_callbacks: *const CFDictionaryCallBacks,
+    ) -> CFTypeRef;
+    fn CFRelease(object: CFTypeRef);
+}
+
+/// Returns whether this process is trusted for Accessibility on macOS.
+///
+/// Capturing and injecting input both need the process (or the terminal it
+/// runs in) to be listed and enabled under Privacy & Security → Accessibility.
+/// The answer can change while the process runs, so it is asked every time.
+#[cfg(target_os = "macos")]
+pub fn is_accessibility_trusted() -> bool {
+    // SAFETY: AXIsProcessTrusted takes no arguments and only reads the TCC
+    // database for the calling process.
+    unsafe { AXIsProcessTrusted() }
+}
+
+/// Returns whether this process is trusted for Accessibility on macOS, and
+/// if it is not, asks macOS to show its "would like to control this
+/// computer" dialog.
+///
+/// The dialog leads to the Accessibility list with MultiShiva already added,
+/// so only its checkbox has to be ticked. macOS shows it once per executable,
+/// and only for a binary in `/Applications` or one that is code-signed: a
+/// binary run from a build directory gets no dialog, and its terminal has to
+/// be enabled by hand instead (see [`request_permission`]).
+#[cfg(target_os = "macos")]
+pub fn prompt_accessibility_trust() -> bool {
+    // SAFETY: the constants are immutable CoreFoundation objects, the
+    // dictionary copies (retains) its single key and value, and it is
+    // released once AXIsProcessTrustedWithOptions has read it.
+    unsafe {
+        let keys = [kAXTrustedCheckOptionPrompt];
+        let values = [kCFBooleanTrue];
+        let options = CFDictionaryCreate(
+            std::ptr::null(),
+            keys.as_ptr(),
+            values.as_ptr(),
+            1,
+            &kCFTypeDictionaryKeyCallBacks,
+            &kCFTypeDictionaryValueCallBacks,
+        );
+        if options.is_null() {
+            return AXIsProcessTrusted();
+        }
+        let trusted = AXIsProcessTrustedWithOptions(options);
+        CFRelease(options);
+        trusted
+    }
+}
+
+/// Settings URL of the Accessibility list under Privacy & Security.
+#[cfg(target_os = "macos")]
+pub const ACCESSIBILITY_SETTINGS_URL: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
+
+#[cfg(target_os = "macos")]
+fn open_accessibility_settings() -> Result<()> {
+    let status = std::process::Command::new("open")
+        .arg(ACCESSIBILITY_SETTINGS_URL)
+        .status()
+        .context("Failed to open System Settings")?;
+    if !status.success() {
+        anyhow::bail!("open {} failed with {}", ACCESSIBILITY_SETTINGS_URL, status);
+    }
+    Ok(())
+}
+
+/// Asks for Accessibility access through the system dialog when it is missing.
+#[cfg(target_os = "macos")]
+fn check_macos_permissions() -> Result<PermissionStatus> {
+    if prompt_accessibility_trust() {
         Ok(PermissionStatus::Granted)
     } else {
         Ok(PermissionStatus::Denied {
@@ -220,6 +426,10 @@ How to grant permissions:
 3. Add MultiShiva to the list of allowed applications
 4. Enable the checkbox next to MultiShiva
 
+When MultiShiva is missing from the list, it is not in /Applications and not
+code-signed, so macOS does not offer to add it: enable the terminal it runs
+in instead, or move the app to /Applications.
+
 Alternative command line:
 sudo sqlite3 /Library/Application\ Support/com.apple.TCC/TCC.db \
   "INSERT or REPLACE INTO access VALUES('kTCCServiceAccessibility','com.yourapp.multishiva',0,1,1,NULL,NULL,NULL,'UNUSED',NULL,0,NULL);"
@@ -299,6 +509,9 @@ How to grant permissions:
 
    Then log out and log back in for changes to take effect.
 
+   If input devices are not owned by the input group, install a udev rule:
+   sudo multishiva setup-permissions
+
 3. Install required libraries:
    # Debian/Ubuntu
    sudo apt-get install libx11-dev libxtst-dev libevdev-dev
@@ -318,6 +531,68 @@ Alternative: Set uinput permissions directly (not recommended):
     .to_string()
 }
 
+/// Writes [`UDEV_RULE`] to [`UDEV_RULES_PATH`] and reloads the udev rules.
+///
+/// Input devices plugged in afterwards, or re-triggered here, become
+/// accessible to the `input` group; the user still needs to be a member.
+///
+/// # Errors
+///
+/// Returns an error, with the `sudo` command to run, when not running as root,
+/// and an error if the rule cannot be written or `udevadm` fails.
+///
+/// # Examples
+///
+/// ```no_run
+/// use multishiva::core::permissions::install_udev_rules;
+///
+/// if let Err(e) = install_udev_rules() {
+///     eprintln!("{}", e);
+/// }
+/// ```
+#[cfg(target_os = "linux")]
+pub fn install_udev_rules() -> Result<()> {
+    use std::process::Command;
+
+    if !nix::unistd::getuid().is_root() {
+        let exe = std::env::current_exe()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| "multishiva".to_string());
+        anyhow::bail!(
+            "Writing {} requires root. Run: sudo {} setup-permissions",
+            UDEV_RULES_PATH,
+            exe
+        );
+    }
+
+    write_udev_rules(std::path::Path::new(UDEV_RULES_PATH))?;
+
+    for args in [
+        &["control", "--reload-rules"][..],
+        &["trigger", "--subsystem-match=input"][..],
+    ] {
+        let status = Command::new("udevadm")
+            .args(args)
+            .status()
+            .context("Failed to run udevadm")?;
+        if !status.success() {
+            anyhow::bail!("udevadm {} failed with {}", args.join(" "), status);
+        }
+    }
+    Ok(())
+}
+
+/// Writes [`UDEV_RULE`] to `path`, creating its directory if needed.
+#[cfg(target_os = "linux")]
+fn write_udev_rules(path: &std::path::Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, UDEV_RULE)
+        .with_context(|| format!("Failed to write udev rule to {}", path.display()))
+}
+
 #[cfg(target_os = "windows")]
 fn check_windows_permissions() -> Result<PermissionStatus> {
     use std::process::Command;
@@ -420,4 +695,69 @@ mod tests {
         #[cfg(target_os = "windows")]
         assert!(help.contains("Windows"));
     }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_macos_accessibility_check() {
+        // Whether the test runner is trusted depends on the machine
+        let trusted = is_accessibility_trusted();
+        let status = check_permissions().unwrap();
+        assert_eq!(status.is_granted(), trusted);
+        if !trusted {
+            assert_eq!(status.missing_permissions(), vec!["Accessibility"]);
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_request_permission_when_trusted() {
+        // Only the trusted branch runs unattended; the other opens System Settings
+        if is_accessibility_trusted() {
+            assert_eq!(
+                request_permission().unwrap(),
+                PermissionRequest::AlreadyGranted
+            );
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_request_permission_lists_manual_steps() {
+        let request = request_permission().unwrap();
+        assert!(!request.prompted());
+
+        let denied = PermissionStatus::Denied {
+            missing: vec!["uinput kernel module".to_string()],
+        };
+        let PermissionRequest::ManualSteps { instructions } = manual_steps(&denied) else {
+            panic!("expected manual steps");
+        };
+        assert!(instructions.starts_with("Missing: uinput kernel module"));
+        assert!(instructions.contains("sudo usermod -a -G input $USER"));
+        assert!(instructions.contains("setup-permissions"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_write_udev_rules() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rules.d").join("99-multishiva.rules");
+
+        write_udev_rules(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "KERNEL==\"event*\", SUBSYSTEM==\"input\", GROUP=\"input\", MODE=\"0660\"\n"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_install_udev_rules_requires_root() {
+        if nix::unistd::getuid().is_root() {
+            return;
+        }
+        let error = install_udev_rules().unwrap_err().to_string();
+        assert!(error.contains("sudo "));
+        assert!(error.contains("setup-permissions"));
+    }
 }
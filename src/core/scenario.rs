@@ -0,0 +1,443 @@
+/// Scripted scenarios for simulation mode
+///
+/// A scenario is a YAML (or JSON) file describing timed input events sent to
+/// the virtual machines of a simulation. While replaying, the runner applies the
+/// same edge detection as production mode against the configured [`Topology`],
+/// so focus transfers can be checked end-to-end without any hardware.
+///
+/// ```yaml
+/// steps:
+///   - at_ms: 100
+///     machine: host
+///     event: {type: mouse_move, x: 1915, y: 500}
+/// expect:
+///   final_focus: agent1
+///   focus_transfers: 1
+/// ```
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use tokio::time::{sleep_until, Duration, Instant};
+
+use crate::core::edge_crossing::{Crossing, EdgeCrossingController};
+use crate::core::events::{Event, Key, MouseButton};
+use crate::core::simulation::SimulationMode;
+use crate::core::topology::Topology;
+
+/// A scripted list of timed events with optional expectations.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::scenario::Scenario;
+///
+/// let scenario = Scenario::from_yaml(
+///     "steps:\n  - at_ms: 0\n    machine: host\n    event: {type: mouse_move, x: 10, y: 20}\n",
+/// )
+/// .unwrap();
+/// assert_eq!(scenario.steps.len(), 1);
+/// assert!(scenario.expect.is_none());
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Scenario {
+    /// Events to replay, ordered by `at_ms` when the scenario runs.
+    pub steps: Vec<ScenarioStep>,
+
+    /// Conditions checked once all steps have been replayed.
+    #[serde(default)]
+    pub expect: Option<Expectation>,
+}
+
+/// A single event sent to a machine at a given time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScenarioStep {
+    /// Milliseconds since the start of the scenario.
+    pub at_ms: u64,
+    /// Name of the virtual machine receiving the event.
+    pub machine: String,
+    /// The event to send.
+    pub event: ScenarioEvent,
+}
+
+/// Input events that can be scripted in a scenario.
+///
+/// Serialized with a `type` tag in snake_case, e.g.
+/// `{type: mouse_move, x: 1915, y: 500}` or `{type: key_press, key: KeyA}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScenarioEvent {
+    /// Move the cursor to an absolute position.
+    MouseMove { x: i32, y: i32 },
+    /// Click a mouse button.
+    MouseClick { button: MouseButton },
+    /// Press a mouse button.
+    MouseButtonPress { button: MouseButton },
+    /// Release a mouse button.
+    MouseButtonRelease { button: MouseButton },
+    /// Scroll the mouse wheel.
+    MouseScroll { delta_x: i64, delta_y: i64 },
+    /// Press a key.
+    KeyPress { key: Key },
+    /// Release a key.
+    KeyRelease { key: Key },
+}
+
+impl From<ScenarioEvent> for Event {
+    fn from(event: ScenarioEvent) -> Self {
+        match event {
+            ScenarioEvent::MouseMove { x, y } => Event::MouseMove { x, y },
+            ScenarioEvent::MouseClick { button } => Event::MouseClick { button },
+            ScenarioEvent::MouseButtonPress { button } => Event::MouseButtonPress { button },
+            ScenarioEvent::MouseButtonRelease { button } => Event::MouseButtonRelease { button },
+            ScenarioEvent::MouseScroll { delta_x, delta_y } => {
+                Event::MouseScroll { delta_x, delta_y }
+            }
+            ScenarioEvent::KeyPress { key } => Event::KeyPress { key },
+            ScenarioEvent::KeyRelease { key } => Event::KeyRelease { key },
+        }
+    }
+}
+
+/// Conditions a scenario must satisfy once replayed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Expectation {
+    /// Machine expected to hold focus at the end.
+    pub final_focus: Option<String>,
+    /// Expected total number of focus transfers.
+    pub focus_transfers: Option<usize>,
+}
+
+impl Scenario {
+    /// Loads a scenario from a YAML or JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scenario file {}", path.display()))?;
+        Self::from_yaml(&contents)
+            .with_context(|| format!("Failed to parse scenario file {}", path.display()))
+    }
+
+    /// Parses a scenario from a YAML string. JSON is accepted as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is not a valid scenario.
+    pub fn from_yaml(contents: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(contents)?)
+    }
+}
+
+/// Replays scenarios against a simulation and a topology.
+///
+/// Focus starts on the host machine. A mouse move on the focused machine that
+/// lands in the zone of an edge with a neighbor starts a transfer; once the
+/// cursor has stayed there for `friction_ms` of scenario time the neighbor
+/// receives a `FocusGrant` and its cursor is placed on the opposite edge.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::scenario::{Scenario, ScenarioRunner};
+/// use multishiva::core::simulation::SimulationMode;
+/// use multishiva::core::topology::{Edge, Position, Topology};
+///
+/// # tokio_test::block_on(async {
+/// let mut sim = SimulationMode::new();
+/// sim.add_virtual_machine("host".to_string(), 1920, 1080);
+/// sim.add_virtual_machine("agent1".to_string(), 1920, 1080);
+///
+/// let mut topology = Topology::new();
+/// topology.add_machine("host".to_string(), Position { x: 0, y: 0 });
+/// topology.add_edge("host".to_string(), Edge::Right, "agent1".to_string());
+///
+/// let scenario = Scenario::from_yaml(
+///     "steps:\n  - at_ms: 0\n    machine: host\n    event: {type: mouse_move, x: 1915, y: 500}\nexpect:\n  final_focus: agent1\n",
+/// )
+/// .unwrap();
+///
+/// let mut runner = ScenarioRunner::new(sim, topology, "host".to_string());
+/// let report = runner.run(&scenario).await.unwrap();
+/// assert!(report.passed());
+/// assert_eq!(report.final_focus, "agent1");
+/// # });
+/// ```
+pub struct ScenarioRunner {
+    sim: SimulationMode,
+    topology: Topology,
+    focus: String,
+    edge_threshold: u32,
+    crossing: EdgeCrossingController,
+    last_position: (i32, i32),
+    // Scenario time 0, so the controller sees scenario time rather than wall time
+    epoch: std::time::Instant,
+    transfers: HashMap<String, usize>,
+}
+
+impl ScenarioRunner {
+    /// Creates a runner with focus on `host`, a 10px edge threshold and no friction.
+    pub fn new(sim: SimulationMode, topology: Topology, host: String) -> Self {
+        Self {
+            sim,
+            topology,
+            focus: host,
+            edge_threshold: 10,
+            crossing: EdgeCrossingController::new(Duration::ZERO),
+            last_position: (0, 0),
+            epoch: std::time::Instant::now(),
+            transfers: HashMap::new(),
+        }
+    }
+
+    /// Sets the distance in pixels from a screen edge that triggers a transfer.
+    pub fn set_edge_threshold(&mut self, threshold: u32) {
+        self.edge_threshold = threshold;
+    }
+
+    /// Sets how long the cursor must dwell in an edge zone before a transfer.
+    pub fn set_friction_ms(&mut self, friction_ms: u64) {
+        self.crossing = EdgeCrossingController::new(Duration::from_millis(friction_ms));
+    }
+
+    /// Returns the simulation being driven.
+    pub fn simulation(&self) -> &SimulationMode {
+        &self.sim
+    }
+
+    /// Replays every step of `scenario` in time order and evaluates its expectations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a step targets a machine that is not part of the
+    /// simulation. Failed expectations are reported in the returned
+    /// [`ScenarioReport`] instead.
+    pub async fn run(&mut self, scenario: &Scenario) -> Result<ScenarioReport> {
+        let mut steps: Vec<&ScenarioStep> = scenario.steps.iter().collect();
+        steps.sort_by_key(|step| step.at_ms);
+
+        let start = Instant::now();
+        for step in steps {
+            sleep_until(start + Duration::from_millis(step.at_ms)).await;
+            let now = self.epoch + Duration::from_millis(step.at_ms);
+            self.complete_pending(now).await?;
+
+            self.sim
+                .send_event_to(&step.machine, step.event.clone().into())
+                .await
+                .with_context(|| format!("Scenario step at {}ms failed", step.at_ms))?;
+
+            if let ScenarioEvent::MouseMove { x, y } = step.event {
+                if step.machine == self.focus {
+                    self.track_edge(&step.machine, x, y, now);
+                    self.complete_pending(now).await?;
+                }
+            }
+        }
+
+        // The cursor stayed put after the last step, so any dwell has elapsed.
+        if let Some(deadline) = self.crossing.deadline() {
+            self.complete_pending(deadline).await?;
+        }
+
+        Ok(self.report(scenario.expect.as_ref()))
+    }
+
+    fn track_edge(&mut self, machine: &str, x: i32, y: i32, now: std::time::Instant) {
+        let Some(vm) = self.sim.get_virtual_machine(machine) else {
+            return;
+        };
+        let (width, height) = vm.screen_size();
+        let zone = self
+            .topology
+            .detect_edge_on_screen(machine, x, y, width, height, self.edge_threshold)
+            .and_then(|edge| {
+                self.topology
+                    .get_neighbor(machine, &edge)
+                    .map(|target| (edge, target.as_str()))
+            });
+
+        self.last_position = (x, y);
+        self.crossing.update(zone, now);
+    }
+
+    async fn complete_pending(&mut self, now: std::time::Instant) -> Result<()> {
+        let Some(Crossing { edge, target }) = self.crossing.poll(now) else {
+            return Ok(());
+        };
+        // Edge detection continues on the machine that receives focus
+        self.crossing.reset();
+
+        let Some(target_size) = self
+            .sim
+            .get_virtual_machine(&target)
+            .map(|vm| vm.screen_size())
+        else {
+            tracing::warn!("Neighbor '{}' is not a simulated machine", target);
+            return Ok(());
+        };
+
+        let (entry_x, entry_y) = self.topology.entry_point(
+            &self.focus,
+            edge,
+            self.last_position,
+            target_size,
+            self.edge_threshold as i32,
+        );
+        let (host_width, host_height) = self
+            .sim
+            .get_virtual_machine(&self.focus)
+            .map(|vm| vm.screen_size())
+            .unwrap_or_default();
+
+        self.sim
+            .send_event_to(
+                &target,
+                Event::FocusGrant {
+                    target: target.clone(),
+                    x: entry_x,
+                    y: entry_y,
+                    host_width,
+                    host_height,
+                },
+            )
+            .await?;
+        if let Some(vm) = self.sim.get_virtual_machine_mut(&target) {
+            vm.set_cursor_position(entry_x, entry_y);
+        }
+
+        tracing::info!(
+            "Focus transferred from '{}' to '{}' via {} edge",
+            self.focus,
+            target,
+            edge
+        );
+        *self.transfers.entry(self.focus.clone()).or_default() += 1;
+        self.focus = target;
+        Ok(())
+    }
+
+    fn report(&self, expect: Option<&Expectation>) -> ScenarioReport {
+        let mut machines: Vec<MachineReport> = self
+            .sim
+            .machine_names()
+            .into_iter()
+            .filter_map(|name| self.sim.get_virtual_machine(name))
+            .map(|vm| MachineReport {
+                name: vm.name().to_string(),
+                events_received: vm.recorded_events().len(),
+                cursor: vm.cursor_position(),
+                focus_transfers: self.transfers.get(vm.name()).copied().unwrap_or(0),
+            })
+            .collect();
+        machines.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let total_transfers: usize = self.transfers.values().sum();
+        let mut failures = Vec::new();
+        if let Some(expect) = expect {
+            if let Some(expected) = &expect.final_focus {
+                if *expected != self.focus {
+                    failures.push(format!(
+                        "expected final focus on '{}', got '{}'",
+                        expected, self.focus
+                    ));
+                }
+            }
+            if let Some(expected) = expect.focus_transfers {
+                if expected != total_transfers {
+                    failures.push(format!(
+                        "expected {} focus transfer(s), got {}",
+                        expected, total_transfers
+                    ));
+                }
+            }
+        }
+
+        ScenarioReport {
+            machines,
+            final_focus: self.focus.clone(),
+            failures,
+        }
+    }
+}
+
+/// Per-machine statistics collected while replaying a scenario.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineReport {
+    /// Name of the virtual machine.
+    pub name: String,
+    /// Number of events the machine received, including focus grants.
+    pub events_received: usize,
+    /// Cursor position at the end of the scenario.
+    pub cursor: (i32, i32),
+    /// Number of focus transfers performed from this machine.
+    pub focus_transfers: usize,
+}
+
+/// Outcome of a scenario run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScenarioReport {
+    /// Statistics for each virtual machine, sorted by name.
+    pub machines: Vec<MachineReport>,
+    /// Machine holding focus at the end of the scenario.
+    pub final_focus: String,
+    /// Expectations that were not met.
+    pub failures: Vec<String>,
+}
+
+impl ScenarioReport {
+    /// Returns `true` if every expectation was met.
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for ScenarioReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<20} {:>8} {:>14} {:>10}",
+            "machine", "events", "cursor", "transfers"
+        )?;
+        for machine in &self.machines {
+            writeln!(
+                f,
+                "{:<20} {:>8} {:>14} {:>10}",
+                machine.name,
+                machine.events_received,
+                format!("({}, {})", machine.cursor.0, machine.cursor.1),
+                machine.focus_transfers
+            )?;
+        }
+        write!(f, "final focus: {}", self.final_focus)?;
+        for failure in &self.failures {
+            write!(f, "\nFAILED: {}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scenario_event_tagged_format() {
+        let scenario = Scenario::from_yaml(
+            r#"{"steps": [{"at_ms": 5, "machine": "host", "event": {"type": "key_press", "key": "KeyA"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            scenario.steps[0].event,
+            ScenarioEvent::KeyPress { key: Key::KeyA }
+        );
+        assert_eq!(
+            Event::from(scenario.steps[0].event.clone()),
+            Event::KeyPress { key: Key::KeyA }
+        );
+    }
+}
@@ -1,8 +1,15 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 use tokio::time::{sleep, Duration};
 
-use crate::core::events::Event;
+use crate::core::edge_crossing::detect_crossing;
+use crate::core::event_recorder::EventReplayer;
+use crate::core::events::{Event, Key, MouseButton};
+use crate::core::focus::ModifierTracker;
+use crate::core::topology::{Edge, EdgeTrigger};
 
 /// A virtual machine instance for simulation mode.
 ///
@@ -26,6 +33,8 @@ pub struct VirtualMachine {
     cursor_x: i32,
     cursor_y: i32,
     recorded_events: Vec<Event>,
+    /// When each entry of `recorded_events` was injected.
+    recorded_at: Vec<DateTime<Utc>>,
 }
 
 impl VirtualMachine {
@@ -48,6 +57,7 @@ impl VirtualMachine {
             cursor_x: (screen_width / 2) as i32,
             cursor_y: (screen_height / 2) as i32,
             recorded_events: Vec::new(),
+            recorded_at: Vec::new(),
         }
     }
 
@@ -116,7 +126,8 @@ impl VirtualMachine {
     /// Injects an event into this virtual machine.
     ///
     /// The event is recorded in the event history and simulated state changes
-    /// are applied (e.g., mouse move updates cursor position).
+    /// are applied (e.g., mouse moves update the cursor position, relative
+    /// moves are added to it and clamped to the screen).
     ///
     /// # Errors
     ///
@@ -139,21 +150,41 @@ impl VirtualMachine {
     pub async fn inject_event(&mut self, event: Event) -> Result<()> {
         // Record the event
         self.recorded_events.push(event.clone());
+        self.recorded_at.push(Utc::now());
 
         // Simulate the event
         match event {
             Event::MouseMove { x, y } => {
                 self.set_cursor_position(x, y);
             }
+            Event::MouseMoveRelative { dx, dy } => {
+                self.set_cursor_position(self.cursor_x + dx, self.cursor_y + dy);
+            }
             Event::MouseButtonPress { .. }
             | Event::MouseButtonRelease { .. }
             | Event::MouseClick { .. }
             | Event::MouseScroll { .. }
+            | Event::MouseScrollPrecise { .. }
+            | Event::MouseScrollWithModifiers { .. }
             | Event::KeyPress { .. }
             | Event::KeyRelease { .. }
             | Event::FocusGrant { .. }
             | Event::FocusRelease
-            | Event::Heartbeat => {
+            | Event::Heartbeat { .. }
+            | Event::ClipboardSync { .. }
+            | Event::Hello { .. }
+            | Event::PskRotate { .. }
+            | Event::PskRotateAck { .. }
+            | Event::FocusGrantChain { .. }
+            | Event::ClipboardChunk { .. }
+            | Event::KillSwitch
+            | Event::TextInput { .. }
+            | Event::Refused { .. }
+            | Event::Disconnect { .. }
+            | Event::HeartbeatAck { .. }
+            | Event::Ping { .. }
+            | Event::Pong { .. }
+            | Event::EdgeReached { .. } => {
                 // Just record these events, no state change needed for simulation
             }
         }
@@ -173,7 +204,7 @@ impl VirtualMachine {
     /// # tokio_test::block_on(async {
     /// let mut vm = VirtualMachine::new("test".to_string(), 1920, 1080);
     /// vm.inject_event(Event::MouseMove { x: 10, y: 20 }).await.unwrap();
-    /// vm.inject_event(Event::Heartbeat).await.unwrap();
+    /// vm.inject_event(Event::Heartbeat { sent_at_ms: 0 }).await.unwrap();
     /// assert_eq!(vm.recorded_events().len(), 2);
     /// # });
     /// ```
@@ -190,7 +221,7 @@ impl VirtualMachine {
     /// # use multishiva::core::events::Event;
     /// # tokio_test::block_on(async {
     /// let mut vm = VirtualMachine::new("test".to_string(), 1920, 1080);
-    /// vm.inject_event(Event::Heartbeat).await.unwrap();
+    /// vm.inject_event(Event::Heartbeat { sent_at_ms: 0 }).await.unwrap();
     /// assert_eq!(vm.recorded_events().len(), 1);
     /// vm.clear_events();
     /// assert_eq!(vm.recorded_events().len(), 0);
@@ -198,6 +229,237 @@ impl VirtualMachine {
     /// ```
     pub fn clear_events(&mut self) {
         self.recorded_events.clear();
+        self.recorded_at.clear();
+    }
+}
+
+/// A virtual machine of a recorded session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionMachine {
+    /// Name of the virtual machine
+    pub name: String,
+    /// Screen width in pixels
+    pub screen_width: u32,
+    /// Screen height in pixels
+    pub screen_height: u32,
+}
+
+/// An event of a recorded session, with when and where it was injected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionEvent {
+    /// When the event was injected
+    pub timestamp: DateTime<Utc>,
+    /// Name of the virtual machine that received the event
+    pub target: String,
+    /// The event itself
+    pub event: Event,
+}
+
+/// The recorded events of all virtual machines of a simulation.
+///
+/// Written by [`SimulationMode::export_session`] and read by
+/// [`SimulationMode::replay_session`]. Events are sorted by timestamp.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// Virtual machines, sorted by name
+    pub machines: Vec<SessionMachine>,
+    /// Events of all machines, in injection order
+    pub events: Vec<SessionEvent>,
+}
+
+/// A scripted sequence of steps run by [`SimulationMode::simulate_scenario`].
+///
+/// Unlike the timed YAML files of [`crate::core::scenario`], steps run one
+/// after the other and can assert on the simulation state in between.
+pub type SimScenario = Vec<SimStep>;
+
+/// A single step of a [`SimScenario`].
+///
+/// Serialized with a `type` tag in snake_case, e.g.
+/// `{type: assert_focus_on, machine: agent1}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SimStep {
+    /// Send an event to a machine; mouse moves and button releases go through
+    /// edge crossing
+    SendEvent { target: String, event: Event },
+    /// Pause before the next step
+    Wait { millis: u64 },
+    /// Check the cursor position of a machine
+    AssertCursorAt { machine: String, x: i32, y: i32 },
+    /// Check which machine holds focus
+    AssertFocusOn { machine: String },
+}
+
+/// Screen size assumed by [`ScenarioBuilder::cross_edge`] for a machine it does not know.
+const DEFAULT_SCREEN_SIZE: (u32, u32) = (1920, 1080);
+
+/// Builds a [`SimScenario`] step by step.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::events::Key;
+/// use multishiva::core::simulation::{ScenarioBuilder, SimulationMode};
+/// use multishiva::core::topology::Edge;
+/// use std::collections::HashMap;
+/// # tokio_test::block_on(async {
+/// let mut sim = SimulationMode::new();
+/// sim.add_virtual_machine("host".to_string(), 1920, 1080);
+/// sim.add_virtual_machine("vm1".to_string(), 1280, 720);
+/// sim.set_edges("host", HashMap::from([("right".to_string(), "vm1".to_string())]));
+///
+/// let scenario = ScenarioBuilder::for_simulation(&sim)
+///     .cross_edge("host", "vm1", Edge::Right)
+///     .move_mouse("vm1", 100, 200)
+///     .wait_ms(50)
+///     .assert_cursor("vm1", 100, 200)
+///     .key_press("vm1", Key::Return)
+///     .build();
+/// assert!(sim.simulate_scenario(scenario).await.unwrap().passed());
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScenarioBuilder {
+    steps: Vec<SimStep>,
+    /// Screen sizes used to reach the edges of the machines
+    screens: HashMap<String, (u32, u32)>,
+}
+
+impl ScenarioBuilder {
+    /// Starts an empty scenario; see [`ScenarioBuilder::screen`] for edge crossings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts an empty scenario knowing the screen sizes of the machines of `sim`.
+    pub fn for_simulation(sim: &SimulationMode) -> Self {
+        Self {
+            steps: Vec::new(),
+            screens: sim
+                .virtual_machines
+                .iter()
+                .map(|(name, vm)| (name.clone(), vm.screen_size()))
+                .collect(),
+        }
+    }
+
+    /// Sets the screen size of `machine` used by [`ScenarioBuilder::cross_edge`].
+    ///
+    /// Machines without one are assumed to have a 1920x1080 screen.
+    pub fn screen(&mut self, machine: &str, width: u32, height: u32) -> &mut Self {
+        self.screens.insert(machine.to_string(), (width, height));
+        self
+    }
+
+    /// Sends `event` to `machine`.
+    pub fn send_event(&mut self, machine: &str, event: Event) -> &mut Self {
+        self.steps.push(SimStep::SendEvent {
+            target: machine.to_string(),
+            event,
+        });
+        self
+    }
+
+    /// Moves the cursor of `machine` to (`x`, `y`).
+    pub fn move_mouse(&mut self, machine: &str, x: i32, y: i32) -> &mut Self {
+        self.send_event(machine, Event::MouseMove { x, y })
+    }
+
+    /// Presses `key` on `machine`.
+    pub fn key_press(&mut self, machine: &str, key: Key) -> &mut Self {
+        self.send_event(machine, Event::KeyPress { key })
+    }
+
+    /// Releases `key` on `machine`.
+    pub fn key_release(&mut self, machine: &str, key: Key) -> &mut Self {
+        self.send_event(machine, Event::KeyRelease { key })
+    }
+
+    /// Pauses for `millis` milliseconds.
+    pub fn wait_ms(&mut self, millis: u64) -> &mut Self {
+        self.steps.push(SimStep::Wait { millis });
+        self
+    }
+
+    /// Checks that the cursor of `machine` is at (`x`, `y`).
+    pub fn assert_cursor(&mut self, machine: &str, x: i32, y: i32) -> &mut Self {
+        self.steps.push(SimStep::AssertCursorAt {
+            machine: machine.to_string(),
+            x,
+            y,
+        });
+        self
+    }
+
+    /// Checks that `machine` holds focus.
+    pub fn assert_focus(&mut self, machine: &str) -> &mut Self {
+        self.steps.push(SimStep::AssertFocusOn {
+            machine: machine.to_string(),
+        });
+        self
+    }
+
+    /// Moves the cursor of `from` from the middle of its screen onto `edge`,
+    /// then checks that `to` holds focus.
+    pub fn cross_edge(&mut self, from: &str, to: &str, edge: Edge) -> &mut Self {
+        let (width, height) = self
+            .screens
+            .get(from)
+            .copied()
+            .unwrap_or(DEFAULT_SCREEN_SIZE);
+        let (width, height) = (width as i32, height as i32);
+        let (center_x, center_y) = (width / 2, height / 2);
+        let (edge_x, edge_y) = match edge {
+            Edge::Left => (0, center_y),
+            Edge::Right => (width - 1, center_y),
+            Edge::Top => (center_x, 0),
+            Edge::Bottom => (center_x, height - 1),
+        };
+        // Halfway first, as a real cursor does not jump to the edge
+        self.move_mouse(from, center_x, center_y)
+            .move_mouse(from, (center_x + edge_x) / 2, (center_y + edge_y) / 2)
+            .move_mouse(from, edge_x, edge_y)
+            .assert_focus(to)
+    }
+
+    /// Returns the steps added so far.
+    pub fn build(&self) -> SimScenario {
+        self.steps.clone()
+    }
+}
+
+/// Outcome of one assertion step of a [`SimScenario`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionResult {
+    /// Index of the step in the scenario
+    pub step: usize,
+    /// Whether the actual value matched
+    pub passed: bool,
+    /// The expected value, e.g. `"agent1"` or `"agent1 at (10, 500)"`
+    pub expected: String,
+    /// The value found when the step ran
+    pub actual: String,
+}
+
+/// Outcome of [`SimulationMode::simulate_scenario`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationReport {
+    /// Every assertion step, in scenario order
+    pub assertions: Vec<AssertionResult>,
+    /// Machine holding focus at the end, if any event was sent
+    pub final_focus: Option<String>,
+}
+
+impl SimulationReport {
+    /// Returns `true` if every assertion passed.
+    pub fn passed(&self) -> bool {
+        self.assertions.iter().all(|assertion| assertion.passed)
+    }
+
+    /// Returns the assertions that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &AssertionResult> {
+        self.assertions.iter().filter(|assertion| !assertion.passed)
     }
 }
 
@@ -205,6 +467,8 @@ impl VirtualMachine {
 ///
 /// Manages multiple virtual machines and simulates network behavior including
 /// latency. Provides statistics tracking for events sent during simulation.
+/// With edges set per machine, cursor moves go through the same edge detection
+/// as host mode and hand focus to the neighbor with a `FocusGrant`.
 ///
 /// # Examples
 ///
@@ -216,7 +480,7 @@ impl VirtualMachine {
 /// sim.add_virtual_machine("vm1".to_string(), 1920, 1080);
 /// sim.set_network_latency(10);
 ///
-/// sim.send_event_to("vm1", Event::Heartbeat).await.unwrap();
+/// sim.send_event_to("vm1", Event::Heartbeat { sent_at_ms: 0 }).await.unwrap();
 /// assert_eq!(sim.get_statistics().total_events_sent, 1);
 /// # });
 /// ```
@@ -224,6 +488,17 @@ pub struct SimulationMode {
     virtual_machines: HashMap<String, VirtualMachine>,
     network_latency_ms: u64,
     total_events_sent: usize,
+    // Per machine, edge name to neighbor, as in the `edges` configuration
+    edges: HashMap<String, HashMap<String, String>>,
+    edge_threshold: i32,
+    edge_trigger: EdgeTrigger,
+    // Mouse buttons held, which defer edge crossings unless transfer_while_dragging
+    buttons: ModifierTracker,
+    transfer_while_dragging: bool,
+    // Machine routing every crossing between other machines, like the host
+    hub: Option<String>,
+    // Machines focus left through the hub and where, for the kill switch
+    focus_history: Vec<(String, i32, i32)>,
 }
 
 impl Default for SimulationMode {
@@ -247,6 +522,13 @@ impl SimulationMode {
             virtual_machines: HashMap::new(),
             network_latency_ms: 0,
             total_events_sent: 0,
+            edges: HashMap::new(),
+            edge_threshold: 10,
+            edge_trigger: EdgeTrigger::FullEdge,
+            buttons: ModifierTracker::new(),
+            transfer_while_dragging: false,
+            hub: None,
+            focus_history: Vec::new(),
         }
     }
 
@@ -282,6 +564,41 @@ impl SimulationMode {
     /// ```
     pub fn remove_virtual_machine(&mut self, name: &str) {
         self.virtual_machines.remove(name);
+        self.edges.remove(name);
+    }
+
+    /// Sets the neighbors of `machine`, keyed by edge name (`"left"`, `"top"`...).
+    pub fn set_edges(&mut self, machine: &str, edges: HashMap<String, String>) {
+        self.edges.insert(machine.to_string(), edges);
+    }
+
+    /// Sets the distance in pixels from a screen edge that triggers a transfer.
+    ///
+    /// Defaults to 10 pixels.
+    pub fn set_edge_threshold(&mut self, threshold: i32) {
+        self.edge_threshold = threshold;
+    }
+
+    /// Sets which part of the edges transfers focus, the full edge by default.
+    pub fn set_edge_trigger(&mut self, trigger: EdgeTrigger) {
+        self.edge_trigger = trigger;
+    }
+
+    /// Sets whether focus crosses edges while a mouse button is held.
+    ///
+    /// Off by default, as with `behavior.transfer_while_dragging`.
+    pub fn set_transfer_while_dragging(&mut self, enabled: bool) {
+        self.transfer_while_dragging = enabled;
+    }
+
+    /// Routes the crossings of every other machine through `machine`, as a host does.
+    ///
+    /// A machine reaching an edge then reports it to `machine` with an
+    /// `EdgeReached` and gets a `FocusRelease` back before the neighbor
+    /// receives its `FocusGrant`. Focus coming back to `machine` itself needs
+    /// no grant. By default machines hand focus to each other directly.
+    pub fn set_hub(&mut self, machine: &str) {
+        self.hub = Some(machine.to_string());
     }
 
     /// Gets an immutable reference to a virtual machine by name.
@@ -334,6 +651,20 @@ impl SimulationMode {
         self.virtual_machines.len()
     }
 
+    /// Returns the names of all virtual machines, in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use multishiva::core::simulation::SimulationMode;
+    /// let mut sim = SimulationMode::new();
+    /// sim.add_virtual_machine("vm1".to_string(), 1920, 1080);
+    /// assert_eq!(sim.machine_names(), vec!["vm1"]);
+    /// ```
+    pub fn machine_names(&self) -> Vec<&str> {
+        self.virtual_machines.keys().map(String::as_str).collect()
+    }
+
     /// Sets the simulated network latency in milliseconds.
     ///
     /// This latency is applied as a delay when sending events to virtual machines.
@@ -368,11 +699,11 @@ impl SimulationMode {
     /// sim.add_virtual_machine("vm1".to_string(), 1920, 1080);
     /// sim.set_network_latency(10);
     ///
-    /// sim.send_event_to("vm1", Event::Heartbeat).await.unwrap();
+    /// sim.send_event_to("vm1", Event::Heartbeat { sent_at_ms: 0 }).await.unwrap();
     /// assert_eq!(sim.get_statistics().total_events_sent, 1);
     ///
     /// // Sending to non-existent VM returns error
-    /// assert!(sim.send_event_to("vm2", Event::Heartbeat).await.is_err());
+    /// assert!(sim.send_event_to("vm2", Event::Heartbeat { sent_at_ms: 0 }).await.is_err());
     /// # });
     /// ```
     pub async fn send_event_to(&mut self, target: &str, event: Event) -> Result<()> {
@@ -383,6 +714,7 @@ impl SimulationMode {
 
         // Send event to target VM
         if let Some(vm) = self.virtual_machines.get_mut(target) {
+            self.buttons.observe(&event);
             vm.inject_event(event).await?;
             self.total_events_sent += 1;
         } else {
@@ -392,6 +724,345 @@ impl SimulationMode {
         Ok(())
     }
 
+    /// Moves the cursor of `machine` to (`x`, `y`) and applies edge crossing.
+    ///
+    /// If the position lies in the zone of an edge with a simulated neighbor,
+    /// the neighbor receives a `FocusGrant` at the mirrored entry point and its
+    /// cursor is placed there, through the hub if one is set (see
+    /// [`SimulationMode::set_hub`]). Returns the neighbor that received focus.
+    ///
+    /// While a mouse button is held, the crossing waits for
+    /// [`SimulationMode::release_button`] unless
+    /// [`SimulationMode::set_transfer_while_dragging`] allows it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `machine` does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use multishiva::core::simulation::SimulationMode;
+    /// # use std::collections::HashMap;
+    /// # tokio_test::block_on(async {
+    /// let mut sim = SimulationMode::new();
+    /// sim.add_virtual_machine("host".to_string(), 1920, 1080);
+    /// sim.add_virtual_machine("agent1".to_string(), 1920, 1080);
+    /// sim.set_edges("host", HashMap::from([("right".to_string(), "agent1".to_string())]));
+    ///
+    /// assert_eq!(sim.move_cursor("host", 960, 500).await.unwrap(), None);
+    /// let target = sim.move_cursor("host", 1915, 500).await.unwrap();
+    /// assert_eq!(target.as_deref(), Some("agent1"));
+    /// assert_eq!(sim.get_virtual_machine("agent1").unwrap().cursor_position(), (10, 500));
+    /// # });
+    /// ```
+    pub async fn move_cursor(&mut self, machine: &str, x: i32, y: i32) -> Result<Option<String>> {
+        self.send_event_to(machine, Event::MouseMove { x, y })
+            .await?;
+        if self.buttons.is_dragging() && !self.transfer_while_dragging {
+            return Ok(None);
+        }
+        self.cross_edge(machine, x, y).await
+    }
+
+    /// Releases `button` on `machine` and applies a crossing it deferred.
+    ///
+    /// Once no button is held, a cursor left in the zone of an edge with a
+    /// simulated neighbor hands focus to it. Returns the neighbor that
+    /// received focus.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `machine` does not exist.
+    pub async fn release_button(
+        &mut self,
+        machine: &str,
+        button: MouseButton,
+    ) -> Result<Option<String>> {
+        self.send_event_to(machine, Event::MouseButtonRelease { button })
+            .await?;
+        if self.buttons.is_dragging() {
+            return Ok(None);
+        }
+        let (x, y) = self.virtual_machines[machine].cursor_position();
+        self.cross_edge(machine, x, y).await
+    }
+
+    /// Hands focus to the neighbor of `machine` if (`x`, `y`) lies in its edge zone.
+    async fn cross_edge(&mut self, machine: &str, x: i32, y: i32) -> Result<Option<String>> {
+        let Some(edges) = self.edges.get(machine) else {
+            return Ok(None);
+        };
+        let screen_size = self.virtual_machines[machine].screen_size();
+        let Some((edge, target, (entry_x, entry_y))) = detect_crossing(
+            screen_size,
+            self.edge_threshold,
+            &self.edge_trigger,
+            edges,
+            (x, y),
+        ) else {
+            return Ok(None);
+        };
+        if !self.virtual_machines.contains_key(&target) {
+            tracing::warn!("Neighbor '{}' is not a simulated machine", target);
+            return Ok(None);
+        }
+
+        tracing::info!(
+            "Focus transferred from '{}' to '{}' via {} edge",
+            machine,
+            target,
+            edge
+        );
+        let hub = self.hub.clone().filter(|hub| hub != machine);
+        if let Some(hub) = &hub {
+            // The machine reports the edge, the hub takes focus away from it
+            self.send_event_to(hub, Event::EdgeReached { edge, x, y })
+                .await?;
+            self.send_event_to(machine, Event::FocusRelease).await?;
+        }
+        if self.hub.as_ref() == Some(&target) {
+            self.focus_history.clear();
+        } else if self.hub.is_some() {
+            self.focus_history.push((machine.to_string(), x, y));
+        }
+        if hub.as_ref() != Some(&target) {
+            self.send_event_to(
+                &target,
+                Event::FocusGrant {
+                    target: target.clone(),
+                    x: entry_x,
+                    y: entry_y,
+                    host_width: screen_size.0,
+                    host_height: screen_size.1,
+                },
+            )
+            .await?;
+        }
+        if let Some(vm) = self.virtual_machines.get_mut(&target) {
+            vm.set_cursor_position(entry_x, entry_y);
+        }
+        Ok(Some(target))
+    }
+
+    /// Presses the kill switch of the hub while `focused` holds focus.
+    ///
+    /// As on the host, focus walks back one machine along the chain it
+    /// crossed through the hub: `focused` gets a `FocusRelease` and the
+    /// previous machine a `FocusGrant` where the cursor left it. Once the
+    /// chain is walked back, focus returns to the hub itself. Returns the
+    /// machine holding focus afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no hub is set (see [`SimulationMode::set_hub`]) or
+    /// `focused` does not exist.
+    pub async fn press_kill_switch(&mut self, focused: &str) -> Result<String> {
+        let Some(hub) = self.hub.clone() else {
+            anyhow::bail!("The kill switch needs a hub, see set_hub");
+        };
+        if focused == hub {
+            return Ok(hub);
+        }
+        self.send_event_to(focused, Event::FocusRelease).await?;
+        while let Some((machine, x, y)) = self.focus_history.pop() {
+            if machine == hub {
+                break;
+            }
+            if machine != focused && self.virtual_machines.contains_key(&machine) {
+                let screen_size = self.virtual_machines[&hub].screen_size();
+                self.send_event_to(
+                    &machine,
+                    Event::FocusGrant {
+                        target: machine.clone(),
+                        x,
+                        y,
+                        host_width: screen_size.0,
+                        host_height: screen_size.1,
+                    },
+                )
+                .await?;
+                if let Some(vm) = self.virtual_machines.get_mut(&machine) {
+                    vm.set_cursor_position(x, y);
+                }
+                return Ok(machine);
+            }
+        }
+        self.focus_history.clear();
+        Ok(hub)
+    }
+
+    /// Moves the cursor along `path`, starting with focus on `start`.
+    ///
+    /// Each position applies to the machine holding focus at that point, so
+    /// the path continues on a neighbor once focus crossed over. Returns the
+    /// machine holding focus at the end.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `start` does not exist.
+    pub async fn run_cursor_path(&mut self, start: &str, path: &[(i32, i32)]) -> Result<String> {
+        let mut focus = start.to_string();
+        for &(x, y) in path {
+            if let Some(target) = self.move_cursor(&focus, x, y).await? {
+                focus = target;
+            }
+        }
+        Ok(focus)
+    }
+
+    /// Runs the steps of `scenario` in order and reports on its assertions.
+    ///
+    /// Focus starts on the target of the first `SendEvent` step and follows
+    /// edge crossings and `FocusGrant` events. A `FocusRelease` sent to the
+    /// machine holding focus gives it back to the hub (see
+    /// [`SimulationMode::set_hub`]), or to no machine without one, until the
+    /// next `SendEvent` step takes it. Failed assertions do not stop
+    /// the scenario; they are listed in the report with the actual value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a `SendEvent` step targets a machine that does not
+    /// exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use multishiva::core::simulation::{SimStep, SimulationMode};
+    /// # use multishiva::core::events::Event;
+    /// # use std::collections::HashMap;
+    /// # tokio_test::block_on(async {
+    /// let mut sim = SimulationMode::new();
+    /// sim.add_virtual_machine("host".to_string(), 1920, 1080);
+    /// sim.add_virtual_machine("agent1".to_string(), 1920, 1080);
+    /// sim.set_edges("host", HashMap::from([("right".to_string(), "agent1".to_string())]));
+    ///
+    /// let report = sim
+    ///     .simulate_scenario(vec![
+    ///         SimStep::SendEvent {
+    ///             target: "host".to_string(),
+    ///             event: Event::MouseMove { x: 1915, y: 500 },
+    ///         },
+    ///         SimStep::AssertFocusOn { machine: "agent1".to_string() },
+    ///         SimStep::AssertCursorAt { machine: "agent1".to_string(), x: 10, y: 500 },
+    ///     ])
+    ///     .await
+    ///     .unwrap();
+    /// assert!(report.passed());
+    /// # });
+    /// ```
+    pub async fn simulate_scenario(&mut self, scenario: SimScenario) -> Result<SimulationReport> {
+        let mut focus: Option<String> = None;
+        let mut assertions = Vec::new();
+
+        for (index, step) in scenario.into_iter().enumerate() {
+            match step {
+                SimStep::SendEvent { target, event } => {
+                    if focus.is_none() {
+                        focus = Some(target.clone());
+                    }
+                    match event {
+                        Event::MouseMove { x, y } => {
+                            if let Some(new_focus) = self
+                                .move_cursor(&target, x, y)
+                                .await
+                                .with_context(|| format!("Scenario step {} failed", index))?
+                            {
+                                focus = Some(new_focus);
+                            }
+                        }
+                        Event::MouseButtonRelease { button } => {
+                            if let Some(new_focus) = self
+                                .release_button(&target, button)
+                                .await
+                                .with_context(|| format!("Scenario step {} failed", index))?
+                            {
+                                focus = Some(new_focus);
+                            }
+                        }
+                        event => {
+                            match &event {
+                                Event::FocusGrant {
+                                    target: granted, ..
+                                } => focus = Some(granted.clone()),
+                                Event::FocusRelease
+                                    if focus.as_ref() == Some(&target)
+                                        && self.hub.as_ref() != Some(&target) =>
+                                {
+                                    focus = self.hub.clone();
+                                }
+                                _ => {}
+                            }
+                            self.send_event_to(&target, event)
+                                .await
+                                .with_context(|| format!("Scenario step {} failed", index))?;
+                        }
+                    }
+                }
+                SimStep::Wait { millis } => sleep(Duration::from_millis(millis)).await,
+                SimStep::AssertCursorAt { machine, x, y } => {
+                    let actual = self
+                        .virtual_machines
+                        .get(&machine)
+                        .map(|vm| vm.cursor_position());
+                    assertions.push(AssertionResult {
+                        step: index,
+                        passed: actual == Some((x, y)),
+                        expected: format!("{} at ({}, {})", machine, x, y),
+                        actual: match actual {
+                            Some((ax, ay)) => format!("{} at ({}, {})", machine, ax, ay),
+                            None => format!("no machine named '{}'", machine),
+                        },
+                    });
+                }
+                SimStep::AssertFocusOn { machine } => {
+                    assertions.push(AssertionResult {
+                        step: index,
+                        passed: focus.as_deref() == Some(machine.as_str()),
+                        actual: focus.clone().unwrap_or_else(|| "no focus".to_string()),
+                        expected: machine,
+                    });
+                }
+            }
+        }
+
+        Ok(SimulationReport {
+            assertions,
+            final_focus: focus,
+        })
+    }
+
+    /// Plays back a recording made with `--record` into `target`.
+    ///
+    /// Events keep their recorded timing, scaled by the replayer's speed, and
+    /// go through [`SimulationMode::simulate_scenario`], so mouse moves cross
+    /// edges as they did live.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `target` does not exist.
+    pub async fn replay_recording(
+        &mut self,
+        replayer: &EventReplayer,
+        target: &str,
+    ) -> Result<SimulationReport> {
+        let mut scenario = SimScenario::new();
+        let mut elapsed = Duration::ZERO;
+        for record in replayer.events() {
+            let at = replayer.playback_offset(record.offset_us);
+            let wait = at.saturating_sub(elapsed).as_millis() as u64;
+            if wait > 0 {
+                scenario.push(SimStep::Wait { millis: wait });
+                elapsed += Duration::from_millis(wait);
+            }
+            scenario.push(SimStep::SendEvent {
+                target: target.to_string(),
+                event: record.event.clone(),
+            });
+        }
+        self.simulate_scenario(scenario).await
+    }
+
     /// Returns simulation statistics.
     ///
     /// # Examples
@@ -402,7 +1073,7 @@ impl SimulationMode {
     /// # tokio_test::block_on(async {
     /// let mut sim = SimulationMode::new();
     /// sim.add_virtual_machine("vm1".to_string(), 1920, 1080);
-    /// sim.send_event_to("vm1", Event::Heartbeat).await.unwrap();
+    /// sim.send_event_to("vm1", Event::Heartbeat { sent_at_ms: 0 }).await.unwrap();
     ///
     /// let stats = sim.get_statistics();
     /// assert_eq!(stats.total_events_sent, 1);
@@ -415,6 +1086,102 @@ impl SimulationMode {
             virtual_machine_count: self.virtual_machines.len(),
         }
     }
+
+    /// Returns the recorded events of all virtual machines as a session.
+    pub fn session(&self) -> Session {
+        let mut vms: Vec<&VirtualMachine> = self.virtual_machines.values().collect();
+        vms.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let machines = vms
+            .iter()
+            .map(|vm| SessionMachine {
+                name: vm.name.clone(),
+                screen_width: vm.screen_width,
+                screen_height: vm.screen_height,
+            })
+            .collect();
+
+        let mut events: Vec<SessionEvent> = vms
+            .iter()
+            .flat_map(|vm| {
+                vm.recorded_at
+                    .iter()
+                    .zip(&vm.recorded_events)
+                    .map(|(timestamp, event)| SessionEvent {
+                        timestamp: *timestamp,
+                        target: vm.name.clone(),
+                        event: event.clone(),
+                    })
+            })
+            .collect();
+        // Stable, so events of one machine keep their order on equal timestamps
+        events.sort_by_key(|event| event.timestamp);
+
+        Session { machines, events }
+    }
+
+    /// Writes the recorded events of all virtual machines to `path` as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use multishiva::core::simulation::SimulationMode;
+    /// # use multishiva::core::events::Event;
+    /// # tokio_test::block_on(async {
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("session.json");
+    ///
+    /// let mut sim = SimulationMode::new();
+    /// sim.add_virtual_machine("vm1".to_string(), 1920, 1080);
+    /// sim.send_event_to("vm1", Event::MouseMove { x: 10, y: 20 }).await.unwrap();
+    /// sim.export_session(&path).unwrap();
+    ///
+    /// let mut replay = SimulationMode::new();
+    /// assert_eq!(replay.replay_session(&path).await.unwrap(), 1);
+    /// let vm = replay.get_virtual_machine("vm1").unwrap();
+    /// assert_eq!(vm.cursor_position(), (10, 20));
+    /// # });
+    /// ```
+    pub fn export_session<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(&self.session())?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write session file: {:?}", path))
+    }
+
+    /// Re-injects the events of a session exported to `path`, in order.
+    ///
+    /// Events go through [`SimulationMode::send_event_to`], so each one waits
+    /// for the configured network latency. Machines of the session that do
+    /// not exist yet are added. Returns the number of replayed events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or an event
+    /// targets a machine missing from the session and the simulation.
+    pub async fn replay_session<P: AsRef<Path>>(&mut self, path: P) -> Result<usize> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read session file: {:?}", path))?;
+        let session: Session = serde_json::from_str(&json)
+            .with_context(|| format!("Invalid session file: {:?}", path))?;
+
+        for machine in session.machines {
+            if !self.virtual_machines.contains_key(&machine.name) {
+                self.add_virtual_machine(machine.name, machine.screen_width, machine.screen_height);
+            }
+        }
+
+        let count = session.events.len();
+        for recorded in session.events {
+            self.send_event_to(&recorded.target, recorded.event).await?;
+        }
+        Ok(count)
+    }
 }
 
 /// Statistics about simulation activity.
@@ -430,7 +1197,7 @@ impl SimulationMode {
 /// # tokio_test::block_on(async {
 /// let mut sim = SimulationMode::new();
 /// sim.add_virtual_machine("vm1".to_string(), 1920, 1080);
-/// sim.send_event_to("vm1", Event::Heartbeat).await.unwrap();
+/// sim.send_event_to("vm1", Event::Heartbeat { sent_at_ms: 0 }).await.unwrap();
 ///
 /// let stats = sim.get_statistics();
 /// assert_eq!(stats.total_events_sent, 1);
@@ -461,4 +1228,23 @@ mod tests {
         let sim = SimulationMode::new();
         assert_eq!(sim.virtual_machine_count(), 0);
     }
+
+    #[tokio::test]
+    async fn test_session_events_are_ordered_across_machines() {
+        let mut sim = SimulationMode::new();
+        sim.add_virtual_machine("b".to_string(), 1920, 1080);
+        sim.add_virtual_machine("a".to_string(), 1920, 1080);
+        sim.send_event_to("b", Event::MouseMove { x: 1, y: 1 })
+            .await
+            .unwrap();
+        sim.send_event_to("a", Event::MouseMove { x: 2, y: 2 })
+            .await
+            .unwrap();
+        sim.send_event_to("b", Event::FocusRelease).await.unwrap();
+
+        let session = sim.session();
+        assert_eq!(session.machines[0].name, "a");
+        let targets: Vec<&str> = session.events.iter().map(|e| e.target.as_str()).collect();
+        assert_eq!(targets, vec!["b", "a", "b"]);
+    }
 }
@@ -1,4 +1,13 @@
-use std::collections::HashMap;
+use anyhow::Result;
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{hash_map, HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::core::config::Config;
+use crate::core::edge_crossing::entry_point;
 
 /// Represents the network topology of connected machines in a multi-screen setup.
 ///
@@ -22,6 +31,9 @@ use std::collections::HashMap;
 pub struct Topology {
     machines: HashMap<String, Position>,
     edges: HashMap<String, HashMap<Edge, String>>,
+    links: HashMap<String, HashMap<Edge, EdgeLink>>,
+    corner_preference: CornerPreference,
+    edge_trigger: EdgeTrigger,
 }
 
 /// Represents a 2D position in the topology coordinate system.
@@ -39,7 +51,7 @@ pub struct Topology {
 /// assert_eq!(pos.x, 0);
 /// assert_eq!(pos.y, 0);
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Position {
     /// The x-coordinate in the topology grid.
     pub x: i32,
@@ -60,7 +72,8 @@ pub struct Position {
 /// let edge = Edge::Right;
 /// assert_eq!(edge, Edge::Right);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Edge {
     /// The right edge of the screen.
     Right,
@@ -72,6 +85,374 @@ pub enum Edge {
     Bottom,
 }
 
+impl Edge {
+    /// All four edges, in the order they are checked for horizontal-first detection.
+    pub const ALL: [Edge; 4] = [Edge::Right, Edge::Left, Edge::Top, Edge::Bottom];
+
+    /// Returns the edge on the opposite side of the screen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::topology::Edge;
+    ///
+    /// assert_eq!(Edge::Right.opposite(), Edge::Left);
+    /// assert_eq!(Edge::Top.opposite(), Edge::Bottom);
+    /// ```
+    pub fn opposite(self) -> Edge {
+        match self {
+            Edge::Right => Edge::Left,
+            Edge::Left => Edge::Right,
+            Edge::Top => Edge::Bottom,
+            Edge::Bottom => Edge::Top,
+        }
+    }
+
+    /// Returns the grid offset `(dx, dy)` of the neighbor that sits on this edge.
+    ///
+    /// The y axis grows downwards, so the machine above has `dy = -1`.
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Edge::Right => (1, 0),
+            Edge::Left => (-1, 0),
+            Edge::Top => (0, -1),
+            Edge::Bottom => (0, 1),
+        }
+    }
+
+    /// Returns whether this is the left or right edge.
+    pub fn is_horizontal(self) -> bool {
+        matches!(self, Edge::Left | Edge::Right)
+    }
+
+    /// Returns the lowercase name used in configuration files.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Edge::Right => "right",
+            Edge::Left => "left",
+            Edge::Top => "top",
+            Edge::Bottom => "bottom",
+        }
+    }
+}
+
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Edge {
+    type Err = anyhow::Error;
+
+    /// Parses a configuration edge name (`right`, `left`, `top`, `bottom`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::topology::Edge;
+    ///
+    /// assert_eq!("right".parse::<Edge>().unwrap(), Edge::Right);
+    /// assert!("diagonal".parse::<Edge>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "right" => Ok(Edge::Right),
+            "left" => Ok(Edge::Left),
+            "top" => Ok(Edge::Top),
+            "bottom" => Ok(Edge::Bottom),
+            _ => anyhow::bail!("Unknown edge direction: {}", s),
+        }
+    }
+}
+
+/// Tie-breaking rule used when the cursor sits in a screen corner.
+///
+/// In a corner the cursor is close to one horizontal and one vertical edge at
+/// the same time. If both edges lead to a neighbor, this preference decides
+/// which one wins so that detection stays deterministic.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::topology::CornerPreference;
+///
+/// assert_eq!(CornerPreference::default(), CornerPreference::Horizontal);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CornerPreference {
+    /// Prefer the left/right neighbor (default).
+    #[default]
+    Horizontal,
+    /// Prefer the top/bottom neighbor.
+    Vertical,
+}
+
+/// A corner of the screen.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::topology::{Corner, Edge};
+///
+/// assert_eq!(Corner::TopRight.edges(), [Edge::Right, Edge::Top]);
+/// assert!(Corner::TopRight.contains((1900, 10), (1920, 1080), 50));
+/// assert!(!Corner::TopRight.contains((1900, 100), (1920, 1080), 50));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    /// The top-left corner.
+    TopLeft,
+    /// The top-right corner.
+    TopRight,
+    /// The bottom-left corner.
+    BottomLeft,
+    /// The bottom-right corner.
+    BottomRight,
+}
+
+impl Corner {
+    /// Returns the horizontal and the vertical edge meeting at this corner.
+    pub fn edges(self) -> [Edge; 2] {
+        match self {
+            Corner::TopLeft => [Edge::Left, Edge::Top],
+            Corner::TopRight => [Edge::Right, Edge::Top],
+            Corner::BottomLeft => [Edge::Left, Edge::Bottom],
+            Corner::BottomRight => [Edge::Right, Edge::Bottom],
+        }
+    }
+
+    /// Returns whether `position` is in the `size_px` × `size_px` box of this
+    /// corner on a screen of `screen_size`.
+    pub fn contains(self, position: (i32, i32), screen_size: (u32, u32), size_px: u32) -> bool {
+        let (x, y) = position;
+        let size = size_px as i32;
+        let [horizontal, vertical] = self.edges();
+        let in_columns = match horizontal {
+            Edge::Left => x < size,
+            _ => x >= screen_size.0 as i32 - size,
+        };
+        let in_rows = match vertical {
+            Edge::Top => y < size,
+            _ => y >= screen_size.1 as i32 - size,
+        };
+        in_columns && in_rows
+    }
+}
+
+/// Which part of a screen edge transfers focus.
+///
+/// Configured as `behavior.edge_trigger`, with `type: full_edge` or a hotspot:
+///
+/// ```yaml
+/// edge_trigger:
+///   type: hotspot
+///   corner: top_right
+///   size_px: 50
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::topology::EdgeTrigger;
+///
+/// assert_eq!(EdgeTrigger::default(), EdgeTrigger::FullEdge);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EdgeTrigger {
+    /// Only a `size_px` × `size_px` box in `corner` transfers focus, through
+    /// the corner's horizontal edge or, without a neighbor there, its vertical one.
+    Hotspot {
+        /// The corner holding the hotspot.
+        corner: Corner,
+        /// Side of the hotspot box, in pixels.
+        size_px: u32,
+    },
+    /// The whole edge transfers focus within the edge threshold (default).
+    #[default]
+    FullEdge,
+}
+
+/// Where an edge of this machine leads, as configured in `edges`.
+///
+/// Written either as the neighbor's name or, for screens that do not line up
+/// pixel for pixel, in full:
+///
+/// ```yaml
+/// edges:
+///   left: laptop
+///   right: { target: agent1, offset_px: -300, scale: 0.75 }
+///   top: { wrap: true }
+/// ```
+///
+/// The coordinate along the edge is multiplied by `scale`, then shifted by
+/// `offset_px`, on its way to the neighbor. An edge without a target and with
+/// `wrap: true` sends the cursor round to the opposite side of this screen.
+///
+/// # Examples
+///
+/// ```
+/// use multishiva::core::topology::EdgeLink;
+///
+/// let link: EdgeLink = serde_yaml::from_str("{ target: agent1, offset_px: -300 }").unwrap();
+/// assert_eq!(link.target.as_deref(), Some("agent1"));
+/// assert_eq!(link.map_along(900), 600);
+///
+/// let simple: EdgeLink = serde_yaml::from_str("agent1").unwrap();
+/// assert_eq!(simple, EdgeLink::to("agent1"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeLink {
+    /// Machine beyond the edge, `None` for an edge that only wraps
+    pub target: Option<String>,
+    /// Pixels added to the coordinate along the edge, after scaling
+    pub offset_px: i32,
+    /// Factor applied to the coordinate along the edge
+    pub scale: f64,
+    /// Whether the cursor wraps to the opposite edge when there is no target
+    pub wrap: bool,
+}
+
+impl EdgeLink {
+    /// Returns a plain link to `target`, as written with the simple form.
+    pub fn to(target: impl Into<String>) -> Self {
+        Self {
+            target: Some(target.into()),
+            offset_px: 0,
+            scale: 1.0,
+            wrap: false,
+        }
+    }
+
+    /// Returns a link without target that wraps the cursor around.
+    pub fn wrapping() -> Self {
+        Self {
+            target: None,
+            offset_px: 0,
+            scale: 1.0,
+            wrap: true,
+        }
+    }
+
+    /// Maps a coordinate along the edge to the neighbor's side.
+    pub fn map_along(&self, coordinate: i32) -> i32 {
+        (coordinate as f64 * self.scale).round() as i32 + self.offset_px
+    }
+
+    /// Returns the link for the way back, undoing the offset and scale.
+    pub fn inverse(&self, target: impl Into<String>) -> Self {
+        Self {
+            target: Some(target.into()),
+            offset_px: (-self.offset_px as f64 / self.scale).round() as i32,
+            scale: 1.0 / self.scale,
+            wrap: false,
+        }
+    }
+
+    /// Returns whether the link only names its target.
+    fn is_simple(&self) -> bool {
+        self.target.is_some() && self.offset_px == 0 && self.scale == 1.0 && !self.wrap
+    }
+}
+
+impl From<&str> for EdgeLink {
+    fn from(target: &str) -> Self {
+        Self::to(target)
+    }
+}
+
+impl From<String> for EdgeLink {
+    fn from(target: String) -> Self {
+        Self::to(target)
+    }
+}
+
+impl fmt::Display for EdgeLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            Some(target) => f.write_str(target)?,
+            None if self.wrap => return f.write_str("wrap"),
+            None => return f.write_str("nothing"),
+        }
+        if !self.is_simple() {
+            write!(f, " (offset {}px, scale {})", self.offset_px, self.scale)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for EdgeLink {
+    /// Writes the simple form when the link only names its target.
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        if let (true, Some(target)) = (self.is_simple(), &self.target) {
+            return serializer.serialize_str(target);
+        }
+        let mut state = serializer.serialize_struct("EdgeLink", 4)?;
+        match &self.target {
+            Some(target) => state.serialize_field("target", target)?,
+            None => state.skip_field("target")?,
+        }
+        state.serialize_field("offset_px", &self.offset_px)?;
+        state.serialize_field("scale", &self.scale)?;
+        state.serialize_field("wrap", &self.wrap)?;
+        state.end()
+    }
+}
+
+/// The full form of an [`EdgeLink`], with its defaults.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EdgeLinkFields {
+    target: Option<String>,
+    #[serde(default)]
+    offset_px: i32,
+    #[serde(default = "default_scale")]
+    scale: f64,
+    #[serde(default)]
+    wrap: bool,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+impl<'de> Deserialize<'de> for EdgeLink {
+    /// Accepts a machine name or the full form.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct LinkVisitor;
+
+        impl<'de> Visitor<'de> for LinkVisitor {
+            type Value = EdgeLink;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a machine name or a map with target, offset_px, scale and wrap")
+            }
+
+            fn visit_str<E: de::Error>(self, target: &str) -> std::result::Result<EdgeLink, E> {
+                Ok(EdgeLink::to(target))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                map: A,
+            ) -> std::result::Result<EdgeLink, A::Error> {
+                let fields =
+                    EdgeLinkFields::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(EdgeLink {
+                    target: fields.target,
+                    offset_px: fields.offset_px,
+                    scale: fields.scale,
+                    wrap: fields.wrap,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(LinkVisitor)
+    }
+}
+
 impl Default for Topology {
     fn default() -> Self {
         Self::new()
@@ -95,9 +476,143 @@ impl Topology {
         Self {
             machines: HashMap::new(),
             edges: HashMap::new(),
+            links: HashMap::new(),
+            corner_preference: CornerPreference::default(),
+            edge_trigger: EdgeTrigger::default(),
         }
     }
 
+    /// Builds a topology from a loaded configuration.
+    ///
+    /// Machines listed in `layout` are placed at their configured grid positions.
+    /// The local machine (`self_name`) defaults to `(0, 0)` when the layout does not
+    /// mention it, and every neighbor declared in `edges` is placed next to it
+    /// (unless the layout already positions it). Each configured edge is also
+    /// mirrored on the neighbor so it knows how to route back.
+    ///
+    /// The offset and scale of each edge are kept for [`Topology::entry_point`],
+    /// inverted on the mirrored edge. Edges without a target only wrap.
+    ///
+    /// Unknown edge directions are logged and skipped. Call [`Topology::validate`]
+    /// afterwards to reject contradictory layouts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::config::Config;
+    /// use multishiva::core::topology::{Edge, Topology};
+    ///
+    /// let mut config = Config::default();
+    /// config.self_name = "host".to_string();
+    /// config.edges.insert("right".to_string(), "agent1".into());
+    ///
+    /// let topology = Topology::from_config(&config);
+    /// assert_eq!(topology.machine_count(), 2);
+    /// assert_eq!(topology.get_neighbor("host", &Edge::Right), Some(&"agent1".to_string()));
+    /// assert_eq!(topology.get_neighbor("agent1", &Edge::Left), Some(&"host".to_string()));
+    /// ```
+    pub fn from_config(config: &Config) -> Self {
+        let mut topology = Self::new();
+        if let Some(trigger) = config.behavior.as_ref().and_then(|b| b.edge_trigger) {
+            topology.set_edge_trigger(trigger);
+        }
+
+        for (name, pos) in &config.layout {
+            topology.add_machine(name.clone(), *pos);
+        }
+
+        let origin = *topology
+            .machines
+            .entry(config.self_name.clone())
+            .or_insert(Position { x: 0, y: 0 });
+
+        for (direction, link) in &config.edges {
+            let edge = match direction.parse::<Edge>() {
+                Ok(edge) => edge,
+                Err(_) => {
+                    tracing::warn!("Unknown edge direction: {}", direction);
+                    continue;
+                }
+            };
+            topology.set_edge_link(config.self_name.clone(), edge, link.clone());
+            let Some(target) = &link.target else {
+                continue;
+            };
+
+            let (dx, dy) = edge.offset();
+            topology.machines.entry(target.clone()).or_insert(Position {
+                x: origin.x + dx,
+                y: origin.y + dy,
+            });
+
+            topology.add_edge(config.self_name.clone(), edge, target.clone());
+            let mirrored = topology.edges.entry(target.clone()).or_default();
+            if let hash_map::Entry::Vacant(entry) = mirrored.entry(edge.opposite()) {
+                entry.insert(config.self_name.clone());
+                topology.set_edge_link(
+                    target.clone(),
+                    edge.opposite(),
+                    link.inverse(config.self_name.clone()),
+                );
+            }
+
+            tracing::debug!(
+                "Added edge: {} -> {:?} -> {}",
+                config.self_name,
+                edge,
+                target
+            );
+        }
+
+        topology
+    }
+
+    /// Sets which part of the edges [`Topology::detect_edge`] reacts to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::topology::{Corner, EdgeTrigger, Topology};
+    ///
+    /// let mut topology = Topology::new();
+    /// let hotspot = EdgeTrigger::Hotspot { corner: Corner::TopRight, size_px: 50 };
+    /// topology.set_edge_trigger(hotspot);
+    /// assert_eq!(topology.edge_trigger(), hotspot);
+    /// ```
+    pub fn set_edge_trigger(&mut self, trigger: EdgeTrigger) {
+        self.edge_trigger = trigger;
+    }
+
+    /// Returns which part of the edges transfers focus.
+    pub fn edge_trigger(&self) -> EdgeTrigger {
+        self.edge_trigger
+    }
+
+    /// Sets how corner positions are resolved by [`Topology::detect_edge`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::topology::{CornerPreference, Topology};
+    ///
+    /// let mut topology = Topology::new();
+    /// topology.set_corner_preference(CornerPreference::Vertical);
+    /// assert_eq!(topology.corner_preference(), CornerPreference::Vertical);
+    /// ```
+    pub fn set_corner_preference(&mut self, preference: CornerPreference) {
+        self.corner_preference = preference;
+    }
+
+    /// Returns the current corner tie-breaking rule.
+    pub fn corner_preference(&self) -> CornerPreference {
+        self.corner_preference
+    }
+
+    /// Returns the grid position of a machine, if it is part of the topology.
+    pub fn position(&self, machine: &str) -> Option<Position> {
+        self.machines.get(machine).copied()
+    }
+
     /// Returns the number of machines in the topology.
     ///
     /// # Examples
@@ -168,11 +683,86 @@ impl Topology {
         self.edges.entry(from).or_default().insert(edge, to);
     }
 
+    /// Sets the offset, scale and wrapping of an edge of `machine`.
+    ///
+    /// The link's target is not added as a neighbor; use [`Topology::add_edge`].
+    pub fn set_edge_link(&mut self, machine: String, edge: Edge, link: EdgeLink) {
+        self.links.entry(machine).or_default().insert(edge, link);
+    }
+
+    /// Returns the configured link of an edge of `machine`, if any.
+    pub fn edge_link(&self, machine: &str, edge: Edge) -> Option<&EdgeLink> {
+        self.links.get(machine)?.get(&edge)
+    }
+
+    /// Maps `position` on `machine` to the neighbor's side of `edge`.
+    ///
+    /// The coordinate along the edge goes through the edge's offset and scale;
+    /// the other one is kept. Positions go through unchanged on edges without
+    /// a link.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::topology::{Edge, EdgeLink, Topology};
+    ///
+    /// let mut topology = Topology::new();
+    /// let link = EdgeLink { offset_px: -300, ..EdgeLink::to("agent1") };
+    /// topology.set_edge_link("host".to_string(), Edge::Right, link);
+    ///
+    /// assert_eq!(topology.map_position("host", Edge::Right, (1915, 900)), (1915, 600));
+    /// assert_eq!(topology.map_position("host", Edge::Left, (5, 900)), (5, 900));
+    /// ```
+    pub fn map_position(&self, machine: &str, edge: Edge, position: (i32, i32)) -> (i32, i32) {
+        let Some(link) = self.edge_link(machine, edge) else {
+            return position;
+        };
+        let (x, y) = position;
+        if edge.is_horizontal() {
+            (x, link.map_along(y))
+        } else {
+            (link.map_along(x), y)
+        }
+    }
+
+    /// Returns where the cursor enters the neighbor of `machine` through `edge`.
+    ///
+    /// Like [`entry_point`], on a neighbor of `target_size`, after mapping the
+    /// position with [`Topology::map_position`].
+    pub fn entry_point(
+        &self,
+        machine: &str,
+        edge: Edge,
+        position: (i32, i32),
+        target_size: (u32, u32),
+        threshold: i32,
+    ) -> (i32, i32) {
+        entry_point(
+            edge,
+            self.map_position(machine, edge, position),
+            target_size,
+            threshold,
+        )
+    }
+
+    /// Returns whether the cursor wraps around at `edge` of `machine`.
+    ///
+    /// Only an edge marked `wrap: true` with no neighbor wraps.
+    pub fn wraps(&self, machine: &str, edge: Edge) -> bool {
+        self.edge_link(machine, edge).is_some_and(|link| link.wrap)
+            && self.get_neighbor(machine, &edge).is_none()
+    }
+
     /// Retrieves the neighboring machine connected to a specific edge.
     ///
     /// Returns the name of the machine connected to the given edge of the specified machine,
     /// or `None` if the machine doesn't exist or has no connection on that edge.
     ///
+    /// Explicit edges added with [`Topology::add_edge`] take precedence. Otherwise the
+    /// neighbor is derived from positions: the machine one grid cell away in the
+    /// direction of `edge` is returned. This works for any machine in the topology,
+    /// not only the local one.
+    ///
     /// # Arguments
     ///
     /// * `machine` - The name of the machine to query
@@ -195,16 +785,85 @@ impl Topology {
     ///
     /// assert_eq!(topology.get_neighbor("main", &Edge::Right), Some(&"aux".to_string()));
     /// assert_eq!(topology.get_neighbor("main", &Edge::Left), None);
+    ///
+    /// // Derived from positions: "aux" sits directly to the right of "main"
+    /// assert_eq!(topology.get_neighbor("aux", &Edge::Left), Some(&"main".to_string()));
     /// ```
     pub fn get_neighbor(&self, machine: &str, edge: &Edge) -> Option<&String> {
-        self.edges.get(machine)?.get(edge)
+        if let Some(neighbor) = self.edges.get(machine).and_then(|edges| edges.get(edge)) {
+            return Some(neighbor);
+        }
+
+        let pos = self.machines.get(machine)?;
+        let (dx, dy) = edge.offset();
+        let target = Position {
+            x: pos.x + dx,
+            y: pos.y + dy,
+        };
+
+        self.machines
+            .iter()
+            .find(|(name, p)| **p == target && name.as_str() != machine)
+            .map(|(name, _)| name)
+    }
+
+    /// Finds the shortest chain of machines leading from `from` to `to`.
+    ///
+    /// Walks the neighbors returned by [`Topology::get_neighbor`] breadth-first,
+    /// trying edges in [`Edge::ALL`] order, so the result is deterministic.
+    /// The path starts with `from` and ends with `to`; it is `[from]` when both
+    /// are the same machine. Returns `None` if `to` cannot be reached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::topology::{Position, Topology};
+    ///
+    /// let mut topology = Topology::new();
+    /// topology.add_machine("host".to_string(), Position { x: 0, y: 0 });
+    /// topology.add_machine("agent1".to_string(), Position { x: 1, y: 0 });
+    /// topology.add_machine("agent2".to_string(), Position { x: 2, y: 0 });
+    ///
+    /// assert_eq!(
+    ///     topology.shortest_path("host", "agent2"),
+    ///     Some(vec!["host".to_string(), "agent1".to_string(), "agent2".to_string()])
+    /// );
+    /// assert_eq!(topology.shortest_path("host", "unknown"), None);
+    /// ```
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let mut previous: HashMap<&str, &str> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        previous.insert(from, from);
+
+        while let Some(machine) = queue.pop_front() {
+            if machine == to {
+                let mut path = vec![to.to_string()];
+                let mut current = to;
+                while current != from {
+                    current = previous[current];
+                    path.push(current.to_string());
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for edge in Edge::ALL {
+                if let Some(neighbor) = self.get_neighbor(machine, &edge) {
+                    if !previous.contains_key(neighbor.as_str()) {
+                        previous.insert(neighbor, machine);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+        None
     }
 
     /// Detects which edge of the screen a cursor position is near.
     ///
     /// Determines if a cursor at position (x, y) is within the threshold distance
     /// of any configured edge on the specified machine. Only returns edges that
-    /// have connections configured for the machine.
+    /// have a neighbor, either explicitly or derived from positions.
     ///
     /// # Arguments
     ///
@@ -239,9 +898,8 @@ impl Topology {
     ///
     /// # Note
     ///
-    /// For bottom edge detection, the method currently assumes a screen height of 1080 pixels.
-    /// This is a temporary implementation detail that will be improved to use actual screen
-    /// dimensions in future versions.
+    /// This method assumes a screen height of 1080 pixels for bottom edge detection.
+    /// Use [`Topology::detect_edge_on_screen`] when the actual height is known.
     pub fn detect_edge(
         &self,
         machine: &str,
@@ -250,40 +908,197 @@ impl Topology {
         screen_width: u32,
         threshold: u32,
     ) -> Option<Edge> {
-        // Check if machine has any configured edges
-        let machine_edges = self.edges.get(machine)?;
+        self.detect_edge_on_screen(machine, x, y, screen_width, 1080, threshold)
+    }
 
+    /// Detects which edge of a screen of known size a cursor position is near.
+    ///
+    /// Behaves like [`Topology::detect_edge`] but uses the real screen height for
+    /// the bottom edge. When the cursor is in a corner and both the horizontal and
+    /// the vertical edge lead to a neighbor, the [`CornerPreference`] decides which
+    /// edge is returned. With an [`EdgeTrigger::Hotspot`], only the edges of the
+    /// hotspot corner are detected, while the cursor is inside its box.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::topology::{CornerPreference, Edge, Position, Topology};
+    ///
+    /// let mut topology = Topology::new();
+    /// topology.add_machine("host".to_string(), Position { x: 0, y: 0 });
+    /// topology.add_machine("right".to_string(), Position { x: 1, y: 0 });
+    /// topology.add_machine("below".to_string(), Position { x: 0, y: 1 });
+    ///
+    /// // Bottom-right corner: horizontal wins by default
+    /// let edge = topology.detect_edge_on_screen("host", 2559, 1439, 2560, 1440, 5);
+    /// assert_eq!(edge, Some(Edge::Right));
+    ///
+    /// topology.set_corner_preference(CornerPreference::Vertical);
+    /// let edge = topology.detect_edge_on_screen("host", 2559, 1439, 2560, 1440, 5);
+    /// assert_eq!(edge, Some(Edge::Bottom));
+    /// ```
+    pub fn detect_edge_on_screen(
+        &self,
+        machine: &str,
+        x: i32,
+        y: i32,
+        screen_width: u32,
+        screen_height: u32,
+        threshold: u32,
+    ) -> Option<Edge> {
         let threshold = threshold as i32;
         let screen_width = screen_width as i32;
+        let screen_height = screen_height as i32;
+        let in_zone = |edge: Edge| match self.edge_trigger {
+            EdgeTrigger::FullEdge => match edge {
+                Edge::Right => x >= screen_width - threshold,
+                Edge::Left => x < threshold,
+                Edge::Top => y < threshold,
+                Edge::Bottom => y >= screen_height - threshold,
+            },
+            EdgeTrigger::Hotspot { corner, size_px } => {
+                corner.edges().contains(&edge)
+                    && corner.contains((x, y), (screen_width as u32, screen_height as u32), size_px)
+            }
+        };
+        let detected = |edge: &Edge| in_zone(*edge) && self.get_neighbor(machine, edge).is_some();
 
-        // Check right edge
-        if machine_edges.contains_key(&Edge::Right) && x >= screen_width - threshold {
-            return Some(Edge::Right);
-        }
+        let horizontal = [Edge::Right, Edge::Left].into_iter().find(detected);
+        let vertical = [Edge::Top, Edge::Bottom].into_iter().find(detected);
 
-        // Check left edge
-        if machine_edges.contains_key(&Edge::Left) && x < threshold {
-            return Some(Edge::Left);
+        match (horizontal, vertical) {
+            (Some(h), Some(v)) => match self.corner_preference {
+                CornerPreference::Horizontal => Some(h),
+                CornerPreference::Vertical => Some(v),
+            },
+            (h, v) => h.or(v),
         }
+    }
 
-        // Check top edge
-        if machine_edges.contains_key(&Edge::Top) && y < threshold {
-            return Some(Edge::Top);
+    /// Checks that the topology is geometrically consistent.
+    ///
+    /// The following layouts are rejected:
+    /// - A machine declared as its own neighbor
+    /// - Contradictory reciprocal edges (e.g. `A.right = B` but `B.left = C`)
+    /// - An edge between two positioned machines that are not adjacent in the grid
+    /// - Edge chains that place a machine at two different positions (e.g. a
+    ///   cycle `A.right = B`, `B.right = A`), or two machines at the same position
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first inconsistency found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multishiva::core::topology::{Edge, Topology};
+    ///
+    /// let mut topology = Topology::new();
+    /// topology.add_edge("a".to_string(), Edge::Right, "b".to_string());
+    /// topology.add_edge("b".to_string(), Edge::Left, "a".to_string());
+    /// assert!(topology.validate().is_ok());
+    ///
+    /// topology.add_edge("b".to_string(), Edge::Left, "c".to_string());
+    /// assert!(topology.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        // Undirected adjacency: each edge constrains the relative offset of both ends
+        let mut links: HashMap<&str, Vec<(&str, i32, i32)>> = HashMap::new();
+
+        for (from, edges) in &self.edges {
+            for (edge, to) in edges {
+                if from == to {
+                    anyhow::bail!(
+                        "Machine '{}' cannot be its own neighbor on the {} edge",
+                        from,
+                        edge
+                    );
+                }
+
+                if let Some(back) = self
+                    .edges
+                    .get(to)
+                    .and_then(|edges| edges.get(&edge.opposite()))
+                {
+                    if back != from {
+                        anyhow::bail!(
+                            "Contradictory topology: {}.{} = {} but {}.{} = {}",
+                            from,
+                            edge,
+                            to,
+                            to,
+                            edge.opposite(),
+                            back
+                        );
+                    }
+                }
+
+                let (dx, dy) = edge.offset();
+                if let (Some(a), Some(b)) = (self.machines.get(from), self.machines.get(to)) {
+                    if b.x - a.x != dx || b.y - a.y != dy {
+                        anyhow::bail!(
+                            "'{}' is on the {} edge of '{}' but their positions ({}, {}) and ({}, {}) are not adjacent",
+                            to,
+                            edge,
+                            from,
+                            a.x,
+                            a.y,
+                            b.x,
+                            b.y
+                        );
+                    }
+                }
+
+                links.entry(from).or_default().push((to, dx, dy));
+                links.entry(to).or_default().push((from, -dx, -dy));
+            }
         }
 
-        // Check bottom edge
-        // Note: We assume screen_height for bottom edge detection
-        // In practice, this would come from the actual screen dimensions
-        if machine_edges.contains_key(&Edge::Bottom) {
-            // Using a reasonable assumption for now
-            // This will be improved when we have actual screen info
-            let screen_height = 1080; // Default assumption
-            if y >= screen_height - threshold {
-                return Some(Edge::Bottom);
+        // Walk each connected group of machines and assign relative coordinates
+        let mut names: Vec<&str> = links.keys().copied().collect();
+        names.sort_unstable();
+
+        let mut coords: HashMap<&str, (i32, i32)> = HashMap::new();
+        for start in names {
+            if coords.contains_key(start) {
+                continue;
+            }
+
+            let mut occupied: HashMap<(i32, i32), &str> = HashMap::new();
+            let mut queue = VecDeque::new();
+            coords.insert(start, (0, 0));
+            occupied.insert((0, 0), start);
+            queue.push_back(start);
+
+            while let Some(current) = queue.pop_front() {
+                let (cx, cy) = coords[current];
+                for &(next, dx, dy) in links.get(current).into_iter().flatten() {
+                    let expected = (cx + dx, cy + dy);
+                    match coords.get(next) {
+                        Some(&actual) if actual != expected => {
+                            anyhow::bail!(
+                                "Topology edges form an impossible cycle: '{}' would be placed at two different positions",
+                                next
+                            );
+                        }
+                        Some(_) => {}
+                        None => {
+                            if let Some(other) = occupied.insert(expected, next) {
+                                anyhow::bail!(
+                                    "Machines '{}' and '{}' would occupy the same position",
+                                    other,
+                                    next
+                                );
+                            }
+                            coords.insert(next, expected);
+                            queue.push_back(next);
+                        }
+                    }
+                }
             }
         }
 
-        None
+        Ok(())
     }
 
     /// Calculates the relative cursor position when transitioning between screens.
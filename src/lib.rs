@@ -37,8 +37,10 @@
 //! - [`core::events`] - Input event handling and forwarding
 //! - [`core::focus`] - Focus management across machines
 //! - [`core::topology`] - Machine layout and edge definitions
+//! - [`core::edge_crossing`] - Edge dwell (friction) before focus transfers
 //!
 //! ### Security
+//! - [`core::audit`] - Tamper-evident audit log
 //! - [`core::fingerprint`] - TLS fingerprint verification
 //! - [`core::keyring`] - Secure credential storage using system keyring
 //! - [`core::permissions`] - System permission checks
@@ -46,7 +48,10 @@
 //! ### Features
 //! - [`core::discovery`] - mDNS auto-discovery of peer machines
 //! - [`core::clipboard`] - Cross-machine clipboard synchronization
+//! - [`core::ipc`] - Control endpoint for `multishiva status` queries
 //! - [`core::logging`] - Structured logging with rotation
+//! - [`core::metrics`] - Prometheus metrics endpoint
+//! - [`core::scenario`] - Scripted scenarios for simulation mode
 //! - [`core::simulation`] - Testing mode for development
 //!
 //! ### User Interface
@@ -1,17 +1,59 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::Utc;
 use multishiva::cli;
+use multishiva::core::audit::AuditLog;
+use multishiva::core::clipboard::{
+    ClipboardContent, ClipboardHistory, ClipboardManager, DEFAULT_HISTORY_CAPACITY,
+};
+use multishiva::core::clipboard_transfer::{
+    ClipboardOutbox, ClipboardReassembler, DEFAULT_MAX_CLIPBOARD_BYTES,
+};
 use multishiva::core::config::{Config, ConfigMode};
-use multishiva::core::focus::FocusManager;
-use multishiva::core::network::Network;
+use multishiva::core::daemon::{self, PidFile, SignalAction, SignalListener};
+use multishiva::core::edge_crossing::{
+    detect_crossing, edge_at, entry_point, is_in_edge_zone, return_edge, scale_position, Crossing,
+    DeltaScaler, EdgeCrossingController,
+};
+use multishiva::core::event_recorder::{EventRecorder, EventReplayer};
+use multishiva::core::events::{Event, Key};
+use multishiva::core::focus::{FocusManager, IdleRelease, ModifierTracker};
+use multishiva::core::hotkey_parser::{is_triggered, parse_hotkey};
+use multishiva::core::input_backend::InputBackend;
+use multishiva::core::ipc::{
+    ClipboardEntry, ClipboardHistoryProvider, ClipboardRestorer, DiscoveredPeer, PeerStatus,
+    PeerTraffic, PskRotator, ShutdownHandler, StatusProvider, StatusResponse,
+};
+use multishiva::core::keyring::{KeyringManager, KEYRING_PLACEHOLDER};
+use multishiva::core::metrics;
+use multishiva::core::network::{
+    compute_psk_hash, next_focus_hop, AgentAllowlist, BindAddress, ConnectionEvent,
+    ConnectionStatus, FocusHop, Network, NetworkConfig, NetworkMonitor, SocketOptions,
+};
 use multishiva::core::permissions;
+use multishiva::core::scenario::{Scenario, ScenarioRunner};
 use multishiva::core::simulation::SimulationMode;
-use multishiva::core::topology::{Edge, Position, Topology};
+use multishiva::core::topology::{Edge, EdgeTrigger, Topology};
 use tokio::signal;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Parse and validate CLI arguments
+    let args = cli::parse_and_validate()?;
+
+    // Completion scripts go to stdout, before any log line can
+    if let Some(cli::Command::Completions { shell }) = &args.command {
+        return cli::write_completions(*shell, &mut std::io::stdout());
+    }
+
+    // The background process started here runs the daemon; systemd keeps us in the foreground
+    if args.daemon && std::env::var_os(daemon::DETACHED_ENV).is_none() && !daemon::under_systemd() {
+        let pid = daemon::detach()?;
+        println!("MultiShiva running in the background with PID {}", pid);
+        return Ok(());
+    }
+
     // Initialize logging system with default configuration
-    use multishiva::core::logging::{init_logging, LogConfig, LogLevel};
+    use multishiva::core::logging::{init_logging, LogConfig, LogFormat, LogLevel};
 
     let log_config = LogConfig {
         level: if cfg!(debug_assertions) {
@@ -23,21 +65,112 @@ async fn main() -> Result<()> {
         enable_console: true,
         log_dir: None, // Use default: ~/.local/share/multishiva/logs/
         filter: std::env::var("RUST_LOG").ok(),
+        format: match std::env::var("MULTISHIVA_LOG_FORMAT") {
+            Ok(format) => format.parse()?,
+            Err(_) => LogFormat::Pretty,
+        },
+        max_file_bytes: match std::env::var("MULTISHIVA_LOG_MAX_FILE_BYTES") {
+            Ok(bytes) => Some(bytes.parse().map_err(|e| {
+                anyhow::anyhow!("Invalid MULTISHIVA_LOG_MAX_FILE_BYTES '{}': {}", bytes, e)
+            })?),
+            Err(_) => None,
+        },
+        compress_rotated: std::env::var("MULTISHIVA_LOG_COMPRESS")
+            .is_ok_and(|v| v == "1" || v == "true"),
     };
 
     init_logging(log_config)?;
 
     tracing::info!("🕉️  MultiShiva v{} starting...", env!("CARGO_PKG_VERSION"));
 
-    // Parse and validate CLI arguments
-    let args = cli::parse_and_validate()?;
-
     // Check if GUI mode is requested
     if args.gui {
         tracing::info!("🖥️  Launching GUI mode...");
         return multishiva::app::launch_gui();
     }
 
+    if args.list_devices {
+        return run_list_devices();
+    }
+
+    // Query a running daemon instead of starting one
+    if let Some(json) = args.status_request() {
+        return print_daemon_status(json).await;
+    }
+    if let Some(cli::Command::RotatePsk { new_psk }) = &args.command {
+        return rotate_daemon_psk(new_psk.clone()).await;
+    }
+    if let Some(cli::Command::Clipboard { action }) = &args.command {
+        return run_clipboard_command(action).await;
+    }
+    if let Some(cli::Command::Keyring { action }) = &args.command {
+        return run_keyring_command(action);
+    }
+    if let Some(cli::Command::Logs(logs)) = &args.command {
+        return run_logs_command(logs).await;
+    }
+    if let Some(cli::Command::Doctor { json }) = &args.command {
+        let config_path = args.config.as_deref().unwrap_or("multishiva.yml");
+        return run_doctor(std::path::Path::new(config_path), *json);
+    }
+    if let Some(cli::Command::Record { output }) = &args.command {
+        return run_record(output).await;
+    }
+    if let Some(cli::Command::Replay { input, speed }) = &args.command {
+        return run_replay(input, *speed).await;
+    }
+    if let Some(cli::Command::Trust {
+        machine,
+        fingerprint,
+        ..
+    }) = &args.command
+    {
+        let machine = machine.clone();
+        let action = match fingerprint.clone() {
+            Some(hash) => cli::FingerprintsCommand::Trust { machine, hash },
+            None => cli::FingerprintsCommand::Remove { machine },
+        };
+        return run_fingerprints_command(&action);
+    }
+    if let Some(cli::Command::Fingerprints { action }) = &args.command {
+        return run_fingerprints_command(action);
+    }
+    if let Some(cli::Command::ListAgents) = &args.command {
+        return run_list_agents(args.config.as_deref().unwrap_or("multishiva.yml")).await;
+    }
+    if let Some(cli::Command::Pair { code, host }) = &args.command {
+        let config_path = args.config.as_deref().unwrap_or("multishiva.yml");
+        return match code {
+            Some(code) if !host => run_pair_agent(code, config_path, args.host.as_deref()).await,
+            _ => run_pair_host(config_path).await,
+        };
+    }
+    if let Some(cli::Command::SetupPermissions) = &args.command {
+        return run_setup_permissions();
+    }
+    if let Some(cli::Command::Init(init)) = &args.command {
+        let path = args
+            .config
+            .as_ref()
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(Config::default_path);
+        let stdin = std::io::stdin();
+        let config = cli::init_config(init, &path, stdin.lock(), std::io::stdout())?;
+        println!("Configuration written to {}", path.display());
+        println!("Share this PSK with the other machines: {}", config.tls.psk);
+        return Ok(());
+    }
+
+    // Start the Prometheus metrics endpoint if requested
+    if let Some(metrics_addr) = args.metrics_addr {
+        metrics::install();
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve_metrics(metrics_addr).await {
+                tracing::error!("Metrics endpoint stopped: {}", e);
+            }
+        });
+    }
+
     // Load configuration
     let config_path = args.config.as_deref().unwrap_or("multishiva.yml");
     let config = Config::from_file(config_path).map_err(|e| {
@@ -45,7 +178,8 @@ async fn main() -> Result<()> {
             anyhow::anyhow!(
                 "Configuration file not found: {}\n\n\
                  To get started:\n\
-                 1. Copy the example config: cp multishiva.yml.example multishiva.yml\n\
+                 0. Generate one interactively: multishiva init --config multishiva.yml\n\
+                 1. Or copy the example config: cp multishiva.yml.example multishiva.yml\n\
                  2. Edit the config file to match your setup\n\
                  3. For agent mode: cp multishiva-agent.yml.example multishiva-agent.yml\n\n\
                  Or specify a custom config: multishiva --config /path/to/config.yml\n\n\
@@ -58,8 +192,15 @@ async fn main() -> Result<()> {
         }
     })?;
 
-    // Override config mode with CLI argument if provided
+    let trace = EventTrace::open(&args)?;
+
+    // A PSK moved to the keyring is only a placeholder in the file
     let mut config = config;
+    if config.resolve_secrets(&KeyringManager::new())? {
+        tracing::info!("🔑 PSK loaded from the system keyring");
+    }
+
+    // Override config mode with CLI argument if provided
     if let Some(cli_mode) = args.mode {
         let config_mode = match cli_mode {
             cli::Mode::Host => multishiva::core::config::ConfigMode::Host,
@@ -78,6 +219,20 @@ async fn main() -> Result<()> {
         );
         config.host_address = Some(host_address);
     }
+    if let Some(bind_address) = args.bind {
+        tracing::info!(
+            "CLI bind address override: {:?} -> {}",
+            config.bind_address,
+            bind_address
+        );
+        config.bind_address = Some(bind_address);
+    }
+    if let Some(secs) = args.discovery_timeout {
+        config
+            .behavior
+            .get_or_insert_with(Default::default)
+            .discovery_timeout_ms = Some(secs * 1000);
+    }
 
     config.validate()?;
 
@@ -85,7 +240,8 @@ async fn main() -> Result<()> {
     tracing::info!("Running as: {:?} on port {}", config.mode, config.port);
 
     // Build topology from configuration
-    let topology = build_topology(&config);
+    let topology = Topology::from_config(&config);
+    topology.validate()?;
     tracing::info!(
         "Topology configured with {} machine(s)",
         topology.machine_count()
@@ -93,8 +249,17 @@ async fn main() -> Result<()> {
 
     // Check if simulation mode is enabled
     if args.simulate {
-        run_simulation_mode(config, topology).await?;
+        run_simulation_mode(config, topology, args.scenario.as_deref(), trace.replayer).await?;
     } else {
+        // Removed when the daemon exits
+        let _pid_file = if args.daemon {
+            let pid_file = PidFile::create(PidFile::default_path())?;
+            tracing::info!("PID file written to {:?}", pid_file.path());
+            Some(pid_file)
+        } else {
+            None
+        };
+
         // Check system permissions before starting in production mode
         tracing::info!("Checking system permissions...");
         match permissions::check_permissions() {
@@ -117,43 +282,26 @@ async fn main() -> Result<()> {
             }
         }
 
-        run_production_mode(config, topology).await?;
+        run_production_mode(
+            config,
+            std::path::Path::new(config_path),
+            topology,
+            trace,
+            args.accept_new_fingerprint,
+            args.input_backend,
+        )
+        .await?;
     }
 
     Ok(())
 }
 
-fn build_topology(config: &Config) -> Topology {
-    let mut topology = Topology::new();
-
-    // Add self machine
-    topology.add_machine(config.self_name.clone(), Position { x: 0, y: 0 });
-
-    // Add configured edges
-    for (direction, target) in &config.edges {
-        let edge = match direction.as_str() {
-            "right" => Edge::Right,
-            "left" => Edge::Left,
-            "top" => Edge::Top,
-            "bottom" => Edge::Bottom,
-            _ => {
-                tracing::warn!("Unknown edge direction: {}", direction);
-                continue;
-            }
-        };
-        topology.add_edge(config.self_name.clone(), edge, target.clone());
-        tracing::debug!(
-            "Added edge: {} -> {:?} -> {}",
-            config.self_name,
-            edge,
-            target
-        );
-    }
-
-    topology
-}
-
-async fn run_simulation_mode(config: Config, _topology: Topology) -> Result<()> {
+async fn run_simulation_mode(
+    config: Config,
+    topology: Topology,
+    scenario: Option<&str>,
+    replay: Option<EventReplayer>,
+) -> Result<()> {
     tracing::info!("🎭 Running in SIMULATION mode");
 
     let mut sim = SimulationMode::new();
@@ -161,13 +309,65 @@ async fn run_simulation_mode(config: Config, _topology: Topology) -> Result<()>
     // Add host VM
     sim.add_virtual_machine(config.self_name.clone(), 1920, 1080);
 
-    // Add VMs for each edge target
-    for target in config.edges.values() {
+    // Add VMs for each edge target and positioned machine
+    let edge_targets = config.edge_targets();
+    for target in edge_targets.values().chain(config.layout.keys()) {
         sim.add_virtual_machine(target.clone(), 1920, 1080);
     }
 
+    sim.set_edges(&config.self_name, edge_targets);
+    if let Some(threshold) = config.behavior.as_ref().and_then(|b| b.edge_threshold_px) {
+        sim.set_edge_threshold(threshold as i32);
+    }
+    if let Some(trigger) = config.behavior.as_ref().and_then(|b| b.edge_trigger) {
+        sim.set_edge_trigger(trigger);
+    }
+
     tracing::info!("Created {} virtual machine(s)", sim.virtual_machine_count());
 
+    if let Some(replayer) = replay {
+        tracing::info!("Replaying {} recorded event(s)", replayer.events().len());
+        let report = sim.replay_recording(&replayer, &config.self_name).await?;
+        println!(
+            "final focus: {}",
+            report.final_focus.as_deref().unwrap_or(&config.self_name)
+        );
+        tracing::info!(
+            "Total events sent: {}",
+            sim.get_statistics().total_events_sent
+        );
+        return Ok(());
+    }
+
+    if let Some(path) = scenario {
+        let scenario = Scenario::from_file(path)?;
+        tracing::info!(
+            "Replaying scenario {} ({} step(s))",
+            path,
+            scenario.steps.len()
+        );
+
+        let behavior = config.behavior.as_ref();
+        let mut runner = ScenarioRunner::new(sim, topology, config.self_name.clone());
+        runner.set_edge_threshold(behavior.and_then(|b| b.edge_threshold_px).unwrap_or(10));
+        runner.set_friction_ms(behavior.and_then(|b| b.friction_ms).unwrap_or(0));
+
+        let report = runner.run(&scenario).await?;
+        println!("{}", report);
+        tracing::info!(
+            "Total events sent: {}",
+            runner.simulation().get_statistics().total_events_sent
+        );
+
+        if !report.passed() {
+            anyhow::bail!(
+                "Scenario expectations failed: {}",
+                report.failures.join("; ")
+            );
+        }
+        return Ok(());
+    }
+
     // Run simulation until Ctrl+C
     tracing::info!("Press Ctrl+C to exit");
     signal::ctrl_c().await?;
@@ -181,104 +381,1213 @@ async fn run_simulation_mode(config: Config, _topology: Topology) -> Result<()>
 
 /// Discover a MultiShiva host on the network using mDNS
 ///
-/// This function starts mDNS service discovery and waits for up to 5 seconds
-/// to find a host. If multiple hosts are found, it returns the first one.
+/// This function starts mDNS service discovery and waits for up to
+/// `--discovery-timeout` or `behavior.discovery_timeout_ms` (5 seconds by
+/// default) to find a host.
+/// If multiple hosts are found, it returns the first one.
 async fn discover_host_via_mdns(config: &Config) -> Result<String> {
-    use multishiva::core::discovery::Discovery;
+    use multishiva::core::discovery::{Discovery, DiscoveryConfig, DiscoveryError};
 
     tracing::info!("Starting mDNS discovery...");
     let discovery = Discovery::new(config.self_name.clone())?;
 
     // Start browsing for MultiShiva services
     discovery.start_browsing()?;
+    let local_psk_hash = compute_psk_hash(&config.tls.psk);
 
-    // Wait for discovery (check every 500ms for up to 5 seconds)
-    let max_attempts = 10;
-    for attempt in 1..=max_attempts {
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    let discovery_config = DiscoveryConfig::from_config(config);
+    tracing::info!(
+        "Waiting up to {:?} for a host (polling every {:?})",
+        discovery_config.timeout,
+        discovery_config.poll_interval
+    );
 
-        let peers = discovery.get_peers();
+    let error = match discovery
+        .find_host(&config.self_name, &local_psk_hash, discovery_config)
+        .await
+    {
+        Ok(host) => {
+            let address = host.full_address();
+            tracing::info!("✓ Found host '{}' at {}", host.name, address);
+            return Ok(address);
+        }
+        Err(e) => e.downcast::<DiscoveryError>()?,
+    };
 
-        // Filter for hosts (not other agents - exclude self)
-        let hosts: Vec<_> = peers
+    if !error.rejected.is_empty() {
+        let peers: Vec<String> = error
+            .rejected
             .iter()
-            .filter(|peer| peer.name != config.self_name)
+            .map(|(peer, mismatch)| {
+                format!("  - {} at {}: {}", peer.name, peer.full_address(), mismatch)
+            })
             .collect();
+        anyhow::bail!(
+            "No compatible MultiShiva host found on the network after {:?}.\n\
+             \n\
+             These instances were found but cannot be used:\n\
+             {}\n\
+             \n\
+             Check that the host uses the same tls.psk, or specify its address:\n\
+             `multishiva --mode agent --host <address>`",
+            discovery_config.timeout,
+            peers.join("\n")
+        )
+    }
 
-        if !hosts.is_empty() {
-            let peer_info = hosts[0];
-            let address = peer_info.full_address();
-            tracing::info!(
-                "✓ Found host '{}' at {} (attempt {}/{})",
-                peer_info.name,
-                address,
-                attempt,
-                max_attempts
-            );
+    anyhow::bail!(
+        "No MultiShiva host found on the network after {:?}.\n\
+         \n\
+         Troubleshooting:\n\
+         1. Make sure a host is running: `multishiva --mode host`\n\
+         2. Check firewall settings (port {} should be open)\n\
+         3. Verify both machines are on the same network\n\
+         4. Increase `--discovery-timeout` or `behavior.discovery_timeout_ms` on slow networks\n\
+         5. Manually specify host address: `multishiva --mode agent --host <address>`",
+        discovery_config.timeout,
+        config.port
+    )
+}
 
-            if hosts.len() > 1 {
-                tracing::warn!(
-                    "Multiple hosts found on network, using first one: {}",
-                    peer_info.name
+async fn run_production_mode(
+    config: Config,
+    config_path: &std::path::Path,
+    _topology: Topology,
+    trace: EventTrace,
+    accept_new_fingerprint: bool,
+    input_backend: Option<InputBackend>,
+) -> Result<()> {
+    tracing::info!("🚀 Running in PRODUCTION mode");
+
+    match config.mode {
+        ConfigMode::Host => {
+            let focus = FocusManager::new(config.self_name.clone());
+            run_host_mode(config, config_path, focus, trace).await
+        }
+        ConfigMode::Agent => {
+            // The agent mirrors the host's view: focus starts on the host
+            let host_name = config.host_name.as_deref().unwrap_or("host");
+            let focus = FocusManager::new(host_name.to_string());
+            // If host_address is not specified, try to discover it via mDNS
+            let host_address = if let Some(addr) = config.host_address.clone() {
+                addr
+            } else if trace.is_replaying() {
+                // A replaying agent does not connect to the host
+                "replay".to_string()
+            } else {
+                tracing::info!("🔍 No host address specified, using mDNS auto-discovery...");
+                discover_host_via_mdns(&config).await?
+            };
+            run_agent_mode(
+                config,
+                focus,
+                &host_address,
+                trace,
+                accept_new_fingerprint,
+                input_backend,
+            )
+            .await
+        }
+    }
+}
+
+/// The `--record` and `--replay` files of the host or agent loop.
+struct EventTrace {
+    recorder: Option<EventRecorder>,
+    replayer: Option<EventReplayer>,
+}
+
+impl EventTrace {
+    fn open(args: &cli::Args) -> Result<Self> {
+        let recorder = match &args.record {
+            Some(path) => {
+                tracing::info!("⏺️  Recording events to {:?}", path);
+                Some(EventRecorder::create(path)?)
+            }
+            None => None,
+        };
+        let replayer = match &args.replay {
+            Some(path) => {
+                let mut replayer = EventReplayer::open(path)?;
+                replayer.set_speed(args.speed);
+                tracing::info!(
+                    "▶️  Replaying {} event(s) from {:?} at {}x speed",
+                    replayer.events().len(),
+                    path,
+                    args.speed
                 );
-                for info in hosts.iter().skip(1) {
-                    tracing::warn!("  - Also found: {} at {}", info.name, info.full_address());
+                Some(replayer)
+            }
+            None => None,
+        };
+        Ok(Self { recorder, replayer })
+    }
+
+    fn is_replaying(&self) -> bool {
+        self.replayer.is_some()
+    }
+
+    /// Appends `event` to the recording, if any.
+    ///
+    /// A write failure stops the recording rather than the loop.
+    fn record(&mut self, event: &Event) {
+        let Some(recorder) = &mut self.recorder else {
+            return;
+        };
+        if let Err(e) = recorder.record(event) {
+            tracing::error!("Recording stopped: {}", e);
+            self.recorder = None;
+        }
+    }
+
+    /// Plays the recording into `tx` in the background.
+    ///
+    /// Returns `false` if there is nothing to replay.
+    fn start_replay(&mut self, tx: tokio::sync::mpsc::Sender<Event>) -> bool {
+        let Some(replayer) = self.replayer.take() else {
+            return false;
+        };
+        tokio::spawn(async move {
+            let sent = replayer.replay(&tx).await;
+            tracing::info!("⏹️  Replay finished after {} event(s)", sent);
+        });
+        true
+    }
+}
+
+/// The system clipboard with the clipboard transfers in flight.
+struct ClipboardState {
+    manager: ClipboardManager,
+    /// Local changes waiting to be sent, one event per loop iteration
+    outbox: ClipboardOutbox,
+    /// Chunked changes being received
+    reassembler: ClipboardReassembler,
+}
+
+impl ClipboardState {
+    /// Applies a clipboard event received from another machine.
+    ///
+    /// Returns any other event unchanged.
+    fn receive(&mut self, event: Event) -> Option<Event> {
+        match event {
+            Event::ClipboardSync { source, text } => {
+                if text.len() > self.reassembler.max_bytes() {
+                    tracing::warn!(
+                        "Ignoring {} bytes of clipboard from '{}': above the {} byte limit",
+                        text.len(),
+                        source,
+                        self.reassembler.max_bytes()
+                    );
+                } else {
+                    apply_remote_clipboard(&mut self.manager, source, text);
+                }
+                None
+            }
+            Event::ClipboardChunk {
+                source,
+                id,
+                seq,
+                total,
+                data,
+            } => {
+                let now = std::time::Instant::now();
+                if let Some(text) = self.reassembler.push(&source, id, seq, total, data, now) {
+                    apply_remote_clipboard(&mut self.manager, source, text);
                 }
+                None
             }
+            other => Some(other),
+        }
+    }
+}
 
-            return Ok(address);
+/// Starts clipboard monitoring and returns the clipboard state together with
+/// a channel of `ClipboardSync` events to forward to the other side.
+fn start_clipboard_sync(config: &Config) -> (ClipboardState, tokio::sync::mpsc::Receiver<Event>) {
+    let (tx, rx) = tokio::sync::mpsc::channel(16);
+    let mut clipboard = ClipboardManager::default();
+    clipboard.set_direction(config.clipboard_direction, config.mode.clone());
+    clipboard.set_history_capacity(
+        config
+            .behavior
+            .as_ref()
+            .and_then(|b| b.clipboard_history)
+            .unwrap_or(DEFAULT_HISTORY_CAPACITY),
+    );
+
+    let source = config.self_name.clone();
+    let result = clipboard.start_monitoring(move |change| {
+        if let Some(text) = change.content.as_text() {
+            let event = Event::ClipboardSync {
+                source: source.clone(),
+                text: text.to_string(),
+            };
+            if tx.try_send(event).is_err() {
+                tracing::warn!("Clipboard sync queue full, dropping change");
+            }
         }
+    });
+    match result {
+        Ok(()) => tracing::info!(
+            "📋 Clipboard sync enabled ({:?})",
+            config.clipboard_direction
+        ),
+        Err(e) => tracing::warn!("Clipboard sync unavailable: {}", e),
+    }
 
-        if attempt < max_attempts {
-            tracing::debug!(
-                "No hosts found yet, waiting... (attempt {}/{})",
-                attempt,
-                max_attempts
+    let max_bytes = config
+        .behavior
+        .as_ref()
+        .and_then(|b| b.clipboard_max_bytes)
+        .unwrap_or(DEFAULT_MAX_CLIPBOARD_BYTES);
+    let state = ClipboardState {
+        manager: clipboard,
+        outbox: ClipboardOutbox::new(max_bytes),
+        reassembler: ClipboardReassembler::new(max_bytes),
+    };
+    (state, rx)
+}
+
+/// Applies clipboard content received from another machine.
+fn apply_remote_clipboard(clipboard: &mut ClipboardManager, source: String, text: String) {
+    tracing::info!("📋 Clipboard received from '{}'", source);
+    let content = ClipboardContent::Text(text);
+    if let Err(e) = clipboard.set_content_from_remote_suppressing_echo(content, source) {
+        tracing::error!("Failed to apply remote clipboard: {}", e);
+    }
+}
+
+/// Sends a `FocusGrant` to `target` for a cursor leaving through `edge` at `position`.
+///
+/// The edge's configured offset and scale are applied, then the entry
+/// position is scaled to the agent's screen when the agent reported its size
+/// in the handshake. Returns the entry position sent to `target`.
+async fn grant_focus(
+    network: &Network,
+    settings: &HostSettings,
+    self_name: &str,
+    target: &str,
+    edge: Edge,
+    position: (i32, i32),
+    screen_size: (u32, u32),
+) -> Result<(i32, i32)> {
+    let (x, y) = position;
+    let edge_threshold = settings.edge_threshold;
+
+    // Enter on the opposite edge, at the same relative spot on the agent's screen
+    let agent_size = network
+        .agent_state(target)
+        .map(|state| state.screen_size)
+        .unwrap_or(screen_size);
+    let mapped = settings.topology.map_position(self_name, edge, position);
+    let scaled = scale_position(mapped, screen_size, agent_size);
+    let (entry_x, entry_y) = entry_point(edge, scaled, agent_size, edge_threshold);
+
+    tracing::debug!(
+        "Exit {} edge at host position ({}, {}), entry position on agent: ({}, {})",
+        edge,
+        x,
+        y,
+        entry_x,
+        entry_y
+    );
+
+    network
+        .send_event(Event::FocusGrant {
+            target: target.to_string(),
+            x: entry_x,
+            y: entry_y,
+            host_width: screen_size.0,
+            host_height: screen_size.1,
+        })
+        .await?;
+    Ok((entry_x, entry_y))
+}
+
+/// Moves focus from agent `from`, whose cursor reached `edge` at `position`, to agent `to`.
+///
+/// `from` gets a `FocusRelease` and `to` a `FocusGrant` at the entry position,
+/// computed as in [`grant_focus`] but from `from`'s screen. The grant carries
+/// the host's `screen_size`, since the host keeps sending its own cursor
+/// movement. Returns the entry position sent to `to`.
+async fn relay_focus(
+    network: &Network,
+    settings: &HostSettings,
+    from: &str,
+    to: &str,
+    edge: Edge,
+    position: (i32, i32),
+    screen_size: (u32, u32),
+) -> Result<(i32, i32)> {
+    let size_of = |agent: &str| {
+        network
+            .agent_state(agent)
+            .map(|state| state.screen_size)
+            .unwrap_or(screen_size)
+    };
+    let (from_size, to_size) = (size_of(from), size_of(to));
+    let mapped = settings.topology.map_position(from, edge, position);
+    let scaled = scale_position(mapped, from_size, to_size);
+    let (entry_x, entry_y) = entry_point(edge, scaled, to_size, settings.edge_threshold);
+
+    network.send_event_to(from, Event::FocusRelease).await?;
+    network
+        .send_event_to(
+            to,
+            Event::FocusGrant {
+                target: to.to_string(),
+                x: entry_x,
+                y: entry_y,
+                host_width: screen_size.0,
+                host_height: screen_size.1,
+            },
+        )
+        .await?;
+    Ok((entry_x, entry_y))
+}
+
+/// Takes the machine the kill switch sends focus back to from the focus history.
+///
+/// Skips agents that disconnected since. Returns `None` when focus should go
+/// back to the host, which ends the walk back.
+fn previous_machine(focus: &mut FocusManager, network: &Network) -> Option<(String, i32, i32)> {
+    while let Some((machine, x, y)) = focus.pop_history() {
+        if machine == focus.host() {
+            return None;
+        }
+        if machine != focus.current() && network.agent_state(&machine).is_some() {
+            return Some((machine, x, y));
+        }
+    }
+    None
+}
+
+/// Prints the status reported by the daemon's control endpoint, as text or JSON.
+async fn print_daemon_status(json: bool) -> Result<()> {
+    use multishiva::core::ipc::{default_endpoint, query_status};
+
+    let status = query_status(&default_endpoint()).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+    } else {
+        println!("{}", status);
+    }
+    Ok(())
+}
+
+/// Runs a `keyring` subcommand against the system keyring.
+fn run_keyring_command(action: &cli::KeyringCommand) -> Result<()> {
+    let manager = KeyringManager::new();
+    match action {
+        cli::KeyringCommand::Set { psk } => {
+            let psk = match psk {
+                Some(psk) => psk.clone(),
+                None => {
+                    let mut line = String::new();
+                    std::io::stdin().read_line(&mut line)?;
+                    line.trim().to_string()
+                }
+            };
+            if psk.is_empty() {
+                anyhow::bail!("The PSK cannot be empty");
+            }
+            manager.set_psk(&psk)?;
+            println!("PSK stored in the system keyring.");
+        }
+        cli::KeyringCommand::Get { show } => {
+            let psk = manager.get_psk()?;
+            if *show {
+                println!("{}", psk);
+            } else {
+                println!("{}", cli::mask_secret(&psk));
+            }
+        }
+        cli::KeyringCommand::Delete => {
+            manager.delete_psk()?;
+            println!("PSK removed from the system keyring.");
+        }
+        cli::KeyringCommand::Migrate { from_config } => {
+            let mut config = Config::from_file(from_config)?;
+            if config.tls.psk == KEYRING_PLACEHOLDER {
+                anyhow::bail!("{} already keeps its PSK in the keyring", from_config);
+            }
+            config.tls.psk = manager.migrate_from_config(&config.tls.psk)?;
+            let path = std::path::Path::new(from_config);
+            config.save_to_file(path)?;
+            println!("PSK of {} moved to the system keyring.", from_config);
+            println!(
+                "Delete {} once checked: it still holds the plaintext PSK.",
+                path.with_extension("yml.backup").display()
             );
         }
-    }
+    }
+    Ok(())
+}
+
+/// Lists, removes or trusts stored fingerprints for the `fingerprints` subcommand.
+fn run_fingerprints_command(action: &cli::FingerprintsCommand) -> Result<()> {
+    use multishiva::core::fingerprint::FingerprintStore;
+
+    let mut store = FingerprintStore::load_default()?;
+    match action {
+        cli::FingerprintsCommand::List => {
+            let fingerprints = store.list_sorted();
+            if fingerprints.is_empty() {
+                println!("No fingerprint stored.");
+            }
+            let date = |timestamp: Option<chrono::DateTime<Utc>>| {
+                timestamp.map_or_else(
+                    || "-".to_string(),
+                    |timestamp| timestamp.format("%Y-%m-%d %H:%M").to_string(),
+                )
+            };
+            for (machine, fingerprint) in fingerprints {
+                let hash = fingerprint.hash();
+                println!(
+                    "{:<24} {:<16} first seen {:<16} last verified {}",
+                    machine,
+                    &hash[..hash.len().min(16)],
+                    date(fingerprint.first_seen()),
+                    date(fingerprint.last_verified())
+                );
+            }
+        }
+        cli::FingerprintsCommand::Remove { machine } => {
+            if store.get(machine).is_none() {
+                anyhow::bail!("No fingerprint is stored for {}", machine);
+            }
+            store.remove(machine)?;
+            println!(
+                "Removed the fingerprint of {}: the next connection trusts the one it presents.",
+                machine
+            );
+        }
+        cli::FingerprintsCommand::Trust { machine, hash } => match store.upsert(machine, hash)? {
+            Some(previous) if previous != *hash => {
+                println!(
+                    "Fingerprint of {} is now {} (was {}).",
+                    machine, hash, previous
+                )
+            }
+            _ => println!("Fingerprint of {} is now {}.", machine, hash),
+        },
+    }
+    Ok(())
+}
+
+/// Prints the input devices found for `--list-devices`.
+fn run_list_devices() -> Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        let devices = multishiva::core::input_evdev::list_input_devices()?;
+        if devices.is_empty() {
+            println!("No event device in /dev/input.");
+            return Ok(());
+        }
+
+        println!("{:<20} {:<8} {:<8} NAME", "PATH", "KEYBOARD", "MOUSE");
+        let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+        for device in &devices {
+            match &device.error {
+                Some(error) => println!(
+                    "{:<20} {:<8} {:<8} cannot open: {}",
+                    device.path.display(),
+                    "?",
+                    "?",
+                    error
+                ),
+                None => println!(
+                    "{:<20} {:<8} {:<8} {}",
+                    device.path.display(),
+                    yes_no(device.keyboard),
+                    yes_no(device.mouse),
+                    device.name
+                ),
+            }
+        }
+
+        let usable = devices.iter().filter(|device| device.is_input()).count();
+        println!(
+            "\n{} of {} device(s) usable for capture.",
+            usable,
+            devices.len()
+        );
+        if devices.iter().any(|device| device.error.is_some()) {
+            println!("Devices that cannot be opened need: sudo usermod -aG input $USER");
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        println!(
+            "Listing input devices is only supported on Linux; on this platform \
+             MultiShiva captures the system-wide keyboard and mouse."
+        );
+        Ok(())
+    }
+}
+
+/// Browses mDNS and prints the peers found for the `list-agents` subcommand.
+///
+/// The configuration at `config_path` names this machine, which is left out,
+/// and provides the PSK whose hash the peers' advertised hash is compared with.
+async fn run_list_agents(config_path: &str) -> Result<()> {
+    use multishiva::core::discovery::{Discovery, DiscoveryConfig};
+
+    let mut config = Config::from_file(config_path)?;
+    config.resolve_secrets(&KeyringManager::new())?;
+    let local_psk_hash = compute_psk_hash(&config.tls.psk);
+    let timeout = DiscoveryConfig::from_config(&config).timeout;
+
+    println!("Browsing for MultiShiva instances for {:?}...", timeout);
+    let discovery = Discovery::new(config.self_name.clone())?;
+    let peers = discovery
+        .find_peers_with_timeout(&config.self_name, timeout)
+        .await?;
+    if peers.is_empty() {
+        println!("No MultiShiva instance found.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<24} {:<40} {:<6} {:<8} LAST SEEN",
+        "NAME", "IP", "PORT", "PSK"
+    );
+    for peer in peers {
+        let psk = match &peer.psk_hash {
+            Some(hash) if *hash == local_psk_hash => "match",
+            Some(_) => "differs",
+            None => "unknown",
+        };
+        let last_seen = peer.last_seen.map_or_else(
+            || "-".to_string(),
+            |timestamp| {
+                timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%H:%M:%S")
+                    .to_string()
+            },
+        );
+        println!(
+            "{:<24} {:<40} {:<6} {:<8} {}",
+            peer.name,
+            peer.address.to_string(),
+            peer.port,
+            psk,
+            last_seen
+        );
+    }
+    Ok(())
+}
+
+/// Shows a pairing code and gives the PSK to the agent that types it, for `pair --host`.
+async fn run_pair_host(config_path: &str) -> Result<()> {
+    use multishiva::core::discovery::Discovery;
+    use multishiva::core::pairing::{PairingCode, PairingHost, PairingOffer, PAIRING_CODE_TTL};
+
+    let mut config = Config::from_file(config_path)?;
+    config.resolve_secrets(&KeyringManager::new())?;
+    let offer = PairingOffer {
+        host_name: config.self_name.clone(),
+        port: config.port,
+        psk: config.tls.psk.clone(),
+    };
+    let code = PairingCode::generate(PAIRING_CODE_TTL)?;
+    // Listens where the host does: on bind_address, or dual-stack where IPv6 is available
+    let bind_address = config
+        .bind_address
+        .as_deref()
+        .map(str::parse::<BindAddress>)
+        .transpose()?;
+    let host = match bind_address {
+        Some(bind_address) => {
+            let Some(addr) = bind_address.socket_addrs(0)?.into_iter().next() else {
+                anyhow::bail!("bind_address {} has no address to listen on", bind_address);
+            };
+            PairingHost::bind(addr, offer, code).await?
+        }
+        None => match PairingHost::bind("[::]:0".parse()?, offer.clone(), code.clone()).await {
+            Ok(host) => host,
+            Err(_) => PairingHost::bind("0.0.0.0:0".parse()?, offer, code).await?,
+        },
+    };
+    let local_addr = host.local_addr()?;
+    let port = local_addr.port();
+
+    let mut discovery = Discovery::new(format!("{}-pairing", config.self_name))?;
+    if config.bind_address.is_some() {
+        discovery.set_advertised_addresses(vec![local_addr.ip()]);
+    }
+    if let Err(e) = discovery.register(port, None, PairingHost::properties()) {
+        println!(
+            "mDNS unavailable ({}): run `multishiva --host ADDRESS:{} pair CODE` on the agent",
+            e, port
+        );
+    }
+
+    println!("Pairing code: {}", host.code());
+    println!(
+        "On the agent, run `multishiva pair {}` within {}s (waiting on port {}).",
+        host.code().as_str(),
+        PAIRING_CODE_TTL.as_secs(),
+        port
+    );
+    let paired = host.serve().await;
+    let _ = discovery.unregister();
+    let paired = paired?;
+    println!("✓ Paired with '{}' ({})", paired.name, paired.address.ip());
+    Ok(())
+}
+
+/// Pairs with a host showing `code` and writes the agent configuration, for `pair CODE`.
+///
+/// The host is found over mDNS unless `host_address` is given.
+async fn run_pair_agent(code: &str, config_path: &str, host_address: Option<&str>) -> Result<()> {
+    use multishiva::core::discovery::{Discovery, DiscoveryConfig};
+    use multishiva::core::pairing::{pair_with_host, parse_pairing_code, PAIRING_MODE};
+
+    let code = parse_pairing_code(code)?;
+    let existing = if std::path::Path::new(config_path).exists() {
+        Some(Config::from_file(config_path)?)
+    } else {
+        None
+    };
+    let self_name = existing
+        .as_ref()
+        .map(|config| config.self_name.clone())
+        .unwrap_or_else(|| {
+            hostname::get()
+                .ok()
+                .and_then(|h| h.into_string().ok())
+                .unwrap_or_else(|| "multishiva".to_string())
+        });
+
+    let address = match host_address {
+        Some(address) => address.to_string(),
+        None => {
+            println!("Looking for a host showing a pairing code...");
+            let discovery = Discovery::new(self_name.clone())?;
+            discovery.start_browsing()?;
+            let hosts = discovery
+                .wait_for_peers(
+                    |peer| peer.mode() == Some(PAIRING_MODE),
+                    DiscoveryConfig {
+                        timeout: std::time::Duration::from_secs(10),
+                        ..DiscoveryConfig::default()
+                    },
+                )
+                .await;
+            let _ = discovery.shutdown();
+            match hosts.as_slice() {
+                [host] => host.full_address(),
+                [] => anyhow::bail!("No host is waiting to pair, run `multishiva pair --host` on it"),
+                _ => anyhow::bail!(
+                    "{} hosts are waiting to pair, pick one with `multishiva --host ADDRESS pair CODE`",
+                    hosts.len()
+                ),
+            }
+        }
+    };
+
+    let paired = pair_with_host(&address, &code, &self_name).await?;
+    println!("✓ Paired with host '{}'", paired.offer.host_name);
+
+    let keyring = KeyringManager::new();
+    let psk = match keyring.set_psk(&paired.offer.psk) {
+        Ok(()) => {
+            println!("PSK stored in the system keyring.");
+            KEYRING_PLACEHOLDER.to_string()
+        }
+        Err(e) => {
+            println!(
+                "Keyring unavailable ({}), the PSK is written into the configuration.",
+                e
+            );
+            paired.offer.psk.clone()
+        }
+    };
+    let mut config = existing.unwrap_or_else(|| Config {
+        self_name,
+        ..Config::default()
+    });
+    paired.configure(&mut config, psk);
+    config.save_to_file(std::path::Path::new(config_path))?;
+    println!("Configuration written to {}", config_path);
+    Ok(())
+}
+
+/// Installs the udev rule for the `setup-permissions` subcommand, or asks for
+/// Accessibility access on macOS.
+fn run_setup_permissions() -> Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        permissions::install_udev_rules()?;
+        println!(
+            "udev rule written to {} and rules reloaded.",
+            permissions::UDEV_RULES_PATH
+        );
+        println!("Add your user to the input group if needed: sudo usermod -a -G input $USER");
+        Ok(())
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use permissions::PermissionRequest;
+
+        match permissions::request_permission()? {
+            PermissionRequest::AlreadyGranted => println!("Accessibility access already granted."),
+            PermissionRequest::Prompted => {
+                println!("Enable MultiShiva (or your terminal) in the Accessibility list,");
+                println!("then restart MultiShiva.");
+            }
+            PermissionRequest::ManualSteps { instructions } => println!("{}", instructions),
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        println!("{}", permissions::get_permission_help());
+        anyhow::bail!("setup-permissions only installs udev rules on Linux")
+    }
+}
+
+/// Tails or follows the log files for the `logs` subcommand.
+async fn run_logs_command(args: &cli::LogsArgs) -> Result<()> {
+    use multishiva::core::log_viewer::{follow, newest_log_file, print_tail, LogFilter};
+    use multishiva::core::logging::{cleanup_old_logs, get_default_log_dir};
+
+    if let Some(keep) = args.cleanup {
+        cleanup_old_logs(keep)?;
+        println!("Kept the {} most recent log file(s).", keep);
+        return Ok(());
+    }
+
+    let log_dir = get_default_log_dir();
+    let filter = LogFilter {
+        level: args.level,
+        since: args.since.map(|age| Utc::now() - age),
+    };
+    let mut stdout = std::io::stdout();
+
+    match newest_log_file(&log_dir)? {
+        Some(path) => {
+            let file = std::fs::File::open(&path)
+                .with_context(|| format!("Failed to open log file: {:?}", path))?;
+            print_tail(file, &filter, args.lines, &mut stdout)?;
+        }
+        None if !args.follow => {
+            anyhow::bail!("No log file found in {}", log_dir.display());
+        }
+        None => {}
+    }
+
+    if args.follow {
+        follow(&log_dir, &filter, &mut stdout).await?;
+    }
+    Ok(())
+}
+
+fn run_doctor(config_path: &std::path::Path, json: bool) -> Result<()> {
+    let report = multishiva::core::doctor::run_checks(config_path);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report);
+    }
+
+    match report.failures() {
+        0 => Ok(()),
+        n => anyhow::bail!("{} doctor check(s) failed", n),
+    }
+}
+
+/// Records local input to `output` until Ctrl+C for the `record` subcommand.
+async fn run_record(output: &std::path::Path) -> Result<()> {
+    use multishiva::core::input::InputHandler;
+
+    #[cfg(target_os = "linux")]
+    let mut input_handler = multishiva::core::input_evdev::EvdevInputHandler::new()?;
+    #[cfg(not(target_os = "linux"))]
+    let mut input_handler = multishiva::core::input::RdevInputHandler::new();
+
+    let mut recorder = EventRecorder::create(output)?;
+    let (event_tx, event_rx) = tokio::sync::mpsc::channel(100);
+    input_handler.start_capture(event_tx).await?;
+    tracing::info!("⏺️  Recording to {:?}, press Ctrl+C to stop", output);
+
+    let result = tokio::select! {
+        result = recorder.record_all(event_rx) => result.map(|_| ()),
+        _ = signal::ctrl_c() => Ok(()),
+    };
+    input_handler.stop_capture().await;
+    result?;
+
+    println!(
+        "Recorded {} event(s) to {}",
+        recorder.recorded(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// Injects the events of `input` into this machine for the `replay` subcommand.
+async fn run_replay(input: &std::path::Path, speed: f64) -> Result<()> {
+    #[cfg(target_os = "linux")]
+    let input_handler = multishiva::core::input_evdev::EvdevInputHandler::new()?;
+    #[cfg(not(target_os = "linux"))]
+    let input_handler = multishiva::core::input::RdevInputHandler::new();
+
+    let mut replayer = EventReplayer::open(input)?;
+    replayer.set_speed(speed);
+    tracing::info!(
+        "▶️  Replaying {} event(s) from {:?} at {}x speed",
+        replayer.events().len(),
+        input,
+        speed
+    );
+
+    tokio::select! {
+        result = replayer.replay_into(&input_handler) => {
+            println!("Replayed {} event(s)", result?);
+        }
+        _ = signal::ctrl_c() => {
+            println!("Replay interrupted");
+        }
+    }
+    Ok(())
+}
+
+/// Asks the running daemon to switch to `new_psk`.
+async fn rotate_daemon_psk(new_psk: String) -> Result<()> {
+    use multishiva::core::ipc::{default_endpoint, request_psk_rotation};
+
+    request_psk_rotation(&default_endpoint(), new_psk).await?;
+    println!("PSK rotation started.");
+    println!("Update tls.psk in the configuration so the new key survives a restart.");
+    Ok(())
+}
+
+async fn run_clipboard_command(action: &cli::ClipboardCommand) -> Result<()> {
+    use multishiva::core::ipc::{
+        default_endpoint, query_clipboard_history, request_clipboard_restore,
+    };
+
+    match action {
+        cli::ClipboardCommand::History => {
+            let entries = query_clipboard_history(&default_endpoint()).await?;
+            if entries.is_empty() {
+                println!("The clipboard history is empty.");
+            }
+            for (index, entry) in entries.iter().enumerate() {
+                let line = entry.text.lines().next().unwrap_or_default();
+                let mut preview: String = line.chars().take(60).collect();
+                if preview.len() < entry.text.len() {
+                    preview.push('…');
+                }
+                println!(
+                    "{:>3}  {}  {:<16} {}",
+                    index,
+                    entry
+                        .copied_at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S"),
+                    entry.source.as_deref().unwrap_or("local"),
+                    preview
+                );
+            }
+        }
+        cli::ClipboardCommand::Restore { index } => {
+            request_clipboard_restore(&default_endpoint(), *index).await?;
+            println!("Clipboard entry {} restored.", index);
+        }
+    }
+    Ok(())
+}
+
+/// Serves status queries, PSK rotations, clipboard history requests and
+/// shutdown requests on the default control endpoint in the background.
+///
+/// Returns what is notified when a client asks the daemon to stop; see
+/// [`shutdown_signal`]. Failing to bind only logs a warning so the daemon
+/// keeps running.
+fn start_control_socket(
+    provider: StatusProvider,
+    rotator: PskRotator,
+    clipboard: ClipboardHistory,
+) -> std::sync::Arc<tokio::sync::Notify> {
+    let stop = std::sync::Arc::new(tokio::sync::Notify::new());
+    let requested = stop.clone();
+    let shutdown: ShutdownHandler = std::sync::Arc::new(move || requested.notify_one());
+    let history = clipboard.clone();
+    let list_history: ClipboardHistoryProvider = std::sync::Arc::new(move || {
+        history
+            .entries()
+            .into_iter()
+            .map(ClipboardEntry::from)
+            .collect()
+    });
+    let restorer: ClipboardRestorer = std::sync::Arc::new(move |index| clipboard.restore(index));
+
+    tokio::spawn(async move {
+        use multishiva::core::ipc::{default_endpoint, IpcServer};
+
+        match IpcServer::bind(default_endpoint()).await {
+            Ok(server) => {
+                let server = server
+                    .with_psk_rotator(rotator)
+                    .with_clipboard_history(list_history, restorer)
+                    .with_shutdown_handler(shutdown);
+                if let Err(e) = server.serve(provider).await {
+                    tracing::warn!("Control endpoint stopped: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Control endpoint unavailable: {}", e),
+        }
+    });
+    stop
+}
+
+/// Completes on SIGINT, SIGTERM or when `stop` is notified by the control endpoint.
+///
+/// On SIGHUP the configuration at `config_path` is read again and sent on
+/// `reload`, as when the file changes. Without `reload`, as on an agent,
+/// SIGHUP is logged and ignored. Tells systemd, or the terminal that started
+/// `--daemon`, that the daemon is ready.
+async fn shutdown_signal(
+    stop: std::sync::Arc<tokio::sync::Notify>,
+    mut signals: SignalListener,
+    reload: Option<(&std::path::Path, tokio::sync::mpsc::Sender<Config>)>,
+) {
+    if let Err(e) = daemon::notify_systemd("READY=1") {
+        tracing::warn!("{:#}", e);
+    }
+    if let Err(e) = daemon::notify_detached_ready() {
+        tracing::warn!("{:#}", e);
+    }
+    loop {
+        tokio::select! {
+            signal = signals.recv() => match signal.action() {
+                SignalAction::Shutdown => {
+                    tracing::info!("Received {}", signal);
+                    return;
+                }
+                SignalAction::ReloadConfig => {
+                    let Some((config_path, reload)) = &reload else {
+                        tracing::info!(
+                            "Received {}; only a host reloads its configuration, \
+                             restart to apply changes",
+                            signal
+                        );
+                        continue;
+                    };
+                    tracing::info!("Received {}, reading {:?} again", signal, config_path);
+                    match Config::reload(config_path) {
+                        Ok(config) => {
+                            let _ = reload.send(config).await;
+                        }
+                        Err(e) => tracing::warn!("Keeping the current configuration: {:#}", e),
+                    }
+                }
+            },
+            _ = stop.notified() => {
+                tracing::info!("Shutdown requested over the control endpoint");
+                return;
+            }
+        }
+    }
+}
+
+/// How often the network figures are written to the debug log.
+const METRICS_LOG_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Returns the TCP options of `behavior.low_latency`.
+fn socket_options(config: &Config) -> SocketOptions {
+    let nodelay = config
+        .behavior
+        .as_ref()
+        .and_then(|b| b.low_latency)
+        .unwrap_or(true);
+    if !nodelay {
+        tracing::info!("🐢 Low latency disabled: small frames may be batched");
+    }
+    SocketOptions {
+        nodelay,
+        ..SocketOptions::default()
+    }
+}
+
+/// Returns the allowlist of `allowed_agents`, `None` to let every agent in.
+fn allowed_agents(config: &Config) -> Option<AgentAllowlist> {
+    let entries = config.allowed_agents.as_ref()?;
+    match AgentAllowlist::new(entries) {
+        Ok(allowlist) => {
+            tracing::info!("🔒 Allowed agents: {}", entries.join(", "));
+            Some(allowlist)
+        }
+        // Checked when the configuration is read, but never fail open
+        Err(e) => {
+            tracing::error!("Refusing every agent, invalid allowed_agents: {:#}", e);
+            Some(AgentAllowlist::default())
+        }
+    }
+}
+
+/// Gives `network` the PSKs stored in the keyring for single peers.
+///
+/// Without a usable keyring every peer uses the configured PSK.
+fn load_peer_psks(network: &Network) {
+    let manager = KeyringManager::new();
+    let peers = match manager.list_peers() {
+        Ok(peers) => peers,
+        Err(e) => {
+            tracing::debug!("No per-peer PSKs: {}", e);
+            return;
+        }
+    };
+    for peer in peers {
+        match manager.get_psk_for(&peer) {
+            Ok(psk) => {
+                tracing::info!("Using the keyring PSK of '{}' for its connections", peer);
+                network.set_peer_psk(&peer, psk);
+            }
+            Err(e) => tracing::warn!("{}", e),
+        }
+    }
+}
+
+/// Logs the latency and traffic of the network at debug level every
+/// [`METRICS_LOG_INTERVAL`], in the background, and serves them at
+/// `/metrics.json` with the `metrics-json` feature.
+fn start_metrics_log(monitor: NetworkMonitor) {
+    #[cfg(feature = "metrics-json")]
+    {
+        let monitor = monitor.clone();
+        metrics::set_json_source(Some(std::sync::Arc::new(move || {
+            monitor.metrics_snapshot()
+        })));
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(METRICS_LOG_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            tracing::debug!("📈 Network: {}", monitor.metrics_snapshot());
+        }
+    });
+}
+
+/// Host loop settings derived from the configuration, rebuilt when it is reloaded.
+struct HostSettings {
+    /// Width in pixels of the edge zones
+    edge_threshold: i32,
+    /// Part of the edges that transfers focus
+    edge_trigger: EdgeTrigger,
+    /// Dwell time at an edge before focus is transferred
+    friction: std::time::Duration,
+    /// Whether focus crosses edges while a mouse button is held
+    transfer_while_dragging: bool,
+    /// Emergency hotkey that takes focus back from the agent
+    kill_switch: Vec<Key>,
+    /// Neighbor of each configured edge
+    edges: std::collections::HashMap<String, String>,
+    /// Entry offsets, scales and wrapping of the configured edges
+    topology: Topology,
+    /// Time without input after which focus is taken back from the agent
+    auto_release: Option<std::time::Duration>,
+}
+
+impl HostSettings {
+    fn from_config(config: &Config) -> Self {
+        let behavior = config.behavior.as_ref();
+
+        let edge_threshold = behavior.and_then(|b| b.edge_threshold_px).unwrap_or(10) as i32;
+        tracing::info!("🎯 Edge threshold: {} pixels", edge_threshold);
+
+        let edge_trigger = behavior.and_then(|b| b.edge_trigger).unwrap_or_default();
+        if let EdgeTrigger::Hotspot { corner, size_px } = edge_trigger {
+            tracing::info!("🎯 Hotspot: {}px box in the {:?} corner", size_px, corner);
+        }
 
-    anyhow::bail!(
-        "No MultiShiva host found on the network after 5 seconds.\n\
-         \n\
-         Troubleshooting:\n\
-         1. Make sure a host is running: `multishiva --mode host`\n\
-         2. Check firewall settings (port {} should be open)\n\
-         3. Verify both machines are on the same network\n\
-         4. Manually specify host address: `multishiva --mode agent --host <address>`",
-        config.port
-    )
-}
+        let friction =
+            std::time::Duration::from_millis(behavior.and_then(|b| b.friction_ms).unwrap_or(0));
+        tracing::info!("⏳ Edge friction: {:?}", friction);
 
-async fn run_production_mode(config: Config, _topology: Topology) -> Result<()> {
-    tracing::info!("🚀 Running in PRODUCTION mode");
+        let transfer_while_dragging = behavior
+            .and_then(|b| b.transfer_while_dragging)
+            .unwrap_or(false);
 
-    let focus = FocusManager::new(config.self_name.clone());
-    tracing::debug!("Focus manager initialized for: {}", config.self_name);
+        let auto_release = behavior
+            .and_then(|b| b.auto_release_after_ms)
+            .map(std::time::Duration::from_millis);
+        if let Some(after) = auto_release {
+            tracing::info!("💤 Auto-release after {:?} without input", after);
+        }
 
-    match config.mode {
-        ConfigMode::Host => run_host_mode(config, focus).await,
-        ConfigMode::Agent => {
-            // If host_address is not specified, try to discover it via mDNS
-            let host_address = if let Some(addr) = config.host_address.clone() {
-                addr
-            } else {
-                tracing::info!("🔍 No host address specified, using mDNS auto-discovery...");
-                discover_host_via_mdns(&config).await?
-            };
-            run_agent_mode(config, focus, &host_address).await
+        let kill_switch = match config
+            .hotkeys
+            .as_ref()
+            .and_then(|h| h.kill_switch.as_deref())
+            .map(parse_hotkey)
+        {
+            Some(Ok(keys)) => keys,
+            Some(Err(e)) => {
+                tracing::warn!("Kill switch disabled: {}", e);
+                Vec::new()
+            }
+            None => Vec::new(),
+        };
+
+        Self {
+            edge_threshold,
+            edge_trigger,
+            friction,
+            transfer_while_dragging,
+            kill_switch,
+            edges: config.edge_targets(),
+            topology: Topology::from_config(config),
+            auto_release,
         }
     }
 }
 
-async fn run_host_mode(config: Config, _focus: FocusManager) -> Result<()> {
-    use multishiva::core::discovery::Discovery;
+async fn run_host_mode(
+    mut config: Config,
+    config_path: &std::path::Path,
+    mut focus: FocusManager,
+    mut trace: EventTrace,
+) -> Result<()> {
+    use multishiva::core::discovery::{host_properties, Discovery};
     use multishiva::core::input::InputHandler;
-    use std::collections::HashMap;
 
     tracing::info!("Starting as HOST on port {}", config.port);
 
-    let mut network = Network::new(config.tls.psk.clone());
+    let mut network_config = NetworkConfig::default();
+    if let Some(ms) = config.behavior.as_ref().and_then(|b| b.agent_timeout_ms) {
+        network_config.agent_timeout = std::time::Duration::from_millis(ms);
+    }
+    if let Some(burst) = config.behavior.as_ref().and_then(|b| b.connection_burst) {
+        network_config.connection_burst = burst;
+    }
+    if let Some(per_minute) = config
+        .behavior
+        .as_ref()
+        .and_then(|b| b.connections_per_minute)
+    {
+        network_config.connections_per_minute = per_minute;
+    }
+    if let Some(per_second) = config
+        .behavior
+        .as_ref()
+        .and_then(|b| b.max_events_per_second)
+    {
+        network_config.max_events_per_second = per_second;
+    }
+    tracing::info!("⏱️  Agent timeout: {:?}", network_config.agent_timeout);
+    let mut network = Network::with_config(config.tls.psk.clone(), network_config)
+        .with_socket_options(socket_options(&config));
+    load_peer_psks(&network);
 
     // Log topology
     for (edge_name, neighbor_name) in &config.edges {
@@ -301,51 +1610,387 @@ async fn run_host_mode(config: Config, _focus: FocusManager) -> Result<()> {
     };
     let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(100);
 
-    tracing::info!("🖱️  Starting mouse/keyboard capture...");
-    input_handler.start_capture(event_tx.clone()).await?;
-    tracing::info!("✓ Input capture started");
+    // A replay stands in for the real input devices
+    if !trace.start_replay(event_tx.clone()) {
+        tracing::info!("🖱️  Starting mouse/keyboard capture...");
+        input_handler.start_capture(event_tx.clone()).await?;
+        tracing::info!("✓ Input capture started");
+    }
+
+    // Taking focus back after inactivity goes through the same path as a FocusRelease
+    let idle_tx = event_tx.clone();
 
     // Pass event_tx to network so agents can send events back (like FocusRelease)
+    let bind_address = config
+        .bind_address
+        .as_deref()
+        .map(str::parse::<BindAddress>)
+        .transpose()?;
+    network.set_bind_address(bind_address.clone());
+    network.set_self_name(Some(config.self_name.clone()));
+    network.set_allowed_agents(allowed_agents(&config));
     let actual_port = network.start_host(config.port, Some(event_tx)).await?;
-    tracing::info!("✓ Host listening on port {}", actual_port);
+    let bound: Vec<String> = network
+        .local_addrs()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    tracing::info!("✓ Host listening on {}", bound.join(", "));
 
     // Register this host on mDNS for auto-discovery
     tracing::info!("📡 Registering host on mDNS for auto-discovery...");
-    let discovery = Discovery::new(config.self_name.clone())?;
-    discovery.register(actual_port, None, HashMap::new())?;
+    let mut discovery = Discovery::new(config.self_name.clone())?;
+    if bind_address.is_some() {
+        discovery
+            .set_advertised_addresses(network.local_addrs().iter().map(|addr| addr.ip()).collect());
+    }
+    let discovery = std::sync::Arc::new(discovery);
+    discovery.register(
+        actual_port,
+        Some(compute_psk_hash(&config.tls.psk)),
+        host_properties(),
+    )?;
     tracing::info!("✓ Host registered on mDNS as '{}'", config.self_name);
 
+    // Browse as well so `multishiva status` can list the other instances
+    if let Err(e) = discovery.start_browsing() {
+        tracing::warn!("mDNS browsing unavailable: {}", e);
+    }
+
+    let monitor = network.monitor();
+    let status_discovery = discovery.clone();
+    let focus_holder = focus.watch();
+    let host_name = config.self_name.clone();
+    let started = std::time::Instant::now();
+    let last_auto_release = std::sync::Arc::new(std::sync::Mutex::new(None));
+    let status_auto_release = last_auto_release.clone();
+    let status: StatusProvider = std::sync::Arc::new(move || {
+        let holder = focus_holder.borrow().clone();
+        let mut discovered_peers: Vec<DiscoveredPeer> = status_discovery
+            .get_peers()
+            .into_iter()
+            .map(DiscoveredPeer::from)
+            .collect();
+        discovered_peers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        StatusResponse {
+            mode: "host".to_string(),
+            uptime_secs: started.elapsed().as_secs(),
+            connected_agents: monitor.connected_agents(),
+            peers: monitor
+                .connected_peers()
+                .into_iter()
+                .map(|peer| PeerStatus {
+                    name: peer.name,
+                    address: peer.address.to_string(),
+                    last_heartbeat_ms: Some(peer.last_seen.as_millis() as u64),
+                    connected_secs: Some(peer.connected_for.as_secs()),
+                    latency_ms: peer.latency.map(|rtt| rtt.as_millis() as u64),
+                    traffic: Some(PeerTraffic {
+                        events_sent: peer.events_sent,
+                        events_dropped: peer.events_dropped,
+                        bytes_sent: peer.bytes_sent,
+                    }),
+                })
+                .collect(),
+            focus: (holder != host_name).then_some(holder),
+            events_forwarded: monitor.events_forwarded(),
+            queue_depth: monitor.queue_depth(),
+            metrics: Some(monitor.metrics_snapshot()),
+            discovered_peers,
+            last_auto_release: *status_auto_release.lock().unwrap(),
+        }
+    });
+
+    // Rotating the key also updates the hash agents filter hosts with
+    let rotation = network.psk_rotation();
+    let rotation_discovery = discovery.clone();
+    let (psk_rotated_tx, mut psk_rotated_rx) = tokio::sync::mpsc::unbounded_channel();
+    let rotator: PskRotator = std::sync::Arc::new(move |new_psk: String| {
+        let new_psk_hash = compute_psk_hash(&new_psk);
+        rotation.rotate(new_psk)?;
+        let _ = psk_rotated_tx.send(Utc::now());
+        if let Err(e) =
+            rotation_discovery.register(actual_port, Some(new_psk_hash), host_properties())
+        {
+            tracing::warn!("Failed to advertise the new PSK hash over mDNS: {}", e);
+        }
+        Ok(())
+    });
+    let (mut clipboard, mut clipboard_rx) = start_clipboard_sync(&config);
+    let stop = start_control_socket(status, rotator, clipboard.manager.history_handle());
+    start_metrics_log(network.monitor());
+
     let screen_size = input_handler.get_screen_size();
     tracing::info!("📺 Screen size: {}x{}", screen_size.0, screen_size.1);
 
-    // Get edge threshold from config or use default
-    let edge_threshold = config
-        .behavior
-        .as_ref()
-        .and_then(|b| b.edge_threshold_px)
-        .unwrap_or(10) as i32;
-    tracing::info!("🎯 Edge threshold: {} pixels", edge_threshold);
+    let mut settings = HostSettings::from_config(&config);
+    #[cfg(not(target_os = "linux"))]
+    input_handler.set_kill_switch(settings.kill_switch.clone());
+
+    // Edge mappings and behavior are applied again when the file changes
+    let (reload_tx, mut reload_rx) = tokio::sync::mpsc::channel(4);
+    if let Err(e) = Config::watch(config_path.to_path_buf(), reload_tx.clone()) {
+        tracing::warn!("Configuration hot-reload disabled: {}", e);
+    }
+
+    // Audit trail of who connected and controlled what
+    let mut connections = network.subscribe();
+    let mut audit = match AuditLog::open_default() {
+        Ok(audit) => {
+            tracing::info!("📝 Audit log: {:?}", audit.path());
+            Some(audit)
+        }
+        Err(e) => {
+            tracing::warn!("Audit log disabled: {}", e);
+            None
+        }
+    };
 
     tracing::info!("Waiting for agents to connect...");
     tracing::info!("Press Ctrl+C to exit");
 
-    // Track which machine has focus (None = local, Some(name) = remote)
-    let mut focus_target: Option<String> = None;
+    // The network routes to the agent `focus` hands focus to
+    let mut crossing = EdgeCrossingController::new(settings.friction);
+    // Keys held on the focused machine, released on every focus transition
+    let mut modifiers = ModifierTracker::new();
+    let mut last_position = (0, 0);
+    // Takes focus back when nothing was forwarded to the agent for a while
+    let mut idle = IdleRelease::new(settings.auto_release);
 
     // Event processing loop
-    let ctrl_c = signal::ctrl_c();
+    let ctrl_c = shutdown_signal(stop, SignalListener::new()?, Some((config_path, reload_tx)));
     tokio::pin!(ctrl_c);
 
     let mut event_count = 0u64;
     loop {
+        let friction_deadline = crossing.deadline().map(tokio::time::Instant::from_std);
+        idle.track(focus.remote().is_some(), std::time::Instant::now());
+        let idle_deadline = idle.deadline().map(tokio::time::Instant::from_std);
+
         tokio::select! {
+            Some(Event::ClipboardSync { source, text }) = clipboard_rx.recv() => {
+                clipboard.outbox.push(&source, text);
+            }
+            Ok(ConnectionEvent::AgentConnected { name }) = connections.recv() => {
+                if let Some(audit) = audit.as_mut() {
+                    if let Err(e) = audit.log_connection(&name, Utc::now()) {
+                        tracing::warn!("Failed to write audit log: {}", e);
+                    }
+                }
+            }
+            Some(at) = psk_rotated_rx.recv() => {
+                if let Some(audit) = audit.as_mut() {
+                    if let Err(e) = audit.log_psk_change(at) {
+                        tracing::warn!("Failed to write audit log: {}", e);
+                    }
+                }
+            }
+            // One clipboard event at a time, once the bulk lane has room
+            _ = network.bulk_lane_ready(), if !clipboard.outbox.is_empty() => {
+                let Some(sync) = clipboard.outbox.pop() else {
+                    continue;
+                };
+                if let Err(e) = network.send_event(sync).await {
+                    tracing::error!("Failed to send clipboard: {}", e);
+                }
+            }
+            _ = tokio::time::sleep_until(idle_deadline.unwrap_or_else(tokio::time::Instant::now)),
+                if idle_deadline.is_some() =>
+            {
+                if !idle.poll(std::time::Instant::now()) {
+                    continue;
+                }
+                let Some(agent) = focus.remote().map(str::to_string) else {
+                    continue;
+                };
+                tracing::warn!(
+                    "💤 No input for {:?}, taking focus back from '{}'",
+                    settings.auto_release.unwrap_or_default(),
+                    agent
+                );
+                *last_auto_release.lock().unwrap() = Some(Utc::now());
+                if let Err(e) = network.send_event_to(&agent, Event::FocusRelease).await {
+                    tracing::error!("Failed to send FocusRelease to '{}': {}", agent, e);
+                }
+                // Handled below like a release from the agent: devices are ungrabbed
+                if idle_tx.try_send(Event::FocusRelease).is_err() {
+                    tracing::error!("Failed to queue the release of '{}'", agent);
+                }
+            }
+            Some(new_config) = reload_rx.recv() => {
+                tracing::info!("🔄 Configuration changed, reloading");
+                for field in config.restart_required(&new_config) {
+                    tracing::warn!("{} changed: restart required, keeping the current value", field);
+                }
+                config.edges = new_config.edges;
+                config.behavior = new_config.behavior;
+                config.hotkeys = new_config.hotkeys;
+                config.allowed_agents = new_config.allowed_agents;
+                network.set_allowed_agents(allowed_agents(&config));
+                for (edge_name, neighbor_name) in &config.edges {
+                    tracing::info!("🔗 Topology: {} at edge {}", neighbor_name, edge_name);
+                }
+
+                settings = HostSettings::from_config(&config);
+                crossing.set_friction(settings.friction);
+                idle.set_timeout(settings.auto_release);
+                #[cfg(not(target_os = "linux"))]
+                input_handler.set_kill_switch(settings.kill_switch.clone());
+                // A pending crossing may target an edge that no longer exists
+                if crossing.is_pending() {
+                    crossing.reset();
+                }
+            }
             Some(event) = event_rx.recv() => {
                 event_count += 1;
+                trace.record(&event);
+
+                // Clipboard content from an agent is applied locally, never forwarded
+                let Some(event) = clipboard.receive(event) else {
+                    continue;
+                };
+
+                modifiers.observe(&event);
+                // A drag ending at an edge stays local until the button is released
+                crossing.set_held(modifiers.is_dragging() && !settings.transfer_while_dragging);
+                // The rdev backend stops capturing by itself on the kill switch
+                let capture_stopped = matches!(event, Event::KillSwitch);
+                let killed = capture_stopped
+                    || (!focus.is_local() && is_triggered(modifiers.held(), &settings.kill_switch));
+                // An agent that disconnected while focused cannot give focus back
+                let lost = focus
+                    .remote()
+                    .is_some_and(|agent| network.agent_state(agent).is_none());
+
+                // Each press of the kill switch walks focus back one machine along the chain
+                if killed && !capture_stopped && !lost {
+                    if let Some((machine, x, y)) = previous_machine(&mut focus, &network) {
+                        tracing::warn!("⛔ Kill switch pressed, going back to '{}'", machine);
+                        for release in modifiers.release_modifiers() {
+                            if let Err(e) = network.send_event(release).await {
+                                tracing::error!("Failed to release modifier on remote: {}", e);
+                            }
+                        }
+                        // As in relay_focus, the agent losing focus is told first
+                        let from = focus.current().to_string();
+                        if let Err(e) = network.send_event_to(&from, Event::FocusRelease).await {
+                            tracing::error!("Failed to send FocusRelease to '{}': {}", from, e);
+                        }
+                        let grant = Event::FocusGrant {
+                            target: machine.clone(),
+                            x,
+                            y,
+                            host_width: screen_size.0,
+                            host_height: screen_size.1,
+                        };
+                        if let Err(e) = network.send_event(grant).await {
+                            tracing::error!("Failed to send FocusGrant to '{}': {}", machine, e);
+                        } else {
+                            focus.transfer_focus(machine.clone(), x, y).await?;
+                            network.set_focused_agent(Some(&machine));
+                            if let Some(audit) = audit.as_mut() {
+                                if let Err(e) = audit.log_focus_transfer(&from, &machine, Utc::now()) {
+                                    tracing::warn!("Failed to write audit log: {}", e);
+                                }
+                            }
+                            continue;
+                        }
+                    }
+                }
+
+                // The focused agent reached an edge: the topology tells where focus goes
+                let event = match event {
+                    Event::EdgeReached { edge, x, y } => {
+                        let Some(from) = focus.remote().map(str::to_string) else {
+                            continue;
+                        };
+                        let next = match settings.topology.get_neighbor(&from, &edge).cloned() {
+                            Some(next) => next,
+                            None => {
+                                // The agent's view differs from ours: do not leave the cursor trapped there
+                                tracing::warn!(
+                                    "No neighbor on the {} edge of '{}' in the host's topology, taking focus back",
+                                    edge,
+                                    from
+                                );
+                                config.self_name.clone()
+                            }
+                        };
+                        if next == config.self_name {
+                            // Taken back below, as if the agent released focus
+                            if let Err(e) = network.send_event_to(&from, Event::FocusRelease).await {
+                                tracing::error!("Failed to send FocusRelease to '{}': {}", from, e);
+                            }
+                            Event::FocusRelease
+                        } else {
+                            if network.agent_state(&next).is_none() {
+                                tracing::warn!("Agent '{}' is not connected, focus stays on '{}'", next, from);
+                                continue;
+                            }
+                            tracing::info!("🚀 Relaying focus from '{}' to '{}' via {} edge", from, next, edge);
+                            for release in modifiers.release_modifiers() {
+                                if let Err(e) = network.send_event_to(&from, release).await {
+                                    tracing::error!("Failed to release modifier on '{}': {}", from, e);
+                                }
+                            }
+                            match relay_focus(&network, &settings, &from, &next, edge, (x, y), screen_size).await {
+                                Ok((entry_x, entry_y)) => {
+                                    focus.push_history(from.clone(), x, y);
+                                    focus.transfer_focus(next.clone(), entry_x, entry_y).await?;
+                                    network.set_focused_agent(Some(&next));
+                                    if let Some(audit) = audit.as_mut() {
+                                        if let Err(e) = audit.log_focus_transfer(&from, &next, Utc::now()) {
+                                            tracing::warn!("Failed to write audit log: {}", e);
+                                        }
+                                    }
+                                }
+                                Err(e) => tracing::error!("Failed to relay focus to '{}': {}", next, e),
+                            }
+                            continue;
+                        }
+                    }
+                    other => other,
+                };
+
+                // Focus comes back on a FocusRelease from remote, the kill switch or a disconnect
+                if killed || lost || matches!(event, Event::FocusRelease) {
+                    if capture_stopped {
+                        tracing::warn!("⛔ Kill switch pressed: input capture stopped, restart to share again");
+                    } else if killed {
+                        tracing::warn!("⛔ Kill switch pressed, taking focus back");
+                    } else if lost {
+                        tracing::warn!("⚠️  Focused agent disconnected, taking focus back");
+                    } else {
+                        tracing::info!("◀ Focus returned from remote machine");
+                    }
+                    let returned = if killed || lost {
+                        focus.recall()
+                    } else {
+                        focus.release()
+                    };
+                    let transition = match returned {
+                        Ok(transition) => transition,
+                        Err(e) => {
+                            tracing::debug!("{}", e);
+                            continue;
+                        }
+                    };
+                    focus.clear_history();
 
-                // Check if we received a FocusRelease from remote
-                if matches!(event, multishiva::core::events::Event::FocusRelease) {
-                    tracing::info!("◀ Focus returned from remote machine");
-                    focus_target = None;
+                    // Modifiers pressed on the agent would stay down there
+                    for release in modifiers.release_modifiers() {
+                        if let Err(e) = network.send_event(release).await {
+                            tracing::error!("Failed to release modifier on remote: {}", e);
+                        }
+                    }
+                    if let Some(audit) = audit.as_mut() {
+                        if let Err(e) = audit.log_focus_transfer(&transition.from, &config.self_name, Utc::now()) {
+                            tracing::warn!("Failed to write audit log: {}", e);
+                        }
+                    }
+                    network.set_focused_agent(None);
+                    crossing.reset();
 
                     // Ungrab devices to allow local input again
                     #[cfg(target_os = "linux")]
@@ -357,8 +2002,30 @@ async fn run_host_mode(config: Config, _focus: FocusManager) -> Result<()> {
                     continue;
                 }
 
+                // An agent leaving without focus changes nothing here
+                if let Event::Disconnect { reason } = &event {
+                    tracing::info!("Agent disconnected: {}", reason);
+                    continue;
+                }
+
+                // An agent passes on a focus grant for a machine further down the chain
+                if let Event::FocusGrantChain { path, .. } = &event {
+                    if let Some(target) = path.last() {
+                        tracing::info!("Relaying chained focus grant to '{}'", target);
+                        let (x, y) = focus.current_position();
+                        focus.push_history(focus.current().to_string(), x, y);
+                        focus.transfer_focus(target.clone(), 0, 0).await?;
+                        network.set_focused_agent(Some(target));
+                    }
+                    if let Err(e) = network.send_event(event).await {
+                        tracing::error!("Failed to relay focus grant: {}", e);
+                    }
+                    continue;
+                }
+
                 // If focus is on remote machine, send ALL events there
-                if let Some(ref target) = focus_target {
+                if let Some(target) = focus.remote() {
+                    idle.touch(std::time::Instant::now());
                     tracing::trace!("Forwarding event to {}: {:?}", target, event);
                     if let Err(e) = network.send_event(event).await {
                         tracing::error!("Failed to send event to {}: {}", target, e);
@@ -367,113 +2034,119 @@ async fn run_host_mode(config: Config, _focus: FocusManager) -> Result<()> {
                 }
 
                 // Process events locally when we have focus
-                // Log mouse movement for debugging
-                if let multishiva::core::events::Event::MouseMove { x, y } = &event {
+                if let Event::MouseMove { x, y } = event {
                     // Log every 100th event to see if we're receiving them
-                    if event_count % 100 == 0 {
+                    if event_count.is_multiple_of(100) {
                         tracing::info!("📊 Received {} events. Current mouse: ({}, {})", event_count, x, y);
                     }
-
-                    // Log ALL mouse movements temporarily to debug
                     tracing::trace!("Mouse position: ({}, {})", x, y);
-
-                    // Check edge proximity
-                    let threshold = edge_threshold;
-                    let at_left = *x < threshold;
-                    let at_right = *x > (screen_size.0 as i32 - threshold);
-                    let at_top = *y < threshold;
-                    let at_bottom = *y > (screen_size.1 as i32 - threshold);
-
-                    // DEBUG: Log the actual check values
-                    if *x < 50 || *x > 1870 {
-                        tracing::warn!("🔍 DEBUG: x={}, threshold={}, at_left={} (x < {}), at_right={} (x > {})",
-                            x, threshold, at_left, threshold, at_right, screen_size.0 as i32 - threshold);
+                    last_position = (x, y);
+
+                    // Check if the cursor is in the edge zone of a configured neighbor
+                    let detected = detect_crossing(
+                        screen_size,
+                        settings.edge_threshold,
+                        &settings.edge_trigger,
+                        &settings.edges,
+                        (x, y),
+                    );
+                    if detected.is_none() && settings.edge_trigger == EdgeTrigger::FullEdge {
+                        if let Some(edge) = edge_at((x, y), screen_size, settings.edge_threshold) {
+                            if settings.topology.wraps(&config.self_name, edge) {
+                                // Wrap around to the opposite side of our own screen
+                                let (wrap_x, wrap_y) = settings.topology.entry_point(
+                                    &config.self_name,
+                                    edge,
+                                    (x, y),
+                                    screen_size,
+                                    settings.edge_threshold,
+                                );
+                                tracing::debug!("Wrapping {} edge to ({}, {})", edge, wrap_x, wrap_y);
+                                let wrap = Event::MouseMove { x: wrap_x, y: wrap_y };
+                                if let Err(e) = input_handler.inject_event(wrap).await {
+                                    network.record_injection_failure();
+                                    tracing::error!("Failed to wrap cursor: {}", e);
+                                }
+                                last_position = (wrap_x, wrap_y);
+                                crossing.update(None, std::time::Instant::now());
+                                continue;
+                            }
+                            tracing::debug!("No neighbor configured on {} edge", edge);
+                        }
                     }
+                    let neighbor = detected
+                        .as_ref()
+                        .map(|(edge, target, _)| (*edge, target.as_str()));
+                    crossing.update(neighbor, std::time::Instant::now());
+                }
+            }
+            _ = tokio::time::sleep_until(friction_deadline.unwrap_or_else(tokio::time::Instant::now)),
+                if friction_deadline.is_some() =>
+            {
+                let Some(Crossing { edge, target }) = crossing.poll(std::time::Instant::now()) else {
+                    continue;
+                };
+                if network.agent_state(&target).is_none() {
+                    tracing::warn!("Agent '{}' is not connected, staying on host", target);
+                    crossing.reset();
+                    continue;
+                }
+                tracing::info!(
+                    "🚀 Edge crossed! Transferring focus to '{}' via {} edge",
+                    target,
+                    edge
+                );
 
-                    if at_left || at_right || at_top || at_bottom {
-                        tracing::info!(
-                            "🖱️  Mouse near edge at ({}, {}) - Left:{} Right:{} Top:{} Bottom:{} (screen: {}x{}, threshold: {})",
-                            x, y, at_left, at_right, at_top, at_bottom, screen_size.0, screen_size.1, threshold
-                        );
-
-                        // Check if there's a neighbor on this edge
-                        let edge = if at_left {
-                            Some("left")
-                        } else if at_right {
-                            Some("right")
-                        } else if at_top {
-                            Some("top")
-                        } else if at_bottom {
-                            Some("bottom")
-                        } else {
-                            None
-                        };
-
-                        if let Some(edge_name) = edge {
-                            if let Some(neighbor) = config.edges.get(edge_name) {
-                                tracing::info!(
-                                    "🚀 Edge crossed! Transferring focus to '{}' via {} edge",
-                                    neighbor,
-                                    edge_name
-                                );
+                let (entry_x, entry_y) = match grant_focus(
+                    &network,
+                    &settings,
+                    &config.self_name,
+                    &target,
+                    edge,
+                    last_position,
+                    screen_size,
+                )
+                .await
+                {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        tracing::error!("Failed to send FocusGrant: {}", e);
+                        crossing.reset();
+                        continue;
+                    }
+                };
 
-                                // Calculate entry position on agent (opposite edge)
-                                // If we exit left (x≈0), we should enter right (x≈screen_width)
-                                // If we exit right (x≈screen_width), we should enter left (x≈0)
-                                // If we exit top (y≈0), we should enter bottom (y≈screen_height)
-                                // If we exit bottom (y≈screen_height), we should enter top (y≈0)
-                                // For now, assume agent has same screen size as host
-                                let (entry_x, entry_y) = match edge_name {
-                                    "left" => (screen_size.0 as i32 - edge_threshold - 1, *y),
-                                    "right" => (edge_threshold, *y),
-                                    "top" => (*x, screen_size.1 as i32 - edge_threshold - 1),
-                                    "bottom" => (*x, edge_threshold),
-                                    _ => (*x, *y),
-                                };
+                // Transfer focus to remote machine
+                if let Err(e) = focus.grant_to(&target, entry_x, entry_y) {
+                    tracing::error!("{}", e);
+                    crossing.reset();
+                    continue;
+                }
+                focus.push_history(config.self_name.clone(), last_position.0, last_position.1);
+                network.set_focused_agent(Some(&target));
+                if let Some(audit) = audit.as_mut() {
+                    if let Err(e) = audit.log_focus_transfer(&config.self_name, &target, Utc::now()) {
+                        tracing::warn!("Failed to write audit log: {}", e);
+                    }
+                }
 
-                                tracing::warn!(
-                                    "🔍 EXIT EDGE: {} at host position ({}, {}), calculated ENTRY position on agent: ({}, {}), screen={}x{}",
-                                    edge_name,
-                                    x,
-                                    y,
-                                    entry_x,
-                                    entry_y,
-                                    screen_size.0,
-                                    screen_size.1
-                                );
+                // Modifiers pressed here would otherwise be released on the agent
+                for release in modifiers.release_modifiers() {
+                    if let Err(e) = input_handler.inject_event(release).await {
+                        tracing::error!("Failed to release modifier locally: {}", e);
+                    }
+                }
 
-                                // Send FocusGrant event with entry position
-                                use multishiva::core::events::Event;
-                                let focus_event = Event::FocusGrant {
-                                    target: neighbor.clone(),
-                                    x: entry_x,
-                                    y: entry_y,
-                                };
-
-                                if let Err(e) = network.send_event(focus_event).await {
-                                    tracing::error!("Failed to send FocusGrant: {}", e);
-                                } else {
-                                    // Transfer focus to remote machine
-                                    focus_target = Some(neighbor.clone());
-                                    tracing::info!("✓ Focus transferred to '{}'", neighbor);
-
-                                    // Grab devices on Linux to block local input
-                                    #[cfg(target_os = "linux")]
-                                    {
-                                        if let Err(e) = input_handler.grab_devices() {
-                                            tracing::error!("Failed to grab devices: {}", e);
-                                        }
-                                    }
-                                }
-                            } else {
-                                tracing::debug!("No neighbor configured on {} edge", edge_name);
-                            }
-                        }
+                // Grab devices on Linux to block local input
+                #[cfg(target_os = "linux")]
+                {
+                    if let Err(e) = input_handler.grab_devices() {
+                        tracing::error!("Failed to grab devices: {}", e);
                     }
                 }
             }
             _ = &mut ctrl_c => {
-                tracing::info!("Received Ctrl+C, stopping...");
+                tracing::info!("Stopping...");
                 break;
             }
         }
@@ -481,42 +2154,142 @@ async fn run_host_mode(config: Config, _focus: FocusManager) -> Result<()> {
 
     tracing::info!("Host stopping...");
     input_handler.stop_capture().await;
-    network.stop().await;
+    network.stop_with_reason("host shutting down").await;
     tracing::info!("Host stopped");
 
     Ok(())
 }
 
+/// The handler an agent injects with on Linux, as picked by `select_backend`.
+#[cfg(target_os = "linux")]
+enum AgentInjector {
+    Uinput(multishiva::core::input_uinput::UinputInjector),
+    Wayland(multishiva::core::input_wayland::WaylandInjector),
+}
+
+#[cfg(target_os = "linux")]
+impl AgentInjector {
+    fn new(requested: Option<InputBackend>) -> Result<Self> {
+        use multishiva::core::input_backend::detect_backend;
+
+        let backend = detect_backend(requested)?;
+        tracing::info!("Injecting input with the {} backend", backend);
+        Ok(match backend {
+            InputBackend::Uinput => {
+                Self::Uinput(multishiva::core::input_uinput::UinputInjector::create()?)
+            }
+            InputBackend::Wayland => {
+                Self::Wayland(multishiva::core::input_wayland::WaylandInjector::connect()?)
+            }
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl multishiva::core::input::InputHandler for AgentInjector {
+    async fn start_capture(&mut self, tx: tokio::sync::mpsc::Sender<Event>) -> Result<()> {
+        match self {
+            Self::Uinput(handler) => handler.start_capture(tx).await,
+            Self::Wayland(handler) => handler.start_capture(tx).await,
+        }
+    }
+
+    async fn stop_capture(&mut self) {
+        match self {
+            Self::Uinput(handler) => handler.stop_capture().await,
+            Self::Wayland(handler) => handler.stop_capture().await,
+        }
+    }
+
+    async fn inject_event(&self, event: Event) -> Result<()> {
+        match self {
+            Self::Uinput(handler) => handler.inject_event(event).await,
+            Self::Wayland(handler) => handler.inject_event(event).await,
+        }
+    }
+
+    fn is_capturing(&self) -> bool {
+        match self {
+            Self::Uinput(handler) => handler.is_capturing(),
+            Self::Wayland(handler) => handler.is_capturing(),
+        }
+    }
+
+    fn get_screen_size(&self) -> (u32, u32) {
+        match self {
+            Self::Uinput(handler) => handler.get_screen_size(),
+            Self::Wayland(handler) => handler.get_screen_size(),
+        }
+    }
+
+    fn get_cursor_position(&self) -> Result<(i32, i32)> {
+        match self {
+            Self::Uinput(handler) => handler.get_cursor_position(),
+            Self::Wayland(handler) => handler.get_cursor_position(),
+        }
+    }
+
+    fn check_permissions(&self) -> bool {
+        match self {
+            Self::Uinput(handler) => handler.check_permissions(),
+            Self::Wayland(handler) => handler.check_permissions(),
+        }
+    }
+}
+
 async fn run_agent_mode(
     config: Config,
-    mut _focus: FocusManager,
+    mut focus: FocusManager,
     host_address: &str,
+    mut trace: EventTrace,
+    accept_new_fingerprint: bool,
+    input_backend: Option<InputBackend>,
 ) -> Result<()> {
     use multishiva::core::input::InputHandler;
 
     tracing::info!("Starting as AGENT, connecting to: {}", host_address);
 
-    let mut network = Network::new(config.tls.psk.clone());
-
-    // Connect to host
-    network.connect_to_host(host_address).await?;
-    tracing::info!("✓ Connected to host at {}", host_address);
+    let mut network =
+        Network::new(config.tls.psk.clone()).with_socket_options(socket_options(&config));
+    network.set_accept_new_fingerprint(accept_new_fingerprint);
+    network.set_host_name(config.host_name.clone());
+    if let Some(days) = config
+        .behavior
+        .as_ref()
+        .and_then(|b| b.fingerprint_expiry_days)
+    {
+        let expiry =
+            (days > 0).then(|| std::time::Duration::from_secs(u64::from(days) * 24 * 60 * 60));
+        network.set_fingerprint_expiry(expiry).await;
+    }
+    load_peer_psks(&network);
 
     // Create input handler for event injection
     let input_handler = {
         #[cfg(target_os = "linux")]
         {
-            use multishiva::core::input_evdev::EvdevInputHandler;
-            EvdevInputHandler::new()?
+            AgentInjector::new(input_backend)?
         }
 
         #[cfg(not(target_os = "linux"))]
         {
             use multishiva::core::input::RdevInputHandler;
+            let _ = input_backend;
             RdevInputHandler::new()
         }
     };
 
+    // Announce our name and screen size to the host
+    network.set_agent_info(config.self_name.clone(), input_handler.get_screen_size());
+
+    // A replay stands in for the events of the host
+    let (replay_tx, mut replay_rx) = tokio::sync::mpsc::channel(100);
+    let replaying = trace.start_replay(replay_tx);
+    if !replaying {
+        network.connect_to_host(host_address).await?;
+        tracing::info!("✓ Connected to host at {}", host_address);
+    }
+
     // Create a separate input handler for local capture (to detect edge crossing)
     #[cfg(target_os = "linux")]
     let mut local_input_handler = {
@@ -531,7 +2304,9 @@ async fn run_agent_mode(
     };
 
     let (local_event_tx, mut local_event_rx) = tokio::sync::mpsc::channel(100);
-    local_input_handler.start_capture(local_event_tx).await?;
+    if !replaying {
+        local_input_handler.start_capture(local_event_tx).await?;
+    }
 
     let screen_size = local_input_handler.get_screen_size();
     tracing::info!("📺 Screen size: {}x{}", screen_size.0, screen_size.1);
@@ -542,29 +2317,223 @@ async fn run_agent_mode(
         .and_then(|b| b.edge_threshold_px)
         .unwrap_or(10) as i32;
 
+    let reconnect_delay = std::time::Duration::from_millis(
+        config
+            .behavior
+            .as_ref()
+            .and_then(|b| b.reconnect_delay_ms)
+            .unwrap_or(5000),
+    );
+
     tracing::info!("✓ Input injection ready");
     tracing::info!("Waiting for events from host...");
 
-    // Track whether we currently have focus
-    let mut has_focus = false;
+    // Only the host grants focus
+    let granter = config.host_name.as_deref().unwrap_or("host");
+    let edge_targets = config.edge_targets();
+    // Edge leading back to the machine that granted focus
+    let mut focus_return: (Edge, Option<String>) = (Edge::Right, None);
+    // Edge last reported to the host, until the cursor leaves its zone
+    let mut reported_edge: Option<Edge> = None;
+    // Maps host pixels to ours while we have focus
+    let mut delta_scaler = DeltaScaler::default();
+    // Keys and buttons we injected and have not released yet
+    let mut injected = ModifierTracker::new();
+
+    let (mut clipboard, mut clipboard_rx) = start_clipboard_sync(&config);
+
+    let stop = {
+        let focus_holder = focus.watch();
+        let self_name = config.self_name.clone();
+        let host_address = host_address.to_string();
+        let host_name = granter.to_string();
+        let monitor = network.monitor();
+        let rotation = network.psk_rotation();
+        let started = std::time::Instant::now();
+        let status: StatusProvider = std::sync::Arc::new(move || {
+            let peers = match monitor.connection_status() {
+                ConnectionStatus::Connected { last_heartbeat } => vec![PeerStatus {
+                    name: host_name.clone(),
+                    address: host_address.clone(),
+                    last_heartbeat_ms: Some(last_heartbeat.elapsed().as_millis() as u64),
+                    connected_secs: None,
+                    latency_ms: None,
+                    traffic: None,
+                }],
+                _ => Vec::new(),
+            };
+
+            StatusResponse {
+                mode: "agent".to_string(),
+                uptime_secs: started.elapsed().as_secs(),
+                connected_agents: Vec::new(),
+                peers,
+                focus: (*focus_holder.borrow() == self_name).then(|| self_name.clone()),
+                events_forwarded: monitor.events_forwarded(),
+                queue_depth: monitor.queue_depth(),
+                metrics: Some(monitor.metrics_snapshot()),
+                discovered_peers: Vec::new(),
+                last_auto_release: None,
+            }
+        });
+        let stop = start_control_socket(
+            status,
+            std::sync::Arc::new(move |new_psk| rotation.rotate(new_psk)),
+            clipboard.manager.history_handle(),
+        );
+        start_metrics_log(network.monitor());
+        stop
+    };
 
     // Track our current cursor position and last received position from host
     let mut current_position: Option<(i32, i32)> = None;
     let mut last_host_position: Option<(i32, i32)> = None;
 
+    // Periodically check that the host is still alive
+    let mut liveness_check = tokio::time::interval(std::time::Duration::from_secs(1));
+
     // Event receiving loop
-    let ctrl_c = signal::ctrl_c();
+    let ctrl_c = shutdown_signal(stop, SignalListener::new()?, None);
     tokio::pin!(ctrl_c);
 
-    loop {
+    'events: loop {
         tokio::select! {
-            Some(event) = network.receive_event() => {
+            _ = liveness_check.tick(), if !replaying => {
+                if network.connection_status() != ConnectionStatus::Disconnected {
+                    continue;
+                }
+
+                tracing::warn!("⚠️  Lost connection to host at {}", host_address);
+                // The host takes focus back when we disconnect
+                if focus.has_focus(&config.self_name) {
+                    let _ = focus.recall();
+                }
+                current_position = None;
+                last_host_position = None;
+
+                // Reconnect until it succeeds or the user interrupts
+                loop {
+                    tracing::info!("Reconnecting in {:?}...", reconnect_delay);
+                    tokio::select! {
+                        _ = tokio::time::sleep(reconnect_delay) => {}
+                        _ = &mut ctrl_c => {
+                            tracing::info!("Stopping...");
+                            break 'events;
+                        }
+                    }
+                    match network.connect_to_host(host_address).await {
+                        Ok(()) => {
+                            tracing::info!("✓ Reconnected to host at {}", host_address);
+                            break;
+                        }
+                        Err(e) => tracing::warn!("Reconnection failed: {}", e),
+                    }
+                }
+            }
+            Some(Event::ClipboardSync { source, text }) = clipboard_rx.recv() => {
+                clipboard.outbox.push(&source, text);
+            }
+            // One clipboard event at a time, once the bulk lane has room
+            _ = network.host_bulk_lane_ready(), if !clipboard.outbox.is_empty() => {
+                let Some(sync) = clipboard.outbox.pop() else {
+                    continue;
+                };
+                if let Err(e) = network.send_event_to_host(sync).await {
+                    tracing::error!("Failed to send clipboard: {}", e);
+                }
+            }
+            Some(event) = async {
+                if replaying {
+                    replay_rx.recv().await
+                } else {
+                    network.receive_event().await
+                }
+            } => {
                 tracing::debug!("Received event from host: {:?}", event);
+                trace.record(&event);
+
+                let Some(event) = clipboard.receive(event) else {
+                    continue;
+                };
+
+                // The host is gone: nothing it pressed will be released otherwise
+                if let Event::Disconnect { reason } = &event {
+                    tracing::warn!("⚠️  Host at {} disconnected: {}", host_address, reason);
+                    for release in injected.release_all() {
+                        if let Err(e) = input_handler.inject_event(release).await {
+                            network.record_injection_failure();
+                            tracing::error!("Failed to release held input: {}", e);
+                        }
+                    }
+                    if focus.has_focus(&config.self_name) {
+                        let _ = focus.recall();
+                    }
+                    current_position = None;
+                    last_host_position = None;
+                    continue;
+                }
+
+                // The host moved focus on, e.g. to the agent behind the edge we reached
+                if matches!(event, Event::FocusRelease) {
+                    if focus.has_focus(&config.self_name) {
+                        tracing::info!("◀ Focus released by the host");
+                        for release in injected.release_all() {
+                            if let Err(e) = input_handler.inject_event(release).await {
+                                network.record_injection_failure();
+                                tracing::error!("Failed to release held input: {}", e);
+                            }
+                        }
+                        let _ = focus.release();
+                        current_position = None;
+                        last_host_position = None;
+                    }
+                    continue;
+                }
+
+                // A chained grant is either ours or passed on toward its target
+                let event = match event {
+                    Event::FocusGrantChain { path, x, y } => {
+                        match next_focus_hop(&path, &config.self_name) {
+                            FocusHop::Target => Event::FocusGrant {
+                                target: config.self_name.clone(),
+                                x,
+                                y,
+                                host_width: 0,
+                                host_height: 0,
+                            },
+                            FocusHop::Relay(next) => {
+                                tracing::info!("Relaying focus grant to '{}'", next);
+                                let chain = Event::FocusGrantChain { path, x, y };
+                                if let Err(e) = network.send_event_to_host(chain).await {
+                                    tracing::error!("Failed to relay focus grant: {}", e);
+                                }
+                                continue;
+                            }
+                            FocusHop::NotOnPath => {
+                                tracing::warn!("Ignoring focus grant along {}", path.join(" → "));
+                                continue;
+                            }
+                        }
+                    }
+                    other => other,
+                };
 
                 // Check if we're receiving focus
-                if let multishiva::core::events::Event::FocusGrant { target: _, x, y } = event {
+                if let Event::FocusGrant { x, y, host_width, host_height, .. } = event {
                     tracing::warn!("🎯 RECEIVED FocusGrant with entry position ({}, {})", x, y);
-                    has_focus = true;
+                    delta_scaler = DeltaScaler::new((host_width, host_height), screen_size);
+                    // A repeated grant only moves the cursor
+                    if let Err(e) = focus.grant_to(&config.self_name, x, y) {
+                        tracing::warn!("{}", e);
+                    }
+
+                    focus_return = return_edge(&edge_targets, granter, (x, y), screen_size);
+                    reported_edge = None;
+                    tracing::info!(
+                        "Focus returns through the {} edge to '{}'",
+                        focus_return.0,
+                        focus_return.1.as_deref().unwrap_or(granter)
+                    );
 
                     // Set initial position
                     current_position = Some((x, y));
@@ -574,6 +2543,7 @@ async fn run_agent_mode(
                     // FocusGrant is not directly injectable, so we convert it to a MouseMove
                     let move_event = multishiva::core::events::Event::MouseMove { x, y };
                     if let Err(e) = input_handler.inject_event(move_event).await {
+                        network.record_injection_failure();
                         tracing::error!("❌ Failed to position cursor: {}", e);
                     } else {
                         tracing::warn!("✅ Cursor INJECTED at ({}, {})", x, y);
@@ -581,13 +2551,28 @@ async fn run_agent_mode(
                     continue;
                 }
 
+                // Relative motion from a grabbed host mouse moves our cursor directly
+                if let Event::MouseMoveRelative { dx, dy } = event {
+                    if let (true, Some((curr_x, curr_y))) = (focus.has_focus(&config.self_name), current_position) {
+                        let new_x = (curr_x + dx).clamp(0, screen_size.0 as i32 - 1);
+                        let new_y = (curr_y + dy).clamp(0, screen_size.1 as i32 - 1);
+                        current_position = Some((new_x, new_y));
+
+                        let move_event = Event::MouseMove { x: new_x, y: new_y };
+                        if let Err(e) = input_handler.inject_event(move_event).await {
+                            network.record_injection_failure();
+                            tracing::error!("Failed to inject mouse movement: {}", e);
+                        }
+                    }
+                    continue;
+                }
+
                 // Handle MouseMove with delta calculation when we have focus
-                if has_focus && matches!(event, multishiva::core::events::Event::MouseMove { .. }) {
+                if focus.has_focus(&config.self_name) && matches!(event, multishiva::core::events::Event::MouseMove { .. }) {
                     if let multishiva::core::events::Event::MouseMove { x: host_x, y: host_y } = event {
                         if let (Some((curr_x, curr_y)), Some((last_x, last_y))) = (current_position, last_host_position) {
-                            // Calculate delta from host's movement
-                            let delta_x = host_x - last_x;
-                            let delta_y = host_y - last_y;
+                            // Calculate delta from host's movement, in our pixels
+                            let (delta_x, delta_y) = delta_scaler.scale(host_x - last_x, host_y - last_y);
 
                             // Apply delta to our current position
                             let new_x = curr_x + delta_x;
@@ -605,6 +2590,7 @@ async fn run_agent_mode(
                             // Inject the new position
                             let move_event = multishiva::core::events::Event::MouseMove { x: new_x, y: new_y };
                             if let Err(e) = input_handler.inject_event(move_event).await {
+                                network.record_injection_failure();
                                 tracing::error!("Failed to inject mouse movement: {}", e);
                             }
                         }
@@ -613,39 +2599,61 @@ async fn run_agent_mode(
                 }
 
                 // Inject other events locally (skip FocusRelease and Heartbeat as they're not injectable)
-                if !matches!(event, multishiva::core::events::Event::FocusRelease | multishiva::core::events::Event::Heartbeat | multishiva::core::events::Event::MouseMove { .. }) {
+                if !matches!(event, multishiva::core::events::Event::FocusRelease | multishiva::core::events::Event::Heartbeat { .. } | multishiva::core::events::Event::MouseMove { .. }) {
                     if let Err(e) = input_handler.inject_event(event.clone()).await {
+                        network.record_injection_failure();
                         tracing::error!("Failed to inject event: {}", e);
                     } else {
                         tracing::trace!("✓ Event injected: {:?}", event);
+                        injected.observe(&event);
                     }
                 }
             }
             Some(local_event) = local_event_rx.recv() => {
                 // Monitor local mouse movement to detect edge crossing (return to host)
-                if has_focus {
+                if focus.has_focus(&config.self_name) {
                     if let multishiva::core::events::Event::MouseMove { x, y } = &local_event {
                         tracing::trace!("Local mouse position: ({}, {})", x, y);
 
-                        // Check if mouse reached the right edge (opposite from where we entered)
-                        let at_right = *x > (screen_size.0 as i32 - edge_threshold);
-
-                        if at_right {
-                            tracing::info!("🚀 Right edge reached! Returning focus to host");
+                        // Check if mouse reached an edge leading back to where focus came from:
+                        // one whose configured neighbor is the granter, or the computed return
+                        // edge when none is configured
+                        let (return_edge, return_to) = &focus_return;
+                        let edge = edge_at((*x, *y), screen_size, edge_threshold);
+                        let back_edge = edge
+                            .filter(|edge| edge_targets.get(edge.as_str()).is_some_and(|n| n == granter))
+                            .or_else(|| {
+                                (return_to.is_none() && is_in_edge_zone(*return_edge, (*x, *y), screen_size, edge_threshold))
+                                    .then_some(*return_edge)
+                            });
+                        if let Some(back_edge) = back_edge {
+                            tracing::info!("🚀 {} edge reached! Returning focus to '{}'", back_edge, granter);
 
                             // Send FocusRelease back to host
                             if let Err(e) = network.send_event_to_host(multishiva::core::events::Event::FocusRelease).await {
                                 tracing::error!("Failed to send FocusRelease: {}", e);
+                            } else if let Err(e) = focus.release() {
+                                tracing::warn!("{}", e);
                             } else {
-                                has_focus = false;
                                 tracing::info!("✓ Focus released back to host");
                             }
+                        } else if let Some(edge) = edge.filter(|edge| {
+                            Some(*edge) != reported_edge && (edge_targets.is_empty() || edge_targets.contains_key(edge.as_str()))
+                        }) {
+                            // An onward hop: the host knows the topology and moves focus to the neighbor.
+                            // Without edges of our own, every edge is left to the host's topology
+                            tracing::debug!("{} edge reached, reporting it to the host", edge);
+                            let reached = Event::EdgeReached { edge, x: *x, y: *y };
+                            if let Err(e) = network.send_event_to_host(reached).await {
+                                tracing::error!("Failed to report edge: {}", e);
+                            }
                         }
+                        reported_edge = edge;
                     }
                 }
             }
             _ = &mut ctrl_c => {
-                tracing::info!("Received Ctrl+C, stopping...");
+                tracing::info!("Stopping...");
                 break;
             }
         }
@@ -653,7 +2661,7 @@ async fn run_agent_mode(
 
     tracing::info!("Agent stopping...");
     local_input_handler.stop_capture().await;
-    network.stop().await;
+    network.stop_with_reason("agent shutting down").await;
     tracing::info!("Agent stopped");
 
     Ok(())
@@ -18,7 +18,7 @@ fn test_cli_version() {
     cmd.arg("--version");
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("0.1.0"));
+        .stdout(predicate::str::contains(env!("CARGO_PKG_VERSION")));
 }
 
 #[test]
@@ -164,3 +164,70 @@ edges:
         .env("MULTISHIVA_CONFIG", config_path.to_str().unwrap());
     // Should read simulate flag from environment variable
 }
+
+#[test]
+fn test_init_non_interactive_writes_valid_config() {
+    use multishiva::cli::{init_config, InitArgs, Mode};
+    use multishiva::core::config::{Config, ConfigMode};
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("multishiva").join("config.yml");
+    let init = InitArgs {
+        non_interactive: true,
+        self_name: Some("laptop".to_string()),
+        mode: Some(Mode::Agent),
+        port: Some(6000),
+        host_address: Some("192.168.1.10:6000".to_string()),
+        ..Default::default()
+    };
+
+    init_config(&init, &config_path, std::io::empty(), std::io::sink()).unwrap();
+
+    let config = Config::from_file(config_path.to_str().unwrap()).unwrap();
+    assert!(config.validate().is_ok());
+    assert_eq!(config.self_name, "laptop");
+    assert_eq!(config.mode, ConfigMode::Agent);
+    assert_eq!(config.port, 6000);
+    assert_eq!(config.host_address.as_deref(), Some("192.168.1.10:6000"));
+    // A random 256-bit key was generated
+    assert_eq!(config.tls.psk.len(), 64);
+
+    // An existing file is only replaced with --force
+    assert!(init_config(&init, &config_path, std::io::empty(), std::io::sink()).is_err());
+    let force = InitArgs {
+        force: true,
+        psk: Some("chosen-psk".to_string()),
+        ..init
+    };
+    init_config(&force, &config_path, std::io::empty(), std::io::sink()).unwrap();
+    let config = Config::from_file(config_path.to_str().unwrap()).unwrap();
+    assert_eq!(config.tls.psk, "chosen-psk");
+}
+
+#[test]
+fn test_init_interactive_reads_answers() {
+    use multishiva::cli::{init_config, InitArgs};
+    use multishiva::core::config::ConfigMode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.yml");
+    // Name, mode, default port, PSK, empty host address
+    let answers = "desk\nagent\n\nsecret\n\n";
+    let mut prompts = Vec::new();
+
+    let config = init_config(
+        &InitArgs::default(),
+        &config_path,
+        answers.as_bytes(),
+        &mut prompts,
+    )
+    .unwrap();
+    assert_eq!(config.self_name, "desk");
+    assert_eq!(config.mode, ConfigMode::Agent);
+    assert_eq!(config.port, 53421);
+    assert_eq!(config.tls.psk, "secret");
+    assert_eq!(config.host_address, None);
+    assert!(String::from_utf8(prompts)
+        .unwrap()
+        .contains("Port [53421]: "));
+}
@@ -26,8 +26,8 @@ edges:
     assert_eq!(config.port, 53421);
     assert_eq!(config.tls.psk, "test-key");
     assert!(matches!(config.mode, ConfigMode::Host));
-    assert_eq!(config.edges.get("right_of"), Some(&"laptop".to_string()));
-    assert_eq!(config.edges.get("below"), Some(&"desktop2".to_string()));
+    assert_eq!(config.edge_targets()["right_of"], "laptop");
+    assert_eq!(config.edge_targets()["below"], "desktop2");
 }
 
 #[test]
@@ -52,6 +52,31 @@ behavior:
     assert_eq!(config.behavior.unwrap().reconnect_delay_ms, Some(2000));
 }
 
+#[test]
+fn test_config_discovery_timing() {
+    use multishiva::core::discovery::DiscoveryConfig;
+    use std::time::Duration;
+
+    let yaml_content = r#"
+self_name: test-agent
+mode: agent
+port: 53421
+tls:
+  psk: agent-key
+edges: {}
+behavior:
+  discovery_timeout_ms: 15000
+"#;
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(yaml_content.as_bytes()).unwrap();
+
+    let config = Config::from_file(temp_file.path().to_str().unwrap()).unwrap();
+    let discovery = DiscoveryConfig::from_config(&config);
+    assert_eq!(discovery.timeout, Duration::from_secs(15));
+    assert_eq!(discovery.poll_interval, Duration::from_millis(500));
+}
+
 #[test]
 fn test_config_with_hotkeys() {
     let yaml_content = r#"
@@ -0,0 +1,29 @@
+use multishiva::core::event_recorder::{EventRecorder, EventReplayer};
+use multishiva::core::events::{Event, Key};
+
+#[test]
+fn test_event_recording_ignores_truncated_tail() {
+    let mut recording = Vec::new();
+    {
+        let mut recorder = EventRecorder::new(&mut recording);
+        recorder.record(&Event::MouseMove { x: 1, y: 2 }).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        recorder
+            .record(&Event::KeyPress { key: Key::KeyA })
+            .unwrap();
+    }
+
+    let replayer = EventReplayer::from_reader(recording.as_slice()).unwrap();
+    let events = replayer.events();
+    assert_eq!(events.len(), 2);
+    assert!(events[1].offset_us >= events[0].offset_us + 2_000);
+
+    // A crash while writing the last record leaves a partial frame
+    recording.truncate(recording.len() - 1);
+    let replayer = EventReplayer::from_reader(recording.as_slice()).unwrap();
+    assert_eq!(replayer.events().len(), 1);
+
+    // A complete frame holding garbage is an error
+    let garbage = [0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0xc1, 0xc1];
+    assert!(EventReplayer::from_reader(&garbage[..]).is_err());
+}
@@ -1,4 +1,4 @@
-use multishiva::core::events::{Event, Key, MouseButton};
+use multishiva::core::events::{Event, Key, Modifiers, MouseButton};
 
 #[test]
 fn test_event_mouse_move_serialization() {
@@ -51,27 +51,106 @@ fn test_event_focus_grant_serialization() {
         target: "agent1".to_string(),
         x: 50,
         y: 100,
+        host_width: 2560,
+        host_height: 1440,
     };
     let serialized = rmp_serde::to_vec(&event).unwrap();
     let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
 
     match deserialized {
-        Event::FocusGrant { target, x, y } => {
+        Event::FocusGrant {
+            target,
+            x,
+            y,
+            host_width,
+            host_height,
+        } => {
             assert_eq!(target, "agent1");
             assert_eq!(x, 50);
             assert_eq!(y, 100);
+            assert_eq!((host_width, host_height), (2560, 1440));
         }
         _ => panic!("Wrong event type"),
     }
 }
 
+#[test]
+fn test_event_mouse_scroll_precise_serialization() {
+    let event = Event::MouseScrollPrecise {
+        dx_120: -15,
+        dy_120: 360,
+    };
+    let serialized = rmp_serde::to_vec(&event).unwrap();
+    let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
+    assert_eq!(deserialized, event);
+}
+
+#[test]
+fn test_event_scroll_with_modifiers_serialization() {
+    let event = Event::MouseScrollWithModifiers {
+        delta_x: 0,
+        delta_y: -3,
+        modifiers: Modifiers::CTRL | Modifiers::META,
+    };
+    let serialized = rmp_serde::to_vec(&event).unwrap();
+    let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
+    assert_eq!(deserialized, event);
+
+    // Readable in recordings
+    let json = serde_json::to_string(&event).unwrap();
+    assert!(json.contains("\"CTRL | META\""), "{}", json);
+    assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), event);
+}
+
+#[test]
+fn test_event_text_input_serialization_multibyte() {
+    for text in [
+        "é",
+        "Crème brûlée",
+        "日本語の入力",
+        "👍🏽 done",
+        "e\u{301}",
+        "",
+    ] {
+        let event = Event::TextInput {
+            text: text.to_string(),
+        };
+        let serialized = rmp_serde::to_vec(&event).unwrap();
+        let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
+        assert_eq!(deserialized, event);
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), event);
+    }
+}
+
 #[test]
 fn test_event_heartbeat_serialization() {
-    let event = Event::Heartbeat;
+    let event = Event::Heartbeat { sent_at_ms: 1234 };
     let serialized = rmp_serde::to_vec(&event).unwrap();
     let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
+    assert_eq!(deserialized, event);
 
-    assert!(matches!(deserialized, Event::Heartbeat));
+    let ack = Event::HeartbeatAck { sent_at_ms: 1234 };
+    let serialized = rmp_serde::to_vec(&ack).unwrap();
+    assert_eq!(rmp_serde::from_slice::<Event>(&serialized).unwrap(), ack);
+}
+
+#[test]
+fn test_event_ping_pong_serialization() {
+    for event in [
+        Event::Ping {
+            seq: 7,
+            sent_at_ms: 1234,
+        },
+        Event::Pong {
+            seq: 7,
+            sent_at_ms: 1234,
+        },
+    ] {
+        let serialized = rmp_serde::to_vec(&event).unwrap();
+        assert_eq!(rmp_serde::from_slice::<Event>(&serialized).unwrap(), event);
+    }
 }
 
 #[test]
@@ -103,3 +182,176 @@ fn test_event_focus_release() {
 
     assert!(matches!(deserialized, Event::FocusRelease));
 }
+
+#[test]
+fn test_event_clipboard_sync_serialization() {
+    let event = Event::ClipboardSync {
+        source: "host".to_string(),
+        text: "copied text".to_string(),
+    };
+    let serialized = rmp_serde::to_vec(&event).unwrap();
+    let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
+
+    match deserialized {
+        Event::ClipboardSync { source, text } => {
+            assert_eq!(source, "host");
+            assert_eq!(text, "copied text");
+        }
+        _ => panic!("Wrong event type"),
+    }
+}
+
+#[test]
+fn test_event_hello_serialization() {
+    let event = Event::Hello {
+        name: "agent1".to_string(),
+        screen_width: 2560,
+        screen_height: 1440,
+    };
+    let serialized = rmp_serde::to_vec(&event).unwrap();
+    let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
+    assert_eq!(deserialized, event);
+}
+
+#[test]
+fn test_event_psk_rotate_serialization() {
+    let event = Event::PskRotate {
+        new_psk_hash: "abc123".to_string(),
+        signature: "def456".to_string(),
+    };
+    let serialized = rmp_serde::to_vec(&event).unwrap();
+    let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
+    assert_eq!(deserialized, event);
+
+    let ack = Event::PskRotateAck {
+        new_psk_hash: "abc123".to_string(),
+    };
+    let serialized = rmp_serde::to_vec(&ack).unwrap();
+    assert_eq!(rmp_serde::from_slice::<Event>(&serialized).unwrap(), ack);
+}
+
+#[test]
+fn test_event_mouse_move_relative_serialization() {
+    let event = Event::MouseMoveRelative { dx: -3, dy: 12 };
+    let serialized = rmp_serde::to_vec(&event).unwrap();
+    let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
+    assert_eq!(deserialized, event);
+}
+
+#[test]
+fn test_event_focus_grant_chain_serialization() {
+    let event = Event::FocusGrantChain {
+        path: vec![
+            "host".to_string(),
+            "agent1".to_string(),
+            "agent2".to_string(),
+        ],
+        x: 0,
+        y: 540,
+    };
+    let serialized = rmp_serde::to_vec(&event).unwrap();
+    let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
+    assert_eq!(deserialized, event);
+}
+
+#[test]
+fn test_event_clipboard_chunk_serialization() {
+    let event = Event::ClipboardChunk {
+        source: "host".to_string(),
+        id: 42,
+        seq: 3,
+        total: 16,
+        data: "part of a large log".to_string(),
+    };
+    let serialized = rmp_serde::to_vec(&event).unwrap();
+    let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
+    assert_eq!(deserialized, event);
+}
+
+#[test]
+fn test_event_extended_keys_round_trip() {
+    let keys = [
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::F11,
+        Key::F12,
+        Key::Delete,
+        Key::Num0,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+        Key::Minus,
+        Key::Equal,
+        Key::LeftBracket,
+        Key::RightBracket,
+        Key::SemiColon,
+        Key::Quote,
+        Key::BackQuote,
+        Key::BackSlash,
+        Key::IntlBackslash,
+        Key::Comma,
+        Key::Dot,
+        Key::Slash,
+        Key::UpArrow,
+        Key::DownArrow,
+        Key::LeftArrow,
+        Key::RightArrow,
+        Key::Insert,
+        Key::Home,
+        Key::End,
+        Key::PageUp,
+        Key::PageDown,
+        Key::CapsLock,
+        Key::NumLock,
+        Key::ScrollLock,
+        Key::PrintScreen,
+        Key::Pause,
+        Key::Kp0,
+        Key::Kp1,
+        Key::Kp2,
+        Key::Kp3,
+        Key::Kp4,
+        Key::Kp5,
+        Key::Kp6,
+        Key::Kp7,
+        Key::Kp8,
+        Key::Kp9,
+        Key::KpPlus,
+        Key::KpMinus,
+        Key::KpMultiply,
+        Key::KpDivide,
+        Key::KpReturn,
+        Key::KpDelete,
+        Key::MediaPlayPause,
+        Key::MediaStop,
+        Key::MediaNext,
+        Key::MediaPrevious,
+        Key::VolumeUp,
+        Key::VolumeDown,
+        Key::VolumeMute,
+    ];
+    for key in keys {
+        for event in [
+            Event::KeyPress { key: key.clone() },
+            Event::KeyRelease { key: key.clone() },
+        ] {
+            let serialized = rmp_serde::to_vec(&event).unwrap();
+            let deserialized: Event = rmp_serde::from_slice(&serialized).unwrap();
+            assert_eq!(deserialized, event);
+        }
+    }
+}
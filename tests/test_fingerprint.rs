@@ -1,4 +1,8 @@
-use multishiva::core::fingerprint::{Fingerprint, FingerprintStore};
+use multishiva::core::fingerprint::{
+    Fingerprint, FingerprintStore, FingerprintVerification, MergeStrategy,
+    DEFAULT_FINGERPRINT_EXPIRY,
+};
+use std::time::Duration;
 use tempfile::TempDir;
 
 #[test]
@@ -140,6 +144,35 @@ fn test_fingerprint_store_remove() {
     assert!(store.get("machine1").is_none());
 }
 
+#[test]
+fn test_fingerprint_store_rename() {
+    let temp_dir = TempDir::new().unwrap();
+    let store_path = temp_dir.path().join("fingerprints.json");
+
+    let mut store = FingerprintStore::new(store_path.clone()).unwrap();
+    store
+        .save(
+            "10.0.0.2:53421",
+            Fingerprint::new("10.0.0.2:53421", "hash123"),
+        )
+        .unwrap();
+
+    assert!(store.rename("10.0.0.2:53421", "desk").unwrap());
+    let mut store = FingerprintStore::new(store_path).unwrap();
+    assert!(store.get("10.0.0.2:53421").is_none());
+    let fp = store.get("desk").unwrap();
+    assert_eq!(fp.machine_name(), "desk");
+    assert_eq!(fp.hash(), "hash123");
+
+    // An existing fingerprint is never overwritten
+    store
+        .save("laptop", Fingerprint::new("laptop", "hash456"))
+        .unwrap();
+    assert!(!store.rename("desk", "laptop").unwrap());
+    assert_eq!(store.get("laptop").unwrap().hash(), "hash456");
+    assert!(!store.rename("missing", "other").unwrap());
+}
+
 #[test]
 fn test_fingerprint_store_list_all() {
     let temp_dir = TempDir::new().unwrap();
@@ -183,3 +216,246 @@ fn test_fingerprint_hash_consistency() {
     // Same data should produce same hash
     assert_eq!(fp1.hash(), fp2.hash());
 }
+
+/// Writes a store holding one fingerprint first seen `age_days` ago.
+fn store_with_fingerprint_aged(path: &std::path::Path, name: &str, age_days: i64) {
+    let first_seen = (chrono::Utc::now() - chrono::Duration::days(age_days)).to_rfc3339();
+    let json = format!(
+        r#"{{"{name}": {{"machine_name": "{name}", "hash": "old-hash", "first_seen": "{first_seen}"}}}}"#
+    );
+    std::fs::write(path, json).unwrap();
+}
+
+#[test]
+fn test_expired_fingerprint_is_still_compared() {
+    let temp_dir = TempDir::new().unwrap();
+    let store_path = temp_dir.path().join("fingerprints.json");
+    store_with_fingerprint_aged(&store_path, "host", 91);
+
+    let mut store = FingerprintStore::new(store_path.clone()).unwrap();
+    assert!(store.get("host").unwrap().is_expired());
+    // Expiry never trusts another hash
+    assert_eq!(
+        store.verify_or_save("host", "new-hash").unwrap(),
+        FingerprintVerification::Mismatch {
+            stored: "old-hash".to_string(),
+            received: "new-hash".to_string(),
+        }
+    );
+    assert!(store.get("host").unwrap().is_expired());
+
+    // The same hash renews it, on disk too
+    assert_eq!(
+        store.verify_or_save("host", "old-hash").unwrap(),
+        FingerprintVerification::Renewed
+    );
+    assert!(!store.get("host").unwrap().is_expired());
+    let mut reloaded = FingerprintStore::new(store_path).unwrap();
+    assert!(!reloaded.get("host").unwrap().is_expired());
+    assert_eq!(
+        reloaded.verify_or_save("host", "old-hash").unwrap(),
+        FingerprintVerification::Verified
+    );
+}
+
+#[test]
+fn test_fingerprint_store_update_trusts_rotated_hash() {
+    let temp_dir = TempDir::new().unwrap();
+    let store_path = temp_dir.path().join("fingerprints.json");
+    store_with_fingerprint_aged(&store_path, "host", 60);
+
+    let mut store = FingerprintStore::new(store_path.clone()).unwrap();
+    assert!(matches!(
+        store.verify_or_save("host", "rotated-hash").unwrap(),
+        FingerprintVerification::Mismatch { .. }
+    ));
+
+    store.update("host", "rotated-hash").unwrap();
+    let updated = store.get("host").unwrap();
+    assert_eq!(updated.hash(), "rotated-hash");
+    // first_seen restarted, so the 90-day expiry did too
+    store.set_expires_after(Some(Duration::from_secs(30 * 24 * 60 * 60)));
+    assert!(!store.get("host").unwrap().is_expired());
+    assert_eq!(
+        store.verify_or_save("host", "rotated-hash").unwrap(),
+        FingerprintVerification::Verified
+    );
+
+    // Persisted, and a machine without a fingerprint just gets one
+    let mut reloaded = FingerprintStore::new(store_path).unwrap();
+    assert_eq!(reloaded.get("host").unwrap().hash(), "rotated-hash");
+    reloaded.update("new-host", "some-hash").unwrap();
+    assert_eq!(
+        reloaded.verify_or_save("new-host", "some-hash").unwrap(),
+        FingerprintVerification::Verified
+    );
+}
+
+#[test]
+fn test_fingerprint_expiry_is_configurable() {
+    let temp_dir = TempDir::new().unwrap();
+    let store_path = temp_dir.path().join("fingerprints.json");
+    store_with_fingerprint_aged(&store_path, "host", 30);
+
+    let mut store = FingerprintStore::new(store_path).unwrap();
+    assert_eq!(store.expires_after(), Some(DEFAULT_FINGERPRINT_EXPIRY));
+    assert_eq!(
+        store.verify_or_save("host", "old-hash").unwrap(),
+        FingerprintVerification::Verified
+    );
+
+    store.set_expires_after(Some(Duration::from_secs(7 * 24 * 60 * 60)));
+    assert!(store.get("host").unwrap().is_expired());
+    store.set_expires_after(None);
+    assert!(!store.get("host").unwrap().is_expired());
+}
+
+/// Exports "shared" and "new" from one store and sets up another holding
+/// "shared" with a different hash and an identical "same".
+fn overlapping_stores(temp_dir: &TempDir) -> (std::path::PathBuf, FingerprintStore) {
+    let mut source = FingerprintStore::new(temp_dir.path().join("source.json")).unwrap();
+    source.update("shared", "exported-hash").unwrap();
+    source.update("new", "new-hash").unwrap();
+    source.update("same", "same-hash").unwrap();
+    let export_path = temp_dir.path().join("export.json");
+    assert_eq!(source.export_to(&export_path).unwrap(), 3);
+
+    let mut target = FingerprintStore::new(temp_dir.path().join("target.json")).unwrap();
+    target.update("shared", "local-hash").unwrap();
+    target.update("same", "same-hash").unwrap();
+    (export_path, target)
+}
+
+#[test]
+fn test_fingerprint_export_is_sorted_array() {
+    let temp_dir = TempDir::new().unwrap();
+    let (export_path, _) = overlapping_stores(&temp_dir);
+
+    let exported: Vec<Fingerprint> =
+        serde_json::from_str(&std::fs::read_to_string(&export_path).unwrap()).unwrap();
+    let names: Vec<&str> = exported.iter().map(|fp| fp.machine_name()).collect();
+    assert_eq!(names, ["new", "same", "shared"]);
+}
+
+#[test]
+fn test_fingerprint_import_keep_existing() {
+    let temp_dir = TempDir::new().unwrap();
+    let (export_path, mut store) = overlapping_stores(&temp_dir);
+
+    let imported = store
+        .import_from(&export_path, MergeStrategy::KeepExisting)
+        .unwrap();
+    assert_eq!(imported, 1);
+    assert_eq!(store.get("shared").unwrap().hash(), "local-hash");
+    assert_eq!(store.get("new").unwrap().hash(), "new-hash");
+
+    // The import was persisted
+    let reloaded = FingerprintStore::new(temp_dir.path().join("target.json")).unwrap();
+    assert_eq!(reloaded.get("new").unwrap().hash(), "new-hash");
+}
+
+#[test]
+fn test_fingerprint_import_overwrite() {
+    let temp_dir = TempDir::new().unwrap();
+    let (export_path, mut store) = overlapping_stores(&temp_dir);
+
+    let imported = store
+        .import_from(&export_path, MergeStrategy::Overwrite)
+        .unwrap();
+    assert_eq!(imported, 2);
+    assert_eq!(store.get("shared").unwrap().hash(), "exported-hash");
+    assert_eq!(
+        store.verify_or_save("new", "new-hash").unwrap(),
+        FingerprintVerification::Verified
+    );
+
+    let reloaded = FingerprintStore::new(temp_dir.path().join("target.json")).unwrap();
+    assert_eq!(reloaded.get("shared").unwrap().hash(), "exported-hash");
+}
+
+#[test]
+fn test_fingerprint_import_error_on_conflict() {
+    let temp_dir = TempDir::new().unwrap();
+    let (export_path, mut store) = overlapping_stores(&temp_dir);
+
+    let error = store
+        .import_from(&export_path, MergeStrategy::ErrorOnConflict)
+        .unwrap_err();
+    assert!(error.to_string().contains("shared"));
+    assert!(!error.to_string().contains("same"));
+    // Nothing was imported
+    assert!(store.get("new").is_none());
+    assert_eq!(store.get("shared").unwrap().hash(), "local-hash");
+
+    // Without the conflict, identical fingerprints do not get in the way
+    store.remove("shared").unwrap();
+    assert_eq!(
+        store
+            .import_from(&export_path, MergeStrategy::ErrorOnConflict)
+            .unwrap(),
+        2
+    );
+}
+
+#[test]
+fn test_fingerprint_store_upsert_and_list_sorted() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut store = FingerprintStore::new(temp_dir.path().join("fingerprints.json")).unwrap();
+
+    assert_eq!(store.upsert("laptop", "hash1").unwrap(), None);
+    assert_eq!(store.upsert("desktop", "hash2").unwrap(), None);
+    assert_eq!(
+        store.upsert("laptop", "hash3").unwrap(),
+        Some("hash1".to_string())
+    );
+
+    let listed: Vec<(&str, &str)> = store
+        .list_sorted()
+        .into_iter()
+        .map(|(machine, fp)| (machine, fp.hash()))
+        .collect();
+    assert_eq!(listed, [("desktop", "hash2"), ("laptop", "hash3")]);
+
+    let (_, laptop) = store.list_sorted()[1];
+    let first_seen = laptop.first_seen().unwrap();
+    assert!(chrono::Utc::now() - first_seen < chrono::Duration::seconds(60));
+    assert!(laptop.last_verified().is_some());
+}
+
+#[test]
+fn test_fingerprint_store_write_is_atomic() {
+    let temp_dir = TempDir::new().unwrap();
+    let store_path = temp_dir.path().join("fingerprints.json");
+    let mut store = FingerprintStore::new(store_path.clone()).unwrap();
+    store
+        .save("laptop", Fingerprint::new("laptop", "hash0"))
+        .unwrap();
+
+    // Readers never see a half-written store while another thread saves
+    let writer = std::thread::spawn(move || {
+        for i in 1..=200 {
+            let hash = format!("hash{}", i);
+            store
+                .save("laptop", Fingerprint::new("laptop", &hash))
+                .unwrap();
+        }
+    });
+    while !writer.is_finished() {
+        let loaded = FingerprintStore::new(store_path.clone()).unwrap();
+        assert!(loaded.get("laptop").is_some());
+    }
+    writer.join().unwrap();
+
+    // A process killed while writing leaves a truncated temporary file behind
+    let tmp_path = temp_dir.path().join("fingerprints.json.tmp");
+    std::fs::write(&tmp_path, r#"{"laptop": {"machine_na"#).unwrap();
+    let mut store = FingerprintStore::new(store_path.clone()).unwrap();
+    assert_eq!(store.get("laptop").unwrap().hash(), "hash200");
+
+    store
+        .save("desktop", Fingerprint::new("desktop", "hash"))
+        .unwrap();
+    assert!(!tmp_path.exists());
+    let store = FingerprintStore::new(store_path).unwrap();
+    assert_eq!(store.get("desktop").unwrap().hash(), "hash");
+}
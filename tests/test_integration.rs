@@ -111,33 +111,22 @@ async fn test_integration_config_to_topology() {
         host_address: None,
         tls: multishiva::core::config::TlsConfig {
             psk: "test-psk".to_string(),
+            psk_file: None,
         },
         edges: {
             let mut edges = std::collections::HashMap::new();
-            edges.insert("right".to_string(), "agent1".to_string());
+            edges.insert("right".to_string(), "agent1".into());
             edges
         },
-        hotkeys: None,
-        behavior: None,
+        ..Default::default()
     };
 
     // Validate config
     config.validate().unwrap();
 
     // Convert config edges to topology
-    let mut topology = Topology::new();
-    topology.add_machine(config.self_name.clone(), Position { x: 0, y: 0 });
-
-    for (direction, target) in &config.edges {
-        let edge = match direction.as_str() {
-            "right" => Edge::Right,
-            "left" => Edge::Left,
-            "top" => Edge::Top,
-            "bottom" => Edge::Bottom,
-            _ => continue,
-        };
-        topology.add_edge(config.self_name.clone(), edge, target.clone());
-    }
+    let topology = Topology::from_config(&config);
+    topology.validate().unwrap();
 
     // Verify topology
     let neighbor = topology.get_neighbor(&config.self_name, &Edge::Right);
@@ -219,8 +208,10 @@ async fn test_integration_event_serialization_over_network() {
             target: "agent1".to_string(),
             x: 50,
             y: 100,
+            host_width: 1920,
+            host_height: 1080,
         },
-        Event::Heartbeat,
+        Event::Heartbeat { sent_at_ms: 0 },
     ];
 
     for event in events {
@@ -351,14 +342,14 @@ async fn test_integration_complete_workflow() {
         host_address: None,
         tls: multishiva::core::config::TlsConfig {
             psk: "integration-test".to_string(),
+            psk_file: None,
         },
         edges: {
             let mut edges = std::collections::HashMap::new();
-            edges.insert("right".to_string(), "agent1".to_string());
+            edges.insert("right".to_string(), "agent1".into());
             edges
         },
-        hotkeys: None,
-        behavior: None,
+        ..Default::default()
     };
     config.validate().unwrap();
 
@@ -1,5 +1,5 @@
 use multishiva::core::events::Event;
-use multishiva::core::network::Network;
+use multishiva::core::network::{next_focus_hop, FocusHop, Network};
 use tokio::time::{sleep, Duration};
 
 #[tokio::test]
@@ -131,6 +131,8 @@ async fn test_network_multiple_agents() {
     let mut host_network = Network::new("shared-psk".to_string());
     let mut agent1 = Network::new("shared-psk".to_string());
     let mut agent2 = Network::new("shared-psk".to_string());
+    agent1.set_agent_info("agent1".to_string(), (1920, 1080));
+    agent2.set_agent_info("agent2".to_string(), (1920, 1080));
 
     // Start host
     let port = host_network.start_host(0, None).await.unwrap();
@@ -151,6 +153,628 @@ async fn test_network_multiple_agents() {
     agent2.stop().await;
 }
 
+#[tokio::test]
+async fn test_network_registers_agents_from_hello() {
+    let mut host_network = Network::new("shared-psk".to_string());
+    let mut agent1 = Network::new("shared-psk".to_string());
+    let mut agent2 = Network::new("shared-psk".to_string());
+    agent1.set_agent_info("agent1".to_string(), (2560, 1440));
+    agent2.set_agent_info("agent2".to_string(), (1280, 800));
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    sleep(Duration::from_millis(100)).await;
+
+    let addr = format!("127.0.0.1:{}", port);
+    agent1.connect_to_host(&addr).await.unwrap();
+    agent2.connect_to_host(&addr).await.unwrap();
+    sleep(Duration::from_millis(300)).await;
+
+    assert_eq!(host_network.connected_agents(), vec!["agent1", "agent2"]);
+    let state = host_network.agent_state("agent1").unwrap();
+    assert_eq!(state.screen_size, (2560, 1440));
+    assert!(!state.has_focus);
+
+    // Focus is tracked independently per agent
+    host_network.set_focused_agent(Some("agent2"));
+    assert_eq!(host_network.focused_agent().as_deref(), Some("agent2"));
+    assert!(!host_network.agent_state("agent1").unwrap().has_focus);
+    assert!(host_network.agent_state("agent2").unwrap().has_focus);
+
+    host_network.set_focused_agent(None);
+    assert_eq!(host_network.focused_agent(), None);
+
+    host_network.stop().await;
+    agent1.stop().await;
+    agent2.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_routes_events_to_addressed_agent() {
+    let mut host_network = Network::new("shared-psk".to_string());
+    let mut agent1 = Network::new("shared-psk".to_string());
+    let mut agent2 = Network::new("shared-psk".to_string());
+    agent1.set_agent_info("agent1".to_string(), (1920, 1080));
+    agent2.set_agent_info("agent2".to_string(), (1920, 1080));
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    let addr = format!("127.0.0.1:{}", port);
+    agent1.connect_to_host(&addr).await.unwrap();
+    agent2.connect_to_host(&addr).await.unwrap();
+    sleep(Duration::from_millis(300)).await;
+
+    // Input goes to the focused agent, whichever connection reads the queue
+    host_network.set_focused_agent(Some("agent2"));
+    host_network
+        .send_event(Event::MouseMove { x: 1, y: 2 })
+        .await
+        .unwrap();
+    let event = tokio::time::timeout(Duration::from_secs(5), agent2.receive_event())
+        .await
+        .expect("focused agent received nothing");
+    assert_eq!(event, Some(Event::MouseMove { x: 1, y: 2 }));
+
+    // A grant goes to its target, a direct send to the named agent
+    let grant = Event::FocusGrant {
+        target: "agent1".to_string(),
+        x: 10,
+        y: 500,
+        host_width: 1920,
+        host_height: 1080,
+    };
+    host_network.send_event(grant.clone()).await.unwrap();
+    host_network
+        .send_event_to("agent2", Event::FocusRelease)
+        .await
+        .unwrap();
+    let event = tokio::time::timeout(Duration::from_secs(5), agent1.receive_event())
+        .await
+        .expect("granted agent received nothing");
+    assert_eq!(event, Some(grant));
+    let event = tokio::time::timeout(Duration::from_secs(5), agent2.receive_event())
+        .await
+        .expect("released agent received nothing");
+    assert_eq!(event, Some(Event::FocusRelease));
+
+    assert!(host_network
+        .send_event_to("agent3", Event::FocusRelease)
+        .await
+        .is_err());
+
+    host_network.stop().await;
+    agent1.stop().await;
+    agent2.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_refuses_duplicate_agent_name() {
+    use multishiva::core::network::{ConnectionEvent, ConnectionStatus};
+
+    let mut host_network = Network::new("shared-psk".to_string());
+    let mut first = Network::new("shared-psk".to_string());
+    let mut second = Network::new("shared-psk".to_string());
+    first.set_agent_info("ubuntu".to_string(), (1920, 1080));
+    second.set_agent_info("ubuntu".to_string(), (1280, 800));
+    let mut second_events = second.subscribe();
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    let addr = format!("127.0.0.1:{}", port);
+    first.connect_to_host(&addr).await.unwrap();
+    sleep(Duration::from_millis(200)).await;
+    second.connect_to_host(&addr).await.unwrap();
+
+    // The second agent is told why it was refused
+    let reason = loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), second_events.recv())
+            .await
+            .expect("second agent was not refused")
+            .unwrap();
+        if let ConnectionEvent::Refused { reason, .. } = event {
+            break reason;
+        }
+    };
+    assert!(reason.contains("set self_name uniquely"));
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(second.connection_status(), ConnectionStatus::Disconnected);
+
+    // The first agent is still registered and receives events
+    let peers = host_network.connected_peers();
+    assert_eq!(peers.len(), 1);
+    assert_eq!(peers[0].name, "ubuntu");
+    assert_eq!(
+        host_network.agent_state("ubuntu").unwrap().screen_size,
+        (1920, 1080)
+    );
+    assert!(first.is_connected());
+    host_network
+        .send_event(Event::MouseMove { x: 7, y: 8 })
+        .await
+        .unwrap();
+    let event = tokio::time::timeout(Duration::from_secs(5), first.receive_event())
+        .await
+        .expect("first agent stopped receiving events");
+    assert_eq!(event, Some(Event::MouseMove { x: 7, y: 8 }));
+
+    host_network.stop().await;
+    first.stop().await;
+    second.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_refuses_agent_with_host_name() {
+    use multishiva::core::network::ConnectionEvent;
+
+    let mut host_network = Network::new("shared-psk".to_string());
+    host_network.set_self_name(Some("desk".to_string()));
+    let mut agent = Network::new("shared-psk".to_string());
+    agent.set_agent_info("desk".to_string(), (1920, 1080));
+    let mut agent_events = agent.subscribe();
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    agent
+        .connect_to_host(&format!("127.0.0.1:{}", port))
+        .await
+        .unwrap();
+
+    let reason = loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), agent_events.recv())
+            .await
+            .expect("agent was not refused")
+            .unwrap();
+        if let ConnectionEvent::Refused { reason, .. } = event {
+            break reason;
+        }
+    };
+    assert!(reason.contains("connecting to itself"));
+    assert!(host_network.connected_peers().is_empty());
+
+    host_network.stop().await;
+    agent.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_throttles_mouse_moves_per_agent() {
+    use multishiva::core::network::NetworkConfig;
+
+    let config = NetworkConfig {
+        max_events_per_second: 5,
+        ..NetworkConfig::default()
+    };
+    let mut host_network = Network::with_config("shared-psk".to_string(), config);
+    let mut agent = Network::new("shared-psk".to_string());
+    agent.set_agent_info("laptop".to_string(), (1920, 1080));
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    agent
+        .connect_to_host(&format!("127.0.0.1:{}", port))
+        .await
+        .unwrap();
+    sleep(Duration::from_millis(200)).await;
+
+    for x in 0..20 {
+        host_network
+            .send_event(Event::MouseMove { x, y: 0 })
+            .await
+            .unwrap();
+    }
+    // Other events still go through once the moves are throttled
+    host_network
+        .send_event(Event::KeyPress {
+            key: multishiva::core::events::Key::KeyA,
+        })
+        .await
+        .unwrap();
+    let last = loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), agent.receive_event())
+            .await
+            .expect("agent stopped receiving events")
+            .unwrap();
+        if !matches!(event, Event::MouseMove { .. }) {
+            break event;
+        }
+    };
+    assert!(matches!(last, Event::KeyPress { .. }));
+
+    let peers = host_network.connected_peers();
+    assert_eq!(peers.len(), 1);
+    assert!(peers[0].events_dropped >= 10, "{:?}", peers[0]);
+    assert_eq!(peers[0].events_sent + peers[0].events_dropped, 21);
+    assert!(peers[0].bytes_sent > 0);
+
+    host_network.stop().await;
+    agent.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_metrics_count_framed_events() {
+    const EVENTS: u64 = 25;
+
+    let mut host_network = Network::new("shared-psk".to_string());
+    let mut agent = Network::new("shared-psk".to_string());
+    agent.set_agent_info("laptop".to_string(), (1920, 1080));
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    agent
+        .connect_to_host(&format!("127.0.0.1:{}", port))
+        .await
+        .unwrap();
+    sleep(Duration::from_millis(200)).await;
+    let (host_before, agent_before) = (host_network.metrics(), agent.metrics());
+
+    for x in 0..EVENTS {
+        host_network
+            .send_event(Event::MouseMove { x: x as i32, y: 0 })
+            .await
+            .unwrap();
+    }
+    for _ in 0..EVENTS {
+        tokio::time::timeout(Duration::from_secs(5), agent.receive_event())
+            .await
+            .expect("agent stopped receiving events")
+            .unwrap();
+    }
+
+    let (host_after, agent_after) = (host_network.metrics(), agent.metrics());
+    assert_eq!(
+        host_after.events_forwarded - host_before.events_forwarded,
+        EVENTS
+    );
+    assert_eq!(
+        agent_after.events_received - agent_before.events_received,
+        EVENTS
+    );
+    // Every frame is at least its 4-byte length and a non-empty payload
+    assert!(host_after.bytes_sent - host_before.bytes_sent > 5 * EVENTS);
+    assert!(agent_after.bytes_received - agent_before.bytes_received > 5 * EVENTS);
+
+    host_network.stop().await;
+    agent.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_queue_depth_drains_once_agent_connects() {
+    use multishiva::core::network::NetworkConfig;
+
+    let config = NetworkConfig {
+        channel_capacity: 2,
+        ..NetworkConfig::default()
+    };
+    let mut host_network = Network::with_config("shared-psk".to_string(), config);
+    let mut agent_network = Network::new("shared-psk".to_string());
+    agent_network.set_agent_info("agent1".to_string(), (1920, 1080));
+
+    // Nobody reads the queue yet
+    host_network.send_event(Event::FocusRelease).await.unwrap();
+    host_network
+        .send_event(Event::MouseMove { x: 1, y: 2 })
+        .await
+        .unwrap();
+    assert_eq!(host_network.queue_depth(), 2);
+    // A full queue makes senders wait, giving up does not count the event
+    assert!(tokio::time::timeout(
+        Duration::from_millis(100),
+        host_network.send_event(Event::FocusRelease)
+    )
+    .await
+    .is_err());
+    assert_eq!(host_network.queue_depth(), 2);
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    agent_network
+        .connect_to_host(&format!("127.0.0.1:{}", port))
+        .await
+        .unwrap();
+    for expected in [Event::FocusRelease, Event::MouseMove { x: 1, y: 2 }] {
+        let event = tokio::time::timeout(Duration::from_secs(3), agent_network.receive_event())
+            .await
+            .expect("queued event was not delivered")
+            .unwrap();
+        assert_eq!(event, expected);
+    }
+    assert_eq!(host_network.monitor().queue_depth(), 0);
+
+    host_network.stop().await;
+    agent_network.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_host_binds_to_configured_address() {
+    let mut host_network = Network::new("shared-psk".to_string());
+    let mut agent_network = Network::new("shared-psk".to_string());
+    agent_network.set_agent_info("agent1".to_string(), (1920, 1080));
+
+    host_network.set_bind_address(Some("127.0.0.1".parse().unwrap()));
+    let port = host_network.start_host(0, None).await.unwrap();
+    let local_addrs = host_network.local_addrs().to_vec();
+    assert_eq!(local_addrs.len(), 1);
+    assert_eq!(local_addrs[0].ip().to_string(), "127.0.0.1");
+    assert_eq!(local_addrs[0].port(), port);
+
+    agent_network
+        .connect_to_host(&format!("127.0.0.1:{}", port))
+        .await
+        .unwrap();
+    host_network.send_event(Event::FocusRelease).await.unwrap();
+    let event = tokio::time::timeout(Duration::from_secs(3), agent_network.receive_event())
+        .await
+        .expect("event was not delivered")
+        .unwrap();
+    assert_eq!(event, Event::FocusRelease);
+
+    host_network.stop().await;
+    agent_network.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_host_uses_peer_psk() {
+    // The agent's handshake carries its hostname
+    let agent_name = hostname::get().unwrap().into_string().unwrap();
+    let mut host_network = Network::new("shared-psk".to_string());
+    host_network.set_peer_psk(&agent_name, "agent-psk".to_string());
+    let port = host_network.start_host(0, None).await.unwrap();
+    let addr = format!("127.0.0.1:{}", port);
+
+    let shared_agent = Network::new("shared-psk".to_string());
+    assert!(shared_agent.connect_to_host(&addr).await.is_err());
+
+    // The agent looks the host's PSK up by its name
+    let mut agent_network = Network::new("other-psk".to_string());
+    agent_network.set_host_name(Some("peer-psk-host".to_string()));
+    agent_network.set_peer_psk("peer-psk-host", "agent-psk".to_string());
+    agent_network.set_agent_info("agent1".to_string(), (1920, 1080));
+    agent_network.connect_to_host(&addr).await.unwrap();
+    host_network.send_event(Event::FocusRelease).await.unwrap();
+    let event = tokio::time::timeout(Duration::from_secs(3), agent_network.receive_event())
+        .await
+        .expect("event was not delivered")
+        .unwrap();
+    assert_eq!(event, Event::FocusRelease);
+
+    host_network.stop().await;
+    agent_network.stop().await;
+    // Tests share the default fingerprint store: do not leave a fingerprint of
+    // this PSK behind for a later test
+    let mut store = multishiva::core::fingerprint::FingerprintStore::load_default().unwrap();
+    store.remove("peer-psk-host").unwrap();
+}
+
+#[tokio::test]
+async fn test_network_measures_agent_latency() {
+    let mut host_network = Network::new("shared-psk".to_string());
+    let mut agent_network = Network::new("shared-psk".to_string());
+    agent_network.set_agent_info("agent1".to_string(), (1920, 1080));
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    agent_network
+        .connect_to_host(&format!("127.0.0.1:{}", port))
+        .await
+        .unwrap();
+
+    // The first ping goes out as soon as the agent is registered
+    let mut latency = None;
+    for _ in 0..30 {
+        latency = host_network.agent_latency("agent1");
+        if latency.is_some() {
+            break;
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+    assert!(latency.unwrap() < Duration::from_secs(1));
+    assert_eq!(
+        host_network.monitor().connected_peers()[0].latency,
+        host_network.agent_latency("agent1")
+    );
+    assert_eq!(host_network.agent_latency("agent2"), None);
+    // Pongs are not events
+    assert_eq!(agent_network.events_forwarded(), 0);
+
+    host_network.stop().await;
+    agent_network.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_measures_heartbeat_latency() {
+    use multishiva::core::network::NetworkConfig;
+
+    let config = NetworkConfig {
+        heartbeat_interval: Duration::from_millis(50),
+        ..NetworkConfig::default()
+    };
+    let mut host_network = Network::with_config("shared-psk".to_string(), config);
+    let mut agent_network = Network::with_config("shared-psk".to_string(), config);
+    agent_network.set_agent_info("agent1".to_string(), (1920, 1080));
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    agent_network
+        .connect_to_host(&format!("127.0.0.1:{}", port))
+        .await
+        .unwrap();
+    host_network
+        .send_event(Event::MouseMove { x: 1, y: 2 })
+        .await
+        .unwrap();
+    assert_eq!(
+        agent_network.receive_event().await,
+        Some(Event::MouseMove { x: 1, y: 2 })
+    );
+    sleep(Duration::from_millis(300)).await;
+
+    // Both sides answer each other's heartbeats
+    for network in [&host_network, &agent_network] {
+        let snapshot = network.metrics_snapshot();
+        assert!(snapshot.latency_samples >= 2, "{:?}", snapshot);
+        assert!(snapshot.latency_max_ms.unwrap() < 1000);
+        assert!(snapshot.bytes_sent > 0 && snapshot.bytes_received > 0);
+    }
+    // Heartbeats and their answers are not counted as events
+    assert_eq!(host_network.metrics_snapshot().events_forwarded, 1);
+    assert_eq!(agent_network.events_forwarded(), 0);
+
+    host_network.stop().await;
+    agent_network.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_stop_sends_disconnect_after_queued_events() {
+    use multishiva::core::network::ConnectionStatus;
+
+    let mut host_network = Network::new("shared-psk".to_string());
+    let mut agent_network = Network::new("shared-psk".to_string());
+    agent_network.set_agent_info("agent1".to_string(), (1920, 1080));
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    agent_network
+        .connect_to_host(&format!("127.0.0.1:{}", port))
+        .await
+        .unwrap();
+    sleep(Duration::from_millis(200)).await;
+
+    // Queued right before the host stops, so it must be flushed first
+    host_network
+        .send_event(Event::MouseButtonPress {
+            button: multishiva::core::events::MouseButton::Left,
+        })
+        .await
+        .unwrap();
+    let started = tokio::time::Instant::now();
+    host_network.stop_with_reason("host shutting down").await;
+
+    let mut received = Vec::new();
+    while received.len() < 2 {
+        let event = tokio::time::timeout(Duration::from_secs(3), agent_network.receive_event())
+            .await
+            .expect("agent did not see the host leave")
+            .unwrap();
+        received.push(event);
+    }
+    assert_eq!(
+        received,
+        vec![
+            Event::MouseButtonPress {
+                button: multishiva::core::events::MouseButton::Left
+            },
+            Event::Disconnect {
+                reason: "host shutting down".to_string()
+            },
+        ]
+    );
+
+    // Well before the heartbeat timeout
+    while agent_network.connection_status() != ConnectionStatus::Disconnected {
+        assert!(started.elapsed() < Duration::from_secs(5));
+        sleep(Duration::from_millis(20)).await;
+    }
+    agent_network.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_agent_sees_clean_disconnect_event() {
+    use multishiva::core::network::ConnectionEvent;
+
+    let mut host_network = Network::new("shared-psk".to_string());
+    let mut agent_network = Network::new("shared-psk".to_string());
+    agent_network.set_agent_info("agent1".to_string(), (1920, 1080));
+    let mut agent_events = agent_network.subscribe();
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    let addr = format!("127.0.0.1:{}", port);
+    agent_network.connect_to_host(&addr).await.unwrap();
+    sleep(Duration::from_millis(200)).await;
+
+    host_network.stop_with_reason("host shutting down").await;
+    let mut events = Vec::new();
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(3), agent_events.recv())
+            .await
+            .expect("agent did not see the host leave")
+            .unwrap();
+        let done = matches!(event, ConnectionEvent::AgentDisconnected { .. });
+        events.push(event);
+        if done {
+            break;
+        }
+    }
+    assert_eq!(
+        events,
+        vec![
+            ConnectionEvent::AgentConnected { name: addr.clone() },
+            ConnectionEvent::PeerLeft {
+                name: addr.clone(),
+                reason: "host shutting down".to_string()
+            },
+            ConnectionEvent::AgentDisconnected { name: addr },
+        ]
+    );
+
+    agent_network.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_host_sees_agent_disconnect() {
+    let (input_tx, mut input_rx) = tokio::sync::mpsc::channel(16);
+    let mut host_network = Network::new("shared-psk".to_string());
+    let mut agent_network = Network::new("shared-psk".to_string());
+    agent_network.set_agent_info("agent1".to_string(), (1920, 1080));
+
+    let port = host_network.start_host(0, Some(input_tx)).await.unwrap();
+    agent_network
+        .connect_to_host(&format!("127.0.0.1:{}", port))
+        .await
+        .unwrap();
+    sleep(Duration::from_millis(200)).await;
+    assert_eq!(host_network.connected_agents(), vec!["agent1"]);
+
+    agent_network.stop_with_reason("agent shutting down").await;
+    let event = tokio::time::timeout(Duration::from_secs(3), input_rx.recv())
+        .await
+        .expect("host did not see the agent leave")
+        .unwrap();
+    assert_eq!(
+        event,
+        Event::Disconnect {
+            reason: "agent shutting down".to_string()
+        }
+    );
+    assert!(host_network.connected_agents().is_empty());
+
+    host_network.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_emits_agent_connected_event() {
+    use multishiva::core::network::ConnectionEvent;
+
+    let mut host_network = Network::new("shared-psk".to_string());
+    let mut agent_network = Network::new("shared-psk".to_string());
+    agent_network.set_agent_info("agent1".to_string(), (1920, 1080));
+    let mut host_events = host_network.subscribe();
+    let mut agent_events = agent_network.subscribe();
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    sleep(Duration::from_millis(100)).await;
+    let addr = format!("127.0.0.1:{}", port);
+    agent_network.connect_to_host(&addr).await.unwrap();
+
+    let event = tokio::time::timeout(Duration::from_secs(5), host_events.recv())
+        .await
+        .expect("no connection event on host")
+        .unwrap();
+    assert_eq!(
+        event,
+        ConnectionEvent::AgentConnected {
+            name: "agent1".to_string()
+        }
+    );
+
+    let event = tokio::time::timeout(Duration::from_secs(5), agent_events.recv())
+        .await
+        .expect("no connection event on agent")
+        .unwrap();
+    assert_eq!(event, ConnectionEvent::AgentConnected { name: addr });
+
+    host_network.stop().await;
+    agent_network.stop().await;
+}
+
 #[tokio::test]
 async fn test_network_connection_timeout() {
     let agent_network = Network::new("shared-psk".to_string());
@@ -174,3 +798,320 @@ async fn test_network_get_connection_count() {
 
     host_network.stop().await;
 }
+
+#[tokio::test]
+async fn test_network_agent_detects_silent_host() {
+    use multishiva::core::network::ConnectionStatus;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // A host that accepts the handshake and then hangs without closing the socket
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let silent_host = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 256];
+        let _ = stream.read(&mut buf).await.unwrap();
+        stream.write_all(b"OK").await.unwrap();
+        sleep(Duration::from_secs(60)).await;
+        drop(stream);
+    });
+
+    let agent_network = Network::new("shared-psk".to_string());
+    agent_network
+        .connect_to_host(&format!("127.0.0.1:{}", port))
+        .await
+        .unwrap();
+    assert!(matches!(
+        agent_network.connection_status(),
+        ConnectionStatus::Connected { .. }
+    ));
+
+    let start = tokio::time::Instant::now();
+    while agent_network.connection_status() != ConnectionStatus::Disconnected {
+        assert!(
+            start.elapsed() < Duration::from_secs(20),
+            "agent did not detect the silent host"
+        );
+        sleep(Duration::from_millis(250)).await;
+    }
+    assert!(!agent_network.is_connected());
+
+    silent_host.abort();
+}
+
+/// Connects to the host on `port` as `name` by hand, stopping after the Hello.
+async fn connect_raw_agent(port: u16, name: &str) -> tokio::net::TcpStream {
+    use multishiva::core::network::compute_psk_hash;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port))
+        .await
+        .unwrap();
+    let mut handshake = b"MULTISHIVA_PSK_V1".to_vec();
+    handshake.extend_from_slice(name.as_bytes());
+    handshake.push(0);
+    handshake.extend_from_slice(compute_psk_hash("shared-psk").as_bytes());
+    stream.write_all(&handshake).await.unwrap();
+    let mut ack = [0u8; 2];
+    stream.read_exact(&mut ack).await.unwrap();
+    assert_eq!(&ack, b"OK");
+
+    let hello = rmp_serde::to_vec(&Event::Hello {
+        name: name.to_string(),
+        screen_width: 1920,
+        screen_height: 1080,
+    })
+    .unwrap();
+    stream
+        .write_all(&(hello.len() as u32).to_be_bytes())
+        .await
+        .unwrap();
+    stream.write_all(&hello).await.unwrap();
+    stream
+}
+
+#[tokio::test]
+async fn test_network_host_drops_silent_agent() {
+    use multishiva::core::network::{ConnectionEvent, NetworkConfig};
+
+    let config = NetworkConfig {
+        agent_timeout: Duration::from_millis(500),
+        ..NetworkConfig::default()
+    };
+    let (input_tx, mut input_rx) = tokio::sync::mpsc::channel(16);
+    let mut host_network = Network::with_config("shared-psk".to_string(), config);
+    let mut host_events = host_network.subscribe();
+    let port = host_network.start_host(0, Some(input_tx)).await.unwrap();
+
+    // An agent that says hello and then hangs without closing the socket
+    let stream = connect_raw_agent(port, "silent").await;
+
+    let event = tokio::time::timeout(Duration::from_secs(3), input_rx.recv())
+        .await
+        .expect("host did not drop the silent agent")
+        .unwrap();
+    assert_eq!(
+        event,
+        Event::Disconnect {
+            reason: "timed out".to_string()
+        }
+    );
+    assert!(host_network.connected_agents().is_empty());
+
+    let mut timed_out = false;
+    while let Ok(event) = host_events.try_recv() {
+        timed_out |= event
+            == ConnectionEvent::HeartbeatTimeout {
+                name: "silent".to_string(),
+            };
+    }
+    assert!(timed_out);
+
+    drop(stream);
+    host_network.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_input_events_overtake_bulk_flood() {
+    use multishiva::core::network::{NetworkConfig, SendError};
+    use tokio::io::AsyncReadExt;
+
+    let config = NetworkConfig {
+        bulk_capacity: 4,
+        ..NetworkConfig::default()
+    };
+    let mut host_network = Network::with_config("shared-psk".to_string(), config);
+    let port = host_network.start_host(0, None).await.unwrap();
+
+    // Flood the bulk lane with large clipboard events before any agent reads it
+    let sync = Event::ClipboardSync {
+        source: "host".to_string(),
+        text: "x".repeat(60_000),
+    };
+    for _ in 0..4 {
+        host_network.send_event(sync.clone()).await.unwrap();
+    }
+    let error = host_network.send_event(sync.clone()).await.unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<SendError>(),
+        Some(&SendError::BulkLaneFull { capacity: 4 })
+    );
+    host_network
+        .send_event(Event::MouseMove { x: 10, y: 20 })
+        .await
+        .unwrap();
+    host_network
+        .send_event(Event::KeyPress {
+            key: multishiva::core::events::Key::KeyA,
+        })
+        .await
+        .unwrap();
+    assert_eq!(host_network.queue_depth(), 6);
+    assert_eq!(host_network.bulk_queue_depth(), 4);
+
+    // The input events are written before the clipboard, however they were queued
+    let mut stream = connect_raw_agent(port, "reader").await;
+    let mut events = Vec::new();
+    while events.len() < 3 {
+        let mut len_buf = [0u8; 4];
+        tokio::time::timeout(Duration::from_secs(3), stream.read_exact(&mut len_buf))
+            .await
+            .expect("host stopped writing")
+            .unwrap();
+        let mut data = vec![0u8; u32::from_be_bytes(len_buf) as usize];
+        stream.read_exact(&mut data).await.unwrap();
+        match rmp_serde::from_slice::<Event>(&data).unwrap() {
+            Event::Heartbeat { .. } | Event::Ping { .. } => {}
+            event => events.push(event),
+        }
+    }
+    assert_eq!(events[0], Event::MouseMove { x: 10, y: 20 });
+    assert!(matches!(events[1], Event::KeyPress { .. }));
+    assert!(events[2].is_bulk());
+
+    drop(stream);
+    host_network.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_rotates_psk_without_reconnecting() {
+    let mut host_network = Network::new("old-psk".to_string());
+    let agent_network = Network::new("old-psk".to_string());
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    let addr = format!("127.0.0.1:{}", port);
+    agent_network.connect_to_host(&addr).await.unwrap();
+    sleep(Duration::from_millis(100)).await;
+
+    // The agent learns the new key first, then the host announces it
+    agent_network.rotate_psk("new-psk".to_string()).unwrap();
+    host_network.rotate_psk("new-psk".to_string()).unwrap();
+
+    let rotation = host_network.psk_rotation();
+    let start = tokio::time::Instant::now();
+    while rotation.is_pending() {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "agent did not acknowledge the new PSK"
+        );
+        sleep(Duration::from_millis(50)).await;
+    }
+    assert!(!agent_network.psk_rotation().is_pending());
+    assert!(agent_network.is_connected());
+
+    // New connections need the new key
+    let stale_agent = Network::new("old-psk".to_string());
+    assert!(stale_agent.connect_to_host(&addr).await.is_err());
+
+    host_network.stop().await;
+}
+
+#[test]
+fn test_next_focus_hop_along_chain() {
+    let path: Vec<String> = ["host", "agent1", "agent2"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+    assert_eq!(
+        next_focus_hop(&path, "agent1"),
+        FocusHop::Relay("agent2".to_string())
+    );
+    assert_eq!(next_focus_hop(&path, "agent2"), FocusHop::Target);
+    assert_eq!(next_focus_hop(&path, "agent3"), FocusHop::NotOnPath);
+}
+
+#[tokio::test]
+async fn test_transfer_focus_chain_rejects_short_path() {
+    let network = Network::new("test-psk".to_string());
+    assert!(network
+        .transfer_focus_chain(vec!["agent1".to_string()], 0, 0)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_connection_checks_psk_without_registering() {
+    use multishiva::core::network::test_connection;
+
+    let mut host_network = Network::new("shared-psk".to_string());
+    let port = host_network.start_host(0, None).await.unwrap();
+    let addr = format!("127.0.0.1:{}", port);
+
+    let check = test_connection(&addr, "shared-psk", Duration::from_secs(2))
+        .await
+        .unwrap();
+    assert_eq!(check.address, addr);
+
+    let err = test_connection(&addr, "wrong-psk", Duration::from_secs(2))
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("PSK handshake failed"));
+
+    sleep(Duration::from_millis(100)).await;
+    assert!(host_network.connected_agents().is_empty());
+    host_network.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_allowlist_refuses_unlisted_agent() {
+    use multishiva::core::network::{AgentAllowlist, ConnectionEvent};
+
+    let mut host_network = Network::new("shared-psk".to_string());
+    host_network.set_allowed_agents(Some(AgentAllowlist::new(&["laptop"]).unwrap()));
+    let mut laptop = Network::new("shared-psk".to_string());
+    let mut intruder = Network::new("shared-psk".to_string());
+    laptop.set_agent_info("laptop".to_string(), (1920, 1080));
+    intruder.set_agent_info("intruder".to_string(), (1920, 1080));
+    let mut intruder_events = intruder.subscribe();
+
+    let port = host_network.start_host(0, None).await.unwrap();
+    let addr = format!("127.0.0.1:{}", port);
+    laptop.connect_to_host(&addr).await.unwrap();
+    intruder.connect_to_host(&addr).await.unwrap();
+
+    let reason = loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), intruder_events.recv())
+            .await
+            .expect("unlisted agent was not refused")
+            .unwrap();
+        if let ConnectionEvent::Refused { reason, .. } = event {
+            break reason;
+        }
+    };
+    assert!(reason.contains("allowed_agents"));
+
+    let peers = host_network.connected_peers();
+    assert_eq!(peers.len(), 1);
+    assert_eq!(peers[0].name, "laptop");
+
+    host_network.stop().await;
+    laptop.stop().await;
+    intruder.stop().await;
+}
+
+#[tokio::test]
+async fn test_network_allowlist_checks_address_on_accept() {
+    use multishiva::core::network::AgentAllowlist;
+
+    let mut host_network = Network::new("shared-psk".to_string());
+    host_network.set_allowed_agents(Some(AgentAllowlist::new(&["10.0.0.0/8"]).unwrap()));
+    let port = host_network.start_host(0, None).await.unwrap();
+    let addr = format!("127.0.0.1:{}", port);
+
+    // Dropped before the handshake
+    let agent = Network::new("shared-psk".to_string());
+    assert!(agent.connect_to_host(&addr).await.is_err());
+
+    // Any name is let in from an allowed address
+    host_network.set_allowed_agents(Some(AgentAllowlist::new(&["127.0.0.0/8"]).unwrap()));
+    let mut agent = Network::new("shared-psk".to_string());
+    agent.set_agent_info("agent1".to_string(), (1920, 1080));
+    agent.connect_to_host(&addr).await.unwrap();
+    sleep(Duration::from_millis(200)).await;
+    assert_eq!(host_network.connected_peers().len(), 1);
+
+    host_network.stop().await;
+    agent.stop().await;
+}
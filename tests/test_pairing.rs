@@ -0,0 +1,141 @@
+use multishiva::core::config::{Config, ConfigMode};
+use multishiva::core::discovery::{PeerInfo, MODE_PROPERTY};
+use multishiva::core::network::Network;
+use multishiva::core::pairing::{
+    pair_with_host, PairingCode, PairingError, PairingHost, PairingOffer, MAX_FAILED_ATTEMPTS,
+    PAIRING_CODE_TTL, PAIRING_MODE,
+};
+use std::time::{Duration, Instant};
+
+fn offer(port: u16) -> PairingOffer {
+    PairingOffer {
+        host_name: "desk".to_string(),
+        port,
+        psk: "the-real-psk".to_string(),
+    }
+}
+
+async fn pairing_host(ttl: Duration) -> (PairingHost, String, String) {
+    let code = PairingCode::generate(ttl).unwrap();
+    let typed = code.as_str().to_string();
+    let host = PairingHost::bind("127.0.0.1:0".parse().unwrap(), offer(53421), code)
+        .await
+        .unwrap();
+    let address = host.local_addr().unwrap().to_string();
+    (host, address, typed)
+}
+
+/// Any code but `code`
+fn wrong_code(code: &str) -> String {
+    format!("{:06}", (code.parse::<u32>().unwrap() + 1) % 1_000_000)
+}
+
+fn pairing_error(error: &anyhow::Error) -> Option<PairingError> {
+    error.downcast_ref::<PairingError>().copied()
+}
+
+#[tokio::test]
+async fn test_pairing_configures_agent_that_connects() {
+    let mut host_network = Network::new("the-real-psk".to_string());
+    let host_port = host_network.start_host(0, None).await.unwrap();
+
+    let code = PairingCode::generate(PAIRING_CODE_TTL).unwrap();
+    let typed = code.to_string();
+    let host = PairingHost::bind("127.0.0.1:0".parse().unwrap(), offer(host_port), code)
+        .await
+        .unwrap();
+    // What mDNS would have found
+    let mut peer = PeerInfo::new(
+        "desk-pairing".to_string(),
+        "127.0.0.1".parse().unwrap(),
+        host.local_addr().unwrap().port(),
+    );
+    peer.properties = PairingHost::properties();
+    assert_eq!(peer.mode(), Some(PAIRING_MODE));
+    assert_eq!(peer.properties[MODE_PROPERTY], PAIRING_MODE);
+    let serving = tokio::spawn(host.serve());
+
+    let paired = pair_with_host(&peer.full_address(), &typed, "laptop")
+        .await
+        .unwrap();
+    assert_eq!(paired.offer, offer(host_port));
+    assert_eq!(
+        paired.address.to_string(),
+        format!("127.0.0.1:{}", host_port)
+    );
+    let agent = serving.await.unwrap().unwrap();
+    assert_eq!(agent.name, "laptop");
+
+    // The code cannot be used again
+    assert!(pair_with_host(&peer.full_address(), &typed, "other")
+        .await
+        .is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("agent.yml");
+    let mut config = Config {
+        self_name: "laptop".to_string(),
+        ..Config::default()
+    };
+    paired.configure(&mut config, paired.offer.psk.clone());
+    config.save_to_file(&path).unwrap();
+    let config = Config::from_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(config.mode, ConfigMode::Agent);
+    assert_eq!(config.self_name, "laptop");
+
+    let agent_network = Network::new(config.tls.psk.clone());
+    agent_network
+        .connect_to_host(config.host_address.as_deref().unwrap())
+        .await
+        .unwrap();
+    host_network.stop().await;
+}
+
+#[tokio::test]
+async fn test_pairing_wrong_code_is_rate_limited() {
+    let (mut host, address, code) = pairing_host(PAIRING_CODE_TTL).await;
+    host.set_failure_delay(Duration::from_millis(300));
+    let serving = tokio::spawn(host.serve());
+
+    let error = pair_with_host(&address, &wrong_code(&code), "laptop")
+        .await
+        .unwrap_err();
+    assert_eq!(pairing_error(&error), Some(PairingError::Rejected));
+
+    // The next attempt waits for the pause, then the right code still works
+    let started = Instant::now();
+    let paired = pair_with_host(&address, &code, "laptop").await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(250));
+    assert_eq!(paired.offer.psk, "the-real-psk");
+    assert_eq!(serving.await.unwrap().unwrap().name, "laptop");
+}
+
+#[tokio::test]
+async fn test_pairing_code_revoked_after_failed_attempts() {
+    let (mut host, address, code) = pairing_host(PAIRING_CODE_TTL).await;
+    host.set_failure_delay(Duration::ZERO);
+    let serving = tokio::spawn(host.serve());
+
+    for _ in 0..MAX_FAILED_ATTEMPTS {
+        let error = pair_with_host(&address, &wrong_code(&code), "intruder")
+            .await
+            .unwrap_err();
+        assert_eq!(pairing_error(&error), Some(PairingError::Rejected));
+    }
+    let error = serving.await.unwrap().unwrap_err();
+    assert_eq!(
+        pairing_error(&error),
+        Some(PairingError::TooManyAttempts {
+            attempts: MAX_FAILED_ATTEMPTS
+        })
+    );
+    assert!(pair_with_host(&address, &code, "laptop").await.is_err());
+}
+
+#[tokio::test]
+async fn test_pairing_code_expires() {
+    let (host, address, code) = pairing_host(Duration::from_millis(100)).await;
+    let error = host.serve().await.unwrap_err();
+    assert_eq!(pairing_error(&error), Some(PairingError::Expired));
+    assert!(pair_with_host(&address, &code, "laptop").await.is_err());
+}
@@ -1,5 +1,9 @@
-use multishiva::core::events::Event;
-use multishiva::core::simulation::{SimulationMode, VirtualMachine};
+use multishiva::core::event_recorder::{EventRecorder, EventReplayer};
+use multishiva::core::events::{Event, Key, MouseButton};
+use multishiva::core::scenario::{Scenario, ScenarioRunner};
+use multishiva::core::simulation::{ScenarioBuilder, SimStep, SimulationMode, VirtualMachine};
+use multishiva::core::topology::{Edge, Position, Topology};
+use std::collections::HashMap;
 use tokio::time::Duration;
 
 #[tokio::test]
@@ -40,6 +44,27 @@ async fn test_virtual_machine_event_injection() {
     assert_eq!(y, 300);
 }
 
+#[tokio::test]
+async fn test_virtual_machine_relative_motion() {
+    let mut vm = VirtualMachine::new("test-vm".to_string(), 1920, 1080);
+    vm.set_cursor_position(100, 100);
+
+    vm.inject_event(Event::MouseMoveRelative { dx: 25, dy: -40 })
+        .await
+        .unwrap();
+    assert_eq!(vm.cursor_position(), (125, 60));
+
+    // Relative motion keeps working at the screen edge but stays on screen
+    vm.inject_event(Event::MouseMoveRelative { dx: -500, dy: 0 })
+        .await
+        .unwrap();
+    assert_eq!(vm.cursor_position(), (0, 60));
+    vm.inject_event(Event::MouseMoveRelative { dx: 30, dy: 0 })
+        .await
+        .unwrap();
+    assert_eq!(vm.cursor_position(), (30, 60));
+}
+
 #[tokio::test]
 async fn test_virtual_machine_event_recording() {
     let mut vm = VirtualMachine::new("test-vm".to_string(), 1920, 1080);
@@ -240,3 +265,710 @@ async fn test_simulation_mode_statistics() {
     let stats = sim.get_statistics();
     assert_eq!(stats.total_events_sent, 10);
 }
+
+fn scenario_runner() -> ScenarioRunner {
+    let mut sim = SimulationMode::new();
+    sim.add_virtual_machine("host".to_string(), 1920, 1080);
+    sim.add_virtual_machine("agent1".to_string(), 1920, 1080);
+
+    let mut topology = Topology::new();
+    topology.add_machine("host".to_string(), Position { x: 0, y: 0 });
+    topology.add_machine("agent1".to_string(), Position { x: 1, y: 0 });
+    topology.add_edge("host".to_string(), Edge::Right, "agent1".to_string());
+
+    ScenarioRunner::new(sim, topology, "host".to_string())
+}
+
+#[tokio::test]
+async fn test_scenario_transfers_focus_across_edge() {
+    let scenario = Scenario::from_yaml(
+        r#"
+steps:
+  - at_ms: 0
+    machine: host
+    event: {type: mouse_move, x: 1000, y: 500}
+  - at_ms: 10
+    machine: host
+    event: {type: mouse_move, x: 1915, y: 500}
+  - at_ms: 20
+    machine: agent1
+    event: {type: mouse_move, x: 300, y: 400}
+expect:
+  final_focus: agent1
+  focus_transfers: 1
+"#,
+    )
+    .unwrap();
+
+    let mut runner = scenario_runner();
+    let report = runner.run(&scenario).await.unwrap();
+    assert!(report.passed(), "{}", report);
+
+    let host = &report.machines[1];
+    assert_eq!(host.name, "host");
+    assert_eq!(host.events_received, 2);
+    assert_eq!(host.focus_transfers, 1);
+
+    // The focus grant counts as an event received by the agent
+    let agent = &report.machines[0];
+    assert_eq!(agent.name, "agent1");
+    assert_eq!(agent.events_received, 2);
+    assert_eq!(agent.cursor, (300, 400));
+}
+
+#[tokio::test]
+async fn test_scenario_friction_requires_dwell() {
+    let scenario = Scenario::from_yaml(
+        r#"
+steps:
+  - at_ms: 0
+    machine: host
+    event: {type: mouse_move, x: 1915, y: 500}
+  - at_ms: 20
+    machine: host
+    event: {type: mouse_move, x: 1000, y: 500}
+expect:
+  final_focus: agent1
+"#,
+    )
+    .unwrap();
+
+    let mut runner = scenario_runner();
+    runner.set_friction_ms(50);
+    let report = runner.run(&scenario).await.unwrap();
+
+    // The cursor left the edge before the dwell time elapsed
+    assert!(!report.passed());
+    assert_eq!(report.final_focus, "host");
+    assert_eq!(report.failures.len(), 1);
+}
+
+#[tokio::test]
+async fn test_scenario_unknown_machine_fails() {
+    let scenario = Scenario::from_yaml(
+        r#"{"steps": [{"at_ms": 0, "machine": "ghost", "event": {"type": "mouse_move", "x": 1, "y": 1}}]}"#,
+    )
+    .unwrap();
+
+    let mut runner = scenario_runner();
+    assert!(runner.run(&scenario).await.is_err());
+}
+
+#[tokio::test]
+async fn test_session_export_and_replay() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.json");
+
+    let mut sim = SimulationMode::new();
+    sim.add_virtual_machine("host".to_string(), 1920, 1080);
+    sim.add_virtual_machine("agent1".to_string(), 2560, 1440);
+    sim.send_event_to("host", Event::MouseMove { x: 1900, y: 500 })
+        .await
+        .unwrap();
+    sim.send_event_to("host", Event::MouseMove { x: 1919, y: 510 })
+        .await
+        .unwrap();
+    sim.send_event_to(
+        "agent1",
+        Event::FocusGrant {
+            target: "agent1".to_string(),
+            x: 0,
+            y: 680,
+            host_width: 1920,
+            host_height: 1080,
+        },
+    )
+    .await
+    .unwrap();
+    sim.send_event_to("agent1", Event::MouseMove { x: 40, y: 700 })
+        .await
+        .unwrap();
+    sim.export_session(&path).unwrap();
+
+    let expected: Vec<(String, (i32, i32))> = ["host", "agent1"]
+        .iter()
+        .map(|name| {
+            let vm = sim.get_virtual_machine(name).unwrap();
+            (name.to_string(), vm.cursor_position())
+        })
+        .collect();
+    for name in ["host", "agent1"] {
+        sim.get_virtual_machine_mut(name).unwrap().clear_events();
+    }
+    assert!(sim.session().events.is_empty());
+
+    let mut replay = SimulationMode::new();
+    replay.set_network_latency(1);
+    assert_eq!(replay.replay_session(&path).await.unwrap(), 4);
+    for (name, position) in expected {
+        let vm = replay.get_virtual_machine(&name).unwrap();
+        assert_eq!(vm.cursor_position(), position);
+    }
+    assert_eq!(
+        replay.get_virtual_machine("agent1").unwrap().screen_size(),
+        (2560, 1440)
+    );
+    assert_eq!(replay.get_statistics().total_events_sent, 4);
+}
+
+/// A host surrounded by one agent on each edge.
+fn surrounded_host() -> SimulationMode {
+    let mut sim = SimulationMode::new();
+    sim.add_virtual_machine("host".to_string(), 1920, 1080);
+    let mut edges = HashMap::new();
+    for edge in ["left", "right", "top", "bottom"] {
+        sim.add_virtual_machine(edge.to_string(), 1920, 1080);
+        edges.insert(edge.to_string(), edge.to_string());
+    }
+    sim.set_edges("host", edges);
+    sim
+}
+
+async fn assert_cursor_path_grants(path: &[(i32, i32)], target: &str, entry: (i32, i32)) {
+    let mut sim = surrounded_host();
+    let focus = sim.run_cursor_path("host", path).await.unwrap();
+    assert_eq!(focus, target);
+
+    let vm = sim.get_virtual_machine(target).unwrap();
+    assert_eq!(
+        vm.recorded_events(),
+        &[Event::FocusGrant {
+            target: target.to_string(),
+            x: entry.0,
+            y: entry.1,
+            host_width: 1920,
+            host_height: 1080,
+        }]
+    );
+    assert_eq!(vm.cursor_position(), entry);
+
+    // Only the neighbor on the crossed edge heard about it
+    for other in ["left", "right", "top", "bottom"] {
+        if other != target {
+            assert!(sim
+                .get_virtual_machine(other)
+                .unwrap()
+                .recorded_events()
+                .is_empty());
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_cursor_path_crosses_left_edge() {
+    assert_cursor_path_grants(&[(960, 300), (400, 300), (2, 300)], "left", (1909, 300)).await;
+}
+
+#[tokio::test]
+async fn test_cursor_path_crosses_right_edge() {
+    assert_cursor_path_grants(&[(960, 700), (1700, 700), (1915, 700)], "right", (10, 700)).await;
+}
+
+#[tokio::test]
+async fn test_cursor_path_crosses_top_edge() {
+    assert_cursor_path_grants(&[(960, 540), (1200, 200), (1200, 4)], "top", (1200, 1069)).await;
+}
+
+#[tokio::test]
+async fn test_cursor_path_crosses_bottom_edge() {
+    assert_cursor_path_grants(&[(960, 540), (500, 900), (500, 1075)], "bottom", (500, 10)).await;
+}
+
+#[tokio::test]
+async fn test_cursor_path_continues_on_neighbor() {
+    let mut sim = SimulationMode::new();
+    for name in ["host", "agent1", "agent2"] {
+        sim.add_virtual_machine(name.to_string(), 1920, 1080);
+    }
+    sim.set_edges(
+        "host",
+        HashMap::from([("right".to_string(), "agent1".to_string())]),
+    );
+    sim.set_edges(
+        "agent1",
+        HashMap::from([("right".to_string(), "agent2".to_string())]),
+    );
+
+    let focus = sim
+        .run_cursor_path("host", &[(1915, 500), (900, 450), (1915, 450)])
+        .await
+        .unwrap();
+    assert_eq!(focus, "agent2");
+    assert_eq!(
+        sim.get_virtual_machine("agent2").unwrap().cursor_position(),
+        (10, 450)
+    );
+}
+
+#[tokio::test]
+async fn test_hub_relays_focus_between_agents_and_back() {
+    let mut sim = SimulationMode::new();
+    for name in ["host", "agent1", "agent2"] {
+        sim.add_virtual_machine(name.to_string(), 1920, 1080);
+    }
+    sim.set_edges(
+        "host",
+        HashMap::from([("right".to_string(), "agent1".to_string())]),
+    );
+    sim.set_edges(
+        "agent1",
+        HashMap::from([
+            ("left".to_string(), "host".to_string()),
+            ("right".to_string(), "agent2".to_string()),
+        ]),
+    );
+    sim.set_edges(
+        "agent2",
+        HashMap::from([("left".to_string(), "agent1".to_string())]),
+    );
+    sim.set_hub("host");
+
+    let mut visited = vec!["host".to_string()];
+    let mut focus = "host".to_string();
+    for &(x, y) in &[
+        (1915, 500),
+        (960, 500),
+        (1915, 500),
+        (960, 500),
+        (5, 500),
+        (960, 500),
+        (5, 500),
+    ] {
+        if let Some(target) = sim.move_cursor(&focus, x, y).await.unwrap() {
+            visited.push(target.clone());
+            focus = target;
+        }
+    }
+    assert_eq!(visited, ["host", "agent1", "agent2", "agent1", "host"]);
+
+    // Each agent crossing was reported to the host, which released the agent
+    let host = sim.get_virtual_machine("host").unwrap();
+    let reached: Vec<Edge> = host
+        .recorded_events()
+        .iter()
+        .filter_map(|event| match event {
+            Event::EdgeReached { edge, .. } => Some(*edge),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(reached, [Edge::Right, Edge::Left, Edge::Left]);
+    assert!(!host
+        .recorded_events()
+        .iter()
+        .any(|event| matches!(event, Event::FocusGrant { .. })));
+
+    let focus_events = |name: &str| -> Vec<&'static str> {
+        sim.get_virtual_machine(name)
+            .unwrap()
+            .recorded_events()
+            .iter()
+            .filter_map(|event| match event {
+                Event::FocusGrant { .. } => Some("grant"),
+                Event::FocusRelease => Some("release"),
+                _ => None,
+            })
+            .collect()
+    };
+    assert_eq!(
+        focus_events("agent1"),
+        ["grant", "release", "grant", "release"]
+    );
+    assert_eq!(focus_events("agent2"), ["grant", "release"]);
+}
+
+#[tokio::test]
+async fn test_kill_switch_walks_back_a_relayed_chain() {
+    let mut sim = SimulationMode::new();
+    for name in ["host", "agent1", "agent2"] {
+        sim.add_virtual_machine(name.to_string(), 1920, 1080);
+    }
+    sim.set_edges(
+        "host",
+        HashMap::from([("right".to_string(), "agent1".to_string())]),
+    );
+    sim.set_edges(
+        "agent1",
+        HashMap::from([("right".to_string(), "agent2".to_string())]),
+    );
+    sim.set_hub("host");
+
+    let focus = sim
+        .run_cursor_path("host", &[(1915, 500), (960, 500), (1915, 600)])
+        .await
+        .unwrap();
+    assert_eq!(focus, "agent2");
+
+    let releases = |sim: &SimulationMode, name: &str| {
+        sim.get_virtual_machine(name)
+            .unwrap()
+            .recorded_events()
+            .iter()
+            .filter(|event| matches!(event, Event::FocusRelease))
+            .count()
+    };
+    let grants = |sim: &SimulationMode, name: &str| {
+        sim.get_virtual_machine(name)
+            .unwrap()
+            .recorded_events()
+            .iter()
+            .filter(|event| matches!(event, Event::FocusGrant { .. }))
+            .count()
+    };
+
+    // First press: agent2 is released and agent1 gets focus back where it left
+    assert_eq!(sim.press_kill_switch("agent2").await.unwrap(), "agent1");
+    assert_eq!(releases(&sim, "agent2"), 1);
+    assert_eq!(grants(&sim, "agent1"), 2);
+    assert_eq!(
+        sim.get_virtual_machine("agent1").unwrap().cursor_position(),
+        (1915, 600)
+    );
+
+    // Second press: agent1 is released and focus is back on the host
+    assert_eq!(sim.press_kill_switch("agent1").await.unwrap(), "host");
+    assert_eq!(releases(&sim, "agent1"), 2);
+    assert!(sim.press_kill_switch("host").await.is_ok());
+}
+
+fn host_with_right_neighbor() -> SimulationMode {
+    let mut sim = SimulationMode::new();
+    sim.add_virtual_machine("host".to_string(), 1920, 1080);
+    sim.add_virtual_machine("agent1".to_string(), 1920, 1080);
+    sim.set_edges(
+        "host",
+        HashMap::from([("right".to_string(), "agent1".to_string())]),
+    );
+    sim
+}
+
+#[tokio::test]
+async fn test_scenario_focus_release_returns_focus() {
+    let grant = || SimStep::SendEvent {
+        target: "agent1".to_string(),
+        event: Event::FocusGrant {
+            target: "agent1".to_string(),
+            x: 10,
+            y: 500,
+            host_width: 1920,
+            host_height: 1080,
+        },
+    };
+    let release = || SimStep::SendEvent {
+        target: "agent1".to_string(),
+        event: Event::FocusRelease,
+    };
+
+    // The hub takes focus back
+    let mut sim = host_with_right_neighbor();
+    sim.set_hub("host");
+    let report = sim
+        .simulate_scenario(vec![
+            grant(),
+            SimStep::AssertFocusOn {
+                machine: "agent1".to_string(),
+            },
+            release(),
+            SimStep::AssertFocusOn {
+                machine: "host".to_string(),
+            },
+        ])
+        .await
+        .unwrap();
+    assert!(
+        report.passed(),
+        "{:?}",
+        report.failures().collect::<Vec<_>>()
+    );
+    assert_eq!(report.final_focus.as_deref(), Some("host"));
+
+    // Without a hub no machine holds it
+    let mut sim = host_with_right_neighbor();
+    let report = sim
+        .simulate_scenario(vec![grant(), release()])
+        .await
+        .unwrap();
+    assert_eq!(report.final_focus, None);
+}
+
+#[tokio::test]
+async fn test_drag_defers_crossing_until_release() {
+    let mut sim = host_with_right_neighbor();
+
+    let report = sim
+        .simulate_scenario(vec![
+            SimStep::SendEvent {
+                target: "host".to_string(),
+                event: Event::MouseButtonPress {
+                    button: MouseButton::Left,
+                },
+            },
+            SimStep::SendEvent {
+                target: "host".to_string(),
+                event: Event::MouseMove { x: 1915, y: 500 },
+            },
+            SimStep::AssertFocusOn {
+                machine: "host".to_string(),
+            },
+            SimStep::SendEvent {
+                target: "host".to_string(),
+                event: Event::MouseButtonRelease {
+                    button: MouseButton::Left,
+                },
+            },
+            SimStep::AssertFocusOn {
+                machine: "agent1".to_string(),
+            },
+            SimStep::AssertCursorAt {
+                machine: "agent1".to_string(),
+                x: 10,
+                y: 500,
+            },
+        ])
+        .await
+        .unwrap();
+
+    assert!(
+        report.passed(),
+        "{:?}",
+        report.failures().collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_drag_release_away_from_edge_stays_local() {
+    let mut sim = host_with_right_neighbor();
+    sim.send_event_to(
+        "host",
+        Event::MouseButtonPress {
+            button: MouseButton::Left,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(sim.move_cursor("host", 1915, 500).await.unwrap(), None);
+    assert_eq!(sim.move_cursor("host", 900, 500).await.unwrap(), None);
+    assert_eq!(
+        sim.release_button("host", MouseButton::Left).await.unwrap(),
+        None
+    );
+    assert!(sim
+        .get_virtual_machine("agent1")
+        .unwrap()
+        .recorded_events()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_transfer_while_dragging_crosses_immediately() {
+    let mut sim = host_with_right_neighbor();
+    sim.set_transfer_while_dragging(true);
+    sim.send_event_to(
+        "host",
+        Event::MouseButtonPress {
+            button: MouseButton::Left,
+        },
+    )
+    .await
+    .unwrap();
+    let target = sim.move_cursor("host", 1915, 500).await.unwrap();
+    assert_eq!(target.as_deref(), Some("agent1"));
+}
+
+#[tokio::test]
+async fn test_simulate_scenario_reports_expected_and_actual() {
+    let mut sim = SimulationMode::new();
+    sim.add_virtual_machine("host".to_string(), 1920, 1080);
+    sim.add_virtual_machine("agent1".to_string(), 1920, 1080);
+    sim.set_edges(
+        "host",
+        HashMap::from([("right".to_string(), "agent1".to_string())]),
+    );
+    sim.set_edges(
+        "agent1",
+        HashMap::from([("left".to_string(), "host".to_string())]),
+    );
+
+    let report = sim
+        .simulate_scenario(vec![
+            SimStep::SendEvent {
+                target: "host".to_string(),
+                event: Event::MouseMove { x: 960, y: 500 },
+            },
+            SimStep::AssertFocusOn {
+                machine: "host".to_string(),
+            },
+            SimStep::SendEvent {
+                target: "host".to_string(),
+                event: Event::MouseMove { x: 1915, y: 500 },
+            },
+            SimStep::Wait { millis: 5 },
+            SimStep::AssertFocusOn {
+                machine: "agent1".to_string(),
+            },
+            SimStep::AssertCursorAt {
+                machine: "agent1".to_string(),
+                x: 10,
+                y: 500,
+            },
+            // Deliberately wrong
+            SimStep::AssertCursorAt {
+                machine: "agent1".to_string(),
+                x: 0,
+                y: 0,
+            },
+            SimStep::SendEvent {
+                target: "agent1".to_string(),
+                event: Event::MouseMove { x: 3, y: 200 },
+            },
+            SimStep::AssertFocusOn {
+                machine: "agent1".to_string(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    assert!(!report.passed());
+    assert_eq!(report.assertions.len(), 5);
+    assert_eq!(report.final_focus.as_deref(), Some("host"));
+
+    let failures: Vec<_> = report.failures().collect();
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[0].step, 6);
+    assert_eq!(failures[0].expected, "agent1 at (0, 0)");
+    assert_eq!(failures[0].actual, "agent1 at (10, 500)");
+    assert_eq!(failures[1].expected, "agent1");
+    assert_eq!(failures[1].actual, "host");
+
+    let missing = sim
+        .simulate_scenario(vec![SimStep::SendEvent {
+            target: "ghost".to_string(),
+            event: Event::Heartbeat { sent_at_ms: 0 },
+        }])
+        .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_scenario_builder_crosses_every_edge() {
+    let mut sim = SimulationMode::new();
+    sim.add_virtual_machine("host".to_string(), 1920, 1080);
+    for (edge, name) in [
+        ("left", "west"),
+        ("right", "east"),
+        ("top", "north"),
+        ("bottom", "south"),
+    ] {
+        sim.add_virtual_machine(name.to_string(), 1280, 720);
+        sim.set_edges(
+            name,
+            HashMap::from([(edge.to_string(), "host".to_string())]),
+        );
+    }
+    sim.set_edges(
+        "host",
+        HashMap::from([
+            ("left".to_string(), "west".to_string()),
+            ("right".to_string(), "east".to_string()),
+            ("top".to_string(), "north".to_string()),
+            ("bottom".to_string(), "south".to_string()),
+        ]),
+    );
+
+    // Each neighbor has the edge leading back on the same side as the one leading to it
+    let scenario = ScenarioBuilder::for_simulation(&sim)
+        .cross_edge("host", "west", Edge::Left)
+        .cross_edge("west", "host", Edge::Left)
+        .cross_edge("host", "east", Edge::Right)
+        .cross_edge("east", "host", Edge::Right)
+        .cross_edge("host", "north", Edge::Top)
+        .cross_edge("north", "host", Edge::Top)
+        .cross_edge("host", "south", Edge::Bottom)
+        .key_press("south", Key::Return)
+        .key_release("south", Key::Return)
+        .wait_ms(1)
+        .assert_cursor("south", 960, 10)
+        .build();
+    let report = sim.simulate_scenario(scenario).await.unwrap();
+    assert!(
+        report.passed(),
+        "{:?}",
+        report.failures().collect::<Vec<_>>()
+    );
+    assert_eq!(report.final_focus.as_deref(), Some("south"));
+
+    // Unknown screens are assumed to be 1920x1080
+    let steps = ScenarioBuilder::new()
+        .cross_edge("ghost", "host", Edge::Bottom)
+        .build();
+    assert_eq!(steps.len(), 4);
+    assert_eq!(
+        steps[2],
+        SimStep::SendEvent {
+            target: "ghost".to_string(),
+            event: Event::MouseMove { x: 960, y: 1079 },
+        }
+    );
+    let steps = ScenarioBuilder::new()
+        .screen("ghost", 800, 600)
+        .cross_edge("ghost", "host", Edge::Bottom)
+        .build();
+    assert_eq!(
+        steps[2],
+        SimStep::SendEvent {
+            target: "ghost".to_string(),
+            event: Event::MouseMove { x: 400, y: 599 },
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_recorded_stream_replays_to_identical_state() {
+    let stream = vec![
+        Event::MouseMove { x: 100, y: 200 },
+        Event::MouseButtonPress {
+            button: MouseButton::Left,
+        },
+        Event::MouseMoveRelative { dx: 40, dy: -20 },
+        Event::MouseButtonRelease {
+            button: MouseButton::Left,
+        },
+        Event::KeyPress { key: Key::KeyA },
+        Event::KeyRelease { key: Key::KeyA },
+        Event::MouseMoveRelative { dx: 5000, dy: 0 },
+    ];
+
+    // Live: events go straight to the machine while being recorded
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("events.bin");
+    let mut live = VirtualMachine::new("agent1".to_string(), 1920, 1080);
+    let mut recorder = EventRecorder::create(&path).unwrap();
+    for event in &stream {
+        recorder.record(event).unwrap();
+        live.inject_event(event.clone()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    drop(recorder);
+
+    // Replayed at 10x through the same channel the loops read from
+    let mut replayer = EventReplayer::open(&path).unwrap();
+    replayer.set_speed(10.0);
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    assert_eq!(replayer.replay(&tx).await, stream.len());
+    drop(tx);
+
+    let mut replayed = VirtualMachine::new("agent1".to_string(), 1920, 1080);
+    while let Some(event) = rx.recv().await {
+        replayed.inject_event(event).await.unwrap();
+    }
+    assert_eq!(replayed.recorded_events(), live.recorded_events());
+    assert_eq!(replayed.cursor_position(), live.cursor_position());
+    assert_eq!(replayed.cursor_position(), (1920, 180));
+
+    // The simulation plays the same recording with edge crossing applied
+    let mut sim = SimulationMode::new();
+    sim.add_virtual_machine("agent1".to_string(), 1920, 1080);
+    let report = sim.replay_recording(&replayer, "agent1").await.unwrap();
+    assert_eq!(report.final_focus.as_deref(), Some("agent1"));
+    let vm = sim.get_virtual_machine("agent1").unwrap();
+    assert_eq!(vm.recorded_events(), live.recorded_events());
+}
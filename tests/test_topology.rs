@@ -1,4 +1,7 @@
-use multishiva::core::topology::{Edge, Position, Topology};
+use multishiva::core::config::Config;
+use multishiva::core::topology::{
+    Corner, CornerPreference, Edge, EdgeLink, EdgeTrigger, Position, Topology,
+};
 
 #[test]
 fn test_topology_creation() {
@@ -118,3 +121,323 @@ fn test_topology_calculate_relative_position() {
     assert_eq!(rel_x, 0); // Should wrap to left edge
     assert_eq!(rel_y, 500); // Y should stay the same
 }
+
+#[test]
+fn test_topology_three_machine_row() {
+    let mut topology = Topology::new();
+    topology.add_machine("left".to_string(), Position { x: 0, y: 0 });
+    topology.add_machine("middle".to_string(), Position { x: 1, y: 0 });
+    topology.add_machine("right".to_string(), Position { x: 2, y: 0 });
+    topology.add_edge("left".to_string(), Edge::Right, "middle".to_string());
+    topology.add_edge("middle".to_string(), Edge::Right, "right".to_string());
+    assert!(topology.validate().is_ok());
+
+    // Neighbors resolve in both directions for every machine in the row
+    assert_eq!(
+        topology.get_neighbor("middle", &Edge::Left),
+        Some(&"left".to_string())
+    );
+    assert_eq!(
+        topology.get_neighbor("right", &Edge::Left),
+        Some(&"middle".to_string())
+    );
+    assert_eq!(topology.get_neighbor("right", &Edge::Right), None);
+
+    // The middle machine can exit on either side
+    assert_eq!(
+        topology.detect_edge("middle", 0, 500, 1920, 3),
+        Some(Edge::Left)
+    );
+    assert_eq!(
+        topology.detect_edge("middle", 1919, 500, 1920, 3),
+        Some(Edge::Right)
+    );
+}
+
+#[test]
+fn test_topology_two_by_two_grid() {
+    let mut topology = Topology::new();
+    topology.add_machine("host".to_string(), Position { x: 0, y: 0 });
+    topology.add_machine("top_right".to_string(), Position { x: 1, y: 0 });
+    topology.add_machine("bottom_left".to_string(), Position { x: 0, y: 1 });
+    topology.add_machine("bottom_right".to_string(), Position { x: 1, y: 1 });
+    assert!(topology.validate().is_ok());
+
+    assert_eq!(
+        topology.get_neighbor("host", &Edge::Right),
+        Some(&"top_right".to_string())
+    );
+    assert_eq!(
+        topology.get_neighbor("host", &Edge::Bottom),
+        Some(&"bottom_left".to_string())
+    );
+    assert_eq!(
+        topology.get_neighbor("top_right", &Edge::Bottom),
+        Some(&"bottom_right".to_string())
+    );
+    assert_eq!(
+        topology.get_neighbor("bottom_right", &Edge::Left),
+        Some(&"bottom_left".to_string())
+    );
+    assert_eq!(topology.get_neighbor("host", &Edge::Left), None);
+
+    // Bottom-right corner of the host: horizontal neighbor wins by default
+    let edge = topology.detect_edge_on_screen("host", 1919, 1079, 1920, 1080, 3);
+    assert_eq!(edge, Some(Edge::Right));
+
+    topology.set_corner_preference(CornerPreference::Vertical);
+    let edge = topology.detect_edge_on_screen("host", 1919, 1079, 1920, 1080, 3);
+    assert_eq!(edge, Some(Edge::Bottom));
+
+    // Top-right corner only has a horizontal neighbor, so preference doesn't matter
+    let edge = topology.detect_edge_on_screen("host", 1919, 0, 1920, 1080, 3);
+    assert_eq!(edge, Some(Edge::Right));
+}
+
+#[test]
+fn test_topology_from_config_with_layout() {
+    let mut config = Config {
+        self_name: "host".to_string(),
+        ..Default::default()
+    };
+    config.edges.insert("right".to_string(), "top_right".into());
+    config
+        .edges
+        .insert("bottom".to_string(), "bottom_left".into());
+    config
+        .layout
+        .insert("bottom_right".to_string(), Position { x: 1, y: 1 });
+
+    let topology = Topology::from_config(&config);
+    assert_eq!(topology.machine_count(), 4);
+    assert!(topology.validate().is_ok());
+
+    assert_eq!(
+        topology.get_neighbor("top_right", &Edge::Left),
+        Some(&"host".to_string())
+    );
+    assert_eq!(
+        topology.get_neighbor("bottom_left", &Edge::Right),
+        Some(&"bottom_right".to_string())
+    );
+}
+
+#[test]
+fn test_topology_from_config_skips_unknown_direction() {
+    let mut config = Config {
+        self_name: "host".to_string(),
+        ..Default::default()
+    };
+    config.edges.insert("diagonal".to_string(), "agent1".into());
+
+    let topology = Topology::from_config(&config);
+    assert_eq!(topology.machine_count(), 1);
+}
+
+#[test]
+fn test_topology_entry_point_applies_edge_offset_and_scale() {
+    let mut config = Config {
+        self_name: "host".to_string(),
+        ..Default::default()
+    };
+    // The agent's screen sits lower: y=900 on the host is y=600 there
+    config.edges.insert(
+        "right".to_string(),
+        EdgeLink {
+            offset_px: -300,
+            ..EdgeLink::to("agent1")
+        },
+    );
+    config.edges.insert(
+        "bottom".to_string(),
+        EdgeLink {
+            scale: 0.5,
+            offset_px: 100,
+            ..EdgeLink::to("agent2")
+        },
+    );
+    let topology = Topology::from_config(&config);
+    let screen = (1920, 1080);
+
+    assert_eq!(
+        topology.entry_point("host", Edge::Right, (1915, 900), screen, 10),
+        (10, 600)
+    );
+    // Mapped above the top of the agent's screen: clamped to it
+    assert_eq!(
+        topology.entry_point("host", Edge::Right, (1915, 100), screen, 10),
+        (10, 0)
+    );
+    // Along a horizontal edge x is mapped: 800 * 0.5 + 100
+    assert_eq!(
+        topology.entry_point("host", Edge::Bottom, (800, 1075), screen, 10),
+        (500, 10)
+    );
+
+    // The way back undoes the mapping
+    assert_eq!(
+        topology.entry_point("agent1", Edge::Left, (5, 600), screen, 10),
+        (1909, 900)
+    );
+    assert_eq!(
+        topology.entry_point("agent2", Edge::Top, (500, 3), screen, 10),
+        (800, 1069)
+    );
+}
+
+#[test]
+fn test_topology_wraps_edges_without_neighbor() {
+    let mut config = Config {
+        self_name: "host".to_string(),
+        ..Default::default()
+    };
+    config
+        .edges
+        .insert("left".to_string(), EdgeLink::wrapping());
+    config.edges.insert("right".to_string(), "agent1".into());
+    let topology = Topology::from_config(&config);
+
+    assert_eq!(topology.machine_count(), 2);
+    assert!(topology.wraps("host", Edge::Left));
+    assert!(!topology.wraps("host", Edge::Right));
+    assert!(!topology.wraps("host", Edge::Top));
+    // Leaving through the left edge comes back in on the right
+    assert_eq!(
+        topology.entry_point("host", Edge::Left, (3, 400), (1920, 1080), 10),
+        (1909, 400)
+    );
+}
+
+#[test]
+fn test_topology_validate_rejects_contradiction() {
+    let mut topology = Topology::new();
+    topology.add_edge("a".to_string(), Edge::Right, "b".to_string());
+    topology.add_edge("b".to_string(), Edge::Left, "c".to_string());
+
+    assert!(topology.validate().is_err());
+}
+
+#[test]
+fn test_topology_validate_rejects_cycle() {
+    let mut topology = Topology::new();
+    topology.add_edge("a".to_string(), Edge::Right, "b".to_string());
+    topology.add_edge("b".to_string(), Edge::Right, "c".to_string());
+    topology.add_edge("c".to_string(), Edge::Right, "a".to_string());
+
+    assert!(topology.validate().is_err());
+}
+
+#[test]
+fn test_topology_validate_rejects_non_adjacent_positions() {
+    let mut topology = Topology::new();
+    topology.add_machine("a".to_string(), Position { x: 0, y: 0 });
+    topology.add_machine("b".to_string(), Position { x: 2, y: 0 });
+    topology.add_edge("a".to_string(), Edge::Right, "b".to_string());
+
+    assert!(topology.validate().is_err());
+}
+
+fn machine_row(names: &[&str]) -> Topology {
+    let mut topology = Topology::new();
+    for (x, name) in names.iter().enumerate() {
+        topology.add_machine(name.to_string(), Position { x: x as i32, y: 0 });
+    }
+    for pair in names.windows(2) {
+        topology.add_edge(pair[0].to_string(), Edge::Right, pair[1].to_string());
+    }
+    topology
+}
+
+#[test]
+fn test_topology_shortest_path_through_row() {
+    let topology = machine_row(&["host", "agent1", "agent2"]);
+
+    assert_eq!(
+        topology.shortest_path("host", "agent2"),
+        Some(vec![
+            "host".to_string(),
+            "agent1".to_string(),
+            "agent2".to_string()
+        ])
+    );
+    // Edges are bidirectional, so the path back is the reverse
+    assert_eq!(
+        topology.shortest_path("agent2", "host"),
+        Some(vec![
+            "agent2".to_string(),
+            "agent1".to_string(),
+            "host".to_string()
+        ])
+    );
+}
+
+#[test]
+fn test_topology_shortest_path_same_machine() {
+    let topology = machine_row(&["host", "agent1"]);
+    assert_eq!(
+        topology.shortest_path("host", "host"),
+        Some(vec!["host".to_string()])
+    );
+}
+
+#[test]
+fn test_topology_shortest_path_unreachable() {
+    let mut topology = machine_row(&["host", "agent1"]);
+    topology.add_machine("island".to_string(), Position { x: 5, y: 5 });
+
+    assert_eq!(topology.shortest_path("host", "island"), None);
+    assert_eq!(topology.shortest_path("host", "unknown"), None);
+}
+
+#[test]
+fn test_topology_shortest_path_prefers_fewest_hops() {
+    // host → a → b → target, plus a shortcut host ↓ target
+    let mut topology = machine_row(&["host", "a", "b", "target"]);
+    topology.add_edge("host".to_string(), Edge::Bottom, "target".to_string());
+
+    assert_eq!(
+        topology.shortest_path("host", "target"),
+        Some(vec!["host".to_string(), "target".to_string()])
+    );
+}
+
+#[test]
+fn test_topology_hotspot_trigger_from_config() {
+    let yaml = r#"
+self_name: host
+mode: host
+port: 53421
+tls:
+  psk: test-psk
+edges:
+  right: agent1
+behavior:
+  edge_trigger:
+    type: hotspot
+    corner: top_right
+    size_px: 50
+"#;
+    let config: Config = serde_yaml::from_str(yaml).unwrap();
+    let topology = Topology::from_config(&config);
+    assert_eq!(
+        topology.edge_trigger(),
+        EdgeTrigger::Hotspot {
+            corner: Corner::TopRight,
+            size_px: 50
+        }
+    );
+
+    // The rest of the right edge no longer transfers focus
+    assert_eq!(
+        topology.detect_edge_on_screen("host", 1919, 500, 1920, 1080, 3),
+        None
+    );
+    assert_eq!(
+        topology.detect_edge_on_screen("host", 1880, 30, 1920, 1080, 3),
+        Some(Edge::Right)
+    );
+    assert_eq!(
+        topology.detect_edge_on_screen("host", 1860, 30, 1920, 1080, 3),
+        None
+    );
+}